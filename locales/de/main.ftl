@@ -0,0 +1,842 @@
+## Shared admin chrome
+
+admin-brand = 📤 NeedADrop Admin
+admin-welcome = Willkommen, { $username }!
+nav-dashboard = Übersicht
+nav-logout = Abmelden
+action-cancel = Abbrechen
+action-delete = Löschen
+action-view = Ansehen
+action-edit = Bearbeiten
+action-preview = Vorschau
+action-download = Herunterladen
+action-retry = Wiederholen
+action-discard = Verwerfen
+pagination-previous = ← Zurück
+pagination-next = Weiter →
+pagination-position = Seite { $page } von { $pages }
+
+## Theme toggle
+
+theme-switch-dark = Zum dunklen Design wechseln
+theme-switch-light = Zum hellen Design wechseln
+
+## Home page
+
+index-title = NeedADrop - Sicherer Datei-Upload
+index-subtitle = Sicherer Datei-Upload-Dienst
+index-intro = Willkommen bei NeedADrop, einem sicheren Datei-Upload-Dienst, mit dem Administratoren zeitlich begrenzte Upload-Links für Gäste erstellen können.
+index-admin-login = 🔐 Admin-Anmeldung
+index-feature-links-title = 🔒 Sichere Links
+index-feature-links = Administratoren erstellen eindeutige, sichere Upload-Links mit tokenbasiertem Zugriff
+index-feature-expiry-title = ⏰ Zeitlich begrenzt
+index-feature-expiry = Links können nach einem festgelegten Zeitraum ablaufen, für mehr Sicherheit
+index-feature-quota-title = 📏 Kontingente
+index-feature-quota = Pro Link ein Gesamtkontingent festlegen, das mit jedem Upload sinkt
+index-feature-isolation-title = 👤 Getrennte Gastordner
+index-feature-isolation = Jeder Upload wird in einem eigenen, isolierten Ordner gespeichert
+index-feature-control-title = 🛡️ Admin-Kontrolle
+index-feature-control = Nur Administratoren können hochgeladene Dateien ansehen, herunterladen und verwalten
+index-feature-ui-title = 🎨 Moderne Oberfläche
+index-feature-ui = Übersichtliches, responsives Design mit Drag & Drop-Uploads
+
+## Error pages
+
+error-title = Fehler { $status } - NeedADrop
+error-not-found-heading = Seite nicht gefunden
+error-not-found-text = Die aufgerufene Adresse existiert nicht. Bitte prüfen Sie den erhaltenen Link.
+error-forbidden-heading = Zugriff verweigert
+error-forbidden-text = Sie dürfen diese Seite nicht öffnen.
+error-gone-heading = Dieser Link ist nicht mehr verfügbar
+error-gone-text = Der Upload-Link ist abgelaufen oder wurde deaktiviert. Bitte fragen Sie die Person, die ihn Ihnen geschickt hat, nach einem neuen.
+error-server-heading = Etwas ist schiefgelaufen
+error-server-text = Der Server konnte Ihre Anfrage nicht abschließen. Bitte versuchen Sie es später erneut.
+error-generic-heading = Anfrage konnte nicht abgeschlossen werden
+error-generic-text = Bitte prüfen Sie Ihre Anfrage und versuchen Sie es erneut.
+error-home = Zur Startseite
+error-request-id = Anfrage-ID:
+
+## Guest upload page
+
+upload-title = Datei hochladen - { $name }
+upload-info-heading = 📊 Upload-Informationen
+upload-remaining-quota = Verbleibendes Kontingent
+upload-total-quota = Gesamtkontingent
+upload-per-file-limit = Pro Datei
+upload-files-left = Verbleibende Dateien
+upload-guest-quota = Ihr Limit
+upload-daily-quota = Pro 24 Stunden
+upload-file-types = Erlaubte Dateitypen
+upload-link-expires = Link läuft ab
+upload-your-name = Ihr Name (optional)
+upload-your-name-placeholder = Damit der Empfänger weiß, von wem die Dateien stammen
+upload-message = Nachricht (optional)
+upload-message-placeholder = Was der Empfänger wissen sollte, z. B. „Rechnungen für März“
+upload-message-too-long = Ihre Nachricht ist zu lang (höchstens { $limit } Zeichen).
+upload-select-file = 📁 Datei zum Hochladen auswählen:
+upload-drop-here = Datei hierher ziehen oder klicken zum Auswählen
+upload-max-size-hint = Maximale Größe richtet sich nach dem verbleibenden Kontingent und dem Limit pro Datei
+upload-remaining-hint = 💡 Verbleibendes Kontingent:
+upload-collision-keep-both = 📑 Wenn Sie eine Datei mit einem bereits verwendeten Namen hochladen, bleiben beide als eigene Versionen erhalten.
+upload-collision-overwrite-latest = 🔁 Wenn Sie eine Datei mit einem bereits verwendeten Namen hochladen, ersetzt sie die frühere Datei.
+upload-collision-reject-duplicate = 🚫 Jeder Dateiname kann nur einmal hochgeladen werden; benennen Sie die Datei um, um eine neue Kopie zu senden.
+upload-button = 🚀 Datei hochladen
+upload-js-select-file = ❌ Bitte wählen Sie eine Datei zum Hochladen aus
+upload-js-files-selected = { $count } Dateien ausgewählt
+upload-js-too-large = ❌ Die Dateigröße ({ $size }) überschreitet das verbleibende Kontingent ({ $remaining })
+upload-js-file-too-large = ❌ { $name } ({ $size }) ist größer als das Limit von { $limit } pro Datei
+upload-js-uploading = ⏳ Wird hochgeladen...
+upload-js-success = ✅ Datei erfolgreich hochgeladen!
+upload-js-failed = Hochladen fehlgeschlagen
+upload-js-failed-retry = ❌ Hochladen fehlgeschlagen. Bitte versuchen Sie es erneut.
+upload-js-waiting = Warte auf den Server...
+upload-js-progress = { $received } von { $total } beim Server angekommen
+upload-js-queued = 📶 Sie sind offline. { $name } wurde vorgemerkt und wird automatisch hochgeladen, sobald Sie wieder online sind.
+upload-js-queued-done = ✅ { $name } wurde aus der Warteschlange hochgeladen.
+upload-js-queued-failed = ❌ { $name } konnte nicht hochgeladen werden: { $error }
+upload-expired-link-name = Abgelaufener Link
+upload-success = Datei erfolgreich hochgeladen!
+upload-success-replaced = Datei erfolgreich hochgeladen; sie ersetzt die frühere Datei mit demselben Namen!
+upload-success-same-content = Datei hochgeladen, aber sie scheint doppelt zu sein: Dieselbe Datei wurde bereits als „{ $name }“ hochgeladen.
+upload-results-stored = { $stored } von { $total } Dateien erfolgreich hochgeladen.
+upload-results-failed = { $failed } von { $total } Dateien konnten nicht hochgeladen werden:
+upload-error-expired = Der Upload-Link ist abgelaufen oder deaktiviert
+upload-error-not-found = Upload-Link nicht gefunden
+upload-error-busy = Der Server verarbeitet gerade andere Uploads. Bitte versuchen Sie es gleich noch einmal.
+upload-error-save-file = Die hochgeladene Datei konnte nicht gespeichert werden
+upload-error-read-file = Die hochgeladene Datei konnte nicht gelesen werden
+upload-error-save-info = Die Upload-Informationen konnten nicht gespeichert werden
+upload-error-no-file = Es wurde keine Datei hochgeladen
+upload-error-files-limit = Dieser Link nimmt keine weiteren Dateien mehr an.
+upload-error-too-large = Die Dateigröße ({ $size } MB) überschreitet das verbleibende Kontingent ({ $remaining } MB). Gesamtkontingent: { $total } MB
+upload-error-too-large-unknown = Die Datei überschreitet das verbleibende Kontingent ({ $remaining } MB). Gesamtkontingent: { $total } MB
+upload-error-file-limit = Die Dateigröße ({ $size } MB) überschreitet das Limit von { $limit } MB pro Datei
+upload-error-file-limit-unknown = Die Datei überschreitet das Limit von { $limit } MB pro Datei
+upload-error-guest-quota = Sie haben Ihr eigenes Upload-Limit für diesen Link erreicht: { $remaining } von { $quota } übrig. Die Datei wurde nicht gespeichert.
+upload-error-daily-quota = Dieser Link nimmt { $quota } pro 24 Stunden an, derzeit sind nur noch { $remaining } frei. Bitte versuchen Sie es später erneut; die Datei wurde nicht gespeichert.
+upload-error-file-type = Dieser Link nimmt Dateien wie „{ $name }“ nicht an. Erlaubte Dateitypen: { $types }. Die Datei wurde nicht gespeichert.
+upload-error-file-content = Der Inhalt von „{ $name }“ passt nicht zu seinem Dateityp. Die Datei wurde nicht gespeichert.
+upload-error-duplicate = Eine Datei namens { $name } wurde bereits über diesen Link hochgeladen. Bitte benennen Sie die Datei um und versuchen Sie es erneut.
+upload-error-same-content = Diese Datei wurde bereits als „{ $name }“ hochgeladen und daher nicht erneut gespeichert.
+upload-error-rules = Diese Datei wird über diesen Link nicht angenommen.
+upload-error-infected = „{ $name }“ wurde abgelehnt: Der Virenscanner hat Schadsoftware darin gefunden.
+upload-error-scan-failed = Die Datei konnte nicht auf Viren geprüft werden und wurde nicht gespeichert. Bitte versuchen Sie es später erneut.
+
+## Public drop
+
+captcha-title = Öffentlicher Upload - NeedADrop
+captcha-prompt = 🤖 Bitte lösen Sie diese Aufgabe, um fortzufahren:
+captcha-button = ✅ Weiter
+captcha-error = Falsche Antwort, bitte versuchen Sie es erneut
+captcha-required = Bitte öffnen Sie die öffentliche Upload-Seite erneut und beantworten Sie zuerst die Frage
+
+## Guest share page
+
+share-title = { $name } - NeedADrop
+share-intro = Diese Dateien wurden für Sie freigegeben.
+share-expires = Verfügbar bis { $time }
+share-downloads-left = { $count ->
+    [one] Noch 1 Download
+   *[other] Noch { $count } Downloads
+}
+share-empty = Hier liegen (noch) keine Dateien.
+share-error-not-found = Freigabe-Link nicht gefunden
+share-error-expired = Dieser Freigabe-Link ist abgelaufen oder seine Downloads sind aufgebraucht
+
+## Login page
+
+login-title = Admin-Anmeldung - NeedADrop
+login-heading = 🔐 NeedADrop Admin
+login-username = 👤 Benutzername:
+login-password = 🔑 Passwort:
+login-button = 🚀 Anmelden
+login-js-logging-in = ⏳ Anmeldung läuft...
+login-back-home = ⬅️ Zurück zur Startseite
+login-error-invalid = Ungültiger Benutzername oder ungültiges Passwort
+login-code-intro = Geben Sie den 6-stelligen Code aus Ihrer Authenticator-App ein.
+login-code = 🔢 Code:
+login-code-button = ✅ Bestätigen
+login-error-code = Falscher Code, bitte versuchen Sie es erneut
+login-error-code-expired = Ihre Anmeldung ist abgelaufen, bitte melden Sie sich erneut an
+login-error-code-attempts = Zu viele falsche Codes, bitte melden Sie sich erneut an
+
+## Dashboard
+
+dashboard-title = Admin-Übersicht - NeedADrop
+dashboard-heading = Admin-Übersicht
+dashboard-intro = Verwalten Sie Ihren sicheren Datei-Upload-Dienst von hier aus.
+dashboard-failed-tasks = ⚠️ { $count ->
+    [one] 1 Hintergrundaufgabe ist endgültig fehlgeschlagen (z. B. Replikation oder WebDAV-Übertragung).
+   *[other] { $count } Hintergrundaufgaben sind endgültig fehlgeschlagen (z. B. Replikation oder WebDAV-Übertragung).
+}
+dashboard-review-failed-tasks = Fehlgeschlagene Aufgaben prüfen
+dashboard-pii-flagged = ⚠️ { $count ->
+    [one] 1 Datei enthält wahrscheinlich personenbezogene Daten und wartet auf Prüfung.
+   *[other] { $count } Dateien enthalten wahrscheinlich personenbezogene Daten und warten auf Prüfung.
+}
+dashboard-review-pii = Markierte Dateien prüfen
+dashboard-low-quota = ⚠️ { $count ->
+    [one] 1 Link hat weniger als { $percent } % seines Kontingents übrig:
+   *[other] { $count } Links haben weniger als { $percent } % ihres Kontingents übrig:
+}
+dashboard-low-quota-item = { $remaining } von { $total } übrig
+dashboard-manage-links = Links verwalten
+dashboard-links-heading = 🔗 Upload-Links verwalten
+dashboard-links-text = Sichere Upload-Links für Gäste erstellen, ansehen und verwalten. Größenlimits und Ablaufzeiten festlegen.
+dashboard-view-links = Links anzeigen
+dashboard-create-link = Neuen Link erstellen
+dashboard-uploads-heading = 📁 Uploads ansehen
+dashboard-uploads-text = Alle hochgeladenen Dateien durchsuchen, herunterladen oder nicht benötigte löschen.
+dashboard-view-uploads = Uploads anzeigen
+dashboard-stats-heading = 📊 Kurzstatistik
+dashboard-stats-text = Überblick über Nutzung und Aktivität Ihres Upload-Dienstes.
+dashboard-stats-active-links = Aktive Links:
+dashboard-stats-uploads = Uploads insgesamt:
+dashboard-stats-storage = Belegter Speicher:
+dashboard-volumes-heading = 💽 Speicher-Volumes
+dashboard-volumes-text = Auf jedem Datenträger gespeicherte Dateien und der verbleibende Platz.
+dashboard-volume-stored = { $count ->
+    [one] 1 Datei, { $size }
+   *[other] { $count } Dateien, { $size }
+}
+dashboard-volume-free = { $free } von { $total } frei
+dashboard-volume-unavailable = Freier Speicher unbekannt (Volume nicht eingehängt?)
+dashboard-projects-heading = 📁 Projekte
+dashboard-projects-text = Links nach Kunde oder Auftrag gruppieren und ihr Kontingent verfolgen.
+dashboard-manage-projects = Projekte verwalten
+dashboard-shares-heading = 📦 Freigabe-Links
+dashboard-shares-text = Dateien über einen Download-Link an Gäste weitergeben.
+dashboard-manage-shares = Freigabe-Links verwalten
+
+dashboard-api-keys-heading = 🔑 API-Schlüssel
+dashboard-api-keys-text = Skripten und CI-Jobs Zugriff auf die REST-API geben.
+dashboard-manage-api-keys = API-Schlüssel verwalten
+dashboard-admins-heading = 👤 Admin-Konten
+dashboard-admins-text = Admins hinzufügen und festlegen, was jeder von ihnen darf.
+dashboard-manage-admins = Admins verwalten
+dashboard-webhooks-heading = 🪝 Webhooks
+dashboard-webhooks-text = Signierte Ereignisse an eigene Endpunkte senden.
+dashboard-manage-webhooks = Webhooks verwalten
+dashboard-tasks-heading = 🧰 Hintergrundaufgaben
+dashboard-tasks-text = Ausstehende Nacharbeiten nach Uploads überwachen und endgültig fehlgeschlagene Aufgaben wiederholen.
+dashboard-view-tasks = Aufgaben anzeigen
+dashboard-audit-heading = 🧾 Audit-Protokoll
+dashboard-audit-text = Sehen Sie Änderungen an rechtlichen Sperren und Betroffenenanfragen ein.
+dashboard-view-audit = Audit-Protokoll anzeigen
+dashboard-gdpr-heading = 🪪 Datenanfragen
+dashboard-gdpr-text = Alle zu einer Person gespeicherten Daten exportieren oder löschen (DSGVO).
+dashboard-open-gdpr = Datenanfragen öffnen
+dashboard-email-templates-heading = ✉️ E-Mail-Vorlagen
+dashboard-email-templates-text = Betreff und Text der Benachrichtigungs-E-Mails anpassen.
+dashboard-edit-email-templates = E-Mails bearbeiten
+dashboard-account-heading = ⚙️ Kontoeinstellungen
+dashboard-account-text = Einstellungen und Sicherheit Ihres Admin-Kontos verwalten.
+dashboard-change-password = Passwort ändern
+dashboard-preferences = Anzeigeeinstellungen
+dashboard-two-factor = Zwei-Faktor-Authentifizierung
+dashboard-system-heading = ℹ️ Systeminfo
+dashboard-system-text = Informationen zu Ihrer NeedADrop-Installation.
+dashboard-version = Version:
+dashboard-status = Status:
+dashboard-status-running = Läuft
+
+## Upload links
+
+links-title = Upload-Links - NeedADrop Admin
+links-heading = Upload-Links
+links-create = Neuen Link erstellen
+links-empty = Noch keine Upload-Links erstellt.
+links-create-first = Ersten Link erstellen
+links-col-name = Name
+links-col-url = Upload-URL
+links-col-quota = Kontingent
+links-col-expires = Läuft ab
+links-col-status = Status
+links-col-created = Erstellt
+links-col-actions = Aktionen
+links-bytes = Bytes
+links-quota-remaining = verbleibend
+links-rate-limit = begrenzt auf { $kbps } KB/s
+links-uploads-used = { $used } von { $max } Dateien erhalten
+links-daily-quota = { $quota } pro 24 h
+links-per-file-limit = max. { $limit } pro Datei
+links-guest-quota = { $quota } pro Gast
+links-file-types = Nur { $types }
+links-webdav-folder = wird nach WebDAV kopiert: { $folder }
+links-post-upload-command = Führt aus: { $command }
+links-notify-on-upload = Benachrichtigt den Admin bei jedem Upload
+links-guest-email = Gast: { $email }
+links-language = Sprache: { $language }
+links-collision-policy = Gleicher Dateiname: { $policy }
+links-accent-color = Akzentfarbe: { $color }
+links-custom-logo = eigenes Logo
+links-public-drop = öffentlicher Upload (/drop)
+links-never = Nie
+links-status-active = Aktiv
+links-status-inactive = Abgelaufen/Inaktiv
+links-status-paused = Pausiert
+links-confirm-delete = Möchten Sie diesen Link wirklich löschen?
+links-tab-current = Aktuelle Links
+links-tab-archived = 🗄️ Archiviert ({ $count })
+links-archive-empty = Keine archivierten Links.
+links-select-all = Alle Links auswählen
+links-status-archived = Archiviert
+links-archive = Archivieren
+links-unarchive = Wiederherstellen
+links-pause = Pausieren
+links-resume = Fortsetzen
+links-attempts = Versuche
+links-stats = Statistik
+links-copy-url = Kopieren
+links-url-copied = Kopiert
+links-select-finished = Abgeschlossene Links auswählen
+links-bulk-archive = 🗄️ Auswahl archivieren
+links-bulk-unarchive = Auswahl wiederherstellen
+links-error-has-uploads = Der Link kann nicht gelöscht werden, da noch Dateien vorhanden sind. Bitte löschen Sie zuerst die Dateien.
+links-error-legal-hold = Der Link kann nicht gelöscht werden, da er einer rechtlichen Sperre unterliegt.
+
+## Refused uploads
+
+attempts-title = Abgelehnte Uploads - NeedADrop Admin
+attempts-back = ← Upload-Links
+attempts-heading = Abgelehnte Uploads: { $name }
+attempts-text = Uploads, die dieser Link in den letzten 90 Tagen abgelehnt hat, neueste zuerst.
+attempts-empty = Es wurden keine Uploads abgelehnt.
+attempts-col-time = Zeit
+attempts-col-file = Datei
+attempts-col-reason = Grund
+attempts-col-ip = Client-Adresse
+attempts-col-request = Anfrage-ID
+attempt-reason-link-unavailable = Link abgelaufen, inaktiv oder voll
+attempt-reason-too-large = Größer als das verbleibende Kontingent
+attempt-reason-guest-quota = Eigenes Limit des Gasts erreicht
+attempt-reason-daily-quota = Limit pro 24 Stunden erreicht
+attempt-reason-file-type = Dateityp nicht erlaubt
+attempt-reason-file-content = Inhalt passt nicht zum Dateityp
+attempt-reason-duplicate = Eine Datei mit diesem Namen existiert bereits
+attempt-reason-same-content = Dieselbe Datei wurde bereits hochgeladen als
+attempt-reason-refused = Von einer Regel oder einem Plugin abgelehnt
+attempt-reason-interrupted = Upload abgebrochen
+attempt-reason-infected = Virenscanner fand
+
+## Link statistics
+
+link-stats-title = Link-Statistik - NeedADrop Admin
+link-stats-heading = Statistik: { $name }
+link-stats-uploads = Empfangene Dateien
+link-stats-total-size = Gesamtgröße
+link-stats-remaining = Verbleibendes Kontingent von { $total }
+link-stats-uploader-ips = Client-Adressen
+link-stats-refused = Abgelehnte Uploads
+link-stats-last-activity = Letzte Aktivität
+link-stats-daily-heading = Uploads der letzten 30 Tage (UTC)
+link-stats-daily-empty = In den letzten 30 Tagen sind keine Dateien eingegangen.
+link-stats-day-uploads = { $count } Dateien
+
+## Create link
+
+create-link-title = Upload-Link erstellen - NeedADrop Admin
+create-link-heading = Upload-Link erstellen
+create-link-intro = Erstellen Sie einen neuen sicheren Upload-Link, über den Gäste Dateien hochladen können.
+create-link-name = Name des Links:
+create-link-name-placeholder = z. B. Kundendokumente, Projektdateien
+create-link-name-help = Ein aussagekräftiger Name für diesen Upload-Link (wird Gästen angezeigt)
+create-link-note = Hinweis für Gäste
+create-link-note-placeholder = z. B. Bitte laden Sie den unterschriebenen Vertrag als PDF hoch
+create-link-note-help = Wird oben auf der Upload-Seite angezeigt. Optional.
+create-link-total-quota = Gesamtkontingent (MB):
+create-link-total-quota-help = Gesamtgröße aller Dateien, die der Link annimmt (in Megabyte)
+create-link-per-file-limit = Limit pro Datei in MB (optional)
+create-link-per-file-limit-placeholder = Nur das Kontingent gilt
+create-link-per-file-limit-help = Größte einzelne Datei, die ein Gast hochladen darf; das Gesamtkontingent gilt weiterhin
+create-link-max-uploads = Anzahl der Dateien (optional)
+create-link-max-uploads-placeholder = Unbegrenzt
+create-link-max-uploads-help = Wie viele Dateien der Link insgesamt annimmt, z. B. 3 für „genau 3 Dokumente einreichen“. Das Ersetzen einer Datei zählt nicht erneut.
+create-link-daily-quota = Limit pro 24 Stunden in MB (optional)
+create-link-daily-quota-placeholder = Unbegrenzt
+create-link-daily-quota-help = Für langlebige Links: wie viel innerhalb von 24 Stunden ankommen darf, damit der Link nicht auf einen Schlag aufgebraucht wird
+create-link-guest-quota = Limit pro Gast in MB (optional)
+create-link-guest-quota-placeholder = Unbegrenzt
+create-link-guest-quota-help = Für Links, die mit vielen Personen geteilt werden: wie viel ein Gast hochladen darf, damit niemand das ganze Kontingent verbraucht
+create-link-file-types = Erlaubte Dateitypen (optional)
+create-link-file-types-placeholder = Alle Typen, z. B. image/*, .pdf
+create-link-file-types-help = Dateiendungen und MIME-Typen, durch Kommas getrennt; image/* steht für alle Bilder. Dateien werden nach Name und Inhalt geprüft
+create-link-expires = Läuft ab in (Stunden):
+create-link-expires-placeholder = Leer lassen für kein Ablaufdatum
+create-link-expires-help = Anzahl Stunden, bis der Link abläuft (optional, maximal 1 Jahr)
+create-link-retention = Dateien löschen nach (Tagen):
+create-link-retention-placeholder = Dateien behalten, bis sie gelöscht werden
+create-link-retention-help = Dateien werden so viele Tage nach dem Hochladen gelöscht, außer Dateien mit rechtlicher Sperre
+create-link-rate-limit = Bandbreitenlimit (KB/s):
+create-link-rate-limit-placeholder = Leer lassen für unbegrenzt
+create-link-rate-limit-help = Maximale Upload-/Download-Geschwindigkeit für diesen Link (optional)
+create-link-project = Projekt:
+create-link-project-none = Kein Projekt
+create-link-project-help = Ordnen Sie den Link einem Projekt zu, um ihn mit anderen Links desselben Kunden zu gruppieren.
+create-link-collision = Gleicher Dateiname erneut hochgeladen
+create-link-collision-help = Was passiert, wenn ein Gast eine Datei unter einem Namen hochlädt, der auf diesem Link bereits existiert
+collision-policy-keep-both = Beide als Versionen behalten
+collision-policy-overwrite-latest = Neueste Version ersetzen
+collision-policy-reject-duplicate = Neue Datei ablehnen
+create-link-webdav = Nextcloud-/WebDAV-Ordner:
+create-link-webdav-placeholder = z. B. Kunden/Acme/Eingang
+create-link-webdav-help = Empfangene Dateien werden zusätzlich in diesen Ordner auf dem konfigurierten WebDAV-Server kopiert (optional)
+create-link-command = Befehl nach dem Hochladen (optional)
+create-link-command-placeholder = /usr/local/bin/import-scan --archive
+create-link-command-help = Läuft nach jedem Upload; die Datei wird in NEEDADROP_*-Umgebungsvariablen beschrieben. Die Ausgabe steht auf der Seite der Hintergrundaufgaben.
+create-link-notify = Benachrichtigen, wenn eine Datei eintrifft
+create-link-notify-help = Sendet für jede Datei, die dieser Link empfängt, eine E-Mail an die Admin-Adresse (ADMIN_EMAIL).
+create-link-error-command = Dieser Befehl ist nicht erlaubt. Verwenden Sie ein Programm aus der konfigurierten Liste, mit vollständigem Pfad.
+create-link-error-file-types = „{ $entry }“ ist kein Dateityp. Verwenden Sie Endungen wie .pdf oder MIME-Typen wie image/*.
+create-link-guest-email = E-Mail des Gastes:
+create-link-guest-email-placeholder = z. B. kunde@example.com
+create-link-guest-email-help = Der Gast wird kurz vor Ablauf per E-Mail erinnert, falls noch nichts hochgeladen wurde (optional)
+create-link-language = Sprache der Upload-Seite:
+create-link-language-auto = Automatisch (Browsersprache des Gastes)
+create-link-language-help = Die Upload-Seite unabhängig von den Browsereinstellungen des Gastes in dieser Sprache anzeigen
+create-link-logo = Logo-URL:
+create-link-logo-placeholder = z. B. https://example.com/logo.png
+create-link-logo-help = Wird auf der Upload-Seite statt des Standardsymbols angezeigt. Eine http(s)-URL oder ein Pfad auf diesem Server wie /static/logo.png (optional)
+create-link-accent-color = Akzentfarbe:
+create-link-accent-color-help = Farbe der Upload-Seite im Format #rrggbb, passend zum Erscheinungsbild des Kunden (optional)
+create-link-submit = Link erstellen
+create-link-tips = 💡 Tipps:
+create-link-tip-name = Wählen Sie einen aussagekräftigen Namen, an dem Sie den Zweck des Links erkennen
+create-link-tip-size = Legen Sie Größenlimits passend zu Serverkapazität und Verwendungszweck fest
+create-link-tip-expiry = Setzen Sie für vorübergehende Uploads eine Ablaufzeit
+create-link-tip-isolation = Jeder Upload eines Gastes wird in einem eigenen, isolierten Ordner gespeichert
+create-link-error-form = Ungültige Formulardaten. Bitte prüfen Sie, ob die Ablaufzeit eine gültige Zahl ist.
+create-link-error-email = Bitte geben Sie eine gültige E-Mail-Adresse für den Gast ein.
+create-link-error-logo = Das Logo muss eine http(s)-URL oder ein mit / beginnender Pfad sein.
+create-link-error-note = Der Hinweis für Gäste ist zu lang (höchstens { $limit } Zeichen).
+create-link-error-color = Die Akzentfarbe muss im Format #rrggbb angegeben werden, z. B. #0a7cff.
+create-link-error-project = Das ausgewählte Projekt existiert nicht mehr.
+create-link-error-project-quota = Das Kontingent übersteigt den Rest des Projekts „{ $project }“ ({ $available }).
+create-link-error-failed = Der Upload-Link konnte nicht erstellt werden
+
+## Edit link
+
+edit-link-title = Upload-Link bearbeiten - NeedADrop Admin
+edit-link-heading = Upload-Link bearbeiten
+edit-link-intro = Ändern Sie die Einstellungen von „{ $name }“. Die Upload-Adresse bleibt gleich.
+edit-link-quota-help = Gesamtgröße der Dateien, die der Link annimmt (in Megabyte). Bereits hochgeladen wurden { $used }; kleiner kann das Kontingent nicht werden.
+edit-link-expires = Neuer Ablauf in Stunden:
+edit-link-expires-placeholder = Leer lassen, um den aktuellen Ablauf beizubehalten
+edit-link-expires-current = Läuft derzeit am { $date } ab.
+edit-link-expires-never = Läuft derzeit nicht ab.
+edit-link-expires-help = Geben Sie die Stunden ab jetzt bis zum Ablauf an, oder 0 für keinen Ablauf.
+edit-link-active = Link ist aktiv
+edit-link-active-help = Inaktive Links nehmen keine Uploads an, behalten aber ihre Dateien.
+edit-link-submit = Änderungen speichern
+edit-link-error-name = Bitte geben Sie einen Namen für den Link ein.
+edit-link-error-quota = Das Kontingent kann nicht kleiner sein als das bereits Hochgeladene ({ $used }).
+edit-link-error-public = Der Link der öffentlichen Ablage wird über die PUBLIC_DROP_*-Einstellungen konfiguriert und kann hier nicht bearbeitet werden.
+edit-link-error-failed = Der Upload-Link konnte nicht gespeichert werden
+
+## Projects
+
+projects-title = Projekte - NeedADrop Admin
+projects-heading = 📁 Projekte
+projects-intro = Projekte gruppieren Upload-Links nach Kunde oder Auftrag. Ein Projektkontingent begrenzt das Gesamtkontingent seiner Links.
+projects-name = Name
+projects-quota = Kontingent (MB)
+projects-quota-placeholder = Unbegrenzt
+projects-create = Projekt anlegen
+projects-empty = Noch keine Projekte.
+projects-col-name = Projekt
+projects-col-links = Links
+projects-col-quota = Kontingent
+projects-col-uploads = Uploads
+projects-col-actions = Aktionen
+projects-quota-of = { $allocated } von { $quota } vergeben
+projects-quota-unlimited = { $allocated } vergeben (unbegrenzt)
+projects-upload-count = { $count ->
+    [one] 1 Datei
+   *[other] { $count } Dateien
+}
+projects-view-links = Links
+projects-view-uploads = Uploads
+projects-confirm-delete = Dieses Projekt löschen? Seine Links und Dateien bleiben erhalten.
+projects-error-form = Bitte geben Sie einen Projektnamen ein.
+projects-error-exists = Ein Projekt mit diesem Namen existiert bereits.
+projects-filter = 📁 Projekt:
+projects-filter-all = Alle Projekte
+projects-filter-apply = Filtern
+
+## Share links
+
+shares-title = Freigabe-Links - NeedADrop Admin
+shares-heading = 📦 Freigabe-Links
+shares-intro = Über Freigabe-Links laden Gäste Dateien herunter, die Sie aus den Uploads auswählen oder selbst hochladen. Ein Link kann ablaufen und die Zahl der Downloads begrenzen.
+shares-sharing = „{ $name }“ freigeben: Legen Sie dafür einen neuen Freigabe-Link an oder fügen Sie die Datei einem bestehenden hinzu.
+shares-name = Name
+shares-expires = Läuft ab in (Stunden)
+shares-expires-placeholder = Nie
+shares-max-downloads = Max. Downloads
+shares-max-downloads-placeholder = Unbegrenzt
+shares-create = Freigabe-Link anlegen
+shares-empty = Noch keine Freigabe-Links.
+shares-col-name = Freigabe-Link
+shares-col-url = URL
+shares-col-expires = Läuft ab
+shares-col-downloads = Downloads
+shares-col-files = Dateien
+shares-col-actions = Aktionen
+shares-status-expired = Abgelaufen oder aufgebraucht
+shares-file-count = { $count ->
+    [one] 1 Datei
+   *[other] { $count } Dateien
+}
+shares-downloads = { $count } Downloads
+shares-downloads-of = { $count } von { $max } Downloads
+shares-add-here = Hier hinzufügen
+shares-manage = Dateien
+shares-confirm-delete = Diesen Freigabe-Link löschen? Selbst hochgeladene Dateien werden mitgelöscht, freigegebene Uploads bleiben erhalten.
+shares-error-form = Bitte geben Sie einen Namen für den Freigabe-Link ein.
+share-admin-title = { $name } - Freigabe-Link - NeedADrop Admin
+share-admin-upload = Dateien zum Freigeben hochladen
+share-admin-upload-button = Hochladen
+share-admin-upload-help = Um eine Datei freizugeben, die Ihnen ein Gast geschickt hat, nutzen Sie die Schaltfläche „Freigeben“ auf der Upload-Seite.
+share-admin-empty = Dieser Freigabe-Link enthält noch keine Dateien.
+share-admin-col-file = Datei
+share-admin-col-size = Größe
+share-admin-col-source = Herkunft
+share-admin-col-added = Hinzugefügt
+share-admin-source-upload = Upload
+share-admin-source-admin = Von einem Admin hochgeladen
+share-admin-remove = Entfernen
+share-admin-confirm-remove = Diese Datei aus dem Freigabe-Link entfernen?
+share-admin-back = Alle Freigabe-Links
+share-error-upload = Die Datei konnte nicht gespeichert werden. Bitte versuchen Sie es erneut.
+
+## API keys
+
+api-keys-title = API-Schlüssel - NeedADrop Admin
+api-keys-heading = 🔑 API-Schlüssel
+api-keys-intro = API-Schlüssel authentifizieren Anfragen an die REST-API unter /api/v1. Senden Sie einen Schlüssel als „Authorization: Bearer <Schlüssel>“ oder im Header X-Api-Key.
+api-keys-created = Schlüssel erstellt. Kopieren Sie ihn jetzt; er wird nicht noch einmal angezeigt.
+api-keys-name = Name
+api-keys-name-placeholder = z. B. CI-Pipeline
+api-keys-create = API-Schlüssel erstellen
+api-keys-empty = Noch keine API-Schlüssel.
+api-keys-col-name = Name
+api-keys-col-key = Schlüssel
+api-keys-col-created = Erstellt
+api-keys-col-last-used = Zuletzt verwendet
+api-keys-col-actions = Aktionen
+api-keys-never-used = Nie
+api-keys-revoke = Widerrufen
+api-keys-confirm-revoke = Diesen API-Schlüssel widerrufen? Skripte, die ihn verwenden, funktionieren sofort nicht mehr.
+api-keys-error-form = Bitte geben Sie einen Namen für den API-Schlüssel ein.
+
+## Admin accounts
+
+admins-title = Admin-Konten - NeedADrop Admin
+admins-heading = 👤 Admin-Konten
+admins-intro = Alle, die sich an der Admin-Oberfläche anmelden können. Nur Eigentümer sehen diese Seite.
+admins-username = Benutzername
+admins-password = Anfangspasswort
+admins-role = Rolle
+admins-create = Admin hinzufügen
+admins-roles-help = Eigentümer dürfen alles, auch Admins verwalten, rechtliche Sperren aufheben und personenbezogene Daten löschen. Manager verwalten Links, Uploads, Freigaben, Schlüssel und Einstellungen. Prüfer können alles ansehen und herunterladen, aber nichts ändern.
+admins-col-username = Benutzername
+admins-col-role = Rolle
+admins-col-created = Erstellt
+admins-col-actions = Aktionen
+admins-you = (Sie)
+admins-save-role = Speichern
+admins-confirm-delete = Dieses Admin-Konto löschen? Die Person wird sofort abgemeldet.
+admins-col-two-factor = Zwei-Faktor
+admins-two-factor-on = An
+admins-two-factor-off = Aus
+admins-reset-two-factor = Zurücksetzen
+admins-confirm-reset-two-factor = Zwei-Faktor-Authentifizierung für dieses Konto ausschalten? Die Anmeldung ist dann nur mit Passwort möglich, bis sie erneut eingerichtet wird.
+admins-error-form = Bitte geben Sie einen Benutzernamen ein und wählen Sie eine Rolle.
+admins-error-taken = Es gibt bereits einen Admin namens „{ $username }“.
+admins-error-last-owner = Es muss immer mindestens einen Eigentümer geben.
+admins-error-self = Sie können Ihr eigenes Konto nicht löschen.
+admin-role-owner = Eigentümer
+admin-role-manager = Manager
+admin-role-auditor = Prüfer (nur lesen)
+
+## Webhooks
+
+webhooks-title = Webhooks - NeedADrop Admin
+webhooks-heading = 🪝 Webhooks
+webhooks-intro = Ereignisse werden als JSON an jeden Endpunkt gesendet (POST), signiert mit seinem Geheimnis im Header X-NeedADrop-Signature (sha256=HMAC des Inhalts). Fehlgeschlagene Zustellungen werden im Hintergrund wiederholt.
+webhooks-url = Endpunkt-URL
+webhooks-events = Ereignisse
+webhooks-events-placeholder = Alle Ereignisse
+webhooks-secret = Geheimnis
+webhooks-secret-placeholder = Wird erzeugt
+webhooks-create = Webhook hinzufügen
+webhooks-available-events = Verfügbare Ereignisse:
+webhooks-empty = Noch keine Webhooks.
+webhooks-col-url = Endpunkt
+webhooks-col-events = Ereignisse
+webhooks-col-secret = Geheimnis
+webhooks-col-created = Hinzugefügt
+webhooks-col-actions = Aktionen
+webhooks-all-events = Alle Ereignisse
+webhooks-confirm-delete = Diesen Webhook entfernen? Noch ausstehende Zustellungen werden verworfen.
+webhooks-error-url = Bitte geben Sie eine http://- oder https://-URL ein.
+webhooks-error-event = „{ $event }“ ist kein Ereignis. Wählen Sie aus den Ereignissen unter dem Formular.
+
+## Uploads
+
+uploads-title = Uploads - NeedADrop Admin
+uploads-heading = Hochgeladene Dateien
+uploads-total = Uploads insgesamt
+uploads-total-size = Gesamtgröße
+uploads-empty = Es wurden noch keine Dateien hochgeladen.
+uploads-show-archived = 🗄️ Dateien archivierter Links anzeigen
+uploads-hide-archived = ⬅️ Zurück zu den Dateien aktueller Links
+uploads-show-starred = ⭐ Nur markierte
+uploads-show-all = Alle Dateien
+uploads-show-flagged = ⚠️ Personenbezogene Daten zu prüfen
+uploads-star = Datei markieren
+uploads-unstar = Markierung entfernen
+uploads-pii-badge = Personenbezogene Daten: { $kinds }
+uploads-pii-hint = Die Prüfung hat vermutlich personenbezogene Daten gefunden; Datei ansehen und als geprüft markieren
+uploads-pii-reviewed = Geprüft: { $kinds }
+uploads-scan-clean-hint = Auf Viren geprüft, nichts gefunden
+uploads-pii-mark-reviewed = Als geprüft markieren
+uploads-share = Freigeben
+pii-kind-ssn = Sozialversicherungsnummer
+pii-kind-iban = IBAN
+pii-kind-credit-card = Kartennummer
+uploads-create-link = Upload-Link erstellen
+uploads-link-created = Erstellt: { $date }
+uploads-link-total-quota = Kontingent: { $size }
+uploads-link-token = Token: { $token }
+uploads-download-all = 📦 Alle herunterladen (ZIP)
+uploads-link-expires = Läuft ab: { $date }
+uploads-link-no-expiry = Kein Ablaufdatum
+uploads-link-retention = { $count ->
+    [one] Dateien werden 1 Tag aufbewahrt
+   *[other] Dateien werden { $count } Tage aufbewahrt
+}
+uploads-file-count = { $count ->
+    [one] 1 Datei hochgeladen
+   *[other] { $count } Dateien hochgeladen
+}
+uploads-col-name = Dateiname
+uploads-col-size = Größe
+uploads-col-type = Typ
+uploads-col-uploaded = Hochgeladen
+uploads-col-actions = Aktionen
+uploads-mirror-status = Spiegelung: { $status }
+uploads-detected-mime = Inhalt sieht aus wie { $type }
+uploads-uploader-name = Gesendet von { $name }
+uploads-has-message = Mit Nachricht gesendet
+uploads-message-sent = Nachricht gesendet am { $date }
+uploads-message-files = { $count ->
+    [one] Gesendet mit 1 Datei:
+   *[other] Gesendet mit { $count } Dateien:
+}
+uploads-checksum-none = Noch keine Prüfsumme
+uploads-integrity-ok = ✔ Unverändert ({ $date })
+uploads-integrity-failed = ✖ Auf dem Datenträger fehlend oder verändert ({ $date })
+uploads-verify = Prüfen
+uploads-verify-hint = Datei vom Datenträger lesen und mit der beim Hochladen berechneten Prüfsumme vergleichen
+uploads-deleted-in = { $count ->
+    [one] Wird in 1 Tag gelöscht
+   *[other] Wird in { $count } Tagen gelöscht
+}
+uploads-deletion-due = Wird bei der nächsten Bereinigung gelöscht
+uploads-confirm-delete = Möchten Sie diese Datei wirklich löschen?
+uploads-version = v{ $version }
+uploads-version-history = { $count ->
+    [one] 1 ältere Version
+   *[other] { $count } ältere Versionen
+}
+uploads-select-all = Alle Dateien dieses Links auswählen
+uploads-bulk-download = ⬇️ Auswahl als ZIP herunterladen
+uploads-bulk-delete = Auswahl löschen
+uploads-bulk-confirm-delete = Die ausgewählten Dateien löschen? Steht eine davon unter rechtlicher Sperre, wird keine gelöscht.
+uploads-confirm-delete-version = Möchten Sie diese Version wirklich löschen?
+legal-hold-badge = Rechtliche Sperre
+legal-hold-hint = Rechtlich gesperrt: kann erst gelöscht werden, wenn ein Eigentümer die Sperre aufhebt
+legal-hold-place = Sperren
+legal-hold-release = Sperre aufheben
+legal-hold-confirm-place = Diese Datei rechtlich sperren? Nur ein Eigentümer kann die Sperre aufheben.
+legal-hold-confirm-place-link = Alle Dateien dieses Links rechtlich sperren? Nur ein Eigentümer kann die Sperre aufheben.
+legal-hold-confirm-release = Rechtliche Sperre aufheben? Die Dateien können danach wieder gelöscht werden.
+
+## Upload preview
+preview-title = { $name } - Vorschau - NeedADrop Admin
+preview-back = ← Uploads
+preview-unavailable = Dieser Dateityp kann nicht im Browser angezeigt werden. Laden Sie die Datei herunter, um sie zu öffnen.
+preview-truncated = Es wird nur der Anfang der Datei angezeigt. Laden Sie sie herunter, um alles zu sehen.
+
+## Search
+
+search-title = Suche - NeedADrop Admin
+search-heading = 🔍 Suche
+search-placeholder = Dateien und Links durchsuchen…
+search-button = Suchen
+search-no-results = Keine Treffer für „{ $query }“.
+search-links-heading = { $count ->
+    [one] 1 Link
+   *[other] { $count } Links
+}
+search-uploads-heading = { $count ->
+    [one] 1 Datei
+   *[other] { $count } Dateien
+}
+search-col-link = Link
+
+## Background tasks
+
+tasks-title = Hintergrundaufgaben - NeedADrop Admin
+tasks-heading = Hintergrundaufgaben
+tasks-pending = Ausstehend
+tasks-running = Laufend
+tasks-failed = Fehlgeschlagen (Dead-Letter)
+tasks-dead-letter-heading = Dead-Letter-Warteschlange
+tasks-empty = Keine fehlgeschlagenen Aufgaben. Alles läuft reibungslos.
+tasks-col-type = Typ
+tasks-col-details = Details
+tasks-col-attempts = Versuche
+tasks-col-last-error = Letzter Fehler
+tasks-col-last-attempt = Letzter Versuch
+tasks-col-actions = Aktionen
+tasks-confirm-discard = Diese Aufgabe verwerfen? Sie wird nicht erneut ausgeführt.
+tasks-commands-heading = Befehle nach dem Hochladen
+tasks-col-status = Status
+tasks-col-output = Ausgabe
+tasks-command-output = Ausgabe anzeigen
+tasks-command-failed = Fehlgeschlagen, Ausgabe anzeigen
+
+## Audit log
+
+audit-title = Audit-Protokoll - NeedADrop Admin
+audit-heading = Audit-Protokoll
+audit-text = Änderungen an rechtlichen Sperren, Betroffenenanfragen und Prüfungen personenbezogener Daten, neueste zuerst.
+audit-empty = Noch keine Einträge.
+audit-col-time = Zeit
+audit-col-admin = Admin
+audit-col-action = Aktion
+audit-col-target = Betroffen
+audit-action-legal-hold-placed = Rechtliche Sperre gesetzt
+audit-action-legal-hold-released = Rechtliche Sperre aufgehoben
+audit-action-gdpr-exported = Personenbezogene Daten exportiert
+audit-action-gdpr-erased = Personenbezogene Daten gelöscht
+audit-action-pii-reviewed = Funde personenbezogener Daten geprüft
+audit-action-retention-deleted = Dateien eines abgelaufenen Links gelöscht
+audit-target-upload = Datei
+audit-target-link = Link
+audit-target-report = Bericht
+
+## Data subject requests
+
+gdpr-title = Datenanfragen - NeedADrop Admin
+gdpr-heading = Betroffenenanfragen
+gdpr-intro = Finden Sie alle zu einer Person gespeicherten Daten: ihre hochgeladenen Dateien und Dateiangaben, die Gast-E-Mail der für sie erstellten Links und Audit-Einträge zu ihren Dateien. Der Export lädt einen Bericht herunter; die Löschung entfernt die Daten und lädt einen Bericht über das Entfernte herunter. Rechtlich gesperrte Dateien bleiben erhalten.
+gdpr-unsigned-hint = Berichte werden nicht signiert. Setzen Sie GDPR_REPORT_KEY, um sie zu signieren.
+gdpr-subject = Person
+gdpr-subject-placeholder = gast@example.com, 203.0.113.7 oder eine Gastordner-ID
+gdpr-subject-help = E-Mail-Adresse, mit der hochgeladen wurde oder für die der Link erstellt wurde, die Adresse, von der hochgeladen wurde, oder der Gastordner.
+gdpr-export = Daten exportieren
+gdpr-erase = Daten löschen
+gdpr-confirm-erase = Alle Daten dieser Person endgültig löschen? Dies kann nicht rückgängig gemacht werden.
+gdpr-erase-owner-only = Nur Eigentümer können Daten löschen.
+gdpr-error-subject = Geben Sie eine E-Mail-Adresse, Client-Adresse oder einen Gastordner ein.
+gdpr-error-not-owner = Nur Eigentümer können Daten löschen.
+gdpr-verify-heading = Bericht prüfen
+gdpr-verify-report = Inhalt der Berichtsdatei
+gdpr-verify = Signatur prüfen
+gdpr-verify-valid = Der Bericht ist unverändert: Die Signatur ist gültig.
+gdpr-verify-invalid = Die Signatur stimmt nicht: Der Bericht wurde verändert oder mit einem anderen Schlüssel signiert.
+gdpr-verify-unsigned = Der Bericht ist nicht signiert.
+gdpr-verify-no-key = Berichte können nicht geprüft werden: GDPR_REPORT_KEY ist nicht gesetzt.
+gdpr-verify-malformed = Dies ist keine Berichtsdatei.
+
+## Change password
+
+password-title = Passwort ändern - NeedADrop Admin
+password-heading = Passwort ändern
+password-intro = Ändern Sie das Passwort Ihres Admin-Kontos für mehr Sicherheit.
+password-current = Aktuelles Passwort:
+password-current-help = Geben Sie Ihr aktuelles Passwort zur Bestätigung Ihrer Identität ein
+password-new = Neues Passwort:
+password-new-help = Wählen Sie ein starkes Passwort mit mindestens 6 Zeichen
+password-confirm = Neues Passwort bestätigen:
+password-confirm-help = Geben Sie Ihr neues Passwort zur Bestätigung erneut ein
+password-submit = Passwort ändern
+password-tips = 🔒 Sicherheitstipps:
+password-tip-unique = Verwenden Sie ein starkes, einzigartiges Passwort, das Sie nirgendwo sonst nutzen
+password-tip-manager = Nutzen Sie einen Passwortmanager, um sichere Passwörter zu erzeugen und zu speichern
+password-tip-hashed = Ihr Passwort wird vor dem Speichern sicher gehasht
+password-tip-session = Nach der Änderung bleiben Sie in Ihrer aktuellen Sitzung angemeldet
+password-success = Passwort erfolgreich geändert!
+password-error-mismatch = Die neuen Passwörter stimmen nicht überein
+password-error-too-short = Das Passwort muss mindestens 6 Zeichen lang sein
+password-error-not-found = Admin-Benutzer nicht gefunden
+password-error-database = Datenbankfehler
+password-error-incorrect = Das aktuelle Passwort ist falsch
+password-error-hash = Das neue Passwort konnte nicht gehasht werden
+password-error-update = Das Passwort konnte nicht in der Datenbank gespeichert werden
+
+## Display preferences
+
+preferences-title = Anzeigeeinstellungen - NeedADrop Admin
+preferences-heading = Anzeigeeinstellungen
+preferences-intro = Legen Sie fest, wie Datum und Uhrzeit im Admin-Bereich angezeigt werden.
+preferences-timezone = Zeitzone:
+preferences-timezone-default = Serverstandard ({ $timezone })
+preferences-timezone-help = IANA-Zeitzonenname wie Europe/Berlin. Leer lassen, um den Serverstandard zu verwenden.
+preferences-preview = Aktuelle Zeit: { $time }
+preferences-submit = Einstellungen speichern
+preferences-saved = Einstellungen gespeichert.
+preferences-error-timezone = Unbekannte Zeitzone „{ $timezone }“.
+preferences-error-save = Die Einstellungen konnten nicht gespeichert werden
+
+## Two-factor authentication
+
+two-factor-title = Zwei-Faktor-Authentifizierung - NeedADrop Admin
+two-factor-heading = Zwei-Faktor-Authentifizierung
+two-factor-intro = Bei der Anmeldung zusätzlich zum Passwort einen Code aus einer Authenticator-App abfragen.
+two-factor-status-on = ✅ Zwei-Faktor-Authentifizierung ist eingeschaltet.
+two-factor-status-off = Zwei-Faktor-Authentifizierung ist ausgeschaltet.
+two-factor-setup-scan = Scannen Sie diesen QR-Code mit einer Authenticator-App wie Aegis, Google Authenticator oder 1Password.
+two-factor-setup-manual = Oder geben Sie diesen Schlüssel von Hand ein:
+two-factor-code-help = Geben Sie den Code aus der App ein, um die Einrichtung zu bestätigen
+two-factor-enable = Einschalten
+two-factor-disable = Ausschalten
+two-factor-disable-help = Geben Sie Ihr Passwort ein, um die Zwei-Faktor-Authentifizierung auszuschalten
+two-factor-enabled = Zwei-Faktor-Authentifizierung ist jetzt eingeschaltet.
+two-factor-disabled = Zwei-Faktor-Authentifizierung ist jetzt ausgeschaltet.
+two-factor-error-code = Der Code stimmt nicht. Prüfen Sie die Uhrzeit Ihres Telefons und versuchen Sie es erneut.
+two-factor-error-save = Die Zwei-Faktor-Einstellungen konnten nicht gespeichert werden
+
+## Email templates
+
+email-templates-title = E-Mail-Vorlagen - NeedADrop Admin
+email-templates-heading = E-Mail-Vorlagen
+email-templates-intro = Benachrichtigungs-E-Mails verwenden diese Texte. Platzhalter wie {"{{"}link_name{"}}"} werden beim Versand ersetzt.
+email-templates-col-email = E-Mail
+email-templates-col-status = Status
+email-templates-col-actions = Aktionen
+email-templates-customized = Angepasst am { $date }
+email-templates-default = Standardtext
+email-templates-edit = Bearbeiten
+email-template-edit-title = E-Mail-Vorlage bearbeiten - NeedADrop Admin
+email-template-expiry-reminder-admin = Ablauferinnerung (Admin)
+email-template-expiry-reminder-admin-description = Wird an Admins gesendet, wenn ein ungenutzter Link bald abläuft.
+email-template-expiry-reminder-guest = Ablauferinnerung (Gast)
+email-template-expiry-reminder-guest-description = Wird an den eingeladenen Gast gesendet, wenn sein ungenutzter Link bald abläuft.
+email-template-upload-received = Datei empfangen (Admin)
+email-template-upload-received-description = Wird für jede Datei an Admins gesendet, die über einen Link mit aktivierten Benachrichtigungen hochgeladen wird.
+email-template-subject = Betreff:
+email-template-body = Text:
+email-template-variables = Verfügbare Variablen:
+email-template-save = Vorlage speichern
+email-template-preview = Vorschau
+email-template-reset = Auf Standard zurücksetzen
+email-template-confirm-reset = Änderungen verwerfen und den Standardtext wiederherstellen?
+email-template-preview-heading = Vorschau
+email-template-preview-help = Mit Beispielwerten dargestellt.
+email-template-saved = E-Mail-Vorlage gespeichert.
+email-template-error-empty = Betreff und Text dürfen nicht leer sein.
+email-template-error-save = E-Mail-Vorlage konnte nicht gespeichert werden
@@ -0,0 +1,842 @@
+## Shared admin chrome
+
+admin-brand = 📤 NeedADrop Admin
+admin-welcome = Welcome, { $username }!
+nav-dashboard = Dashboard
+nav-logout = Logout
+action-cancel = Cancel
+action-delete = Delete
+action-view = View
+action-edit = Edit
+action-preview = Preview
+action-download = Download
+action-retry = Retry
+action-discard = Discard
+pagination-previous = ← Previous
+pagination-next = Next →
+pagination-position = Page { $page } of { $pages }
+
+## Theme toggle
+
+theme-switch-dark = Switch to dark theme
+theme-switch-light = Switch to light theme
+
+## Home page
+
+index-title = NeedADrop - Secure File Upload
+index-subtitle = Secure File Upload Service
+index-intro = Welcome to NeedADrop, a secure file upload service that allows administrators to create time-limited upload links for guests.
+index-admin-login = 🔐 Admin Login
+index-feature-links-title = 🔒 Secure Links
+index-feature-links = Administrators create unique, secure upload links with token-based access
+index-feature-expiry-title = ⏰ Time Limited
+index-feature-expiry = Links can expire after a specified time period for better security
+index-feature-quota-title = 📏 Quota System
+index-feature-quota = Set total upload quota per link that decreases with each upload
+index-feature-isolation-title = 👤 Guest Isolation
+index-feature-isolation = Each upload link stores files in a separate, isolated folder
+index-feature-control-title = 🛡️ Admin Control
+index-feature-control = Only administrators can view, download, and manage uploaded files
+index-feature-ui-title = 🎨 Modern Interface
+index-feature-ui = Clean, responsive design with drag & drop file uploads
+
+## Error pages
+
+error-title = Error { $status } - NeedADrop
+error-not-found-heading = Page not found
+error-not-found-text = The address you followed does not exist. Please check the link you received.
+error-forbidden-heading = Access denied
+error-forbidden-text = You are not allowed to open this page.
+error-gone-heading = This link is no longer available
+error-gone-text = The upload link has expired or was deactivated. Please ask the person who sent it to you for a new one.
+error-server-heading = Something went wrong
+error-server-text = The server could not complete your request. Please try again later.
+error-generic-heading = Request could not be completed
+error-generic-text = Please check your request and try again.
+error-home = Back to home
+error-request-id = Request ID:
+
+## Guest upload page
+
+upload-title = Upload File - { $name }
+upload-info-heading = 📊 Upload Information
+upload-remaining-quota = Remaining Quota
+upload-total-quota = Total Quota
+upload-per-file-limit = Per File
+upload-files-left = Files Left
+upload-guest-quota = Your Limit
+upload-daily-quota = Per 24 Hours
+upload-file-types = Accepted File Types
+upload-link-expires = Link Expires
+upload-your-name = Your name (optional)
+upload-your-name-placeholder = So the recipient knows who sent the files
+upload-message = Message (optional)
+upload-message-placeholder = Anything the recipient should know, e.g. "Invoices for March"
+upload-message-too-long = Your message is too long (at most { $limit } characters).
+upload-select-file = 📁 Select file to upload:
+upload-drop-here = Drop your file here or click to browse
+upload-max-size-hint = Maximum size based on remaining quota and the limit per file
+upload-remaining-hint = 💡 Remaining quota:
+upload-collision-keep-both = 📑 Uploading a file with a name you already used keeps both as separate versions.
+upload-collision-overwrite-latest = 🔁 Uploading a file with a name you already used replaces the earlier file.
+upload-collision-reject-duplicate = 🚫 Each filename can only be uploaded once; rename the file to send a new copy.
+upload-button = 🚀 Upload File
+upload-js-select-file = ❌ Please select a file to upload
+upload-js-files-selected = { $count } files selected
+upload-js-too-large = ❌ File size ({ $size }) exceeds remaining quota ({ $remaining })
+upload-js-file-too-large = ❌ { $name } ({ $size }) is larger than the limit of { $limit } per file
+upload-js-uploading = ⏳ Uploading...
+upload-js-success = ✅ File uploaded successfully!
+upload-js-failed = Upload failed
+upload-js-failed-retry = ❌ Upload failed. Please try again.
+upload-js-waiting = Waiting for server...
+upload-js-progress = { $received } of { $total } received by server
+upload-js-queued = 📶 You are offline. { $name } was queued and will upload automatically once you are back online.
+upload-js-queued-done = ✅ { $name } was uploaded from the offline queue.
+upload-js-queued-failed = ❌ { $name } could not be uploaded: { $error }
+upload-expired-link-name = Expired Link
+upload-success = File uploaded successfully!
+upload-success-replaced = File uploaded successfully and replaced the earlier file with the same name!
+upload-success-same-content = File uploaded, but it looks like a duplicate: the same file was already uploaded as "{ $name }".
+upload-results-stored = { $stored } of { $total } files uploaded successfully.
+upload-results-failed = { $failed } of { $total } files could not be uploaded:
+upload-error-expired = Upload link has expired or is inactive
+upload-error-not-found = Upload link not found
+upload-error-busy = The server is busy processing other uploads. Please try again in a moment.
+upload-error-save-file = Failed to save uploaded file
+upload-error-read-file = Failed to read uploaded file
+upload-error-save-info = Failed to save upload information
+upload-error-no-file = No file was uploaded
+upload-error-files-limit = This link does not accept any more files.
+upload-error-too-large = File size ({ $size } MB) exceeds remaining quota ({ $remaining } MB). Total quota: { $total } MB
+upload-error-too-large-unknown = File exceeds remaining quota ({ $remaining } MB). Total quota: { $total } MB
+upload-error-file-limit = File size ({ $size } MB) exceeds the limit of { $limit } MB per file
+upload-error-file-limit-unknown = File exceeds the limit of { $limit } MB per file
+upload-error-guest-quota = You have reached your own upload limit on this link: { $remaining } left of { $quota }. The file was not saved.
+upload-error-daily-quota = This link accepts { $quota } per 24 hours and only { $remaining } is left right now. Please try again later; the file was not saved.
+upload-error-file-type = This link doesn't accept files like "{ $name }". Accepted file types: { $types }. The file was not saved.
+upload-error-file-content = The contents of "{ $name }" don't match its file type. The file was not saved.
+upload-error-duplicate = A file named { $name } was already uploaded to this link. Please rename the file and try again.
+upload-error-same-content = This file was already uploaded as "{ $name }", so it was not saved again.
+upload-error-rules = This file is not accepted on this link.
+upload-error-infected = "{ $name }" was refused: the virus scanner found malware in it.
+upload-error-scan-failed = The file could not be checked for viruses, so it was not saved. Please try again later.
+
+## Public drop
+
+captcha-title = Public Drop - NeedADrop
+captcha-prompt = 🤖 Please solve this to continue:
+captcha-button = ✅ Continue
+captcha-error = Wrong answer, please try again
+captcha-required = Please open the public drop page again and answer the question first
+
+## Guest share page
+
+share-title = { $name } - NeedADrop
+share-intro = These files were shared with you.
+share-expires = Available until { $time }
+share-downloads-left = { $count ->
+    [one] 1 download left
+   *[other] { $count } downloads left
+}
+share-empty = There are no files here (yet).
+share-error-not-found = Share link not found
+share-error-expired = This share link has expired or its downloads are used up
+
+## Login page
+
+login-title = Admin Login - NeedADrop
+login-heading = 🔐 NeedADrop Admin
+login-username = 👤 Username:
+login-password = 🔑 Password:
+login-button = 🚀 Login
+login-js-logging-in = ⏳ Logging in...
+login-back-home = ⬅️ Back to Home
+login-error-invalid = Invalid username or password
+login-code-intro = Enter the 6-digit code from your authenticator app.
+login-code = 🔢 Code:
+login-code-button = ✅ Verify
+login-error-code = Wrong code, please try again
+login-error-code-expired = Your login has expired, please sign in again
+login-error-code-attempts = Too many wrong codes, please sign in again
+
+## Dashboard
+
+dashboard-title = Admin Dashboard - NeedADrop
+dashboard-heading = Admin Dashboard
+dashboard-intro = Manage your secure file upload service from this dashboard.
+dashboard-failed-tasks = ⚠️ { $count ->
+    [one] 1 background task failed permanently (e.g. replication or WebDAV pushes).
+   *[other] { $count } background tasks failed permanently (e.g. replication or WebDAV pushes).
+}
+dashboard-review-failed-tasks = Review failed tasks
+dashboard-pii-flagged = ⚠️ { $count ->
+    [one] 1 file likely contains personal data and awaits review.
+   *[other] { $count } files likely contain personal data and await review.
+}
+dashboard-review-pii = Review flagged files
+dashboard-low-quota = ⚠️ { $count ->
+    [one] 1 link has less than { $percent }% of its quota left:
+   *[other] { $count } links have less than { $percent }% of their quota left:
+}
+dashboard-low-quota-item = { $remaining } of { $total } remaining
+dashboard-manage-links = Manage links
+dashboard-links-heading = 🔗 Manage Upload Links
+dashboard-links-text = Create, view, and manage secure upload links for guests. Set file size limits and expiration times.
+dashboard-view-links = View Links
+dashboard-create-link = Create New Link
+dashboard-uploads-heading = 📁 View Uploads
+dashboard-uploads-text = Browse all uploaded files, download them, or delete unwanted uploads.
+dashboard-view-uploads = View Uploads
+dashboard-stats-heading = 📊 Quick Stats
+dashboard-stats-text = Get an overview of your upload service usage and activity.
+dashboard-stats-active-links = Total active links:
+dashboard-stats-uploads = Total uploads:
+dashboard-stats-storage = Storage used:
+dashboard-volumes-heading = 💽 Storage Volumes
+dashboard-volumes-text = Files stored on each disk and the space left on it.
+dashboard-volume-stored = { $count ->
+    [one] 1 file, { $size }
+   *[other] { $count } files, { $size }
+}
+dashboard-volume-free = { $free } free of { $total }
+dashboard-volume-unavailable = Free space unknown (volume not mounted?)
+dashboard-projects-heading = 📁 Projects
+dashboard-projects-text = Group links by client or engagement and track their quota.
+dashboard-manage-projects = Manage Projects
+dashboard-shares-heading = 📦 Share Links
+dashboard-shares-text = Hand files to guests through a download link.
+dashboard-manage-shares = Manage Share Links
+
+dashboard-api-keys-heading = 🔑 API Keys
+dashboard-api-keys-text = Let scripts and CI jobs use the REST API.
+dashboard-manage-api-keys = Manage API Keys
+dashboard-admins-heading = 👤 Admin Accounts
+dashboard-admins-text = Add admins and choose what each of them may do.
+dashboard-manage-admins = Manage Admins
+dashboard-webhooks-heading = 🪝 Webhooks
+dashboard-webhooks-text = Send signed event notifications to your own endpoints.
+dashboard-manage-webhooks = Manage Webhooks
+dashboard-tasks-heading = 🧰 Background Tasks
+dashboard-tasks-text = Monitor queued post-upload work and retry tasks that failed permanently.
+dashboard-view-tasks = View Tasks
+dashboard-audit-heading = 🧾 Audit Log
+dashboard-audit-text = Review legal hold changes and data subject requests.
+dashboard-view-audit = View Audit Log
+dashboard-gdpr-heading = 🪪 Data Requests
+dashboard-gdpr-text = Export or erase everything stored about a person (GDPR).
+dashboard-open-gdpr = Open Data Requests
+dashboard-email-templates-heading = ✉️ Email Templates
+dashboard-email-templates-text = Customize the subjects and texts of notification emails.
+dashboard-edit-email-templates = Edit Emails
+dashboard-account-heading = ⚙️ Account Settings
+dashboard-account-text = Manage your admin account settings and security.
+dashboard-change-password = Change Password
+dashboard-preferences = Display Preferences
+dashboard-two-factor = Two-Factor Authentication
+dashboard-system-heading = ℹ️ System Info
+dashboard-system-text = Information about your NeedADrop installation.
+dashboard-version = Version:
+dashboard-status = Status:
+dashboard-status-running = Running
+
+## Upload links
+
+links-title = Upload Links - NeedADrop Admin
+links-heading = Upload Links
+links-create = Create New Link
+links-empty = No upload links created yet.
+links-create-first = Create Your First Link
+links-col-name = Name
+links-col-url = Upload URL
+links-col-quota = Quota
+links-col-expires = Expires
+links-col-status = Status
+links-col-created = Created
+links-col-actions = Actions
+links-bytes = bytes
+links-quota-remaining = remaining
+links-rate-limit = limited to { $kbps } KB/s
+links-uploads-used = { $used } of { $max } files received
+links-daily-quota = { $quota } per 24 h
+links-per-file-limit = max. { $limit } per file
+links-guest-quota = { $quota } per guest
+links-file-types = Only { $types }
+links-webdav-folder = copied to WebDAV: { $folder }
+links-post-upload-command = Runs: { $command }
+links-notify-on-upload = Emails the admin on each upload
+links-guest-email = guest: { $email }
+links-language = language: { $language }
+links-collision-policy = same filename: { $policy }
+links-accent-color = accent color: { $color }
+links-custom-logo = custom logo
+links-public-drop = public drop (/drop)
+links-never = Never
+links-status-active = Active
+links-status-inactive = Expired/Inactive
+links-status-paused = Paused
+links-confirm-delete = Are you sure you want to delete this link?
+links-tab-current = Current links
+links-tab-archived = 🗄️ Archived ({ $count })
+links-archive-empty = No archived links.
+links-select-all = Select all links
+links-status-archived = Archived
+links-archive = Archive
+links-unarchive = Restore
+links-pause = Pause
+links-resume = Resume
+links-attempts = Attempts
+links-stats = Stats
+links-copy-url = Copy
+links-url-copied = Copied
+links-select-finished = Select finished links
+links-bulk-archive = 🗄️ Archive selected
+links-bulk-unarchive = Restore selected
+links-error-has-uploads = Cannot delete link: it still has uploaded files. Please delete the files first.
+links-error-legal-hold = Cannot delete link: it is under legal hold.
+
+## Refused uploads
+
+attempts-title = Refused Uploads - NeedADrop Admin
+attempts-back = ← Upload Links
+attempts-heading = Refused Uploads: { $name }
+attempts-text = Uploads this link turned away in the last 90 days, newest first.
+attempts-empty = No uploads were refused.
+attempts-col-time = Time
+attempts-col-file = File
+attempts-col-reason = Reason
+attempts-col-ip = Client Address
+attempts-col-request = Request ID
+attempt-reason-link-unavailable = Link expired, inactive or full
+attempt-reason-too-large = Larger than the remaining quota
+attempt-reason-guest-quota = Guest's own limit reached
+attempt-reason-daily-quota = Limit per 24 hours reached
+attempt-reason-file-type = File type not accepted
+attempt-reason-file-content = Contents don't match the file type
+attempt-reason-duplicate = A file with this name already exists
+attempt-reason-same-content = Same file already uploaded as
+attempt-reason-refused = Refused by a rule or plugin
+attempt-reason-interrupted = Upload interrupted
+attempt-reason-infected = Virus scanner found
+
+## Link statistics
+
+link-stats-title = Link Statistics - NeedADrop Admin
+link-stats-heading = Statistics: { $name }
+link-stats-uploads = Files received
+link-stats-total-size = Total size
+link-stats-remaining = Quota left of { $total }
+link-stats-uploader-ips = Client addresses
+link-stats-refused = Refused uploads
+link-stats-last-activity = Last activity
+link-stats-daily-heading = Uploads in the last 30 days (UTC)
+link-stats-daily-empty = No files arrived in the last 30 days.
+link-stats-day-uploads = { $count } files
+
+## Create link
+
+create-link-title = Create Upload Link - NeedADrop Admin
+create-link-heading = Create Upload Link
+create-link-intro = Create a new secure upload link for guests to upload files.
+create-link-name = Link Name:
+create-link-name-placeholder = e.g., Client Documents, Project Files
+create-link-name-help = A descriptive name for this upload link (shown to users)
+create-link-note = Note for guests
+create-link-note-placeholder = e.g. Please upload the signed contract as PDF
+create-link-note-help = Shown at the top of the upload page. Optional.
+create-link-total-quota = Total Quota (MB):
+create-link-total-quota-help = Total size of all files the link accepts (in megabytes)
+create-link-per-file-limit = Limit per file in MB (optional)
+create-link-per-file-limit-placeholder = Only the quota applies
+create-link-per-file-limit-help = Largest single file a guest may upload; the total quota still applies
+create-link-max-uploads = Number of files (optional)
+create-link-max-uploads-placeholder = Unlimited
+create-link-max-uploads-help = How many files the link accepts in total, e.g. 3 for "submit exactly 3 documents". Replacing a file doesn't count again.
+create-link-daily-quota = Limit per 24 hours in MB (optional)
+create-link-daily-quota-placeholder = Unlimited
+create-link-daily-quota-help = For long-lived links: how much may arrive in any 24 hours, so the link can't be used up in one burst
+create-link-guest-quota = Limit per guest in MB (optional)
+create-link-guest-quota-placeholder = Unlimited
+create-link-guest-quota-help = For links shared with many people: how much one guest may upload, so nobody uses up the whole quota
+create-link-file-types = Accepted file types (optional)
+create-link-file-types-placeholder = Any type, e.g. image/*, .pdf
+create-link-file-types-help = Comma-separated extensions and MIME types; image/* stands for all images. Files are checked by name and by contents
+create-link-expires = Expires In (hours):
+create-link-expires-placeholder = Leave empty for no expiration
+create-link-expires-help = Number of hours until the link expires (optional, max 1 year)
+create-link-retention = Delete Files After (days):
+create-link-retention-placeholder = Keep files until deleted
+create-link-retention-help = Files are deleted this many days after they were uploaded, except files under legal hold
+create-link-rate-limit = Bandwidth Limit (KB/s):
+create-link-rate-limit-placeholder = Leave empty for unlimited
+create-link-rate-limit-help = Maximum upload/download speed for this link (optional)
+create-link-project = Project:
+create-link-project-none = No project
+create-link-project-help = File the link under a project to group it with other links for the same client.
+create-link-collision = Same Filename Uploaded Again
+create-link-collision-help = What happens when a guest uploads a file under a name that is already on this link
+collision-policy-keep-both = Keep both as versions
+collision-policy-overwrite-latest = Replace the latest version
+collision-policy-reject-duplicate = Reject the new file
+create-link-webdav = Nextcloud / WebDAV Folder:
+create-link-webdav-placeholder = e.g. Clients/Acme/Received
+create-link-webdav-help = Received files are also copied to this folder on the configured WebDAV server (optional)
+create-link-command = Post-upload command (optional)
+create-link-command-placeholder = /usr/local/bin/import-scan --archive
+create-link-command-help = Runs after each upload with the file described in NEEDADROP_* environment variables. Output is kept on the background tasks page.
+create-link-notify = Email me when a file arrives
+create-link-notify-help = Sends a notification to the admin address (ADMIN_EMAIL) for every file this link receives.
+create-link-error-command = This command is not allowed. Use a program from the configured allowlist, named by its full path.
+create-link-error-file-types = "{ $entry }" is not a file type. Use extensions such as .pdf or MIME types such as image/*.
+create-link-guest-email = Guest Email:
+create-link-guest-email-placeholder = e.g. client@example.com
+create-link-guest-email-help = The guest is reminded by email shortly before the link expires if nothing was uploaded yet (optional)
+create-link-language = Upload Page Language:
+create-link-language-auto = Automatic (guest's browser language)
+create-link-language-help = Show the upload page in this language regardless of the guest's browser settings
+create-link-logo = Logo URL:
+create-link-logo-placeholder = e.g. https://example.com/logo.png
+create-link-logo-help = Shown on the upload page instead of the default icon. Use an http(s) URL or a path on this server such as /static/logo.png (optional)
+create-link-accent-color = Accent Color:
+create-link-accent-color-help = Color of the upload page in #rrggbb form, to match the client's branding (optional)
+create-link-submit = Create Link
+create-link-tips = 💡 Tips:
+create-link-tip-name = Choose a descriptive name that helps you identify the purpose of the link
+create-link-tip-size = Set appropriate file size limits based on your server capacity and intended use
+create-link-tip-expiry = Consider setting expiration times for temporary uploads
+create-link-tip-isolation = Each guest upload will be stored in a separate, isolated folder
+create-link-error-form = Invalid form data. Please check that the expiration time is a valid number.
+create-link-error-email = Please enter a valid guest email address.
+create-link-error-logo = The logo must be an http(s) URL or a path starting with /.
+create-link-error-note = The note for guests is too long (at most { $limit } characters).
+create-link-error-color = The accent color must be written as #rrggbb, e.g. #0a7cff.
+create-link-error-project = The selected project no longer exists.
+create-link-error-project-quota = The quota exceeds what is left of project "{ $project }" ({ $available }).
+create-link-error-failed = Failed to create upload link
+
+## Edit link
+
+edit-link-title = Edit Upload Link - NeedADrop Admin
+edit-link-heading = Edit Upload Link
+edit-link-intro = Change the settings of "{ $name }". The upload URL stays the same.
+edit-link-quota-help = Total size of the files the link accepts (in megabytes). { $used } has already been uploaded, so the quota can't be set below that.
+edit-link-expires = New expiration in hours:
+edit-link-expires-placeholder = Leave empty to keep the current expiration
+edit-link-expires-current = Currently expires { $date }.
+edit-link-expires-never = Currently never expires.
+edit-link-expires-help = Enter the hours from now until the link expires, or 0 for no expiration.
+edit-link-active = Link is active
+edit-link-active-help = Inactive links refuse uploads but keep their files.
+edit-link-submit = Save Changes
+edit-link-error-name = Please enter a name for the link.
+edit-link-error-quota = The quota can't be smaller than what has already been uploaded ({ $used }).
+edit-link-error-public = The public drop link is configured through PUBLIC_DROP_* settings and can't be edited here.
+edit-link-error-failed = Failed to save the upload link
+
+## Projects
+
+projects-title = Projects - NeedADrop Admin
+projects-heading = 📁 Projects
+projects-intro = Projects group upload links per client or engagement. A project quota caps the total quota of its links.
+projects-name = Name
+projects-quota = Quota (MB)
+projects-quota-placeholder = Unlimited
+projects-create = Create Project
+projects-empty = No projects yet.
+projects-col-name = Project
+projects-col-links = Links
+projects-col-quota = Quota
+projects-col-uploads = Uploads
+projects-col-actions = Actions
+projects-quota-of = { $allocated } of { $quota } allocated
+projects-quota-unlimited = { $allocated } allocated (unlimited)
+projects-upload-count = { $count ->
+    [one] 1 file
+   *[other] { $count } files
+}
+projects-view-links = Links
+projects-view-uploads = Uploads
+projects-confirm-delete = Delete this project? Its links and files are kept.
+projects-error-form = Please enter a project name.
+projects-error-exists = A project with this name already exists.
+projects-filter = 📁 Project:
+projects-filter-all = All projects
+projects-filter-apply = Filter
+
+## Share links
+
+shares-title = Share Links - NeedADrop Admin
+shares-heading = 📦 Share Links
+shares-intro = Share links let guests download files you pick from the uploads or upload yourself. A link can expire and limit the number of downloads.
+shares-sharing = Sharing "{ $name }": create a new share link for it, or add it to an existing one.
+shares-name = Name
+shares-expires = Expires in (hours)
+shares-expires-placeholder = Never
+shares-max-downloads = Max downloads
+shares-max-downloads-placeholder = Unlimited
+shares-create = Create Share Link
+shares-empty = No share links yet.
+shares-col-name = Share link
+shares-col-url = URL
+shares-col-expires = Expires
+shares-col-downloads = Downloads
+shares-col-files = Files
+shares-col-actions = Actions
+shares-status-expired = Expired or used up
+shares-file-count = { $count ->
+    [one] 1 file
+   *[other] { $count } files
+}
+shares-downloads = { $count } downloads
+shares-downloads-of = { $count } of { $max } downloads
+shares-add-here = Add file here
+shares-manage = Files
+shares-confirm-delete = Delete this share link? Files you uploaded to it are deleted too; shared uploads are kept.
+shares-error-form = Please enter a name for the share link.
+share-admin-title = { $name } - Share Link - NeedADrop Admin
+share-admin-upload = Upload files to share
+share-admin-upload-button = Upload
+share-admin-upload-help = To share a file a guest sent you, use the Share button on the uploads page.
+share-admin-empty = This share link has no files yet.
+share-admin-col-file = File
+share-admin-col-size = Size
+share-admin-col-source = Source
+share-admin-col-added = Added
+share-admin-source-upload = Upload
+share-admin-source-admin = Uploaded by an admin
+share-admin-remove = Remove
+share-admin-confirm-remove = Take this file off the share link?
+share-admin-back = All share links
+share-error-upload = The file could not be stored. Please try again.
+
+## API keys
+
+api-keys-title = API Keys - NeedADrop Admin
+api-keys-heading = 🔑 API Keys
+api-keys-intro = API keys authenticate requests to the REST API under /api/v1. Send a key as "Authorization: Bearer <key>" or in the X-Api-Key header.
+api-keys-created = Key created. Copy it now; it won't be shown again.
+api-keys-name = Name
+api-keys-name-placeholder = e.g. CI pipeline
+api-keys-create = Create API Key
+api-keys-empty = No API keys yet.
+api-keys-col-name = Name
+api-keys-col-key = Key
+api-keys-col-created = Created
+api-keys-col-last-used = Last used
+api-keys-col-actions = Actions
+api-keys-never-used = Never
+api-keys-revoke = Revoke
+api-keys-confirm-revoke = Revoke this API key? Scripts using it stop working immediately.
+api-keys-error-form = Please enter a name for the API key.
+
+## Admin accounts
+
+admins-title = Admin Accounts - NeedADrop Admin
+admins-heading = 👤 Admin Accounts
+admins-intro = Everyone who can log in to the admin interface. Only owners see this page.
+admins-username = Username
+admins-password = Initial password
+admins-role = Role
+admins-create = Add Admin
+admins-roles-help = Owners can do everything, including managing admins, releasing legal holds and erasing personal data. Managers handle links, uploads, shares, keys and settings. Auditors can look at and download everything but change nothing.
+admins-col-username = Username
+admins-col-role = Role
+admins-col-created = Created
+admins-col-actions = Actions
+admins-you = (you)
+admins-save-role = Save
+admins-confirm-delete = Delete this admin account? They are logged out immediately.
+admins-col-two-factor = Two-factor
+admins-two-factor-on = On
+admins-two-factor-off = Off
+admins-reset-two-factor = Reset
+admins-confirm-reset-two-factor = Turn off two-factor authentication for this account? They can log in with their password alone until they set it up again.
+admins-error-form = Please enter a username and choose a role.
+admins-error-taken = There is already an admin called "{ $username }".
+admins-error-last-owner = There must always be at least one owner.
+admins-error-self = You can't delete your own account.
+admin-role-owner = Owner
+admin-role-manager = Manager
+admin-role-auditor = Auditor (read-only)
+
+## Webhooks
+
+webhooks-title = Webhooks - NeedADrop Admin
+webhooks-heading = 🪝 Webhooks
+webhooks-intro = Events are POSTed as JSON to each endpoint, signed with its secret in the X-NeedADrop-Signature header (sha256=HMAC of the body). Failed deliveries are retried in the background.
+webhooks-url = Endpoint URL
+webhooks-events = Events
+webhooks-events-placeholder = All events
+webhooks-secret = Secret
+webhooks-secret-placeholder = Generated
+webhooks-create = Add Webhook
+webhooks-available-events = Available events:
+webhooks-empty = No webhooks yet.
+webhooks-col-url = Endpoint
+webhooks-col-events = Events
+webhooks-col-secret = Secret
+webhooks-col-created = Added
+webhooks-col-actions = Actions
+webhooks-all-events = All events
+webhooks-confirm-delete = Remove this webhook? Deliveries still waiting for it are dropped.
+webhooks-error-url = Please enter an http:// or https:// URL.
+webhooks-error-event = "{ $event }" is not an event. Pick from the events listed below the form.
+
+## Uploads
+
+uploads-title = Uploads - NeedADrop Admin
+uploads-heading = File Uploads
+uploads-total = Total Uploads
+uploads-total-size = Total Size
+uploads-empty = No files have been uploaded yet.
+uploads-show-archived = 🗄️ Show files of archived links
+uploads-hide-archived = ⬅️ Back to files of current links
+uploads-show-starred = ⭐ Starred only
+uploads-show-all = All files
+uploads-show-flagged = ⚠️ Awaiting personal data review
+uploads-star = Star this file
+uploads-unstar = Remove star
+uploads-pii-badge = Personal data: { $kinds }
+uploads-pii-hint = The scan found what looks like personal data; check the file and mark it as reviewed
+uploads-pii-reviewed = Reviewed: { $kinds }
+uploads-scan-clean-hint = Scanned for viruses, nothing found
+uploads-pii-mark-reviewed = Mark reviewed
+uploads-share = Share
+pii-kind-ssn = social security number
+pii-kind-iban = IBAN
+pii-kind-credit-card = card number
+uploads-create-link = Create Upload Link
+uploads-link-created = Created: { $date }
+uploads-link-total-quota = Quota: { $size }
+uploads-link-token = Token: { $token }
+uploads-download-all = 📦 Download all (ZIP)
+uploads-link-expires = Expires: { $date }
+uploads-link-no-expiry = No expiration
+uploads-link-retention = { $count ->
+    [one] Files kept 1 day
+   *[other] Files kept { $count } days
+}
+uploads-file-count = { $count ->
+    [one] 1 file uploaded
+   *[other] { $count } files uploaded
+}
+uploads-col-name = File Name
+uploads-col-size = Size
+uploads-col-type = Type
+uploads-col-uploaded = Uploaded
+uploads-col-actions = Actions
+uploads-mirror-status = Mirror: { $status }
+uploads-detected-mime = Contents look like { $type }
+uploads-uploader-name = Sent by { $name }
+uploads-has-message = Sent with a message
+uploads-message-sent = Message sent { $date }
+uploads-message-files = { $count ->
+    [one] Sent with 1 file:
+   *[other] Sent with { $count } files:
+}
+uploads-checksum-none = No checksum yet
+uploads-integrity-ok = ✔ Intact ({ $date })
+uploads-integrity-failed = ✖ Missing or changed on disk ({ $date })
+uploads-verify = Verify
+uploads-verify-hint = Read the file back from disk and compare it to the checksum taken at upload
+uploads-deleted-in = { $count ->
+    [one] Deleted in 1 day
+   *[other] Deleted in { $count } days
+}
+uploads-deletion-due = Deleted at the next cleanup
+uploads-confirm-delete = Are you sure you want to delete this file?
+uploads-version = v{ $version }
+uploads-version-history = { $count ->
+    [one] 1 earlier version
+   *[other] { $count } earlier versions
+}
+uploads-select-all = Select all files of this link
+uploads-bulk-download = ⬇️ Download selected as ZIP
+uploads-bulk-delete = Delete selected
+uploads-bulk-confirm-delete = Delete the selected files? If any of them is under legal hold, none is deleted.
+uploads-confirm-delete-version = Are you sure you want to delete this version?
+legal-hold-badge = Legal hold
+legal-hold-hint = Under legal hold: can't be deleted until an owner releases the hold
+legal-hold-place = Hold
+legal-hold-release = Release Hold
+legal-hold-confirm-place = Place this file under legal hold? Only an owner can release it.
+legal-hold-confirm-place-link = Place all files of this link under legal hold? Only an owner can release it.
+legal-hold-confirm-release = Release the legal hold? The files can be deleted again afterwards.
+
+## Upload preview
+preview-title = { $name } - Preview - NeedADrop Admin
+preview-back = ← Uploads
+preview-unavailable = This file type can't be shown in the browser. Download it to open it.
+preview-truncated = Only the beginning of the file is shown. Download it to see all of it.
+
+## Search
+
+search-title = Search - NeedADrop Admin
+search-heading = 🔍 Search
+search-placeholder = Search files and links…
+search-button = Search
+search-no-results = Nothing matches "{ $query }".
+search-links-heading = { $count ->
+    [one] 1 link
+   *[other] { $count } links
+}
+search-uploads-heading = { $count ->
+    [one] 1 file
+   *[other] { $count } files
+}
+search-col-link = Link
+
+## Background tasks
+
+tasks-title = Background Tasks - NeedADrop Admin
+tasks-heading = Background Tasks
+tasks-pending = Pending
+tasks-running = Running
+tasks-failed = Failed (dead-letter)
+tasks-dead-letter-heading = Dead-Letter Queue
+tasks-empty = No failed tasks. Everything is running smoothly.
+tasks-col-type = Type
+tasks-col-details = Details
+tasks-col-attempts = Attempts
+tasks-col-last-error = Last Error
+tasks-col-last-attempt = Last Attempt
+tasks-col-actions = Actions
+tasks-confirm-discard = Discard this task? It will not be retried.
+tasks-commands-heading = Post-Upload Commands
+tasks-col-status = Status
+tasks-col-output = Output
+tasks-command-output = Show output
+tasks-command-failed = Failed, show output
+
+## Audit log
+
+audit-title = Audit Log - NeedADrop Admin
+audit-heading = Audit Log
+audit-text = Legal hold changes, data subject requests and personal data reviews, newest first.
+audit-empty = No entries yet.
+audit-col-time = Time
+audit-col-admin = Admin
+audit-col-action = Action
+audit-col-target = Affected
+audit-action-legal-hold-placed = Placed legal hold
+audit-action-legal-hold-released = Released legal hold
+audit-action-gdpr-exported = Exported personal data
+audit-action-gdpr-erased = Erased personal data
+audit-action-pii-reviewed = Reviewed personal data findings
+audit-action-retention-deleted = Deleted files of expired link
+audit-target-upload = File
+audit-target-link = Link
+audit-target-report = Report
+
+## Data subject requests
+
+gdpr-title = Data Requests - NeedADrop Admin
+gdpr-heading = Data Subject Requests
+gdpr-intro = Find everything stored about one person: their uploaded files and file details, the guest email of links created for them and audit log entries about their files. Export downloads a report; erasure deletes the data and downloads a report of what was removed. Files under legal hold are kept.
+gdpr-unsigned-hint = Reports are not signed. Set GDPR_REPORT_KEY to sign them.
+gdpr-subject = Person
+gdpr-subject-placeholder = guest@example.com, 203.0.113.7 or a guest folder ID
+gdpr-subject-help = Email address they uploaded with or their link was created for, the address they uploaded from, or their guest folder.
+gdpr-export = Export Data
+gdpr-erase = Erase Data
+gdpr-confirm-erase = Permanently delete all data of this person? This cannot be undone.
+gdpr-erase-owner-only = Only owners can erase data.
+gdpr-error-subject = Enter an email address, client address or guest folder.
+gdpr-error-not-owner = Only owners can erase data.
+gdpr-verify-heading = Check a Report
+gdpr-verify-report = Contents of the report file
+gdpr-verify = Check Signature
+gdpr-verify-valid = The report is unaltered: its signature is valid.
+gdpr-verify-invalid = The signature does not match: the report was altered or signed with another key.
+gdpr-verify-unsigned = The report is not signed.
+gdpr-verify-no-key = Reports can't be checked: GDPR_REPORT_KEY is not set.
+gdpr-verify-malformed = This is not a report file.
+
+## Change password
+
+password-title = Change Password - NeedADrop Admin
+password-heading = Change Password
+password-intro = Update your admin account password for enhanced security.
+password-current = Current Password:
+password-current-help = Enter your current password to verify your identity
+password-new = New Password:
+password-new-help = Choose a strong password with at least 6 characters
+password-confirm = Confirm New Password:
+password-confirm-help = Re-enter your new password to confirm
+password-submit = Change Password
+password-tips = 🔒 Security Tips:
+password-tip-unique = Use a strong, unique password that you don't use elsewhere
+password-tip-manager = Consider using a password manager to generate and store secure passwords
+password-tip-hashed = Your password will be securely hashed before being stored
+password-tip-session = After changing your password, you'll remain logged in to your current session
+password-success = Password changed successfully!
+password-error-mismatch = New passwords do not match
+password-error-too-short = Password must be at least 6 characters long
+password-error-not-found = Admin user not found
+password-error-database = Database error
+password-error-incorrect = Current password is incorrect
+password-error-hash = Failed to hash new password
+password-error-update = Failed to update password in database
+
+## Display preferences
+
+preferences-title = Display Preferences - NeedADrop Admin
+preferences-heading = Display Preferences
+preferences-intro = Choose how dates and times are shown to you in the admin area.
+preferences-timezone = Time Zone:
+preferences-timezone-default = Server default ({ $timezone })
+preferences-timezone-help = IANA time zone name such as Europe/Berlin. Leave empty to use the server default.
+preferences-preview = Current time: { $time }
+preferences-submit = Save Preferences
+preferences-saved = Preferences saved.
+preferences-error-timezone = Unknown time zone "{ $timezone }".
+preferences-error-save = Failed to save preferences
+
+## Two-factor authentication
+
+two-factor-title = Two-Factor Authentication - NeedADrop Admin
+two-factor-heading = Two-Factor Authentication
+two-factor-intro = Ask for a code from an authenticator app in addition to your password when logging in.
+two-factor-status-on = ✅ Two-factor authentication is on.
+two-factor-status-off = Two-factor authentication is off.
+two-factor-setup-scan = Scan this QR code with an authenticator app such as Aegis, Google Authenticator or 1Password.
+two-factor-setup-manual = Or enter this key manually:
+two-factor-code-help = Enter the code the app shows to confirm the setup
+two-factor-enable = Turn On
+two-factor-disable = Turn Off
+two-factor-disable-help = Enter your password to turn two-factor authentication off
+two-factor-enabled = Two-factor authentication is now on.
+two-factor-disabled = Two-factor authentication is now off.
+two-factor-error-code = The code doesn't match. Check the time on your phone and try again.
+two-factor-error-save = Failed to save two-factor settings
+
+## Email templates
+
+email-templates-title = Email Templates - NeedADrop Admin
+email-templates-heading = Email Templates
+email-templates-intro = Notification emails use these texts. Placeholders such as {"{{"}link_name{"}}"} are replaced when an email is sent.
+email-templates-col-email = Email
+email-templates-col-status = Status
+email-templates-col-actions = Actions
+email-templates-customized = Customized { $date }
+email-templates-default = Default text
+email-templates-edit = Edit
+email-template-edit-title = Edit Email Template - NeedADrop Admin
+email-template-expiry-reminder-admin = Expiry reminder (admin)
+email-template-expiry-reminder-admin-description = Sent to admins when an unused link is about to expire.
+email-template-expiry-reminder-guest = Expiry reminder (guest)
+email-template-expiry-reminder-guest-description = Sent to the invited guest when their unused link is about to expire.
+email-template-upload-received = File received (admin)
+email-template-upload-received-description = Sent to admins for each file uploaded to a link with notifications turned on.
+email-template-subject = Subject:
+email-template-body = Body:
+email-template-variables = Available variables:
+email-template-save = Save Template
+email-template-preview = Preview
+email-template-reset = Reset to Default
+email-template-confirm-reset = Discard your changes and restore the default text?
+email-template-preview-heading = Preview
+email-template-preview-help = Rendered with sample values.
+email-template-saved = Email template saved.
+email-template-error-empty = Subject and body must not be empty.
+email-template-error-save = Failed to save email template
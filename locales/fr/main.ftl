@@ -0,0 +1,842 @@
+## Shared admin chrome
+
+admin-brand = 📤 NeedADrop Admin
+admin-welcome = Bienvenue, { $username } !
+nav-dashboard = Tableau de bord
+nav-logout = Déconnexion
+action-cancel = Annuler
+action-delete = Supprimer
+action-view = Voir
+action-edit = Modifier
+action-preview = Aperçu
+action-download = Télécharger
+action-retry = Réessayer
+action-discard = Abandonner
+pagination-previous = ← Précédent
+pagination-next = Suivant →
+pagination-position = Page { $page } sur { $pages }
+
+## Theme toggle
+
+theme-switch-dark = Passer au thème sombre
+theme-switch-light = Passer au thème clair
+
+## Home page
+
+index-title = NeedADrop - Dépôt de fichiers sécurisé
+index-subtitle = Service de dépôt de fichiers sécurisé
+index-intro = Bienvenue sur NeedADrop, un service de dépôt de fichiers sécurisé qui permet aux administrateurs de créer des liens de dépôt à durée limitée pour leurs invités.
+index-admin-login = 🔐 Connexion administrateur
+index-feature-links-title = 🔒 Liens sécurisés
+index-feature-links = Les administrateurs créent des liens de dépôt uniques et sécurisés, avec un accès par jeton
+index-feature-expiry-title = ⏰ Durée limitée
+index-feature-expiry = Les liens peuvent expirer après une durée définie, pour plus de sécurité
+index-feature-quota-title = 📏 Quotas
+index-feature-quota = Un quota total par lien, qui diminue à chaque dépôt
+index-feature-isolation-title = 👤 Dossiers invités isolés
+index-feature-isolation = Chaque dépôt est stocké dans un dossier séparé et isolé
+index-feature-control-title = 🛡️ Contrôle administrateur
+index-feature-control = Seuls les administrateurs peuvent consulter, télécharger et gérer les fichiers déposés
+index-feature-ui-title = 🎨 Interface moderne
+index-feature-ui = Interface claire et adaptative, avec dépôt par glisser-déposer
+
+## Error pages
+
+error-title = Erreur { $status } - NeedADrop
+error-not-found-heading = Page introuvable
+error-not-found-text = L'adresse demandée n'existe pas. Veuillez vérifier le lien que vous avez reçu.
+error-forbidden-heading = Accès refusé
+error-forbidden-text = Vous n'êtes pas autorisé à ouvrir cette page.
+error-gone-heading = Ce lien n'est plus disponible
+error-gone-text = Le lien de dépôt a expiré ou a été désactivé. Veuillez demander un nouveau lien à la personne qui vous l'a envoyé.
+error-server-heading = Une erreur est survenue
+error-server-text = Le serveur n'a pas pu traiter votre demande. Veuillez réessayer plus tard.
+error-generic-heading = La demande n'a pas pu aboutir
+error-generic-text = Veuillez vérifier votre demande et réessayer.
+error-home = Retour à l'accueil
+error-request-id = ID de requête :
+
+## Guest upload page
+
+upload-title = Déposer un fichier - { $name }
+upload-info-heading = 📊 Informations de dépôt
+upload-remaining-quota = Quota restant
+upload-total-quota = Quota total
+upload-per-file-limit = Par fichier
+upload-files-left = Fichiers restants
+upload-guest-quota = Votre limite
+upload-daily-quota = Par 24 heures
+upload-file-types = Types de fichiers acceptés
+upload-link-expires = Expiration du lien
+upload-your-name = Votre nom (facultatif)
+upload-your-name-placeholder = Pour que le destinataire sache qui a envoyé les fichiers
+upload-message = Message (facultatif)
+upload-message-placeholder = Ce que le destinataire doit savoir, par ex. « Factures de mars »
+upload-message-too-long = Votre message est trop long ({ $limit } caractères au maximum).
+upload-select-file = 📁 Choisissez le fichier à déposer :
+upload-drop-here = Déposez votre fichier ici ou cliquez pour parcourir
+upload-max-size-hint = Taille maximale selon le quota restant et la limite par fichier
+upload-remaining-hint = 💡 Quota restant :
+upload-collision-keep-both = 📑 Envoyer un fichier sous un nom déjà utilisé conserve les deux comme versions distinctes.
+upload-collision-overwrite-latest = 🔁 Envoyer un fichier sous un nom déjà utilisé remplace le fichier précédent.
+upload-collision-reject-duplicate = 🚫 Chaque nom de fichier ne peut être envoyé qu'une fois ; renommez le fichier pour envoyer une nouvelle copie.
+upload-button = 🚀 Déposer le fichier
+upload-js-select-file = ❌ Veuillez choisir un fichier à déposer
+upload-js-files-selected = { $count } fichiers sélectionnés
+upload-js-too-large = ❌ La taille du fichier ({ $size }) dépasse le quota restant ({ $remaining })
+upload-js-file-too-large = ❌ { $name } ({ $size }) dépasse la limite de { $limit } par fichier
+upload-js-uploading = ⏳ Envoi en cours...
+upload-js-success = ✅ Fichier déposé avec succès !
+upload-js-failed = Échec du dépôt
+upload-js-failed-retry = ❌ Échec du dépôt. Veuillez réessayer.
+upload-js-waiting = En attente du serveur...
+upload-js-progress = { $received } sur { $total } reçus par le serveur
+upload-js-queued = 📶 Vous êtes hors ligne. { $name } a été mis en file d'attente et sera envoyé automatiquement dès votre retour en ligne.
+upload-js-queued-done = ✅ { $name } a été envoyé depuis la file d'attente.
+upload-js-queued-failed = ❌ Impossible d'envoyer { $name } : { $error }
+upload-expired-link-name = Lien expiré
+upload-success = Fichier déposé avec succès !
+upload-success-replaced = Fichier envoyé avec succès ; il remplace le fichier précédent du même nom !
+upload-success-same-content = Fichier envoyé, mais il semble être un doublon : le même fichier a déjà été envoyé sous le nom « { $name } ».
+upload-results-stored = { $stored } fichiers sur { $total } déposés avec succès.
+upload-results-failed = { $failed } fichiers sur { $total } n'ont pas pu être déposés :
+upload-error-expired = Le lien de dépôt a expiré ou est désactivé
+upload-error-not-found = Lien de dépôt introuvable
+upload-error-busy = Le serveur traite d'autres dépôts. Veuillez réessayer dans un instant.
+upload-error-save-file = Impossible d'enregistrer le fichier déposé
+upload-error-read-file = Impossible de lire le fichier déposé
+upload-error-save-info = Impossible d'enregistrer les informations du dépôt
+upload-error-no-file = Aucun fichier n'a été déposé
+upload-error-files-limit = Ce lien n'accepte plus de fichiers.
+upload-error-too-large = La taille du fichier ({ $size } Mo) dépasse le quota restant ({ $remaining } Mo). Quota total : { $total } Mo
+upload-error-too-large-unknown = Le fichier dépasse le quota restant ({ $remaining } Mo). Quota total : { $total } Mo
+upload-error-file-limit = La taille du fichier ({ $size } Mo) dépasse la limite de { $limit } Mo par fichier
+upload-error-file-limit-unknown = Le fichier dépasse la limite de { $limit } Mo par fichier
+upload-error-guest-quota = Vous avez atteint votre propre limite d'envoi sur ce lien : il reste { $remaining } sur { $quota }. Le fichier n'a pas été enregistré.
+upload-error-daily-quota = Ce lien accepte { $quota } par 24 heures et il ne reste que { $remaining } pour le moment. Veuillez réessayer plus tard ; le fichier n’a pas été enregistré.
+upload-error-file-type = Ce lien n'accepte pas les fichiers comme « { $name } ». Types de fichiers acceptés : { $types }. Le fichier n'a pas été enregistré.
+upload-error-file-content = Le contenu de « { $name } » ne correspond pas à son type de fichier. Le fichier n'a pas été enregistré.
+upload-error-duplicate = Un fichier nommé { $name } a déjà été envoyé sur ce lien. Veuillez renommer le fichier et réessayer.
+upload-error-same-content = Ce fichier a déjà été envoyé sous le nom « { $name } », il n’a donc pas été enregistré à nouveau.
+upload-error-rules = Ce fichier n'est pas accepté sur ce lien.
+upload-error-infected = « { $name } » a été refusé : l'antivirus y a trouvé un logiciel malveillant.
+upload-error-scan-failed = Le fichier n'a pas pu être analysé par l'antivirus et n'a donc pas été enregistré. Veuillez réessayer plus tard.
+
+## Public drop
+
+captcha-title = Dépôt public - NeedADrop
+captcha-prompt = 🤖 Veuillez résoudre ce calcul pour continuer :
+captcha-button = ✅ Continuer
+captcha-error = Mauvaise réponse, veuillez réessayer
+captcha-required = Veuillez rouvrir la page de dépôt public et répondre d'abord à la question
+
+## Guest share page
+
+share-title = { $name } - NeedADrop
+share-intro = Ces fichiers ont été partagés avec vous.
+share-expires = Disponible jusqu'au { $time }
+share-downloads-left = { $count ->
+    [one] Encore 1 téléchargement
+   *[other] Encore { $count } téléchargements
+}
+share-empty = Il n'y a (encore) aucun fichier ici.
+share-error-not-found = Lien de partage introuvable
+share-error-expired = Ce lien de partage a expiré ou ses téléchargements sont épuisés
+
+## Login page
+
+login-title = Connexion administrateur - NeedADrop
+login-heading = 🔐 NeedADrop Admin
+login-username = 👤 Nom d'utilisateur :
+login-password = 🔑 Mot de passe :
+login-button = 🚀 Se connecter
+login-js-logging-in = ⏳ Connexion...
+login-back-home = ⬅️ Retour à l'accueil
+login-error-invalid = Nom d'utilisateur ou mot de passe incorrect
+login-code-intro = Saisissez le code à 6 chiffres de votre application d'authentification.
+login-code = 🔢 Code :
+login-code-button = ✅ Vérifier
+login-error-code = Code incorrect, veuillez réessayer
+login-error-code-expired = Votre connexion a expiré, veuillez vous reconnecter
+login-error-code-attempts = Trop de codes incorrects, veuillez vous reconnecter
+
+## Dashboard
+
+dashboard-title = Tableau de bord - NeedADrop
+dashboard-heading = Tableau de bord
+dashboard-intro = Gérez votre service de dépôt de fichiers sécurisé depuis ce tableau de bord.
+dashboard-failed-tasks = ⚠️ { $count ->
+    [one] 1 tâche de fond a définitivement échoué (p. ex. réplication ou envoi WebDAV).
+   *[other] { $count } tâches de fond ont définitivement échoué (p. ex. réplication ou envoi WebDAV).
+}
+dashboard-review-failed-tasks = Voir les tâches en échec
+dashboard-pii-flagged = ⚠️ { $count ->
+    [one] 1 fichier contient probablement des données personnelles et attend une vérification.
+   *[other] { $count } fichiers contiennent probablement des données personnelles et attendent une vérification.
+}
+dashboard-review-pii = Vérifier les fichiers signalés
+dashboard-low-quota = ⚠️ { $count ->
+    [one] 1 lien a moins de { $percent } % de son quota restant :
+   *[other] { $count } liens ont moins de { $percent } % de leur quota restant :
+}
+dashboard-low-quota-item = { $remaining } restants sur { $total }
+dashboard-manage-links = Gérer les liens
+dashboard-links-heading = 🔗 Gérer les liens de dépôt
+dashboard-links-text = Créez, consultez et gérez des liens de dépôt sécurisés pour vos invités. Définissez des limites de taille et des dates d'expiration.
+dashboard-view-links = Voir les liens
+dashboard-create-link = Créer un lien
+dashboard-uploads-heading = 📁 Voir les dépôts
+dashboard-uploads-text = Parcourez tous les fichiers déposés, téléchargez-les ou supprimez ceux dont vous n'avez plus besoin.
+dashboard-view-uploads = Voir les dépôts
+dashboard-stats-heading = 📊 Statistiques
+dashboard-stats-text = Un aperçu de l'utilisation et de l'activité de votre service.
+dashboard-stats-active-links = Liens actifs :
+dashboard-stats-uploads = Nombre de dépôts :
+dashboard-stats-storage = Espace utilisé :
+dashboard-volumes-heading = 💽 Volumes de stockage
+dashboard-volumes-text = Fichiers stockés sur chaque disque et espace restant.
+dashboard-volume-stored = { $count ->
+    [one] 1 fichier, { $size }
+   *[other] { $count } fichiers, { $size }
+}
+dashboard-volume-free = { $free } libres sur { $total }
+dashboard-volume-unavailable = Espace libre inconnu (volume non monté ?)
+dashboard-projects-heading = 📁 Projets
+dashboard-projects-text = Regroupez les liens par client ou mission et suivez leur quota.
+dashboard-manage-projects = Gérer les projets
+dashboard-shares-heading = 📦 Liens de partage
+dashboard-shares-text = Remettre des fichiers à des invités par un lien de téléchargement.
+dashboard-manage-shares = Gérer les liens de partage
+
+dashboard-api-keys-heading = 🔑 Clés d'API
+dashboard-api-keys-text = Permettre aux scripts et aux jobs de CI d'utiliser l'API REST.
+dashboard-manage-api-keys = Gérer les clés d'API
+dashboard-admins-heading = 👤 Comptes admin
+dashboard-admins-text = Ajouter des admins et choisir ce que chacun peut faire.
+dashboard-manage-admins = Gérer les admins
+dashboard-webhooks-heading = 🪝 Webhooks
+dashboard-webhooks-text = Envoyer des événements signés à vos propres points de terminaison.
+dashboard-manage-webhooks = Gérer les webhooks
+dashboard-tasks-heading = 🧰 Tâches de fond
+dashboard-tasks-text = Suivez le travail en attente après les dépôts et relancez les tâches en échec définitif.
+dashboard-view-tasks = Voir les tâches
+dashboard-audit-heading = 🧾 Journal d'audit
+dashboard-audit-text = Consultez les conservations légales et les demandes des personnes concernées.
+dashboard-view-audit = Voir le journal d'audit
+dashboard-gdpr-heading = 🪪 Demandes de données
+dashboard-gdpr-text = Exportez ou effacez toutes les données conservées sur une personne (RGPD).
+dashboard-open-gdpr = Ouvrir les demandes de données
+dashboard-email-templates-heading = ✉️ Modèles d'e-mail
+dashboard-email-templates-text = Personnalisez l'objet et le texte des e-mails de notification.
+dashboard-edit-email-templates = Modifier les e-mails
+dashboard-account-heading = ⚙️ Paramètres du compte
+dashboard-account-text = Gérez les paramètres et la sécurité de votre compte administrateur.
+dashboard-change-password = Changer le mot de passe
+dashboard-preferences = Préférences d'affichage
+dashboard-two-factor = Authentification à deux facteurs
+dashboard-system-heading = ℹ️ Informations système
+dashboard-system-text = Informations sur votre installation de NeedADrop.
+dashboard-version = Version :
+dashboard-status = État :
+dashboard-status-running = En service
+
+## Upload links
+
+links-title = Liens de dépôt - NeedADrop Admin
+links-heading = Liens de dépôt
+links-create = Créer un lien
+links-empty = Aucun lien de dépôt pour l'instant.
+links-create-first = Créer votre premier lien
+links-col-name = Nom
+links-col-url = URL de dépôt
+links-col-quota = Quota
+links-col-expires = Expiration
+links-col-status = État
+links-col-created = Créé le
+links-col-actions = Actions
+links-bytes = octets
+links-quota-remaining = restants
+links-rate-limit = limité à { $kbps } Ko/s
+links-uploads-used = { $used } fichiers reçus sur { $max }
+links-daily-quota = { $quota } par 24 h
+links-per-file-limit = max. { $limit } par fichier
+links-guest-quota = { $quota } par invité
+links-file-types = Uniquement { $types }
+links-webdav-folder = copié vers WebDAV : { $folder }
+links-post-upload-command = Exécute : { $command }
+links-notify-on-upload = Prévient l'administrateur à chaque envoi
+links-guest-email = invité : { $email }
+links-language = langue : { $language }
+links-collision-policy = même nom de fichier : { $policy }
+links-accent-color = couleur d'accent : { $color }
+links-custom-logo = logo personnalisé
+links-public-drop = dépôt public (/drop)
+links-never = Jamais
+links-status-active = Actif
+links-status-inactive = Expiré/Inactif
+links-status-paused = En pause
+links-confirm-delete = Voulez-vous vraiment supprimer ce lien ?
+links-tab-current = Liens en cours
+links-tab-archived = 🗄️ Archivés ({ $count })
+links-archive-empty = Aucun lien archivé.
+links-select-all = Sélectionner tous les liens
+links-status-archived = Archivé
+links-archive = Archiver
+links-unarchive = Restaurer
+links-pause = Suspendre
+links-resume = Reprendre
+links-attempts = Tentatives
+links-stats = Statistiques
+links-copy-url = Copier
+links-url-copied = Copié
+links-select-finished = Sélectionner les liens terminés
+links-bulk-archive = 🗄️ Archiver la sélection
+links-bulk-unarchive = Restaurer la sélection
+links-error-has-uploads = Impossible de supprimer le lien : il contient encore des fichiers. Supprimez d'abord les fichiers.
+links-error-legal-hold = Impossible de supprimer le lien : il fait l'objet d'une conservation légale.
+
+## Refused uploads
+
+attempts-title = Envois refusés - NeedADrop Admin
+attempts-back = ← Liens d’envoi
+attempts-heading = Envois refusés : { $name }
+attempts-text = Envois refusés par ce lien au cours des 90 derniers jours, les plus récents d’abord.
+attempts-empty = Aucun envoi n’a été refusé.
+attempts-col-time = Heure
+attempts-col-file = Fichier
+attempts-col-reason = Motif
+attempts-col-ip = Adresse du client
+attempts-col-request = ID de requête
+attempt-reason-link-unavailable = Lien expiré, inactif ou plein
+attempt-reason-too-large = Plus grand que le quota restant
+attempt-reason-guest-quota = Limite propre à l’invité atteinte
+attempt-reason-daily-quota = Limite par 24 heures atteinte
+attempt-reason-file-type = Type de fichier non accepté
+attempt-reason-file-content = Contenu ne correspondant pas au type de fichier
+attempt-reason-duplicate = Un fichier portant ce nom existe déjà
+attempt-reason-same-content = Même fichier déjà envoyé sous le nom
+attempt-reason-refused = Refusé par une règle ou un plugin
+attempt-reason-interrupted = Envoi interrompu
+attempt-reason-infected = L'antivirus a trouvé
+
+## Link statistics
+
+link-stats-title = Statistiques du lien - NeedADrop Admin
+link-stats-heading = Statistiques : { $name }
+link-stats-uploads = Fichiers reçus
+link-stats-total-size = Taille totale
+link-stats-remaining = Quota restant sur { $total }
+link-stats-uploader-ips = Adresses clientes
+link-stats-refused = Envois refusés
+link-stats-last-activity = Dernière activité
+link-stats-daily-heading = Envois des 30 derniers jours (UTC)
+link-stats-daily-empty = Aucun fichier reçu ces 30 derniers jours.
+link-stats-day-uploads = { $count } fichiers
+
+## Create link
+
+create-link-title = Créer un lien de dépôt - NeedADrop Admin
+create-link-heading = Créer un lien de dépôt
+create-link-intro = Créez un nouveau lien sécurisé permettant à vos invités de déposer des fichiers.
+create-link-name = Nom du lien :
+create-link-name-placeholder = p. ex. Documents client, Fichiers projet
+create-link-name-help = Un nom descriptif pour ce lien (affiché aux invités)
+create-link-note = Note pour les invités
+create-link-note-placeholder = ex. Merci de déposer le contrat signé en PDF
+create-link-note-help = Affichée en haut de la page de dépôt. Facultatif.
+create-link-total-quota = Quota total (Mo) :
+create-link-total-quota-help = Taille totale de tous les fichiers acceptés par le lien (en mégaoctets)
+create-link-per-file-limit = Limite par fichier en Mo (facultatif)
+create-link-per-file-limit-placeholder = Seul le quota s'applique
+create-link-per-file-limit-help = Taille maximale d'un seul fichier déposé par un invité ; le quota total s'applique toujours
+create-link-max-uploads = Nombre de fichiers (facultatif)
+create-link-max-uploads-placeholder = Illimité
+create-link-max-uploads-help = Combien de fichiers le lien accepte au total, p. ex. 3 pour « envoyer exactement 3 documents ». Remplacer un fichier ne compte pas à nouveau.
+create-link-daily-quota = Limite par 24 heures en Mo (facultatif)
+create-link-daily-quota-placeholder = Illimitée
+create-link-daily-quota-help = Pour les liens de longue durée : combien peut arriver en 24 heures, afin que le lien ne soit pas épuisé d’un coup
+create-link-guest-quota = Limite par invité en Mo (facultatif)
+create-link-guest-quota-placeholder = Illimitée
+create-link-guest-quota-help = Pour les liens partagés avec beaucoup de personnes : ce qu'un invité peut envoyer, pour que personne n'épuise tout le quota
+create-link-file-types = Types de fichiers acceptés (facultatif)
+create-link-file-types-placeholder = Tous les types, p. ex. image/*, .pdf
+create-link-file-types-help = Extensions et types MIME séparés par des virgules ; image/* désigne toutes les images. Les fichiers sont vérifiés par leur nom et leur contenu
+create-link-expires = Expire dans (heures) :
+create-link-expires-placeholder = Laisser vide pour ne jamais expirer
+create-link-expires-help = Nombre d'heures avant l'expiration du lien (facultatif, 1 an maximum)
+create-link-retention = Supprimer les fichiers après (jours) :
+create-link-retention-placeholder = Conserver les fichiers jusqu'à leur suppression
+create-link-retention-help = Les fichiers sont supprimés ce nombre de jours après leur dépôt, sauf ceux sous conservation légale
+create-link-rate-limit = Limite de bande passante (Ko/s) :
+create-link-rate-limit-placeholder = Laisser vide pour illimité
+create-link-rate-limit-help = Vitesse maximale d'envoi et de téléchargement pour ce lien (facultatif)
+create-link-project = Projet :
+create-link-project-none = Aucun projet
+create-link-project-help = Classez le lien dans un projet pour le regrouper avec les autres liens du même client.
+create-link-collision = Même nom de fichier envoyé à nouveau
+create-link-collision-help = Ce qui se passe lorsqu'un invité envoie un fichier sous un nom déjà présent sur ce lien
+collision-policy-keep-both = Conserver les deux comme versions
+collision-policy-overwrite-latest = Remplacer la dernière version
+collision-policy-reject-duplicate = Refuser le nouveau fichier
+create-link-webdav = Dossier Nextcloud / WebDAV :
+create-link-webdav-placeholder = p. ex. Clients/Acme/Reçus
+create-link-webdav-help = Les fichiers reçus sont aussi copiés dans ce dossier sur le serveur WebDAV configuré (facultatif)
+create-link-command = Commande après envoi (facultatif)
+create-link-command-placeholder = /usr/local/bin/import-scan --archive
+create-link-command-help = S'exécute après chaque envoi ; le fichier est décrit dans les variables d'environnement NEEDADROP_*. La sortie est conservée sur la page des tâches de fond.
+create-link-notify = M'avertir par e-mail à l'arrivée d'un fichier
+create-link-notify-help = Envoie une notification à l'adresse de l'administrateur (ADMIN_EMAIL) pour chaque fichier reçu par ce lien.
+create-link-error-command = Cette commande n'est pas autorisée. Utilisez un programme de la liste configurée, avec son chemin complet.
+create-link-error-file-types = « { $entry } » n'est pas un type de fichier. Utilisez des extensions comme .pdf ou des types MIME comme image/*.
+create-link-guest-email = E-mail de l'invité :
+create-link-guest-email-placeholder = p. ex. client@example.com
+create-link-guest-email-help = L'invité reçoit un rappel par e-mail peu avant l'expiration si rien n'a encore été déposé (facultatif)
+create-link-language = Langue de la page de dépôt :
+create-link-language-auto = Automatique (langue du navigateur de l'invité)
+create-link-language-help = Afficher la page de dépôt dans cette langue, quels que soient les réglages du navigateur de l'invité
+create-link-logo = URL du logo :
+create-link-logo-placeholder = ex. https://example.com/logo.png
+create-link-logo-help = Affiché sur la page de dépôt à la place de l'icône par défaut. Une URL http(s) ou un chemin sur ce serveur comme /static/logo.png (facultatif)
+create-link-accent-color = Couleur d'accent :
+create-link-accent-color-help = Couleur de la page de dépôt au format #rrggbb, assortie à l'identité du client (facultatif)
+create-link-submit = Créer le lien
+create-link-tips = 💡 Conseils :
+create-link-tip-name = Choisissez un nom descriptif qui vous aide à identifier l'usage du lien
+create-link-tip-size = Fixez des limites de taille adaptées à la capacité du serveur et à l'usage prévu
+create-link-tip-expiry = Pensez à définir une expiration pour les dépôts temporaires
+create-link-tip-isolation = Chaque dépôt d'invité est stocké dans un dossier séparé et isolé
+create-link-error-form = Données de formulaire invalides. Vérifiez que la durée d'expiration est un nombre valide.
+create-link-error-email = Veuillez saisir une adresse e-mail valide pour l'invité.
+create-link-error-logo = Le logo doit être une URL http(s) ou un chemin commençant par /.
+create-link-error-note = La note pour les invités est trop longue ({ $limit } caractères au maximum).
+create-link-error-color = La couleur d'accent doit être au format #rrggbb, ex. #0a7cff.
+create-link-error-project = Le projet sélectionné n'existe plus.
+create-link-error-project-quota = Le quota dépasse ce qui reste du projet « { $project } » ({ $available }).
+create-link-error-failed = Impossible de créer le lien de dépôt
+
+## Edit link
+
+edit-link-title = Modifier le lien de dépôt - NeedADrop Admin
+edit-link-heading = Modifier le lien de dépôt
+edit-link-intro = Modifiez les réglages de « { $name } ». L'adresse d'envoi reste la même.
+edit-link-quota-help = Taille totale des fichiers acceptés par le lien (en mégaoctets). { $used } ont déjà été envoyés : le quota ne peut pas descendre en dessous.
+edit-link-expires = Nouvelle expiration en heures :
+edit-link-expires-placeholder = Laisser vide pour garder l'expiration actuelle
+edit-link-expires-current = Expire actuellement le { $date }.
+edit-link-expires-never = N'expire actuellement jamais.
+edit-link-expires-help = Indiquez le nombre d'heures à partir de maintenant avant l'expiration, ou 0 pour ne jamais expirer.
+edit-link-active = Le lien est actif
+edit-link-active-help = Un lien inactif refuse les envois mais conserve ses fichiers.
+edit-link-submit = Enregistrer les modifications
+edit-link-error-name = Veuillez saisir un nom pour le lien.
+edit-link-error-quota = Le quota ne peut pas être inférieur à ce qui a déjà été envoyé ({ $used }).
+edit-link-error-public = Le lien du dépôt public se configure via les réglages PUBLIC_DROP_* et ne peut pas être modifié ici.
+edit-link-error-failed = Impossible d'enregistrer le lien de dépôt
+
+## Projects
+
+projects-title = Projets - NeedADrop Admin
+projects-heading = 📁 Projets
+projects-intro = Les projets regroupent les liens de dépôt par client ou mission. Le quota d'un projet plafonne le quota total de ses liens.
+projects-name = Nom
+projects-quota = Quota (Mo)
+projects-quota-placeholder = Illimité
+projects-create = Créer le projet
+projects-empty = Aucun projet pour l'instant.
+projects-col-name = Projet
+projects-col-links = Liens
+projects-col-quota = Quota
+projects-col-uploads = Fichiers
+projects-col-actions = Actions
+projects-quota-of = { $allocated } sur { $quota } attribués
+projects-quota-unlimited = { $allocated } attribués (illimité)
+projects-upload-count = { $count ->
+    [one] 1 fichier
+   *[other] { $count } fichiers
+}
+projects-view-links = Liens
+projects-view-uploads = Fichiers
+projects-confirm-delete = Supprimer ce projet ? Ses liens et fichiers sont conservés.
+projects-error-form = Veuillez saisir un nom de projet.
+projects-error-exists = Un projet portant ce nom existe déjà.
+projects-filter = 📁 Projet :
+projects-filter-all = Tous les projets
+projects-filter-apply = Filtrer
+
+## Share links
+
+shares-title = Liens de partage - NeedADrop Admin
+shares-heading = 📦 Liens de partage
+shares-intro = Les liens de partage permettent aux invités de télécharger des fichiers choisis parmi les envois ou que vous envoyez vous-même. Un lien peut expirer et limiter le nombre de téléchargements.
+shares-sharing = Partage de « { $name } » : créez un nouveau lien de partage ou ajoutez le fichier à un lien existant.
+shares-name = Nom
+shares-expires = Expire dans (heures)
+shares-expires-placeholder = Jamais
+shares-max-downloads = Téléchargements max.
+shares-max-downloads-placeholder = Illimité
+shares-create = Créer le lien de partage
+shares-empty = Aucun lien de partage pour l'instant.
+shares-col-name = Lien de partage
+shares-col-url = URL
+shares-col-expires = Expire
+shares-col-downloads = Téléchargements
+shares-col-files = Fichiers
+shares-col-actions = Actions
+shares-status-expired = Expiré ou épuisé
+shares-file-count = { $count ->
+    [one] 1 fichier
+   *[other] { $count } fichiers
+}
+shares-downloads = { $count } téléchargements
+shares-downloads-of = { $count } sur { $max } téléchargements
+shares-add-here = Ajouter ici
+shares-manage = Fichiers
+shares-confirm-delete = Supprimer ce lien de partage ? Les fichiers que vous y avez envoyés sont supprimés aussi ; les envois partagés sont conservés.
+shares-error-form = Veuillez saisir un nom pour le lien de partage.
+share-admin-title = { $name } - Lien de partage - NeedADrop Admin
+share-admin-upload = Envoyer des fichiers à partager
+share-admin-upload-button = Envoyer
+share-admin-upload-help = Pour partager un fichier envoyé par un invité, utilisez le bouton « Partager » de la page des envois.
+share-admin-empty = Ce lien de partage ne contient encore aucun fichier.
+share-admin-col-file = Fichier
+share-admin-col-size = Taille
+share-admin-col-source = Origine
+share-admin-col-added = Ajouté
+share-admin-source-upload = Envoi
+share-admin-source-admin = Envoyé par un admin
+share-admin-remove = Retirer
+share-admin-confirm-remove = Retirer ce fichier du lien de partage ?
+share-admin-back = Tous les liens de partage
+share-error-upload = Impossible d'enregistrer le fichier. Veuillez réessayer.
+
+## API keys
+
+api-keys-title = Clés d'API - NeedADrop Admin
+api-keys-heading = 🔑 Clés d'API
+api-keys-intro = Les clés d'API authentifient les requêtes vers l'API REST sous /api/v1. Envoyez une clé sous la forme « Authorization: Bearer <clé> » ou dans l'en-tête X-Api-Key.
+api-keys-created = Clé créée. Copiez-la maintenant ; elle ne sera plus affichée.
+api-keys-name = Nom
+api-keys-name-placeholder = p. ex. pipeline CI
+api-keys-create = Créer une clé d'API
+api-keys-empty = Aucune clé d'API pour l'instant.
+api-keys-col-name = Nom
+api-keys-col-key = Clé
+api-keys-col-created = Créée le
+api-keys-col-last-used = Dernière utilisation
+api-keys-col-actions = Actions
+api-keys-never-used = Jamais
+api-keys-revoke = Révoquer
+api-keys-confirm-revoke = Révoquer cette clé d'API ? Les scripts qui l'utilisent cessent immédiatement de fonctionner.
+api-keys-error-form = Veuillez saisir un nom pour la clé d'API.
+
+## Admin accounts
+
+admins-title = Comptes admin - NeedADrop Admin
+admins-heading = 👤 Comptes admin
+admins-intro = Toutes les personnes pouvant se connecter à l'interface d'administration. Seuls les propriétaires voient cette page.
+admins-username = Nom d'utilisateur
+admins-password = Mot de passe initial
+admins-role = Rôle
+admins-create = Ajouter un admin
+admins-roles-help = Les propriétaires peuvent tout faire, y compris gérer les admins, lever les conservations légales et effacer des données personnelles. Les gestionnaires s'occupent des liens, des fichiers, des partages, des clés et des réglages. Les auditeurs peuvent tout consulter et télécharger, mais ne rien modifier.
+admins-col-username = Nom d'utilisateur
+admins-col-role = Rôle
+admins-col-created = Créé le
+admins-col-actions = Actions
+admins-you = (vous)
+admins-save-role = Enregistrer
+admins-confirm-delete = Supprimer ce compte admin ? La personne est déconnectée immédiatement.
+admins-col-two-factor = Deux facteurs
+admins-two-factor-on = Activée
+admins-two-factor-off = Désactivée
+admins-reset-two-factor = Réinitialiser
+admins-confirm-reset-two-factor = Désactiver l'authentification à deux facteurs pour ce compte ? La connexion se fera avec le seul mot de passe jusqu'à une nouvelle configuration.
+admins-error-form = Veuillez saisir un nom d'utilisateur et choisir un rôle.
+admins-error-taken = Il existe déjà un admin nommé « { $username } ».
+admins-error-last-owner = Il doit toujours y avoir au moins un propriétaire.
+admins-error-self = Vous ne pouvez pas supprimer votre propre compte.
+admin-role-owner = Propriétaire
+admin-role-manager = Gestionnaire
+admin-role-auditor = Auditeur (lecture seule)
+
+## Webhooks
+
+webhooks-title = Webhooks - NeedADrop Admin
+webhooks-heading = 🪝 Webhooks
+webhooks-intro = Les événements sont envoyés en JSON (POST) à chaque point de terminaison, signés avec son secret dans l'en-tête X-NeedADrop-Signature (sha256=HMAC du corps). Les livraisons échouées sont réessayées en arrière-plan.
+webhooks-url = URL du point de terminaison
+webhooks-events = Événements
+webhooks-events-placeholder = Tous les événements
+webhooks-secret = Secret
+webhooks-secret-placeholder = Généré
+webhooks-create = Ajouter un webhook
+webhooks-available-events = Événements disponibles :
+webhooks-empty = Aucun webhook pour l'instant.
+webhooks-col-url = Point de terminaison
+webhooks-col-events = Événements
+webhooks-col-secret = Secret
+webhooks-col-created = Ajouté le
+webhooks-col-actions = Actions
+webhooks-all-events = Tous les événements
+webhooks-confirm-delete = Supprimer ce webhook ? Les livraisons encore en attente sont abandonnées.
+webhooks-error-url = Veuillez saisir une URL http:// ou https://.
+webhooks-error-event = « { $event } » n'est pas un événement. Choisissez parmi les événements listés sous le formulaire.
+
+## Uploads
+
+uploads-title = Dépôts - NeedADrop Admin
+uploads-heading = Fichiers déposés
+uploads-total = Nombre de dépôts
+uploads-total-size = Taille totale
+uploads-empty = Aucun fichier n'a encore été déposé.
+uploads-show-archived = 🗄️ Afficher les fichiers des liens archivés
+uploads-hide-archived = ⬅️ Retour aux fichiers des liens en cours
+uploads-show-starred = ⭐ Favoris uniquement
+uploads-show-all = Tous les fichiers
+uploads-show-flagged = ⚠️ Données personnelles à vérifier
+uploads-star = Ajouter aux favoris
+uploads-unstar = Retirer des favoris
+uploads-pii-badge = Données personnelles : { $kinds }
+uploads-pii-hint = L'analyse a trouvé ce qui ressemble à des données personnelles ; vérifiez le fichier puis marquez-le comme vérifié
+uploads-pii-reviewed = Vérifié : { $kinds }
+uploads-scan-clean-hint = Analysé par l'antivirus, rien trouvé
+uploads-pii-mark-reviewed = Marquer comme vérifié
+uploads-share = Partager
+pii-kind-ssn = numéro de sécurité sociale
+pii-kind-iban = IBAN
+pii-kind-credit-card = numéro de carte
+uploads-create-link = Créer un lien de dépôt
+uploads-link-created = Créé le : { $date }
+uploads-link-total-quota = Quota : { $size }
+uploads-link-token = Jeton : { $token }
+uploads-download-all = 📦 Tout télécharger (ZIP)
+uploads-link-expires = Expire le : { $date }
+uploads-link-no-expiry = Pas d'expiration
+uploads-link-retention = { $count ->
+    [one] Fichiers conservés 1 jour
+   *[other] Fichiers conservés { $count } jours
+}
+uploads-file-count = { $count ->
+    [one] 1 fichier déposé
+   *[other] { $count } fichiers déposés
+}
+uploads-col-name = Nom du fichier
+uploads-col-size = Taille
+uploads-col-type = Type
+uploads-col-uploaded = Déposé le
+uploads-col-actions = Actions
+uploads-mirror-status = Miroir : { $status }
+uploads-detected-mime = Le contenu ressemble à { $type }
+uploads-uploader-name = Envoyé par { $name }
+uploads-has-message = Envoyé avec un message
+uploads-message-sent = Message envoyé le { $date }
+uploads-message-files = { $count ->
+    [one] Envoyé avec 1 fichier :
+   *[other] Envoyé avec { $count } fichiers :
+}
+uploads-checksum-none = Pas encore de somme de contrôle
+uploads-integrity-ok = ✔ Intact ({ $date })
+uploads-integrity-failed = ✖ Absent ou modifié sur le disque ({ $date })
+uploads-verify = Vérifier
+uploads-verify-hint = Relire le fichier sur le disque et le comparer à la somme de contrôle calculée au dépôt
+uploads-deleted-in = { $count ->
+    [one] Supprimé dans 1 jour
+   *[other] Supprimé dans { $count } jours
+}
+uploads-deletion-due = Supprimé lors du prochain nettoyage
+uploads-confirm-delete = Voulez-vous vraiment supprimer ce fichier ?
+uploads-version = v{ $version }
+uploads-version-history = { $count ->
+    [one] 1 version précédente
+   *[other] { $count } versions précédentes
+}
+uploads-select-all = Sélectionner tous les fichiers de ce lien
+uploads-bulk-download = ⬇️ Télécharger la sélection en ZIP
+uploads-bulk-delete = Supprimer la sélection
+uploads-bulk-confirm-delete = Supprimer les fichiers sélectionnés ? Si l’un d’eux est sous conservation légale, aucun n’est supprimé.
+uploads-confirm-delete-version = Voulez-vous vraiment supprimer cette version ?
+legal-hold-badge = Conservation légale
+legal-hold-hint = Sous conservation légale : suppression impossible tant qu'un propriétaire ne l'a pas levée
+legal-hold-place = Conserver
+legal-hold-release = Lever la conservation
+legal-hold-confirm-place = Placer ce fichier sous conservation légale ? Seul un propriétaire pourra la lever.
+legal-hold-confirm-place-link = Placer tous les fichiers de ce lien sous conservation légale ? Seul un propriétaire pourra la lever.
+legal-hold-confirm-release = Lever la conservation légale ? Les fichiers pourront de nouveau être supprimés.
+
+## Upload preview
+preview-title = { $name } - Aperçu - NeedADrop Admin
+preview-back = ← Dépôts
+preview-unavailable = Ce type de fichier ne peut pas être affiché dans le navigateur. Téléchargez-le pour l’ouvrir.
+preview-truncated = Seul le début du fichier est affiché. Téléchargez-le pour le voir en entier.
+
+## Search
+
+search-title = Recherche - NeedADrop Admin
+search-heading = 🔍 Recherche
+search-placeholder = Rechercher fichiers et liens…
+search-button = Rechercher
+search-no-results = Aucun résultat pour « { $query } ».
+search-links-heading = { $count ->
+    [one] 1 lien
+   *[other] { $count } liens
+}
+search-uploads-heading = { $count ->
+    [one] 1 fichier
+   *[other] { $count } fichiers
+}
+search-col-link = Lien
+
+## Background tasks
+
+tasks-title = Tâches de fond - NeedADrop Admin
+tasks-heading = Tâches de fond
+tasks-pending = En attente
+tasks-running = En cours
+tasks-failed = En échec (dead-letter)
+tasks-dead-letter-heading = File des tâches en échec
+tasks-empty = Aucune tâche en échec. Tout fonctionne normalement.
+tasks-col-type = Type
+tasks-col-details = Détails
+tasks-col-attempts = Tentatives
+tasks-col-last-error = Dernière erreur
+tasks-col-last-attempt = Dernière tentative
+tasks-col-actions = Actions
+tasks-confirm-discard = Abandonner cette tâche ? Elle ne sera pas relancée.
+tasks-commands-heading = Commandes après envoi
+tasks-col-status = Statut
+tasks-col-output = Sortie
+tasks-command-output = Afficher la sortie
+tasks-command-failed = Échec, afficher la sortie
+
+## Audit log
+
+audit-title = Journal d'audit - NeedADrop Admin
+audit-heading = Journal d'audit
+audit-text = Modifications des conservations légales, demandes des personnes concernées et vérifications de données personnelles, les plus récentes d'abord.
+audit-empty = Aucune entrée pour le moment.
+audit-col-time = Date
+audit-col-admin = Admin
+audit-col-action = Action
+audit-col-target = Concerné
+audit-action-legal-hold-placed = Conservation légale placée
+audit-action-legal-hold-released = Conservation légale levée
+audit-action-gdpr-exported = Données personnelles exportées
+audit-action-gdpr-erased = Données personnelles effacées
+audit-action-pii-reviewed = Données personnelles vérifiées
+audit-action-retention-deleted = Fichiers d'un lien expiré supprimés
+audit-target-upload = Fichier
+audit-target-link = Lien
+audit-target-report = Rapport
+
+## Data subject requests
+
+gdpr-title = Demandes de données - NeedADrop Admin
+gdpr-heading = Demandes des personnes concernées
+gdpr-intro = Retrouvez tout ce qui est conservé sur une personne : ses fichiers et leurs détails, l'e-mail invité des liens créés pour elle et les entrées du journal d'audit sur ses fichiers. L'export télécharge un rapport ; l'effacement supprime les données et télécharge un rapport de ce qui a été supprimé. Les fichiers sous conservation légale sont conservés.
+gdpr-unsigned-hint = Les rapports ne sont pas signés. Définissez GDPR_REPORT_KEY pour les signer.
+gdpr-subject = Personne
+gdpr-subject-placeholder = invite@example.com, 203.0.113.7 ou un identifiant de dossier invité
+gdpr-subject-help = Adresse e-mail utilisée pour l'envoi ou pour laquelle le lien a été créé, adresse d'envoi ou dossier invité.
+gdpr-export = Exporter les données
+gdpr-erase = Effacer les données
+gdpr-confirm-erase = Supprimer définitivement toutes les données de cette personne ? Cette action est irréversible.
+gdpr-erase-owner-only = Seuls les propriétaires peuvent effacer des données.
+gdpr-error-subject = Saisissez une adresse e-mail, une adresse client ou un dossier invité.
+gdpr-error-not-owner = Seuls les propriétaires peuvent effacer des données.
+gdpr-verify-heading = Vérifier un rapport
+gdpr-verify-report = Contenu du fichier de rapport
+gdpr-verify = Vérifier la signature
+gdpr-verify-valid = Le rapport est intact : sa signature est valide.
+gdpr-verify-invalid = La signature ne correspond pas : le rapport a été modifié ou signé avec une autre clé.
+gdpr-verify-unsigned = Le rapport n'est pas signé.
+gdpr-verify-no-key = Impossible de vérifier les rapports : GDPR_REPORT_KEY n'est pas défini.
+gdpr-verify-malformed = Ce n'est pas un fichier de rapport.
+
+## Change password
+
+password-title = Changer le mot de passe - NeedADrop Admin
+password-heading = Changer le mot de passe
+password-intro = Modifiez le mot de passe de votre compte administrateur pour plus de sécurité.
+password-current = Mot de passe actuel :
+password-current-help = Saisissez votre mot de passe actuel pour confirmer votre identité
+password-new = Nouveau mot de passe :
+password-new-help = Choisissez un mot de passe robuste d'au moins 6 caractères
+password-confirm = Confirmer le nouveau mot de passe :
+password-confirm-help = Saisissez à nouveau votre nouveau mot de passe
+password-submit = Changer le mot de passe
+password-tips = 🔒 Conseils de sécurité :
+password-tip-unique = Utilisez un mot de passe robuste et unique, que vous n'utilisez nulle part ailleurs
+password-tip-manager = Utilisez un gestionnaire de mots de passe pour générer et conserver des mots de passe sûrs
+password-tip-hashed = Votre mot de passe est haché de manière sécurisée avant d'être enregistré
+password-tip-session = Après le changement, vous restez connecté à votre session actuelle
+password-success = Mot de passe modifié avec succès !
+password-error-mismatch = Les nouveaux mots de passe ne correspondent pas
+password-error-too-short = Le mot de passe doit contenir au moins 6 caractères
+password-error-not-found = Administrateur introuvable
+password-error-database = Erreur de base de données
+password-error-incorrect = Le mot de passe actuel est incorrect
+password-error-hash = Impossible de hacher le nouveau mot de passe
+password-error-update = Impossible d'enregistrer le mot de passe dans la base de données
+
+## Display preferences
+
+preferences-title = Préférences d'affichage - NeedADrop Admin
+preferences-heading = Préférences d'affichage
+preferences-intro = Choisissez comment les dates et heures vous sont affichées dans l'administration.
+preferences-timezone = Fuseau horaire :
+preferences-timezone-default = Valeur du serveur ({ $timezone })
+preferences-timezone-help = Nom de fuseau IANA, p. ex. Europe/Paris. Laisser vide pour utiliser la valeur du serveur.
+preferences-preview = Heure actuelle : { $time }
+preferences-submit = Enregistrer
+preferences-saved = Préférences enregistrées.
+preferences-error-timezone = Fuseau horaire inconnu « { $timezone } ».
+preferences-error-save = Impossible d'enregistrer les préférences
+
+## Two-factor authentication
+
+two-factor-title = Authentification à deux facteurs - NeedADrop Admin
+two-factor-heading = Authentification à deux facteurs
+two-factor-intro = Demander, en plus du mot de passe, un code d'une application d'authentification à la connexion.
+two-factor-status-on = ✅ L'authentification à deux facteurs est activée.
+two-factor-status-off = L'authentification à deux facteurs est désactivée.
+two-factor-setup-scan = Scannez ce code QR avec une application d'authentification comme Aegis, Google Authenticator ou 1Password.
+two-factor-setup-manual = Ou saisissez cette clé manuellement :
+two-factor-code-help = Saisissez le code affiché par l'application pour confirmer la configuration
+two-factor-enable = Activer
+two-factor-disable = Désactiver
+two-factor-disable-help = Saisissez votre mot de passe pour désactiver l'authentification à deux facteurs
+two-factor-enabled = L'authentification à deux facteurs est maintenant activée.
+two-factor-disabled = L'authentification à deux facteurs est maintenant désactivée.
+two-factor-error-code = Le code ne correspond pas. Vérifiez l'heure de votre téléphone et réessayez.
+two-factor-error-save = Impossible d'enregistrer les paramètres à deux facteurs
+
+## Email templates
+
+email-templates-title = Modèles d'e-mail - NeedADrop Admin
+email-templates-heading = Modèles d'e-mail
+email-templates-intro = Les e-mails de notification utilisent ces textes. Les variables comme {"{{"}link_name{"}}"} sont remplacées à l'envoi.
+email-templates-col-email = E-mail
+email-templates-col-status = Statut
+email-templates-col-actions = Actions
+email-templates-customized = Personnalisé le { $date }
+email-templates-default = Texte par défaut
+email-templates-edit = Modifier
+email-template-edit-title = Modifier le modèle d'e-mail - NeedADrop Admin
+email-template-expiry-reminder-admin = Rappel d'expiration (admin)
+email-template-expiry-reminder-admin-description = Envoyé aux administrateurs lorsqu'un lien inutilisé va bientôt expirer.
+email-template-expiry-reminder-guest = Rappel d'expiration (invité)
+email-template-expiry-reminder-guest-description = Envoyé à l'invité lorsque son lien inutilisé va bientôt expirer.
+email-template-upload-received = Fichier reçu (admin)
+email-template-upload-received-description = Envoyé aux administrateurs pour chaque fichier envoyé via un lien dont les notifications sont activées.
+email-template-subject = Objet :
+email-template-body = Texte :
+email-template-variables = Variables disponibles :
+email-template-save = Enregistrer le modèle
+email-template-preview = Aperçu
+email-template-reset = Rétablir le texte par défaut
+email-template-confirm-reset = Abandonner vos modifications et rétablir le texte par défaut ?
+email-template-preview-heading = Aperçu
+email-template-preview-help = Affiché avec des valeurs d'exemple.
+email-template-saved = Modèle d'e-mail enregistré.
+email-template-error-empty = L'objet et le texte ne doivent pas être vides.
+email-template-error-save = Échec de l'enregistrement du modèle d'e-mail
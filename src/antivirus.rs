@@ -0,0 +1,235 @@
+//! # Virus Scanning
+//!
+//! Optionally passes every received file through ClamAV before it is
+//! accepted. The file is scanned once it is complete on disk and before the
+//! upload is recorded, so an infected file never shows up in the uploads
+//! list: it is deleted, the guest is told, and the refusal is kept on the
+//! link's attempts page with the name of the signature that matched.
+//!
+//! A file that can't be scanned (scanner down, timeout, file over clamd's
+//! `StreamMaxLength`) is refused as well; turning scanning on means nothing
+//! unscanned gets in. Accepted files are marked with their verdict in the
+//! `scan_result` column.
+//!
+//! ## Configuration
+//! - `VIRUS_SCAN`: `off` (default), `clamd` or `clamscan`
+//! - `CLAMD_ADDRESS`: clamd's socket path or `host:port` (default: `/run/clamav/clamd.ctl`)
+//! - `CLAMSCAN_PATH`: clamscan program for `VIRUS_SCAN=clamscan` (default: `clamscan`)
+//! - `VIRUS_SCAN_TIMEOUT_SECS`: How long one scan may take (default: `120`)
+//!
+//! With `clamd` the file is streamed over the socket (`INSTREAM`), so clamd
+//! needs no access to the upload directory. `clamscan` loads the signature
+//! database for every file and is only practical for small installations.
+
+use std::{path::Path, process::Stdio, time::Duration};
+use tokio::{
+    fs,
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+    process::Command,
+};
+
+use crate::config::Config;
+
+/// `scan_result` of a file the scanner found nothing in
+pub const RESULT_CLEAN: &str = "clean";
+
+/// Size of the chunks a file is streamed to clamd in
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Verdict of the scanner on one file
+#[derive(Debug)]
+pub enum ScanOutcome {
+    Clean,
+
+    /// Holds the name of the signature that matched, e.g. `Eicar-Test-Signature`
+    Infected(String),
+}
+
+/// Whether received files are scanned
+pub fn is_enabled(config: &Config) -> bool {
+    config.virus_scan != "off"
+}
+
+/// Scan a file with the configured scanner
+///
+/// # Returns
+/// The verdict, or why the file could not be scanned
+pub async fn scan_file(config: &Config, path: &Path) -> Result<ScanOutcome, String> {
+    let timeout = Duration::from_secs(config.virus_scan_timeout_secs.max(1));
+
+    let scan = async {
+        match config.virus_scan.as_str() {
+            "clamd" => scan_with_clamd(&config.clamd_address, path).await,
+            "clamscan" => scan_with_clamscan(&config.clamscan_path, path).await,
+            other => Err(format!("Unknown VIRUS_SCAN mode: {}", other)),
+        }
+    };
+
+    tokio::time::timeout(timeout, scan)
+        .await
+        .map_err(|_| format!("Virus scan timed out after {}s", timeout.as_secs()))?
+}
+
+/// Stream a file to clamd and interpret its answer
+async fn scan_with_clamd(address: &str, path: &Path) -> Result<ScanOutcome, String> {
+    let reply = if address.starts_with('/') {
+        scan_over_socket(address, path).await?
+    } else {
+        let stream = TcpStream::connect(address)
+            .await
+            .map_err(|e| format!("Failed to connect to clamd at {}: {}", address, e))?;
+        instream(stream, path).await?
+    };
+
+    // `stream: OK` or `stream: <signature> FOUND`; anything else is an error
+    let verdict = reply.strip_prefix("stream:").unwrap_or(&reply).trim();
+    if verdict == "OK" {
+        Ok(ScanOutcome::Clean)
+    } else if let Some(signature) = verdict.strip_suffix(" FOUND") {
+        Ok(ScanOutcome::Infected(signature.trim().to_string()))
+    } else {
+        Err(format!("clamd answered: {}", reply))
+    }
+}
+
+/// Stream a file to clamd over its Unix socket
+#[cfg(unix)]
+async fn scan_over_socket(address: &str, path: &Path) -> Result<String, String> {
+    let stream = tokio::net::UnixStream::connect(address)
+        .await
+        .map_err(|e| format!("Failed to connect to clamd at {}: {}", address, e))?;
+    instream(stream, path).await
+}
+
+#[cfg(not(unix))]
+async fn scan_over_socket(_address: &str, _path: &Path) -> Result<String, String> {
+    Err("clamd sockets need a Unix system; use host:port".to_string())
+}
+
+/// Send a file with clamd's `INSTREAM` command and return the reply
+///
+/// The file goes in chunks, each prefixed with its length as a 4-byte
+/// big-endian number; a zero length ends the stream.
+async fn instream<S>(mut stream: S, path: &Path) -> Result<String, String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut file = fs::File::open(path)
+        .await
+        .map_err(|e| format!("Failed to open file for scanning: {}", e))?;
+    let clamd_error = |e: std::io::Error| format!("clamd connection failed: {}", e);
+
+    stream
+        .write_all(b"zINSTREAM\0")
+        .await
+        .map_err(clamd_error)?;
+
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .await
+            .map_err(|e| format!("Failed to read file for scanning: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        stream
+            .write_all(&(read as u32).to_be_bytes())
+            .await
+            .map_err(clamd_error)?;
+        stream
+            .write_all(&buffer[..read])
+            .await
+            .map_err(clamd_error)?;
+    }
+    stream.write_all(&[0u8; 4]).await.map_err(clamd_error)?;
+    stream.flush().await.map_err(clamd_error)?;
+
+    // clamd closes the connection after answering a `z` command
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply).await.map_err(clamd_error)?;
+
+    Ok(String::from_utf8_lossy(&reply)
+        .trim_end_matches('\0')
+        .trim()
+        .to_string())
+}
+
+/// Run clamscan on a file; exit code 1 means a signature matched
+async fn scan_with_clamscan(program: &str, path: &Path) -> Result<ScanOutcome, String> {
+    let output = Command::new(program)
+        .arg("--no-summary")
+        .arg("--infected")
+        .arg("--")
+        .arg(path)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+    match output.status.code() {
+        Some(0) => Ok(ScanOutcome::Clean),
+        Some(1) => {
+            // `<path>: <signature> FOUND`
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let signature = stdout
+                .lines()
+                .find_map(|line| line.strip_suffix(" FOUND"))
+                .and_then(|line| line.rsplit(": ").next())
+                .unwrap_or("unknown")
+                .to_string();
+            Ok(ScanOutcome::Infected(signature))
+        }
+        _ => Err(format!(
+            "clamscan failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{in_bounded_memory, sparse_file, GIB};
+    use tokio::io::{duplex, DuplexStream};
+
+    /// A clamd answering `INSTREAM` with `OK`, returning how many bytes it was sent
+    fn fake_clamd(mut stream: DuplexStream) -> tokio::task::JoinHandle<u64> {
+        tokio::spawn(async move {
+            let mut command = [0u8; 10];
+            stream.read_exact(&mut command).await.unwrap();
+            assert_eq!(&command, b"zINSTREAM\0");
+
+            let mut received = 0u64;
+            let mut chunk = vec![0u8; CHUNK_SIZE];
+            loop {
+                let len = stream.read_u32().await.unwrap() as usize;
+                if len == 0 {
+                    break;
+                }
+                stream.read_exact(&mut chunk[..len]).await.unwrap();
+                received += len as u64;
+            }
+            stream.write_all(b"stream: OK\0").await.unwrap();
+            stream.shutdown().await.unwrap();
+            received
+        })
+    }
+
+    #[tokio::test]
+    #[ignore = "streams 5 GiB; run with `cargo test -- --ignored`"]
+    async fn scanning_a_file_larger_than_memory_streams_it_in_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = sparse_file(dir.path(), 5 * GIB);
+        let (client, server) = duplex(CHUNK_SIZE);
+        let clamd = fake_clamd(server);
+
+        let reply = in_bounded_memory(instream(client, &source)).await.unwrap();
+
+        assert_eq!(reply, "stream: OK");
+        assert_eq!(clamd.await.unwrap(), 5 * GIB);
+    }
+}
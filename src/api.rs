@@ -0,0 +1,699 @@
+//! # REST API
+//!
+//! JSON endpoints under `/api/v1` so scripts and CI jobs can create links,
+//! see what arrived and move files without going through the admin pages.
+//!
+//! ## Authentication
+//! Admins create API keys on the API keys page (`/admin/api-keys`). Send the
+//! key as `Authorization: Bearer <key>` or `X-Api-Key: <key>`. Only a
+//! SHA-256 hash of each key is stored, so a lost key can't be shown again;
+//! revoke it and create a new one. When bearer tokens are enabled, a signed
+//! JWT in `Authorization: Bearer` works as well (see [`crate::jwt`]).
+//!
+//! ## Endpoints
+//! - `GET /api/v1/links` - All upload links
+//! - `POST /api/v1/links` - Create a link
+//!   (`{"name", "quota_mb", "per_file_limit_mb", "expires_in_hours", "max_uploads", "guest_email", "allowed_file_types", "rate_limit_kbps", "notify_on_upload"}`)
+//! - `GET /api/v1/links/{id}` - One link
+//! - `PATCH /api/v1/links/{id}` - Rename, re-date (`expires_in_hours`, 0 = never) or (de)activate a link
+//! - `DELETE /api/v1/links/{id}` - Delete a link without uploads
+//! - `GET /api/v1/links/{id}/uploads` - Files received on a link, newest first
+//! - `POST /api/v1/links/{id}/uploads?filename=<name>` - Upload a file; the request body is its contents
+//! - `GET /api/v1/uploads?limit=<n>` - Most recent uploads across all links, newest first
+//! - `GET /api/v1/uploads/{id}` - One upload
+//! - `GET /api/v1/uploads/{id}/download` - File contents
+//!
+//! Links and uploads are returned with the same fields as stored, plus the
+//! guest `upload_url` of a link and the `download_url` of an upload. Errors
+//! are `{"error": "..."}` with a matching status code.
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+    Extension,
+};
+use chrono::{Duration, Utc};
+use futures::StreamExt;
+use ring::{
+    digest,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+use tokio_util::io::StreamReader;
+use tracing::{error, info, warn};
+
+use crate::{
+    database::*,
+    encryption, events,
+    filetypes::AllowedTypes,
+    handlers::attachment_response,
+    ingest::{store_upload, ClientIp, IngestError},
+    jwt::{self, Claims},
+    models::*,
+    request_id::RequestId,
+    resumable::error_response,
+    throttle::TokenBucket,
+    AppState,
+};
+
+/// Characters of a key kept in the clear, to recognize it in the admin list
+const KEY_PREFIX_LEN: usize = 12;
+
+/// Uploads returned by the upload list unless the client asks for fewer
+const DEFAULT_LIST_LIMIT: i64 = 100;
+
+/// Upper bound for the number of uploads a client may request
+const MAX_LIST_LIMIT: i64 = 1000;
+
+/// A new random API key: `nad_` followed by 32 random bytes in hex
+pub fn generate_key() -> String {
+    let mut bytes = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("system random number generator failed");
+    format!("nad_{}", to_hex(&bytes))
+}
+
+/// The hash a key is stored and looked up by
+pub fn hash_key(key: &str) -> String {
+    to_hex(digest::digest(&digest::SHA256, key.as_bytes()).as_ref())
+}
+
+/// First characters of a key, shown in the admin list
+pub fn key_prefix(key: &str) -> String {
+    key.chars().take(KEY_PREFIX_LEN).collect()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Who an API request was made by
+#[derive(Debug, Clone)]
+pub enum ApiCaller {
+    Key(ApiKey),
+    Token(Claims),
+}
+
+impl ApiCaller {
+    /// Name logged for the caller: the key's label or the token's subject
+    pub fn name(&self) -> &str {
+        match self {
+            ApiCaller::Key(api_key) => &api_key.name,
+            ApiCaller::Token(claims) => &claims.subject,
+        }
+    }
+}
+
+/// Let requests through only with a valid API key or bearer token
+///
+/// The caller is attached to the request, so handlers can log who acted.
+pub async fn require_api_key(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if let Some(claims) = jwt::authenticate(&state, request.headers()) {
+        request.extensions_mut().insert(ApiCaller::Token(claims));
+        return next.run(request).await;
+    }
+
+    let key_hash = supplied_key(request.headers()).map(hash_key);
+    let Some(key_hash) = key_hash else {
+        return error_response(StatusCode::UNAUTHORIZED, "Missing API key");
+    };
+
+    let found = use_api_key(&state.db, &key_hash).map_err(|e| e.to_string());
+    match found {
+        Ok(Some(api_key)) => {
+            request.extensions_mut().insert(ApiCaller::Key(api_key));
+            next.run(request).await
+        }
+        Ok(None) => error_response(StatusCode::UNAUTHORIZED, "Invalid API key"),
+        Err(error_msg) => {
+            error!(error = %error_msg, "Database error while checking API key");
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+        }
+    }
+}
+
+/// Key from `Authorization: Bearer` or `X-Api-Key`
+fn supplied_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| {
+            headers
+                .get("x-api-key")
+                .and_then(|value| value.to_str().ok())
+        })
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+}
+
+/// An upload link as returned by the API
+#[derive(Debug, Serialize)]
+pub struct LinkResponse {
+    #[serde(flatten)]
+    pub link: UploadLink,
+
+    /// Address of the guest upload page (only the path without `PUBLIC_BASE_URL`)
+    pub upload_url: String,
+}
+
+impl LinkResponse {
+    fn new(state: &AppState, link: UploadLink) -> Self {
+        LinkResponse {
+            upload_url: link.upload_url(state.config.public_base_url.as_deref()),
+            link,
+        }
+    }
+}
+
+/// An upload as returned by the API
+#[derive(Debug, Serialize)]
+pub struct UploadResponse {
+    #[serde(flatten)]
+    pub upload: FileUpload,
+
+    /// Path of the file contents under the API
+    pub download_url: String,
+}
+
+impl From<FileUpload> for UploadResponse {
+    fn from(upload: FileUpload) -> Self {
+        UploadResponse {
+            download_url: format!("/api/v1/uploads/{}/download", upload.id),
+            upload,
+        }
+    }
+}
+
+/// Body of `POST /api/v1/links`
+#[derive(Debug, Deserialize)]
+pub struct CreateLinkRequest {
+    pub name: String,
+
+    /// Total quota in megabytes
+    pub quota_mb: f64,
+
+    /// Size limit for a single file in megabytes (None = only the quota applies)
+    pub per_file_limit_mb: Option<f64>,
+
+    /// Hours until the link expires (None or 0 = never)
+    pub expires_in_hours: Option<i64>,
+
+    /// Limit on the number of files the link accepts
+    pub max_uploads: Option<i64>,
+
+    pub guest_email: Option<String>,
+
+    /// Accepted types, e.g. `image/*, .pdf` (see [`crate::filetypes`])
+    pub allowed_file_types: Option<String>,
+
+    /// Bandwidth limit for the link's uploads and downloads
+    pub rate_limit_kbps: Option<i64>,
+
+    /// Email the admin about each file received
+    #[serde(default)]
+    pub notify_on_upload: bool,
+
+    /// Note shown to guests on the upload page
+    pub guest_note: Option<String>,
+}
+
+/// Body of `PATCH /api/v1/links/{id}`; missing fields are left as they are
+#[derive(Debug, Deserialize)]
+pub struct UpdateLinkRequest {
+    pub name: Option<String>,
+
+    /// Hours from now until the link expires (0 = never)
+    pub expires_in_hours: Option<i64>,
+
+    pub is_active: Option<bool>,
+
+    /// Note shown to guests on the upload page (empty = remove it)
+    pub guest_note: Option<String>,
+}
+
+/// `?filename=` of an API upload
+#[derive(Debug, Deserialize)]
+pub struct UploadFileQuery {
+    pub filename: String,
+}
+
+/// `?limit=` of the upload list
+#[derive(Debug, Deserialize)]
+pub struct ListQuery {
+    pub limit: Option<i64>,
+}
+
+pub async fn list_links(State(state): State<AppState>) -> Response {
+    match get_all_upload_links(&state.db) {
+        Ok(links) => Json(
+            links
+                .into_iter()
+                .map(|link| LinkResponse::new(&state, link))
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(e) => {
+            error!(error = %e, "Database error while listing links");
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+        }
+    }
+}
+
+pub async fn create_link(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+    Json(request): Json<CreateLinkRequest>,
+) -> Response {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return error_response(StatusCode::UNPROCESSABLE_ENTITY, "name must not be empty");
+    }
+    if !request.quota_mb.is_finite() || request.quota_mb <= 0.0 {
+        return error_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "quota_mb must be greater than 0",
+        );
+    }
+
+    let guest_email = request
+        .guest_email
+        .as_deref()
+        .map(str::trim)
+        .filter(|email| !email.is_empty());
+    if guest_email.is_some_and(|email| !email.contains('@')) {
+        return error_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "guest_email is not an email address",
+        );
+    }
+
+    // Stored in one spelling, as from the create link form
+    let allowed_file_types =
+        match AllowedTypes::parse(request.allowed_file_types.as_deref().unwrap_or_default()) {
+            Ok(types) => types.map(|types| types.to_list()),
+            Err(entry) => {
+                return error_response(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    &format!("allowed_file_types: unknown entry {}", entry),
+                );
+            }
+        };
+
+    let guest_note = match validate_guest_note(request.guest_note.as_deref()) {
+        Ok(note) => note,
+        Err(response) => return response,
+    };
+
+    let new_link = NewUploadLink {
+        name,
+        total_quota: (request.quota_mb * 1024.0 * 1024.0) as i64,
+        per_file_limit: request
+            .per_file_limit_mb
+            .filter(|mb| mb.is_finite() && *mb > 0.0)
+            .map(|mb| (mb * 1024.0 * 1024.0) as i64),
+        expires_at: expiry_from_hours(request.expires_in_hours),
+        rate_limit_kbps: request.rate_limit_kbps.filter(|kbps| *kbps > 0),
+        guest_email,
+        max_uploads: request.max_uploads.filter(|count| *count > 0),
+        allowed_file_types: allowed_file_types.as_deref(),
+        notify_on_upload: request.notify_on_upload,
+        guest_note,
+        ..Default::default()
+    };
+
+    let created = create_upload_link(&state.db, &new_link)
+        .and_then(|token| get_upload_link_by_token(&state.db, &token));
+    match created {
+        Ok(Some(link)) => {
+            info!(link_id = %link.id, caller = %caller.name(), "Upload link created via API");
+            events::link_created(&state, &link);
+            (StatusCode::CREATED, Json(LinkResponse::new(&state, link))).into_response()
+        }
+        Ok(None) => error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create link"),
+        Err(e) => {
+            error!(error = %e, "Failed to create upload link via API");
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create link")
+        }
+    }
+}
+
+/// Expiry `hours` from now (None or 0 = never)
+fn expiry_from_hours(hours: Option<i64>) -> Option<chrono::DateTime<Utc>> {
+    hours
+        .filter(|hours| *hours > 0)
+        .map(|hours| Utc::now() + Duration::hours(hours))
+}
+
+/// A guest note from a request, trimmed (None if blank), answering 422 when it is too long
+#[allow(clippy::result_large_err)]
+fn validate_guest_note(note: Option<&str>) -> Result<Option<&str>, Response> {
+    let note = note.map(str::trim).filter(|note| !note.is_empty());
+    if note.is_some_and(|note| note.chars().count() > UploadLink::MAX_GUEST_NOTE_CHARS) {
+        return Err(error_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            &format!(
+                "guest_note must be at most {} characters",
+                UploadLink::MAX_GUEST_NOTE_CHARS
+            ),
+        ));
+    }
+    Ok(note)
+}
+
+/// Look up a link, answering 404 when there is none
+#[allow(clippy::result_large_err)]
+fn find_link(state: &AppState, id: &str) -> Result<UploadLink, Response> {
+    match get_upload_link_by_id(&state.db, id) {
+        Ok(Some(link)) => Ok(link),
+        Ok(None) => Err(error_response(StatusCode::NOT_FOUND, "Link not found")),
+        Err(e) => {
+            error!(link_id = %id, error = %e, "Database error while fetching link");
+            Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Database error",
+            ))
+        }
+    }
+}
+
+pub async fn get_link(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match find_link(&state, &id) {
+        Ok(link) => Json(LinkResponse::new(&state, link)).into_response(),
+        Err(response) => response,
+    }
+}
+
+pub async fn update_link(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateLinkRequest>,
+) -> Response {
+    let link = match find_link(&state, &id) {
+        Ok(link) => link,
+        Err(response) => return response,
+    };
+
+    let name = request
+        .name
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or(link.name.as_str());
+    if name.is_empty() {
+        return error_response(StatusCode::UNPROCESSABLE_ENTITY, "name must not be empty");
+    }
+    let expires_at = match request.expires_in_hours {
+        Some(hours) => expiry_from_hours(Some(hours)),
+        None => link.expires_at,
+    };
+    let is_active = request.is_active.unwrap_or(link.is_active);
+    let guest_note = match request.guest_note.as_deref() {
+        Some(note) => match validate_guest_note(Some(note)) {
+            Ok(note) => note,
+            Err(response) => return response,
+        },
+        None => link.guest_note.as_deref(),
+    };
+
+    let updated = update_upload_link(
+        &state.db,
+        &id,
+        name,
+        link.total_quota,
+        link.per_file_limit,
+        expires_at,
+        link.retention_days,
+        guest_note,
+        is_active,
+    )
+    .and_then(|_| get_upload_link_by_id(&state.db, &id));
+    match updated {
+        Ok(Some(link)) => {
+            info!(link_id = %id, caller = %caller.name(), "Upload link updated via API");
+            Json(LinkResponse::new(&state, link)).into_response()
+        }
+        Ok(None) => error_response(StatusCode::NOT_FOUND, "Link not found"),
+        Err(e) => {
+            error!(link_id = %id, error = %e, "Failed to update upload link via API");
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update link")
+        }
+    }
+}
+
+/// Delete a link; like on the links page, only links without uploads or legal hold
+pub async fn delete_link(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+    Path(id): Path<String>,
+) -> Response {
+    let link = match find_link(&state, &id) {
+        Ok(link) => link,
+        Err(response) => return response,
+    };
+
+    if link.legal_hold {
+        return error_response(StatusCode::CONFLICT, "Link is under legal hold");
+    }
+    match get_file_uploads_by_link_id(&state.db, &id) {
+        Ok(uploads) if !uploads.is_empty() => {
+            return error_response(StatusCode::CONFLICT, "Link still has uploads");
+        }
+        Ok(_) => {}
+        Err(e) => {
+            error!(link_id = %id, error = %e, "Database error while checking link uploads");
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database error");
+        }
+    }
+
+    match delete_upload_link(&state.db, &id) {
+        Ok(_) => {
+            info!(link_id = %id, caller = %caller.name(), "Upload link deleted via API");
+            events::link_deleted(&state, &link);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(e) => {
+            error!(link_id = %id, error = %e, "Failed to delete upload link via API");
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to delete link")
+        }
+    }
+}
+
+pub async fn list_link_uploads(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    if let Err(response) = find_link(&state, &id) {
+        return response;
+    }
+
+    match get_file_uploads_by_link_id(&state.db, &id) {
+        Ok(uploads) => Json(
+            uploads
+                .into_iter()
+                .map(UploadResponse::from)
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(e) => {
+            error!(link_id = %id, error = %e, "Database error while listing uploads");
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+        }
+    }
+}
+
+/// Upload a file to a link, with the same checks as a guest upload
+#[allow(clippy::too_many_arguments)]
+pub async fn upload_file(
+    State(state): State<AppState>,
+    Extension(caller): Extension<ApiCaller>,
+    Path(id): Path<String>,
+    Query(query): Query<UploadFileQuery>,
+    client_ip: ClientIp,
+    request_id: RequestId,
+    headers: HeaderMap,
+    body: Body,
+) -> Response {
+    let link = match find_link(&state, &id) {
+        Ok(link) => link,
+        Err(response) => return response,
+    };
+
+    let filename = query.filename.trim();
+    if filename.is_empty() {
+        return error_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "filename must not be empty",
+        );
+    }
+    if !link.is_valid() {
+        return error_response(StatusCode::GONE, "Upload link has expired or is inactive");
+    }
+
+    // Wait for a free upload slot, like browser uploads do
+    let queue_timeout = std::time::Duration::from_secs(state.config.upload_queue_timeout_secs);
+    let _permit =
+        match tokio::time::timeout(queue_timeout, state.upload_limiter.clone().acquire_owned())
+            .await
+        {
+            Ok(Ok(permit)) => permit,
+            _ => {
+                return error_response(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "Too many concurrent uploads, please retry",
+                );
+            }
+        };
+
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+    let stream = body
+        .into_data_stream()
+        .map(|chunk| chunk.map_err(std::io::Error::other));
+
+    let stored = store_upload(
+        &state,
+        &link,
+        filename,
+        &content_type,
+        None,
+        &client_ip.uploader(request_id, &headers),
+        StreamReader::new(stream),
+    )
+    .await
+    .and_then(
+        |stored| match get_file_upload_by_id(&state.db, &stored.id) {
+            Ok(Some(upload)) => Ok(upload),
+            Ok(None) => Err(IngestError::Database("upload record missing".to_string())),
+            Err(e) => Err(IngestError::Database(e.to_string())),
+        },
+    );
+
+    match stored {
+        Ok(upload) => {
+            info!(upload_id = %upload.id, link_id = %link.id, caller = %caller.name(), "File uploaded via API");
+            (StatusCode::CREATED, Json(UploadResponse::from(upload))).into_response()
+        }
+        // The link already holds these exact bytes; answer with the existing upload
+        Err(IngestError::SameContent(existing)) => {
+            Json(UploadResponse::from(*existing)).into_response()
+        }
+        Err(
+            e @ (IngestError::QuotaExceeded
+            | IngestError::GuestQuotaExceeded(_)
+            | IngestError::DailyQuotaExceeded(_)),
+        ) => error_response(StatusCode::PAYLOAD_TOO_LARGE, &e.to_string()),
+        Err(e @ IngestError::LinkUnavailable) => error_response(StatusCode::GONE, &e.to_string()),
+        Err(e @ IngestError::FileLimitReached) => {
+            error_response(StatusCode::FORBIDDEN, &e.to_string())
+        }
+        Err(e @ IngestError::Duplicate) => error_response(StatusCode::CONFLICT, &e.to_string()),
+        Err(e @ (IngestError::FileType | IngestError::ContentMismatch)) => {
+            error_response(StatusCode::UNSUPPORTED_MEDIA_TYPE, &e.to_string())
+        }
+        Err(e @ IngestError::Infected(_)) => {
+            error_response(StatusCode::UNPROCESSABLE_ENTITY, &e.to_string())
+        }
+        Err(e @ IngestError::ScanFailed(_)) => {
+            error_response(StatusCode::SERVICE_UNAVAILABLE, &e.to_string())
+        }
+        Err(e @ (IngestError::Rejected(_) | IngestError::RulesRefused(_))) => {
+            error_response(StatusCode::FORBIDDEN, &e.to_string())
+        }
+        Err(e) => {
+            warn!(link_id = %link.id, filename = %filename, error = %e, "API upload failed");
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to store file")
+        }
+    }
+}
+
+pub async fn list_uploads(
+    State(state): State<AppState>,
+    Query(query): Query<ListQuery>,
+) -> Response {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .clamp(1, MAX_LIST_LIMIT);
+
+    match get_file_uploads_after(&state.db, None, limit) {
+        Ok(rows) => Json(
+            rows.into_iter()
+                .rev()
+                .map(|(upload, _)| UploadResponse::from(upload))
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(e) => {
+            error!(error = %e, "Database error while listing uploads");
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+        }
+    }
+}
+
+/// Look up an upload, answering 404 when there is none
+#[allow(clippy::result_large_err)]
+fn find_upload(state: &AppState, id: &str) -> Result<FileUpload, Response> {
+    match get_file_upload_by_id(&state.db, id) {
+        Ok(Some(upload)) => Ok(upload),
+        Ok(None) => Err(error_response(StatusCode::NOT_FOUND, "Upload not found")),
+        Err(e) => {
+            error!(upload_id = %id, error = %e, "Database error while fetching upload");
+            Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Database error",
+            ))
+        }
+    }
+}
+
+pub async fn get_upload(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match find_upload(&state, &id) {
+        Ok(upload) => Json(UploadResponse::from(upload)).into_response(),
+        Err(response) => response,
+    }
+}
+
+pub async fn download_upload(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let upload = match find_upload(&state, &id) {
+        Ok(upload) => upload,
+        Err(response) => return response,
+    };
+
+    let file_path = upload.file_path(&state.upload_dir);
+    let opened = encryption::open(
+        state.encryption.as_deref(),
+        &file_path,
+        upload.encryption_key_id.as_deref(),
+    )
+    .await;
+    let (file, content_length) = match opened {
+        Ok(opened) => opened,
+        Err(e) => {
+            warn!(upload_id = %id, file_path = %file_path.display(), error = %e, "File not found on disk");
+            return error_response(StatusCode::NOT_FOUND, "File not found on disk");
+        }
+    };
+
+    // Apply the link's bandwidth limit (if any), as for admin downloads
+    let throttle = get_upload_link_by_id(&state.db, &upload.link_id)
+        .ok()
+        .flatten()
+        .and_then(|link| TokenBucket::for_link(&link));
+
+    attachment_response(
+        file,
+        content_length,
+        &upload.original_filename,
+        &upload.mime_type,
+        throttle,
+    )
+}
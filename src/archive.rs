@@ -0,0 +1,509 @@
+//! # ZIP Downloads
+//!
+//! Lets admins fetch every file of a link as one ZIP archive instead of
+//! clicking through the downloads one by one.
+//!
+//! The archive is assembled while it is sent, so nothing but one read buffer
+//! is held in memory and the download starts at once. Files are stored
+//! without compression (most uploads are compressed already, and it keeps
+//! the CPU out of the way); each file's CRC-32 is computed on the way and
+//! written in a data descriptor after it. Files or archives over 4 GiB get
+//! ZIP64 records.
+//!
+//! Every upload of the link is included, older versions too. Entries are
+//! named after the original filename, with the version for versions after
+//! the first (`report (v2).pdf`) and a counter for any remaining clash.
+//! Files missing on disk are skipped. If reading a file fails mid-way the
+//! download ends early and the client gets a truncated archive.
+
+use axum::body::Body;
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use std::{collections::HashSet, io, path::PathBuf, sync::Arc};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio_util::io::ReaderStream;
+use tracing::{error, warn};
+
+use crate::{
+    encryption::{self, Keys},
+    models::FileUpload,
+    throttle::{throttle_stream, TokenBucket},
+};
+
+/// Bytes read from a file at a time
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Bytes buffered between the archive writer and the response body
+const PIPE_CAPACITY: usize = 256 * 1024;
+
+/// Largest value that fits a classic (non-ZIP64) size or offset field
+const MAX_U32: u64 = 0xFFFF_FFFF;
+
+/// Largest entry count of a classic end of central directory record
+const MAX_ENTRIES: usize = 0xFFFF;
+
+/// General purpose flags: sizes follow in a data descriptor, names are UTF-8
+const FLAGS: u16 = (1 << 3) | (1 << 11);
+
+/// A file to put in the archive
+pub struct ArchiveEntry {
+    /// Name inside the archive
+    pub name: String,
+
+    /// Where the file is on disk
+    pub path: PathBuf,
+
+    /// Key the file is encrypted with (None = unencrypted)
+    pub key_id: Option<String>,
+
+    /// Size recorded for the upload, used to decide on ZIP64 up front
+    pub size: u64,
+
+    /// Modification time shown by archive tools
+    pub modified: DateTime<Utc>,
+}
+
+/// Archive entries for a link's uploads, oldest first, with unique names
+pub fn entries(uploads: &[FileUpload], upload_dir: &std::path::Path) -> Vec<ArchiveEntry> {
+    let mut ordered: Vec<&FileUpload> = uploads.iter().collect();
+    ordered.sort_by_key(|upload| upload.uploaded_at);
+
+    let mut taken = HashSet::new();
+    ordered
+        .into_iter()
+        .map(|upload| {
+            let name = unique_name(&mut taken, &entry_name(upload));
+            ArchiveEntry {
+                name,
+                path: upload.file_path(upload_dir),
+                key_id: upload.encryption_key_id.clone(),
+                size: upload.file_size.max(0) as u64,
+                modified: upload.uploaded_at,
+            }
+        })
+        .collect()
+}
+
+/// A file's name in the archive: its original name, marked with the version
+/// after the first, and without path separators
+fn entry_name(upload: &FileUpload) -> String {
+    let name: String = upload
+        .original_filename
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\') { '_' } else { c })
+        .collect();
+    let name = if name.trim_matches('.').is_empty() {
+        "file".to_string()
+    } else {
+        name
+    };
+    if upload.version > 1 {
+        with_suffix(&name, &format!(" (v{})", upload.version))
+    } else {
+        name
+    }
+}
+
+/// `name`, or `name (2)`, `name (3)`, ... if taken already
+fn unique_name(taken: &mut HashSet<String>, name: &str) -> String {
+    let mut candidate = name.to_string();
+    let mut counter = 2;
+    while !taken.insert(candidate.to_lowercase()) {
+        candidate = with_suffix(name, &format!(" ({})", counter));
+        counter += 1;
+    }
+    candidate
+}
+
+/// Insert `suffix` before the extension of `name`
+fn with_suffix(name: &str, suffix: &str) -> String {
+    match name.rfind('.') {
+        Some(dot) if dot > 0 => format!("{}{}{}", &name[..dot], suffix, &name[dot..]),
+        _ => format!("{}{}", name, suffix),
+    }
+}
+
+/// Response body streaming the archive of `entries`, optionally paced by
+/// the link's `throttle`; encrypted files are decrypted with `keys`
+pub fn body(
+    entries: Vec<ArchiveEntry>,
+    keys: Option<Arc<Keys>>,
+    throttle: Option<TokenBucket>,
+) -> Body {
+    let (reader, mut writer) = tokio::io::duplex(PIPE_CAPACITY);
+    tokio::spawn(async move {
+        if let Err(e) = write_archive(&mut writer, entries, keys.as_deref()).await {
+            // A client that went away closes the pipe; anything else is worth a log line
+            if e.kind() != io::ErrorKind::BrokenPipe {
+                error!(error = %e, "Failed to write ZIP archive");
+            }
+        }
+    });
+
+    let stream = ReaderStream::new(reader);
+    match throttle {
+        Some(bucket) => Body::from_stream(throttle_stream(stream, bucket)),
+        None => Body::from_stream(stream),
+    }
+}
+
+/// Where an entry ended up, for its central directory record
+struct WrittenEntry {
+    name: String,
+    modified: DateTime<Utc>,
+    crc: u32,
+    size: u64,
+    offset: u64,
+    zip64: bool,
+}
+
+/// Write a complete archive of `entries` to `out`
+async fn write_archive<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    entries: Vec<ArchiveEntry>,
+    keys: Option<&Keys>,
+) -> io::Result<()> {
+    let mut offset = 0u64;
+    let mut written = Vec::with_capacity(entries.len());
+
+    for entry in entries {
+        let (file, size) = match encryption::open(keys, &entry.path, entry.key_id.as_deref()).await
+        {
+            Ok(opened) => opened,
+            Err(e) => {
+                warn!(path = %entry.path.display(), error = %e, "Skipping file missing from ZIP archive");
+                continue;
+            }
+        };
+        let zip64 = size.max(entry.size) >= MAX_U32;
+
+        let header = local_header(&entry.name, entry.modified, zip64);
+        out.write_all(&header).await?;
+
+        let (crc, copied) = copy_file(file, size, out).await?;
+        let descriptor = data_descriptor(crc, copied, zip64);
+        out.write_all(&descriptor).await?;
+
+        written.push(WrittenEntry {
+            name: entry.name,
+            modified: entry.modified,
+            crc,
+            size: copied,
+            offset,
+            zip64,
+        });
+        offset += (header.len() + descriptor.len()) as u64 + copied;
+    }
+
+    let central_offset = offset;
+    let mut central_size = 0u64;
+    for entry in &written {
+        let record = central_header(entry);
+        out.write_all(&record).await?;
+        central_size += record.len() as u64;
+    }
+
+    out.write_all(&end_of_central_directory(
+        written.len(),
+        central_offset,
+        central_size,
+    ))
+    .await?;
+    out.shutdown().await
+}
+
+/// Copy up to `size` bytes of `file` to `out`, returning their CRC-32 and count
+async fn copy_file<R, W>(file: R, size: u64, out: &mut W) -> io::Result<(u32, u64)>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut reader = file.take(size);
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut crc = Crc32::new();
+    let mut copied = 0u64;
+    loop {
+        let read = reader.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        crc.update(&buffer[..read]);
+        out.write_all(&buffer[..read]).await?;
+        copied += read as u64;
+    }
+    Ok((crc.finish(), copied))
+}
+
+fn local_header(name: &str, modified: DateTime<Utc>, zip64: bool) -> Vec<u8> {
+    let (time, date) = dos_date_time(modified);
+    let mut header = Vec::with_capacity(30 + name.len() + 20);
+    put_u32(&mut header, 0x0403_4b50);
+    put_u16(&mut header, version_needed(zip64));
+    put_u16(&mut header, FLAGS);
+    put_u16(&mut header, 0); // stored
+    put_u16(&mut header, time);
+    put_u16(&mut header, date);
+    put_u32(&mut header, 0); // CRC-32, in the data descriptor
+
+    // Sizes are in the data descriptor too; all ones announce 8-byte sizes there
+    let size_marker = if zip64 { MAX_U32 as u32 } else { 0 };
+    put_u32(&mut header, size_marker);
+    put_u32(&mut header, size_marker);
+    put_u16(&mut header, name.len() as u16);
+    put_u16(&mut header, if zip64 { 20 } else { 0 });
+    header.extend_from_slice(name.as_bytes());
+    if zip64 {
+        put_u16(&mut header, 0x0001);
+        put_u16(&mut header, 16);
+        put_u64(&mut header, 0);
+        put_u64(&mut header, 0);
+    }
+    header
+}
+
+fn data_descriptor(crc: u32, size: u64, zip64: bool) -> Vec<u8> {
+    let mut descriptor = Vec::with_capacity(24);
+    put_u32(&mut descriptor, 0x0807_4b50);
+    put_u32(&mut descriptor, crc);
+    if zip64 {
+        put_u64(&mut descriptor, size);
+        put_u64(&mut descriptor, size);
+    } else {
+        put_u32(&mut descriptor, size as u32);
+        put_u32(&mut descriptor, size as u32);
+    }
+    descriptor
+}
+
+fn central_header(entry: &WrittenEntry) -> Vec<u8> {
+    let (time, date) = dos_date_time(entry.modified);
+    let large_size = entry.zip64 || entry.size >= MAX_U32;
+    let large_offset = entry.offset >= MAX_U32;
+
+    // ZIP64 extra field with only the values that don't fit their classic field
+    let mut extra = Vec::new();
+    if large_size || large_offset {
+        let mut values = Vec::new();
+        if large_size {
+            put_u64(&mut values, entry.size);
+            put_u64(&mut values, entry.size);
+        }
+        if large_offset {
+            put_u64(&mut values, entry.offset);
+        }
+        put_u16(&mut extra, 0x0001);
+        put_u16(&mut extra, values.len() as u16);
+        extra.extend_from_slice(&values);
+    }
+
+    let version = version_needed(entry.zip64 || large_offset);
+    let mut record = Vec::with_capacity(46 + entry.name.len() + extra.len());
+    put_u32(&mut record, 0x0201_4b50);
+    put_u16(&mut record, version); // made by
+    put_u16(&mut record, version); // needed to extract
+    put_u16(&mut record, FLAGS);
+    put_u16(&mut record, 0); // stored
+    put_u16(&mut record, time);
+    put_u16(&mut record, date);
+    put_u32(&mut record, entry.crc);
+    let size = if large_size { MAX_U32 } else { entry.size };
+    put_u32(&mut record, size as u32);
+    put_u32(&mut record, size as u32);
+    put_u16(&mut record, entry.name.len() as u16);
+    put_u16(&mut record, extra.len() as u16);
+    put_u16(&mut record, 0); // comment length
+    put_u16(&mut record, 0); // disk number
+    put_u16(&mut record, 0); // internal attributes
+    put_u32(&mut record, 0); // external attributes
+    put_u32(&mut record, entry.offset.min(MAX_U32) as u32);
+    record.extend_from_slice(entry.name.as_bytes());
+    record.extend_from_slice(&extra);
+    record
+}
+
+fn end_of_central_directory(entries: usize, offset: u64, size: u64) -> Vec<u8> {
+    let mut record = Vec::with_capacity(98);
+    if entries >= MAX_ENTRIES || offset >= MAX_U32 || size >= MAX_U32 {
+        let zip64_offset = offset + size;
+        // ZIP64 end of central directory record
+        put_u32(&mut record, 0x0606_4b50);
+        put_u64(&mut record, 44);
+        put_u16(&mut record, 45);
+        put_u16(&mut record, 45);
+        put_u32(&mut record, 0);
+        put_u32(&mut record, 0);
+        put_u64(&mut record, entries as u64);
+        put_u64(&mut record, entries as u64);
+        put_u64(&mut record, size);
+        put_u64(&mut record, offset);
+        // ZIP64 end of central directory locator
+        put_u32(&mut record, 0x0706_4b50);
+        put_u32(&mut record, 0);
+        put_u64(&mut record, zip64_offset);
+        put_u32(&mut record, 1);
+    }
+    put_u32(&mut record, 0x0605_4b50);
+    put_u16(&mut record, 0);
+    put_u16(&mut record, 0);
+    put_u16(&mut record, entries.min(MAX_ENTRIES) as u16);
+    put_u16(&mut record, entries.min(MAX_ENTRIES) as u16);
+    put_u32(&mut record, size.min(MAX_U32) as u32);
+    put_u32(&mut record, offset.min(MAX_U32) as u32);
+    put_u16(&mut record, 0); // comment length
+    record
+}
+
+/// ZIP spec version needed: 2.0 for plain stored files, 4.5 for ZIP64
+fn version_needed(zip64: bool) -> u16 {
+    if zip64 {
+        45
+    } else {
+        20
+    }
+}
+
+/// MS-DOS time and date fields (UTC, two second resolution, from 1980)
+fn dos_date_time(time: DateTime<Utc>) -> (u16, u16) {
+    if time.year() < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    let dos_time = (time.hour() << 11) | (time.minute() << 5) | (time.second() / 2);
+    let dos_date = (((time.year() - 1980) as u32) << 9) | (time.month() << 5) | time.day();
+    (dos_time as u16, dos_date as u16)
+}
+
+fn put_u16(buffer: &mut Vec<u8>, value: u16) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(buffer: &mut Vec<u8>, value: u64) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+/// CRC-32 (IEEE 802.3), as ZIP uses it
+struct Crc32(u32);
+
+/// Lookup table for the reflected polynomial 0xEDB88320
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut value = n as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 1 != 0 {
+                0xEDB8_8320 ^ (value >> 1)
+            } else {
+                value >> 1
+            };
+            bit += 1;
+        }
+        table[n] = value;
+        n += 1;
+    }
+    table
+};
+
+impl Crc32 {
+    fn new() -> Self {
+        Self(0xFFFF_FFFF)
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = CRC_TABLE[((self.0 ^ byte as u32) & 0xFF) as usize] ^ (self.0 >> 8);
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        self.0 ^ 0xFFFF_FFFF
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{in_bounded_memory, sparse_file, GIB};
+    use std::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    /// Bytes kept from the end of the archive, enough for its closing records
+    const TAIL: usize = 256;
+
+    /// Counts what is written and keeps only its last `TAIL` bytes
+    #[derive(Default)]
+    struct TailSink {
+        written: u64,
+        tail: Vec<u8>,
+    }
+
+    impl AsyncWrite for TailSink {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.written += buf.len() as u64;
+            self.tail
+                .extend_from_slice(&buf[buf.len().saturating_sub(TAIL)..]);
+            let excess = self.tail.len().saturating_sub(TAIL);
+            self.tail.drain(..excess);
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    fn u64_at(bytes: &[u8], at: usize) -> u64 {
+        u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+    }
+
+    #[tokio::test]
+    #[ignore = "archives 5 GiB; run with `cargo test -- --ignored`"]
+    async fn archiving_a_file_larger_than_memory_streams_it_as_zip64() {
+        const SIZE: u64 = 5 * GIB;
+
+        let dir = tempfile::tempdir().unwrap();
+        let entry = ArchiveEntry {
+            name: "big.bin".to_string(),
+            path: sparse_file(dir.path(), SIZE),
+            key_id: None,
+            size: SIZE,
+            modified: Utc::now(),
+        };
+        let mut sink = TailSink::default();
+
+        in_bounded_memory(write_archive(&mut sink, vec![entry], None))
+            .await
+            .unwrap();
+
+        // Local header with ZIP64 extra field, data, ZIP64 data descriptor
+        let central_offset = 30 + 7 + 20 + SIZE + 24;
+        // Central header with ZIP64 sizes, then the ZIP64 and classic end records
+        assert_eq!(sink.written, central_offset + 73 + 98);
+
+        let tail = &sink.tail[TAIL - 73 - 98..];
+        assert_eq!(u32_at(tail, 0), 0x0201_4b50);
+        // CRC-32 of 5 GiB of zeros
+        assert_eq!(u32_at(tail, 16), 0x1938_38c3);
+        let zip64_end = &tail[73..];
+        assert_eq!(u32_at(zip64_end, 0), 0x0606_4b50);
+        assert_eq!(u64_at(zip64_end, 48), central_offset);
+        assert_eq!(u32_at(zip64_end, 76), 0x0605_4b50);
+    }
+}
@@ -7,21 +7,67 @@
 //! - Session-based authentication with UUIDs
 //! - Secure cookie handling with HttpOnly and SameSite flags
 //! - Password verification using bcrypt
+//! - Optional TOTP codes as a second login step (see [`crate::totp`])
 //! - Automatic session cleanup on logout
+//! - Idle and absolute session timeouts, so a stolen cookie stops working
+//! - CSRF tokens tied to the session for admin form posts
+//! - Roles limiting what an admin may do (see below)
 //!
 //! ## Session Storage
 //! Currently uses in-memory storage for simplicity. In production,
 //! consider using Redis or database-backed sessions for persistence
 //! across server restarts and horizontal scaling.
+//!
+//! ## Session Expiry
+//! A session ends when it hasn't been used for the idle timeout, or once it
+//! reaches the maximum age however active it is. Every authenticated request
+//! counts as activity and pushes the idle expiry back. Expired sessions are
+//! dropped when next presented and purged periodically by the scheduler.
+//!
+//! ## CSRF Protection
+//! Every session gets a random CSRF token at login. Admin pages embed it in
+//! their forms as a hidden `csrf_token` field, and [`csrf_middleware`]
+//! refuses state-changing requests of a logged-in admin that don't carry it,
+//! so another site can't make an admin's browser delete links or change the
+//! password. The token is taken from the `X-CSRF-Token` header, the query
+//! string (used by multipart forms, whose body isn't read up front) or the
+//! url-encoded form body.
+//!
+//! ## Roles
+//! Each admin has an [`AdminRole`], copied into the session at login.
+//! [`auth_middleware`] refuses with `403 Forbidden`:
+//! - anything under `/admin/admins` unless the admin is an owner
+//! - any post by an auditor, apart from their own password, two-factor
+//!   setup, display preferences and stars, and the read-only data subject
+//!   requests
+//!
+//! Owner-only actions elsewhere (releasing legal holds, erasing data) are
+//! checked by their handlers. Changing an admin's role updates their open
+//! sessions; deleting an admin ends them.
+//!
+//! ## Configuration
+//! - `SESSION_IDLE_TIMEOUT_MINS`: Minutes of inactivity after which a session ends (default: `60`, `0` = never)
+//! - `SESSION_MAX_AGE_HOURS`: Hours after login after which a session ends regardless of activity (default: `12`, `0` = never)
 
 use axum::{
+    body::{to_bytes, Body},
     extract::Request,
-    http::header::COOKIE,
+    http::{
+        header::{CONTENT_TYPE, COOKIE, SET_COOKIE},
+        Method, StatusCode,
+    },
     middleware::Next,
-    response::{IntoResponse, Redirect},
+    response::{IntoResponse, Redirect, Response},
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+use tracing::{debug, info, warn};
+
+use crate::{config::Config, models::AdminRole};
 
 /// Session data stored for authenticated administrators
 ///
@@ -34,6 +80,81 @@ pub struct Session {
 
     /// Username of the authenticated admin (for display purposes)
     pub username: String,
+
+    /// Token admin forms must send back with every post
+    pub csrf_token: String,
+
+    /// What the admin may do
+    pub role: AdminRole,
+}
+
+/// A session in the store, with the times its expiry is computed from
+struct StoredSession {
+    session: Session,
+
+    /// When the admin logged in
+    created_at: Instant,
+
+    /// When the session was last used
+    last_seen: Instant,
+}
+
+impl StoredSession {
+    fn is_expired(&self, timeouts: &SessionTimeouts, now: Instant) -> bool {
+        let idle = timeouts
+            .idle
+            .is_some_and(|idle| now.duration_since(self.last_seen) >= idle);
+        let too_old = timeouts
+            .max_age
+            .is_some_and(|max_age| now.duration_since(self.created_at) >= max_age);
+        idle || too_old
+    }
+}
+
+/// How long sessions may live (None = no limit)
+#[derive(Debug, Clone, Copy)]
+struct SessionTimeouts {
+    idle: Option<Duration>,
+    max_age: Option<Duration>,
+}
+
+impl SessionTimeouts {
+    fn from_config(config: &Config) -> Self {
+        Self {
+            idle: (config.session_idle_timeout_mins > 0)
+                .then(|| Duration::from_secs(config.session_idle_timeout_mins * 60)),
+            max_age: (config.session_max_age_hours > 0)
+                .then(|| Duration::from_secs(config.session_max_age_hours * 3600)),
+        }
+    }
+}
+
+/// Session timeouts, set once at startup
+static TIMEOUTS: OnceLock<SessionTimeouts> = OnceLock::new();
+
+/// Timeouts in effect until [`configure`] runs
+const DEFAULT_TIMEOUTS: SessionTimeouts = SessionTimeouts {
+    idle: Some(Duration::from_secs(60 * 60)),
+    max_age: Some(Duration::from_secs(12 * 3600)),
+};
+
+/// The configured session timeouts
+fn timeouts() -> SessionTimeouts {
+    TIMEOUTS.get().copied().unwrap_or(DEFAULT_TIMEOUTS)
+}
+
+/// Apply the session timeouts of the configuration
+///
+/// Called once at startup, before any session is created.
+pub fn configure(config: &Config) {
+    let timeouts = SessionTimeouts::from_config(config);
+    if TIMEOUTS.set(timeouts).is_ok() {
+        info!(
+            idle_timeout_mins = config.session_idle_timeout_mins,
+            max_age_hours = config.session_max_age_hours,
+            "Admin session timeouts configured"
+        );
+    }
 }
 
 /// Type alias for the thread-safe session storage
@@ -42,7 +163,7 @@ pub struct Session {
 /// - Arc: Multiple ownership across threads
 /// - RwLock: Multiple readers OR single writer
 /// - HashMap: Fast key-value lookup by session ID
-type SessionStore = std::sync::Arc<tokio::sync::RwLock<HashMap<String, Session>>>;
+type SessionStore = std::sync::Arc<tokio::sync::RwLock<HashMap<String, StoredSession>>>;
 
 // Global in-memory session store
 //
@@ -61,12 +182,23 @@ lazy_static::lazy_static! {
 /// # Arguments
 /// * `admin_id` - Unique identifier of the admin user
 /// * `username` - Username for display purposes
+/// * `role` - What the admin may do
 ///
 /// # Returns
 /// New session ID (UUID string) to be used in cookies
-pub async fn create_session(admin_id: String, username: String) -> String {
+pub async fn create_session(admin_id: String, username: String, role: AdminRole) -> String {
     let session_id = uuid::Uuid::new_v4().to_string();
-    let session = Session { admin_id, username };
+    let now = Instant::now();
+    let session = StoredSession {
+        session: Session {
+            admin_id,
+            username,
+            csrf_token: uuid::Uuid::new_v4().simple().to_string(),
+            role,
+        },
+        created_at: now,
+        last_seen: now,
+    };
 
     // Acquire write lock and insert session
     let mut sessions = SESSIONS.write().await;
@@ -78,16 +210,45 @@ pub async fn create_session(admin_id: String, username: String) -> String {
 /// Retrieve session data by session ID
 ///
 /// Looks up the session in the store and returns a copy of the session data.
-/// Returns None if the session ID is not found or has expired.
+/// Returns None if the session ID is not found or has expired. A valid
+/// session counts as used, which pushes its idle expiry back; an expired
+/// one is removed from the store.
 ///
 /// # Arguments
 /// * `session_id` - Session ID to look up
 ///
 /// # Returns
-/// Some(Session) if found, None if not found
+/// Some(Session) if found and still valid, None otherwise
 pub async fn get_session(session_id: &str) -> Option<Session> {
-    let sessions = SESSIONS.read().await;
-    sessions.get(session_id).cloned()
+    let now = Instant::now();
+    let mut sessions = SESSIONS.write().await;
+    let stored = sessions.get_mut(session_id)?;
+
+    if stored.is_expired(&timeouts(), now) {
+        debug!(username = %stored.session.username, "Admin session expired");
+        sessions.remove(session_id);
+        return None;
+    }
+
+    stored.last_seen = now;
+    Some(stored.session.clone())
+}
+
+/// Remove every expired session from the store
+///
+/// Sessions are also dropped when presented after expiring; this frees the
+/// ones that are never presented again.
+pub async fn purge_expired_sessions() {
+    let now = Instant::now();
+    let timeouts = timeouts();
+    let mut sessions = SESSIONS.write().await;
+    let before = sessions.len();
+    sessions.retain(|_, stored| !stored.is_expired(&timeouts, now));
+
+    let purged = before - sessions.len();
+    if purged > 0 {
+        debug!(purged, "Purged expired admin sessions");
+    }
 }
 
 /// Remove a session from the store (logout)
@@ -102,6 +263,22 @@ pub async fn remove_session(session_id: &str) {
     sessions.remove(session_id);
 }
 
+/// Apply a changed role to every open session of an admin
+pub async fn set_session_role(admin_id: &str, role: AdminRole) {
+    let mut sessions = SESSIONS.write().await;
+    for stored in sessions.values_mut() {
+        if stored.session.admin_id == admin_id {
+            stored.session.role = role;
+        }
+    }
+}
+
+/// End every open session of an admin (their account was deleted)
+pub async fn remove_admin_sessions(admin_id: &str) {
+    let mut sessions = SESSIONS.write().await;
+    sessions.retain(|_, stored| stored.session.admin_id != admin_id);
+}
+
 /// Extract session ID from HTTP cookie header
 ///
 /// Parses the Cookie header to find the session_id cookie value.
@@ -148,6 +325,37 @@ pub fn verify_password(password: &str, hash: &str) -> bool {
     bcrypt::verify(password, hash).unwrap_or(false)
 }
 
+/// Verify a password on the blocking thread pool
+///
+/// Bcrypt is deliberately slow (~100ms+ per call). Running it directly in an
+/// async handler would stall a Tokio worker thread and every request scheduled
+/// on it, including in-flight uploads, so handlers should use this variant.
+///
+/// # Arguments
+/// * `password` - Plaintext password to verify
+/// * `hash` - Bcrypt hash to verify against
+///
+/// # Returns
+/// true if password matches hash, false otherwise (including if the task panicked)
+pub async fn verify_password_async(password: String, hash: String) -> bool {
+    tokio::task::spawn_blocking(move || verify_password(&password, &hash))
+        .await
+        .unwrap_or(false)
+}
+
+/// Hash a password with bcrypt on the blocking thread pool
+///
+/// # Arguments
+/// * `password` - Plaintext password to hash
+///
+/// # Returns
+/// The bcrypt hash, or an error if hashing failed
+pub async fn hash_password(password: String) -> Result<String, bcrypt::BcryptError> {
+    tokio::task::spawn_blocking(move || bcrypt::hash(password, bcrypt::DEFAULT_COST))
+        .await
+        .unwrap_or_else(|e| Err(bcrypt::BcryptError::Io(std::io::Error::other(e))))
+}
+
 /// Authentication middleware for protecting admin routes
 ///
 /// This middleware is applied to all routes under `/admin` to ensure
@@ -155,9 +363,10 @@ pub fn verify_password(password: &str, hash: &str) -> bool {
 ///
 /// ## Process
 /// 1. Extract session ID from HTTP cookies
-/// 2. Look up session in the session store
+/// 2. Look up session in the session store, which drops it if it expired
+///    and otherwise records the activity
 /// 3. If valid session found, continue to the protected route
-/// 4. If no valid session, redirect to login page
+/// 4. If no valid session, clear the cookie and redirect to login page
 ///
 /// # Arguments
 /// * `request` - Incoming HTTP request
@@ -176,13 +385,21 @@ pub async fn auth_middleware(request: Request, next: Next) -> impl IntoResponse
     match session_id {
         Some(session_id) => {
             // Attempt to validate the session by looking it up in the store
-            if get_session(session_id).await.is_some() {
+            match get_session(session_id).await {
+                // Valid session, but the admin's role doesn't cover this request
+                Some(session) if !role_allows(session.role, &request) => {
+                    warn!(username = %session.username, role = session.role.code(), method = %request.method(), path = %request.uri().path(), "Request refused: not allowed for role");
+                    (
+                        StatusCode::FORBIDDEN,
+                        "Your account is not allowed to do this",
+                    )
+                        .into_response()
+                }
                 // Session is valid, continue to the protected route
-                next.run(request).await
-            } else {
+                Some(_) => next.run(request).await,
                 // Session ID found but not in store (expired/invalid)
-                // Redirect to login page
-                Redirect::to("/login").into_response()
+                // Clear the stale cookie and redirect to login page
+                None => expired_session_redirect(),
             }
         }
         None => {
@@ -192,3 +409,149 @@ pub async fn auth_middleware(request: Request, next: Next) -> impl IntoResponse
         }
     }
 }
+
+/// Posts under `/admin` an auditor may still make: their own account and
+/// stars, integrity checks, data subject requests that only read, and bulk
+/// downloads (the bulk handler refuses deletions itself)
+const AUDITOR_POSTS: &[&str] = &[
+    "/uploads/bulk",
+    "/change-password",
+    "/preferences",
+    "/two-factor/enable",
+    "/two-factor/disable",
+    "/gdpr/export",
+    "/gdpr/verify",
+];
+
+/// Whether an admin with `role` may make this request
+fn role_allows(role: AdminRole, request: &Request) -> bool {
+    // Nested routes see their path without the `/admin` prefix
+    let path = request.uri().path();
+    let path = path.strip_prefix("/admin").unwrap_or(path);
+
+    if path.starts_with("/admins") && !role.is_owner() {
+        return false;
+    }
+    if role.is_read_only()
+        && !matches!(
+            *request.method(),
+            Method::GET | Method::HEAD | Method::OPTIONS
+        )
+    {
+        let upload_check =
+            path.starts_with("/uploads/") && (path.ends_with("/star") || path.ends_with("/verify"));
+        return upload_check || AUDITOR_POSTS.contains(&path);
+    }
+    true
+}
+
+/// Form field and query parameter carrying the CSRF token
+const CSRF_FIELD: &str = "csrf_token";
+
+/// Header carrying the CSRF token for scripted requests
+const CSRF_HEADER: &str = "x-csrf-token";
+
+/// Largest form body read while looking for the CSRF token
+const MAX_FORM_BYTES: usize = 1024 * 1024;
+
+/// CSRF middleware for routes a logged-in admin changes state through
+///
+/// Safe methods and requests without a valid session pass through: the
+/// latter are turned away by [`auth_middleware`], or have nothing to protect.
+/// Everything else must carry the session's CSRF token, or is answered with
+/// `403 Forbidden`.
+pub async fn csrf_middleware(request: Request, next: Next) -> Response {
+    if matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    ) {
+        return next.run(request).await;
+    }
+
+    let session_id = request
+        .headers()
+        .get(COOKIE)
+        .and_then(|header| header.to_str().ok())
+        .and_then(extract_session_id_from_cookies);
+    let session = match session_id {
+        Some(session_id) => get_session(session_id).await,
+        None => None,
+    };
+    let Some(session) = session else {
+        return next.run(request).await;
+    };
+
+    let supplied = request
+        .headers()
+        .get(CSRF_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| request.uri().query().and_then(form_csrf_token))
+        .map(str::to_string);
+
+    let is_form = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"));
+
+    // The token may be in the form itself: read the body and hand it on
+    let (supplied, request) = match supplied {
+        Some(token) => (Some(token), request),
+        None if is_form => {
+            let (parts, body) = request.into_parts();
+            let Ok(bytes) = to_bytes(body, MAX_FORM_BYTES).await else {
+                return (StatusCode::PAYLOAD_TOO_LARGE, "Form too large").into_response();
+            };
+            let token = std::str::from_utf8(&bytes)
+                .ok()
+                .and_then(form_csrf_token)
+                .map(str::to_string);
+            (token, Request::from_parts(parts, Body::from(bytes)))
+        }
+        None => (None, request),
+    };
+
+    let valid = supplied.is_some_and(|token| tokens_match(&token, &session.csrf_token));
+    if !valid {
+        warn!(username = %session.username, method = %request.method(), path = %request.uri().path(), "Request refused: missing or wrong CSRF token");
+        return (
+            StatusCode::FORBIDDEN,
+            "The form has expired, please reload the page and try again",
+        )
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
+/// The `csrf_token` value of a url-encoded form or query string
+///
+/// Tokens are plain hex, so the value needs no percent-decoding.
+fn form_csrf_token(form: &str) -> Option<&str> {
+    form.split('&').find_map(|pair| {
+        pair.strip_prefix(CSRF_FIELD)
+            .and_then(|rest| rest.strip_prefix('='))
+    })
+}
+
+/// Compare tokens in constant time
+fn tokens_match(supplied: &str, expected: &str) -> bool {
+    supplied.len() == expected.len()
+        && supplied
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Redirect to the login page, clearing a session cookie that is no longer valid
+fn expired_session_redirect() -> Response {
+    let mut response = Redirect::to("/login").into_response();
+    response.headers_mut().insert(
+        SET_COOKIE,
+        axum::http::HeaderValue::from_static(
+            "session_id=; Path=/; HttpOnly; SameSite=Strict; Max-Age=0",
+        ),
+    );
+    response
+}
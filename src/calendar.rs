@@ -0,0 +1,150 @@
+//! # Calendar Feed
+//!
+//! An iCalendar (`.ics`) feed of link expiry dates, so they show up in the
+//! team calendar next to everything else. Subscribe to
+//! `/calendar/<CALENDAR_FEED_TOKEN>/links.ics` from any calendar app; the
+//! token in the URL is the only credential, as calendar clients can't log in.
+//!
+//! The feed lists every active link expiring in the future, plus links that
+//! expired during the last [`PAST_DAYS`] days so events don't disappear from
+//! the calendar on the day they happen.
+//!
+//! ## Configuration
+//! - `CALENDAR_FEED_TOKEN`: Secret part of the feed URL (unset = feed disabled)
+
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Duration, Utc};
+use tracing::error;
+
+use crate::{
+    database::get_all_upload_links,
+    models::{format_file_size, UploadLink},
+    AppState,
+};
+
+/// How long expired links stay in the feed
+const PAST_DAYS: i64 = 30;
+
+/// Maximum length of a content line in octets (RFC 5545 section 3.1)
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Serve the link expiry calendar
+pub async fn link_expiry_feed(
+    Path(token): Path<String>,
+    State(state): State<AppState>,
+) -> Response {
+    let authorized = state
+        .config
+        .calendar_feed_token
+        .as_deref()
+        .is_some_and(|expected| expected == token);
+    if !authorized {
+        return (StatusCode::NOT_FOUND, "Not found").into_response();
+    }
+
+    let links = match get_all_upload_links(&state.db) {
+        Ok(links) => links,
+        Err(e) => {
+            error!(error = %e, "Database error while building calendar feed");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
+        }
+    };
+
+    let cutoff = Utc::now() - Duration::days(PAST_DAYS);
+    let events: Vec<(&UploadLink, DateTime<Utc>)> = links
+        .iter()
+        .filter(|link| link.is_active && !link.is_archived())
+        .filter_map(|link| link.expires_at.map(|expires_at| (link, expires_at)))
+        .filter(|(_, expires_at)| *expires_at > cutoff)
+        .collect();
+
+    (
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+            (header::CACHE_CONTROL, "no-cache"),
+        ],
+        render_calendar(&events),
+    )
+        .into_response()
+}
+
+/// Render link expiries as an iCalendar document
+fn render_calendar(events: &[(&UploadLink, DateTime<Utc>)]) -> String {
+    let now = format_timestamp(Utc::now());
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//NeedADrop//Link Expiry//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        "X-WR-CALNAME:NeedADrop link expiry".to_string(),
+    ];
+
+    for (link, expires_at) in events {
+        let start = format_timestamp(*expires_at);
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:link-expiry-{}@needadrop", link.id),
+            format!("DTSTAMP:{}", now),
+            format!("DTSTART:{}", start),
+            format!("DTEND:{}", start),
+            format!(
+                "SUMMARY:{}",
+                escape_text(&format!("Upload link expires: {}", link.name))
+            ),
+            format!(
+                "DESCRIPTION:{}",
+                escape_text(&format!(
+                    "The upload link \"{}\" stops accepting files. Remaining quota: {}.",
+                    link.name,
+                    format_file_size(link.remaining_quota)
+                ))
+            ),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+/// Format a timestamp as an iCalendar UTC date-time
+fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape a value of type TEXT (RFC 5545 section 3.3.11)
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Terminate a content line, folding it if it is too long
+///
+/// Continuation lines start with a space, which counts towards their length.
+/// Lines are only split between characters so UTF-8 sequences stay intact.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut line_octets = 0;
+
+    for ch in line.chars() {
+        if line_octets + ch.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            line_octets = 1;
+        }
+        folded.push(ch);
+        line_octets += ch.len_utf8();
+    }
+
+    folded.push_str("\r\n");
+    folded
+}
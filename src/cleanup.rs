@@ -0,0 +1,180 @@
+//! # Expired Link Cleanup
+//!
+//! A background job that tidies up after links once they expire:
+//! - Deactivates every link past its expiry time, so it shows as inactive
+//!   and stays closed even if its expiry is later moved out by mistake
+//!   (the admin reactivates it on purpose, like a paused link)
+//! - Deletes files kept longer than their link's retention period, set per
+//!   link as days after upload
+//! - Optionally deletes the files of links that expired more than
+//!   `EXPIRED_FILE_RETENTION_DAYS` ago
+//!
+//! Each deletion run records one audit log entry per link.
+//!
+//! Files under legal hold, on their own or through their link, are never
+//! deleted, and upload hooks may keep a file as for any other deletion. The
+//! file rows go at once; the files themselves are removed by the task queue.
+//! Each run that changed anything logs a summary.
+//!
+//! The `link.expired` event is sent by the scheduler (see
+//! [`crate::scheduler`]) as soon as a link expires; this job runs less often.
+//!
+//! ## Configuration
+//! - `LINK_CLEANUP_INTERVAL_MINS`: Minutes between runs (default: `60`, `0` = off)
+//! - `EXPIRED_FILE_RETENTION_DAYS`: Days after expiry that a link's files are deleted (default: `0` = keep them)
+
+use chrono::{Duration, Utc};
+use std::collections::HashMap;
+use tracing::{error, info, warn};
+
+use crate::{
+    database::*,
+    models::FileUpload,
+    tasks::{enqueue, remove_upload_file, Task},
+    AppState,
+};
+
+/// Actor recorded in the audit log for deletions made by this job
+const AUDIT_ACTOR: &str = "system";
+
+/// What one run did, for the summary log line
+#[derive(Debug, Default)]
+struct CleanupSummary {
+    deactivated_links: usize,
+    deleted_files: usize,
+    freed_bytes: i64,
+    kept_files: usize,
+    failed_files: usize,
+}
+
+/// Start the cleanup loop (no-op when `LINK_CLEANUP_INTERVAL_MINS` is 0)
+pub fn spawn(state: AppState) {
+    let minutes = state.config.link_cleanup_interval_mins;
+    if minutes == 0 {
+        info!("Expired link cleanup disabled");
+        return;
+    }
+    let interval = std::time::Duration::from_secs(minutes * 60);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            run(&state).await;
+        }
+    });
+}
+
+/// Run the cleanup once
+async fn run(state: &AppState) {
+    let mut summary = CleanupSummary::default();
+
+    match deactivate_expired_links(&state.db) {
+        Ok(links) => {
+            for link in &links {
+                info!(link_id = %link.id, name = %link.name, "Deactivated expired link");
+            }
+            summary.deactivated_links = links.len();
+        }
+        Err(e) => error!(error = %e, "Failed to deactivate expired links"),
+    }
+
+    match get_uploads_past_retention(&state.db).map_err(|e| e.to_string()) {
+        Ok(uploads) => {
+            let deleted = delete_uploads(state, &uploads, &mut summary).await;
+            record_deletions(state, deleted, "after the link's retention period");
+        }
+        Err(e) => error!(error = %e, "Failed to find files past their retention period"),
+    }
+
+    let retention_days = state.config.expired_file_retention_days;
+    if retention_days > 0 {
+        let cutoff = Utc::now() - Duration::days(retention_days);
+        match get_uploads_of_links_expired_before(&state.db, cutoff).map_err(|e| e.to_string()) {
+            Ok(uploads) => {
+                let deleted = delete_uploads(state, &uploads, &mut summary).await;
+                let reason = format!("{} days after the link expired", retention_days);
+                record_deletions(state, deleted, &reason);
+            }
+            Err(e) => error!(error = %e, "Failed to find files of expired links"),
+        }
+    }
+
+    if summary.deactivated_links > 0 || summary.deleted_files > 0 || summary.failed_files > 0 {
+        info!(
+            deactivated_links = summary.deactivated_links,
+            deleted_files = summary.deleted_files,
+            freed_bytes = summary.freed_bytes,
+            kept_files = summary.kept_files,
+            failed_files = summary.failed_files,
+            "Expired link cleanup finished"
+        );
+    }
+}
+
+/// Delete `uploads`, returning how many files were deleted per link
+async fn delete_uploads(
+    state: &AppState,
+    uploads: &[FileUpload],
+    summary: &mut CleanupSummary,
+) -> HashMap<String, usize> {
+    let mut deleted_per_link: HashMap<String, usize> = HashMap::new();
+
+    for upload in uploads {
+        if let Err(reason) = state.hooks.before_delete(upload) {
+            warn!(upload_id = %upload.id, reason = %reason, "Upload hook kept file due for cleanup");
+            summary.kept_files += 1;
+            continue;
+        }
+
+        let delete_result = delete_file_upload(&state.db, &upload.id).map_err(|e| e.to_string());
+        if let Err(error_msg) = delete_result {
+            error!(upload_id = %upload.id, error = %error_msg, "Failed to delete file due for cleanup");
+            summary.failed_files += 1;
+            continue;
+        }
+
+        // Remove the file from disk in the background so transient failures are retried
+        let task = Task::RemoveUploadFile {
+            storage_dir: upload.storage_dir.clone(),
+            stored_filename: upload.stored_filename.clone(),
+            volume: upload.volume.clone(),
+        };
+        let enqueue_result = enqueue(state, &task).map_err(|e| e.to_string());
+        if let Err(error_msg) = enqueue_result {
+            error!(upload_id = %upload.id, error = %error_msg, "Failed to enqueue file removal, removing directly");
+            let _ = remove_upload_file(
+                state,
+                &upload.storage_dir,
+                &upload.stored_filename,
+                upload.volume.clone(),
+            )
+            .await;
+        }
+
+        summary.deleted_files += 1;
+        summary.freed_bytes += upload.file_size;
+        *deleted_per_link.entry(upload.link_id.clone()).or_default() += 1;
+    }
+
+    deleted_per_link
+}
+
+/// Record one audit log entry per link with the number of files deleted and why
+fn record_deletions(state: &AppState, deleted_per_link: HashMap<String, usize>, reason: &str) {
+    for (link_id, count) in deleted_per_link {
+        let detail = format!("{} files deleted {}", count, reason);
+        if let Err(e) = record_audit_event(
+            &state.db,
+            AUDIT_ACTOR,
+            "retention.deleted",
+            "link",
+            &link_id,
+            Some(&detail),
+        ) {
+            error!(link_id = %link_id, error = %e, "Failed to record file cleanup in the audit log");
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! # Post-Upload Commands
+//!
+//! Runs a program chosen per link after each upload, to feed received files
+//! into existing processing pipelines (conversion, import, archiving, ...).
+//! Commands run as background tasks (see [`crate::tasks`]): a failing
+//! command is retried like any other task, and its output is kept with the
+//! task and shown on the tasks page.
+//!
+//! The command line is split on whitespace, with single or double quotes
+//! grouping words; no shell is involved. The file is described to the
+//! program through environment variables, which are the only ones it gets
+//! besides `PATH`:
+//! - `NEEDADROP_FILE`: Path of the stored file, or of a decrypted copy (see below)
+//! - `NEEDADROP_FILENAME`, `NEEDADROP_MIME_TYPE`, `NEEDADROP_SIZE`: As sent by the guest
+//! - `NEEDADROP_UPLOAD_ID`, `NEEDADROP_UPLOADED_AT`, `NEEDADROP_GUEST_FOLDER`
+//! - `NEEDADROP_LINK_ID`, `NEEDADROP_LINK_NAME`
+//!
+//! ## Sandboxing
+//! Commands run with no standard input, in the file's folder and with the
+//! environment cleared. They are killed when they run past the timeout.
+//! `POST_UPLOAD_COMMAND_ALLOWLIST` limits which programs admins may set,
+//! and `POST_UPLOAD_COMMAND_WRAPPER` runs every command inside a sandbox
+//! tool such as `bwrap` or `firejail`.
+//!
+//! ## Encrypted Files
+//! With encryption at rest (see [`crate::encryption`]), the stored file is
+//! ciphertext. An encrypted file is decrypted into a private temporary folder
+//! for the command and `NEEDADROP_FILE` points at that copy; the folder is
+//! removed as soon as the command ends, so changes to the copy are lost.
+//!
+//! ## Configuration
+//! - `POST_UPLOAD_COMMANDS`: `true` to let admins set commands on links (default: `false`)
+//! - `POST_UPLOAD_COMMAND_ALLOWLIST`: Comma-separated programs or folders of programs commands may run (empty = any)
+//! - `POST_UPLOAD_COMMAND_WRAPPER`: Command line every command is run through, e.g. `firejail --quiet --net=none`
+//! - `POST_UPLOAD_COMMAND_TIMEOUT_SECS`: How long a command may run (default: 300)
+
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+use tempfile::TempDir;
+use tokio::{
+    fs,
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    process::Command,
+};
+use tracing::info;
+
+use crate::{
+    config::Config,
+    encryption::{self, Keys},
+    models::{FileUpload, UploadLink},
+};
+
+/// Most output kept from a command, in bytes (the end is kept)
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// Bytes read from a command's output at a time
+const READ_CHUNK_BYTES: usize = 8 * 1024;
+
+/// Fallback `PATH` when the server's own is unset
+const DEFAULT_PATH: &str = "/usr/local/bin:/usr/bin:/bin";
+
+/// Split a command line into words, honouring single and double quotes
+pub fn split(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+/// Whether a command line may be set on a link
+///
+/// The program must be named by an absolute path when an allowlist is set,
+/// so the check can't be sidestepped through `PATH`.
+pub fn is_allowed(config: &Config, command: &str) -> bool {
+    let words = split(command);
+    let Some(program) = words.first() else {
+        return false;
+    };
+    if config.post_upload_command_allowlist.is_empty() {
+        return true;
+    }
+
+    let program = Path::new(program);
+    program.is_absolute()
+        && config
+            .post_upload_command_allowlist
+            .iter()
+            .any(|allowed| program == allowed || program.parent() == Some(allowed.as_path()))
+}
+
+/// Run a link's command for one of its uploads, returning the command's output
+pub async fn run(
+    config: &Config,
+    keys: Option<&Keys>,
+    upload_dir: &Path,
+    link: &UploadLink,
+    upload: &FileUpload,
+    command: &str,
+) -> Result<String, String> {
+    if !is_allowed(config, command) {
+        return Err(format!("command not allowed: {}", command));
+    }
+
+    let stored_path = std::path::absolute(upload.file_path(upload_dir))
+        .map_err(|e| format!("failed to resolve file path: {}", e))?;
+    let working_dir = stored_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| upload_dir.to_path_buf());
+
+    // Kept until the command has finished; dropping it removes the copy
+    let decrypted = decrypted_copy(keys, &stored_path, upload.encryption_key_id.as_deref()).await?;
+    let file_path = match &decrypted {
+        Some((_, copy)) => copy.clone(),
+        None => stored_path,
+    };
+
+    let mut words = config
+        .post_upload_command_wrapper
+        .as_deref()
+        .map(split)
+        .unwrap_or_default();
+    words.extend(split(command));
+    let (program, args) = words.split_first().ok_or("empty command")?;
+
+    let mut child = Command::new(program)
+        .args(args)
+        .current_dir(&working_dir)
+        .env_clear()
+        .env(
+            "PATH",
+            std::env::var("PATH").unwrap_or_else(|_| DEFAULT_PATH.to_string()),
+        )
+        .env("NEEDADROP_FILE", &file_path)
+        .env("NEEDADROP_FILENAME", &upload.original_filename)
+        .env("NEEDADROP_MIME_TYPE", &upload.mime_type)
+        .env("NEEDADROP_SIZE", upload.file_size.to_string())
+        .env("NEEDADROP_UPLOAD_ID", &upload.id)
+        .env("NEEDADROP_UPLOADED_AT", upload.uploaded_at.to_rfc3339())
+        .env("NEEDADROP_GUEST_FOLDER", &upload.guest_folder)
+        .env("NEEDADROP_LINK_ID", &link.id)
+        .env("NEEDADROP_LINK_NAME", &link.name)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("failed to start {}: {}", program, e))?;
+
+    // Both pipes are read as the command runs, so it never blocks on a full one
+    let (stdout, stderr) = (child.stdout.take(), child.stderr.take());
+    let run = async {
+        let (stdout, stderr, status) =
+            tokio::join!(read_tail(stdout), read_tail(stderr), child.wait());
+        Ok::<_, std::io::Error>((stdout?, stderr?, status?))
+    };
+
+    let timeout = Duration::from_secs(config.post_upload_command_timeout_secs);
+    // Dropping the child on timeout kills it
+    let (stdout, mut combined, status) = tokio::time::timeout(timeout, run)
+        .await
+        .map_err(|_| format!("timed out after {} seconds", timeout.as_secs()))?
+        .map_err(|e| format!("failed to run {}: {}", program, e))?;
+
+    combined.splice(0..0, stdout);
+    let start = combined.len().saturating_sub(MAX_OUTPUT_BYTES);
+    let text = String::from_utf8_lossy(&combined[start..]).into_owned();
+
+    if !status.success() {
+        return Err(format!("{}\n{}", status, text.trim_end()));
+    }
+
+    info!(upload_id = %upload.id, link_id = %link.id, "Post-upload command finished");
+    Ok(text)
+}
+
+/// Read a command's output to the end, keeping only its last `MAX_OUTPUT_BYTES`
+///
+/// Output beyond that is read and dropped as it arrives, so a chatty command
+/// neither blocks nor fills memory.
+async fn read_tail<R: AsyncRead + Unpin>(output: Option<R>) -> std::io::Result<Vec<u8>> {
+    let mut tail = Vec::new();
+    let Some(mut output) = output else {
+        return Ok(tail);
+    };
+
+    let mut buffer = vec![0u8; READ_CHUNK_BYTES];
+    loop {
+        let read = output.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        tail.extend_from_slice(&buffer[..read]);
+        // Dropped in batches rather than on every read
+        if tail.len() >= 2 * MAX_OUTPUT_BYTES {
+            tail.drain(..tail.len() - MAX_OUTPUT_BYTES);
+        }
+    }
+
+    let start = tail.len().saturating_sub(MAX_OUTPUT_BYTES);
+    tail.drain(..start);
+    Ok(tail)
+}
+
+/// Decrypt an encrypted stored file into a private temporary folder
+///
+/// `key_id` is the key recorded for the file. Returns the folder, which is
+/// removed when dropped, and the copy's path; None for files stored without
+/// encryption.
+async fn decrypted_copy(
+    keys: Option<&Keys>,
+    path: &Path,
+    key_id: Option<&str>,
+) -> Result<Option<(TempDir, PathBuf)>, String> {
+    if key_id.is_none() {
+        return Ok(None);
+    }
+
+    let dir = tempfile::Builder::new()
+        .prefix("needadrop-command-")
+        .tempdir()
+        .map_err(|e| format!("failed to create a folder for the decrypted file: {}", e))?;
+    let copy = dir.path().join(path.file_name().unwrap_or_default());
+
+    let (mut reader, _) = encryption::open(keys, path, key_id)
+        .await
+        .map_err(|e| format!("failed to decrypt {}: {}", path.display(), e))?;
+    let mut file = fs::File::create(&copy)
+        .await
+        .map_err(|e| format!("failed to create {}: {}", copy.display(), e))?;
+    tokio::io::copy(&mut reader, &mut file)
+        .await
+        .map_err(|e| format!("failed to decrypt {}: {}", path.display(), e))?;
+    file.flush()
+        .await
+        .map_err(|e| format!("failed to write {}: {}", copy.display(), e))?;
+
+    Ok(Some((dir, copy)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn output_keeps_only_its_end() {
+        let output: Vec<u8> = (0..5 * MAX_OUTPUT_BYTES + 123)
+            .map(|i| (i % 251) as u8)
+            .collect();
+
+        let tail = read_tail(Some(output.as_slice())).await.unwrap();
+        assert_eq!(tail, &output[output.len() - MAX_OUTPUT_BYTES..]);
+
+        let short = b"done\n";
+        assert_eq!(read_tail(Some(&short[..])).await.unwrap(), short);
+        assert!(read_tail(None::<&[u8]>).await.unwrap().is_empty());
+    }
+}
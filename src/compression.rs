@@ -0,0 +1,62 @@
+//! # Response Compression
+//!
+//! Pages, JSON and static assets are compressed with brotli or gzip,
+//! whichever the client prefers, which speeds up the admin interface over
+//! slow links. Stored files are sent as they are: most are already
+//! compressed (archives, images, video), and downloads keep their exact
+//! `Content-Length`. Handlers serving stored files mark their response with
+//! [`Uncompressed`].
+//!
+//! ## Configuration
+//! - `COMPRESS_RESPONSES`: `false` to send every response uncompressed (default: `true`)
+
+use axum::http::{header, Extensions, HeaderMap, StatusCode, Version};
+use tower_http::compression::{
+    predicate::{DefaultPredicate, Predicate},
+    CompressionLayer,
+};
+
+use crate::config::Config;
+
+/// Content types that don't get smaller when compressed again
+const COMPRESSED_TYPES: [&str; 10] = [
+    "application/zip",
+    "application/gzip",
+    "application/x-gzip",
+    "application/x-7z-compressed",
+    "application/x-rar-compressed",
+    "application/x-bzip2",
+    "application/x-xz",
+    "application/zstd",
+    "audio/",
+    "video/",
+];
+
+/// Marks a response that must be sent uncompressed, such as a stored file
+#[derive(Debug, Clone, Copy)]
+pub struct Uncompressed;
+
+/// Compression for responses that benefit from it
+pub fn layer(config: &Config) -> CompressionLayer<impl Predicate> {
+    let enabled = config.compress_responses;
+    let compressible =
+        move |_: StatusCode, _: Version, headers: &HeaderMap, extensions: &Extensions| {
+            enabled
+                && extensions.get::<Uncompressed>().is_none()
+                && !headers.contains_key(header::CONTENT_DISPOSITION)
+                && !headers
+                    .get(header::CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .is_some_and(|content_type| {
+                        COMPRESSED_TYPES
+                            .iter()
+                            .any(|compressed| content_type.starts_with(compressed))
+                    })
+        };
+
+    // The default predicate already skips images, event streams and tiny bodies
+    CompressionLayer::new()
+        .gzip(true)
+        .br(true)
+        .compress_when(DefaultPredicate::new().and(compressible))
+}
@@ -0,0 +1,642 @@
+//! # Runtime Configuration
+//!
+//! Settings that operators may want to tune per deployment. Every value is read
+//! from an environment variable (or the `.env` file) once at startup and falls
+//! back to a sensible default for small single-server installations.
+//!
+//! ## Secrets from files
+//! So credentials don't have to be passed as plain environment variables in
+//! container deployments, every setting can also be read from a file:
+//! - `<NAME>_FILE`: Path of a file holding the value, e.g. `SMTP_PASSWORD_FILE=/run/secrets/smtp`
+//! - `SECRETS_DIR`: Directory of files named after settings, e.g. `/run/secrets` with a
+//!   file `SMTP_PASSWORD` or `smtp_password`
+//!
+//! A variable set directly wins over `<NAME>_FILE`, which wins over `SECRETS_DIR`.
+//! Surrounding whitespace (such as a trailing newline) is ignored.
+//!
+//! ## Config file
+//! Settings can also be kept in a TOML file, read from `CONFIG_FILE` or, when
+//! that is unset, from `needadrop.toml` in the working directory if it exists.
+//! Keys are the setting names in lower case; lists may be given as arrays:
+//!
+//! ```toml
+//! bind_address = "127.0.0.1"
+//! port = 8080
+//! max_body_size_mb = 500
+//! upload_dir = "/srv/needadrop/uploads"
+//! database_url = "/srv/needadrop/needadrop.db"
+//! wasm_plugins = ["/etc/needadrop/virus-total.wasm"]
+//! ```
+//!
+//! Any of the sources above overrides the file, so a deployment can share one
+//! file and change single settings through the environment.
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::OnceLock,
+};
+use tracing::warn;
+
+/// Config file read when `CONFIG_FILE` is not set, if it exists
+const DEFAULT_CONFIG_FILE: &str = "needadrop.toml";
+
+/// Settings from the config file, by upper-case setting name
+static FILE_SETTINGS: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Application configuration loaded at startup
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Address the main listener binds to (`BIND_ADDRESS`)
+    pub bind_address: IpAddr,
+
+    /// Port of the main listener (`PORT`)
+    pub port: u16,
+
+    /// Address guests reach the server at, e.g. `https://drop.example.com`, used for
+    /// upload URLs in the admin pages, emails and the API (`PUBLIC_BASE_URL`, unset =
+    /// relative to the host the admin is browsing)
+    pub public_base_url: Option<String>,
+
+    /// Largest request body outside of guest uploads, in MB (`MAX_BODY_SIZE_MB`)
+    pub max_body_size_mb: u64,
+
+    /// Directory uploaded files are stored in (`UPLOAD_DIR`)
+    pub upload_dir: PathBuf,
+
+    /// SQLite database path, optionally prefixed with `sqlite:` (`DATABASE_URL`)
+    pub database_url: String,
+
+    /// Maximum number of uploads processed at the same time (`MAX_CONCURRENT_UPLOADS`)
+    pub max_concurrent_uploads: usize,
+
+    /// Maximum number of uploads writing to disk at the same time (`MAX_CONCURRENT_DISK_WRITES`)
+    pub max_concurrent_disk_writes: usize,
+
+    /// How long an upload may wait for a free slot before being rejected (`UPLOAD_QUEUE_TIMEOUT_SECS`)
+    pub upload_queue_timeout_secs: u64,
+
+    /// Number of background task workers (`TASK_WORKERS`)
+    pub task_workers: usize,
+
+    /// Attempts before a failing background task is dead-lettered (`TASK_MAX_ATTEMPTS`)
+    pub task_max_attempts: i64,
+
+    /// Remote `user@host:/path` to mirror uploads to; disabled when unset (`REPLICATION_TARGET`)
+    pub replication_target: Option<String>,
+
+    /// Transfer method for replication, `rsync` or `sftp` (`REPLICATION_METHOD`)
+    pub replication_method: String,
+
+    /// Base URL of the WebDAV server links can push files to (`WEBDAV_URL`)
+    pub webdav_url: Option<String>,
+
+    /// WebDAV username (`WEBDAV_USERNAME`)
+    pub webdav_username: Option<String>,
+
+    /// WebDAV password or app password (`WEBDAV_PASSWORD`)
+    pub webdav_password: Option<String>,
+
+    /// IMAP server polled by the email-in gateway; disabled when unset (`EMAIL_IN_IMAP_HOST`)
+    pub email_in_imap_host: Option<String>,
+
+    /// IMAPS port of the email-in mailbox (`EMAIL_IN_IMAP_PORT`)
+    pub email_in_imap_port: u16,
+
+    /// Email-in mailbox username (`EMAIL_IN_USERNAME`)
+    pub email_in_username: Option<String>,
+
+    /// Email-in mailbox password (`EMAIL_IN_PASSWORD`)
+    pub email_in_password: Option<String>,
+
+    /// Folder polled for new messages (`EMAIL_IN_MAILBOX`)
+    pub email_in_mailbox: String,
+
+    /// Public gateway address, e.g. `drop@example.com` (`EMAIL_IN_ADDRESS`)
+    pub email_in_address: Option<String>,
+
+    /// Seconds between mailbox polls (`EMAIL_IN_POLL_SECS`)
+    pub email_in_poll_secs: u64,
+
+    /// S3-compatible API mode: `off`, `write` or `read-write` (`S3_API`)
+    pub s3_api: String,
+
+    /// API key for the integrations polling endpoint (`INTEGRATION_API_KEY`, unset = disabled)
+    pub integration_api_key: Option<String>,
+
+    /// MQTT broker host for event publishing (`MQTT_HOST`, unset = disabled)
+    pub mqtt_host: Option<String>,
+
+    /// MQTT broker port (`MQTT_PORT`)
+    pub mqtt_port: u16,
+
+    /// MQTT username (`MQTT_USERNAME`)
+    pub mqtt_username: Option<String>,
+
+    /// MQTT password (`MQTT_PASSWORD`)
+    pub mqtt_password: Option<String>,
+
+    /// Prefix of all published topics (`MQTT_TOPIC_PREFIX`)
+    pub mqtt_topic_prefix: String,
+
+    /// Client ID used when connecting to the broker (`MQTT_CLIENT_ID`)
+    pub mqtt_client_id: String,
+
+    /// Secret in the calendar feed URL (`CALENDAR_FEED_TOKEN`, unset = disabled)
+    pub calendar_feed_token: Option<String>,
+
+    /// Seconds between runs of the periodic housekeeping jobs (`SCHEDULER_INTERVAL_SECS`)
+    pub scheduler_interval_secs: u64,
+
+    /// Remaining-quota percentage below which a link is flagged (`QUOTA_WARNING_PERCENT`, 0 = off)
+    pub quota_warning_percent: u8,
+
+    /// SMTP server for outgoing email (`SMTP_HOST`, unset = email disabled)
+    pub smtp_host: Option<String>,
+
+    /// SMTP port (`SMTP_PORT`)
+    pub smtp_port: u16,
+
+    /// SMTP connection security: `starttls`, `tls` or `none` (`SMTP_SECURITY`)
+    pub smtp_security: String,
+
+    /// SMTP username (`SMTP_USERNAME`)
+    pub smtp_username: Option<String>,
+
+    /// SMTP password (`SMTP_PASSWORD`)
+    pub smtp_password: Option<String>,
+
+    /// Sender address of outgoing email (`SMTP_FROM`)
+    pub smtp_from: String,
+
+    /// Address that admin notifications are sent to (`ADMIN_EMAIL`)
+    pub admin_email: Option<String>,
+
+    /// Hours before expiry to remind about unused links (`EXPIRY_REMINDER_HOURS`, 0 = off)
+    pub expiry_reminder_hours: i64,
+
+    /// Minutes between runs of the expired link cleanup (`LINK_CLEANUP_INTERVAL_MINS`, 0 = off)
+    pub link_cleanup_interval_mins: u64,
+
+    /// Days after expiry that a link's files are deleted (`EXPIRED_FILE_RETENTION_DAYS`, 0 = keep)
+    pub expired_file_retention_days: i64,
+
+    /// Time zone timestamps are shown in unless an admin picked their own (`DISPLAY_TIMEZONE`)
+    pub display_timezone: chrono_tz::Tz,
+
+    /// strftime format for displayed timestamps (`DATETIME_FORMAT`)
+    pub datetime_format: String,
+
+    /// strftime format for displayed dates (`DATE_FORMAT`)
+    pub date_format: String,
+
+    /// Hours a resumable upload session survives without new data (`UPLOAD_SESSION_TTL_HOURS`)
+    pub upload_session_ttl_hours: i64,
+
+    /// Serve a shared upload page at `/drop` that needs no link (`PUBLIC_DROP`)
+    pub public_drop: bool,
+
+    /// Name shown on the public drop page (`PUBLIC_DROP_NAME`)
+    pub public_drop_name: String,
+
+    /// Total quota of the public drop in MB (`PUBLIC_DROP_QUOTA_MB`)
+    pub public_drop_quota_mb: i64,
+
+    /// Bandwidth limit of the public drop in KB/s (`PUBLIC_DROP_RATE_LIMIT_KBPS`, 0 = unlimited)
+    pub public_drop_rate_limit_kbps: i64,
+
+    /// Ask visitors of the public drop to solve a CAPTCHA first (`PUBLIC_DROP_CAPTCHA`)
+    pub public_drop_captcha: bool,
+
+    /// Directory layout of stored files under the upload directory (`STORAGE_PATH_TEMPLATE`)
+    pub storage_path_template: String,
+
+    /// Storage roots new files are spread across (`STORAGE_VOLUMES`, empty = the upload directory)
+    pub storage_volumes: Vec<PathBuf>,
+
+    /// How a volume is picked for a new file: `most-free-space` or `round-robin` (`STORAGE_PLACEMENT`)
+    pub storage_placement: String,
+
+    /// Take client addresses from `X-Forwarded-For` set by a reverse proxy (`TRUST_PROXY_HEADERS`)
+    pub trust_proxy_headers: bool,
+
+    /// Reverse proxies in front of the server, each adding an `X-Forwarded-For` entry (`TRUSTED_PROXY_HOPS`)
+    pub trusted_proxy_hops: usize,
+
+    /// HMAC key that GDPR reports are signed with (`GDPR_REPORT_KEY`, unset = unsigned)
+    pub gdpr_report_key: Option<String>,
+
+    /// Scan text uploads for personal data such as IBANs and card numbers (`PII_SCAN`)
+    pub pii_scan: bool,
+
+    /// How much of each file the personal data scan reads, in KB (`PII_SCAN_MAX_KB`)
+    pub pii_scan_max_kb: u64,
+
+    /// Virus scanner received files must pass: `off`, `clamd` or `clamscan` (`VIRUS_SCAN`)
+    pub virus_scan: String,
+
+    /// clamd socket path or `host:port` (`CLAMD_ADDRESS`)
+    pub clamd_address: String,
+
+    /// clamscan program run for `VIRUS_SCAN=clamscan` (`CLAMSCAN_PATH`)
+    pub clamscan_path: String,
+
+    /// How long a scan may take before the file is refused (`VIRUS_SCAN_TIMEOUT_SECS`)
+    pub virus_scan_timeout_secs: u64,
+
+    /// Login attempts per window and client address (`LOGIN_RATE_LIMIT`, 0 = unlimited)
+    pub login_rate_limit: u32,
+
+    /// Uploads started per window, client address and link (`UPLOAD_RATE_LIMIT`, 0 = unlimited)
+    pub upload_rate_limit: u32,
+
+    /// Length of a rate limiting window (`RATE_LIMIT_WINDOW_SECS`)
+    pub rate_limit_window_secs: u64,
+
+    /// Rhai script deciding whether received files are kept (`UPLOAD_RULES_FILE`)
+    pub upload_rules_file: Option<PathBuf>,
+
+    /// How guests are told apart for per-guest quotas: `session`, `ip` or `both` (`GUEST_QUOTA_BY`)
+    pub guest_quota_by: String,
+
+    /// What happens to a file whose contents a link already has: `warn`, `skip` or `accept` (`DUPLICATE_UPLOADS`)
+    pub duplicate_uploads: String,
+
+    /// Keep one copy on disk of files with the same contents, across all links (`DEDUPLICATE_STORAGE`)
+    pub deduplicate_storage: bool,
+
+    /// Refuse files whose contents don't match their declared MIME type (`REJECT_TYPE_MISMATCH`)
+    pub reject_type_mismatch: bool,
+
+    /// Hex key stored files are encrypted with (`ENCRYPTION_KEY`, unset = no encryption)
+    pub encryption_key: Option<String>,
+
+    /// Older hex keys files may still be encrypted with (`ENCRYPTION_PREVIOUS_KEYS`)
+    pub encryption_previous_keys: Vec<String>,
+
+    /// Let admins set a command run after each upload on a link (`POST_UPLOAD_COMMANDS`)
+    pub post_upload_commands: bool,
+
+    /// Programs, or folders of programs, post-upload commands may run (`POST_UPLOAD_COMMAND_ALLOWLIST`, empty = any)
+    pub post_upload_command_allowlist: Vec<PathBuf>,
+
+    /// Command line post-upload commands are run through, e.g. a sandbox tool (`POST_UPLOAD_COMMAND_WRAPPER`)
+    pub post_upload_command_wrapper: Option<String>,
+
+    /// How long a post-upload command may run (`POST_UPLOAD_COMMAND_TIMEOUT_SECS`)
+    pub post_upload_command_timeout_secs: u64,
+
+    /// WASM plugins run as upload hooks, in order (`WASM_PLUGINS`)
+    pub wasm_plugins: Vec<PathBuf>,
+
+    /// Instruction budget of one plugin hook call (`WASM_PLUGIN_FUEL`)
+    #[cfg_attr(not(feature = "wasm-plugins"), allow(dead_code))]
+    pub wasm_plugin_fuel: u64,
+
+    /// Memory a plugin may use, in MB (`WASM_PLUGIN_MEMORY_MB`)
+    #[cfg_attr(not(feature = "wasm-plugins"), allow(dead_code))]
+    pub wasm_plugin_memory_mb: u64,
+
+    /// Bind the main listener with `SO_REUSEPORT` for overlapping restarts (`LISTEN_REUSE_PORT`)
+    pub listen_reuse_port: bool,
+
+    /// How long requests in flight may take to finish after a shutdown signal (`SHUTDOWN_DRAIN_SECS`)
+    pub shutdown_drain_secs: u64,
+
+    /// Compress pages, JSON and static assets (`COMPRESS_RESPONSES`)
+    pub compress_responses: bool,
+
+    /// Address of the mutual TLS admin listener (`ADMIN_TLS_ADDR`, None = admin on the main listener)
+    pub admin_tls_addr: Option<String>,
+
+    /// PEM certificate chain of the admin listener (`ADMIN_TLS_CERT`)
+    pub admin_tls_cert: Option<PathBuf>,
+
+    /// PEM private key of the admin listener (`ADMIN_TLS_KEY`)
+    pub admin_tls_key: Option<PathBuf>,
+
+    /// PEM CA certificates client certificates must be issued by (`ADMIN_TLS_CLIENT_CA`)
+    pub admin_tls_client_ca: Option<PathBuf>,
+
+    /// Log admins in whose client certificate CN is their username (`ADMIN_TLS_CERT_LOGIN`)
+    pub admin_tls_cert_login: bool,
+
+    /// Expected issuer of API bearer tokens (`JWT_ISSUER`, None = bearer tokens disabled)
+    pub jwt_issuer: Option<String>,
+
+    /// Expected audience of API bearer tokens (`JWT_AUDIENCE`, None = not checked)
+    pub jwt_audience: Option<String>,
+
+    /// Shared secret for HS256-signed bearer tokens (`JWT_HS256_SECRET`)
+    pub jwt_hs256_secret: Option<String>,
+
+    /// PEM public keys for RS256/ES256-signed bearer tokens (`JWT_PUBLIC_KEYS`)
+    pub jwt_public_keys: Vec<PathBuf>,
+
+    /// Clock difference tolerated on token expiry, in seconds (`JWT_LEEWAY_SECS`)
+    pub jwt_leeway_secs: i64,
+
+    /// Minutes of inactivity after which an admin session ends (`SESSION_IDLE_TIMEOUT_MINS`, 0 = never)
+    pub session_idle_timeout_mins: u64,
+
+    /// Hours after login after which an admin session ends (`SESSION_MAX_AGE_HOURS`, 0 = never)
+    pub session_max_age_hours: u64,
+
+    /// Maximum number of open SQLite connections (`DATABASE_POOL_SIZE`)
+    pub database_pool_size: u32,
+
+    /// How long a request waits for a free connection before failing (`DATABASE_POOL_TIMEOUT_SECS`)
+    pub database_pool_timeout_secs: u64,
+}
+
+impl Config {
+    /// Load the configuration from environment variables
+    pub fn from_env() -> Self {
+        Self {
+            bind_address: env_or("BIND_ADDRESS", IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            port: env_or("PORT", 3000),
+            public_base_url: env_opt("PUBLIC_BASE_URL")
+                .map(|url| url.trim_end_matches('/').to_string()),
+            max_body_size_mb: env_or("MAX_BODY_SIZE_MB", 100),
+            upload_dir: env_opt("UPLOAD_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("uploads")),
+            database_url: env_or("DATABASE_URL", "needadrop.db".to_string()),
+            max_concurrent_uploads: env_or("MAX_CONCURRENT_UPLOADS", 8),
+            max_concurrent_disk_writes: env_or("MAX_CONCURRENT_DISK_WRITES", 4),
+            upload_queue_timeout_secs: env_or("UPLOAD_QUEUE_TIMEOUT_SECS", 30),
+            task_workers: env_or("TASK_WORKERS", 2),
+            task_max_attempts: env_or("TASK_MAX_ATTEMPTS", 5),
+            replication_target: env_opt("REPLICATION_TARGET"),
+            replication_method: env_or("REPLICATION_METHOD", "rsync".to_string()),
+            webdav_url: env_opt("WEBDAV_URL"),
+            webdav_username: env_opt("WEBDAV_USERNAME"),
+            webdav_password: env_opt("WEBDAV_PASSWORD"),
+            email_in_imap_host: env_opt("EMAIL_IN_IMAP_HOST"),
+            email_in_imap_port: env_or("EMAIL_IN_IMAP_PORT", 993),
+            email_in_username: env_opt("EMAIL_IN_USERNAME"),
+            email_in_password: env_opt("EMAIL_IN_PASSWORD"),
+            email_in_mailbox: env_or("EMAIL_IN_MAILBOX", "INBOX".to_string()),
+            email_in_address: env_opt("EMAIL_IN_ADDRESS"),
+            email_in_poll_secs: env_or("EMAIL_IN_POLL_SECS", 60),
+            s3_api: env_or("S3_API", "off".to_string()).to_lowercase(),
+            integration_api_key: env_opt("INTEGRATION_API_KEY"),
+            mqtt_host: env_opt("MQTT_HOST"),
+            mqtt_port: env_or("MQTT_PORT", 1883),
+            mqtt_username: env_opt("MQTT_USERNAME"),
+            mqtt_password: env_opt("MQTT_PASSWORD"),
+            mqtt_topic_prefix: env_or("MQTT_TOPIC_PREFIX", "needadrop".to_string()),
+            mqtt_client_id: env_or("MQTT_CLIENT_ID", "needadrop".to_string()),
+            calendar_feed_token: env_opt("CALENDAR_FEED_TOKEN"),
+            scheduler_interval_secs: env_or("SCHEDULER_INTERVAL_SECS", 60),
+            quota_warning_percent: env_or("QUOTA_WARNING_PERCENT", 10).min(100),
+            smtp_host: env_opt("SMTP_HOST"),
+            smtp_port: env_or("SMTP_PORT", 587),
+            smtp_security: env_or("SMTP_SECURITY", "starttls".to_string()).to_lowercase(),
+            smtp_username: env_opt("SMTP_USERNAME"),
+            smtp_password: env_opt("SMTP_PASSWORD"),
+            smtp_from: env_or("SMTP_FROM", "NeedADrop <needadrop@localhost>".to_string()),
+            admin_email: env_opt("ADMIN_EMAIL"),
+            expiry_reminder_hours: env_or("EXPIRY_REMINDER_HOURS", 24),
+            link_cleanup_interval_mins: env_or("LINK_CLEANUP_INTERVAL_MINS", 60),
+            expired_file_retention_days: env_or("EXPIRED_FILE_RETENTION_DAYS", 0i64).max(0),
+            display_timezone: env_or("DISPLAY_TIMEZONE", chrono_tz::UTC),
+            datetime_format: env_format("DATETIME_FORMAT", "%Y-%m-%d %H:%M %Z"),
+            date_format: env_format("DATE_FORMAT", "%Y-%m-%d"),
+            upload_session_ttl_hours: env_or("UPLOAD_SESSION_TTL_HOURS", 72),
+            public_drop: env_or("PUBLIC_DROP", false),
+            public_drop_name: env_or("PUBLIC_DROP_NAME", "Public Drop".to_string()),
+            public_drop_quota_mb: env_or("PUBLIC_DROP_QUOTA_MB", 1024),
+            public_drop_rate_limit_kbps: env_or("PUBLIC_DROP_RATE_LIMIT_KBPS", 0),
+            public_drop_captcha: env_or("PUBLIC_DROP_CAPTCHA", true),
+            storage_path_template: env_path_template("STORAGE_PATH_TEMPLATE", "{guest}"),
+            storage_volumes: env_opt("STORAGE_VOLUMES")
+                .map(|volumes| {
+                    volumes
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|volume| !volume.is_empty())
+                        .map(PathBuf::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            storage_placement: env_or("STORAGE_PLACEMENT", "most-free-space".to_string())
+                .to_lowercase(),
+            trust_proxy_headers: env_or("TRUST_PROXY_HEADERS", false),
+            trusted_proxy_hops: env_or("TRUSTED_PROXY_HOPS", 1usize).max(1),
+            gdpr_report_key: env_opt("GDPR_REPORT_KEY"),
+            pii_scan: env_or("PII_SCAN", false),
+            pii_scan_max_kb: env_or("PII_SCAN_MAX_KB", 10 * 1024),
+            virus_scan: env_or("VIRUS_SCAN", "off".to_string()).to_lowercase(),
+            clamd_address: env_or("CLAMD_ADDRESS", "/run/clamav/clamd.ctl".to_string()),
+            clamscan_path: env_or("CLAMSCAN_PATH", "clamscan".to_string()),
+            virus_scan_timeout_secs: env_or("VIRUS_SCAN_TIMEOUT_SECS", 120),
+            login_rate_limit: env_or("LOGIN_RATE_LIMIT", 10),
+            upload_rate_limit: env_or("UPLOAD_RATE_LIMIT", 60),
+            rate_limit_window_secs: env_or("RATE_LIMIT_WINDOW_SECS", 60),
+            upload_rules_file: env_opt("UPLOAD_RULES_FILE").map(PathBuf::from),
+            guest_quota_by: env_or("GUEST_QUOTA_BY", "session".to_string()).to_lowercase(),
+            duplicate_uploads: env_or("DUPLICATE_UPLOADS", "warn".to_string()).to_lowercase(),
+            deduplicate_storage: env_or("DEDUPLICATE_STORAGE", true),
+            reject_type_mismatch: env_or("REJECT_TYPE_MISMATCH", false),
+            encryption_key: env_opt("ENCRYPTION_KEY"),
+            encryption_previous_keys: env_opt("ENCRYPTION_PREVIOUS_KEYS")
+                .map(|keys| {
+                    keys.split(',')
+                        .map(str::trim)
+                        .filter(|key| !key.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            post_upload_commands: env_or("POST_UPLOAD_COMMANDS", false),
+            post_upload_command_allowlist: env_opt("POST_UPLOAD_COMMAND_ALLOWLIST")
+                .map(|programs| {
+                    programs
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|program| !program.is_empty())
+                        .map(PathBuf::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            post_upload_command_wrapper: env_opt("POST_UPLOAD_COMMAND_WRAPPER"),
+            post_upload_command_timeout_secs: env_or("POST_UPLOAD_COMMAND_TIMEOUT_SECS", 300),
+            wasm_plugins: env_opt("WASM_PLUGINS")
+                .map(|plugins| {
+                    plugins
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|plugin| !plugin.is_empty())
+                        .map(PathBuf::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            wasm_plugin_fuel: env_or("WASM_PLUGIN_FUEL", 10_000_000),
+            wasm_plugin_memory_mb: env_or("WASM_PLUGIN_MEMORY_MB", 16),
+            listen_reuse_port: env_or("LISTEN_REUSE_PORT", false),
+            shutdown_drain_secs: env_or("SHUTDOWN_DRAIN_SECS", 3600),
+            compress_responses: env_or("COMPRESS_RESPONSES", true),
+            admin_tls_addr: env_opt("ADMIN_TLS_ADDR"),
+            admin_tls_cert: env_opt("ADMIN_TLS_CERT").map(PathBuf::from),
+            admin_tls_key: env_opt("ADMIN_TLS_KEY").map(PathBuf::from),
+            admin_tls_client_ca: env_opt("ADMIN_TLS_CLIENT_CA").map(PathBuf::from),
+            admin_tls_cert_login: env_or("ADMIN_TLS_CERT_LOGIN", false),
+            jwt_issuer: env_opt("JWT_ISSUER"),
+            jwt_audience: env_opt("JWT_AUDIENCE"),
+            jwt_hs256_secret: env_opt("JWT_HS256_SECRET"),
+            jwt_public_keys: env_opt("JWT_PUBLIC_KEYS")
+                .map(|keys| {
+                    keys.split(',')
+                        .map(str::trim)
+                        .filter(|key| !key.is_empty())
+                        .map(PathBuf::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            jwt_leeway_secs: env_or("JWT_LEEWAY_SECS", 60),
+            session_idle_timeout_mins: env_or("SESSION_IDLE_TIMEOUT_MINS", 60),
+            session_max_age_hours: env_or("SESSION_MAX_AGE_HOURS", 12),
+            database_pool_size: env_or("DATABASE_POOL_SIZE", 8),
+            database_pool_timeout_secs: env_or("DATABASE_POOL_TIMEOUT_SECS", 30),
+        }
+    }
+}
+
+/// Read the config file, if there is one
+///
+/// Called once at startup, before [`Config::from_env`]. A missing
+/// `CONFIG_FILE` or a file that isn't valid TOML stops startup rather than
+/// running with half the intended settings.
+///
+/// # Returns
+/// The path of the file that was read
+pub fn load_file() -> Result<Option<PathBuf>, String> {
+    let path = match std::env::var("CONFIG_FILE") {
+        Ok(path) if !path.trim().is_empty() => PathBuf::from(path.trim()),
+        _ if Path::new(DEFAULT_CONFIG_FILE).is_file() => PathBuf::from(DEFAULT_CONFIG_FILE),
+        _ => return Ok(None),
+    };
+
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| format!("failed to read config file {}: {}", path.display(), e))?;
+    let table: toml::Table = text
+        .parse()
+        .map_err(|e| format!("invalid config file {}: {}", path.display(), e))?;
+
+    let mut settings = HashMap::new();
+    for (key, value) in table {
+        let value = file_value(value).ok_or_else(|| {
+            format!(
+                "config file {}: `{}` must be a value, not a table",
+                path.display(),
+                key
+            )
+        })?;
+        settings.insert(key.to_uppercase(), value);
+    }
+    let _ = FILE_SETTINGS.set(settings);
+
+    Ok(Some(path))
+}
+
+/// A config file value as it would be written in an environment variable
+///
+/// Arrays become comma-separated lists; tables have no such form.
+fn file_value(value: toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(value) => Some(value),
+        toml::Value::Array(items) => items
+            .into_iter()
+            .map(file_value)
+            .collect::<Option<Vec<_>>>()
+            .map(|items| items.join(",")),
+        toml::Value::Table(_) => None,
+        other => Some(other.to_string()),
+    }
+}
+
+/// Read and parse an environment variable, falling back to a default
+///
+/// Invalid values are logged and replaced by the default rather than
+/// aborting startup.
+fn env_or<T: FromStr>(key: &str, default: T) -> T {
+    match env_var(key) {
+        Some(value) => value.trim().parse().unwrap_or_else(|_| {
+            warn!(key = %key, value = %value, "Invalid configuration value, using default");
+            default
+        }),
+        None => default,
+    }
+}
+
+/// Read a strftime format, falling back to the default when it is invalid
+fn env_format(key: &str, default: &str) -> String {
+    match env_opt(key) {
+        Some(format) if crate::datetime::is_valid_format(&format) => format,
+        Some(format) => {
+            warn!(key = %key, value = %format, "Invalid date format, using default");
+            default.to_string()
+        }
+        None => default.to_string(),
+    }
+}
+
+/// Read a storage path template, falling back to the default when it is invalid
+fn env_path_template(key: &str, default: &str) -> String {
+    match env_opt(key) {
+        Some(template) if crate::storage::is_valid_template(&template) => template,
+        Some(template) => {
+            warn!(key = %key, value = %template, "Invalid storage path template, using default");
+            default.to_string()
+        }
+        None => default.to_string(),
+    }
+}
+
+/// Read an optional environment variable, treating empty values as unset
+fn env_opt(key: &str) -> Option<String> {
+    env_var(key)
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Read a setting from the environment, its `_FILE` variant, the secrets directory or the config file
+fn env_var(key: &str) -> Option<String> {
+    if let Ok(value) = std::env::var(key) {
+        return Some(value);
+    }
+
+    if let Ok(path) = std::env::var(format!("{}_FILE", key)) {
+        return read_secret(key, Path::new(path.trim()));
+    }
+
+    if let Ok(dir) = std::env::var("SECRETS_DIR") {
+        let dir = Path::new(dir.trim());
+        let secret = [key.to_string(), key.to_lowercase()]
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file());
+        if let Some(path) = secret {
+            return read_secret(key, &path);
+        }
+    }
+
+    FILE_SETTINGS
+        .get()
+        .and_then(|settings| settings.get(key))
+        .cloned()
+}
+
+/// Read a secret file, logging (but not failing on) unreadable files
+fn read_secret(key: &str, path: &Path) -> Option<String> {
+    match std::fs::read_to_string(path) {
+        Ok(value) => Some(value.trim().to_string()),
+        Err(e) => {
+            warn!(key = %key, path = %path.display(), error = %e, "Failed to read secret file, treating setting as unset");
+            None
+        }
+    }
+}
@@ -1,17 +1,46 @@
-use crate::models::*;
-use chrono::Utc;
+use crate::{config::Config, models::*};
+use chrono::{DateTime, Utc};
+use moka::sync::Cache;
+use r2d2_sqlite::SqliteConnectionManager;
 use rusqlite::{params, Connection, Result as SqliteResult};
-use std::{
-    path::Path,
-    sync::{Arc, Mutex},
-};
-use tracing::{debug, info};
+use std::{collections::HashSet, path::Path, time::Duration};
+use tracing::{debug, info, warn};
 use uuid::Uuid;
 
-pub fn init_database() -> Result<Arc<Mutex<Connection>>, Box<dyn std::error::Error>> {
-    let database_path = std::env::var("DATABASE_URL")
-        .unwrap_or_else(|_| "needadrop.db".to_string())
-        .replace("sqlite:", "");
+/// How long a cached upload link may be served before it is re-read from the database
+const LINK_CACHE_TTL_SECS: u64 = 30;
+
+/// Maximum number of upload links kept in the cache
+const LINK_CACHE_CAPACITY: u64 = 10_000;
+
+/// How long a statement waits for another connection's write lock before failing
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pool of SQLite connections shared by all handlers
+///
+/// Each function checks a connection out for the duration of its queries,
+/// so reads run in parallel; SQLite itself serializes writes.
+pub type DbPool = r2d2::Pool<SqliteConnectionManager>;
+
+// Cache of upload links keyed by token
+//
+// Every guest form view and upload looks its link up by token, so popular
+// links are served from memory. Functions that modify a link invalidate its
+// entry; the TTL bounds staleness for anything that slips through.
+lazy_static::lazy_static! {
+    static ref LINK_CACHE: Cache<String, UploadLink> = Cache::builder()
+        .max_capacity(LINK_CACHE_CAPACITY)
+        .time_to_live(Duration::from_secs(LINK_CACHE_TTL_SECS))
+        .build();
+}
+
+/// Drop a link from the token cache after it has been modified
+fn invalidate_cached_link(token: &str) {
+    LINK_CACHE.invalidate(token);
+}
+
+pub fn init_database(config: &Config) -> Result<DbPool, Box<dyn std::error::Error>> {
+    let database_path = config.database_url.replace("sqlite:", "");
 
     info!(database_path = %database_path, "Initializing database");
 
@@ -20,17 +49,51 @@ pub fn init_database() -> Result<Arc<Mutex<Connection>>, Box<dyn std::error::Err
         std::fs::create_dir_all(parent)?;
     }
 
-    debug!("Connecting to database");
-    let conn = Connection::open(&database_path)?;
+    debug!(
+        pool_size = config.database_pool_size,
+        "Connecting to database"
+    );
+    // WAL lets readers work while a write is in progress; the busy timeout
+    // makes a writer wait for another connection instead of failing at once
+    let manager = SqliteConnectionManager::file(&database_path).with_init(|conn| {
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")
+    });
+    let pool = r2d2::Pool::builder()
+        .max_size(config.database_pool_size.max(1))
+        .connection_timeout(Duration::from_secs(
+            config.database_pool_timeout_secs.max(1),
+        ))
+        .build(manager)?;
+    let conn = pool.get()?;
 
     info!("Running database migrations");
     create_tables(&conn)?;
 
+    info!("Preparing search index");
+    create_search_index(&conn)?;
+
     info!("Checking for default admin user");
     create_default_admin(&conn)?;
 
+    drop(conn);
     info!("Database initialization completed successfully");
-    Ok(Arc::new(Mutex::new(conn)))
+    Ok(pool)
+}
+
+/// Write the WAL back into the database file before the process exits
+///
+/// SQLite recovers from the WAL on its own, but a checkpointed database is a
+/// single self-contained file for backups taken while the server is stopped.
+pub fn close(db: &DbPool) {
+    let checkpoint = db.get().map_err(|e| e.to_string()).and_then(|conn| {
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+            .map_err(|e| e.to_string())
+    });
+    match checkpoint {
+        Ok(()) => info!("Database closed"),
+        Err(e) => warn!(error = %e, "Failed to checkpoint the database on shutdown"),
+    }
 }
 
 fn create_tables(conn: &Connection) -> SqliteResult<()> {
@@ -54,7 +117,7 @@ fn create_tables(conn: &Connection) -> SqliteResult<()> {
             id TEXT PRIMARY KEY,
             token TEXT UNIQUE NOT NULL,
             name TEXT NOT NULL,
-            max_file_size INTEGER NOT NULL,
+            total_quota INTEGER NOT NULL,
             remaining_quota INTEGER NOT NULL DEFAULT 0,
             expires_at TEXT,
             created_at TEXT NOT NULL,
@@ -82,389 +145,3885 @@ fn create_tables(conn: &Connection) -> SqliteResult<()> {
         [],
     )?;
 
-    // Try to add the remaining_quota column if it doesn't exist (migration)
-    let _ = conn.execute(
-        "ALTER TABLE upload_links ADD COLUMN remaining_quota INTEGER DEFAULT 0",
-        [],
-    );
+    // Create tasks table (persistent background job queue)
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS tasks (
+            id TEXT PRIMARY KEY,
+            kind TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            attempts INTEGER NOT NULL DEFAULT 0,
+            max_attempts INTEGER NOT NULL,
+            last_error TEXT,
+            run_at TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )
+        "#,
+        [],
+    )?;
+
+    // Admin-edited email templates; built-in defaults apply when a key has no row
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS email_templates (
+            key TEXT PRIMARY KEY,
+            subject TEXT NOT NULL,
+            body TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )
+        "#,
+        [],
+    )?;
+
+    // Resumable uploads in progress; the data received so far lives in a partial file
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS upload_sessions (
+            id TEXT PRIMARY KEY,
+            link_id TEXT NOT NULL,
+            original_filename TEXT NOT NULL,
+            content_type TEXT NOT NULL,
+            total_size INTEGER NOT NULL,
+            received INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (link_id) REFERENCES upload_links (id)
+        )
+        "#,
+        [],
+    )?;
+
+    // Uploads starred by an admin; each admin has their own stars
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS upload_stars (
+            admin_id TEXT NOT NULL,
+            upload_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (admin_id, upload_id)
+        )
+        "#,
+        [],
+    )?;
+
+    // Projects group links (and their uploads) per client or engagement
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS projects (
+            id TEXT PRIMARY KEY,
+            name TEXT UNIQUE NOT NULL,
+            quota_bytes INTEGER,
+            created_at TEXT NOT NULL
+        )
+        "#,
+        [],
+    )?;
+
+    // Record of sensitive admin actions, such as legal hold changes
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS audit_log (
+            id TEXT PRIMARY KEY,
+            actor TEXT NOT NULL,
+            action TEXT NOT NULL,
+            target_type TEXT NOT NULL,
+            target_id TEXT NOT NULL,
+            detail TEXT,
+            created_at TEXT NOT NULL
+        )
+        "#,
+        [],
+    )?;
+
+    // The total quota was called max_file_size before links had a separate per-file limit
+    let _ = conn.execute(
+        "ALTER TABLE upload_links RENAME COLUMN max_file_size TO total_quota",
+        [],
+    );
+
+    // Try to add the remaining_quota column if it doesn't exist (migration);
+    // links from before it start with their whole quota left
+    if conn
+        .execute(
+            "ALTER TABLE upload_links ADD COLUMN remaining_quota INTEGER DEFAULT 0",
+            [],
+        )
+        .is_ok()
+    {
+        conn.execute("UPDATE upload_links SET remaining_quota = total_quota", [])?;
+    }
+
+    // Optional size limit for a single file (NULL = only the quota applies)
+    let _ = conn.execute(
+        "ALTER TABLE upload_links ADD COLUMN per_file_limit INTEGER",
+        [],
+    );
+
+    // Optional number of days files are kept after upload (NULL = kept until deleted)
+    let _ = conn.execute(
+        "ALTER TABLE upload_links ADD COLUMN retention_days INTEGER",
+        [],
+    );
+
+    // Note from the admin shown on the guest upload page
+    let _ = conn.execute("ALTER TABLE upload_links ADD COLUMN guest_note TEXT", []);
+
+    // Optional per-link bandwidth limit in KB/s (NULL = unlimited)
+    let _ = conn.execute(
+        "ALTER TABLE upload_links ADD COLUMN rate_limit_kbps INTEGER",
+        [],
+    );
+
+    // Optional WebDAV folder that uploads on this link are pushed to
+    let _ = conn.execute("ALTER TABLE upload_links ADD COLUMN webdav_folder TEXT", []);
+
+    // Optional email address of the guest the link was created for
+    let _ = conn.execute("ALTER TABLE upload_links ADD COLUMN guest_email TEXT", []);
+
+    // Optional language code the guest upload page is always shown in
+    let _ = conn.execute("ALTER TABLE upload_links ADD COLUMN language TEXT", []);
+
+    // Optional branding of the guest upload page
+    let _ = conn.execute("ALTER TABLE upload_links ADD COLUMN logo_url TEXT", []);
+    let _ = conn.execute("ALTER TABLE upload_links ADD COLUMN accent_color TEXT", []);
+
+    // Optional project the link belongs to
+    let _ = conn.execute("ALTER TABLE upload_links ADD COLUMN project_id TEXT", []);
+
+    // When the link was archived (NULL = not archived)
+    let _ = conn.execute("ALTER TABLE upload_links ADD COLUMN archived_at TEXT", []);
+
+    // What happens to uploads under a filename that already exists on the link
+    let _ = conn.execute(
+        "ALTER TABLE upload_links ADD COLUMN collision_policy TEXT NOT NULL DEFAULT 'keep-both'",
+        [],
+    );
+
+    // Marks the shared link behind the public drop URL
+    let _ = conn.execute(
+        "ALTER TABLE upload_links ADD COLUMN is_public INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+
+    // Whether the expiry reminder email was sent
+    let _ = conn.execute(
+        "ALTER TABLE upload_links ADD COLUMN expiry_reminder_sent INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+
+    // Whether the link.expired event was sent. Links that expired before the
+    // column existed are marked as sent so upgrading doesn't replay old expiries.
+    if conn
+        .execute(
+            "ALTER TABLE upload_links ADD COLUMN expired_notified INTEGER NOT NULL DEFAULT 0",
+            [],
+        )
+        .is_ok()
+    {
+        conn.execute(
+            "UPDATE upload_links SET expired_notified = 1 WHERE expires_at IS NOT NULL AND expires_at <= ?",
+            [Utc::now().to_rfc3339()],
+        )?;
+    }
+
+    // Status of the copy on the replication target (NULL = not replicated)
+    let _ = conn.execute(
+        "ALTER TABLE file_uploads ADD COLUMN replication_status TEXT",
+        [],
+    );
+
+    // Directory the file is stored in; files stored before path templates lived in their guest folder
+    if conn
+        .execute("ALTER TABLE file_uploads ADD COLUMN storage_dir TEXT", [])
+        .is_ok()
+    {
+        conn.execute("UPDATE file_uploads SET storage_dir = guest_folder", [])?;
+    }
+
+    // Storage root the file was written to (NULL = the upload directory)
+    let _ = conn.execute("ALTER TABLE file_uploads ADD COLUMN volume TEXT", []);
+
+    // Version of a file among same-name uploads on its link. Existing
+    // duplicates are numbered in upload order when the column is added.
+    if conn
+        .execute(
+            "ALTER TABLE file_uploads ADD COLUMN version INTEGER NOT NULL DEFAULT 1",
+            [],
+        )
+        .is_ok()
+    {
+        conn.execute(
+            "UPDATE file_uploads SET version = ( \
+                 SELECT COUNT(*) FROM file_uploads earlier \
+                 WHERE earlier.link_id = file_uploads.link_id \
+                 AND earlier.original_filename = file_uploads.original_filename \
+                 AND earlier.uploaded_at <= file_uploads.uploaded_at)",
+            [],
+        )?;
+    }
+
+    // Legal hold blocks deletion of an upload, or of every upload on a link
+    let _ = conn.execute(
+        "ALTER TABLE upload_links ADD COLUMN legal_hold INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE file_uploads ADD COLUMN legal_hold INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+
+    // Who sent the file, so a person's data can be exported or erased on request
+    let _ = conn.execute("ALTER TABLE file_uploads ADD COLUMN uploader_ip TEXT", []);
+    let _ = conn.execute(
+        "ALTER TABLE file_uploads ADD COLUMN uploader_email TEXT",
+        [],
+    );
+
+    // Personal data scan results (NULL = not scanned) and whether an admin reviewed them
+    let _ = conn.execute("ALTER TABLE file_uploads ADD COLUMN pii_findings TEXT", []);
+    let _ = conn.execute(
+        "ALTER TABLE file_uploads ADD COLUMN pii_reviewed INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+
+    // Per-link command run after each upload, and the output tasks leave for the job log
+    let _ = conn.execute(
+        "ALTER TABLE upload_links ADD COLUMN post_upload_command TEXT",
+        [],
+    );
+    let _ = conn.execute("ALTER TABLE tasks ADD COLUMN output TEXT", []);
+
+    // Upload limit for each guest on a shared link (NULL = only the link's quota)
+    let _ = conn.execute(
+        "ALTER TABLE upload_links ADD COLUMN guest_quota INTEGER",
+        [],
+    );
+
+    // Limit on the number of files a link accepts, and how many it has received
+    let _ = conn.execute(
+        "ALTER TABLE upload_links ADD COLUMN max_uploads INTEGER",
+        [],
+    );
+    if conn
+        .execute(
+            "ALTER TABLE upload_links ADD COLUMN uploads_used INTEGER NOT NULL DEFAULT 0",
+            [],
+        )
+        .is_ok()
+    {
+        conn.execute(
+            "UPDATE upload_links SET uploads_used = \
+                (SELECT COUNT(*) FROM file_uploads WHERE file_uploads.link_id = upload_links.id)",
+            [],
+        )?;
+    }
+
+    // Rolling limit on the bytes a link accepts per 24 hours
+    let _ = conn.execute(
+        "ALTER TABLE upload_links ADD COLUMN daily_quota INTEGER",
+        [],
+    );
+
+    // Extensions and MIME types a link accepts
+    let _ = conn.execute(
+        "ALTER TABLE upload_links ADD COLUMN allowed_file_types TEXT",
+        [],
+    );
+
+    // Hash of each file's contents, to spot the same file uploaded twice
+    let _ = conn.execute("ALTER TABLE file_uploads ADD COLUMN content_hash TEXT", []);
+
+    // Verdict of the virus scanner for each stored file (NULL = not scanned)
+    let _ = conn.execute("ALTER TABLE file_uploads ADD COLUMN scan_result TEXT", []);
+
+    // Outcome and time of the last integrity check against the stored hash (NULL = never checked)
+    let _ = conn.execute(
+        "ALTER TABLE file_uploads ADD COLUMN integrity_ok INTEGER",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE file_uploads ADD COLUMN integrity_checked_at TEXT",
+        [],
+    );
+
+    // MIME type recognized from each file's contents, next to the declared one
+    let _ = conn.execute("ALTER TABLE file_uploads ADD COLUMN detected_mime TEXT", []);
+
+    // Where an upload came from: the browser or tool, and a name the guest gave
+    let _ = conn.execute(
+        "ALTER TABLE file_uploads ADD COLUMN uploader_user_agent TEXT",
+        [],
+    );
+    let _ = conn.execute("ALTER TABLE file_uploads ADD COLUMN uploader_name TEXT", []);
+
+    // Messages guests send along with their files, shared by the files of one submission
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS upload_messages (
+            id TEXT PRIMARY KEY,
+            link_id TEXT NOT NULL,
+            message TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (link_id) REFERENCES upload_links (id) ON DELETE CASCADE
+        )
+        "#,
+        [],
+    )?;
+    let _ = conn.execute("ALTER TABLE file_uploads ADD COLUMN message_id TEXT", []);
+
+    // Whether the admin is emailed about each file a link receives
+    let _ = conn.execute(
+        "ALTER TABLE upload_links ADD COLUMN notify_on_upload INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+
+    // Indexes for hot lookup paths. `upload_links.token` needs none of its own:
+    // the UNIQUE constraint already gives it an index (sqlite_autoindex_upload_links_2).
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_file_uploads_link_id ON file_uploads (link_id)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_file_uploads_uploaded_at ON file_uploads (uploaded_at)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_file_uploads_link_filename ON file_uploads (link_id, original_filename)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_file_uploads_guest_folder ON file_uploads (guest_folder)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_tasks_status_run_at ON tasks (status, run_at)",
+        [],
+    )?;
+    // Uploads that were turned away, shown per link
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS upload_attempts (
+            id TEXT PRIMARY KEY,
+            link_id TEXT NOT NULL,
+            filename TEXT,
+            reason TEXT NOT NULL,
+            detail TEXT,
+            uploader_ip TEXT,
+            attempted_at TEXT NOT NULL,
+            FOREIGN KEY (link_id) REFERENCES upload_links (id) ON DELETE CASCADE
+        )
+        "#,
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_upload_attempts_link_id ON upload_attempts (link_id, attempted_at)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_upload_attempts_uploader_ip ON upload_attempts (uploader_ip)",
+        [],
+    )?;
+    // Request the refusal happened in, to find it in the logs
+    let _ = conn.execute("ALTER TABLE upload_attempts ADD COLUMN request_id TEXT", []);
+
+    // Keys for the REST API; only a SHA-256 hash of each key is kept
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS api_keys (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            key_hash TEXT UNIQUE NOT NULL,
+            prefix TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            last_used_at TEXT
+        )
+        "#,
+        [],
+    )?;
+
+    // Endpoints that server events are delivered to
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS webhooks (
+            id TEXT PRIMARY KEY,
+            url TEXT NOT NULL,
+            secret TEXT NOT NULL,
+            events TEXT,
+            created_at TEXT NOT NULL
+        )
+        "#,
+        [],
+    )?;
+
+    // Share links hand files to guests for download, and the files they offer
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS share_links (
+            id TEXT PRIMARY KEY,
+            token TEXT UNIQUE NOT NULL,
+            name TEXT NOT NULL,
+            expires_at TEXT,
+            max_downloads INTEGER,
+            download_count INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL
+        )
+        "#,
+        [],
+    )?;
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS shared_files (
+            id TEXT PRIMARY KEY,
+            share_link_id TEXT NOT NULL,
+            upload_id TEXT,
+            stored_filename TEXT,
+            original_filename TEXT NOT NULL,
+            file_size INTEGER NOT NULL,
+            mime_type TEXT NOT NULL,
+            added_at TEXT NOT NULL,
+            FOREIGN KEY (share_link_id) REFERENCES share_links (id) ON DELETE CASCADE
+        )
+        "#,
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_shared_files_share_link_id ON shared_files (share_link_id)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_shared_files_upload_id ON shared_files (upload_id)",
+        [],
+    )?;
+
+    // Whether each stored file is encrypted at rest, and with which key
+    // (NULL = stored before this was recorded; see `encryption::record_existing`)
+    let _ = conn.execute("ALTER TABLE file_uploads ADD COLUMN encrypted INTEGER", []);
+    let _ = conn.execute(
+        "ALTER TABLE file_uploads ADD COLUMN encryption_key_id TEXT",
+        [],
+    );
+    let _ = conn.execute("ALTER TABLE shared_files ADD COLUMN encrypted INTEGER", []);
+    let _ = conn.execute(
+        "ALTER TABLE shared_files ADD COLUMN encryption_key_id TEXT",
+        [],
+    );
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_audit_log_created_at ON audit_log (created_at)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_audit_log_target_id ON audit_log (target_id)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_file_uploads_uploader_email ON file_uploads (uploader_email)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_file_uploads_content_hash ON file_uploads (content_hash)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_file_uploads_stored_filename ON file_uploads (stored_filename)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_file_uploads_uploader_ip ON file_uploads (uploader_ip)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_file_uploads_link_hash ON file_uploads (link_id, content_hash)",
+        [],
+    )?;
+
+    // Optional per-admin display time zone (NULL = server default)
+    let _ = conn.execute("ALTER TABLE admins ADD COLUMN timezone TEXT", []);
+
+    // Per-admin light/dark theme (NULL = follow the theme cookie)
+    let _ = conn.execute("ALTER TABLE admins ADD COLUMN theme TEXT", []);
+
+    // Owners may release legal holds. On upgrade the oldest admin becomes the owner.
+    if conn
+        .execute(
+            "ALTER TABLE admins ADD COLUMN is_owner INTEGER NOT NULL DEFAULT 0",
+            [],
+        )
+        .is_ok()
+    {
+        conn.execute(
+            "UPDATE admins SET is_owner = 1 WHERE id = (SELECT id FROM admins ORDER BY created_at LIMIT 1)",
+            [],
+        )?;
+    }
+
+    // Roles replace the owner flag: owners stay owners, every other admin becomes a manager
+    if conn
+        .execute(
+            "ALTER TABLE admins ADD COLUMN role TEXT NOT NULL DEFAULT 'manager'",
+            [],
+        )
+        .is_ok()
+    {
+        conn.execute("UPDATE admins SET role = 'owner' WHERE is_owner = 1", [])?;
+    }
+
+    // Two-factor secret (NULL = password only) and the last time step a code was used for
+    let _ = conn.execute("ALTER TABLE admins ADD COLUMN totp_secret TEXT", []);
+    let _ = conn.execute("ALTER TABLE admins ADD COLUMN totp_last_step INTEGER", []);
+
+    Ok(())
+}
+
+/// Layout of the search index and its triggers, kept in the database's `user_version`
+///
+/// Raise it whenever the triggers or the indexed text change; the index is
+/// then rebuilt on the next start.
+const SEARCH_INDEX_VERSION: i64 = 2;
+
+/// Full-text index (FTS5) behind the admin search
+///
+/// One row per upload (original filename and the guest's message) and per
+/// link (name, guest email and note for guests), kept in sync by triggers. The index is
+/// filled from the existing rows when it is created or its layout changed.
+fn create_search_index(conn: &Connection) -> SqliteResult<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'search_index')",
+        [],
+        |row| row.get(0),
+    )?;
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    let outdated = exists && version < SEARCH_INDEX_VERSION;
+
+    // Triggers from an older layout are replaced and the rows indexed again
+    if outdated {
+        conn.execute_batch(
+            r#"
+            DROP TRIGGER IF EXISTS search_upload_insert;
+            DROP TRIGGER IF EXISTS search_upload_update;
+            DROP TRIGGER IF EXISTS search_upload_delete;
+            DROP TRIGGER IF EXISTS search_link_insert;
+            DROP TRIGGER IF EXISTS search_link_update;
+            DROP TRIGGER IF EXISTS search_link_delete;
+            DELETE FROM search_index;
+            "#,
+        )?;
+    }
+
+    conn.execute_batch(
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+            kind UNINDEXED,
+            item_id UNINDEXED,
+            title,
+            body,
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS search_upload_insert AFTER INSERT ON file_uploads BEGIN
+            INSERT INTO search_index (kind, item_id, title, body)
+            VALUES ('upload', new.id, new.original_filename,
+                COALESCE((SELECT message FROM upload_messages WHERE id = new.message_id), ''));
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_upload_update AFTER UPDATE OF original_filename, message_id ON file_uploads BEGIN
+            UPDATE search_index SET title = new.original_filename,
+                body = COALESCE((SELECT message FROM upload_messages WHERE id = new.message_id), '')
+            WHERE kind = 'upload' AND item_id = new.id;
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_upload_delete AFTER DELETE ON file_uploads BEGIN
+            DELETE FROM search_index WHERE kind = 'upload' AND item_id = old.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS search_link_insert AFTER INSERT ON upload_links BEGIN
+            INSERT INTO search_index (kind, item_id, title, body)
+            VALUES ('link', new.id, new.name,
+                COALESCE(new.guest_email, '') || ' ' || COALESCE(new.guest_note, ''));
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_link_update AFTER UPDATE OF name, guest_email, guest_note ON upload_links BEGIN
+            UPDATE search_index SET title = new.name,
+                body = COALESCE(new.guest_email, '') || ' ' || COALESCE(new.guest_note, '')
+            WHERE kind = 'link' AND item_id = new.id;
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_link_delete AFTER DELETE ON upload_links BEGIN
+            DELETE FROM search_index WHERE kind = 'link' AND item_id = old.id;
+        END;
+        "#,
+    )?;
+
+    if !exists || outdated {
+        conn.execute_batch(
+            r#"
+            INSERT INTO search_index (kind, item_id, title, body)
+                SELECT 'upload', u.id, u.original_filename, COALESCE(m.message, '')
+                FROM file_uploads u LEFT JOIN upload_messages m ON m.id = u.message_id;
+            INSERT INTO search_index (kind, item_id, title, body)
+                SELECT 'link', id, name, COALESCE(guest_email, '') || ' ' || COALESCE(guest_note, '')
+                FROM upload_links;
+            "#,
+        )?;
+    }
+    conn.execute_batch(&format!("PRAGMA user_version = {}", SEARCH_INDEX_VERSION))?;
+
+    Ok(())
+}
+
+fn create_default_admin(conn: &Connection) -> SqliteResult<()> {
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM admins", [], |row| row.get(0))?;
+
+    if count == 0 {
+        let admin_id = Uuid::new_v4().to_string();
+        let password_hash = bcrypt::hash("admin123", bcrypt::DEFAULT_COST)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+
+        conn.execute(
+            "INSERT INTO admins (id, username, password_hash, created_at, is_owner, role) VALUES (?1, ?2, ?3, ?4, 1, 'owner')",
+            params![admin_id, "admin", password_hash, Utc::now().to_rfc3339()],
+        )?;
+
+        info!("Created default admin user: admin/admin123");
+    }
+
+    Ok(())
+}
+
+/// Column list used by every upload link query, in the order expected by `row_to_upload_link`
+const UPLOAD_LINK_COLUMNS: &str = "id, token, name, total_quota, remaining_quota, expires_at, created_at, is_active, rate_limit_kbps, webdav_folder, guest_email, language, logo_url, accent_color, is_public, project_id, archived_at, collision_policy, legal_hold, post_upload_command, guest_quota, max_uploads, uploads_used, daily_quota, allowed_file_types, notify_on_upload, per_file_limit, retention_days, guest_note";
+
+/// Map a row selected with `UPLOAD_LINK_COLUMNS` to an `UploadLink`
+fn row_to_upload_link(row: &rusqlite::Row) -> SqliteResult<UploadLink> {
+    upload_link_from_row(row, 0)
+}
+
+/// Map `UPLOAD_LINK_COLUMNS` starting at column `offset` to an `UploadLink` (used by joins)
+fn upload_link_from_row(row: &rusqlite::Row, offset: usize) -> SqliteResult<UploadLink> {
+    let expires_at_str: Option<String> = row.get(offset + 5)?;
+    let expires_at = expires_at_str.map(|s| {
+        chrono::DateTime::parse_from_rfc3339(&s)
+            .unwrap()
+            .with_timezone(&Utc)
+    });
+
+    Ok(UploadLink {
+        id: row.get(offset)?,
+        token: row.get(offset + 1)?,
+        name: row.get(offset + 2)?,
+        total_quota: row.get(offset + 3)?,
+        remaining_quota: row.get(offset + 4)?,
+        expires_at,
+        created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(offset + 6)?)
+            .unwrap()
+            .with_timezone(&Utc),
+        is_active: row.get(offset + 7)?,
+        rate_limit_kbps: row.get(offset + 8)?,
+        webdav_folder: row.get(offset + 9)?,
+        guest_email: row.get(offset + 10)?,
+        language: row.get(offset + 11)?,
+        logo_url: row.get(offset + 12)?,
+        accent_color: row.get(offset + 13)?,
+        is_public: row.get(offset + 14)?,
+        project_id: row.get(offset + 15)?,
+        archived_at: row.get::<_, Option<String>>(offset + 16)?.map(|s| {
+            chrono::DateTime::parse_from_rfc3339(&s)
+                .unwrap()
+                .with_timezone(&Utc)
+        }),
+        collision_policy: CollisionPolicy::from_code(&row.get::<_, String>(offset + 17)?)
+            .unwrap_or_default(),
+        legal_hold: row.get(offset + 18)?,
+        post_upload_command: row.get(offset + 19)?,
+        guest_quota: row.get(offset + 20)?,
+        max_uploads: row.get(offset + 21)?,
+        uploads_used: row.get(offset + 22)?,
+        daily_quota: row.get(offset + 23)?,
+        allowed_file_types: row.get(offset + 24)?,
+        notify_on_upload: row.get(offset + 25)?,
+        per_file_limit: row.get(offset + 26)?,
+        retention_days: row.get(offset + 27)?,
+        guest_note: row.get(offset + 28)?,
+    })
+}
+
+/// Column list used by file upload queries, in the order expected by `row_to_file_upload`
+const FILE_UPLOAD_COLUMNS: &str =
+    "id, link_id, original_filename, stored_filename, file_size, mime_type, uploaded_at, guest_folder, replication_status, version, storage_dir, volume, legal_hold, uploader_ip, uploader_email, pii_findings, pii_reviewed, content_hash, scan_result, integrity_ok, integrity_checked_at, detected_mime, uploader_user_agent, uploader_name, message_id, encryption_key_id";
+
+/// Map a row selected with `FILE_UPLOAD_COLUMNS` to a `FileUpload`
+fn row_to_file_upload(row: &rusqlite::Row) -> SqliteResult<FileUpload> {
+    file_upload_from_row(row, 0)
+}
+
+/// Map `FILE_UPLOAD_COLUMNS` starting at column `offset` to a `FileUpload` (used by joins)
+fn file_upload_from_row(row: &rusqlite::Row, offset: usize) -> SqliteResult<FileUpload> {
+    Ok(FileUpload {
+        id: row.get(offset)?,
+        link_id: row.get(offset + 1)?,
+        original_filename: row.get(offset + 2)?,
+        stored_filename: row.get(offset + 3)?,
+        file_size: row.get(offset + 4)?,
+        mime_type: row.get(offset + 5)?,
+        uploaded_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(offset + 6)?)
+            .unwrap()
+            .with_timezone(&Utc),
+        guest_folder: row.get(offset + 7)?,
+        replication_status: row.get(offset + 8)?,
+        version: row.get(offset + 9)?,
+        storage_dir: row.get(offset + 10)?,
+        volume: row.get(offset + 11)?,
+        legal_hold: row.get(offset + 12)?,
+        uploader_ip: row.get(offset + 13)?,
+        uploader_email: row.get(offset + 14)?,
+        pii_findings: row.get(offset + 15)?,
+        pii_reviewed: row.get(offset + 16)?,
+        content_hash: row.get(offset + 17)?,
+        scan_result: row.get(offset + 18)?,
+        integrity_ok: row.get(offset + 19)?,
+        integrity_checked_at: row.get::<_, Option<String>>(offset + 20)?.map(|s| {
+            chrono::DateTime::parse_from_rfc3339(&s)
+                .unwrap()
+                .with_timezone(&Utc)
+        }),
+        detected_mime: row.get(offset + 21)?,
+        uploader_user_agent: row.get(offset + 22)?,
+        uploader_name: row.get(offset + 23)?,
+        message_id: row.get(offset + 24)?,
+        encryption_key_id: row.get(offset + 25)?,
+    })
+}
+
+/// Qualify every column in a column list with a table alias (`id, name` -> `l.id, l.name`)
+fn qualified_columns(columns: &str, alias: &str) -> String {
+    columns
+        .split(',')
+        .map(|column| format!("{}.{}", alias, column.trim()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Column list used by every admin query, in the order expected by `row_to_admin`
+const ADMIN_COLUMNS: &str =
+    "id, username, password_hash, created_at, timezone, theme, role, totp_secret";
+
+fn row_to_admin(row: &rusqlite::Row) -> SqliteResult<Admin> {
+    Ok(Admin {
+        id: row.get(0)?,
+        username: row.get(1)?,
+        password_hash: row.get(2)?,
+        created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
+            .unwrap()
+            .with_timezone(&Utc),
+        timezone: row.get(4)?,
+        theme: row.get(5)?,
+        role: AdminRole::from_code(&row.get::<_, String>(6)?).unwrap_or_default(),
+        totp_secret: row.get(7)?,
+    })
+}
+
+// Database query functions
+pub fn get_admin_by_username(
+    db: &DbPool,
+    username: &str,
+) -> Result<Option<Admin>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM admins WHERE username = ?",
+        ADMIN_COLUMNS
+    ))?;
+
+    let admin_result = stmt.query_row([username], row_to_admin);
+
+    match admin_result {
+        Ok(admin) => Ok(Some(admin)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+pub fn create_upload_link(
+    db: &DbPool,
+    link: &NewUploadLink,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let link_id = Uuid::new_v4().to_string();
+    let token = Uuid::new_v4().to_string();
+
+    conn.execute(
+        "INSERT INTO upload_links (id, token, name, total_quota, remaining_quota, expires_at, created_at, is_active, rate_limit_kbps, webdav_folder, guest_email, language, logo_url, accent_color, project_id, collision_policy, post_upload_command, guest_quota, max_uploads, daily_quota, allowed_file_types, notify_on_upload, per_file_limit, retention_days, guest_note) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            &link_id,
+            &token,
+            link.name,
+            link.total_quota,
+            link.total_quota, // remaining_quota starts as total_quota
+            link.expires_at.map(|dt| dt.to_rfc3339()),
+            Utc::now().to_rfc3339(),
+            true,
+            link.rate_limit_kbps,
+            link.webdav_folder,
+            link.guest_email,
+            link.language,
+            link.logo_url,
+            link.accent_color,
+            link.project_id,
+            link.collision_policy.code(),
+            link.post_upload_command,
+            link.guest_quota,
+            link.max_uploads,
+            link.daily_quota,
+            link.allowed_file_types,
+            link.notify_on_upload,
+            link.per_file_limit,
+            link.retention_days,
+            link.guest_note,
+        ],
+    )?;
+
+    Ok(token)
+}
+
+pub fn get_upload_link_by_token(
+    db: &DbPool,
+    token: &str,
+) -> Result<Option<UploadLink>, Box<dyn std::error::Error>> {
+    if let Some(link) = LINK_CACHE.get(token) {
+        return Ok(Some(link));
+    }
+
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM upload_links WHERE token = ?",
+        UPLOAD_LINK_COLUMNS
+    ))?;
+
+    let link_result = stmt.query_row([token], row_to_upload_link);
+
+    match link_result {
+        Ok(link) => {
+            LINK_CACHE.insert(token.to_string(), link.clone());
+            Ok(Some(link))
+        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+/// The link behind the public drop URL, if one was ever set up
+pub fn get_public_upload_link(
+    db: &DbPool,
+) -> Result<Option<UploadLink>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM upload_links WHERE is_public = 1 ORDER BY created_at LIMIT 1",
+        UPLOAD_LINK_COLUMNS
+    ))?;
+
+    match stmt.query_row([], row_to_upload_link) {
+        Ok(link) => Ok(Some(link)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+/// Create or update the public drop link so it matches the configuration
+///
+/// A changed quota is applied to the remaining quota as a difference, so
+/// restarting with the same settings never refills a used-up quota.
+pub fn upsert_public_upload_link(
+    db: &DbPool,
+    name: &str,
+    total_quota: i64,
+    rate_limit_kbps: Option<i64>,
+) -> Result<UploadLink, Box<dyn std::error::Error>> {
+    if let Some(link) = get_public_upload_link(db)? {
+        let conn = db.get()?;
+        conn.execute(
+            "UPDATE upload_links SET name = ?, total_quota = ?, \
+             remaining_quota = MAX(remaining_quota + ? - total_quota, 0), \
+             rate_limit_kbps = ?, expires_at = NULL, archived_at = NULL, is_active = 1 WHERE id = ?",
+            params![
+                name,
+                total_quota,
+                total_quota,
+                rate_limit_kbps,
+                &link.id
+            ],
+        )?;
+        drop(conn);
+        invalidate_cached_link(&link.token);
+    } else {
+        let token = create_upload_link(
+            db,
+            &NewUploadLink {
+                name,
+                total_quota,
+                rate_limit_kbps,
+                ..Default::default()
+            },
+        )?;
+        let conn = db.get()?;
+        conn.execute(
+            "UPDATE upload_links SET is_public = 1 WHERE token = ?",
+            [&token],
+        )?;
+    }
+
+    get_public_upload_link(db)?.ok_or_else(|| "Public drop link vanished".into())
+}
+
+/// Deactivate the public drop link while the public drop is switched off
+pub fn deactivate_public_upload_link(db: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt =
+        conn.prepare("UPDATE upload_links SET is_active = 0 WHERE is_public = 1 RETURNING token")?;
+    let tokens = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    for token in tokens {
+        invalidate_cached_link(&token?);
+    }
+
+    Ok(())
+}
+
+pub fn get_upload_link_by_id(
+    db: &DbPool,
+    id: &str,
+) -> Result<Option<UploadLink>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM upload_links WHERE id = ?",
+        UPLOAD_LINK_COLUMNS
+    ))?;
+
+    let link_result = stmt.query_row([id], row_to_upload_link);
+
+    match link_result {
+        Ok(link) => Ok(Some(link)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+pub fn get_all_upload_links(db: &DbPool) -> Result<Vec<UploadLink>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM upload_links ORDER BY created_at DESC",
+        UPLOAD_LINK_COLUMNS
+    ))?;
+
+    let link_iter = stmt.query_map([], row_to_upload_link)?;
+
+    let mut links = Vec::new();
+    for link in link_iter {
+        links.push(link?);
+    }
+
+    Ok(links)
+}
+
+/// Load one page of the links list in the given order, with the number of links across all pages
+///
+/// Lists the archived links instead of the current ones when `archived` is set.
+pub fn get_upload_links_page(
+    db: &DbPool,
+    project: Option<&str>,
+    archived: bool,
+    sort: ListSort,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<UploadLink>, i64), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    // Sort columns come from a fixed list, never from the request
+    let order_column = match sort.column {
+        SortColumn::Name => "name COLLATE NOCASE",
+        SortColumn::CreatedAt => "created_at",
+        SortColumn::Size => "total_quota",
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM upload_links \
+         WHERE (archived_at IS NOT NULL) = ?1 AND (?2 IS NULL OR project_id = ?2) \
+         ORDER BY {} {}, created_at DESC LIMIT ?3 OFFSET ?4",
+        UPLOAD_LINK_COLUMNS,
+        order_column,
+        sort.direction.sql()
+    ))?;
+
+    let link_iter = stmt.query_map(
+        params![archived, project, limit, offset],
+        row_to_upload_link,
+    )?;
+
+    let mut links = Vec::new();
+    for link in link_iter {
+        links.push(link?);
+    }
+
+    let total = conn.query_row(
+        "SELECT COUNT(*) FROM upload_links \
+         WHERE (archived_at IS NOT NULL) = ?1 AND (?2 IS NULL OR project_id = ?2)",
+        params![archived, project],
+        |row| row.get(0),
+    )?;
+
+    Ok((links, total))
+}
+
+/// Count the current or archived links, optionally within a project
+pub fn count_upload_links(
+    db: &DbPool,
+    project: Option<&str>,
+    archived: bool,
+) -> Result<i64, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let count = conn.query_row(
+        "SELECT COUNT(*) FROM upload_links \
+         WHERE (archived_at IS NOT NULL) = ?1 AND (?2 IS NULL OR project_id = ?2)",
+        params![archived, project],
+        |row| row.get(0),
+    )?;
+
+    Ok(count)
+}
+
+/// Archive or restore links, returning how many changed
+pub fn set_links_archived(
+    db: &DbPool,
+    ids: &[&str],
+    archived: bool,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let archived_at = archived.then(|| Utc::now().to_rfc3339());
+    let mut stmt = conn.prepare(
+        "UPDATE upload_links SET archived_at = ? \
+         WHERE id = ? AND (archived_at IS NULL) = ? RETURNING token",
+    )?;
+
+    let mut changed = 0;
+    for id in ids {
+        let tokens = stmt.query_map(params![archived_at, id, archived], |row| {
+            row.get::<_, String>(0)
+        })?;
+        for token in tokens {
+            invalidate_cached_link(&token?);
+            changed += 1;
+        }
+    }
+
+    Ok(changed)
+}
+
+/// Pause an active link or resume a paused one
+///
+/// The public drop link is left alone, as `PUBLIC_DROP_ENABLED` decides
+/// whether it is active.
+///
+/// # Returns
+/// The link's new active flag, or None if there's no such (non-public) link
+pub fn toggle_upload_link_active(
+    db: &DbPool,
+    id: &str,
+) -> Result<Option<bool>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let result = conn.query_row(
+        "UPDATE upload_links SET is_active = NOT is_active \
+         WHERE id = ? AND is_public = 0 RETURNING token, is_active",
+        [id],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?)),
+    );
+
+    match result {
+        Ok((token, is_active)) => {
+            invalidate_cached_link(&token);
+            Ok(Some(is_active))
+        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn delete_upload_link(db: &DbPool, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    // Foreign keys aren't enforced, so the link's refused uploads go by hand
+    conn.execute("DELETE FROM upload_attempts WHERE link_id = ?", [id])?;
+
+    let token_result = conn.query_row(
+        "DELETE FROM upload_links WHERE id = ? RETURNING token",
+        [id],
+        |row| row.get::<_, String>(0),
+    );
+
+    match token_result {
+        Ok(token) => {
+            invalidate_cached_link(&token);
+            Ok(())
+        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(()),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+/// Change a link's name, quotas, expiry, retention period, guest note and active flag
+///
+/// A changed quota is applied to the remaining quota as a difference, so
+/// space already used stays used. A new expiry re-arms the expiry reminder
+/// and the link.expired event. Returns false if there is no such link.
+#[allow(clippy::too_many_arguments)]
+pub fn update_upload_link(
+    db: &DbPool,
+    id: &str,
+    name: &str,
+    total_quota: i64,
+    per_file_limit: Option<i64>,
+    expires_at: Option<DateTime<Utc>>,
+    retention_days: Option<i64>,
+    guest_note: Option<&str>,
+    is_active: bool,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    // SET expressions see the row as it was, so the flags compare against the old
+    // expiry and the remaining quota against the old quota
+    let token_result = conn.query_row(
+        "UPDATE upload_links SET name = ?1, is_active = ?3, \
+             remaining_quota = MAX(remaining_quota + ?5 - total_quota, 0), \
+             total_quota = ?5, per_file_limit = ?6, retention_days = ?7, guest_note = ?8, \
+             expiry_reminder_sent = CASE WHEN expires_at IS ?2 THEN expiry_reminder_sent ELSE 0 END, \
+             expired_notified = CASE WHEN expires_at IS ?2 THEN expired_notified ELSE 0 END, \
+             expires_at = ?2 \
+         WHERE id = ?4 RETURNING token",
+        params![
+            name,
+            expires_at.map(|dt| dt.to_rfc3339()),
+            is_active,
+            id,
+            total_quota,
+            per_file_limit,
+            retention_days,
+            guest_note
+        ],
+        |row| row.get::<_, String>(0),
+    );
+
+    match token_result {
+        Ok(token) => {
+            invalidate_cached_link(&token);
+            Ok(true)
+        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(false),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+/// Find links that expired since the last check and mark them as notified
+///
+/// Each expired link is returned exactly once, so the caller can announce
+/// the expiry without sending duplicates across sweeps or restarts.
+pub fn claim_newly_expired_links(
+    db: &DbPool,
+) -> Result<Vec<UploadLink>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "UPDATE upload_links SET expired_notified = 1 \
+         WHERE expires_at IS NOT NULL AND expires_at <= ? AND expired_notified = 0 \
+         RETURNING {}",
+        UPLOAD_LINK_COLUMNS
+    ))?;
+
+    let link_iter = stmt.query_map([Utc::now().to_rfc3339()], row_to_upload_link)?;
+
+    let mut links = Vec::new();
+    for link in link_iter {
+        links.push(link?);
+    }
+
+    Ok(links)
+}
+
+/// Deactivate every active link past its expiry time and return them
+///
+/// The public drop link is left alone; it has no expiry of its own.
+pub fn deactivate_expired_links(
+    db: &DbPool,
+) -> Result<Vec<UploadLink>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "UPDATE upload_links SET is_active = 0 \
+         WHERE is_active = 1 AND is_public = 0 AND expires_at IS NOT NULL AND expires_at <= ? \
+         RETURNING {}",
+        UPLOAD_LINK_COLUMNS
+    ))?;
+
+    let link_iter = stmt.query_map([Utc::now().to_rfc3339()], row_to_upload_link)?;
+
+    let mut links = Vec::new();
+    for link in link_iter {
+        let link = link?;
+        invalidate_cached_link(&link.token);
+        links.push(link);
+    }
+
+    Ok(links)
+}
+
+/// Uploads of links that expired before `cutoff`, except those under legal hold
+pub fn get_uploads_of_links_expired_before(
+    db: &DbPool,
+    cutoff: DateTime<Utc>,
+) -> Result<Vec<FileUpload>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM file_uploads f JOIN upload_links l ON l.id = f.link_id \
+         WHERE l.expires_at IS NOT NULL AND l.expires_at <= ? \
+         AND f.legal_hold = 0 AND l.legal_hold = 0 \
+         ORDER BY f.link_id, f.uploaded_at",
+        qualified_columns(FILE_UPLOAD_COLUMNS, "f")
+    ))?;
+
+    let upload_iter = stmt.query_map([cutoff.to_rfc3339()], row_to_file_upload)?;
+
+    let mut uploads = Vec::new();
+    for upload in upload_iter {
+        uploads.push(upload?);
+    }
+
+    Ok(uploads)
+}
+
+/// Uploads kept longer than their link's retention period, except those under legal hold
+pub fn get_uploads_past_retention(
+    db: &DbPool,
+) -> Result<Vec<FileUpload>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM file_uploads f JOIN upload_links l ON l.id = f.link_id \
+         WHERE l.retention_days IS NOT NULL \
+         AND julianday(f.uploaded_at) + l.retention_days <= julianday(?) \
+         AND f.legal_hold = 0 AND l.legal_hold = 0 \
+         ORDER BY f.link_id, f.uploaded_at",
+        qualified_columns(FILE_UPLOAD_COLUMNS, "f")
+    ))?;
+
+    let upload_iter = stmt.query_map([Utc::now().to_rfc3339()], row_to_file_upload)?;
+
+    let mut uploads = Vec::new();
+    for upload in upload_iter {
+        uploads.push(upload?);
+    }
+
+    Ok(uploads)
+}
+
+/// Find links due for an expiry reminder and mark the reminder as sent
+///
+/// A link is due when it expires within `hours`, is still active, has quota
+/// left and has not received a single upload. Each link is returned once.
+pub fn claim_links_due_for_expiry_reminder(
+    db: &DbPool,
+    hours: i64,
+) -> Result<Vec<UploadLink>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let now = Utc::now();
+    let mut stmt = conn.prepare(&format!(
+        "UPDATE upload_links SET expiry_reminder_sent = 1 \
+         WHERE expiry_reminder_sent = 0 AND is_active = 1 AND archived_at IS NULL AND remaining_quota > 0 \
+         AND expires_at IS NOT NULL AND expires_at > ? AND expires_at <= ? \
+         AND NOT EXISTS (SELECT 1 FROM file_uploads WHERE file_uploads.link_id = upload_links.id) \
+         RETURNING {}",
+        UPLOAD_LINK_COLUMNS
+    ))?;
+
+    let link_iter = stmt.query_map(
+        params![
+            now.to_rfc3339(),
+            (now + chrono::Duration::hours(hours)).to_rfc3339()
+        ],
+        row_to_upload_link,
+    )?;
+
+    let mut links = Vec::new();
+    for link in link_iter {
+        links.push(link?);
+    }
+
+    Ok(links)
+}
+
+pub fn create_file_upload(
+    db: &DbPool,
+    upload: &NewFileUpload,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let id = Uuid::new_v4().to_string();
+    let uploaded_at = Utc::now();
+
+    // A file with a name that already exists on the link becomes its next version
+    conn.execute(
+        "INSERT INTO file_uploads (id, link_id, original_filename, stored_filename, file_size, mime_type, uploaded_at, guest_folder, storage_dir, volume, uploader_ip, uploader_email, content_hash, scan_result, detected_mime, uploader_user_agent, uploader_name, encrypted, encryption_key_id, version) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, \
+             (SELECT COALESCE(MAX(version), 0) + 1 FROM file_uploads WHERE link_id = ?2 AND original_filename = ?3))",
+        params![
+            &id,
+            upload.link_id,
+            upload.original_filename,
+            upload.stored_filename,
+            upload.file_size,
+            upload.mime_type,
+            uploaded_at.to_rfc3339(),
+            upload.guest_folder,
+            upload.storage_dir,
+            upload.volume,
+            upload.uploader.ip,
+            upload.uploader.email.as_deref().map(str::to_lowercase),
+            upload.content_hash,
+            upload.scan_result,
+            upload.detected_mime,
+            upload.uploader.user_agent,
+            upload.uploader.name,
+            upload.encryption_key_id.is_some(),
+            upload.encryption_key_id,
+        ],
+    )?;
+
+    Ok(id)
+}
+
+/// Count active links and uploaded files for the admin dashboard
+///
+/// Mirrors `UploadLink::is_valid` in SQL. Expiry timestamps are stored as
+/// RFC 3339 strings in UTC, which sort chronologically as plain text.
+pub fn get_dashboard_stats(db: &DbPool) -> Result<DashboardStats, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let active_links = conn.query_row(
+        "SELECT COUNT(*) FROM upload_links WHERE is_active = 1 AND archived_at IS NULL AND remaining_quota > 0 AND (max_uploads IS NULL OR uploads_used < max_uploads) AND (expires_at IS NULL OR expires_at > ?)",
+        [Utc::now().to_rfc3339()],
+        |row| row.get(0),
+    )?;
+
+    let (total_uploads, total_upload_size) = conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(file_size), 0) FROM file_uploads",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+
+    Ok(DashboardStats {
+        active_links,
+        total_uploads,
+        total_upload_size,
+    })
+}
+
+/// Totals and recent daily uploads of one link
+///
+/// `daily` covers the last `days` days up to today (UTC), with days
+/// without uploads included as zero.
+pub fn get_link_stats(
+    db: &DbPool,
+    link_id: &str,
+    days: i64,
+) -> Result<LinkStats, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let (upload_count, total_bytes, unique_uploader_ips, last_upload_at) = conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(file_size), 0), COUNT(DISTINCT uploader_ip), MAX(uploaded_at) FROM file_uploads WHERE link_id = ?",
+        [link_id],
+        |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        },
+    )?;
+
+    let (refused_count, last_refused_at) = conn.query_row(
+        "SELECT COUNT(*), MAX(attempted_at) FROM upload_attempts WHERE link_id = ?",
+        [link_id],
+        |row| Ok((row.get(0)?, row.get::<_, Option<String>>(1)?)),
+    )?;
+
+    let today = Utc::now().date_naive();
+    let first_day = today - chrono::Duration::days(days.max(1) - 1);
+    let mut stmt = conn.prepare(
+        "SELECT substr(uploaded_at, 1, 10) AS day, COUNT(*), COALESCE(SUM(file_size), 0) \
+         FROM file_uploads WHERE link_id = ? AND uploaded_at >= ? GROUP BY day",
+    )?;
+    let mut per_day = std::collections::HashMap::new();
+    let rows = stmt.query_map(params![link_id, first_day.to_string()], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            (row.get::<_, i64>(1)?, row.get::<_, i64>(2)?),
+        ))
+    })?;
+    for row in rows {
+        let (day, totals) = row?;
+        per_day.insert(day, totals);
+    }
+
+    let daily = first_day
+        .iter_days()
+        .take_while(|day| *day <= today)
+        .map(|day| {
+            let day = day.to_string();
+            let (upload_count, total_bytes) = per_day.get(&day).copied().unwrap_or((0, 0));
+            DailyUploads {
+                day,
+                upload_count,
+                total_bytes,
+            }
+        })
+        .collect();
+
+    let parse_time = |time: Option<String>| {
+        time.and_then(|time| chrono::DateTime::parse_from_rfc3339(&time).ok())
+            .map(|time| time.with_timezone(&Utc))
+    };
+
+    Ok(LinkStats {
+        upload_count,
+        total_bytes,
+        unique_uploader_ips,
+        refused_count,
+        last_upload_at: parse_time(last_upload_at),
+        last_refused_at: parse_time(last_refused_at),
+        daily,
+    })
+}
+
+/// Load one page of uploads together with their links in a single query
+///
+/// Groups are ordered by link creation date, newest first, and files within
+/// each group as the query asks; a link's files may continue on the next
+/// page. Uploads whose link no longer exists are grouped under a placeholder
+/// "Deleted Link".
+pub fn get_file_uploads_grouped_by_link(
+    db: &DbPool,
+    query: &UploadListQuery,
+    limit: i64,
+    offset: i64,
+) -> Result<UploadsPage, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    // Uploads of deleted links count as not archived
+    let mut conditions: Vec<&str> = vec!["(l.archived_at IS NOT NULL) = ?"];
+    let mut values: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(query.archived)];
+
+    if let Some(project_id) = query.project {
+        conditions.push("l.project_id = ?");
+        values.push(Box::new(project_id.to_string()));
+    }
+    if let Some(admin_id) = query.starred_by {
+        conditions.push("f.id IN (SELECT upload_id FROM upload_stars WHERE admin_id = ?)");
+        values.push(Box::new(admin_id.to_string()));
+    }
+    if query.flagged {
+        conditions
+            .push("f.pii_reviewed = 0 AND f.pii_findings IS NOT NULL AND f.pii_findings != ''");
+    }
+    let from_clause = format!(
+        "FROM file_uploads f LEFT JOIN upload_links l ON l.id = f.link_id WHERE {}",
+        conditions.join(" AND ")
+    );
+
+    let (total, total_size) = conn.query_row(
+        &format!(
+            "SELECT COUNT(*), COALESCE(SUM(f.file_size), 0) {}",
+            from_clause
+        ),
+        rusqlite::params_from_iter(values.iter().map(|value| value.as_ref())),
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+
+    values.push(Box::new(limit));
+    values.push(Box::new(offset));
+
+    // Sort columns come from a fixed list, never from the request
+    let order_column = match query.sort.column {
+        SortColumn::Name => "f.original_filename COLLATE NOCASE",
+        SortColumn::CreatedAt => "f.uploaded_at",
+        SortColumn::Size => "f.file_size",
+    };
+
+    // Link columns come first, followed by the upload columns
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, {} {} \
+         ORDER BY l.created_at DESC, f.link_id, {} {}, f.uploaded_at DESC LIMIT ? OFFSET ?",
+        qualified_columns(UPLOAD_LINK_COLUMNS, "l"),
+        qualified_columns(FILE_UPLOAD_COLUMNS, "f"),
+        from_clause,
+        order_column,
+        query.sort.direction.sql(),
+    ))?;
+    let upload_offset = UPLOAD_LINK_COLUMNS.split(',').count();
+
+    let row_iter = stmt.query_map(
+        rusqlite::params_from_iter(values.iter().map(|value| value.as_ref())),
+        |row| {
+            let link_exists = row.get::<_, Option<String>>(0)?.is_some();
+            let link = if link_exists {
+                Some(row_to_upload_link(row)?)
+            } else {
+                None
+            };
+
+            let upload = file_upload_from_row(row, upload_offset)?;
+
+            Ok((link, upload))
+        },
+    )?;
+
+    let mut grouped: Vec<(UploadLink, Vec<FileUpload>)> = Vec::new();
+    for row in row_iter {
+        let (link, upload) = row?;
+
+        // Rows are sorted by link, so each group is contiguous
+        match grouped.last_mut() {
+            Some((current, uploads)) if current.id == upload.link_id => uploads.push(upload),
+            _ => {
+                let link = link.unwrap_or_else(|| UploadLink {
+                    id: upload.link_id.clone(),
+                    token: "unknown".to_string(),
+                    name: "Deleted Link".to_string(),
+                    created_at: Utc::now(),
+                    ..Default::default()
+                });
+                grouped.push((link, vec![upload]));
+            }
+        }
+    }
+
+    Ok(UploadsPage {
+        groups: grouped,
+        total,
+        total_size,
+    })
+}
+
+/// Bytes one guest has uploaded to a link, matched by guest folder or client address
+///
+/// An identifier that is None matches nothing.
+pub fn get_guest_upload_bytes(
+    db: &DbPool,
+    link_id: &str,
+    guest_folder: Option<&str>,
+    uploader_ip: Option<&str>,
+) -> Result<i64, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let bytes = conn.query_row(
+        "SELECT COALESCE(SUM(file_size), 0) FROM file_uploads WHERE link_id = ? AND (guest_folder = ? OR uploader_ip = ?)",
+        params![link_id, guest_folder, uploader_ip],
+        |row| row.get(0),
+    )?;
+
+    Ok(bytes)
+}
+
+/// Bytes uploaded to a link since a point in time
+pub fn get_link_upload_bytes_since(
+    db: &DbPool,
+    link_id: &str,
+    since: DateTime<Utc>,
+) -> Result<i64, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let bytes = conn.query_row(
+        "SELECT COALESCE(SUM(file_size), 0) FROM file_uploads WHERE link_id = ? AND uploaded_at >= ?",
+        params![link_id, since.to_rfc3339()],
+        |row| row.get(0),
+    )?;
+
+    Ok(bytes)
+}
+
+/// Find the earliest upload on a link with the given contents, other than `except_id`
+pub fn get_file_upload_by_hash(
+    db: &DbPool,
+    link_id: &str,
+    content_hash: &str,
+    except_id: Option<&str>,
+) -> Result<Option<FileUpload>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM file_uploads WHERE link_id = ? AND content_hash = ? AND id IS NOT ? ORDER BY uploaded_at LIMIT 1",
+        FILE_UPLOAD_COLUMNS
+    ))?;
+
+    let upload_result = stmt.query_row(
+        params![link_id, content_hash, except_id],
+        row_to_file_upload,
+    );
+
+    match upload_result {
+        Ok(upload) => Ok(Some(upload)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+/// Find the earliest upload on any link with the given contents and size, whose stored copy can be shared
+pub fn get_stored_copy_by_hash(
+    db: &DbPool,
+    content_hash: &str,
+    file_size: i64,
+) -> Result<Option<FileUpload>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM file_uploads WHERE content_hash = ? AND file_size = ? ORDER BY uploaded_at LIMIT 1",
+        FILE_UPLOAD_COLUMNS
+    ))?;
+
+    let upload_result = stmt.query_row(params![content_hash, file_size], row_to_file_upload);
+
+    match upload_result {
+        Ok(upload) => Ok(Some(upload)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+/// One upload per stored file on disk (deduplicated uploads share one)
+pub fn get_stored_files(db: &DbPool) -> Result<Vec<FileUpload>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM file_uploads GROUP BY storage_dir, stored_filename",
+        FILE_UPLOAD_COLUMNS
+    ))?;
+
+    let upload_iter = stmt.query_map([], row_to_file_upload)?;
+
+    let mut uploads = Vec::new();
+    for upload in upload_iter {
+        uploads.push(upload?);
+    }
+
+    Ok(uploads)
+}
+
+/// One upload per stored file whose encryption isn't recorded yet
+pub fn get_uploads_without_encryption_record(
+    db: &DbPool,
+) -> Result<Vec<FileUpload>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM file_uploads WHERE encrypted IS NULL GROUP BY storage_dir, stored_filename",
+        FILE_UPLOAD_COLUMNS
+    ))?;
+
+    let upload_iter = stmt.query_map([], row_to_file_upload)?;
+
+    let mut uploads = Vec::new();
+    for upload in upload_iter {
+        uploads.push(upload?);
+    }
+
+    Ok(uploads)
+}
+
+/// Record the key an upload's stored file is encrypted with (None = unencrypted)
+///
+/// Applies to every upload sharing the stored file.
+pub fn set_upload_encryption(
+    db: &DbPool,
+    upload: &FileUpload,
+    key_id: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute(
+        "UPDATE file_uploads SET encrypted = ?, encryption_key_id = ? \
+         WHERE storage_dir = ? AND stored_filename = ?",
+        params![
+            key_id.is_some(),
+            key_id,
+            &upload.storage_dir,
+            &upload.stored_filename
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Whether any upload still refers to a stored file (deduplicated uploads share one)
+///
+/// Stored filenames are UUIDs, so the directory and name identify the file
+/// whatever volume it was recorded under.
+pub fn is_stored_file_in_use(
+    db: &DbPool,
+    storage_dir: &str,
+    stored_filename: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let in_use = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM file_uploads WHERE stored_filename = ? AND storage_dir = ?)",
+        params![stored_filename, storage_dir],
+        |row| row.get(0),
+    )?;
+
+    Ok(in_use)
+}
+
+/// Find the most recent upload on a link with the given original filename
+pub fn get_latest_file_upload_by_name(
+    db: &DbPool,
+    link_id: &str,
+    original_filename: &str,
+) -> Result<Option<FileUpload>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM file_uploads WHERE link_id = ? AND original_filename = ? ORDER BY uploaded_at DESC LIMIT 1",
+        FILE_UPLOAD_COLUMNS
+    ))?;
+
+    let upload_result = stmt.query_row([link_id, original_filename], row_to_file_upload);
+
+    match upload_result {
+        Ok(upload) => Ok(Some(upload)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+/// Number and combined size of the uploads on each storage volume
+///
+/// Uploads without a recorded volume are reported under `None`.
+#[allow(clippy::type_complexity)]
+pub fn get_volume_upload_totals(
+    db: &DbPool,
+) -> Result<Vec<(Option<String>, i64, i64)>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT volume, COUNT(*), COALESCE(SUM(file_size), 0) FROM file_uploads GROUP BY volume",
+    )?;
+    let totals = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<SqliteResult<Vec<_>>>()?;
+
+    Ok(totals)
+}
+
+/// Whether a guest folder holds files uploaded through a different link
+pub fn is_guest_folder_used_by_other_link(
+    db: &DbPool,
+    guest_folder: &str,
+    link_id: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let used = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM file_uploads WHERE guest_folder = ? AND link_id != ?)",
+        [guest_folder, link_id],
+        |row| row.get::<_, bool>(0),
+    )?;
+
+    Ok(used)
+}
+
+/// Load uploads (with their links) in upload order, starting after a cursor
+///
+/// The cursor is the `(uploaded_at, id)` pair of the last upload a client has
+/// seen. Without a cursor, the most recent `limit` uploads are returned.
+#[allow(clippy::type_complexity)]
+pub fn get_file_uploads_after(
+    db: &DbPool,
+    cursor: Option<(&str, &str)>,
+    limit: i64,
+) -> Result<Vec<(FileUpload, Option<UploadLink>)>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    // Upload columns come first, followed by the link columns
+    let select = format!(
+        "SELECT {}, {} FROM file_uploads f LEFT JOIN upload_links l ON l.id = f.link_id",
+        qualified_columns(FILE_UPLOAD_COLUMNS, "f"),
+        qualified_columns(UPLOAD_LINK_COLUMNS, "l"),
+    );
+    let link_offset = FILE_UPLOAD_COLUMNS.split(',').count();
+
+    let map_row = |row: &rusqlite::Row| -> SqliteResult<(FileUpload, Option<UploadLink>)> {
+        let upload = row_to_file_upload(row)?;
+        let link = match row.get::<_, Option<String>>(link_offset)? {
+            Some(_) => Some(upload_link_from_row(row, link_offset)?),
+            None => None,
+        };
+        Ok((upload, link))
+    };
+
+    let mut results = Vec::new();
+    match cursor {
+        Some((uploaded_at, id)) => {
+            let mut stmt = conn.prepare(&format!(
+                "{} WHERE (f.uploaded_at, f.id) > (?, ?) ORDER BY f.uploaded_at, f.id LIMIT ?",
+                select
+            ))?;
+            for row in stmt.query_map(params![uploaded_at, id, limit], map_row)? {
+                results.push(row?);
+            }
+        }
+        None => {
+            let mut stmt = conn.prepare(&format!(
+                "{} ORDER BY f.uploaded_at DESC, f.id DESC LIMIT ?",
+                select
+            ))?;
+            for row in stmt.query_map([limit], map_row)? {
+                results.push(row?);
+            }
+            results.reverse();
+        }
+    }
+
+    Ok(results)
+}
+
+pub fn get_file_uploads_by_link_id(
+    db: &DbPool,
+    link_id: &str,
+) -> Result<Vec<FileUpload>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM file_uploads WHERE link_id = ? ORDER BY uploaded_at DESC",
+        FILE_UPLOAD_COLUMNS
+    ))?;
+
+    let upload_iter = stmt.query_map([link_id], row_to_file_upload)?;
+
+    let mut uploads = Vec::new();
+    for upload in upload_iter {
+        uploads.push(upload?);
+    }
+
+    Ok(uploads)
+}
+
+/// Find uploads matching a filter, newest first
+///
+/// All criteria are optional and combined with AND.
+#[cfg_attr(not(feature = "graphql"), allow(dead_code))]
+pub fn search_file_uploads(
+    db: &DbPool,
+    filter: &UploadFilter,
+) -> Result<Vec<FileUpload>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut conditions: Vec<&str> = Vec::new();
+    let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+    if let Some(link_id) = &filter.link_id {
+        conditions.push("link_id = ?");
+        values.push(Box::new(link_id.clone()));
+    }
+    if let Some(mime_type) = &filter.mime_type_prefix {
+        conditions.push("mime_type LIKE ? ESCAPE '\\'");
+        values.push(Box::new(format!("{}%", escape_like(mime_type))));
+    }
+    if let Some(filename) = &filter.filename_contains {
+        conditions.push("original_filename LIKE ? ESCAPE '\\'");
+        values.push(Box::new(format!("%{}%", escape_like(filename))));
+    }
+    if let Some(min_size) = filter.min_size {
+        conditions.push("file_size >= ?");
+        values.push(Box::new(min_size));
+    }
+    if let Some(max_size) = filter.max_size {
+        conditions.push("file_size <= ?");
+        values.push(Box::new(max_size));
+    }
+    if let Some(after) = filter.uploaded_after {
+        conditions.push("uploaded_at >= ?");
+        values.push(Box::new(after.to_rfc3339()));
+    }
+    if let Some(before) = filter.uploaded_before {
+        conditions.push("uploaded_at < ?");
+        values.push(Box::new(before.to_rfc3339()));
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    values.push(Box::new(filter.limit));
+    values.push(Box::new(filter.offset));
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM file_uploads {} ORDER BY uploaded_at DESC LIMIT ? OFFSET ?",
+        FILE_UPLOAD_COLUMNS, where_clause
+    ))?;
+
+    let upload_iter = stmt.query_map(
+        rusqlite::params_from_iter(values.iter().map(|value| value.as_ref())),
+        row_to_file_upload,
+    )?;
+
+    let mut uploads = Vec::new();
+    for upload in upload_iter {
+        uploads.push(upload?);
+    }
+
+    Ok(uploads)
+}
+
+/// Turn free text typed by an admin into an FTS5 query
+///
+/// Every word is quoted, so FTS5 syntax characters are matched literally,
+/// and treated as a prefix; all words must match. Returns None when there is
+/// nothing to search for.
+fn fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Search uploads and links by filename, link name and guest email, best matches first
+pub fn search_everything(
+    db: &DbPool,
+    input: &str,
+    limit: i64,
+) -> Result<SearchResults, Box<dyn std::error::Error>> {
+    let Some(query) = fts_query(input) else {
+        return Ok(SearchResults::default());
+    };
+
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, COALESCE(l.name, '') FROM search_index \
+         JOIN file_uploads u ON u.id = search_index.item_id \
+         LEFT JOIN upload_links l ON l.id = u.link_id \
+         WHERE search_index MATCH ? AND search_index.kind = 'upload' \
+         ORDER BY search_index.rank LIMIT ?",
+        qualified_columns(FILE_UPLOAD_COLUMNS, "u")
+    ))?;
+    let link_name_column = FILE_UPLOAD_COLUMNS.split(',').count();
+    let upload_iter = stmt.query_map(params![&query, limit], |row| {
+        Ok((
+            row_to_file_upload(row)?,
+            row.get::<_, String>(link_name_column)?,
+        ))
+    })?;
+
+    let mut uploads = Vec::new();
+    for upload in upload_iter {
+        uploads.push(upload?);
+    }
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM search_index JOIN upload_links l ON l.id = search_index.item_id \
+         WHERE search_index MATCH ? AND search_index.kind = 'link' \
+         ORDER BY search_index.rank LIMIT ?",
+        qualified_columns(UPLOAD_LINK_COLUMNS, "l")
+    ))?;
+    let link_iter = stmt.query_map(params![&query, limit], row_to_upload_link)?;
+
+    let mut links = Vec::new();
+    for link in link_iter {
+        links.push(link?);
+    }
+
+    Ok(SearchResults { uploads, links })
+}
+
+/// Escape `%`, `_` and the escape character itself for use in a LIKE pattern
+#[cfg_attr(not(feature = "graphql"), allow(dead_code))]
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Uploads with the given IDs, in the same order; unknown IDs are skipped
+pub fn get_file_uploads_by_ids(
+    db: &DbPool,
+    ids: &[&str],
+) -> Result<Vec<FileUpload>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM file_uploads WHERE id = ?",
+        FILE_UPLOAD_COLUMNS
+    ))?;
+
+    let mut uploads = Vec::new();
+    for id in ids {
+        match stmt.query_row([id], row_to_file_upload) {
+            Ok(upload) => uploads.push(upload),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {}
+            Err(e) => return Err(Box::new(e)),
+        }
+    }
+
+    Ok(uploads)
+}
+
+pub fn get_file_upload_by_id(
+    db: &DbPool,
+    id: &str,
+) -> Result<Option<FileUpload>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM file_uploads WHERE id = ?",
+        FILE_UPLOAD_COLUMNS
+    ))?;
+
+    let upload_result = stmt.query_row([id], row_to_file_upload);
+
+    match upload_result {
+        Ok(upload) => Ok(Some(upload)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+pub fn set_replication_status(
+    db: &DbPool,
+    id: &str,
+    status: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute(
+        "UPDATE file_uploads SET replication_status = ? WHERE id = ?",
+        params![status, id],
+    )?;
+
+    Ok(())
+}
+
+pub fn update_admin_password(
+    db: &DbPool,
+    username: &str,
+    new_password_hash: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute(
+        "UPDATE admins SET password_hash = ? WHERE username = ?",
+        params![new_password_hash, username],
+    )?;
+
+    Ok(())
+}
+
+/// Set an admin's display time zone (None = server default)
+pub fn update_admin_timezone(
+    db: &DbPool,
+    username: &str,
+    timezone: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute(
+        "UPDATE admins SET timezone = ? WHERE username = ?",
+        params![timezone, username],
+    )?;
+
+    Ok(())
+}
+
+/// Set an admin's light/dark theme
+pub fn update_admin_theme(
+    db: &DbPool,
+    username: &str,
+    theme: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute(
+        "UPDATE admins SET theme = ? WHERE username = ?",
+        params![theme, username],
+    )?;
+
+    Ok(())
+}
+
+/// Turn two-factor authentication on with `secret`, or off with None
+pub fn update_admin_totp(
+    db: &DbPool,
+    admin_id: &str,
+    secret: Option<&str>,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let changed = conn.execute(
+        "UPDATE admins SET totp_secret = ?, totp_last_step = NULL WHERE id = ?",
+        params![secret, admin_id],
+    )?;
+
+    Ok(changed > 0)
+}
+
+/// Record that an admin used the code of a time step
+///
+/// Returns false if that step or a later one was already used, which means
+/// the code is being replayed.
+pub fn record_totp_step(
+    db: &DbPool,
+    admin_id: &str,
+    step: i64,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let changed = conn.execute(
+        "UPDATE admins SET totp_last_step = ?1 WHERE id = ?2 \
+         AND (totp_last_step IS NULL OR totp_last_step < ?1)",
+        params![step, admin_id],
+    )?;
+
+    Ok(changed > 0)
+}
+
+/// All admin accounts, oldest first
+pub fn get_admins(db: &DbPool) -> Result<Vec<Admin>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM admins ORDER BY created_at",
+        ADMIN_COLUMNS
+    ))?;
+    let admin_iter = stmt.query_map([], row_to_admin)?;
+
+    let mut admins = Vec::new();
+    for admin in admin_iter {
+        admins.push(admin?);
+    }
+
+    Ok(admins)
+}
+
+/// Add an admin account and return its ID
+pub fn create_admin(
+    db: &DbPool,
+    username: &str,
+    password_hash: &str,
+    role: AdminRole,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO admins (id, username, password_hash, created_at, is_owner, role) VALUES (?, ?, ?, ?, ?, ?)",
+        params![
+            &id,
+            username,
+            password_hash,
+            Utc::now().to_rfc3339(),
+            role.is_owner(),
+            role.code()
+        ],
+    )?;
+
+    Ok(id)
+}
+
+/// Change an admin's role
+///
+/// The last owner keeps their role, so there is always someone who can
+/// manage admins. Returns false if nothing changed for that reason or
+/// because there is no such admin.
+pub fn update_admin_role(
+    db: &DbPool,
+    id: &str,
+    role: AdminRole,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let changed = conn.execute(
+        "UPDATE admins SET role = ?1, is_owner = ?2 WHERE id = ?3 \
+         AND (?1 = 'owner' OR role != 'owner' OR (SELECT COUNT(*) FROM admins WHERE role = 'owner') > 1)",
+        params![role.code(), role.is_owner(), id],
+    )?;
+
+    Ok(changed > 0)
+}
+
+/// Delete an admin account, unless it is the last owner
+///
+/// Returns false if nothing was deleted.
+pub fn delete_admin(db: &DbPool, id: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let deleted = conn.execute(
+        "DELETE FROM admins WHERE id = ?1 \
+         AND (role != 'owner' OR (SELECT COUNT(*) FROM admins WHERE role = 'owner') > 1)",
+        [id],
+    )?;
+
+    // Foreign keys aren't enforced, so the admin's stars go by hand
+    if deleted > 0 {
+        conn.execute("DELETE FROM upload_stars WHERE admin_id = ?", [id])?;
+    }
+
+    Ok(deleted > 0)
+}
+
+pub fn update_remaining_quota(
+    db: &DbPool,
+    link_id: &str,
+    uploaded_size: i64,
+    uploaded_files: i64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let token_result = conn.query_row(
+            "UPDATE upload_links SET remaining_quota = remaining_quota - ?, uploads_used = uploads_used + ? WHERE id = ? RETURNING token",
+            params![uploaded_size, uploaded_files, link_id],
+            |row| row.get::<_, String>(0),
+        );
+
+    match token_result {
+        Ok(token) => {
+            invalidate_cached_link(&token);
+            Ok(())
+        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(()),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+/// Charge a new file to its link: take its size off the remaining quota and
+/// one file off the files the link accepts
+///
+/// The quota and file limit are checked in the same statement, so
+/// concurrent uploads can't store more bytes or files than the link accepts.
+/// A file that doesn't fit changes nothing; the result says which limit it
+/// ran into.
+pub fn reserve_upload(
+    db: &DbPool,
+    link_id: &str,
+    file_size: i64,
+) -> Result<Reservation, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let token_result = conn.query_row(
+        "UPDATE upload_links SET remaining_quota = remaining_quota - ?1, uploads_used = uploads_used + 1 \
+         WHERE id = ?2 AND remaining_quota >= ?1 AND (max_uploads IS NULL OR uploads_used < max_uploads) \
+         RETURNING token",
+        params![file_size, link_id],
+        |row| row.get::<_, String>(0),
+    );
+
+    match token_result {
+        Ok(token) => {
+            invalidate_cached_link(&token);
+            Ok(Reservation::Reserved)
+        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => {
+            let files_left = conn.query_row(
+                "SELECT max_uploads IS NULL OR uploads_used < max_uploads FROM upload_links WHERE id = ?",
+                [link_id],
+                |row| row.get::<_, bool>(0),
+            );
+            match files_left {
+                Ok(true) => Ok(Reservation::QuotaExhausted),
+                Ok(false) | Err(rusqlite::Error::QueryReturnedNoRows) => {
+                    Ok(Reservation::FilesExhausted)
+                }
+                Err(e) => Err(Box::new(e)),
+            }
+        }
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+pub fn delete_file_upload(db: &DbPool, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute("DELETE FROM file_uploads WHERE id = ?", [id])?;
+    conn.execute("DELETE FROM upload_stars WHERE upload_id = ?", [id])?;
+    conn.execute("DELETE FROM shared_files WHERE upload_id = ?", [id])?;
+
+    Ok(())
+}
+
+/// Delete several uploads in one transaction, so either all of them go or none
+pub fn delete_file_uploads(db: &DbPool, ids: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = db.get()?;
+    let tx = conn.transaction()?;
+
+    for id in ids {
+        tx.execute("DELETE FROM file_uploads WHERE id = ?", [id])?;
+        tx.execute("DELETE FROM upload_stars WHERE upload_id = ?", [id])?;
+        tx.execute("DELETE FROM shared_files WHERE upload_id = ?", [id])?;
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+/// Store a guest's message and attach it to the files it was sent with
+pub fn add_upload_message(
+    db: &DbPool,
+    link_id: &str,
+    message: &str,
+    upload_ids: &[&str],
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut conn = db.get()?;
+    let tx = conn.transaction()?;
+    let id = Uuid::new_v4().to_string();
+
+    tx.execute(
+        "INSERT INTO upload_messages (id, link_id, message, created_at) VALUES (?, ?, ?, ?)",
+        params![id, link_id, message, Utc::now().to_rfc3339()],
+    )?;
+    for upload_id in upload_ids {
+        tx.execute(
+            "UPDATE file_uploads SET message_id = ? WHERE id = ?",
+            params![id, upload_id],
+        )?;
+    }
+
+    tx.commit()?;
+    Ok(id)
+}
+
+fn row_to_upload_message(row: &rusqlite::Row) -> SqliteResult<UploadMessage> {
+    Ok(UploadMessage {
+        id: row.get(0)?,
+        link_id: row.get(1)?,
+        message: row.get(2)?,
+        created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
+            .unwrap()
+            .with_timezone(&Utc),
+    })
+}
+
+/// Messages attached to any of the given uploads
+pub fn get_upload_messages(
+    db: &DbPool,
+    uploads: &[&FileUpload],
+) -> Result<Vec<UploadMessage>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt =
+        conn.prepare("SELECT id, link_id, message, created_at FROM upload_messages WHERE id = ?")?;
+    let mut messages: Vec<UploadMessage> = Vec::new();
+    for message_id in uploads
+        .iter()
+        .filter_map(|upload| upload.message_id.as_deref())
+    {
+        if messages.iter().any(|message| message.id == message_id) {
+            continue;
+        }
+        for message in stmt.query_map([message_id], row_to_upload_message)? {
+            messages.push(message?);
+        }
+    }
+
+    Ok(messages)
+}
+
+/// Remove messages whose files have all been deleted
+pub fn purge_orphaned_upload_messages(db: &DbPool) -> Result<usize, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let count = conn.execute(
+        "DELETE FROM upload_messages WHERE id NOT IN \
+         (SELECT message_id FROM file_uploads WHERE message_id IS NOT NULL)",
+        [],
+    )?;
+
+    Ok(count)
+}
+
+/// IDs of the uploads an admin has starred
+pub fn get_starred_upload_ids(
+    db: &DbPool,
+    admin_id: &str,
+) -> Result<HashSet<String>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare("SELECT upload_id FROM upload_stars WHERE admin_id = ?")?;
+    let id_iter = stmt.query_map([admin_id], |row| row.get::<_, String>(0))?;
+
+    let mut ids = HashSet::new();
+    for id in id_iter {
+        ids.insert(id?);
+    }
+
+    Ok(ids)
+}
+
+/// Star or unstar an upload for one admin
+pub fn set_upload_starred(
+    db: &DbPool,
+    admin_id: &str,
+    upload_id: &str,
+    starred: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    if starred {
+        conn.execute(
+            "INSERT OR IGNORE INTO upload_stars (admin_id, upload_id, created_at) VALUES (?, ?, ?)",
+            params![admin_id, upload_id, Utc::now().to_rfc3339()],
+        )?;
+    } else {
+        conn.execute(
+            "DELETE FROM upload_stars WHERE admin_id = ? AND upload_id = ?",
+            params![admin_id, upload_id],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Record what the personal data scan found in an upload
+pub fn set_pii_findings(
+    db: &DbPool,
+    id: &str,
+    findings: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute(
+        "UPDATE file_uploads SET pii_findings = ?, pii_reviewed = 0 WHERE id = ?",
+        params![findings, id],
+    )?;
+
+    Ok(())
+}
+
+/// Mark the personal data findings of an upload as reviewed, returning whether it changed
+pub fn mark_pii_reviewed(db: &DbPool, id: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let changed = conn.execute(
+        "UPDATE file_uploads SET pii_reviewed = 1 WHERE id = ? AND pii_findings != '' AND pii_reviewed = 0",
+        [id],
+    )?;
+
+    Ok(changed > 0)
+}
+
+/// Record the outcome of an integrity check, storing `content_hash` for files uploaded before hashing
+pub fn record_integrity_check(
+    db: &DbPool,
+    id: &str,
+    ok: bool,
+    content_hash: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute(
+        "UPDATE file_uploads SET integrity_ok = ?, integrity_checked_at = ?, \
+             content_hash = COALESCE(content_hash, ?) \
+         WHERE id = ?",
+        params![ok, Utc::now().to_rfc3339(), content_hash, id],
+    )?;
+
+    Ok(())
+}
+
+/// Number of uploads with personal data findings that no admin reviewed yet
+pub fn count_unreviewed_pii_uploads(db: &DbPool) -> Result<i64, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let count = conn.query_row(
+        "SELECT COUNT(*) FROM file_uploads WHERE pii_findings != '' AND pii_reviewed = 0",
+        [],
+        |row| row.get(0),
+    )?;
+
+    Ok(count)
+}
+
+/// Whether an upload may not be deleted because it or its link is under legal hold
+///
+/// Unknown uploads count as not held.
+pub fn is_upload_held(db: &DbPool, id: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let held_result = conn.query_row(
+        "SELECT f.legal_hold OR COALESCE(l.legal_hold, 0) FROM file_uploads f \
+         LEFT JOIN upload_links l ON l.id = f.link_id WHERE f.id = ?",
+        [id],
+        |row| row.get::<_, bool>(0),
+    );
+
+    match held_result {
+        Ok(held) => Ok(held),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(false),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+/// Place or release the legal hold on an upload, returning whether it changed
+pub fn set_upload_legal_hold(
+    db: &DbPool,
+    id: &str,
+    held: bool,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let changed = conn.execute(
+        "UPDATE file_uploads SET legal_hold = ? WHERE id = ? AND legal_hold != ?",
+        params![held, id, held],
+    )?;
+
+    Ok(changed > 0)
+}
+
+/// Place or release the legal hold on a link, returning whether it changed
+pub fn set_link_legal_hold(
+    db: &DbPool,
+    id: &str,
+    held: bool,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let token_result = conn.query_row(
+        "UPDATE upload_links SET legal_hold = ? WHERE id = ? AND legal_hold != ? RETURNING token",
+        params![held, id, held],
+        |row| row.get::<_, String>(0),
+    );
+
+    match token_result {
+        Ok(token) => {
+            invalidate_cached_link(&token);
+            Ok(true)
+        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(false),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+pub fn record_audit_event(
+    db: &DbPool,
+    actor: &str,
+    action: &str,
+    target_type: &str,
+    target_id: &str,
+    detail: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute(
+        "INSERT INTO audit_log (id, actor, action, target_type, target_id, detail, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        params![
+            Uuid::new_v4().to_string(),
+            actor,
+            action,
+            target_type,
+            target_id,
+            detail,
+            Utc::now().to_rfc3339(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Audit log entries about any of the given records, oldest first
+pub fn get_audit_events_for_targets(
+    db: &DbPool,
+    target_ids: &[String],
+) -> Result<Vec<AuditEvent>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, actor, action, target_type, target_id, detail, created_at \
+         FROM audit_log WHERE target_id = ? ORDER BY created_at",
+    )?;
+
+    let mut events = Vec::new();
+    for target_id in target_ids {
+        let event_iter = stmt.query_map([target_id], row_to_audit_event)?;
+        for event in event_iter {
+            events.push(event?);
+        }
+    }
+    events.sort_by_key(|event| event.created_at);
+
+    Ok(events)
+}
+
+/// Remove the audit log entries with the given IDs
+pub fn delete_audit_events(db: &DbPool, ids: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare("DELETE FROM audit_log WHERE id = ?")?;
+    for id in ids {
+        stmt.execute([id])?;
+    }
+
+    Ok(())
+}
+
+/// Map an `audit_log` row to an `AuditEvent`
+fn row_to_audit_event(row: &rusqlite::Row) -> SqliteResult<AuditEvent> {
+    Ok(AuditEvent {
+        id: row.get(0)?,
+        actor: row.get(1)?,
+        action: row.get(2)?,
+        target_type: row.get(3)?,
+        target_id: row.get(4)?,
+        detail: row.get(5)?,
+        created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
+            .unwrap()
+            .with_timezone(&Utc),
+    })
+}
+
+/// Most recent audit log entries, newest first
+pub fn get_recent_audit_events(
+    db: &DbPool,
+    limit: i64,
+) -> Result<Vec<AuditEvent>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, actor, action, target_type, target_id, detail, created_at \
+         FROM audit_log ORDER BY created_at DESC LIMIT ?",
+    )?;
+
+    let event_iter = stmt.query_map([limit], row_to_audit_event)?;
+
+    let mut events = Vec::new();
+    for event in event_iter {
+        events.push(event?);
+    }
+
+    Ok(events)
+}
+
+/// Uploads that belong to a person, found by the email address, name, client
+/// address or guest folder they uploaded with, or by the email their link
+/// was created for
+pub fn get_file_uploads_by_subject(
+    db: &DbPool,
+    subject: &str,
+) -> Result<Vec<FileUpload>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM file_uploads \
+         WHERE uploader_email = lower(?1) OR lower(uploader_name) = lower(?1) \
+         OR uploader_ip = ?1 OR guest_folder = ?1 \
+         OR link_id IN (SELECT id FROM upload_links WHERE lower(guest_email) = lower(?1)) \
+         ORDER BY uploaded_at",
+        FILE_UPLOAD_COLUMNS
+    ))?;
+
+    let upload_iter = stmt.query_map([subject], row_to_file_upload)?;
+
+    let mut uploads = Vec::new();
+    for upload in upload_iter {
+        uploads.push(upload?);
+    }
+
+    Ok(uploads)
+}
+
+/// Links created for a guest email address
+pub fn get_upload_links_by_guest_email(
+    db: &DbPool,
+    email: &str,
+) -> Result<Vec<UploadLink>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM upload_links WHERE lower(guest_email) = lower(?) ORDER BY created_at",
+        UPLOAD_LINK_COLUMNS
+    ))?;
+
+    let link_iter = stmt.query_map([email], row_to_upload_link)?;
+
+    let mut links = Vec::new();
+    for link in link_iter {
+        links.push(link?);
+    }
+
+    Ok(links)
+}
+
+/// Forget the guest email of a link
+pub fn clear_link_guest_email(db: &DbPool, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt =
+        conn.prepare("UPDATE upload_links SET guest_email = NULL WHERE id = ? RETURNING token")?;
+    let tokens = stmt.query_map([id], |row| row.get::<_, String>(0))?;
+    for token in tokens {
+        invalidate_cached_link(&token?);
+    }
+
+    Ok(())
+}
+
+/// Column list used by every task query, in the order expected by `row_to_task`
+const TASK_COLUMNS: &str =
+    "id, kind, payload, status, attempts, max_attempts, last_error, run_at, created_at, updated_at, output";
+
+/// Map a row selected with `TASK_COLUMNS` to a `BackgroundTask`
+fn row_to_task(row: &rusqlite::Row) -> SqliteResult<BackgroundTask> {
+    let parse_time = |s: String| {
+        chrono::DateTime::parse_from_rfc3339(&s)
+            .unwrap()
+            .with_timezone(&Utc)
+    };
+
+    Ok(BackgroundTask {
+        id: row.get(0)?,
+        kind: row.get(1)?,
+        payload: row.get(2)?,
+        status: row.get(3)?,
+        attempts: row.get(4)?,
+        max_attempts: row.get(5)?,
+        last_error: row.get(6)?,
+        run_at: parse_time(row.get(7)?),
+        created_at: parse_time(row.get(8)?),
+        updated_at: parse_time(row.get(9)?),
+        output: row.get(10)?,
+    })
+}
+
+pub fn enqueue_task(
+    db: &DbPool,
+    kind: &str,
+    payload: &str,
+    max_attempts: i64,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+
+    conn.execute(
+        "INSERT INTO tasks (id, kind, payload, status, attempts, max_attempts, run_at, created_at, updated_at) VALUES (?, ?, ?, 'pending', 0, ?, ?, ?, ?)",
+        params![&id, kind, payload, max_attempts, &now, &now, &now],
+    )?;
+
+    Ok(id)
+}
+
+/// Atomically claim the next due task and mark it as running
+pub fn claim_next_task(db: &DbPool) -> Result<Option<BackgroundTask>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+    let now = Utc::now().to_rfc3339();
+
+    let mut stmt = conn.prepare(&format!(
+        "UPDATE tasks SET status = 'running', updated_at = ?1 WHERE id = (SELECT id FROM tasks WHERE status = 'pending' AND run_at <= ?1 ORDER BY run_at LIMIT 1) RETURNING {}",
+        TASK_COLUMNS
+    ))?;
+
+    let task_result = stmt.query_row([&now], row_to_task);
+
+    match task_result {
+        Ok(task) => Ok(Some(task)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+pub fn complete_task(
+    db: &DbPool,
+    id: &str,
+    output: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute(
+        "UPDATE tasks SET status = 'done', last_error = NULL, output = ?, updated_at = ? WHERE id = ?",
+        params![output, Utc::now().to_rfc3339(), id],
+    )?;
+
+    Ok(())
+}
+
+/// Record a failed attempt, rescheduling the task or moving it to the dead-letter state
+pub fn fail_task(
+    db: &DbPool,
+    task: &BackgroundTask,
+    error: &str,
+    retry_at: chrono::DateTime<Utc>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let attempts = task.attempts + 1;
+    let status = if attempts >= task.max_attempts {
+        "dead"
+    } else {
+        "pending"
+    };
+
+    conn.execute(
+        "UPDATE tasks SET status = ?, attempts = ?, last_error = ?, run_at = ?, updated_at = ? WHERE id = ?",
+        params![
+            status,
+            attempts,
+            error,
+            retry_at.to_rfc3339(),
+            Utc::now().to_rfc3339(),
+            &task.id,
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Put tasks interrupted by a shutdown or crash back into the queue
+pub fn requeue_running_tasks(db: &DbPool) -> Result<usize, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let count = conn.execute(
+        "UPDATE tasks SET status = 'pending', updated_at = ? WHERE status = 'running'",
+        [Utc::now().to_rfc3339()],
+    )?;
+
+    Ok(count)
+}
+
+pub fn get_tasks_by_status(
+    db: &DbPool,
+    status: &str,
+) -> Result<Vec<BackgroundTask>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM tasks WHERE status = ? ORDER BY updated_at DESC",
+        TASK_COLUMNS
+    ))?;
+
+    let task_iter = stmt.query_map([status], row_to_task)?;
+
+    let mut tasks = Vec::new();
+    for task in task_iter {
+        tasks.push(task?);
+    }
+
+    Ok(tasks)
+}
+
+/// Most recent tasks of one kind in any state, newest first (the job log of e.g. post-upload commands)
+pub fn get_recent_tasks_by_kind(
+    db: &DbPool,
+    kind: &str,
+    limit: i64,
+) -> Result<Vec<BackgroundTask>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM tasks WHERE kind = ? ORDER BY updated_at DESC LIMIT ?",
+        TASK_COLUMNS
+    ))?;
+
+    let task_iter = stmt.query_map(params![kind, limit], row_to_task)?;
+
+    let mut tasks = Vec::new();
+    for task in task_iter {
+        tasks.push(task?);
+    }
+
+    Ok(tasks)
+}
+
+pub fn count_tasks_by_status(db: &DbPool, status: &str) -> Result<i64, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM tasks WHERE status = ?",
+        [status],
+        |row| row.get(0),
+    )?;
+
+    Ok(count)
+}
+
+/// Move a dead-lettered task back into the queue with a fresh attempt budget
+pub fn retry_dead_task(db: &DbPool, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+    let now = Utc::now().to_rfc3339();
+
+    conn.execute(
+        "UPDATE tasks SET status = 'pending', attempts = 0, run_at = ?, updated_at = ? WHERE id = ? AND status = 'dead'",
+        params![&now, &now, id],
+    )?;
+
+    Ok(())
+}
+
+pub fn delete_task(db: &DbPool, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute("DELETE FROM tasks WHERE id = ?", [id])?;
+
+    Ok(())
+}
+
+/// Load an admin-edited email template, if the template was customized
+pub fn get_email_template(
+    db: &DbPool,
+    key: &str,
+) -> Result<Option<EmailTemplate>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let result = conn.query_row(
+        "SELECT subject, body, updated_at FROM email_templates WHERE key = ?",
+        [key],
+        |row| {
+            Ok(EmailTemplate {
+                subject: row.get(0)?,
+                body: row.get(1)?,
+                updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(2)?)
+                    .unwrap()
+                    .with_timezone(&Utc),
+            })
+        },
+    );
+
+    match result {
+        Ok(template) => Ok(Some(template)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+/// Store a customized email template, replacing any earlier version
+pub fn save_email_template(
+    db: &DbPool,
+    key: &str,
+    subject: &str,
+    body: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute(
+        "INSERT INTO email_templates (key, subject, body, updated_at) VALUES (?, ?, ?, ?)
+         ON CONFLICT(key) DO UPDATE SET subject = excluded.subject, body = excluded.body, updated_at = excluded.updated_at",
+        params![key, subject, body, Utc::now().to_rfc3339()],
+    )?;
+
+    Ok(())
+}
+
+/// Drop a customized email template so the built-in default applies again
+pub fn delete_email_template(db: &DbPool, key: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute("DELETE FROM email_templates WHERE key = ?", [key])?;
+
+    Ok(())
+}
+
+/// Start a resumable upload session, returning its ID
+pub fn create_upload_session(
+    db: &DbPool,
+    link_id: &str,
+    original_filename: &str,
+    content_type: &str,
+    total_size: i64,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+
+    conn.execute(
+        "INSERT INTO upload_sessions (id, link_id, original_filename, content_type, total_size, received, created_at, updated_at) VALUES (?, ?, ?, ?, ?, 0, ?, ?)",
+        params![&id, link_id, original_filename, content_type, total_size, &now, &now],
+    )?;
+
+    Ok(id)
+}
+
+pub fn get_upload_session(
+    db: &DbPool,
+    id: &str,
+) -> Result<Option<UploadSession>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let result = conn.query_row(
+        "SELECT id, link_id, original_filename, content_type, total_size, received, updated_at FROM upload_sessions WHERE id = ?",
+        [id],
+        |row| {
+            Ok(UploadSession {
+                id: row.get(0)?,
+                link_id: row.get(1)?,
+                original_filename: row.get(2)?,
+                content_type: row.get(3)?,
+                total_size: row.get(4)?,
+                received: row.get(5)?,
+                updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
+                    .unwrap()
+                    .with_timezone(&Utc),
+            })
+        },
+    );
+
+    match result {
+        Ok(session) => Ok(Some(session)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+/// Record how many bytes of a session have been written to its partial file
+pub fn update_upload_session_received(
+    db: &DbPool,
+    id: &str,
+    received: i64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute(
+        "UPDATE upload_sessions SET received = ?, updated_at = ? WHERE id = ?",
+        params![received, Utc::now().to_rfc3339(), id],
+    )?;
+
+    Ok(())
+}
+
+pub fn delete_upload_session(db: &DbPool, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute("DELETE FROM upload_sessions WHERE id = ?", [id])?;
+
+    Ok(())
+}
+
+/// Remove sessions without activity since the cutoff, returning their IDs so the partial files can be deleted
+pub fn purge_stale_upload_sessions(
+    db: &DbPool,
+    older_than: chrono::DateTime<Utc>,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare("DELETE FROM upload_sessions WHERE updated_at < ? RETURNING id")?;
+    let ids = stmt
+        .query_map([older_than.to_rfc3339()], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+
+    Ok(ids)
+}
+
+/// Record an upload that was turned away
+pub fn record_upload_attempt(
+    db: &DbPool,
+    link_id: &str,
+    filename: Option<&str>,
+    reason: &str,
+    detail: Option<&str>,
+    uploader_ip: Option<&str>,
+    request_id: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute(
+        "INSERT INTO upload_attempts (id, link_id, filename, reason, detail, uploader_ip, attempted_at, request_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            Uuid::new_v4().to_string(),
+            link_id,
+            filename,
+            reason,
+            detail,
+            uploader_ip,
+            Utc::now().to_rfc3339(),
+            request_id,
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Columns selected for an `UploadAttempt`, in the order `row_to_upload_attempt` expects
+const UPLOAD_ATTEMPT_COLUMNS: &str =
+    "id, link_id, filename, reason, detail, uploader_ip, attempted_at, request_id";
+
+fn row_to_upload_attempt(row: &rusqlite::Row) -> SqliteResult<UploadAttempt> {
+    Ok(UploadAttempt {
+        id: row.get(0)?,
+        link_id: row.get(1)?,
+        filename: row.get(2)?,
+        reason: row.get(3)?,
+        detail: row.get(4)?,
+        uploader_ip: row.get(5)?,
+        attempted_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
+            .unwrap()
+            .with_timezone(&Utc),
+        request_id: row.get(7)?,
+    })
+}
+
+/// Most recent refused uploads on a link, newest first
+pub fn get_upload_attempts(
+    db: &DbPool,
+    link_id: &str,
+    limit: i64,
+) -> Result<Vec<UploadAttempt>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM upload_attempts WHERE link_id = ? ORDER BY attempted_at DESC LIMIT ?",
+        UPLOAD_ATTEMPT_COLUMNS
+    ))?;
+
+    let attempt_iter = stmt.query_map(params![link_id, limit], row_to_upload_attempt)?;
+
+    let mut attempts = Vec::new();
+    for attempt in attempt_iter {
+        attempts.push(attempt?);
+    }
+
+    Ok(attempts)
+}
+
+/// Refused uploads made from a client address, oldest first
+pub fn get_upload_attempts_by_ip(
+    db: &DbPool,
+    uploader_ip: &str,
+) -> Result<Vec<UploadAttempt>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM upload_attempts WHERE uploader_ip = ? ORDER BY attempted_at",
+        UPLOAD_ATTEMPT_COLUMNS
+    ))?;
+
+    let attempt_iter = stmt.query_map([uploader_ip.trim()], row_to_upload_attempt)?;
+
+    let mut attempts = Vec::new();
+    for attempt in attempt_iter {
+        attempts.push(attempt?);
+    }
+
+    Ok(attempts)
+}
+
+pub fn delete_upload_attempts(db: &DbPool, ids: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare("DELETE FROM upload_attempts WHERE id = ?")?;
+    for id in ids {
+        stmt.execute([id])?;
+    }
+
+    Ok(())
+}
+
+/// Remove refused uploads recorded before the given cutoff
+pub fn purge_upload_attempts(
+    db: &DbPool,
+    older_than: chrono::DateTime<Utc>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let count = conn.execute(
+        "DELETE FROM upload_attempts WHERE attempted_at < ?",
+        [older_than.to_rfc3339()],
+    )?;
+
+    Ok(count)
+}
+
+/// Remove finished tasks older than the given cutoff so the table doesn't grow forever
+pub fn purge_completed_tasks(
+    db: &DbPool,
+    older_than: chrono::DateTime<Utc>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let count = conn.execute(
+        "DELETE FROM tasks WHERE status = 'done' AND updated_at < ?",
+        [older_than.to_rfc3339()],
+    )?;
+
+    Ok(count)
+}
+
+/// Map a row selected from `projects` (id, name, quota_bytes, created_at) to a `Project`
+fn row_to_project(row: &rusqlite::Row) -> SqliteResult<Project> {
+    Ok(Project {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        quota_bytes: row.get(2)?,
+        created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
+            .unwrap()
+            .with_timezone(&Utc),
+    })
+}
+
+pub fn create_project(
+    db: &DbPool,
+    name: &str,
+    quota_bytes: Option<i64>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
 
-    // Update existing links to set remaining_quota to max_file_size if it's 0
+    let id = Uuid::new_v4().to_string();
     conn.execute(
-        "UPDATE upload_links SET remaining_quota = max_file_size WHERE remaining_quota = 0",
-        [],
+        "INSERT INTO projects (id, name, quota_bytes, created_at) VALUES (?, ?, ?, ?)",
+        params![&id, name, quota_bytes, Utc::now().to_rfc3339()],
     )?;
 
-    Ok(())
+    Ok(id)
 }
 
-fn create_default_admin(conn: &Connection) -> SqliteResult<()> {
-    let count: i64 = conn.query_row("SELECT COUNT(*) FROM admins", [], |row| row.get(0))?;
-
-    if count == 0 {
-        let admin_id = Uuid::new_v4().to_string();
-        let password_hash = bcrypt::hash("admin123", bcrypt::DEFAULT_COST)
-            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+pub fn get_all_projects(db: &DbPool) -> Result<Vec<Project>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
 
-        conn.execute(
-            "INSERT INTO admins (id, username, password_hash, created_at) VALUES (?1, ?2, ?3, ?4)",
-            params![admin_id, "admin", password_hash, Utc::now().to_rfc3339()],
-        )?;
+    let mut stmt =
+        conn.prepare("SELECT id, name, quota_bytes, created_at FROM projects ORDER BY name")?;
+    let project_iter = stmt.query_map([], row_to_project)?;
 
-        info!("Created default admin user: admin/admin123");
+    let mut projects = Vec::new();
+    for project in project_iter {
+        projects.push(project?);
     }
 
-    Ok(())
+    Ok(projects)
 }
 
-// Database query functions
-pub fn get_admin_by_username(
-    db: &Arc<Mutex<Connection>>,
-    username: &str,
-) -> Result<Option<Admin>, Box<dyn std::error::Error>> {
-    let conn = db.lock().unwrap();
-
-    let mut stmt = conn
-        .prepare("SELECT id, username, password_hash, created_at FROM admins WHERE username = ?")?;
+pub fn get_project_by_id(
+    db: &DbPool,
+    id: &str,
+) -> Result<Option<Project>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
 
-    let admin_result = stmt.query_row([username], |row| {
-        Ok(Admin {
-            id: row.get(0)?,
-            username: row.get(1)?,
-            password_hash: row.get(2)?,
-            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
-                .unwrap()
-                .with_timezone(&Utc),
-        })
-    });
+    let result = conn.query_row(
+        "SELECT id, name, quota_bytes, created_at FROM projects WHERE id = ?",
+        [id],
+        row_to_project,
+    );
 
-    match admin_result {
-        Ok(admin) => Ok(Some(admin)),
+    match result {
+        Ok(project) => Ok(Some(project)),
         Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
         Err(e) => Err(Box::new(e)),
     }
 }
 
-pub fn create_upload_link(
-    db: &Arc<Mutex<Connection>>,
-    name: &str,
-    max_file_size: i64,
-    expires_at: Option<chrono::DateTime<Utc>>,
-) -> Result<String, Box<dyn std::error::Error>> {
-    let conn = db.lock().unwrap();
-
-    let link_id = Uuid::new_v4().to_string();
-    let token = Uuid::new_v4().to_string();
+/// Bytes of quota already handed out to the project's links
+pub fn get_project_allocated_bytes(
+    db: &DbPool,
+    id: &str,
+) -> Result<i64, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
 
-    conn.execute(
-        "INSERT INTO upload_links (id, token, name, max_file_size, remaining_quota, expires_at, created_at, is_active) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
-        params![
-            &link_id,
-            &token,
-            name,
-            max_file_size,
-            max_file_size, // remaining_quota starts as max_file_size
-            expires_at.map(|dt| dt.to_rfc3339()),
-            Utc::now().to_rfc3339(),
-            true,
-        ],
+    let allocated = conn.query_row(
+        "SELECT COALESCE(SUM(total_quota), 0) FROM upload_links WHERE project_id = ?",
+        [id],
+        |row| row.get(0),
     )?;
 
-    Ok(token)
+    Ok(allocated)
 }
 
-pub fn get_upload_link_by_token(
-    db: &Arc<Mutex<Connection>>,
-    token: &str,
-) -> Result<Option<UploadLink>, Box<dyn std::error::Error>> {
-    let conn = db.lock().unwrap();
+/// Every project with its link, quota and upload totals
+pub fn get_project_stats(db: &DbPool) -> Result<Vec<ProjectStats>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
 
     let mut stmt = conn.prepare(
-        "SELECT id, token, name, max_file_size, remaining_quota, expires_at, created_at, is_active FROM upload_links WHERE token = ?"
+        "SELECT p.id, p.name, p.quota_bytes, p.created_at, \
+             (SELECT COUNT(*) FROM upload_links l WHERE l.project_id = p.id), \
+             (SELECT COALESCE(SUM(l.total_quota), 0) FROM upload_links l WHERE l.project_id = p.id), \
+             (SELECT COUNT(*) FROM file_uploads f JOIN upload_links l ON f.link_id = l.id WHERE l.project_id = p.id), \
+             (SELECT COALESCE(SUM(f.file_size), 0) FROM file_uploads f JOIN upload_links l ON f.link_id = l.id WHERE l.project_id = p.id) \
+         FROM projects p ORDER BY p.name",
     )?;
 
-    let link_result = stmt.query_row([token], |row| {
-        let expires_at_str: Option<String> = row.get(5)?;
-        let expires_at = expires_at_str.map(|s| {
-            chrono::DateTime::parse_from_rfc3339(&s)
-                .unwrap()
-                .with_timezone(&Utc)
-        });
-
-        Ok(UploadLink {
-            id: row.get(0)?,
-            token: row.get(1)?,
-            name: row.get(2)?,
-            max_file_size: row.get(3)?,
-            remaining_quota: row.get(4)?,
-            expires_at,
-            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
-                .unwrap()
-                .with_timezone(&Utc),
-            is_active: row.get(7)?,
+    let stats_iter = stmt.query_map([], |row| {
+        Ok(ProjectStats {
+            project: row_to_project(row)?,
+            link_count: row.get(4)?,
+            allocated_bytes: row.get(5)?,
+            upload_count: row.get(6)?,
+            used_bytes: row.get(7)?,
         })
-    });
+    })?;
 
-    match link_result {
-        Ok(link) => Ok(Some(link)),
-        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-        Err(e) => Err(Box::new(e)),
+    let mut stats = Vec::new();
+    for project in stats_iter {
+        stats.push(project?);
     }
+
+    Ok(stats)
 }
 
-pub fn get_upload_link_by_id(
-    db: &Arc<Mutex<Connection>>,
+/// Delete a project; its links and their uploads are kept, just no longer grouped
+pub fn delete_project_and_ungroup_links(
+    db: &DbPool,
     id: &str,
-) -> Result<Option<UploadLink>, Box<dyn std::error::Error>> {
-    let conn = db.lock().unwrap();
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
 
     let mut stmt = conn.prepare(
-        "SELECT id, token, name, max_file_size, remaining_quota, expires_at, created_at, is_active FROM upload_links WHERE id = ?"
+        "UPDATE upload_links SET project_id = NULL WHERE project_id = ? RETURNING token",
     )?;
+    let tokens = stmt.query_map([id], |row| row.get::<_, String>(0))?;
+    for token in tokens {
+        invalidate_cached_link(&token?);
+    }
+
+    conn.execute("DELETE FROM projects WHERE id = ?", [id])?;
 
-    let link_result = stmt.query_row([id], |row| {
-        let expires_at_str: Option<String> = row.get(5)?;
-        let expires_at = expires_at_str.map(|s| {
+    Ok(())
+}
+
+/// Column list used by every share link query, in the order expected by `row_to_share_link`
+const SHARE_LINK_COLUMNS: &str =
+    "id, token, name, expires_at, max_downloads, download_count, created_at";
+
+fn row_to_share_link(row: &rusqlite::Row) -> SqliteResult<ShareLink> {
+    Ok(ShareLink {
+        id: row.get(0)?,
+        token: row.get(1)?,
+        name: row.get(2)?,
+        expires_at: row.get::<_, Option<String>>(3)?.map(|s| {
             chrono::DateTime::parse_from_rfc3339(&s)
                 .unwrap()
                 .with_timezone(&Utc)
-        });
-
-        Ok(UploadLink {
-            id: row.get(0)?,
-            token: row.get(1)?,
-            name: row.get(2)?,
-            max_file_size: row.get(3)?,
-            remaining_quota: row.get(4)?,
-            expires_at,
-            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
-                .unwrap()
-                .with_timezone(&Utc),
-            is_active: row.get(7)?,
-        })
-    });
+        }),
+        max_downloads: row.get(4)?,
+        download_count: row.get(5)?,
+        created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
+            .unwrap()
+            .with_timezone(&Utc),
+    })
+}
 
-    match link_result {
+/// Column list used by every shared file query, in the order expected by `row_to_shared_file`
+const SHARED_FILE_COLUMNS: &str = "id, share_link_id, upload_id, stored_filename, original_filename, file_size, mime_type, added_at, encryption_key_id";
+
+fn row_to_shared_file(row: &rusqlite::Row) -> SqliteResult<SharedFile> {
+    Ok(SharedFile {
+        id: row.get(0)?,
+        share_link_id: row.get(1)?,
+        upload_id: row.get(2)?,
+        stored_filename: row.get(3)?,
+        original_filename: row.get(4)?,
+        file_size: row.get(5)?,
+        mime_type: row.get(6)?,
+        added_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?)
+            .unwrap()
+            .with_timezone(&Utc),
+        encryption_key_id: row.get(8)?,
+    })
+}
+
+/// Create a share link and return its ID
+pub fn create_share_link(
+    db: &DbPool,
+    name: &str,
+    expires_at: Option<DateTime<Utc>>,
+    max_downloads: Option<i64>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let id = Uuid::new_v4().to_string();
+    let token = Uuid::new_v4().to_string();
+
+    conn.execute(
+        "INSERT INTO share_links (id, token, name, expires_at, max_downloads, created_at) VALUES (?, ?, ?, ?, ?, ?)",
+        params![
+            &id,
+            &token,
+            name,
+            expires_at.map(|dt| dt.to_rfc3339()),
+            max_downloads,
+            Utc::now().to_rfc3339(),
+        ],
+    )?;
+
+    Ok(id)
+}
+
+pub fn get_share_link_by_id(
+    db: &DbPool,
+    id: &str,
+) -> Result<Option<ShareLink>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let result = conn.query_row(
+        &format!(
+            "SELECT {} FROM share_links WHERE id = ?",
+            SHARE_LINK_COLUMNS
+        ),
+        [id],
+        row_to_share_link,
+    );
+
+    match result {
         Ok(link) => Ok(Some(link)),
         Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
         Err(e) => Err(Box::new(e)),
     }
 }
 
-pub fn get_all_upload_links(
-    db: &Arc<Mutex<Connection>>,
-) -> Result<Vec<UploadLink>, Box<dyn std::error::Error>> {
-    let conn = db.lock().unwrap();
+pub fn get_share_link_by_token(
+    db: &DbPool,
+    token: &str,
+) -> Result<Option<ShareLink>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let result = conn.query_row(
+        &format!(
+            "SELECT {} FROM share_links WHERE token = ?",
+            SHARE_LINK_COLUMNS
+        ),
+        [token],
+        row_to_share_link,
+    );
 
-    let mut stmt = conn.prepare(
-        "SELECT id, token, name, max_file_size, remaining_quota, expires_at, created_at, is_active FROM upload_links ORDER BY created_at DESC"
-    )?;
+    match result {
+        Ok(link) => Ok(Some(link)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(Box::new(e)),
+    }
+}
 
-    let link_iter = stmt.query_map([], |row| {
-        let expires_at_str: Option<String> = row.get(5)?;
-        let expires_at = expires_at_str.map(|s| {
-            chrono::DateTime::parse_from_rfc3339(&s)
-                .unwrap()
-                .with_timezone(&Utc)
-        });
-
-        Ok(UploadLink {
-            id: row.get(0)?,
-            token: row.get(1)?,
-            name: row.get(2)?,
-            max_file_size: row.get(3)?,
-            remaining_quota: row.get(4)?,
-            expires_at,
-            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
-                .unwrap()
-                .with_timezone(&Utc),
-            is_active: row.get(7)?,
+/// Every share link with the number and total size of its files, newest first
+pub fn get_share_link_summaries(
+    db: &DbPool,
+) -> Result<Vec<ShareLinkSummary>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, \
+             (SELECT COUNT(*) FROM shared_files f WHERE f.share_link_id = share_links.id), \
+             (SELECT COALESCE(SUM(f.file_size), 0) FROM shared_files f WHERE f.share_link_id = share_links.id) \
+         FROM share_links ORDER BY created_at DESC",
+        SHARE_LINK_COLUMNS
+    ))?;
+
+    let summary_iter = stmt.query_map([], |row| {
+        Ok(ShareLinkSummary {
+            link: row_to_share_link(row)?,
+            file_count: row.get(7)?,
+            total_bytes: row.get(8)?,
         })
     })?;
 
-    let mut links = Vec::new();
-    for link in link_iter {
-        links.push(link?);
+    let mut summaries = Vec::new();
+    for summary in summary_iter {
+        summaries.push(summary?);
     }
 
-    Ok(links)
+    Ok(summaries)
 }
 
-pub fn delete_upload_link(
-    db: &Arc<Mutex<Connection>>,
+/// Delete a share link and its file list, returning the files it offered
+///
+/// Files the admin uploaded to the share are only referenced here, so the
+/// caller removes them from disk.
+pub fn delete_share_link(
+    db: &DbPool,
     id: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let conn = db.lock().unwrap();
+) -> Result<Vec<SharedFile>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    // Foreign keys aren't enforced, so the share's files go by hand
+    let mut stmt = conn.prepare(&format!(
+        "DELETE FROM shared_files WHERE share_link_id = ? RETURNING {}",
+        SHARED_FILE_COLUMNS
+    ))?;
+    let file_iter = stmt.query_map([id], row_to_shared_file)?;
+
+    let mut files = Vec::new();
+    for file in file_iter {
+        files.push(file?);
+    }
 
-    conn.execute("DELETE FROM upload_links WHERE id = ?", [id])?;
+    conn.execute("DELETE FROM share_links WHERE id = ?", [id])?;
 
-    Ok(())
+    Ok(files)
 }
 
-pub fn create_file_upload(
-    db: &Arc<Mutex<Connection>>,
-    link_id: &str,
-    original_filename: &str,
+/// Offer an existing upload on a share link
+///
+/// Returns false if the upload is already on the share.
+pub fn add_shared_upload(
+    db: &DbPool,
+    share_link_id: &str,
+    upload: &FileUpload,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let inserted = conn.execute(
+        "INSERT INTO shared_files (id, share_link_id, upload_id, original_filename, file_size, mime_type, added_at) \
+         SELECT ?, ?, ?, ?, ?, ?, ? \
+         WHERE NOT EXISTS (SELECT 1 FROM shared_files WHERE share_link_id = ? AND upload_id = ?)",
+        params![
+            Uuid::new_v4().to_string(),
+            share_link_id,
+            &upload.id,
+            &upload.original_filename,
+            upload.file_size,
+            &upload.mime_type,
+            Utc::now().to_rfc3339(),
+            share_link_id,
+            &upload.id,
+        ],
+    )?;
+
+    Ok(inserted > 0)
+}
+
+/// Record a file the admin uploaded to a share link, with the key it is
+/// encrypted with (None = unencrypted)
+pub fn add_shared_file(
+    db: &DbPool,
+    share_link_id: &str,
     stored_filename: &str,
+    original_filename: &str,
     file_size: i64,
     mime_type: &str,
-    guest_folder: &str,
+    encryption_key_id: Option<&str>,
 ) -> Result<String, Box<dyn std::error::Error>> {
-    let conn = db.lock().unwrap();
+    let conn = db.get()?;
 
     let id = Uuid::new_v4().to_string();
-    let uploaded_at = Utc::now();
-
     conn.execute(
-        "INSERT INTO file_uploads (id, link_id, original_filename, stored_filename, file_size, mime_type, uploaded_at, guest_folder) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        "INSERT INTO shared_files (id, share_link_id, stored_filename, original_filename, file_size, mime_type, added_at, encrypted, encryption_key_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
         params![
             &id,
-            link_id,
-            original_filename,
+            share_link_id,
             stored_filename,
+            original_filename,
             file_size,
             mime_type,
-            uploaded_at.to_rfc3339(),
-            guest_folder,
+            Utc::now().to_rfc3339(),
+            encryption_key_id.is_some(),
+            encryption_key_id,
         ],
     )?;
 
     Ok(id)
 }
 
-pub fn get_all_file_uploads(
-    db: &Arc<Mutex<Connection>>,
-) -> Result<Vec<FileUpload>, Box<dyn std::error::Error>> {
-    let conn = db.lock().unwrap();
+/// Files offered on a share link, in the order they were added
+pub fn get_shared_files(
+    db: &DbPool,
+    share_link_id: &str,
+) -> Result<Vec<SharedFile>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM shared_files WHERE share_link_id = ? ORDER BY added_at",
+        SHARED_FILE_COLUMNS
+    ))?;
+    let file_iter = stmt.query_map([share_link_id], row_to_shared_file)?;
+
+    let mut files = Vec::new();
+    for file in file_iter {
+        files.push(file?);
+    }
 
-    let mut stmt = conn.prepare(
-        "SELECT id, link_id, original_filename, stored_filename, file_size, mime_type, uploaded_at, guest_folder FROM file_uploads ORDER BY uploaded_at DESC"
-    )?;
-
-    let upload_iter = stmt.query_map([], |row| {
-        Ok(FileUpload {
-            id: row.get(0)?,
-            link_id: row.get(1)?,
-            original_filename: row.get(2)?,
-            stored_filename: row.get(3)?,
-            file_size: row.get(4)?,
-            mime_type: row.get(5)?,
-            uploaded_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
-                .unwrap()
-                .with_timezone(&Utc),
-            guest_folder: row.get(7)?,
-        })
-    })?;
+    Ok(files)
+}
 
-    let mut uploads = Vec::new();
-    for upload in upload_iter {
-        uploads.push(upload?);
+/// Files admins uploaded to any share link, as opposed to shared uploads
+pub fn get_admin_shared_files(db: &DbPool) -> Result<Vec<SharedFile>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM shared_files WHERE stored_filename IS NOT NULL",
+        SHARED_FILE_COLUMNS
+    ))?;
+    let file_iter = stmt.query_map([], row_to_shared_file)?;
+
+    let mut files = Vec::new();
+    for file in file_iter {
+        files.push(file?);
     }
 
-    Ok(uploads)
+    Ok(files)
 }
 
-pub fn get_file_uploads_by_link_id(
-    db: &Arc<Mutex<Connection>>,
-    link_id: &str,
-) -> Result<Vec<FileUpload>, Box<dyn std::error::Error>> {
-    let conn = db.lock().unwrap();
+/// Files admins uploaded to share links whose encryption isn't recorded yet
+pub fn get_shared_files_without_encryption_record(
+    db: &DbPool,
+) -> Result<Vec<SharedFile>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM shared_files WHERE stored_filename IS NOT NULL AND encrypted IS NULL",
+        SHARED_FILE_COLUMNS
+    ))?;
+    let file_iter = stmt.query_map([], row_to_shared_file)?;
+
+    let mut files = Vec::new();
+    for file in file_iter {
+        files.push(file?);
+    }
 
-    let mut stmt = conn.prepare(
-        "SELECT id, link_id, original_filename, stored_filename, file_size, mime_type, uploaded_at, guest_folder FROM file_uploads WHERE link_id = ? ORDER BY uploaded_at DESC"
-    )?;
-
-    let upload_iter = stmt.query_map([link_id], |row| {
-        Ok(FileUpload {
-            id: row.get(0)?,
-            link_id: row.get(1)?,
-            original_filename: row.get(2)?,
-            stored_filename: row.get(3)?,
-            file_size: row.get(4)?,
-            mime_type: row.get(5)?,
-            uploaded_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
-                .unwrap()
-                .with_timezone(&Utc),
-            guest_folder: row.get(7)?,
-        })
-    })?;
+    Ok(files)
+}
 
-    let mut uploads = Vec::new();
-    for upload in upload_iter {
-        uploads.push(upload?);
-    }
+/// Record the key a shared file is encrypted with (None = unencrypted)
+pub fn set_shared_file_encryption(
+    db: &DbPool,
+    id: &str,
+    key_id: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
 
-    Ok(uploads)
+    conn.execute(
+        "UPDATE shared_files SET encrypted = ?, encryption_key_id = ? WHERE id = ?",
+        params![key_id.is_some(), key_id, id],
+    )?;
+
+    Ok(())
 }
 
-pub fn get_file_upload_by_id(
-    db: &Arc<Mutex<Connection>>,
+/// One file of a share link; None if it isn't on that share
+pub fn get_shared_file(
+    db: &DbPool,
+    share_link_id: &str,
     id: &str,
-) -> Result<Option<FileUpload>, Box<dyn std::error::Error>> {
-    let conn = db.lock().unwrap();
+) -> Result<Option<SharedFile>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let result = conn.query_row(
+        &format!(
+            "SELECT {} FROM shared_files WHERE share_link_id = ? AND id = ?",
+            SHARED_FILE_COLUMNS
+        ),
+        [share_link_id, id],
+        row_to_shared_file,
+    );
 
-    let mut stmt = conn.prepare(
-        "SELECT id, link_id, original_filename, stored_filename, file_size, mime_type, uploaded_at, guest_folder FROM file_uploads WHERE id = ?"
-    )?;
-
-    let upload_result = stmt.query_row([id], |row| {
-        Ok(FileUpload {
-            id: row.get(0)?,
-            link_id: row.get(1)?,
-            original_filename: row.get(2)?,
-            stored_filename: row.get(3)?,
-            file_size: row.get(4)?,
-            mime_type: row.get(5)?,
-            uploaded_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
-                .unwrap()
-                .with_timezone(&Utc),
-            guest_folder: row.get(7)?,
-        })
-    });
+    match result {
+        Ok(file) => Ok(Some(file)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(Box::new(e)),
+    }
+}
 
-    match upload_result {
-        Ok(upload) => Ok(Some(upload)),
+/// Take a file off a share link, returning it so an admin-uploaded file can be removed from disk
+pub fn remove_shared_file(
+    db: &DbPool,
+    share_link_id: &str,
+    id: &str,
+) -> Result<Option<SharedFile>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let result = conn.query_row(
+        &format!(
+            "DELETE FROM shared_files WHERE share_link_id = ? AND id = ? RETURNING {}",
+            SHARED_FILE_COLUMNS
+        ),
+        [share_link_id, id],
+        row_to_shared_file,
+    );
+
+    match result {
+        Ok(file) => Ok(Some(file)),
         Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
         Err(e) => Err(Box::new(e)),
     }
 }
 
-pub fn update_admin_password(
-    db: &Arc<Mutex<Connection>>,
-    username: &str,
-    new_password_hash: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let conn = db.lock().unwrap();
+/// Count one download against a share link
+///
+/// Checks and increments in one statement, so concurrent downloads can't
+/// exceed the limit. Returns false if the share expired or has no
+/// downloads left.
+pub fn claim_share_download(db: &DbPool, id: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let changed = conn.execute(
+        "UPDATE share_links SET download_count = download_count + 1 \
+         WHERE id = ? \
+         AND (expires_at IS NULL OR expires_at > ?) \
+         AND (max_downloads IS NULL OR download_count < max_downloads)",
+        params![id, Utc::now().to_rfc3339()],
+    )?;
+
+    Ok(changed > 0)
+}
+
+/// Column list used by every API key query, in the order expected by `row_to_api_key`
+const API_KEY_COLUMNS: &str = "id, name, prefix, created_at, last_used_at";
+
+fn row_to_api_key(row: &rusqlite::Row) -> SqliteResult<ApiKey> {
+    Ok(ApiKey {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        prefix: row.get(2)?,
+        created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
+            .unwrap()
+            .with_timezone(&Utc),
+        last_used_at: row.get::<_, Option<String>>(4)?.map(|s| {
+            chrono::DateTime::parse_from_rfc3339(&s)
+                .unwrap()
+                .with_timezone(&Utc)
+        }),
+    })
+}
+
+/// Store a new API key by its hash and return its ID
+pub fn create_api_key(
+    db: &DbPool,
+    name: &str,
+    key_hash: &str,
+    prefix: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
 
+    let id = Uuid::new_v4().to_string();
     conn.execute(
-        "UPDATE admins SET password_hash = ? WHERE username = ?",
-        params![new_password_hash, username],
+        "INSERT INTO api_keys (id, name, key_hash, prefix, created_at) VALUES (?, ?, ?, ?, ?)",
+        params![&id, name, key_hash, prefix, Utc::now().to_rfc3339()],
     )?;
 
+    Ok(id)
+}
+
+/// All API keys, newest first
+pub fn get_api_keys(db: &DbPool) -> Result<Vec<ApiKey>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM api_keys ORDER BY created_at DESC",
+        API_KEY_COLUMNS
+    ))?;
+    let key_iter = stmt.query_map([], row_to_api_key)?;
+
+    let mut keys = Vec::new();
+    for key in key_iter {
+        keys.push(key?);
+    }
+
+    Ok(keys)
+}
+
+/// Find the API key with this hash and record that it was used
+pub fn use_api_key(
+    db: &DbPool,
+    key_hash: &str,
+) -> Result<Option<ApiKey>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let result = conn.query_row(
+        &format!(
+            "UPDATE api_keys SET last_used_at = ? WHERE key_hash = ? RETURNING {}",
+            API_KEY_COLUMNS
+        ),
+        params![Utc::now().to_rfc3339(), key_hash],
+        row_to_api_key,
+    );
+
+    match result {
+        Ok(key) => Ok(Some(key)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+pub fn delete_api_key(db: &DbPool, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute("DELETE FROM api_keys WHERE id = ?", [id])?;
+
     Ok(())
 }
 
-pub fn update_remaining_quota(
-    db: &Arc<Mutex<Connection>>,
-    link_id: &str,
-    uploaded_size: i64,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let conn = db.lock().unwrap();
+/// Column list used by every webhook query, in the order expected by `row_to_webhook`
+const WEBHOOK_COLUMNS: &str = "id, url, secret, events, created_at";
+
+fn row_to_webhook(row: &rusqlite::Row) -> SqliteResult<Webhook> {
+    Ok(Webhook {
+        id: row.get(0)?,
+        url: row.get(1)?,
+        secret: row.get(2)?,
+        events: row.get(3)?,
+        created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(4)?)
+            .unwrap()
+            .with_timezone(&Utc),
+    })
+}
+
+/// Add a webhook endpoint and return its ID
+pub fn create_webhook(
+    db: &DbPool,
+    url: &str,
+    secret: &str,
+    events: Option<&str>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
 
+    let id = Uuid::new_v4().to_string();
     conn.execute(
-        "UPDATE upload_links SET remaining_quota = remaining_quota - ? WHERE id = ?",
-        params![uploaded_size, link_id],
+        "INSERT INTO webhooks (id, url, secret, events, created_at) VALUES (?, ?, ?, ?, ?)",
+        params![&id, url, secret, events, Utc::now().to_rfc3339()],
     )?;
 
-    Ok(())
+    Ok(id)
 }
 
-pub fn delete_file_upload(
-    db: &Arc<Mutex<Connection>>,
+/// All webhook endpoints, oldest first
+pub fn get_webhooks(db: &DbPool) -> Result<Vec<Webhook>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM webhooks ORDER BY created_at",
+        WEBHOOK_COLUMNS
+    ))?;
+    let webhook_iter = stmt.query_map([], row_to_webhook)?;
+
+    let mut webhooks = Vec::new();
+    for webhook in webhook_iter {
+        webhooks.push(webhook?);
+    }
+
+    Ok(webhooks)
+}
+
+pub fn get_webhook_by_id(
+    db: &DbPool,
     id: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let conn = db.lock().unwrap();
+) -> Result<Option<Webhook>, Box<dyn std::error::Error>> {
+    let conn = db.get()?;
 
-    conn.execute("DELETE FROM file_uploads WHERE id = ?", [id])?;
+    let result = conn.query_row(
+        &format!("SELECT {} FROM webhooks WHERE id = ?", WEBHOOK_COLUMNS),
+        [id],
+        row_to_webhook,
+    );
+
+    match result {
+        Ok(webhook) => Ok(Some(webhook)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+/// Remove a webhook endpoint; deliveries still queued for it are dropped when they run
+pub fn delete_webhook(db: &DbPool, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db.get()?;
+
+    conn.execute("DELETE FROM webhooks WHERE id = ?", [id])?;
 
     Ok(())
 }
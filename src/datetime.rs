@@ -0,0 +1,91 @@
+//! # Displaying Dates and Times
+//!
+//! Timestamps are stored in UTC. Everything shown to people goes through
+//! [`DisplayTime`], which converts to the display time zone and applies the
+//! configured formats, so pages, emails and exports agree with each other.
+//!
+//! ## Configuration
+//! - `DISPLAY_TIMEZONE`: IANA time zone, e.g. `Europe/Berlin` (default: `UTC`)
+//! - `DATETIME_FORMAT`: strftime format for timestamps (default: `%Y-%m-%d %H:%M %Z`)
+//! - `DATE_FORMAT`: strftime format for dates (default: `%Y-%m-%d`)
+//!
+//! Admins can override the time zone for themselves in their account settings.
+
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Utc,
+};
+use chrono_tz::Tz;
+
+use crate::config::Config;
+
+/// Converts UTC timestamps to the display time zone and formats them
+#[derive(Debug, Clone)]
+pub struct DisplayTime {
+    timezone: Tz,
+    datetime_format: String,
+    date_format: String,
+}
+
+impl DisplayTime {
+    /// Formatting with the server-wide defaults (guest pages, emails)
+    pub fn from_config(config: &Config) -> Self {
+        Self::for_timezone(config, None)
+    }
+
+    /// Formatting with a preferred time zone, falling back to the configured one
+    pub fn for_timezone(config: &Config, timezone: Option<&str>) -> Self {
+        Self {
+            timezone: timezone
+                .and_then(|name| name.parse().ok())
+                .unwrap_or(config.display_timezone),
+            datetime_format: config.datetime_format.clone(),
+            date_format: config.date_format.clone(),
+        }
+    }
+
+    /// Format a timestamp, e.g. `2025-08-01 14:30 CEST`
+    pub fn format(&self, timestamp: impl Timestamp) -> String {
+        timestamp
+            .utc()
+            .with_timezone(&self.timezone)
+            .format(&self.datetime_format)
+            .to_string()
+    }
+
+    /// Format just the (local) date of a timestamp
+    pub fn format_date(&self, timestamp: impl Timestamp) -> String {
+        timestamp
+            .utc()
+            .with_timezone(&self.timezone)
+            .format(&self.date_format)
+            .to_string()
+    }
+}
+
+/// Whether a strftime format string is usable (chrono panics on invalid ones when rendering)
+pub fn is_valid_format(format: &str) -> bool {
+    !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
+/// Names of all known time zones, for the preference picker
+pub fn timezone_names() -> impl Iterator<Item = &'static str> {
+    chrono_tz::TZ_VARIANTS.iter().map(|tz| tz.name())
+}
+
+/// A timestamp as templates pass it: owned or behind any number of references
+pub trait Timestamp {
+    fn utc(&self) -> DateTime<Utc>;
+}
+
+impl Timestamp for DateTime<Utc> {
+    fn utc(&self) -> DateTime<Utc> {
+        *self
+    }
+}
+
+impl<T: Timestamp + ?Sized> Timestamp for &T {
+    fn utc(&self) -> DateTime<Utc> {
+        (**self).utc()
+    }
+}
@@ -0,0 +1,133 @@
+//! # Email Templates
+//!
+//! Subjects and bodies of notification emails are templates with
+//! `{{variable}}` placeholders. Every kind of email has a built-in default;
+//! admins can override it under `/admin/email-templates`, which stores the
+//! edited version in the `email_templates` table.
+//!
+//! ## Adding an Email
+//! Add a [`TemplateKind`] to [`KINDS`] with its defaults, variables and
+//! sample values, then call [`render`] with the kind's key where the email
+//! is queued.
+
+use crate::database::{get_email_template, DbPool};
+
+/// A kind of notification email that can be customized
+pub struct TemplateKind {
+    /// Stable identifier, used in URLs and the database
+    pub key: &'static str,
+
+    /// Variables available in this template, with sample values for previews
+    pub variables: &'static [(&'static str, &'static str)],
+
+    pub default_subject: &'static str,
+    pub default_body: &'static str,
+}
+
+/// All customizable emails
+pub const KINDS: &[TemplateKind] = &[
+    TemplateKind {
+        key: "expiry_reminder_admin",
+        variables: &[
+            ("link_name", "Client Documents"),
+            ("expires_at", "2025-08-01 14:30 UTC"),
+            ("remaining_quota", "100.0 MB"),
+            ("upload_url", "https://drop.example.com/upload/3f2c9a1e-…"),
+        ],
+        default_subject: "Upload link \"{{link_name}}\" expires soon",
+        default_body: "The upload link \"{{link_name}}\" expires on {{expires_at}} and has not received any files yet.\n\n\
+             Remaining quota: {{remaining_quota}}\n\n\
+             Extend the link or contact the guest if you are still waiting for files.\n",
+    },
+    TemplateKind {
+        key: "expiry_reminder_guest",
+        variables: &[
+            ("link_name", "Client Documents"),
+            ("expires_at", "2025-08-01 14:30 UTC"),
+            ("upload_url", "https://drop.example.com/upload/3f2c9a1e-…"),
+        ],
+        default_subject: "Reminder: your upload link \"{{link_name}}\" expires soon",
+        default_body: "Hello,\n\n\
+             this is a reminder that the upload link \"{{link_name}}\" you received expires on {{expires_at}}.\n\
+             No files have been uploaded yet. Please upload your files before then:\n\n\
+             {{upload_url}}\n",
+    },
+    TemplateKind {
+        key: "upload_received",
+        variables: &[
+            ("link_name", "Client Documents"),
+            ("filename", "tax-return-2024.pdf"),
+            ("file_size", "2.4 MB"),
+            ("uploaded_at", "2025-08-01 14:30 UTC"),
+            ("guest_folder", "2025-08-01_143012_a1b2c3d4"),
+            ("uploader_email", "client@example.com"),
+        ],
+        default_subject: "New file on \"{{link_name}}\": {{filename}}",
+        default_body: "A file was uploaded to the link \"{{link_name}}\".\n\n\
+             File: {{filename}} ({{file_size}})\n\
+             Received: {{uploaded_at}}\n\
+             Guest folder: {{guest_folder}}\n\n\
+             Download it from the uploads page of the admin interface.\n",
+    },
+];
+
+/// Look up a template kind by key
+pub fn kind(key: &str) -> Option<&'static TemplateKind> {
+    KINDS.iter().find(|kind| kind.key == key)
+}
+
+impl TemplateKind {
+    /// Sample values used to preview the template in the editor
+    pub fn sample_values(&self) -> Vec<(&'static str, String)> {
+        self.variables
+            .iter()
+            .map(|(name, sample)| (*name, sample.to_string()))
+            .collect()
+    }
+}
+
+/// Replace `{{name}}` placeholders; unknown placeholders are left untouched
+pub fn substitute(text: &str, values: &[(&str, String)]) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+
+        let Some(end) = after_open.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+
+        let name = after_open[..end].trim();
+        match values.iter().find(|(key, _)| *key == name) {
+            Some((_, value)) => output.push_str(value),
+            None => output.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after_open[end + 2..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Render the subject and body of an email, preferring the admin's customized version
+pub fn render(
+    db: &DbPool,
+    key: &str,
+    values: &[(&str, String)],
+) -> Result<(String, String), Box<dyn std::error::Error>> {
+    let (subject, body) = match get_email_template(db, key)? {
+        Some(template) => (template.subject, template.body),
+        None => {
+            let kind = kind(key).ok_or_else(|| format!("Unknown email template: {}", key))?;
+            (
+                kind.default_subject.to_string(),
+                kind.default_body.to_string(),
+            )
+        }
+    };
+
+    Ok((substitute(&subject, values), substitute(&body, values)))
+}
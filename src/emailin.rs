@@ -0,0 +1,329 @@
+//! # Email-In Gateway
+//!
+//! Lets guests who won't use a web form send files by email. A poller checks
+//! an IMAP mailbox and turns the attachments of every new message into
+//! uploads on the link addressed by the recipient.
+//!
+//! ## Addressing
+//! Each link is reached through plus-addressing on the gateway mailbox:
+//! mail to `drop+<link token>@example.com` lands in the `drop@example.com`
+//! inbox and is filed under the link with that token. As with the upload URL,
+//! anyone who knows the address can send files to the link.
+//!
+//! ## Configuration
+//! - `EMAIL_IN_IMAP_HOST`: IMAP server (unset = gateway disabled)
+//! - `EMAIL_IN_IMAP_PORT`: IMAPS port (default: `993`)
+//! - `EMAIL_IN_USERNAME` / `EMAIL_IN_PASSWORD`: Mailbox credentials
+//! - `EMAIL_IN_MAILBOX`: Folder to poll (default: `INBOX`)
+//! - `EMAIL_IN_ADDRESS`: Public gateway address shown on the links page
+//! - `EMAIL_IN_POLL_SECS`: Poll interval (default: `60`)
+//!
+//! Processed messages are marked as seen, whether or not they could be
+//! filed, so a bad message is never retried in a loop. Messages are fetched
+//! whole, so attachment size is bounded by the mail server's message limit.
+
+use mail_parser::{MessageParser, MimeHeaders};
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio_rustls::{
+    rustls::{crypto::ring, pki_types::ServerName, ClientConfig, RootCertStore},
+    TlsConnector,
+};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    database::get_upload_link_by_token,
+    imap,
+    ingest::{store_upload, IngestError},
+    models::Uploader,
+    AppState,
+};
+
+/// Settings needed to reach the gateway mailbox
+struct Mailbox {
+    host: String,
+    port: u16,
+    username: String,
+    password: String,
+    folder: String,
+}
+
+/// Start the mailbox poller if the gateway is configured
+pub fn spawn_poller(state: AppState) {
+    let config = &state.config;
+    let mailbox = match (
+        &config.email_in_imap_host,
+        &config.email_in_username,
+        &config.email_in_password,
+    ) {
+        (Some(host), Some(username), Some(password)) => Mailbox {
+            host: host.clone(),
+            port: config.email_in_imap_port,
+            username: username.clone(),
+            password: password.clone(),
+            folder: config.email_in_mailbox.clone(),
+        },
+        (Some(_), _, _) => {
+            warn!("EMAIL_IN_IMAP_HOST is set but credentials are missing, email-in disabled");
+            return;
+        }
+        _ => return,
+    };
+
+    info!(
+        host = %mailbox.host,
+        folder = %mailbox.folder,
+        poll_secs = config.email_in_poll_secs,
+        "Starting email-in gateway"
+    );
+
+    let interval = std::time::Duration::from_secs(config.email_in_poll_secs.max(10));
+    tokio::spawn(async move {
+        loop {
+            match poll_mailbox(&state, &mailbox).await {
+                Ok(0) => {}
+                Ok(count) => info!(count = count, "Processed incoming emails"),
+                Err(e) => error!(error = %e, "Failed to poll email-in mailbox"),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+/// Fetch and process all unseen messages once
+///
+/// # Returns
+/// Number of messages processed
+async fn poll_mailbox(state: &AppState, mailbox: &Mailbox) -> Result<usize, String> {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let tls_config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("Failed to set up TLS: {}", e))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let connector = TlsConnector::from(Arc::new(tls_config));
+
+    let server_name = ServerName::try_from(mailbox.host.clone())
+        .map_err(|e| format!("Invalid IMAP host: {}", e))?;
+    let tcp = TcpStream::connect((mailbox.host.as_str(), mailbox.port))
+        .await
+        .map_err(|e| format!("Failed to connect to IMAP server: {}", e))?;
+    let tls = connector
+        .connect(server_name, tcp)
+        .await
+        .map_err(|e| format!("TLS handshake with IMAP server failed: {}", e))?;
+
+    let mut session = imap::Session::start(tls)
+        .await
+        .map_err(|e| format!("IMAP server refused the connection: {}", e))?;
+
+    session
+        .login(&mailbox.username, &mailbox.password)
+        .await
+        .map_err(|e| format!("IMAP login failed: {}", e))?;
+
+    session
+        .select(&mailbox.folder)
+        .await
+        .map_err(|e| format!("Failed to select mailbox {}: {}", mailbox.folder, e))?;
+
+    let uids = session
+        .uid_search_unseen()
+        .await
+        .map_err(|e| format!("IMAP search failed: {}", e))?;
+
+    let mut processed = 0;
+    for uid in uids {
+        // Fetch one message at a time so only one is held in memory
+        let raw = session
+            .uid_fetch_message(uid)
+            .await
+            .map_err(|e| format!("IMAP fetch failed: {}", e))?;
+        if let Some(raw) = raw {
+            process_message(state, &raw).await;
+        }
+
+        // Mark as seen even when the message couldn't be filed
+        session
+            .uid_mark_seen(uid)
+            .await
+            .map_err(|e| format!("IMAP store failed: {}", e))?;
+
+        processed += 1;
+    }
+
+    let _ = session.logout().await;
+    Ok(processed)
+}
+
+/// File the attachments of one message under the addressed link
+async fn process_message(state: &AppState, raw: &[u8]) {
+    let Some(message) = MessageParser::default().parse(raw) else {
+        warn!("Skipping unparseable email");
+        return;
+    };
+
+    let sender = message
+        .from()
+        .and_then(|from| from.first())
+        .and_then(|addr| addr.address())
+        .unwrap_or("unknown")
+        .to_string();
+
+    // Recipients from the envelope headers first, then To and Cc
+    let mut recipients: Vec<String> = ["Delivered-To", "X-Original-To"]
+        .iter()
+        .filter_map(|name| message.header_raw(*name))
+        .map(|value| value.trim().trim_matches(['<', '>']).to_string())
+        .collect();
+    for list in [message.to(), message.cc()].into_iter().flatten() {
+        recipients.extend(
+            list.iter()
+                .filter_map(|addr| addr.address())
+                .map(|address| address.to_string()),
+        );
+    }
+
+    let Some(token) = recipients
+        .iter()
+        .find_map(|address| token_from_address(address))
+    else {
+        warn!(sender = %sender, "Email is not addressed to an upload link, ignoring");
+        return;
+    };
+
+    let link = match get_upload_link_by_token(&state.db, &token) {
+        Ok(Some(link)) => link,
+        Ok(None) => {
+            warn!(sender = %sender, token = %token, "Email addressed to unknown upload link");
+            return;
+        }
+        Err(e) => {
+            error!(token = %token, error = %e, "Database error while fetching upload link");
+            return;
+        }
+    };
+
+    // All attachments of one email land in the same folder
+    let guest_folder = uuid::Uuid::new_v4().to_string();
+    let uploader = Uploader {
+        ip: None,
+        email: Some(sender.clone()).filter(|sender| sender != "unknown"),
+        request_id: None,
+        user_agent: None,
+        name: None,
+    };
+    let mut stored = 0;
+    for attachment in message.attachments() {
+        let filename = attachment
+            .attachment_name()
+            .unwrap_or("attachment")
+            .to_string();
+        let content_type = attachment
+            .content_type()
+            .map(|ct| match ct.subtype() {
+                Some(subtype) => format!("{}/{}", ct.ctype(), subtype),
+                None => ct.ctype().to_string(),
+            })
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+
+        // Re-read the link each time: every stored file lowers the remaining quota
+        let current_link = match get_upload_link_by_token(&state.db, &token) {
+            Ok(Some(current)) => current,
+            _ => link.clone(),
+        };
+
+        match store_upload(
+            state,
+            &current_link,
+            &filename,
+            &content_type,
+            Some(&guest_folder),
+            &uploader,
+            attachment.contents(),
+        )
+        .await
+        {
+            Ok(upload) => {
+                debug!(upload_id = %upload.id, filename = %filename, "Stored email attachment");
+                stored += 1;
+            }
+            Err(
+                IngestError::QuotaExceeded
+                | IngestError::GuestQuotaExceeded(_)
+                | IngestError::DailyQuotaExceeded(_)
+                | IngestError::LinkUnavailable
+                | IngestError::FileLimitReached,
+            ) => {
+                warn!(
+                    sender = %sender,
+                    link_id = %link.id,
+                    filename = %filename,
+                    "Email attachment rejected: link expired or out of quota"
+                );
+            }
+            Err(e @ (IngestError::Rejected(_) | IngestError::RulesRefused(_))) => {
+                warn!(
+                    sender = %sender,
+                    link_id = %link.id,
+                    filename = %filename,
+                    reason = %e,
+                    "Email attachment refused by upload hook or rules"
+                );
+            }
+            Err(e @ (IngestError::FileType | IngestError::ContentMismatch)) => {
+                warn!(
+                    sender = %sender,
+                    link_id = %link.id,
+                    filename = %filename,
+                    reason = %e,
+                    "Email attachment rejected: file type not accepted"
+                );
+            }
+            Err(IngestError::Infected(signature)) => {
+                warn!(
+                    sender = %sender,
+                    link_id = %link.id,
+                    filename = %filename,
+                    signature = %signature,
+                    "Email attachment rejected: virus scanner found malware"
+                );
+            }
+            Err(IngestError::Duplicate) => {
+                warn!(
+                    sender = %sender,
+                    link_id = %link.id,
+                    filename = %filename,
+                    "Email attachment rejected: a file with this name already exists"
+                );
+            }
+            Err(IngestError::SameContent(existing)) => {
+                info!(
+                    sender = %sender,
+                    link_id = %link.id,
+                    filename = %filename,
+                    existing_upload_id = %existing.id,
+                    "Email attachment skipped: the same file was already uploaded"
+                );
+            }
+            Err(e) => {
+                error!(link_id = %link.id, filename = %filename, error = %e, "Failed to store email attachment");
+            }
+        }
+    }
+
+    info!(
+        sender = %sender,
+        link_id = %link.id,
+        attachments = stored,
+        "Email processed"
+    );
+}
+
+/// Extract the link token from a plus-address (`drop+<token>@example.com`)
+fn token_from_address(address: &str) -> Option<String> {
+    let (local, _domain) = address.rsplit_once('@')?;
+    let (_, tag) = local.split_once('+')?;
+    uuid::Uuid::parse_str(tag).ok().map(|_| tag.to_string())
+}
@@ -0,0 +1,682 @@
+//! # Encryption at Rest
+//!
+//! With a key configured, every uploaded file is encrypted with AES-256-GCM
+//! once it has passed the upload checks (file type, virus scan, rules) and
+//! before it is recorded. Everything that reads stored files back (downloads,
+//! ZIP archives, share links, the S3 and REST APIs, WebDAV pushes, the
+//! personal data scan, integrity checks) goes through [`open`], which decrypts
+//! on the fly and passes files stored without encryption through unchanged.
+//! Files can therefore be mixed: those uploaded before encryption was switched
+//! on stay readable, and [`rotate`] encrypts them later.
+//!
+//! Whether a file is encrypted, and with which key, is recorded with its
+//! upload or share link file (`encrypted`, `encryption_key_id`) when it is
+//! stored, and that record decides how it is read. The file's own bytes never
+//! do, so a guest's file that happens to start like an encrypted one is still
+//! served as it was uploaded. Files stored before the record existed are
+//! looked at once, at startup (see [`record_existing`]).
+//!
+//! Files admins upload for share links are encrypted the same way.
+//! Replication copies the encrypted files as they are, so a replica needs the
+//! same key. Post-upload commands get a decrypted copy (see
+//! [`crate::commands`]).
+//!
+//! ## File Format
+//! A 32-byte header (magic `NADENC01`, key ID, random nonce prefix, plaintext
+//! length) followed by the contents in 64 KiB chunks. Each chunk is sealed
+//! with the nonce prefix and its index as nonce and the header as associated
+//! data, so chunks can't be reordered, dropped or moved between files. The key
+//! ID is the start of the key's SHA-256 and tells which key a file needs; it
+//! is recorded in hex.
+//!
+//! ## Key Rotation
+//! Set the new key as `ENCRYPTION_KEY`, list the old one in
+//! `ENCRYPTION_PREVIOUS_KEYS` and run `needadrop rotate-encryption-key`. It
+//! re-encrypts every stored file, uploads and share link files alike, that
+//! isn't under the current key yet (including files stored without
+//! encryption); the server can keep running
+//! meanwhile. Once it reports no failures, the old key can be removed.
+//!
+//! ## Configuration
+//! - `ENCRYPTION_KEY`: 32-byte key as 64 hex characters, e.g. from `openssl rand -hex 32`
+//!   (or `ENCRYPTION_KEY_FILE`; unset = files are stored unencrypted)
+//! - `ENCRYPTION_PREVIOUS_KEYS`: Comma-separated older keys that files may still be encrypted with
+
+use bytes::Bytes;
+use ring::{aead, digest, rand::SecureRandom};
+use std::{io, path::Path, pin::Pin, sync::Arc};
+use tokio::{
+    fs,
+    io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+};
+use tokio_util::io::StreamReader;
+use tracing::{error, info, warn};
+
+use crate::{config::Config, database::*, storage};
+
+/// First bytes of every encrypted file
+const MAGIC: &[u8; 8] = b"NADENC01";
+
+/// Length of the file header: magic, key ID, nonce prefix, plaintext length
+const HEADER_LEN: usize = 32;
+
+/// Plaintext bytes per sealed chunk
+const CHUNK_LEN: u64 = 64 * 1024;
+
+/// A stored file opened for reading, decrypted if needed
+pub type StoredReader = Pin<Box<dyn AsyncRead + Send + Sync>>;
+
+/// A key files are encrypted with
+pub struct Key {
+    id: [u8; 8],
+    key: aead::LessSafeKey,
+}
+
+impl Key {
+    /// Parse a key given as 64 hex characters
+    fn parse(hex: &str) -> Result<Key, String> {
+        let hex = hex.trim();
+        if hex.len() != 64 || !hex.is_ascii() {
+            return Err("encryption keys must be 64 hex characters (32 bytes)".to_string());
+        }
+        let bytes = (0..32)
+            .map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| "encryption keys must be 64 hex characters (32 bytes)".to_string())?;
+
+        let mut id = [0u8; 8];
+        id.copy_from_slice(&digest::digest(&digest::SHA256, &bytes).as_ref()[..8]);
+        let key = aead::UnboundKey::new(&aead::AES_256_GCM, &bytes)
+            .map_err(|_| "invalid encryption key".to_string())?;
+
+        Ok(Key {
+            id,
+            key: aead::LessSafeKey::new(key),
+        })
+    }
+}
+
+/// The current key and the older keys files may still be encrypted with
+pub struct Keys {
+    current: Arc<Key>,
+    previous: Vec<Arc<Key>>,
+}
+
+impl Keys {
+    /// Load the keys from the configuration (None = encryption is off)
+    pub fn from_config(config: &Config) -> Result<Option<Keys>, String> {
+        let Some(current) = config.encryption_key.as_deref() else {
+            if !config.encryption_previous_keys.is_empty() {
+                return Err("ENCRYPTION_PREVIOUS_KEYS requires ENCRYPTION_KEY".to_string());
+            }
+            return Ok(None);
+        };
+
+        let current = Arc::new(Key::parse(current)?);
+        let previous = config
+            .encryption_previous_keys
+            .iter()
+            .map(|key| Key::parse(key).map(Arc::new))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        info!(previous_keys = previous.len(), "Encryption at rest enabled");
+        Ok(Some(Keys { current, previous }))
+    }
+
+    /// Hex ID of the key new files are encrypted with
+    pub fn current_id(&self) -> String {
+        hex_id(&self.current.id)
+    }
+
+    fn find(&self, id: &[u8; 8]) -> Option<&Arc<Key>> {
+        std::iter::once(&self.current)
+            .chain(&self.previous)
+            .find(|key| &key.id == id)
+    }
+}
+
+/// Header at the start of an encrypted file
+#[derive(Clone, Copy)]
+struct Header {
+    key_id: [u8; 8],
+    nonce_prefix: [u8; 8],
+    plaintext_len: u64,
+}
+
+impl Header {
+    fn new(key: &Key, plaintext_len: u64) -> io::Result<Header> {
+        let mut nonce_prefix = [0u8; 8];
+        ring::rand::SystemRandom::new()
+            .fill(&mut nonce_prefix)
+            .map_err(|_| io::Error::other("failed to generate a nonce"))?;
+        Ok(Header {
+            key_id: key.id,
+            nonce_prefix,
+            plaintext_len,
+        })
+    }
+
+    fn to_bytes(self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0u8; HEADER_LEN];
+        bytes[..8].copy_from_slice(MAGIC);
+        bytes[8..16].copy_from_slice(&self.key_id);
+        bytes[16..24].copy_from_slice(&self.nonce_prefix);
+        bytes[24..].copy_from_slice(&self.plaintext_len.to_be_bytes());
+        bytes
+    }
+
+    fn parse(bytes: &[u8; HEADER_LEN]) -> Option<Header> {
+        if &bytes[..8] != MAGIC {
+            return None;
+        }
+        let mut key_id = [0u8; 8];
+        key_id.copy_from_slice(&bytes[8..16]);
+        let mut nonce_prefix = [0u8; 8];
+        nonce_prefix.copy_from_slice(&bytes[16..24]);
+        let mut plaintext_len = [0u8; 8];
+        plaintext_len.copy_from_slice(&bytes[24..]);
+        Some(Header {
+            key_id,
+            nonce_prefix,
+            plaintext_len: u64::from_be_bytes(plaintext_len),
+        })
+    }
+
+    /// Number of sealed chunks; an empty file still has one, so it is authenticated too
+    fn chunk_count(&self) -> u64 {
+        self.plaintext_len.div_ceil(CHUNK_LEN).max(1)
+    }
+
+    /// Plaintext length of chunk `index`
+    fn chunk_len(&self, index: u64) -> usize {
+        (self.plaintext_len - index * CHUNK_LEN).min(CHUNK_LEN) as usize
+    }
+
+    fn nonce(&self, index: u64) -> io::Result<aead::Nonce> {
+        let index = u32::try_from(index).map_err(|_| io::Error::other("file too large"))?;
+        let mut nonce = [0u8; aead::NONCE_LEN];
+        nonce[..8].copy_from_slice(&self.nonce_prefix);
+        nonce[8..].copy_from_slice(&index.to_be_bytes());
+        Ok(aead::Nonce::assume_unique_for_key(nonce))
+    }
+}
+
+/// A key ID as recorded in the database
+fn hex_id(id: &[u8; 8]) -> String {
+    id.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Read the header of an encrypted file, leaving other files at their start
+async fn read_header(file: &mut fs::File) -> io::Result<Option<Header>> {
+    let mut bytes = [0u8; HEADER_LEN];
+    let header = match file.read_exact(&mut bytes).await {
+        Ok(_) => Header::parse(&bytes),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+        Err(e) => return Err(e),
+    };
+    if header.is_none() {
+        file.seek(io::SeekFrom::Start(0)).await?;
+    }
+    Ok(header)
+}
+
+/// Open a stored file for reading, returning its contents and their length
+///
+/// `key_id` is the key recorded for the file (None = stored without
+/// encryption, read as it is). Encrypted files are decrypted as they are
+/// read; a chunk that fails to authenticate ends the stream with an error.
+pub async fn open(
+    keys: Option<&Keys>,
+    path: &Path,
+    key_id: Option<&str>,
+) -> io::Result<(StoredReader, u64)> {
+    let mut file = fs::File::open(path).await?;
+
+    if key_id.is_none() {
+        let len = file.metadata().await?.len();
+        return Ok((Box::pin(file), len));
+    }
+    // The header names the key; during a rotation it may already be the new one
+    let header = read_header(&mut file).await?.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "file is recorded as encrypted but isn't",
+        )
+    })?;
+    let key = keys
+        .and_then(|keys| keys.find(&header.key_id))
+        .cloned()
+        .ok_or_else(|| io::Error::other("file is encrypted with a key that isn't configured"))?;
+
+    let chunks = futures::stream::try_unfold((file, 0u64), move |(mut file, index)| {
+        let key = key.clone();
+        async move {
+            if index >= header.chunk_count() {
+                return Ok(None);
+            }
+            let len = header.chunk_len(index);
+            let mut chunk = vec![0u8; len + aead::AES_256_GCM.tag_len()];
+            file.read_exact(&mut chunk).await?;
+            key.key
+                .open_in_place(
+                    header.nonce(index)?,
+                    aead::Aad::from(header.to_bytes()),
+                    &mut chunk,
+                )
+                .map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidData, "file failed to decrypt")
+                })?;
+            chunk.truncate(len);
+            Ok::<_, io::Error>(Some((Bytes::from(chunk), (file, index + 1))))
+        }
+    });
+
+    Ok((Box::pin(StreamReader::new(chunks)), header.plaintext_len))
+}
+
+/// Length of a stored file's contents, without the encryption overhead
+pub async fn content_len(
+    keys: Option<&Keys>,
+    path: &Path,
+    key_id: Option<&str>,
+) -> io::Result<u64> {
+    open(keys, path, key_id).await.map(|(_, len)| len)
+}
+
+/// Encrypt a stored file with the current key, in place, returning the key's ID to record
+///
+/// `key_id` is the key recorded for the file so far (None = unencrypted);
+/// files encrypted with an older key are re-encrypted. The new version is
+/// written next to the file and renamed over it once complete.
+pub async fn encrypt_file(keys: &Keys, path: &Path, key_id: Option<&str>) -> io::Result<String> {
+    let (mut reader, plaintext_len) = open(Some(keys), path, key_id).await?;
+    let header = Header::new(&keys.current, plaintext_len)?;
+    let partial = storage::PartialFile::new(path);
+
+    let result = write_encrypted(&mut reader, &keys.current, header, partial.path()).await;
+    match result {
+        Ok(()) => {
+            fs::rename(&partial, path).await?;
+            Ok(keys.current_id())
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&partial).await;
+            Err(e)
+        }
+    }
+}
+
+async fn write_encrypted(
+    reader: &mut StoredReader,
+    key: &Key,
+    header: Header,
+    path: &Path,
+) -> io::Result<()> {
+    let mut file = fs::File::create(path).await?;
+    file.write_all(&header.to_bytes()).await?;
+
+    for index in 0..header.chunk_count() {
+        let mut chunk = vec![0u8; header.chunk_len(index)];
+        reader.read_exact(&mut chunk).await?;
+        key.key
+            .seal_in_place_append_tag(
+                header.nonce(index)?,
+                aead::Aad::from(header.to_bytes()),
+                &mut chunk,
+            )
+            .map_err(|_| io::Error::other("failed to encrypt"))?;
+        file.write_all(&chunk).await?;
+    }
+
+    file.flush().await?;
+    file.sync_all().await
+}
+
+/// Whether a file recorded with `key_id` still needs encrypting with the current key
+fn needs_rotation(keys: &Keys, key_id: Option<&str>) -> bool {
+    key_id != Some(keys.current_id().as_str())
+}
+
+/// Re-encrypt every stored file that isn't under the current key (`needadrop rotate-encryption-key`)
+pub async fn rotate(
+    keys: &Keys,
+    db: &DbPool,
+    upload_dir: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (mut rotated, mut current, mut failed) = (0usize, 0usize, 0usize);
+    let mut count = |id: &str, path: &Path, result: Result<bool, String>| match result {
+        Ok(true) => rotated += 1,
+        Ok(false) => current += 1,
+        Err(e) => {
+            error!(id = %id, path = %path.display(), error = %e, "Failed to re-encrypt file");
+            failed += 1;
+        }
+    };
+
+    for upload in get_stored_files(db)? {
+        let path = upload.file_path(upload_dir);
+        let key_id = upload.encryption_key_id.as_deref();
+        let result = match rotate_file(keys, &path, key_id).await {
+            Ok(Some(new_key_id)) => set_upload_encryption(db, &upload, Some(&new_key_id))
+                .map(|_| true)
+                .map_err(|e| e.to_string()),
+            Ok(None) => Ok(false),
+            Err(e) => Err(e.to_string()),
+        };
+        count(&upload.id, &path, result);
+    }
+
+    for file in get_admin_shared_files(db)? {
+        let Some(stored_filename) = &file.stored_filename else {
+            continue;
+        };
+        let path = storage::share_dir(upload_dir, &file.share_link_id).join(stored_filename);
+        let key_id = file.encryption_key_id.as_deref();
+        let result = match rotate_file(keys, &path, key_id).await {
+            Ok(Some(new_key_id)) => set_shared_file_encryption(db, &file.id, Some(&new_key_id))
+                .map(|_| true)
+                .map_err(|e| e.to_string()),
+            Ok(None) => Ok(false),
+            Err(e) => Err(e.to_string()),
+        };
+        count(&file.id, &path, result);
+    }
+
+    info!(
+        rotated,
+        already_current = current,
+        failed,
+        "Encryption key rotation finished"
+    );
+    if failed > 0 {
+        return Err(format!("{} files could not be re-encrypted", failed).into());
+    }
+    Ok(())
+}
+
+/// Re-encrypt one file recorded with `key_id` if it isn't under the current
+/// key, returning the new key's ID to record (None = already current)
+async fn rotate_file(keys: &Keys, path: &Path, key_id: Option<&str>) -> io::Result<Option<String>> {
+    if !needs_rotation(keys, key_id) {
+        return Ok(None);
+    }
+    encrypt_file(keys, path, key_id).await.map(Some)
+}
+
+/// Key ID in the header of a stored file (None = not encrypted)
+async fn key_id_on_disk(path: &Path) -> io::Result<Option<String>> {
+    let mut file = fs::File::open(path).await?;
+    Ok(read_header(&mut file)
+        .await?
+        .map(|header| hex_id(&header.key_id)))
+}
+
+/// Record the encryption of files stored before it was recorded, from their headers
+///
+/// Runs at startup; each file is looked at once. Files that can't be read
+/// stay unrecorded, are read as unencrypted and are looked at again next time.
+pub async fn record_existing(
+    db: &DbPool,
+    upload_dir: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut recorded = 0usize;
+
+    for upload in get_uploads_without_encryption_record(db)? {
+        let path = upload.file_path(upload_dir);
+        match key_id_on_disk(&path).await {
+            Ok(key_id) => {
+                set_upload_encryption(db, &upload, key_id.as_deref())?;
+                recorded += 1;
+            }
+            Err(e) => {
+                warn!(upload_id = %upload.id, path = %path.display(), error = %e, "Failed to check whether a stored file is encrypted");
+            }
+        }
+    }
+
+    for file in get_shared_files_without_encryption_record(db)? {
+        let Some(stored_filename) = &file.stored_filename else {
+            continue;
+        };
+        let path = storage::share_dir(upload_dir, &file.share_link_id).join(stored_filename);
+        match key_id_on_disk(&path).await {
+            Ok(key_id) => {
+                set_shared_file_encryption(db, &file.id, key_id.as_deref())?;
+                recorded += 1;
+            }
+            Err(e) => {
+                warn!(file_id = %file.id, path = %path.display(), error = %e, "Failed to check whether a stored file is encrypted");
+            }
+        }
+    }
+
+    if recorded > 0 {
+        info!(
+            recorded,
+            "Recorded the encryption of files stored before it was tracked"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(hex_byte: &str) -> Keys {
+        Keys {
+            current: Arc::new(Key::parse(&hex_byte.repeat(32)).unwrap()),
+            previous: Vec::new(),
+        }
+    }
+
+    /// Contents spanning a few chunks, different in every chunk
+    fn contents(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    async fn read_all(
+        keys: Option<&Keys>,
+        path: &Path,
+        key_id: Option<&str>,
+    ) -> io::Result<Vec<u8>> {
+        let (mut reader, _) = open(keys, path, key_id).await?;
+        let mut contents = Vec::new();
+        reader.read_to_end(&mut contents).await?;
+        Ok(contents)
+    }
+
+    /// Write `plaintext` to a file and encrypt it in place
+    async fn encrypted_file(dir: &Path, keys: &Keys, plaintext: &[u8]) -> std::path::PathBuf {
+        let path = dir.join("stored");
+        std::fs::write(&path, plaintext).unwrap();
+        encrypt_file(keys, &path, None).await.unwrap();
+        path
+    }
+
+    const TAG_LEN: usize = 16;
+    const SEALED_CHUNK_LEN: usize = CHUNK_LEN as usize + TAG_LEN;
+
+    #[tokio::test]
+    async fn round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let keys = keys("11");
+        let key_id = Some(keys.current_id());
+        let key_id = key_id.as_deref();
+
+        for len in [0, 1, CHUNK_LEN as usize, 3 * CHUNK_LEN as usize + 123] {
+            let plaintext = contents(len);
+            let path = encrypted_file(dir.path(), &keys, &plaintext).await;
+
+            let stored = std::fs::read(&path).unwrap();
+            let chunks = len.div_ceil(CHUNK_LEN as usize).max(1);
+            assert_eq!(&stored[..8], MAGIC);
+            assert_eq!(stored.len(), HEADER_LEN + len + chunks * TAG_LEN);
+            assert!(len < 64 || !stored.windows(64).any(|w| w == &plaintext[..64]));
+
+            assert_eq!(
+                read_all(Some(&keys), &path, key_id).await.unwrap(),
+                plaintext
+            );
+            assert_eq!(
+                content_len(Some(&keys), &path, key_id).await.unwrap(),
+                len as u64
+            );
+            assert_eq!(key_id_on_disk(&path).await.unwrap().as_deref(), key_id);
+        }
+    }
+
+    #[tokio::test]
+    async fn unencrypted_files_pass_through() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plain");
+        let plaintext = contents(1000);
+        std::fs::write(&path, &plaintext).unwrap();
+
+        assert_eq!(
+            read_all(Some(&keys("11")), &path, None).await.unwrap(),
+            plaintext
+        );
+        assert_eq!(read_all(None, &path, None).await.unwrap(), plaintext);
+        assert_eq!(key_id_on_disk(&path).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn files_recorded_unencrypted_are_never_decrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let keys = keys("11");
+
+        // A guest's file that starts exactly like an encrypted one
+        let disguised =
+            std::fs::read(encrypted_file(dir.path(), &keys, &contents(1000)).await).unwrap();
+        let path = dir.path().join("disguised");
+        std::fs::write(&path, &disguised).unwrap();
+
+        assert_eq!(read_all(Some(&keys), &path, None).await.unwrap(), disguised);
+        assert_eq!(
+            content_len(Some(&keys), &path, None).await.unwrap(),
+            disguised.len() as u64
+        );
+    }
+
+    #[tokio::test]
+    async fn files_recorded_encrypted_must_be() {
+        let dir = tempfile::tempdir().unwrap();
+        let keys = keys("11");
+        let path = dir.path().join("plain");
+        std::fs::write(&path, contents(1000)).unwrap();
+
+        let error = read_all(Some(&keys), &path, Some(&keys.current_id()))
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    async fn rotation_moves_files_to_the_current_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = keys("11");
+        let plaintext = contents(2 * CHUNK_LEN as usize + 5);
+        let path = encrypted_file(dir.path(), &old, &plaintext).await;
+
+        let rotated = Keys {
+            current: Arc::new(Key::parse(&"22".repeat(32)).unwrap()),
+            previous: vec![old.current.clone()],
+        };
+        let old_id = old.current_id();
+        assert!(needs_rotation(&rotated, None));
+        assert!(needs_rotation(&rotated, Some(&old_id)));
+        let new_id = encrypt_file(&rotated, &path, Some(&old_id)).await.unwrap();
+        assert_eq!(new_id, rotated.current_id());
+        assert!(!needs_rotation(&rotated, Some(&new_id)));
+
+        assert_eq!(
+            read_all(Some(&keys("22")), &path, Some(&new_id))
+                .await
+                .unwrap(),
+            plaintext
+        );
+        assert!(read_all(Some(&old), &path, Some(&new_id)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn truncated_file_fails_to_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let keys = keys("11");
+        let plaintext = contents(2 * CHUNK_LEN as usize + 100);
+        let path = encrypted_file(dir.path(), &keys, &plaintext).await;
+        let full_len = std::fs::metadata(&path).unwrap().len();
+
+        // Short by part of the last chunk, and by the whole last chunk
+        for cut in [10, (100 + TAG_LEN) as u64] {
+            let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+            file.set_len(full_len - cut).unwrap();
+            let error = read_all(Some(&keys), &path, Some(&keys.current_id()))
+                .await
+                .unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        }
+    }
+
+    #[tokio::test]
+    async fn reordered_chunks_fail_to_decrypt() {
+        let dir = tempfile::tempdir().unwrap();
+        let keys = keys("11");
+        let plaintext = contents(3 * CHUNK_LEN as usize);
+        let path = encrypted_file(dir.path(), &keys, &plaintext).await;
+
+        let mut stored = std::fs::read(&path).unwrap();
+        let (first, rest) = stored[HEADER_LEN..].split_at_mut(SEALED_CHUNK_LEN);
+        first.swap_with_slice(&mut rest[..SEALED_CHUNK_LEN]);
+        std::fs::write(&path, &stored).unwrap();
+
+        let error = read_all(Some(&keys), &path, Some(&keys.current_id()))
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    async fn chunks_moved_between_files_fail_to_decrypt() {
+        let dir = tempfile::tempdir().unwrap();
+        let keys = keys("11");
+        let plaintext = contents(CHUNK_LEN as usize);
+        let path = encrypted_file(dir.path(), &keys, &plaintext).await;
+        let other = std::fs::read(&path).unwrap();
+        encrypt_file(&keys, &path, Some(&keys.current_id()))
+            .await
+            .unwrap();
+
+        // Same key and length, but another nonce prefix in the header
+        let mut stored = std::fs::read(&path).unwrap();
+        stored[HEADER_LEN..].copy_from_slice(&other[HEADER_LEN..]);
+        std::fs::write(&path, &stored).unwrap();
+
+        let error = read_all(Some(&keys), &path, Some(&keys.current_id()))
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    async fn wrong_key_id_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let plaintext = contents(1000);
+        let path = encrypted_file(dir.path(), &keys("11"), &plaintext).await;
+
+        // The file's key isn't configured at all
+        let other = keys("22");
+        let key_id = Some(keys("11").current_id());
+        assert!(open(Some(&other), &path, key_id.as_deref()).await.is_err());
+        assert!(open(None, &path, key_id.as_deref()).await.is_err());
+
+        // Relabelled for a configured key, the header no longer authenticates
+        let mut stored = std::fs::read(&path).unwrap();
+        stored[8..16].copy_from_slice(&other.current.id);
+        std::fs::write(&path, &stored).unwrap();
+        let error = read_all(Some(&other), &path, Some(&other.current_id()))
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,215 @@
+//! # Error Pages
+//!
+//! Handlers answer failures with bare `(StatusCode, &str)` responses. The
+//! [`error_pages`] middleware turns those, and axum's empty 404/405
+//! responses, into a themed page in the visitor's language, or into a JSON
+//! object when the client asks for `application/json`.
+//!
+//! Responses that already carry a page or a protocol-specific body (upload
+//! form errors, S3 XML errors, JSON APIs) pass through untouched.
+//!
+//! ## Handler Errors
+//! Handlers that fail on a lookup or a file can return [`AppError`] and use
+//! `?`: database, I/O and template errors are logged and answered with `500`
+//! (or `404` for a missing file) and missing records with `404`. The
+//! response is a bare message, so it gets the same error page as every
+//! other failure.
+//!
+//! ## Request IDs
+//! Every error page shows the request's ID (see [`crate::request_id`]) so
+//! visitors can quote it when asking for help; it is logged with the error.
+
+use axum::{
+    body::to_bytes,
+    extract::Request,
+    http::{
+        header::{ACCEPT, ACCEPT_LANGUAGE, CONTENT_LENGTH, CONTENT_TYPE},
+        Method, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use serde_json::json;
+use std::{fmt, io};
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::{i18n::Lang, request_id::RequestId, templates::ErrorTemplate, theme::Theme};
+
+/// Bare error bodies are short messages; anything larger is left alone
+const MAX_MESSAGE_BYTES: usize = 4 * 1024;
+
+/// Render bare error responses as error pages (or JSON when negotiated)
+pub async fn error_pages(request: Request, next: Next) -> Response {
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .map(|RequestId(id)| id.clone())
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let lang = request
+        .headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(Lang::negotiate)
+        .unwrap_or_default();
+    let theme = Theme::from_headers(request.headers());
+    let wants_json = request
+        .headers()
+        .get(ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(prefers_json);
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+
+    let response = next.run(request).await;
+    let status = response.status();
+
+    if !(status.is_client_error() || status.is_server_error())
+        || method == Method::HEAD
+        || !is_bare(&response)
+    {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let message = match to_bytes(body, MAX_MESSAGE_BYTES).await {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).trim().to_string(),
+        Err(_) => String::new(),
+    };
+
+    if status.is_server_error() {
+        error!(request_id = %request_id, status = status.as_u16(), path = %path, message = %message, "Request failed");
+    } else {
+        warn!(request_id = %request_id, status = status.as_u16(), path = %path, message = %message, "Request rejected");
+    }
+
+    // Internal error messages are for the log, not for visitors
+    let detail =
+        (status != StatusCode::INTERNAL_SERVER_ERROR && !message.is_empty()).then_some(message);
+
+    let page = if wants_json {
+        Json(json!({
+            "status": status.as_u16(),
+            "error": detail.unwrap_or_else(|| ErrorTemplate::heading_for(lang, status)),
+            "request_id": request_id,
+        }))
+        .into_response()
+    } else {
+        ErrorTemplate {
+            lang,
+            theme,
+            status,
+            detail,
+            request_id,
+        }
+        .into_response()
+    };
+
+    // Keep headers such as Retry-After or WWW-Authenticate, but describe the new body
+    let (page_parts, page_body) = page.into_parts();
+    parts.headers.remove(CONTENT_LENGTH);
+    if let Some(content_type) = page_parts.headers.get(CONTENT_TYPE) {
+        parts.headers.insert(CONTENT_TYPE, content_type.clone());
+    }
+
+    Response::from_parts(parts, page_body)
+}
+
+/// Plain-text or empty bodies, as produced by `(StatusCode, &str)` and axum's fallbacks
+fn is_bare(response: &Response) -> bool {
+    match response.headers().get(CONTENT_TYPE) {
+        None => true,
+        Some(content_type) => content_type
+            .to_str()
+            .is_ok_and(|value| value.starts_with("text/plain")),
+    }
+}
+
+/// Whether an `Accept` header asks for JSON rather than a page
+fn prefers_json(accept: &str) -> bool {
+    accept.contains("application/json") && !accept.contains("text/html")
+}
+
+/// Why a handler failed, turned into the matching status and error page
+#[derive(Debug)]
+pub enum AppError {
+    /// A query failed or no database connection was available
+    Database(String),
+
+    /// A stored file couldn't be read or written
+    Io(io::Error),
+
+    /// A page failed to render
+    Template(askama::Error),
+
+    /// The requested record doesn't exist; the message says which
+    NotFound(&'static str),
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Database(e) => write!(f, "database error: {}", e),
+            AppError::Io(e) => write!(f, "file error: {}", e),
+            AppError::Template(e) => write!(f, "template error: {}", e),
+            AppError::NotFound(what) => f.write_str(what),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<Box<dyn std::error::Error>> for AppError {
+    fn from(e: Box<dyn std::error::Error>) -> Self {
+        AppError::Database(e.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        AppError::Database(e.to_string())
+    }
+}
+
+impl From<r2d2::Error> for AppError {
+    fn from(e: r2d2::Error) -> Self {
+        AppError::Database(e.to_string())
+    }
+}
+
+impl From<io::Error> for AppError {
+    fn from(e: io::Error) -> Self {
+        AppError::Io(e)
+    }
+}
+
+impl From<askama::Error> for AppError {
+    fn from(e: askama::Error) -> Self {
+        AppError::Template(e)
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        // The request span adds the request ID and path to these log lines
+        match self {
+            AppError::Database(e) => {
+                error!(error = %e, "Database error");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+            }
+            AppError::Io(e) if e.kind() == io::ErrorKind::NotFound => {
+                warn!(error = %e, "File not found on disk");
+                (StatusCode::NOT_FOUND, "File not found on disk").into_response()
+            }
+            AppError::Io(e) => {
+                error!(error = %e, "File error");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Failed to read file").into_response()
+            }
+            AppError::Template(e) => {
+                error!(error = %e, "Failed to render page");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Template error").into_response()
+            }
+            AppError::NotFound(what) => (StatusCode::NOT_FOUND, what).into_response(),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! # Event Publishing
+//!
+//! Announces things happening on the server (files received, links created
+//! or deleted) to external automation. Events are described by the flat
+//! payloads in [`crate::models`] and handed to every configured sink: the
+//! MQTT broker (see [`crate::mqtt`]) and the webhook endpoints admins
+//! registered (see [`crate::webhooks`]).
+//!
+//! Publishing never blocks or fails the request that triggered the event:
+//! MQTT problems are logged and the event is dropped; webhook deliveries are
+//! queued and retried in the background.
+//!
+//! ## Events
+//! - `upload.created` - A file was received ([`UploadEventPayload`])
+//! - `link.created` / `link.deleted` - An upload link was created or deleted ([`LinkEventPayload`])
+//! - `link.expired` - A link passed its expiry time (sent by [`crate::scheduler`])
+//! - `link.quota_low` - An upload took a link below `QUOTA_WARNING_PERCENT` of its quota
+//! - `link.quota_exhausted` - An upload used up the rest of a link's quota
+
+use serde::Serialize;
+use tracing::{debug, warn};
+
+use crate::{
+    database::{get_file_upload_by_id, get_upload_link_by_id},
+    models::{LinkEventPayload, UploadEventPayload, UploadLink},
+    mqtt, webhooks, AppState,
+};
+
+/// Announce a newly received file
+pub fn upload_created(state: &AppState, link: &UploadLink, upload_id: &str) {
+    match get_file_upload_by_id(&state.db, upload_id) {
+        Ok(Some(upload)) => emit(
+            state,
+            "upload.created",
+            &UploadEventPayload::from_upload(&upload, Some(link)),
+        ),
+        Ok(None) => debug!(upload_id = %upload_id, "Upload vanished before its event was sent"),
+        Err(e) => warn!(upload_id = %upload_id, error = %e, "Failed to load upload for event"),
+    }
+
+    // `link` is the state before the upload; compare with the current quota
+    let Ok(Some(current)) = get_upload_link_by_id(&state.db, &link.id) else {
+        return;
+    };
+
+    let warning_percent = state.config.quota_warning_percent;
+    if !link.is_quota_low(warning_percent) && current.is_quota_low(warning_percent) {
+        emit(
+            state,
+            "link.quota_low",
+            &LinkEventPayload::from_link("link.quota_low", &current),
+        );
+    }
+
+    if link.remaining_quota > 0 && current.remaining_quota <= 0 {
+        emit(
+            state,
+            "link.quota_exhausted",
+            &LinkEventPayload::from_link("link.quota_exhausted", &current),
+        );
+    }
+}
+
+/// Announce a newly created upload link
+pub fn link_created(state: &AppState, link: &UploadLink) {
+    emit(
+        state,
+        "link.created",
+        &LinkEventPayload::from_link("link.created", link),
+    );
+}
+
+/// Announce a deleted upload link
+pub fn link_deleted(state: &AppState, link: &UploadLink) {
+    emit(
+        state,
+        "link.deleted",
+        &LinkEventPayload::from_link("link.deleted", link),
+    );
+}
+
+/// Announce a link that reached its expiry time
+pub fn link_expired(state: &AppState, link: &UploadLink) {
+    emit(
+        state,
+        "link.expired",
+        &LinkEventPayload::from_link("link.expired", link),
+    );
+}
+
+/// Serialize an event and hand it to every sink
+fn emit<P: Serialize>(state: &AppState, event: &str, payload: &P) {
+    let body = match serde_json::to_vec(payload) {
+        Ok(body) => body,
+        Err(e) => {
+            warn!(event = %event, error = %e, "Failed to serialize event");
+            return;
+        }
+    };
+
+    webhooks::publish(state, event, &body);
+    mqtt::publish(event, body);
+}
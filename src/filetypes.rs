@@ -0,0 +1,413 @@
+//! # Allowed File Types
+//!
+//! A link can be limited to certain kinds of files, e.g. images or PDFs
+//! only. The admin lists file extensions (`.pdf`) and MIME types, with `*`
+//! standing for a whole family (`image/*`). A file is accepted when:
+//!
+//! 1. its extension is listed, or belongs to a format whose MIME type is
+//!    listed, and
+//! 2. its contents look like what the extension promises: formats with a
+//!    known signature (magic bytes) must start with it, and files of
+//!    formats without one, such as text, must not carry another format's
+//!    signature (an executable renamed to `.txt`).
+//!
+//! The name is checked before any data is received; the contents once the
+//! file is written. Extensions not in the format table are accepted as
+//! listed, without a content check.
+//!
+//! ## Detected Types
+//! Every stored file is sniffed the same way, on any link, and the MIME type
+//! its contents show is recorded next to the one the client declared. With
+//! `REJECT_TYPE_MISMATCH` on, a file is refused when the two disagree, e.g.
+//! an executable sent as `text/plain`. Declared types not in the format
+//! table, such as `application/octet-stream`, always pass.
+
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+use crate::models::UploadLink;
+
+/// Bytes read from the start of a file to recognize its format
+const SNIFF_BYTES: usize = 1024;
+
+/// Format families recognizable by the first bytes of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Signature {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+    Bmp,
+    Tiff,
+    /// ISO base media (`ftyp` box): MP4, QuickTime, HEIC, AVIF
+    IsoMedia,
+    Pdf,
+    /// ZIP containers: archives, Office Open XML, OpenDocument, EPUB
+    Zip,
+    /// OLE compound files: legacy Office documents
+    Ole,
+    Gzip,
+    SevenZip,
+    Rar,
+    Mp3,
+    Wav,
+    Ogg,
+    Flac,
+    /// Windows executable (`MZ`)
+    Executable,
+    /// Linux executable
+    Elf,
+}
+
+/// A file format known by its extensions, MIME type and signature
+struct Format {
+    extensions: &'static [&'static str],
+    mime: &'static str,
+    /// None for formats without magic bytes, such as plain text
+    signature: Option<Signature>,
+}
+
+impl Signature {
+    /// MIME type of the format family, for files whose name doesn't pick one
+    fn mime(self) -> &'static str {
+        match self {
+            Signature::Png => "image/png",
+            Signature::Jpeg => "image/jpeg",
+            Signature::Gif => "image/gif",
+            Signature::Webp => "image/webp",
+            Signature::Bmp => "image/bmp",
+            Signature::Tiff => "image/tiff",
+            Signature::IsoMedia => "video/mp4",
+            Signature::Pdf => "application/pdf",
+            Signature::Zip => "application/zip",
+            Signature::Ole => "application/x-ole-storage",
+            Signature::Gzip => "application/gzip",
+            Signature::SevenZip => "application/x-7z-compressed",
+            Signature::Rar => "application/vnd.rar",
+            Signature::Mp3 => "audio/mpeg",
+            Signature::Wav => "audio/wav",
+            Signature::Ogg => "audio/ogg",
+            Signature::Flac => "audio/flac",
+            Signature::Executable => "application/vnd.microsoft.portable-executable",
+            Signature::Elf => "application/x-elf",
+        }
+    }
+}
+
+impl Format {
+    const fn new(
+        extensions: &'static [&'static str],
+        mime: &'static str,
+        signature: Option<Signature>,
+    ) -> Format {
+        Format {
+            extensions,
+            mime,
+            signature,
+        }
+    }
+}
+
+/// Formats recognized by extension
+const FORMATS: &[Format] = &[
+    Format::new(&["png"], "image/png", Some(Signature::Png)),
+    Format::new(&["jpg", "jpeg"], "image/jpeg", Some(Signature::Jpeg)),
+    Format::new(&["gif"], "image/gif", Some(Signature::Gif)),
+    Format::new(&["webp"], "image/webp", Some(Signature::Webp)),
+    Format::new(&["bmp"], "image/bmp", Some(Signature::Bmp)),
+    Format::new(&["tif", "tiff"], "image/tiff", Some(Signature::Tiff)),
+    Format::new(&["heic", "heif"], "image/heic", Some(Signature::IsoMedia)),
+    Format::new(&["avif"], "image/avif", Some(Signature::IsoMedia)),
+    Format::new(&["svg"], "image/svg+xml", None),
+    Format::new(&["pdf"], "application/pdf", Some(Signature::Pdf)),
+    Format::new(&["zip"], "application/zip", Some(Signature::Zip)),
+    Format::new(
+        &["docx"],
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        Some(Signature::Zip),
+    ),
+    Format::new(
+        &["xlsx"],
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        Some(Signature::Zip),
+    ),
+    Format::new(
+        &["pptx"],
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        Some(Signature::Zip),
+    ),
+    Format::new(
+        &["odt"],
+        "application/vnd.oasis.opendocument.text",
+        Some(Signature::Zip),
+    ),
+    Format::new(
+        &["ods"],
+        "application/vnd.oasis.opendocument.spreadsheet",
+        Some(Signature::Zip),
+    ),
+    Format::new(
+        &["odp"],
+        "application/vnd.oasis.opendocument.presentation",
+        Some(Signature::Zip),
+    ),
+    Format::new(&["epub"], "application/epub+zip", Some(Signature::Zip)),
+    Format::new(&["doc"], "application/msword", Some(Signature::Ole)),
+    Format::new(&["xls"], "application/vnd.ms-excel", Some(Signature::Ole)),
+    Format::new(
+        &["ppt"],
+        "application/vnd.ms-powerpoint",
+        Some(Signature::Ole),
+    ),
+    Format::new(&["gz", "tgz"], "application/gzip", Some(Signature::Gzip)),
+    Format::new(
+        &["7z"],
+        "application/x-7z-compressed",
+        Some(Signature::SevenZip),
+    ),
+    Format::new(&["rar"], "application/vnd.rar", Some(Signature::Rar)),
+    Format::new(&["mp4", "m4v"], "video/mp4", Some(Signature::IsoMedia)),
+    Format::new(&["mov"], "video/quicktime", Some(Signature::IsoMedia)),
+    Format::new(&["m4a"], "audio/mp4", Some(Signature::IsoMedia)),
+    Format::new(&["mp3"], "audio/mpeg", Some(Signature::Mp3)),
+    Format::new(&["wav"], "audio/wav", Some(Signature::Wav)),
+    Format::new(&["ogg", "oga"], "audio/ogg", Some(Signature::Ogg)),
+    Format::new(&["flac"], "audio/flac", Some(Signature::Flac)),
+    Format::new(&["txt", "log"], "text/plain", None),
+    Format::new(&["csv"], "text/csv", None),
+    Format::new(&["md"], "text/markdown", None),
+    Format::new(&["html", "htm"], "text/html", None),
+    Format::new(&["json"], "application/json", None),
+    Format::new(&["xml"], "application/xml", None),
+    Format::new(
+        &["exe", "dll"],
+        "application/vnd.microsoft.portable-executable",
+        Some(Signature::Executable),
+    ),
+];
+
+/// One entry of a link's list of accepted types
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Entry {
+    /// Lowercase extension without the dot
+    Extension(String),
+    /// Lowercase MIME type; `image/*` matches every image type
+    Mime(String),
+}
+
+/// The file types a link accepts
+#[derive(Debug, Clone)]
+pub struct AllowedTypes {
+    entries: Vec<Entry>,
+}
+
+impl AllowedTypes {
+    /// Parse a comma-separated list such as `image/*, .pdf, docx`
+    ///
+    /// Returns None for an empty list; fails on an entry that is neither an
+    /// extension nor a MIME type.
+    pub fn parse(list: &str) -> Result<Option<AllowedTypes>, String> {
+        let mut entries = Vec::new();
+        for item in list
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            let item = item.to_ascii_lowercase();
+            let entry = if item.contains('/') {
+                let (family, subtype) = item.split_once('/').unwrap_or_default();
+                if !is_token(family) || !(subtype == "*" || is_token(subtype)) {
+                    return Err(item);
+                }
+                Entry::Mime(item)
+            } else {
+                let extension = item.trim_start_matches('.');
+                if !is_token(extension) {
+                    return Err(item);
+                }
+                Entry::Extension(extension.to_string())
+            };
+            if !entries.contains(&entry) {
+                entries.push(entry);
+            }
+        }
+
+        Ok((!entries.is_empty()).then_some(AllowedTypes { entries }))
+    }
+
+    /// The list as stored on the link and shown to guests, e.g. `image/*, .pdf`
+    pub fn to_list(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| match entry {
+                Entry::Extension(extension) => format!(".{}", extension),
+                Entry::Mime(mime) => mime.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Whether a file of this name may be uploaded
+    pub fn allows_name(&self, filename: &str) -> bool {
+        let Some(extension) = extension_of(filename) else {
+            return false;
+        };
+        if self.entries.contains(&Entry::Extension(extension.clone())) {
+            return true;
+        }
+        format_for(&extension).is_some_and(|format| self.allows_mime(format.mime))
+    }
+
+    fn allows_mime(&self, mime: &str) -> bool {
+        self.entries.iter().any(|entry| match entry {
+            Entry::Mime(allowed) => match allowed.strip_suffix("/*") {
+                Some(family) => mime
+                    .split_once('/')
+                    .is_some_and(|(mime_family, _)| mime_family == family),
+                None => allowed == mime,
+            },
+            Entry::Extension(_) => false,
+        })
+    }
+}
+
+/// The types a link accepts (None = any type)
+pub fn for_link(link: &UploadLink) -> Option<AllowedTypes> {
+    link.allowed_file_types
+        .as_deref()
+        .and_then(|list| AllowedTypes::parse(list).ok().flatten())
+}
+
+/// Whether a link accepts a file of this name
+pub fn name_allowed(link: &UploadLink, filename: &str) -> bool {
+    for_link(link).is_none_or(|allowed| allowed.allows_name(filename))
+}
+
+/// What the first bytes of a written file show it to be
+#[derive(Debug, Clone, Copy)]
+pub struct Sniffed(Option<Signature>);
+
+impl Sniffed {
+    /// MIME type of the detected format (None = no known signature, e.g. text)
+    ///
+    /// The name picks among formats sharing a signature, so a `.docx` is
+    /// reported as a Word document rather than a ZIP archive.
+    pub fn mime(&self, filename: &str) -> Option<&'static str> {
+        let signature = self.0?;
+        let named = extension_of(filename)
+            .and_then(|extension| format_for(&extension))
+            .filter(|format| format.signature == Some(signature));
+        Some(named.map_or(signature.mime(), |format| format.mime))
+    }
+
+    /// Whether the contents match the format the name promises
+    ///
+    /// Only checked on links that limit file types.
+    pub fn allowed_on(&self, link: &UploadLink, filename: &str) -> bool {
+        if link.allowed_file_types.is_none() {
+            return true;
+        }
+        extension_of(filename)
+            .and_then(|extension| format_for(&extension))
+            .is_none_or(|format| format.signature == self.0)
+    }
+
+    /// Whether the contents match the MIME type the client declared
+    ///
+    /// Types not in the format table match anything.
+    pub fn matches_declared(&self, declared: &str) -> bool {
+        let declared = declared
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        let mut formats = FORMATS
+            .iter()
+            .filter(|format| format.mime == declared)
+            .peekable();
+        formats.peek().is_none() || formats.any(|format| format.signature == self.0)
+    }
+}
+
+/// Read the first bytes of a written file to recognize its format
+pub async fn sniff_file(path: &Path) -> std::io::Result<Sniffed> {
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    tokio::fs::File::open(path)
+        .await?
+        .take(SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .await?;
+
+    Ok(Sniffed(sniff(&head)))
+}
+
+/// Recognize a format by the first bytes of a file
+fn sniff(head: &[u8]) -> Option<Signature> {
+    let starts = |magic: &[u8]| head.starts_with(magic);
+    let riff = |kind: &[u8]| starts(b"RIFF") && head.get(8..12) == Some(kind);
+
+    if starts(b"\x89PNG\r\n\x1a\n") {
+        Some(Signature::Png)
+    } else if starts(b"\xff\xd8\xff") {
+        Some(Signature::Jpeg)
+    } else if starts(b"GIF87a") || starts(b"GIF89a") {
+        Some(Signature::Gif)
+    } else if riff(b"WEBP") {
+        Some(Signature::Webp)
+    } else if riff(b"WAVE") {
+        Some(Signature::Wav)
+    } else if starts(b"BM") && head.get(6..10) == Some(&[0u8; 4][..]) {
+        Some(Signature::Bmp)
+    } else if starts(b"II*\0") || starts(b"MM\0*") {
+        Some(Signature::Tiff)
+    } else if head.get(4..8) == Some(&b"ftyp"[..]) {
+        Some(Signature::IsoMedia)
+    } else if head.windows(5).any(|window| window == b"%PDF-") {
+        // Some writers put a few bytes of junk before the header
+        Some(Signature::Pdf)
+    } else if starts(b"PK\x03\x04") || starts(b"PK\x05\x06") {
+        Some(Signature::Zip)
+    } else if starts(b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1") {
+        Some(Signature::Ole)
+    } else if starts(b"\x1f\x8b") {
+        Some(Signature::Gzip)
+    } else if starts(b"7z\xbc\xaf\x27\x1c") {
+        Some(Signature::SevenZip)
+    } else if starts(b"Rar!\x1a\x07") {
+        Some(Signature::Rar)
+    } else if starts(b"ID3") || (head.len() > 1 && head[0] == 0xff && head[1] & 0xe0 == 0xe0) {
+        Some(Signature::Mp3)
+    } else if starts(b"OggS") {
+        Some(Signature::Ogg)
+    } else if starts(b"fLaC") {
+        Some(Signature::Flac)
+    } else if starts(b"MZ") {
+        Some(Signature::Executable)
+    } else if starts(b"\x7fELF") {
+        Some(Signature::Elf)
+    } else {
+        None
+    }
+}
+
+fn format_for(extension: &str) -> Option<&'static Format> {
+    FORMATS
+        .iter()
+        .find(|format| format.extensions.contains(&extension))
+}
+
+fn extension_of(filename: &str) -> Option<String> {
+    Path::new(filename)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase)
+}
+
+/// Letters, digits and the punctuation found in extensions and MIME types
+fn is_token(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-' | '_'))
+}
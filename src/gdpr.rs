@@ -0,0 +1,330 @@
+//! # Data Subject Requests
+//!
+//! Finds everything stored about one person so it can be handed over
+//! (export) or deleted (erasure) on request. A person is identified by the
+//! email address they uploaded with or their link was created for, the name
+//! they gave when uploading, the client address they uploaded from, or their
+//! guest folder (see [`crate::ingest`]). Their data is the matching uploads
+//! with their files and the messages sent with them, the guest email of
+//! their links, the audit log entries about their uploads and the refused
+//! uploads made from their client address.
+//!
+//! Both operations produce a JSON report listing what was found or removed.
+//! The report is signed with HMAC-SHA256 over its compact JSON encoding, so
+//! a stored copy can later be shown to be unaltered.
+//! Files under legal hold are never erased; they are listed as retained.
+//!
+//! ## Configuration
+//! - `GDPR_REPORT_KEY`: Secret the reports are signed with (unset = reports are unsigned)
+
+use chrono::{DateTime, Utc};
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    database::*,
+    models::{AuditEvent, FileUpload, UploadAttempt, UploadMessage},
+    tasks::{enqueue, remove_upload_file, Task},
+    AppState,
+};
+
+/// Name of the signature algorithm recorded in signed reports
+const ALGORITHM: &str = "HMAC-SHA256";
+
+/// What a report was produced for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportKind {
+    Export,
+    Erasure,
+}
+
+/// Everything found about, or removed for, one person
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub id: String,
+    pub kind: ReportKind,
+
+    /// Email address, name, client address or guest folder that was searched for
+    pub subject: String,
+
+    pub generated_at: DateTime<Utc>,
+
+    /// Username of the admin who ran the request
+    pub generated_by: String,
+
+    /// Uploads that were found (export) or erased (erasure)
+    pub uploads: Vec<FileUpload>,
+
+    /// Uploads kept because they are under legal hold
+    pub retained: Vec<FileUpload>,
+
+    /// Messages sent with the subject's uploads
+    pub messages: Vec<UploadMessage>,
+
+    /// Links created for the subject's email address
+    pub links: Vec<ReportLink>,
+
+    /// Audit log entries about the subject's uploads
+    pub audit_events: Vec<AuditEvent>,
+
+    /// Refused uploads made from the subject's client address
+    pub upload_attempts: Vec<UploadAttempt>,
+}
+
+/// A link created for the subject, as listed in a report
+#[derive(Debug, Serialize)]
+pub struct ReportLink {
+    pub id: String,
+    pub name: String,
+    pub guest_email: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A report together with its signature, as handed out
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignedReport {
+    pub report: serde_json::Value,
+
+    /// Signature algorithm (None = unsigned)
+    pub algorithm: Option<String>,
+
+    /// Hex-encoded signature of the report (None = unsigned)
+    pub signature: Option<String>,
+}
+
+/// Outcome of checking a report's signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verification {
+    Valid,
+    Invalid,
+    Unsigned,
+    /// No `GDPR_REPORT_KEY` is configured to check against
+    NoKey,
+}
+
+/// Collect everything stored about a person without changing anything
+pub fn export(state: &AppState, subject: &str, admin: &str) -> Result<Report, String> {
+    let report = find(state, subject, admin, ReportKind::Export)?;
+
+    audit(state, admin, "gdpr.exported", &report);
+    info!(report_id = %report.id, username = %admin, uploads = report.uploads.len(), "Exported personal data");
+
+    Ok(report)
+}
+
+/// Delete everything stored about a person, except files under legal hold
+pub async fn erase(state: &AppState, subject: &str, admin: &str) -> Result<Report, String> {
+    let mut report = find(state, subject, admin, ReportKind::Erasure)?;
+
+    // Held files, and their audit entries, stay until the hold is released
+    let (retained, erasable): (Vec<FileUpload>, Vec<FileUpload>) = report
+        .uploads
+        .drain(..)
+        .partition(|upload| is_upload_held(&state.db, &upload.id).unwrap_or(true));
+    report.retained = retained;
+    report.audit_events.retain(|event| {
+        report
+            .retained
+            .iter()
+            .all(|upload| upload.id != event.target_id)
+    });
+
+    for upload in &erasable {
+        let delete_result = delete_file_upload(&state.db, &upload.id).map_err(|e| e.to_string());
+        if let Err(error_msg) = delete_result {
+            error!(upload_id = %upload.id, error = %error_msg, "Failed to erase upload");
+            report.retained.push(upload.clone());
+            continue;
+        }
+
+        let task = Task::RemoveUploadFile {
+            storage_dir: upload.storage_dir.clone(),
+            stored_filename: upload.stored_filename.clone(),
+            volume: upload.volume.clone(),
+        };
+        let enqueue_result = enqueue(state, &task).map_err(|e| e.to_string());
+        if let Err(error_msg) = enqueue_result {
+            error!(upload_id = %upload.id, error = %error_msg, "Failed to enqueue file removal, removing directly");
+            let _ = remove_upload_file(
+                state,
+                &upload.storage_dir,
+                &upload.stored_filename,
+                upload.volume.clone(),
+            )
+            .await;
+        }
+        report.uploads.push(upload.clone());
+    }
+
+    let event_ids: Vec<&str> = report
+        .audit_events
+        .iter()
+        .map(|event| event.id.as_str())
+        .collect();
+    if let Err(e) = delete_audit_events(&state.db, &event_ids) {
+        return Err(format!("failed to erase audit log entries: {}", e));
+    }
+
+    let attempt_ids: Vec<&str> = report
+        .upload_attempts
+        .iter()
+        .map(|attempt| attempt.id.as_str())
+        .collect();
+    if let Err(e) = delete_upload_attempts(&state.db, &attempt_ids) {
+        return Err(format!("failed to erase refused uploads: {}", e));
+    }
+
+    // Messages go with the last of their files; held files keep theirs
+    if let Err(e) = purge_orphaned_upload_messages(&state.db) {
+        return Err(format!("failed to erase messages: {}", e));
+    }
+    report.messages.retain(|message| {
+        report
+            .retained
+            .iter()
+            .all(|upload| upload.message_id.as_deref() != Some(message.id.as_str()))
+    });
+
+    for link in &report.links {
+        if let Err(e) = clear_link_guest_email(&state.db, &link.id) {
+            return Err(format!(
+                "failed to erase guest email of link {}: {}",
+                link.id, e
+            ));
+        }
+    }
+
+    audit(state, admin, "gdpr.erased", &report);
+    info!(
+        report_id = %report.id,
+        username = %admin,
+        erased = report.uploads.len(),
+        retained = report.retained.len(),
+        "Erased personal data"
+    );
+
+    Ok(report)
+}
+
+/// Sign a report with the configured key
+pub fn sign(state: &AppState, report: &Report) -> SignedReport {
+    let report = serde_json::to_value(report).unwrap_or_default();
+
+    match &state.config.gdpr_report_key {
+        Some(key) => SignedReport {
+            signature: Some(signature(key, &report)),
+            algorithm: Some(ALGORITHM.to_string()),
+            report,
+        },
+        None => SignedReport {
+            report,
+            algorithm: None,
+            signature: None,
+        },
+    }
+}
+
+/// Check the signature of a report handed out earlier
+pub fn verify(state: &AppState, signed: &SignedReport) -> Verification {
+    let Some(key) = &state.config.gdpr_report_key else {
+        return Verification::NoKey;
+    };
+    let Some(claimed) = &signed.signature else {
+        return Verification::Unsigned;
+    };
+
+    let expected = hex::decode(claimed);
+    let message = serde_json::to_string(&signed.report).unwrap_or_default();
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key.as_bytes());
+    match expected {
+        Some(tag) if hmac::verify(&key, message.as_bytes(), &tag).is_ok() => Verification::Valid,
+        _ => Verification::Invalid,
+    }
+}
+
+/// Gather the subject's uploads, links and audit entries
+fn find(state: &AppState, subject: &str, admin: &str, kind: ReportKind) -> Result<Report, String> {
+    let uploads = get_file_uploads_by_subject(&state.db, subject).map_err(|e| e.to_string())?;
+    let links = get_upload_links_by_guest_email(&state.db, subject)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|link| ReportLink {
+            id: link.id,
+            name: link.name,
+            guest_email: link.guest_email,
+            created_at: link.created_at,
+        })
+        .collect();
+    let upload_refs: Vec<&FileUpload> = uploads.iter().collect();
+    let messages = get_upload_messages(&state.db, &upload_refs).map_err(|e| e.to_string())?;
+    let upload_ids: Vec<String> = uploads.iter().map(|upload| upload.id.clone()).collect();
+    let audit_events =
+        get_audit_events_for_targets(&state.db, &upload_ids).map_err(|e| e.to_string())?;
+    let upload_attempts =
+        get_upload_attempts_by_ip(&state.db, subject).map_err(|e| e.to_string())?;
+
+    Ok(Report {
+        id: Uuid::new_v4().to_string(),
+        kind,
+        subject: subject.to_string(),
+        generated_at: Utc::now(),
+        generated_by: admin.to_string(),
+        uploads,
+        retained: Vec::new(),
+        messages,
+        links,
+        audit_events,
+        upload_attempts,
+    })
+}
+
+/// Record a data subject request in the audit log
+///
+/// The entry names the report rather than the person, so it survives the
+/// erasure without keeping their data.
+fn audit(state: &AppState, admin: &str, action: &str, report: &Report) {
+    let detail = format!(
+        "{} uploads, {} retained, {} links",
+        report.uploads.len(),
+        report.retained.len(),
+        report.links.len()
+    );
+    if let Err(e) = record_audit_event(
+        &state.db,
+        admin,
+        action,
+        "report",
+        &report.id,
+        Some(&detail),
+    ) {
+        error!(report_id = %report.id, error = %e, "Failed to record data subject request in the audit log");
+    }
+}
+
+/// Hex-encoded HMAC of a report's compact JSON encoding
+fn signature(key: &str, report: &serde_json::Value) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key.as_bytes());
+    let message = serde_json::to_string(report).unwrap_or_default();
+    hex::encode(hmac::sign(&key, message.as_bytes()).as_ref())
+}
+
+/// Lowercase hex encoding of signatures
+mod hex {
+    pub fn encode(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    pub fn decode(text: &str) -> Option<Vec<u8>> {
+        let text = text.trim();
+        if !text.len().is_multiple_of(2) || !text.is_ascii() {
+            return None;
+        }
+        (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok())
+            .collect()
+    }
+}
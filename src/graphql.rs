@@ -0,0 +1,321 @@
+//! # GraphQL API
+//!
+//! Optional read-only GraphQL endpoint at `/api/graphql` for dashboard
+//! builders. Only compiled with the `graphql` cargo feature:
+//!
+//! ```text
+//! cargo build --release --features graphql
+//! ```
+//!
+//! Requests must carry a valid admin session cookie, or a bearer token
+//! accepted by the [`crate::jwt`] settings. `GET /api/graphql` serves a
+//! GraphiQL explorer for trying out queries.
+//!
+//! ## Example Query
+//! ```graphql
+//! {
+//!   stats { activeLinks totalUploads totalUploadSize }
+//!   links(activeOnly: true) {
+//!     name remainingQuota
+//!     uploads(limit: 5) { originalFilename fileSize uploadedAt }
+//!   }
+//! }
+//! ```
+
+use async_graphql::{
+    http::GraphiQLSource, Context, EmptyMutation, EmptySubscription, Object, Result, Schema,
+};
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Json},
+};
+use chrono::{DateTime, Utc};
+
+use crate::{auth::*, database::*, models::*, AppState};
+
+/// Upper bound for list sizes a single query may request
+const MAX_PAGE_SIZE: i32 = 500;
+
+pub type AppSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+// The schema holds no data; the application state is attached to each request
+lazy_static::lazy_static! {
+    static ref SCHEMA: AppSchema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(8)
+        .finish();
+}
+
+/// Execute a GraphQL request for an authenticated admin
+pub async fn graphql_handler(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(request): Json<async_graphql::Request>,
+) -> impl IntoResponse {
+    let session = headers
+        .get(header::COOKIE)
+        .and_then(|header| header.to_str().ok())
+        .and_then(extract_session_id_from_cookies);
+
+    let authenticated = match session {
+        Some(session_id) => get_session(session_id).await.is_some(),
+        None => false,
+    } || crate::jwt::authenticate(&state, &headers).is_some();
+
+    if !authenticated {
+        return (StatusCode::UNAUTHORIZED, "Authentication required").into_response();
+    }
+
+    Json(SCHEMA.execute(request.data(state)).await).into_response()
+}
+
+/// Serve the GraphiQL explorer
+pub async fn graphiql() -> impl IntoResponse {
+    Html(GraphiQLSource::build().endpoint("/api/graphql").finish())
+}
+
+/// Clamp a client-supplied page size to a sane range
+fn page_size(limit: Option<i32>, default: i32) -> i64 {
+    limit.unwrap_or(default).clamp(1, MAX_PAGE_SIZE) as i64
+}
+
+/// Convert a database error into a GraphQL error
+fn db_error(e: Box<dyn std::error::Error>) -> async_graphql::Error {
+    async_graphql::Error::new(format!("Database error: {}", e))
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Upload links, newest first
+    async fn links(
+        &self,
+        ctx: &Context<'_>,
+        active_only: Option<bool>,
+        name_contains: Option<String>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> Result<Vec<Link>> {
+        let state = ctx.data::<AppState>()?;
+        let name_filter = name_contains.map(|name| name.to_lowercase());
+
+        let links = get_all_upload_links(&state.db)
+            .map_err(db_error)?
+            .into_iter()
+            .filter(|link| !active_only.unwrap_or(false) || link.is_valid())
+            .filter(|link| match &name_filter {
+                Some(name) => link.name.to_lowercase().contains(name),
+                None => true,
+            })
+            .skip(offset.unwrap_or(0).max(0) as usize)
+            .take(page_size(limit, 100) as usize)
+            .map(Link)
+            .collect();
+
+        Ok(links)
+    }
+
+    /// A single upload link by ID or token
+    async fn link(
+        &self,
+        ctx: &Context<'_>,
+        id: Option<String>,
+        token: Option<String>,
+    ) -> Result<Option<Link>> {
+        let state = ctx.data::<AppState>()?;
+
+        let link = match (id, token) {
+            (Some(id), _) => get_upload_link_by_id(&state.db, &id),
+            (None, Some(token)) => get_upload_link_by_token(&state.db, &token),
+            (None, None) => return Err("Either id or token is required".into()),
+        }
+        .map_err(db_error)?;
+
+        Ok(link.map(Link))
+    }
+
+    /// Uploaded files matching the given filters, newest first
+    #[allow(clippy::too_many_arguments)]
+    async fn uploads(
+        &self,
+        ctx: &Context<'_>,
+        link_id: Option<String>,
+        mime_type: Option<String>,
+        filename_contains: Option<String>,
+        min_size: Option<i64>,
+        max_size: Option<i64>,
+        uploaded_after: Option<DateTime<Utc>>,
+        uploaded_before: Option<DateTime<Utc>>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> Result<Vec<Upload>> {
+        let state = ctx.data::<AppState>()?;
+
+        let filter = UploadFilter {
+            link_id,
+            mime_type_prefix: mime_type,
+            filename_contains,
+            min_size,
+            max_size,
+            uploaded_after,
+            uploaded_before,
+            limit: page_size(limit, 100),
+            offset: offset.unwrap_or(0).max(0) as i64,
+        };
+
+        let uploads = search_file_uploads(&state.db, &filter).map_err(db_error)?;
+        Ok(uploads.into_iter().map(Upload).collect())
+    }
+
+    /// A single uploaded file by ID
+    async fn upload(&self, ctx: &Context<'_>, id: String) -> Result<Option<Upload>> {
+        let state = ctx.data::<AppState>()?;
+        let upload = get_file_upload_by_id(&state.db, &id).map_err(db_error)?;
+        Ok(upload.map(Upload))
+    }
+
+    /// Service-wide statistics
+    async fn stats(&self, ctx: &Context<'_>) -> Result<Stats> {
+        let state = ctx.data::<AppState>()?;
+        let stats = get_dashboard_stats(&state.db).map_err(db_error)?;
+        Ok(Stats(stats))
+    }
+}
+
+/// An upload link
+pub struct Link(UploadLink);
+
+#[Object]
+impl Link {
+    async fn id(&self) -> &str {
+        &self.0.id
+    }
+
+    async fn token(&self) -> &str {
+        &self.0.token
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    /// Address of the guest upload page (only the path without `PUBLIC_BASE_URL`)
+    async fn upload_url(&self, ctx: &Context<'_>) -> Result<String> {
+        let state = ctx.data::<AppState>()?;
+        Ok(self.0.upload_url(state.config.public_base_url.as_deref()))
+    }
+
+    /// Total upload quota in bytes
+    async fn total_quota(&self) -> i64 {
+        self.0.total_quota
+    }
+
+    /// Size limit for a single file in bytes, if any
+    async fn per_file_limit(&self) -> Option<i64> {
+        self.0.per_file_limit
+    }
+
+    /// Quota left in bytes
+    async fn remaining_quota(&self) -> i64 {
+        self.0.remaining_quota
+    }
+
+    async fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.0.expires_at
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    async fn is_active(&self) -> bool {
+        self.0.is_active
+    }
+
+    /// Active, not expired and with quota left
+    async fn is_valid(&self) -> bool {
+        self.0.is_valid()
+    }
+
+    async fn rate_limit_kbps(&self) -> Option<i64> {
+        self.0.rate_limit_kbps
+    }
+
+    /// Files uploaded through this link, newest first
+    async fn uploads(&self, ctx: &Context<'_>, limit: Option<i32>) -> Result<Vec<Upload>> {
+        let state = ctx.data::<AppState>()?;
+
+        let filter = UploadFilter {
+            link_id: Some(self.0.id.clone()),
+            limit: page_size(limit, 100),
+            ..Default::default()
+        };
+
+        let uploads = search_file_uploads(&state.db, &filter).map_err(db_error)?;
+        Ok(uploads.into_iter().map(Upload).collect())
+    }
+}
+
+/// An uploaded file
+pub struct Upload(FileUpload);
+
+#[Object]
+impl Upload {
+    async fn id(&self) -> &str {
+        &self.0.id
+    }
+
+    async fn link_id(&self) -> &str {
+        &self.0.link_id
+    }
+
+    async fn original_filename(&self) -> &str {
+        &self.0.original_filename
+    }
+
+    /// Size in bytes
+    async fn file_size(&self) -> i64 {
+        self.0.file_size
+    }
+
+    async fn mime_type(&self) -> &str {
+        &self.0.mime_type
+    }
+
+    async fn uploaded_at(&self) -> DateTime<Utc> {
+        self.0.uploaded_at
+    }
+
+    /// Admin download URL (requires an admin session)
+    async fn download_url(&self) -> String {
+        format!("/admin/uploads/{}/download", self.0.id)
+    }
+
+    /// The link the file was uploaded through (null if it has been deleted)
+    async fn link(&self, ctx: &Context<'_>) -> Result<Option<Link>> {
+        let state = ctx.data::<AppState>()?;
+        let link = get_upload_link_by_id(&state.db, &self.0.link_id).map_err(db_error)?;
+        Ok(link.map(Link))
+    }
+}
+
+/// Service-wide statistics
+pub struct Stats(DashboardStats);
+
+#[Object]
+impl Stats {
+    /// Links that are active, not expired and still have quota left
+    async fn active_links(&self) -> i64 {
+        self.0.active_links
+    }
+
+    async fn total_uploads(&self) -> i64 {
+        self.0.total_uploads
+    }
+
+    /// Combined size of all uploads in bytes
+    async fn total_upload_size(&self) -> i64 {
+        self.0.total_upload_size
+    }
+}
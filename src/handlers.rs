@@ -1,15 +1,47 @@
 use axum::{
     body::Body,
-    extract::{rejection::FormRejection, Form, Multipart, Path, State},
+    extract::{multipart::Field, rejection::FormRejection, Form, Multipart, Path, Query, State},
     http::{header, HeaderMap, StatusCode},
-    response::{IntoResponse, Redirect, Response},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json, Redirect, Response,
+    },
 };
 use chrono::{Duration, Utc};
-use tokio::fs;
+use futures::{Stream, StreamExt};
+use serde_json::json;
+use tokio::{fs, io::AsyncReadExt};
+use tokio_util::io::{ReaderStream, StreamReader};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-use crate::{auth::*, database::*, models::*, templates::*, AppState};
+use crate::{
+    api, archive,
+    auth::*,
+    commands,
+    compression::Uncompressed,
+    database::*,
+    datetime::DisplayTime,
+    email_templates::{self, TemplateKind},
+    encryption,
+    errors::AppError,
+    events,
+    filetypes::AllowedTypes,
+    gdpr,
+    i18n::Lang,
+    ingest::{self, ClientIp, ContentHasher, IngestError},
+    models::*,
+    notifications,
+    progress::*,
+    public_drop,
+    request_id::RequestId,
+    resumable, storage,
+    tasks::{enqueue, remove_upload_file, Task, POST_UPLOAD_COMMAND_KIND},
+    templates::*,
+    theme::{referer_path, Theme},
+    throttle::*,
+    totp, webhooks, AppState,
+};
 
 async fn get_session_from_headers(headers: &HeaderMap) -> Option<Session> {
     let session_id = headers
@@ -20,30 +52,73 @@ async fn get_session_from_headers(headers: &HeaderMap) -> Option<Session> {
     get_session(session_id).await
 }
 
+/// Whether the logged-in admin is an owner, who may release legal holds
+fn is_owner(state: &AppState, username: &str) -> bool {
+    get_admin_by_username(&state.db, username)
+        .ok()
+        .flatten()
+        .is_some_and(|admin| admin.role.is_owner())
+}
+
+/// Database ID of the logged-in admin
+fn admin_id_for(state: &AppState, username: &str) -> Option<String> {
+    get_admin_by_username(&state.db, username)
+        .ok()
+        .flatten()
+        .map(|admin| admin.id)
+}
+
+/// Time formatting for an admin, honouring their time zone preference
+fn admin_display_time(state: &AppState, username: &str) -> DisplayTime {
+    let timezone = get_admin_by_username(&state.db, username)
+        .ok()
+        .flatten()
+        .and_then(|admin| admin.timezone);
+    DisplayTime::for_timezone(&state.config, timezone.as_deref())
+}
+
 pub async fn upload_form(
     Path(token): Path<String>,
     State(state): State<AppState>,
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
 ) -> impl IntoResponse {
     debug!(token = %token, "Accessing upload form");
 
     match get_upload_link_by_token(&state.db, &token) {
         Ok(Some(link)) => {
-            if link.is_valid() {
+            let lang = lang.for_link(&link);
+            if link.is_public && !public_drop::has_pass(&state.config, &headers) {
+                // The public drop link is only reachable through the CAPTCHA
+                Redirect::to("/drop").into_response()
+            } else if link.is_valid() {
                 debug!(link_id = %link.id, link_name = %link.name, "Valid upload link accessed");
-                UploadTemplate {
+                let guest_cookie = ingest::new_guest_cookie(&link, &headers);
+                let mut response = UploadTemplate {
+                    lang,
+                    theme,
+                    time: DisplayTime::from_config(&state.config),
                     link,
                     error: None,
                     success: None,
+                    results: Vec::new(),
                 }
-                .into_response()
+                .into_response();
+                if let Some(cookie) = guest_cookie {
+                    response
+                        .headers_mut()
+                        .insert(header::SET_COOKIE, cookie.parse().unwrap());
+                }
+                response
             } else {
                 warn!(token = %token, "Expired or inactive upload link accessed");
-                (StatusCode::GONE, "Upload link has expired or is inactive").into_response()
+                (StatusCode::GONE, lang.t("upload-error-expired")).into_response()
             }
         }
         Ok(None) => {
             warn!(token = %token, "Upload link not found");
-            (StatusCode::NOT_FOUND, "Upload link not found").into_response()
+            (StatusCode::NOT_FOUND, lang.t("upload-error-not-found")).into_response()
         }
         Err(e) => {
             error!(token = %token, error = %e, "Database error while fetching upload link");
@@ -52,15 +127,228 @@ pub async fn upload_form(
     }
 }
 
+/// How an upload request is answered
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum UploadReply {
+    /// The upload page, showing the outcome (plain form posts)
+    Page,
+    /// Per-file results as JSON, for the upload page's script
+    Json,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_upload(
     State(state): State<AppState>,
     Path(token): Path<String>,
-    mut multipart: Multipart,
+    Query(query): Query<UploadQuery>,
+    client_ip: ClientIp,
+    request_id: RequestId,
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    multipart: Multipart,
+) -> Response {
+    let uploader = client_ip.uploader(request_id, &headers);
+    accept_upload(
+        &state,
+        &token,
+        query,
+        &uploader,
+        &headers,
+        lang,
+        theme,
+        multipart,
+        UploadReply::Page,
+    )
+    .await
+}
+
+/// The same upload as [`handle_upload`], answered with per-file JSON results
+///
+/// Each file gets its upload ID (once stored), size, `stored` or `refused`
+/// status and message, so the page can upload asynchronously and show the
+/// outcome without loading a new page.
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_upload_json(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    Query(query): Query<UploadQuery>,
+    client_ip: ClientIp,
+    request_id: RequestId,
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    multipart: Multipart,
+) -> Response {
+    let uploader = client_ip.uploader(request_id, &headers);
+    accept_upload(
+        &state,
+        &token,
+        query,
+        &uploader,
+        &headers,
+        lang,
+        theme,
+        multipart,
+        UploadReply::Json,
+    )
+    .await
+}
+
+/// Admit an upload request to a free upload slot and receive its files
+#[allow(clippy::too_many_arguments)]
+async fn accept_upload(
+    state: &AppState,
+    token: &str,
+    query: UploadQuery,
+    uploader: &Uploader,
+    headers: &HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    multipart: Multipart,
+    reply: UploadReply,
+) -> Response {
+    // Only track progress for well-formed IDs so the store can't be filled with junk keys
+    let upload_id = query.upload_id.filter(|id| Uuid::parse_str(id).is_ok());
+
+    let is_public = matches!(
+        get_upload_link_by_token(&state.db, token),
+        Ok(Some(link)) if link.is_public
+    );
+    if is_public && !public_drop::has_pass(&state.config, headers) {
+        warn!(token = %token, "Public drop upload without a CAPTCHA pass");
+        return (StatusCode::FORBIDDEN, lang.t("captcha-required")).into_response();
+    }
+
+    // Wait for a free upload slot, politely rejecting the upload if the server stays busy
+    let queue_timeout = std::time::Duration::from_secs(state.config.upload_queue_timeout_secs);
+    let _permit =
+        match tokio::time::timeout(queue_timeout, state.upload_limiter.clone().acquire_owned())
+            .await
+        {
+            Ok(Ok(permit)) => permit,
+            _ => {
+                warn!(token = %token, "Upload rejected: too many concurrent uploads");
+                return (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    [(header::RETRY_AFTER, "30")],
+                    lang.t("upload-error-busy"),
+                )
+                    .into_response();
+            }
+        };
+
+    let bytes_expected = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+
+    let Some(upload_id) = upload_id else {
+        return process_upload(
+            state,
+            token,
+            headers,
+            uploader,
+            lang,
+            theme,
+            multipart,
+            bytes_expected,
+            None,
+            reply,
+        )
+        .await;
+    };
+
+    start_progress(&upload_id, token, bytes_expected).await;
+    let response = process_upload(
+        state,
+        token,
+        headers,
+        uploader,
+        lang,
+        theme,
+        multipart,
+        bytes_expected,
+        Some(&upload_id),
+        reply,
+    )
+    .await;
+    finish_progress(&upload_id).await;
+
+    response
+}
+
+pub async fn upload_progress(
+    Path((token, upload_id)): Path<(String, String)>,
 ) -> impl IntoResponse {
+    match get_progress(&upload_id, &token).await {
+        Some(progress) => Json(progress).into_response(),
+        None => (StatusCode::NOT_FOUND, "Upload not found").into_response(),
+    }
+}
+
+/// How often the progress stream looks for newly received bytes
+const PROGRESS_EVENT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Checks for an upload that hasn't been registered (yet) before the stream ends
+const PROGRESS_EVENT_MAX_MISSES: u32 = 20;
+
+/// Progress of an in-flight upload as server-sent events, until it completes
+///
+/// The page opens the stream as it starts posting the file, so the upload may
+/// not be registered yet; the stream waits for it for a few seconds. An event
+/// is only sent when more bytes have arrived, plus a final one on completion.
+pub async fn upload_progress_events(
+    Path((token, upload_id)): Path<(String, String)>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let watch = (token, upload_id, None::<u64>, 0, false);
+    let events = futures::stream::unfold(
+        watch,
+        |(token, upload_id, last, mut misses, done)| async move {
+            if done {
+                return None;
+            }
+            loop {
+                tokio::time::sleep(PROGRESS_EVENT_INTERVAL).await;
+                match get_progress(&upload_id, &token).await {
+                    Some(progress)
+                        if progress.completed || Some(progress.bytes_received) != last =>
+                    {
+                        let event = Event::default().event("progress").json_data(&progress);
+                        let seen = Some(progress.bytes_received);
+                        return Some((event, (token, upload_id, seen, 0, progress.completed)));
+                    }
+                    Some(_) => {}
+                    None if misses >= PROGRESS_EVENT_MAX_MISSES => return None,
+                    None => misses += 1,
+                }
+            }
+        },
+    );
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Extra bytes allowed on top of the file size for multipart boundaries and headers
+const MULTIPART_OVERHEAD_BYTES: u64 = 64 * 1024;
+
+#[allow(clippy::too_many_arguments)]
+async fn process_upload(
+    state: &AppState,
+    token: &str,
+    headers: &HeaderMap,
+    uploader: &Uploader,
+    lang: Lang,
+    theme: Theme,
+    mut multipart: Multipart,
+    content_length: Option<u64>,
+    upload_id: Option<&str>,
+    reply: UploadReply,
+) -> Response {
     info!(token = %token, "File upload initiated");
 
     // Get upload link
-    let link = match get_upload_link_by_token(&state.db, &token) {
+    let mut link = match get_upload_link_by_token(&state.db, token) {
         Ok(Some(link)) if link.is_valid() => {
             debug!(
                 link_id = %link.id,
@@ -70,27 +358,42 @@ pub async fn handle_upload(
             );
             link
         }
-        Ok(Some(_)) => {
+        Ok(Some(expired_link)) => {
             warn!(token = %token, "Upload attempted with expired or inactive link");
+            ingest::record_failed_attempt(
+                state,
+                &expired_link,
+                None,
+                "link_unavailable",
+                None,
+                uploader,
+            );
+            let lang = lang.for_link(&expired_link);
+            if reply == UploadReply::Json {
+                return resumable::error_response(
+                    StatusCode::GONE,
+                    &lang.t("upload-error-expired"),
+                );
+            }
             return UploadTemplate {
+                lang,
+                theme,
+                time: DisplayTime::from_config(&state.config),
                 link: UploadLink {
-                    id: String::new(),
-                    token: token.clone(),
-                    name: "Expired Link".to_string(),
-                    max_file_size: 0,
-                    remaining_quota: 0,
-                    expires_at: None,
+                    token: token.to_string(),
+                    name: lang.t("upload-expired-link-name"),
                     created_at: Utc::now(),
-                    is_active: false,
+                    ..Default::default()
                 },
-                error: Some("Upload link has expired or is inactive".to_string()),
+                error: Some(lang.t("upload-error-expired")),
                 success: None,
+                results: Vec::new(),
             }
             .into_response();
         }
         Ok(None) => {
             warn!(token = %token, "Upload attempted with non-existent link");
-            return (StatusCode::NOT_FOUND, "Upload link not found").into_response();
+            return (StatusCode::NOT_FOUND, lang.t("upload-error-not-found")).into_response();
         }
         Err(e) => {
             error!(token = %token, error = %e, "Database error while fetching upload link");
@@ -98,243 +401,534 @@ pub async fn handle_upload(
         }
     };
 
-    // Process uploaded file
-    while let Some(field) = multipart.next_field().await.unwrap_or(None) {
-        let name = field.name().unwrap_or("").to_string();
-
-        if name == "file" {
-            let filename = field.file_name().unwrap_or("unnamed_file").to_string();
-
-            let content_type = field
-                .content_type()
-                .unwrap_or("application/octet-stream")
-                .to_string();
+    // A language forced on the link wins over the browser's preference
+    let lang = lang.for_link(&link);
 
-            debug!(
-                filename = %filename,
-                content_type = %content_type,
+    // The global body limit is disabled for this route; enforce the link's own limit instead.
+    // A file can never be larger than the remaining quota, so reject obviously oversized
+    // requests before reading any of the body.
+    let max_file_bytes = link.remaining_quota.max(0) as u64;
+    if let Some(content_length) = content_length {
+        if content_length > max_file_bytes + MULTIPART_OVERHEAD_BYTES {
+            warn!(
                 link_id = %link.id,
-                "Processing uploaded file"
+                content_length = content_length,
+                remaining_quota = link.remaining_quota,
+                "Upload request body exceeds link limit"
             );
+            ingest::record_failed_attempt(
+                state,
+                &link,
+                None,
+                "too_large",
+                Some(&format_file_size(content_length as i64)),
+                uploader,
+            );
+            let message = too_large_message(lang, &link, Some(content_length));
+            return upload_error_response(
+                state,
+                lang,
+                theme,
+                link,
+                reply,
+                StatusCode::PAYLOAD_TOO_LARGE,
+                message,
+            );
+        }
+    }
 
-            let data = match field.bytes().await {
-                Ok(data) => {
-                    info!(
-                        filename = %filename,
-                        file_size_mb = data.len() as f64 / 1024.0 / 1024.0,
-                        link_id = %link.id,
-                        "File data read successfully"
-                    );
-                    data
+    // Each file is checked against what the files before it left of the link
+    // Form fields sent ahead of the files describe the sender
+    let mut uploader = uploader.clone();
+    let mut message = None;
+    let mut results = Vec::new();
+    while let Some(mut field) = multipart.next_field().await.unwrap_or(None) {
+        if field.name() == Some("uploader_name") {
+            let max_bytes = ingest::MAX_UPLOADER_NAME_CHARS * 4;
+            if let Some(name) = read_text_field(&mut field, max_bytes).await {
+                uploader.name = ingest::uploader_name(&name);
+            }
+            continue;
+        }
+        if field.name() == Some("message") {
+            let max_bytes = ingest::MAX_UPLOAD_MESSAGE_CHARS * 4;
+            let text = read_text_field(&mut field, max_bytes)
+                .await
+                .map(|text| ingest::upload_message(&text));
+            match text {
+                Some(text) if text.chars().count() <= ingest::MAX_UPLOAD_MESSAGE_CHARS => {
+                    message = (!text.is_empty()).then_some(text);
                 }
-                Err(e) => {
-                    error!(
-                        filename = %filename,
-                        link_id = %link.id,
-                        error = %e,
-                        "Failed to read uploaded file"
+                _ => {
+                    let error = lang.t_arg(
+                        "upload-message-too-long",
+                        "limit",
+                        ingest::MAX_UPLOAD_MESSAGE_CHARS,
+                    );
+                    return upload_error_response(
+                        state,
+                        lang,
+                        theme,
+                        link,
+                        reply,
+                        StatusCode::BAD_REQUEST,
+                        error,
                     );
-                    return UploadTemplate {
-                        link: link.clone(),
-                        error: Some("Failed to read uploaded file".to_string()),
-                        success: None,
-                    }
-                    .into_response();
                 }
-            };
+            }
+            continue;
+        }
+        if field.name() != Some("file") {
+            continue;
+        }
 
-            // Check file size against remaining quota
-            if !link.can_accept_file(data.len() as i64) {
-                warn!(
-                    filename = %filename,
-                    file_size_mb = data.len() as f64 / 1024.0 / 1024.0,
-                    remaining_quota_mb = link.remaining_quota as f64 / 1024.0 / 1024.0,
-                    link_id = %link.id,
-                    "File size exceeds remaining quota"
-                );
-                return UploadTemplate {
-                    link: link.clone(),
-                    error: Some(format!(
-                        "File size ({:.1} MB) exceeds remaining quota ({:.1} MB). Total quota: {:.1} MB",
-                        data.len() as f64 / 1024.0 / 1024.0,
-                        link.remaining_quota as f64 / 1024.0 / 1024.0,
-                        link.max_file_size as f64 / 1024.0 / 1024.0
-                    )),
-                    success: None,
+        // Quota and file count shrink with every stored file
+        if results.iter().any(|result: &FileResult| result.stored) {
+            match get_upload_link_by_token(&state.db, token) {
+                Ok(Some(current)) => link = current,
+                Ok(None) => break,
+                Err(e) => {
+                    error!(token = %token, error = %e, "Database error while refreshing upload link");
+                    break;
                 }
-                .into_response();
             }
+        }
 
-            // Create guest directory
-            let guest_folder = Uuid::new_v4().to_string();
-            let guest_dir = state.upload_dir.join(&guest_folder);
+        if !link.is_valid() {
+            let filename = field.file_name().unwrap_or("unnamed_file").to_string();
+            results.push(refuse_unavailable(state, &link, &filename, lang, &uploader));
+            continue;
+        }
 
-            debug!(
-                guest_folder = %guest_folder,
-                guest_dir = %guest_dir.display(),
-                "Creating upload directory"
-            );
+        results.push(
+            receive_file(
+                state, &link, &mut field, headers, &uploader, lang, upload_id,
+            )
+            .await,
+        );
+    }
 
-            if (fs::create_dir_all(&guest_dir).await).is_err() {
-                error!(
-                    guest_dir = %guest_dir.display(),
-                    "Failed to create upload directory"
-                );
-                return UploadTemplate {
-                    link: link.clone(),
-                    error: Some("Failed to create upload directory".to_string()),
-                    success: None,
-                }
-                .into_response();
+    // The message is kept with the files it came with, so only if one was stored
+    if let Some(message) = message {
+        let upload_ids: Vec<&str> = results
+            .iter()
+            .filter_map(|result| result.upload_id.as_deref())
+            .collect();
+        if !upload_ids.is_empty() {
+            if let Err(e) = add_upload_message(&state.db, &link.id, &message, &upload_ids) {
+                error!(link_id = %link.id, error = %e, "Failed to store upload message");
             }
+        }
+    }
 
-            // Generate unique filename
-            let extension = std::path::Path::new(&filename)
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .unwrap_or("");
+    match reply {
+        UploadReply::Page => upload_results_response(state, lang, theme, link, results),
+        UploadReply::Json => upload_results_json(lang, &results),
+    }
+}
 
-            let stored_filename = if extension.is_empty() {
-                Uuid::new_v4().to_string()
-            } else {
-                format!("{}.{}", Uuid::new_v4(), extension)
-            };
+/// Answer an upload request that was refused before any file was read
+fn upload_error_response(
+    state: &AppState,
+    lang: Lang,
+    theme: Theme,
+    link: UploadLink,
+    reply: UploadReply,
+    status: StatusCode,
+    message: String,
+) -> Response {
+    match reply {
+        UploadReply::Json => resumable::error_response(status, &message),
+        UploadReply::Page => (
+            status,
+            UploadTemplate {
+                lang,
+                theme,
+                time: DisplayTime::from_config(&state.config),
+                error: Some(message),
+                link,
+                success: None,
+                results: Vec::new(),
+            },
+        )
+            .into_response(),
+    }
+}
 
-            let file_path = guest_dir.join(&stored_filename);
+/// Text of a short form field, or None if it is longer than `max_bytes` or not UTF-8
+async fn read_text_field(field: &mut Field<'_>, max_bytes: usize) -> Option<String> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = field.chunk().await.ok()? {
+        if bytes.len() + chunk.len() > max_bytes {
+            return None;
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    String::from_utf8(bytes).ok()
+}
 
-            debug!(
-                original_filename = %filename,
-                stored_filename = %stored_filename,
-                file_path = %file_path.display(),
-                "Generated unique filename"
-            );
+/// Receive one file of an upload request and store it on the link
+///
+/// The file goes through the same pipeline as every other channel (see
+/// [`ingest::store_upload`]); what is left here is reporting progress and
+/// telling the guest what became of the file in their language.
+async fn receive_file(
+    state: &AppState,
+    link: &UploadLink,
+    field: &mut Field<'_>,
+    headers: &HeaderMap,
+    uploader: &Uploader,
+    lang: Lang,
+    upload_id: Option<&str>,
+) -> FileResult {
+    let filename = field.file_name().unwrap_or("unnamed_file").to_string();
 
-            // Write file
-            match fs::write(&file_path, &data).await {
-                Ok(_) => {
-                    debug!(
-                        file_path = %file_path.display(),
-                        file_size = data.len(),
-                        "File written to disk successfully"
-                    );
+    let content_type = field
+        .content_type()
+        .unwrap_or("application/octet-stream")
+        .to_string();
 
-                    // Save to database
-                    let db_save_error = match create_file_upload(
-                        &state.db,
-                        &link.id,
-                        &filename,
-                        &stored_filename,
-                        data.len() as i64,
-                        &content_type,
-                        &guest_folder,
-                    ) {
-                        Ok(_) => None,
-                        Err(e) => Some(format!("{}", e)),
-                    };
-
-                    if let Some(error_msg) = db_save_error {
-                        error!(
-                            original_filename = %filename,
-                            stored_filename = %stored_filename,
-                            link_id = %link.id,
-                            error = %error_msg,
-                            "Failed to save upload information to database"
-                        );
-
-                        // Clean up file on database error
-                        let _ = fs::remove_file(&file_path).await;
-                        let _ = fs::remove_dir(&guest_dir).await;
-
-                        return UploadTemplate {
-                            link: link.clone(),
-                            error: Some("Failed to save upload information".to_string()),
-                            success: None,
-                        }
-                        .into_response();
-                    }
+    debug!(
+        filename = %filename,
+        content_type = %content_type,
+        link_id = %link.id,
+        "Processing uploaded file"
+    );
 
-                    // Success case
-                    info!(
-                        original_filename = %filename,
-                        stored_filename = %stored_filename,
-                        file_size_mb = data.len() as f64 / 1024.0 / 1024.0,
-                        link_id = %link.id,
-                        guest_folder = %guest_folder,
-                        "File upload completed successfully"
-                    );
+    // Files from the same guest on this link share a folder
+    let guest_folder = ingest::guest_folder_for(state, link, headers);
 
-                    // Update remaining quota
-                    if (update_remaining_quota(&state.db, &link.id, data.len() as i64)).is_err() {
-                        // Even if quota update fails, the file was uploaded successfully
-                        error!(
-                            link_id = %link.id,
-                            "Failed to update remaining quota for link"
-                        );
-                    }
+    // Only one chunk is held in memory at a time; progress advances as it is read
+    let chunks = field.then(move |chunk| async move {
+        if let (Some(upload_id), Ok(chunk)) = (upload_id, &chunk) {
+            advance_progress(upload_id, chunk.len() as u64).await;
+        }
+        chunk.map_err(std::io::Error::other)
+    });
 
-                    return UploadTemplate {
-                        link: link.clone(),
-                        error: None,
-                        success: Some("File uploaded successfully!".to_string()),
-                    }
-                    .into_response();
-                }
-                Err(e) => {
-                    error!(
-                        file_path = %file_path.display(),
-                        error = %e,
-                        "Failed to write file to disk"
-                    );
+    let result = ingest::store_upload(
+        state,
+        link,
+        &filename,
+        &content_type,
+        Some(&guest_folder),
+        uploader,
+        StreamReader::new(Box::pin(chunks)),
+    )
+    .await;
 
-                    return UploadTemplate {
-                        link: link.clone(),
-                        error: Some("Failed to save uploaded file".to_string()),
-                        success: None,
-                    }
-                    .into_response();
+    match result {
+        Ok(stored) => {
+            let message = match (stored.replaced, &stored.same_content_as) {
+                (true, _) => lang.t("upload-success-replaced"),
+                (false, Some(existing)) => {
+                    lang.t_arg("upload-success-same-content", "name", existing.as_str())
                 }
-            }
+                (false, None) => lang.t("upload-success"),
+            };
+            FileResult::stored(&filename, &stored.id, stored.file_size, message)
+        }
+        Err(e) => {
+            warn!(filename = %filename, link_id = %link.id, error = %e, "Upload not stored");
+            // Other files of the request may have used up the quota since the
+            // link was loaded; tell the guest what is left now
+            let current = match &e {
+                IngestError::QuotaExceeded => {
+                    get_upload_link_by_id(&state.db, &link.id).ok().flatten()
+                }
+                _ => None,
+            };
+            let link = current.as_ref().unwrap_or(link);
+            let (status, message) = refusal_message(lang, link, &filename, e);
+            FileResult::refused(&filename, status, message)
+        }
+    }
+}
+
+/// Status and message telling a guest why a file wasn't stored
+fn refusal_message(
+    lang: Lang,
+    link: &UploadLink,
+    filename: &str,
+    error: IngestError,
+) -> (StatusCode, String) {
+    match error {
+        IngestError::LinkUnavailable => (StatusCode::FORBIDDEN, lang.t("upload-error-expired")),
+        IngestError::FileLimitReached => {
+            (StatusCode::FORBIDDEN, lang.t("upload-error-files-limit"))
         }
+        IngestError::QuotaExceeded => (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            too_large_message(lang, link, None),
+        ),
+        IngestError::GuestQuotaExceeded(left) => (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            guest_quota_message(lang, link, left),
+        ),
+        IngestError::DailyQuotaExceeded(left) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            daily_quota_message(lang, link, left),
+        ),
+        IngestError::Duplicate => (
+            StatusCode::CONFLICT,
+            lang.t_arg("upload-error-duplicate", "name", filename),
+        ),
+        IngestError::FileType => (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            file_type_message(lang, link, filename),
+        ),
+        IngestError::ContentMismatch => (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            lang.t_arg("upload-error-file-content", "name", filename),
+        ),
+        IngestError::Infected(_) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            lang.t_arg("upload-error-infected", "name", filename),
+        ),
+        IngestError::ScanFailed(_) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            lang.t("upload-error-scan-failed"),
+        ),
+        IngestError::Rejected(reason) => (StatusCode::FORBIDDEN, reason.to_string()),
+        IngestError::RulesRefused(reason) => (
+            StatusCode::FORBIDDEN,
+            reason.unwrap_or_else(|| lang.t("upload-error-rules")),
+        ),
+        IngestError::SameContent(existing) => (
+            StatusCode::CONFLICT,
+            lang.t_arg(
+                "upload-error-same-content",
+                "name",
+                existing.original_filename.as_str(),
+            ),
+        ),
+        IngestError::Interrupted(_) => (StatusCode::OK, lang.t("upload-error-read-file")),
+        IngestError::Io(_) => (StatusCode::OK, lang.t("upload-error-save-file")),
+        IngestError::Database(_) => (StatusCode::OK, lang.t("upload-error-save-info")),
     }
+}
 
-    UploadTemplate {
-        link,
-        error: Some("No file was uploaded".to_string()),
-        success: None,
+/// Refuse a file arriving after the files before it used up the link
+fn refuse_unavailable(
+    state: &AppState,
+    link: &UploadLink,
+    filename: &str,
+    lang: Lang,
+    uploader: &Uploader,
+) -> FileResult {
+    let (reason, status, message) = if link.uploads_left() == Some(0) {
+        (
+            "link_unavailable",
+            StatusCode::FORBIDDEN,
+            lang.t("upload-error-files-limit"),
+        )
+    } else if link.remaining_quota <= 0 {
+        (
+            "too_large",
+            StatusCode::PAYLOAD_TOO_LARGE,
+            too_large_message(lang, link, None),
+        )
+    } else {
+        (
+            "link_unavailable",
+            StatusCode::GONE,
+            lang.t("upload-error-expired"),
+        )
+    };
+    warn!(link_id = %link.id, filename = %filename, reason = %reason, "Upload rejected: link used up by earlier files");
+    ingest::record_failed_attempt(state, link, Some(filename), reason, None, uploader);
+    FileResult::refused(filename, status, message)
+}
+
+/// The upload page reporting what became of each file of a request
+///
+/// A single file keeps its own message and status; several files get a
+/// summary and a line per file, and the request only fails as a whole when
+/// none of them was stored.
+fn upload_results_response(
+    state: &AppState,
+    lang: Lang,
+    theme: Theme,
+    link: UploadLink,
+    results: Vec<FileResult>,
+) -> Response {
+    let time = DisplayTime::from_config(&state.config);
+    let (status, error, success) = upload_results_summary(lang, &results);
+
+    // A lone file's outcome is already the page's message
+    let results = if results.len() > 1 {
+        results
+    } else {
+        Vec::new()
+    };
+
+    (
+        status,
+        UploadTemplate {
+            lang,
+            theme,
+            time,
+            link,
+            error,
+            success,
+            results,
+        },
+    )
+        .into_response()
+}
+
+/// Per-file results of an upload request as JSON, with the page's messages
+fn upload_results_json(lang: Lang, results: &[FileResult]) -> Response {
+    let (status, error, success) = upload_results_summary(lang, results);
+    let files: Vec<_> = results
+        .iter()
+        .map(|result| {
+            json!({
+                "filename": result.filename,
+                "id": result.upload_id,
+                "size": result.file_size,
+                "status": if result.stored { "stored" } else { "refused" },
+                "message": result.message,
+            })
+        })
+        .collect();
+
+    (
+        status,
+        Json(json!({
+            "success": success,
+            "error": error,
+            "files": files,
+        })),
+    )
+        .into_response()
+}
+
+/// Status and error and success messages answering an upload request
+fn upload_results_summary(
+    lang: Lang,
+    results: &[FileResult],
+) -> (StatusCode, Option<String>, Option<String>) {
+    match results {
+        [] => (StatusCode::OK, Some(lang.t("upload-error-no-file")), None),
+        [result] if result.stored => (result.status, None, Some(result.message.clone())),
+        [result] => (result.status, Some(result.message.clone()), None),
+        _ => {
+            let total = results.len();
+            let stored = results.iter().filter(|result| result.stored).count();
+            let failed = total - stored;
+            let status = match results.iter().rev().find(|result| !result.stored) {
+                Some(refused) if stored == 0 => refused.status,
+                _ => StatusCode::OK,
+            };
+            let success = (stored > 0).then(|| {
+                lang.t_args(
+                    "upload-results-stored",
+                    &[("stored", stored.to_string()), ("total", total.to_string())],
+                )
+            });
+            let error = (failed > 0).then(|| {
+                lang.t_args(
+                    "upload-results-failed",
+                    &[("failed", failed.to_string()), ("total", total.to_string())],
+                )
+            });
+            (status, error, success)
+        }
+    }
+}
+
+/// Error message shown when a file does not fit in what is left of a guest's own quota
+fn guest_quota_message(lang: Lang, link: &UploadLink, remaining: u64) -> String {
+    lang.t_args(
+        "upload-error-guest-quota",
+        &[
+            ("remaining", format_file_size(remaining as i64)),
+            ("quota", link.formatted_guest_quota().unwrap_or_default()),
+        ],
+    )
+}
+
+/// Error message shown when a link doesn't accept files of this type
+fn file_type_message(lang: Lang, link: &UploadLink, filename: &str) -> String {
+    lang.t_args(
+        "upload-error-file-type",
+        &[
+            ("name", filename.to_string()),
+            ("types", link.allowed_file_types.clone().unwrap_or_default()),
+        ],
+    )
+}
+
+/// Error message shown when a file does not fit in what a link accepts per 24 hours
+fn daily_quota_message(lang: Lang, link: &UploadLink, remaining: u64) -> String {
+    lang.t_args(
+        "upload-error-daily-quota",
+        &[
+            ("remaining", format_file_size(remaining as i64)),
+            ("quota", link.formatted_daily_quota().unwrap_or_default()),
+        ],
+    )
+}
+
+/// Error message shown when a file does not fit in a link's remaining quota or per-file limit
+///
+/// The file size is unknown when an upload was cut off while streaming.
+fn too_large_message(lang: Lang, link: &UploadLink, file_size: Option<u64>) -> String {
+    let megabytes = |bytes: f64| format!("{:.1}", bytes / 1024.0 / 1024.0);
+    if let Some(limit) = link.per_file_limit.filter(|_| link.is_file_size_limited()) {
+        let limit = ("limit", megabytes(limit as f64));
+        return match file_size {
+            Some(file_size) => lang.t_args(
+                "upload-error-file-limit",
+                &[("size", megabytes(file_size as f64)), limit],
+            ),
+            None => lang.t_args("upload-error-file-limit-unknown", &[limit]),
+        };
+    }
+
+    let remaining = ("remaining", megabytes(link.remaining_quota as f64));
+    let total = ("total", megabytes(link.total_quota as f64));
+
+    match file_size {
+        Some(file_size) => lang.t_args(
+            "upload-error-too-large",
+            &[("size", megabytes(file_size as f64)), remaining, total],
+        ),
+        None => lang.t_args("upload-error-too-large-unknown", &[remaining, total]),
     }
-    .into_response()
 }
 
-pub async fn login_form() -> impl IntoResponse {
-    LoginTemplate { error: None }
+pub async fn login_form(lang: Lang, theme: Theme) -> impl IntoResponse {
+    LoginTemplate {
+        lang,
+        theme,
+        error: None,
+        code_token: None,
+    }
 }
 
 pub async fn handle_login(
     State(state): State<AppState>,
+    lang: Lang,
+    theme: Theme,
     Form(form): Form<LoginForm>,
 ) -> impl IntoResponse {
     info!(username = %form.username, "Login attempt");
 
-    // Extract the admin data and validate
-    let (admin_id, admin_username) = match get_admin_by_username(&state.db, &form.username) {
+    // Look up the admin first; the database result must not be held across an await
+    let admin = match get_admin_by_username(&state.db, &form.username) {
         Ok(Some(admin)) => {
             debug!(admin_id = %admin.id, username = %admin.username, "Found admin user");
-
-            if verify_password(&form.password, &admin.password_hash) {
-                info!(admin_id = %admin.id, username = %admin.username, "Password verification successful");
-                (admin.id, admin.username)
-            } else {
-                warn!(username = %form.username, "Password verification failed");
-                return LoginTemplate {
-                    error: Some("Invalid username or password".to_string()),
-                }
-                .into_response();
-            }
+            admin
         }
         Ok(None) => {
             warn!(username = %form.username, "Admin user not found");
             return LoginTemplate {
-                error: Some("Invalid username or password".to_string()),
+                lang,
+                theme,
+                error: Some(lang.t("login-error-invalid")),
+                code_token: None,
             }
             .into_response();
         }
@@ -342,15 +936,125 @@ pub async fn handle_login(
             let error_msg = format!("{}", e);
             error!(username = %form.username, error = %error_msg, "Database error during login");
             return LoginTemplate {
-                error: Some("Invalid username or password".to_string()),
+                lang,
+                theme,
+                error: Some(lang.t("login-error-invalid")),
+                code_token: None,
             }
             .into_response();
         }
     };
 
+    // Verify the password off the async executor (bcrypt is intentionally slow)
+    if verify_password_async(form.password.clone(), admin.password_hash.clone()).await {
+        info!(admin_id = %admin.id, username = %admin.username, "Password verification successful");
+    } else {
+        warn!(username = %form.username, "Password verification failed");
+        return LoginTemplate {
+            lang,
+            theme,
+            error: Some(lang.t("login-error-invalid")),
+            code_token: None,
+        }
+        .into_response();
+    }
+
+    // With two-factor authentication on, the session waits for a code
+    if admin.totp_secret.is_some() {
+        debug!(admin_id = %admin.id, username = %admin.username, "Asking for two-factor code");
+        let token = totp::start_pending_login(admin.id, admin.username, admin.role);
+        return LoginTemplate {
+            lang,
+            theme,
+            error: None,
+            code_token: Some(token),
+        }
+        .into_response();
+    }
+
     // Now do the async part
-    let session_id = create_session(admin_id, admin_username).await;
+    let session_id = create_session(admin.id, admin.username, admin.role).await;
+    session_response(&session_id)
+}
+
+/// Second login step: check the two-factor code of a pending login
+pub async fn handle_login_code(
+    State(state): State<AppState>,
+    lang: Lang,
+    theme: Theme,
+    Form(form): Form<LoginCodeForm>,
+) -> impl IntoResponse {
+    // An expired or unknown login starts over with the password
+    let Some(pending) = totp::pending_login(&form.token) else {
+        return LoginTemplate {
+            lang,
+            theme,
+            error: Some(lang.t("login-error-code-expired")),
+            code_token: None,
+        }
+        .into_response();
+    };
+
+    let secret = match get_admin_by_username(&state.db, &pending.username) {
+        Ok(Some(admin)) if admin.id == pending.admin_id => admin.totp_secret,
+        Ok(_) => None,
+        Err(e) => {
+            error!(username = %pending.username, error = %e, "Database error during two-factor login");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
+        }
+    };
+
+    // Two-factor authentication was switched off (or the admin deleted) meanwhile
+    let Some(secret) = secret else {
+        totp::finish_pending_login(&form.token);
+        return LoginTemplate {
+            lang,
+            theme,
+            error: Some(lang.t("login-error-code-expired")),
+            code_token: None,
+        }
+        .into_response();
+    };
+
+    let accepted = match totp::verify_code(&secret, &form.code) {
+        Some(step) => match record_totp_step(&state.db, &pending.admin_id, step) {
+            Ok(fresh) => fresh,
+            Err(e) => {
+                error!(username = %pending.username, error = %e, "Failed to record two-factor code");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
+            }
+        },
+        None => false,
+    };
+
+    if !accepted {
+        warn!(username = %pending.username, "Two-factor code rejected");
+        if totp::record_failed_code(&form.token) {
+            return LoginTemplate {
+                lang,
+                theme,
+                error: Some(lang.t("login-error-code")),
+                code_token: Some(form.token),
+            }
+            .into_response();
+        }
+        return LoginTemplate {
+            lang,
+            theme,
+            error: Some(lang.t("login-error-code-attempts")),
+            code_token: None,
+        }
+        .into_response();
+    }
+
+    totp::finish_pending_login(&form.token);
+    info!(admin_id = %pending.admin_id, username = %pending.username, "Two-factor code accepted");
+    let session_id = create_session(pending.admin_id, pending.username, pending.role).await;
+    session_response(&session_id)
+}
 
+/// Redirect to the dashboard, setting the cookie of a new session
+fn session_response(session_id: &str) -> Response {
     let redirect = Redirect::to("/admin");
     let mut response = redirect.into_response();
 
@@ -368,6 +1072,8 @@ pub async fn handle_login(
 
 pub async fn admin_dashboard(
     headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
     State(state): State<AppState>,
 ) -> impl IntoResponse {
     let session = match get_session_from_headers(&headers).await {
@@ -376,56 +1082,153 @@ pub async fn admin_dashboard(
     };
 
     // Get stats for dashboard
-    let active_links_count = match get_all_upload_links(&state.db) {
-        Ok(links) => links.iter().filter(|link| link.is_valid()).count(),
-        Err(_) => 0,
+    let stats = match get_dashboard_stats(&state.db) {
+        Ok(stats) => stats,
+        Err(e) => {
+            error!(error = %e, "Failed to load dashboard statistics");
+            DashboardStats::default()
+        }
     };
 
-    let total_uploads_count = match get_all_file_uploads(&state.db) {
-        Ok(uploads) => uploads.len(),
-        Err(_) => 0,
-    };
+    // Links still in use that are running out of quota
+    let quota_warning_percent = state.config.quota_warning_percent;
+    let low_quota_links = get_all_upload_links(&state.db)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|link| {
+            link.is_active
+                && !link.is_archived()
+                && !link.is_expired()
+                && link.is_quota_low(quota_warning_percent)
+        })
+        .collect();
 
     AdminDashboardTemplate {
+        lang,
+        theme,
         username: session.username,
-        active_links: active_links_count,
-        total_uploads: total_uploads_count,
+        csrf_token: session.csrf_token,
+        stats,
+        failed_tasks: count_tasks_by_status(&state.db, "dead").unwrap_or(0),
+        pii_flagged: count_unreviewed_pii_uploads(&state.db).unwrap_or(0),
+        low_quota_links,
+        quota_warning_percent,
+        volumes: storage::volume_usage(&state),
+        is_owner: session.role.is_owner(),
+        read_only: session.role.is_read_only(),
     }
     .into_response()
 }
 
-pub async fn admin_links(headers: HeaderMap, State(state): State<AppState>) -> impl IntoResponse {
+pub async fn admin_links(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    Query(filter): Query<ListFilter>,
+) -> impl IntoResponse {
     let session = match get_session_from_headers(&headers).await {
         Some(session) => session,
         None => return Redirect::to("/login").into_response(),
     };
 
-    match get_all_upload_links(&state.db) {
-        Ok(links) => AdminLinksTemplate {
-            links,
-            username: session.username,
-            error: None,
+    links_page(&state, lang, theme, session, filter, None)
+}
+
+/// Links shown per page of the links list
+const LINKS_PAGE_SIZE: i64 = 50;
+
+/// One page of the links list, with an optional error from a failed action
+fn links_page(
+    state: &AppState,
+    lang: Lang,
+    theme: Theme,
+    session: Session,
+    filter: ListFilter,
+    error: Option<String>,
+) -> Response {
+    let sort = filter.list_sort();
+    let project_filter = filter.project.filter(|id| !id.is_empty());
+    let mut pagination = Pagination::new(filter.page, LINKS_PAGE_SIZE);
+
+    let page = get_upload_links_page(
+        &state.db,
+        project_filter.as_deref(),
+        filter.archived,
+        sort,
+        pagination.per_page,
+        pagination.offset(),
+    );
+    match page {
+        Ok((links, total)) => {
+            pagination.total = total;
+            let archived_count =
+                count_upload_links(&state.db, project_filter.as_deref(), true).unwrap_or(0);
+
+            AdminLinksTemplate {
+                lang,
+                theme,
+                time: admin_display_time(state, &session.username),
+                is_owner: is_owner(state, &session.username),
+                read_only: session.role.is_read_only(),
+                links,
+                username: session.username,
+                csrf_token: session.csrf_token,
+                error,
+                email_in_address: state.config.email_in_address.clone(),
+                public_base_url: state.config.public_base_url.clone(),
+                projects: get_all_projects(&state.db).unwrap_or_default(),
+                project_filter,
+                show_archived: filter.archived,
+                archived_count,
+                sort,
+                pagination,
+            }
+            .into_response()
         }
-        .into_response(),
         Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
     }
 }
 
-pub async fn create_link_form(headers: HeaderMap) -> impl IntoResponse {
+/// The create link form, with the projects a link can be filed under
+fn create_link_page(
+    state: &AppState,
+    lang: Lang,
+    theme: Theme,
+    session: Session,
+    error: Option<String>,
+) -> Response {
+    CreateLinkTemplate {
+        lang,
+        theme,
+        error,
+        username: session.username,
+        csrf_token: session.csrf_token,
+        projects: get_all_projects(&state.db).unwrap_or_default(),
+        post_upload_commands: state.config.post_upload_commands,
+        notifications: notifications::is_enabled(state),
+    }
+    .into_response()
+}
+
+pub async fn create_link_form(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
     let session = match get_session_from_headers(&headers).await {
         Some(session) => session,
         None => return Redirect::to("/login").into_response(),
     };
 
-    CreateLinkTemplate {
-        error: None,
-        username: session.username,
-    }
-    .into_response()
+    create_link_page(&state, lang, theme, session, None)
 }
 
 pub async fn handle_create_link(
     headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
     State(state): State<AppState>,
     form_result: Result<Form<CreateLinkForm>, FormRejection>,
 ) -> impl IntoResponse {
@@ -438,18 +1241,17 @@ pub async fn handle_create_link(
     let form = match form_result {
         Ok(Form(form)) => form,
         Err(_) => {
-            return CreateLinkTemplate {
-                error: Some(
-                    "Invalid form data. Please check that the expiration time is a valid number."
-                        .to_string(),
-                ),
-                username: session.username,
-            }
-            .into_response();
+            return create_link_page(
+                &state,
+                lang,
+                theme,
+                session,
+                Some(lang.t("create-link-error-form")),
+            );
         }
     };
 
-    let max_file_size = (form.max_file_size_mb * 1024.0 * 1024.0) as i64;
+    let total_quota = (form.total_quota_mb * 1024.0 * 1024.0) as i64;
 
     // Handle empty expiration field
     let expires_at = if let Some(hours) = form.expires_in_hours {
@@ -462,19 +1264,2600 @@ pub async fn handle_create_link(
         None
     };
 
-    match create_upload_link(&state.db, &form.name, max_file_size, expires_at) {
-        Ok(_) => Redirect::to("/admin/links").into_response(),
-        Err(_) => CreateLinkTemplate {
-            error: Some("Failed to create upload link".to_string()),
-            username: session.username,
-        }
-        .into_response(),
+    let rate_limit_kbps = form
+        .rate_limit_kbps
+        .filter(|kbps| *kbps > 0)
+        .map(|kbps| kbps as i64);
+
+    let webdav_folder = form
+        .webdav_folder
+        .as_deref()
+        .map(|folder| folder.trim().trim_matches('/'))
+        .filter(|folder| !folder.is_empty());
+
+    let guest_email = form
+        .guest_email
+        .as_deref()
+        .map(str::trim)
+        .filter(|email| !email.is_empty());
+    if guest_email.is_some_and(|email| !email.contains('@')) {
+        return create_link_page(
+            &state,
+            lang,
+            theme,
+            session,
+            Some(lang.t("create-link-error-email")),
+        );
     }
-}
 
-pub async fn delete_link(
+    // Anything that isn't a supported language means automatic negotiation
+    let language = form
+        .language
+        .as_deref()
+        .and_then(Lang::from_code)
+        .map(Lang::code);
+
+    let logo_url = form
+        .logo_url
+        .as_deref()
+        .map(str::trim)
+        .filter(|url| !url.is_empty());
+    if logo_url.is_some_and(|url| !is_logo_url(url)) {
+        return create_link_page(
+            &state,
+            lang,
+            theme,
+            session,
+            Some(lang.t("create-link-error-logo")),
+        );
+    }
+
+    let accent_color = form
+        .accent_color
+        .as_deref()
+        .map(str::trim)
+        .filter(|color| !color.is_empty());
+    if accent_color.is_some_and(|color| !is_hex_color(color)) {
+        return create_link_page(
+            &state,
+            lang,
+            theme,
+            session,
+            Some(lang.t("create-link-error-color")),
+        );
+    }
+
+    let per_file_limit = form
+        .per_file_limit_mb
+        .filter(|mb| *mb > 0)
+        .map(|mb| mb as i64 * 1024 * 1024);
+    let guest_quota = form
+        .guest_quota_mb
+        .filter(|mb| *mb > 0)
+        .map(|mb| mb as i64 * 1024 * 1024);
+    let daily_quota = form
+        .daily_quota_mb
+        .filter(|mb| *mb > 0)
+        .map(|mb| mb as i64 * 1024 * 1024);
+
+    // Stored in one spelling, which is also what guests are shown
+    let allowed_file_types =
+        match AllowedTypes::parse(form.allowed_file_types.as_deref().unwrap_or_default()) {
+            Ok(types) => types.map(|types| types.to_list()),
+            Err(entry) => {
+                return create_link_page(
+                    &state,
+                    lang,
+                    theme,
+                    session,
+                    Some(lang.t_arg("create-link-error-file-types", "entry", entry.as_str())),
+                );
+            }
+        };
+
+    // Ignored unless the operator allows commands; then only allowlisted programs
+    let post_upload_command = form
+        .post_upload_command
+        .as_deref()
+        .map(str::trim)
+        .filter(|command| !command.is_empty() && state.config.post_upload_commands);
+    if post_upload_command.is_some_and(|command| !commands::is_allowed(&state.config, command)) {
+        return create_link_page(
+            &state,
+            lang,
+            theme,
+            session,
+            Some(lang.t("create-link-error-command")),
+        );
+    }
+
+    let guest_note = form
+        .guest_note
+        .as_deref()
+        .map(str::trim)
+        .filter(|note| !note.is_empty());
+    if guest_note.is_some_and(|note| note.chars().count() > UploadLink::MAX_GUEST_NOTE_CHARS) {
+        return create_link_page(
+            &state,
+            lang,
+            theme,
+            session,
+            Some(lang.t_arg(
+                "create-link-error-note",
+                "limit",
+                UploadLink::MAX_GUEST_NOTE_CHARS,
+            )),
+        );
+    }
+
+    // The link's quota must fit into what is left of its project's quota
+    let project_id = form
+        .project_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|id| !id.is_empty());
+    if let Some(project_id) = project_id {
+        let project = match get_project_by_id(&state.db, project_id) {
+            Ok(Some(project)) => project,
+            Ok(None) => {
+                return create_link_page(
+                    &state,
+                    lang,
+                    theme,
+                    session,
+                    Some(lang.t("create-link-error-project")),
+                );
+            }
+            Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+        };
+        let allocated = get_project_allocated_bytes(&state.db, project_id).unwrap_or(0);
+        if let Some(available) = project.unallocated_quota(allocated) {
+            if total_quota > available {
+                return create_link_page(
+                    &state,
+                    lang,
+                    theme,
+                    session,
+                    Some(lang.t_args(
+                        "create-link-error-project-quota",
+                        &[
+                            ("project", project.name.clone()),
+                            ("available", format_file_size(available)),
+                        ],
+                    )),
+                );
+            }
+        }
+    }
+
+    let new_link = NewUploadLink {
+        name: &form.name,
+        total_quota,
+        per_file_limit,
+        expires_at,
+        rate_limit_kbps,
+        webdav_folder,
+        guest_email,
+        language,
+        logo_url,
+        accent_color,
+        project_id,
+        // Unknown codes fall back to keeping both files, the behavior before policies existed
+        collision_policy: form
+            .collision_policy
+            .as_deref()
+            .and_then(CollisionPolicy::from_code)
+            .unwrap_or_default(),
+        post_upload_command,
+        guest_quota,
+        max_uploads: form.max_uploads.filter(|count| *count > 0).map(i64::from),
+        daily_quota,
+        allowed_file_types: allowed_file_types.as_deref(),
+        notify_on_upload: form.notify_on_upload,
+        retention_days: form.retention_days.filter(|days| *days > 0).map(i64::from),
+        guest_note,
+    };
+
+    match create_upload_link(&state.db, &new_link) {
+        Ok(token) => {
+            if let Ok(Some(link)) = get_upload_link_by_token(&state.db, &token) {
+                events::link_created(&state, &link);
+            }
+            Redirect::to("/admin/links").into_response()
+        }
+        Err(_) => create_link_page(
+            &state,
+            lang,
+            theme,
+            session,
+            Some(lang.t("create-link-error-failed")),
+        ),
+    }
+}
+
+/// Logos are absolute http(s) URLs or paths on this server (e.g. `/static/acme.png`)
+fn is_logo_url(url: &str) -> bool {
+    url.starts_with("https://")
+        || url.starts_with("http://")
+        || (url.starts_with('/') && !url.starts_with("//"))
+}
+
+/// A `#rrggbb` color, the only form that is safe to put into the page's CSS
+fn is_hex_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// The edit form of a link, optionally with an error from the last attempt
+fn edit_link_page(
+    state: &AppState,
+    lang: Lang,
+    theme: Theme,
+    session: Session,
+    link: UploadLink,
+    error: Option<String>,
+) -> Response {
+    EditLinkTemplate {
+        lang,
+        theme,
+        time: admin_display_time(state, &session.username),
+        username: session.username,
+        csrf_token: session.csrf_token,
+        link,
+        error,
+    }
+    .into_response()
+}
+
+pub async fn edit_link_form(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Response, AppError> {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Ok(Redirect::to("/login").into_response()),
+    };
+
+    let link =
+        get_upload_link_by_id(&state.db, &id)?.ok_or(AppError::NotFound("Link not found"))?;
+    Ok(edit_link_page(&state, lang, theme, session, link, None))
+}
+
+pub async fn handle_edit_link(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+    form_result: Result<Form<EditLinkForm>, FormRejection>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    let link = match get_upload_link_by_id(&state.db, &id) {
+        Ok(Some(link)) => link,
+        Ok(None) => return (StatusCode::NOT_FOUND, "Link not found").into_response(),
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    };
+
+    let form = match form_result {
+        Ok(Form(form)) => form,
+        Err(_) => {
+            let error = lang.t("create-link-error-form");
+            return edit_link_page(&state, lang, theme, session, link, Some(error));
+        }
+    };
+
+    // The public drop is set up from the configuration at every start
+    if link.is_public {
+        let error = lang.t("edit-link-error-public");
+        return edit_link_page(&state, lang, theme, session, link, Some(error));
+    }
+
+    let name = form.name.trim();
+    if name.is_empty() {
+        let error = lang.t("edit-link-error-name");
+        return edit_link_page(&state, lang, theme, session, link, Some(error));
+    }
+
+    // Files already received keep their space, so the quota can't drop below it
+    let total_quota = (form.total_quota_mb * 1024.0 * 1024.0) as i64;
+    if total_quota <= 0 || total_quota < link.used_quota() {
+        let error = lang.t_arg("edit-link-error-quota", "used", link.formatted_used_quota());
+        return edit_link_page(&state, lang, theme, session, link, Some(error));
+    }
+
+    let per_file_limit = form
+        .per_file_limit_mb
+        .filter(|mb| *mb > 0)
+        .map(|mb| mb as i64 * 1024 * 1024);
+
+    let guest_note = form
+        .guest_note
+        .as_deref()
+        .map(str::trim)
+        .filter(|note| !note.is_empty());
+    if guest_note.is_some_and(|note| note.chars().count() > UploadLink::MAX_GUEST_NOTE_CHARS) {
+        let error = lang.t_arg(
+            "create-link-error-note",
+            "limit",
+            UploadLink::MAX_GUEST_NOTE_CHARS,
+        );
+        return edit_link_page(&state, lang, theme, session, link, Some(error));
+    }
+
+    // A bigger quota must still fit into what is left of the link's project
+    let project_error = match link.project_id.as_deref() {
+        Some(project_id) if total_quota > link.total_quota => {
+            match get_project_by_id(&state.db, project_id) {
+                Ok(Some(project)) => {
+                    let allocated = get_project_allocated_bytes(&state.db, project_id).unwrap_or(0);
+                    project
+                        .unallocated_quota(allocated - link.total_quota)
+                        .filter(|available| total_quota > *available)
+                        .map(|available| {
+                            lang.t_args(
+                                "create-link-error-project-quota",
+                                &[
+                                    ("project", project.name.clone()),
+                                    ("available", format_file_size(available)),
+                                ],
+                            )
+                        })
+                }
+                Ok(None) => None,
+                Err(_) => {
+                    return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+                }
+            }
+        }
+        _ => None,
+    };
+    if let Some(error) = project_error {
+        return edit_link_page(&state, lang, theme, session, link, Some(error));
+    }
+
+    // Empty keeps the current expiry, 0 removes it
+    let expires_at = match form.expires_in_hours {
+        None => link.expires_at,
+        Some(hours) if hours > 0 => Some(Utc::now() + Duration::hours(hours as i64)),
+        Some(_) => None,
+    };
+
+    match update_upload_link(
+        &state.db,
+        &id,
+        name,
+        total_quota,
+        per_file_limit,
+        expires_at,
+        form.retention_days.filter(|days| *days > 0).map(i64::from),
+        guest_note,
+        form.is_active,
+    ) {
+        Ok(_) => {
+            info!(link_id = %id, username = %session.username, total_quota, is_active = form.is_active, "Upload link updated");
+            Redirect::to("/admin/links").into_response()
+        }
+        Err(e) => {
+            error!(link_id = %id, error = %e, "Failed to update upload link");
+            let error = lang.t("edit-link-error-failed");
+            edit_link_page(&state, lang, theme, session, link, Some(error))
+        }
+    }
+}
+
+pub async fn delete_link(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    // Keep the link details for the deletion event
+    let link = get_upload_link_by_id(&state.db, &id).ok().flatten();
+
+    // Links under legal hold, and links that still have uploads, can't be deleted
+    let error_key = if link.as_ref().is_some_and(|link| link.legal_hold) {
+        Some("links-error-legal-hold")
+    } else {
+        match get_file_uploads_by_link_id(&state.db, &id) {
+            Ok(uploads) if !uploads.is_empty() => Some("links-error-has-uploads"),
+            Ok(_) => None,
+            Err(_) => {
+                // Database error checking uploads
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
+            }
+        }
+    };
+
+    if let Some(error_key) = error_key {
+        let error = Some(lang.t(error_key));
+        return links_page(&state, lang, theme, session, ListFilter::default(), error);
+    }
+
+    // No uploads associated, safe to delete
+    match delete_upload_link(&state.db, &id) {
+        Ok(_) => {
+            if let Some(link) = link {
+                events::link_deleted(&state, &link);
+            }
+            Redirect::to("/admin/links").into_response()
+        }
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Failed to delete link").into_response(),
+    }
+}
+
+pub async fn archive_link(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match set_links_archived(&state.db, &[id.as_str()], true) {
+        Ok(_) => info!(link_id = %id, "Link archived"),
+        Err(e) => error!(link_id = %id, error = %e, "Failed to archive link"),
+    }
+
+    Redirect::to("/admin/links")
+}
+
+pub async fn unarchive_link(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match set_links_archived(&state.db, &[id.as_str()], false) {
+        Ok(_) => info!(link_id = %id, "Link restored from the archive"),
+        Err(e) => error!(link_id = %id, error = %e, "Failed to restore link"),
+    }
+
+    Redirect::to("/admin/links?archived=true")
+}
+
+/// Pause an active link or resume a paused one without touching its files
+pub async fn toggle_link(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match toggle_upload_link_active(&state.db, &id) {
+        Ok(Some(true)) => info!(link_id = %id, "Link resumed"),
+        Ok(Some(false)) => info!(link_id = %id, "Link paused"),
+        Ok(None) => warn!(link_id = %id, "Link to toggle not found or public"),
+        Err(e) => error!(link_id = %id, error = %e, "Failed to toggle link"),
+    }
+
+    Redirect::to("/admin/links")
+}
+
+/// Archive or restore every link ticked in the links list
+///
+/// The form repeats the `id` field once per selected link, so it is read as
+/// raw pairs; `action` is `archive` or `unarchive`.
+pub async fn bulk_archive_links(
+    State(state): State<AppState>,
+    Form(fields): Form<Vec<(String, String)>>,
+) -> impl IntoResponse {
+    let archive = !fields
+        .iter()
+        .any(|(key, value)| key == "action" && value == "unarchive");
+    let ids: Vec<&str> = fields
+        .iter()
+        .filter(|(key, _)| key == "id")
+        .map(|(_, value)| value.as_str())
+        .collect();
+
+    match set_links_archived(&state.db, &ids, archive) {
+        Ok(changed) => info!(changed, archive, "Links archived in bulk"),
+        Err(e) => error!(error = %e, "Failed to archive links in bulk"),
+    }
+
+    if archive {
+        Redirect::to("/admin/links")
+    } else {
+        Redirect::to("/admin/links?archived=true")
+    }
+}
+
+/// The projects page, optionally with an error from the create form
+fn projects_page(
+    state: &AppState,
+    lang: Lang,
+    theme: Theme,
+    session: Session,
+    error: Option<String>,
+) -> Response {
+    match get_project_stats(&state.db) {
+        Ok(projects) => ProjectsTemplate {
+            lang,
+            theme,
+            username: session.username,
+            csrf_token: session.csrf_token,
+            projects,
+            error,
+        }
+        .into_response(),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    }
+}
+
+pub async fn admin_projects(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    projects_page(&state, lang, theme, session, None)
+}
+
+pub async fn handle_create_project(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    form_result: Result<Form<CreateProjectForm>, FormRejection>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    let form = match form_result {
+        Ok(Form(form)) if !form.name.trim().is_empty() => form,
+        _ => {
+            return projects_page(
+                &state,
+                lang,
+                theme,
+                session,
+                Some(lang.t("projects-error-form")),
+            );
+        }
+    };
+
+    let quota_bytes = form
+        .quota_mb
+        .filter(|mb| *mb > 0)
+        .map(|mb| mb as i64 * 1024 * 1024);
+
+    match create_project(&state.db, form.name.trim(), quota_bytes) {
+        Ok(id) => {
+            info!(project_id = %id, name = %form.name.trim(), "Project created");
+            Redirect::to("/admin/projects").into_response()
+        }
+        // Project names are unique
+        Err(_) => projects_page(
+            &state,
+            lang,
+            theme,
+            session,
+            Some(lang.t("projects-error-exists")),
+        ),
+    }
+}
+
+pub async fn delete_project(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match delete_project_and_ungroup_links(&state.db, &id) {
+        Ok(_) => info!(project_id = %id, "Project deleted"),
+        Err(e) => error!(project_id = %id, error = %e, "Failed to delete project"),
+    }
+
+    Redirect::to("/admin/projects")
+}
+
+/// The API keys page, with a key just created (shown this once) or an error
+fn api_keys_page(
+    state: &AppState,
+    lang: Lang,
+    theme: Theme,
+    session: Session,
+    new_key: Option<String>,
+    error: Option<String>,
+) -> Response {
+    match get_api_keys(&state.db) {
+        Ok(keys) => ApiKeysTemplate {
+            lang,
+            theme,
+            time: admin_display_time(state, &session.username),
+            username: session.username,
+            csrf_token: session.csrf_token,
+            keys,
+            new_key,
+            error,
+        }
+        .into_response(),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    }
+}
+
+pub async fn admin_api_keys(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    api_keys_page(&state, lang, theme, session, None, None)
+}
+
+pub async fn handle_create_api_key(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    form_result: Result<Form<CreateApiKeyForm>, FormRejection>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    let form = match form_result {
+        Ok(Form(form)) if !form.name.trim().is_empty() => form,
+        _ => {
+            return api_keys_page(
+                &state,
+                lang,
+                theme,
+                session,
+                None,
+                Some(lang.t("api-keys-error-form")),
+            );
+        }
+    };
+
+    // Only the hash is stored, so the page below is the one chance to copy the key
+    let key = api::generate_key();
+    match create_api_key(
+        &state.db,
+        form.name.trim(),
+        &api::hash_key(&key),
+        &api::key_prefix(&key),
+    ) {
+        Ok(id) => {
+            info!(api_key_id = %id, name = %form.name.trim(), created_by = %session.username, "API key created");
+            api_keys_page(&state, lang, theme, session, Some(key), None)
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to create API key");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match delete_api_key(&state.db, &id) {
+        Ok(_) => info!(api_key_id = %id, "API key revoked"),
+        Err(e) => error!(api_key_id = %id, error = %e, "Failed to revoke API key"),
+    }
+
+    Redirect::to("/admin/api-keys")
+}
+
+/// The admin accounts page, optionally with an error from one of its forms
+fn admins_page(
+    state: &AppState,
+    lang: Lang,
+    theme: Theme,
+    session: Session,
+    error: Option<String>,
+) -> Response {
+    match get_admins(&state.db) {
+        Ok(admins) => AdminsTemplate {
+            lang,
+            theme,
+            time: admin_display_time(state, &session.username),
+            username: session.username,
+            csrf_token: session.csrf_token,
+            admins,
+            roles: AdminRole::ALL,
+            error,
+        }
+        .into_response(),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    }
+}
+
+/// Admin accounts and their roles (owners only, see [`auth_middleware`])
+pub async fn admin_accounts(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    admins_page(&state, lang, theme, session, None)
+}
+
+pub async fn handle_create_admin(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    form_result: Result<Form<CreateAdminForm>, FormRejection>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    let form = match form_result {
+        Ok(Form(form)) if !form.username.trim().is_empty() => form,
+        _ => {
+            let error = Some(lang.t("admins-error-form"));
+            return admins_page(&state, lang, theme, session, error);
+        }
+    };
+    let Some(role) = AdminRole::from_code(&form.role) else {
+        let error = Some(lang.t("admins-error-form"));
+        return admins_page(&state, lang, theme, session, error);
+    };
+    if form.password.len() < 6 {
+        let error = Some(lang.t("password-error-too-short"));
+        return admins_page(&state, lang, theme, session, error);
+    }
+
+    let username = form.username.trim();
+    match get_admin_by_username(&state.db, username) {
+        Ok(None) => {}
+        Ok(Some(_)) => {
+            let error = Some(lang.t_arg("admins-error-taken", "username", username));
+            return admins_page(&state, lang, theme, session, error);
+        }
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    }
+
+    // Hash the password off the async executor
+    let password_hash = match hash_password(form.password).await {
+        Ok(hash) => hash,
+        Err(_) => {
+            let error = Some(lang.t("password-error-hash"));
+            return admins_page(&state, lang, theme, session, error);
+        }
+    };
+
+    match create_admin(&state.db, username, &password_hash, role) {
+        Ok(id) => {
+            info!(admin_id = %id, username = %username, role = role.code(), created_by = %session.username, "Admin account created");
+            Redirect::to("/admin/admins").into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to create admin account");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+pub async fn change_admin_role(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Form(form): Form<AdminRoleForm>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    let Some(role) = AdminRole::from_code(&form.role) else {
+        let error = Some(lang.t("admins-error-form"));
+        return admins_page(&state, lang, theme, session, error);
+    };
+
+    match update_admin_role(&state.db, &id, role).map_err(|e| e.to_string()) {
+        Ok(true) => {
+            info!(admin_id = %id, role = role.code(), changed_by = %session.username, "Admin role changed");
+            set_session_role(&id, role).await;
+            Redirect::to("/admin/admins").into_response()
+        }
+        Ok(false) => {
+            warn!(admin_id = %id, "Admin role not changed: last owner or unknown admin");
+            let error = Some(lang.t("admins-error-last-owner"));
+            admins_page(&state, lang, theme, session, error)
+        }
+        Err(e) => {
+            error!(admin_id = %id, error = %e, "Failed to change admin role");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+pub async fn delete_admin_account(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    // Deleting yourself would end the session mid-request
+    if id == session.admin_id {
+        let error = Some(lang.t("admins-error-self"));
+        return admins_page(&state, lang, theme, session, error);
+    }
+
+    match delete_admin(&state.db, &id).map_err(|e| e.to_string()) {
+        Ok(true) => {
+            info!(admin_id = %id, deleted_by = %session.username, "Admin account deleted");
+            remove_admin_sessions(&id).await;
+            Redirect::to("/admin/admins").into_response()
+        }
+        Ok(false) => {
+            warn!(admin_id = %id, "Admin account not deleted: last owner or unknown admin");
+            let error = Some(lang.t("admins-error-last-owner"));
+            admins_page(&state, lang, theme, session, error)
+        }
+        Err(e) => {
+            error!(admin_id = %id, error = %e, "Failed to delete admin account");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+/// Turn off another admin's two-factor authentication, e.g. after they lost
+/// their phone (owners only, see [`auth_middleware`])
+pub async fn reset_admin_two_factor(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    match update_admin_totp(&state.db, &id, None) {
+        Ok(_) => {
+            info!(admin_id = %id, reset_by = %session.username, "Admin two-factor authentication reset");
+            Redirect::to("/admin/admins").into_response()
+        }
+        Err(e) => {
+            error!(admin_id = %id, error = %e, "Failed to reset two-factor authentication");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+fn webhooks_page(
+    state: &AppState,
+    lang: Lang,
+    theme: Theme,
+    session: Session,
+    error: Option<String>,
+) -> Response {
+    match get_webhooks(&state.db) {
+        Ok(webhooks) => WebhooksTemplate {
+            lang,
+            theme,
+            time: admin_display_time(state, &session.username),
+            username: session.username,
+            csrf_token: session.csrf_token,
+            webhooks,
+            events: webhooks::EVENTS,
+            error,
+        }
+        .into_response(),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    }
+}
+
+pub async fn admin_webhooks(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    webhooks_page(&state, lang, theme, session, None)
+}
+
+pub async fn handle_create_webhook(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    form_result: Result<Form<CreateWebhookForm>, FormRejection>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    let form = match form_result {
+        Ok(Form(form)) if webhooks::is_valid_url(form.url.trim()) => form,
+        _ => {
+            return webhooks_page(
+                &state,
+                lang,
+                theme,
+                session,
+                Some(lang.t("webhooks-error-url")),
+            );
+        }
+    };
+
+    let events = match webhooks::parse_events(form.events.as_deref().unwrap_or_default()) {
+        Ok(events) => events,
+        Err(entry) => {
+            return webhooks_page(
+                &state,
+                lang,
+                theme,
+                session,
+                Some(lang.t_arg("webhooks-error-event", "event", entry.as_str())),
+            );
+        }
+    };
+
+    let secret = form
+        .secret
+        .as_deref()
+        .map(str::trim)
+        .filter(|secret| !secret.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(webhooks::generate_secret);
+
+    match create_webhook(&state.db, form.url.trim(), &secret, events.as_deref()) {
+        Ok(id) => {
+            info!(webhook_id = %id, url = %form.url.trim(), created_by = %session.username, "Webhook added");
+            Redirect::to("/admin/webhooks").into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to add webhook");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+        }
+    }
+}
+
+pub async fn remove_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match delete_webhook(&state.db, &id) {
+        Ok(_) => info!(webhook_id = %id, "Webhook removed"),
+        Err(e) => error!(webhook_id = %id, error = %e, "Failed to remove webhook"),
+    }
+
+    Redirect::to("/admin/webhooks")
+}
+
+/// The share link list, optionally offering to share one upload
+fn shares_page(
+    state: &AppState,
+    lang: Lang,
+    theme: Theme,
+    session: Session,
+    sharing: Option<FileUpload>,
+    error: Option<String>,
+) -> Response {
+    match get_share_link_summaries(&state.db) {
+        Ok(shares) => SharesTemplate {
+            lang,
+            theme,
+            time: admin_display_time(state, &session.username),
+            username: session.username,
+            csrf_token: session.csrf_token,
+            shares,
+            sharing,
+            error,
+        }
+        .into_response(),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    }
+}
+
+/// Share link list; `?upload=<id>` offers to share that upload on a new or existing share
+pub async fn admin_shares(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    Query(query): Query<ShareQuery>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    let sharing = query
+        .upload
+        .filter(|id| !id.is_empty())
+        .and_then(|id| get_file_upload_by_id(&state.db, &id).ok().flatten());
+
+    shares_page(&state, lang, theme, session, sharing, None)
+}
+
+pub async fn handle_create_share(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    form_result: Result<Form<CreateShareLinkForm>, FormRejection>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    let form = match form_result {
+        Ok(Form(form)) if !form.name.trim().is_empty() => form,
+        _ => {
+            return shares_page(
+                &state,
+                lang,
+                theme,
+                session,
+                None,
+                Some(lang.t("shares-error-form")),
+            );
+        }
+    };
+
+    let expires_at = form
+        .expires_in_hours
+        .filter(|hours| *hours > 0)
+        .map(|hours| Utc::now() + Duration::hours(hours as i64));
+    let max_downloads = form
+        .max_downloads
+        .filter(|max| *max > 0)
+        .map(|max| max as i64);
+
+    let id = match create_share_link(&state.db, form.name.trim(), expires_at, max_downloads) {
+        Ok(id) => id,
+        Err(e) => {
+            error!(error = %e, "Failed to create share link");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
+        }
+    };
+    info!(share_id = %id, name = %form.name.trim(), "Share link created");
+
+    if let Some(upload_id) = form.upload_id.filter(|id| !id.is_empty()) {
+        share_upload(&state, &id, &upload_id);
+    }
+
+    Redirect::to(&format!("/admin/shares/{}", id)).into_response()
+}
+
+/// Add an upload to a share link, logging what went wrong
+fn share_upload(state: &AppState, share_id: &str, upload_id: &str) {
+    let result = get_file_upload_by_id(&state.db, upload_id).and_then(|upload| match upload {
+        Some(upload) => add_shared_upload(&state.db, share_id, &upload),
+        None => Ok(false),
+    });
+
+    match result {
+        Ok(true) => {
+            info!(share_id = %share_id, upload_id = %upload_id, "Upload added to share link")
+        }
+        Ok(false) => {
+            debug!(share_id = %share_id, upload_id = %upload_id, "Upload missing or already shared")
+        }
+        Err(e) => {
+            error!(share_id = %share_id, upload_id = %upload_id, error = %e, "Failed to share upload")
+        }
+    }
+}
+
+/// Where a shared file is stored: its upload's file, or the admin's file in
+/// the share directory; with the key it is encrypted with
+fn shared_file_path(
+    state: &AppState,
+    file: &SharedFile,
+) -> Option<(std::path::PathBuf, Option<String>)> {
+    match (&file.upload_id, &file.stored_filename) {
+        (Some(upload_id), _) => get_file_upload_by_id(&state.db, upload_id)
+            .ok()
+            .flatten()
+            .map(|upload| {
+                (
+                    upload.file_path(&state.upload_dir),
+                    upload.encryption_key_id,
+                )
+            }),
+        (None, Some(stored_filename)) => Some((
+            storage::share_dir(&state.upload_dir, &file.share_link_id).join(stored_filename),
+            file.encryption_key_id.clone(),
+        )),
+        (None, None) => None,
+    }
+}
+
+/// A share link's files, with the form for adding more
+fn share_page(
+    state: &AppState,
+    lang: Lang,
+    theme: Theme,
+    session: Session,
+    id: &str,
+    error: Option<String>,
+) -> Response {
+    let link = match get_share_link_by_id(&state.db, id) {
+        Ok(Some(link)) => link,
+        Ok(None) => {
+            return (StatusCode::NOT_FOUND, lang.t("share-error-not-found")).into_response()
+        }
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    };
+
+    match get_shared_files(&state.db, id) {
+        Ok(files) => AdminShareTemplate {
+            lang,
+            theme,
+            time: admin_display_time(state, &session.username),
+            username: session.username,
+            csrf_token: session.csrf_token,
+            link,
+            files,
+            error,
+        }
+        .into_response(),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    }
+}
+
+pub async fn admin_share(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    share_page(&state, lang, theme, session, &id, None)
+}
+
+/// Add an existing upload to a share link
+pub async fn add_share_upload(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Form(form): Form<ShareUploadForm>,
+) -> impl IntoResponse {
+    share_upload(&state, &id, &form.upload_id);
+
+    Redirect::to(&format!("/admin/shares/{}", id))
+}
+
+/// Upload files of the admin's own to a share link
+pub async fn upload_share_files(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    match get_share_link_by_id(&state.db, &id) {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (StatusCode::NOT_FOUND, lang.t("share-error-not-found")).into_response()
+        }
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    }
+
+    let dir = storage::share_dir(&state.upload_dir, &id);
+    if let Err(e) = fs::create_dir_all(&dir).await {
+        error!(share_id = %id, error = %e, "Failed to create share directory");
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to store file").into_response();
+    }
+
+    loop {
+        let mut field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => {
+                warn!(share_id = %id, error = %e, "Share upload interrupted");
+                return share_page(
+                    &state,
+                    lang,
+                    theme,
+                    session,
+                    &id,
+                    Some(lang.t("share-error-upload")),
+                );
+            }
+        };
+        let Some(filename) = field.file_name().filter(|name| !name.is_empty()) else {
+            continue;
+        };
+        let filename = filename.to_string();
+        let mime_type = field
+            .content_type()
+            .unwrap_or("application/octet-stream")
+            .to_string();
+
+        let stored_filename = Uuid::new_v4().to_string();
+        let file_path = dir.join(&stored_filename);
+
+        let mut reader =
+            StreamReader::new((&mut field).map(|chunk| chunk.map_err(std::io::Error::other)));
+        let stored = ingest::copy_with_limit(
+            &state.disk_write_limiter,
+            &mut reader,
+            &file_path,
+            u64::MAX,
+            None,
+            &mut ContentHasher::new(),
+        )
+        .await
+        .map_err(|e| e.to_string());
+        // Encrypted at rest like guest uploads
+        let stored = match stored {
+            Ok(file_size) => ingest::encrypt_stored_file(&state, &file_path)
+                .await
+                .map(|key_id| (file_size, key_id))
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e),
+        };
+
+        let recorded = stored.and_then(|(file_size, key_id)| {
+            add_shared_file(
+                &state.db,
+                &id,
+                &stored_filename,
+                &filename,
+                file_size as i64,
+                &mime_type,
+                key_id.as_deref(),
+            )
+            .map_err(|e| e.to_string())
+        });
+        match recorded {
+            Ok(file_id) => {
+                info!(share_id = %id, file_id = %file_id, filename = %filename, "File uploaded to share link");
+            }
+            Err(error_msg) => {
+                error!(share_id = %id, filename = %filename, error = %error_msg, "Failed to store shared file");
+                let _ = fs::remove_file(&file_path).await;
+                return share_page(
+                    &state,
+                    lang,
+                    theme,
+                    session,
+                    &id,
+                    Some(lang.t("share-error-upload")),
+                );
+            }
+        }
+    }
+
+    Redirect::to(&format!("/admin/shares/{}", id)).into_response()
+}
+
+/// Take a file off a share link; files the admin uploaded to it are deleted
+pub async fn remove_share_file(
+    State(state): State<AppState>,
+    Path((id, file_id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let removed = remove_shared_file(&state.db, &id, &file_id).map_err(|e| e.to_string());
+    match removed {
+        Ok(Some(file)) => {
+            info!(share_id = %id, file_id = %file_id, "File removed from share link");
+            if let Some(stored_filename) = &file.stored_filename {
+                let _ = fs::remove_file(
+                    storage::share_dir(&state.upload_dir, &id).join(stored_filename),
+                )
+                .await;
+            }
+        }
+        Ok(None) => {}
+        Err(e) => {
+            error!(share_id = %id, file_id = %file_id, error = %e, "Failed to remove shared file")
+        }
+    }
+
+    Redirect::to(&format!("/admin/shares/{}", id))
+}
+
+pub async fn delete_share(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let deleted = delete_share_link(&state.db, &id).map_err(|e| e.to_string());
+    match deleted {
+        Ok(_) => {
+            info!(share_id = %id, "Share link deleted");
+            // Shared uploads stay; only the admin's own files go with the share
+            let _ = fs::remove_dir_all(storage::share_dir(&state.upload_dir, &id)).await;
+        }
+        Err(e) => error!(share_id = %id, error = %e, "Failed to delete share link"),
+    }
+
+    Redirect::to("/admin/shares")
+}
+
+/// Guest page listing a share link's files
+pub async fn share_download_page(
+    Path(token): Path<String>,
+    State(state): State<AppState>,
+    lang: Lang,
+    theme: Theme,
+) -> impl IntoResponse {
+    let link = match get_share_link_by_token(&state.db, &token) {
+        Ok(Some(link)) => link,
+        Ok(None) => {
+            warn!(token = %token, "Share link not found");
+            return (StatusCode::NOT_FOUND, lang.t("share-error-not-found")).into_response();
+        }
+        Err(e) => {
+            error!(token = %token, error = %e, "Database error while fetching share link");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
+        }
+    };
+    if !link.is_valid() {
+        warn!(token = %token, "Expired or used up share link accessed");
+        return (StatusCode::GONE, lang.t("share-error-expired")).into_response();
+    }
+
+    match get_shared_files(&state.db, &link.id) {
+        Ok(files) => ShareTemplate {
+            lang,
+            theme,
+            time: DisplayTime::from_config(&state.config),
+            link,
+            files,
+        }
+        .into_response(),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    }
+}
+
+/// Download one file of a share link, counting it against the link's download limit
+pub async fn download_shared_file(
+    Path((token, file_id)): Path<(String, String)>,
+    State(state): State<AppState>,
+    lang: Lang,
+) -> impl IntoResponse {
+    let link = match get_share_link_by_token(&state.db, &token) {
+        Ok(Some(link)) => link,
+        Ok(None) => {
+            return (StatusCode::NOT_FOUND, lang.t("share-error-not-found")).into_response()
+        }
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    };
+    let shared = match get_shared_file(&state.db, &link.id, &file_id) {
+        Ok(Some(shared)) => shared,
+        Ok(None) => return (StatusCode::NOT_FOUND, "File not found").into_response(),
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    };
+
+    // Open the file before counting the download, so a missing file doesn't use one up
+    let opened = match shared_file_path(&state, &shared) {
+        Some((path, key_id)) => {
+            encryption::open(state.encryption.as_deref(), &path, key_id.as_deref()).await
+        }
+        None => Err(std::io::ErrorKind::NotFound.into()),
+    };
+    let (file, content_length) = match opened {
+        Ok(opened) => opened,
+        Err(e) => {
+            warn!(share_id = %link.id, file_id = %file_id, error = %e, "Shared file not found on disk");
+            return (StatusCode::NOT_FOUND, "File not found on disk").into_response();
+        }
+    };
+
+    match claim_share_download(&state.db, &link.id) {
+        Ok(true) => {}
+        Ok(false) => {
+            warn!(share_id = %link.id, "Download refused: share link expired or used up");
+            return (StatusCode::GONE, lang.t("share-error-expired")).into_response();
+        }
+        Err(e) => {
+            error!(share_id = %link.id, error = %e, "Failed to count share download");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
+        }
+    }
+
+    info!(
+        share_id = %link.id,
+        file_id = %file_id,
+        original_filename = %shared.original_filename,
+        file_size = content_length,
+        "Streaming shared file to guest"
+    );
+
+    attachment_response(
+        file,
+        content_length,
+        &shared.original_filename,
+        &shared.mime_type,
+        None,
+    )
+}
+
+/// Maximum number of uploads, and of links, listed for an admin search
+const SEARCH_RESULT_LIMIT: i64 = 50;
+
+/// Global admin search over filenames, link names and guest emails
+pub async fn admin_search(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    Query(search): Query<SearchQuery>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    let query = search.q.trim().to_string();
+    let results = match search_everything(&state.db, &query, SEARCH_RESULT_LIMIT) {
+        Ok(results) => results,
+        Err(e) => {
+            error!(query = %query, error = %e, "Search failed");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
+        }
+    };
+
+    SearchTemplate {
+        lang,
+        theme,
+        time: admin_display_time(&state, &session.username),
+        username: session.username,
+        csrf_token: session.csrf_token,
+        query,
+        results,
+        public_base_url: state.config.public_base_url.clone(),
+    }
+    .into_response()
+}
+
+/// Uploads shown per page of the uploads list
+const UPLOADS_PAGE_SIZE: i64 = 50;
+
+pub async fn admin_uploads(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    Query(filter): Query<ListFilter>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    let sort = filter.list_sort();
+    let project_filter = filter.project.filter(|id| !id.is_empty());
+    let admin_id = admin_id_for(&state, &session.username);
+    let mut pagination = Pagination::new(filter.page, UPLOADS_PAGE_SIZE);
+
+    // Files of archived links stay reachable through the archived view
+    let query = UploadListQuery {
+        project: project_filter.as_deref(),
+        archived: filter.archived,
+        // An admin without an account row has starred nothing
+        starred_by: filter.starred.then(|| admin_id.as_deref().unwrap_or("")),
+        flagged: filter.flagged,
+        sort,
+    };
+    let page = get_file_uploads_grouped_by_link(
+        &state.db,
+        &query,
+        pagination.per_page,
+        pagination.offset(),
+    );
+
+    match page {
+        Ok(page) => {
+            pagination.total = page.total;
+            let starred = admin_id
+                .and_then(|admin_id| get_starred_upload_ids(&state.db, &admin_id).ok())
+                .unwrap_or_default();
+            let uploads: Vec<&FileUpload> = page
+                .groups
+                .iter()
+                .flat_map(|(_, uploads)| uploads)
+                .collect();
+            let messages = get_upload_messages(&state.db, &uploads).unwrap_or_default();
+
+            AdminUploadsTemplate {
+                lang,
+                theme,
+                time: admin_display_time(&state, &session.username),
+                is_owner: is_owner(&state, &session.username),
+                read_only: session.role.is_read_only(),
+                grouped_uploads: page.groups,
+                messages,
+                total_size: page.total_size,
+                username: session.username,
+                csrf_token: session.csrf_token,
+                projects: get_all_projects(&state.db).unwrap_or_default(),
+                project_filter,
+                show_archived: filter.archived,
+                starred,
+                show_starred: filter.starred,
+                show_flagged: filter.flagged,
+                pii_scan: state.config.pii_scan,
+                sort,
+                pagination,
+            }
+            .into_response()
+        }
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    }
+}
+
+pub async fn download_file(
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Response, AppError> {
+    // Check authentication
+    let _session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Ok(Redirect::to("/login").into_response()),
+    };
+
+    // Get the file upload record
+    let Some(upload) = get_file_upload_by_id(&state.db, &id)? else {
+        warn!(upload_id = %id, "File upload not found");
+        return Err(AppError::NotFound("File not found"));
+    };
+    debug!(
+        upload_id = %id,
+        original_filename = %upload.original_filename,
+        stored_filename = %upload.stored_filename,
+        "Found file upload record"
+    );
+
+    // Construct file path
+    let file_path = upload.file_path(&state.upload_dir);
+
+    debug!(
+        upload_id = %id,
+        file_path = %file_path.display(),
+        "Attempting to serve file"
+    );
+
+    // Open the file and stream it in chunks so large files never sit in memory
+    let (file, content_length) = encryption::open(
+        state.encryption.as_deref(),
+        &file_path,
+        upload.encryption_key_id.as_deref(),
+    )
+    .await?;
+
+    info!(
+        upload_id = %id,
+        original_filename = %upload.original_filename,
+        file_size = content_length,
+        "Streaming file to client"
+    );
+
+    // Apply the link's bandwidth limit (if any) to the download as well
+    let throttle = get_upload_link_by_id(&state.db, &upload.link_id)
+        .ok()
+        .flatten()
+        .and_then(|link| TokenBucket::for_link(&link));
+
+    Ok(attachment_response(
+        file,
+        content_length,
+        &upload.original_filename,
+        &upload.mime_type,
+        throttle,
+    ))
+}
+
+/// Stream an opened file as a download, optionally paced by its link's `throttle`
+pub(crate) fn attachment_response(
+    file: encryption::StoredReader,
+    content_length: u64,
+    filename: &str,
+    mime_type: &str,
+    throttle: Option<TokenBucket>,
+) -> Response {
+    let stream = ReaderStream::new(file);
+    let body = match throttle {
+        Some(bucket) => Body::from_stream(throttle_stream(stream, bucket)),
+        None => Body::from_stream(stream),
+    };
+
+    // A stored type that isn't a valid header value must not fail the download
+    let mime_type = header::HeaderValue::from_str(mime_type)
+        .unwrap_or(header::HeaderValue::from_static("application/octet-stream"));
+
+    // Create response with proper headers
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, mime_type)
+        .header(header::CONTENT_DISPOSITION, content_disposition(filename))
+        .header(header::CONTENT_LENGTH, content_length)
+        .extension(Uncompressed)
+        .body(body)
+        .unwrap()
+}
+
+/// `Content-Disposition` value offering a file for download (RFC 6266)
+///
+/// Filenames come from guests and may hold quotes, control characters or
+/// non-ASCII text: `filename` gets an ASCII fallback, `filename*` the exact
+/// name percent-encoded as UTF-8.
+pub(crate) fn content_disposition(filename: &str) -> String {
+    disposition("attachment", filename)
+}
+
+/// `Content-Disposition` value showing a file in the browser, keeping its name for saving
+fn inline_disposition(filename: &str) -> String {
+    disposition("inline", filename)
+}
+
+fn disposition(kind: &str, filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|c| {
+            if (c.is_ascii_graphic() || c == ' ') && !matches!(c, '"' | '\\') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let encoded: String = filename
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => (byte as char).to_string(),
+            b'!' | b'#' | b'$' | b'&' | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect();
+    format!(
+        "{}; filename=\"{}\"; filename*=UTF-8''{}",
+        kind, fallback, encoded
+    )
+}
+
+/// Text shown on the preview page; longer files are cut off
+const PREVIEW_TEXT_BYTES: u64 = 256 * 1024;
+
+/// Preview page showing an image, PDF or text file without downloading it
+pub async fn preview_upload(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Response, AppError> {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Ok(Redirect::to("/login").into_response()),
+    };
+
+    let upload =
+        get_file_upload_by_id(&state.db, &id)?.ok_or(AppError::NotFound("File not found"))?;
+
+    // Text is read here and escaped by the template, never served as a page of its own
+    let kind = upload.preview_kind();
+    let (text, truncated) = if kind == PreviewKind::Text {
+        let (text, truncated) = read_preview_text(&state, &upload).await?;
+        (Some(text), truncated)
+    } else {
+        (None, false)
+    };
+
+    Ok(UploadPreviewTemplate {
+        lang,
+        theme,
+        username: session.username,
+        csrf_token: session.csrf_token,
+        upload,
+        kind,
+        text,
+        truncated,
+    }
+    .into_response())
+}
+
+/// The start of a text file, decoded leniently, and whether there is more
+async fn read_preview_text(
+    state: &AppState,
+    upload: &FileUpload,
+) -> std::io::Result<(String, bool)> {
+    let file_path = upload.file_path(&state.upload_dir);
+    let (file, content_length) = encryption::open(
+        state.encryption.as_deref(),
+        &file_path,
+        upload.encryption_key_id.as_deref(),
+    )
+    .await?;
+
+    let mut bytes = Vec::new();
+    file.take(PREVIEW_TEXT_BYTES)
+        .read_to_end(&mut bytes)
+        .await?;
+
+    Ok((
+        String::from_utf8_lossy(&bytes).into_owned(),
+        content_length > PREVIEW_TEXT_BYTES,
+    ))
+}
+
+/// An image or PDF for the preview page, served inline
+///
+/// The content type comes from a fixed list rather than from the guest, and
+/// browsers are told not to sniff, so a file can't turn into a page that runs
+/// scripts in the admin's session.
+pub async fn preview_upload_content(
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Response, AppError> {
+    if get_session_from_headers(&headers).await.is_none() {
+        return Ok(Redirect::to("/login").into_response());
+    }
+
+    let upload =
+        get_file_upload_by_id(&state.db, &id)?.ok_or(AppError::NotFound("File not found"))?;
+    let content_type = upload
+        .preview_content_type()
+        .ok_or(AppError::NotFound("No preview for this file"))?;
+
+    let file_path = upload.file_path(&state.upload_dir);
+    let (file, content_length) = encryption::open(
+        state.encryption.as_deref(),
+        &file_path,
+        upload.encryption_key_id.as_deref(),
+    )
+    .await?;
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(
+            header::CONTENT_DISPOSITION,
+            inline_disposition(&upload.original_filename),
+        )
+        .header(header::CONTENT_LENGTH, content_length)
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .header(header::CACHE_CONTROL, "private, no-store")
+        .extension(Uncompressed)
+        .body(Body::from_stream(ReaderStream::new(file)))
+        .unwrap())
+}
+
+/// Download every file of a link as one ZIP archive, assembled while it is sent
+pub async fn download_link_files(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Response, AppError> {
+    let link =
+        get_upload_link_by_id(&state.db, &id)?.ok_or(AppError::NotFound("Link not found"))?;
+
+    let uploads = get_file_uploads_by_link_id(&state.db, &id)?;
+    if uploads.is_empty() {
+        return Err(AppError::NotFound("This link has no files"));
+    }
+
+    info!(link_id = %id, file_count = uploads.len(), "Streaming ZIP archive of link files");
+
+    // Path separators would make browsers drop part of the archive name
+    let archive_name = match link.name.trim().replace(['/', '\\'], "_") {
+        name if name.is_empty() => "uploads.zip".to_string(),
+        name => format!("{}.zip", name),
+    };
+
+    let entries = archive::entries(&uploads, &state.upload_dir);
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/zip")
+        .header(
+            header::CONTENT_DISPOSITION,
+            content_disposition(&archive_name),
+        )
+        .extension(Uncompressed)
+        .body(archive::body(
+            entries,
+            state.encryption.clone(),
+            TokenBucket::for_link(&link),
+        ))
+        .unwrap())
+}
+
+pub async fn delete_upload(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let upload = match get_file_upload_by_id(&state.db, &id) {
+        Ok(Some(upload)) => upload,
+        Ok(None) => return Redirect::to("/admin/uploads"),
+        Err(_) => return Redirect::to("/admin/uploads"),
+    };
+
+    // Held files stay until an owner releases the hold; a failed check counts as held
+    if is_upload_held(&state.db, &id).unwrap_or(true) {
+        warn!(upload_id = %id, "Refused to delete upload under legal hold");
+        return Redirect::to("/admin/uploads");
+    }
+    if let Err(reason) = state.hooks.before_delete(&upload) {
+        warn!(upload_id = %id, reason = %reason, "Upload hook refused deletion");
+        return Redirect::to("/admin/uploads");
+    }
+
+    // Delete from database
+    if delete_file_upload(&state.db, &id).is_err() {
+        return Redirect::to("/admin/uploads"); // Still redirect on error for now
+    }
+
+    // Remove the file from disk in the background so transient failures are retried
+    let task = Task::RemoveUploadFile {
+        storage_dir: upload.storage_dir.clone(),
+        stored_filename: upload.stored_filename.clone(),
+        volume: upload.volume.clone(),
+    };
+    let enqueue_result = enqueue(&state, &task).map_err(|e| e.to_string());
+    if let Err(error_msg) = enqueue_result {
+        error!(upload_id = %id, error = %error_msg, "Failed to enqueue file removal, removing directly");
+        let _ = remove_upload_file(
+            &state,
+            &upload.storage_dir,
+            &upload.stored_filename,
+            upload.volume.clone(),
+        )
+        .await;
+    }
+
+    Redirect::to("/admin/uploads")
+}
+
+/// Delete or download every upload ticked in the uploads list
+///
+/// The form repeats the `id` field once per selected upload, so it is read as
+/// raw pairs; `action` is `delete` or `download`. Deleting is all or nothing:
+/// if any selected file is under legal hold or kept by an upload hook, none
+/// is deleted.
+pub async fn bulk_uploads(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Form(fields): Form<Vec<(String, String)>>,
+) -> Response {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    let action = fields
+        .iter()
+        .find(|(key, _)| key == "action")
+        .map(|(_, value)| value.as_str());
+    let ids: Vec<&str> = fields
+        .iter()
+        .filter(|(key, _)| key == "id")
+        .map(|(_, value)| value.as_str())
+        .collect();
+
+    let back = headers
+        .get(header::REFERER)
+        .and_then(|value| value.to_str().ok())
+        .map(referer_path)
+        .filter(|path| path.starts_with("/admin/uploads"))
+        .unwrap_or("/admin/uploads");
+    if ids.is_empty() {
+        return Redirect::to(back).into_response();
+    }
+
+    let uploads = match get_file_uploads_by_ids(&state.db, &ids) {
+        Ok(uploads) => uploads,
+        Err(e) => {
+            error!(error = %e, "Failed to load uploads for a bulk action");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
+        }
+    };
+
+    match action {
+        Some("download") => bulk_download(&state, &uploads),
+        Some("delete") if session.role.is_read_only() => {
+            (StatusCode::FORBIDDEN, "Not allowed").into_response()
+        }
+        Some("delete") => {
+            bulk_delete(&state, &uploads).await;
+            Redirect::to(back).into_response()
+        }
+        _ => (StatusCode::BAD_REQUEST, "Unknown action").into_response(),
+    }
+}
+
+/// Selected uploads as one ZIP archive, assembled while it is sent
+fn bulk_download(state: &AppState, uploads: &[FileUpload]) -> Response {
+    if uploads.is_empty() {
+        return (StatusCode::NOT_FOUND, "File not found").into_response();
+    }
+    info!(
+        file_count = uploads.len(),
+        "Streaming ZIP archive of selected files"
+    );
+
+    let entries = archive::entries(uploads, &state.upload_dir);
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/zip")
+        .header(
+            header::CONTENT_DISPOSITION,
+            content_disposition("uploads.zip"),
+        )
+        .extension(Uncompressed)
+        .body(archive::body(entries, state.encryption.clone(), None))
+        .unwrap()
+}
+
+/// Delete selected uploads together, or none of them if any may not be deleted
+async fn bulk_delete(state: &AppState, uploads: &[FileUpload]) {
+    // Held files stay until an owner releases the hold; a failed check counts as held
+    if let Some(held) = uploads
+        .iter()
+        .find(|upload| is_upload_held(&state.db, &upload.id).unwrap_or(true))
+    {
+        warn!(upload_id = %held.id, "Refused to bulk delete uploads including one under legal hold");
+        return;
+    }
+    for upload in uploads {
+        if let Err(reason) = state.hooks.before_delete(upload) {
+            warn!(upload_id = %upload.id, reason = %reason, "Upload hook refused bulk deletion");
+            return;
+        }
+    }
+
+    let ids: Vec<&str> = uploads.iter().map(|upload| upload.id.as_str()).collect();
+    if let Err(e) = delete_file_uploads(&state.db, &ids) {
+        error!(error = %e, "Failed to delete uploads in bulk");
+        return;
+    }
+    info!(count = uploads.len(), "Uploads deleted in bulk");
+
+    // Remove the files from disk in the background so transient failures are retried
+    for upload in uploads {
+        let task = Task::RemoveUploadFile {
+            storage_dir: upload.storage_dir.clone(),
+            stored_filename: upload.stored_filename.clone(),
+            volume: upload.volume.clone(),
+        };
+        let enqueue_result = enqueue(state, &task).map_err(|e| e.to_string());
+        if let Err(error_msg) = enqueue_result {
+            error!(upload_id = %upload.id, error = %error_msg, "Failed to enqueue file removal, removing directly");
+            let _ = remove_upload_file(
+                state,
+                &upload.storage_dir,
+                &upload.stored_filename,
+                upload.volume.clone(),
+            )
+            .await;
+        }
+    }
+}
+
+/// Star or unstar an upload for the logged-in admin, then return to the list
+pub async fn star_upload(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Form(form): Form<StarForm>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login"),
+    };
+
+    match admin_id_for(&state, &session.username) {
+        Some(admin_id) => {
+            if let Err(e) = set_upload_starred(&state.db, &admin_id, &id, form.starred) {
+                error!(upload_id = %id, error = %e, "Failed to update star");
+            }
+        }
+        None => warn!(username = %session.username, "Star requested by unknown admin"),
+    }
+
+    let back = headers
+        .get(header::REFERER)
+        .and_then(|value| value.to_str().ok())
+        .map(referer_path)
+        .filter(|path| path.starts_with("/admin"))
+        .unwrap_or("/admin/uploads");
+    Redirect::to(back)
+}
+
+/// Mark the personal data findings of an upload as reviewed, then return to the list
+pub async fn review_pii_findings(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login"),
+    };
+
+    match mark_pii_reviewed(&state.db, &id) {
+        Ok(true) => {
+            let detail = get_file_upload_by_id(&state.db, &id)
+                .ok()
+                .flatten()
+                .map(|upload| upload.original_filename);
+            if let Err(e) = record_audit_event(
+                &state.db,
+                &session.username,
+                "pii.reviewed",
+                "upload",
+                &id,
+                detail.as_deref(),
+            ) {
+                error!(upload_id = %id, error = %e, "Failed to record personal data review in the audit log");
+            }
+        }
+        Ok(false) => {}
+        Err(e) => {
+            error!(upload_id = %id, error = %e, "Failed to mark personal data findings as reviewed")
+        }
+    }
+
+    let back = headers
+        .get(header::REFERER)
+        .and_then(|value| value.to_str().ok())
+        .map(referer_path)
+        .filter(|path| path.starts_with("/admin"))
+        .unwrap_or("/admin/uploads");
+    Redirect::to(back)
+}
+
+/// Re-hash an upload on disk and compare it to the checksum taken while it was written
+///
+/// Files stored before hashing get the computed checksum recorded as their
+/// baseline. A missing or changed file is logged and flagged on the uploads page.
+pub async fn verify_upload(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login"),
+    };
+
+    let upload = match get_file_upload_by_id(&state.db, &id) {
+        Ok(Some(upload)) => upload,
+        _ => return Redirect::to("/admin/uploads"),
+    };
+
+    let file_path = upload.file_path(&state.upload_dir);
+    let hashed = ingest::hash_file(
+        state.encryption.as_deref(),
+        &file_path,
+        upload.encryption_key_id.as_deref(),
+    );
+    let (ok, computed) = match hashed.await {
+        Ok(hash) => (
+            upload
+                .content_hash
+                .as_deref()
+                .is_none_or(|stored| stored == hash),
+            Some(hash),
+        ),
+        Err(e) => {
+            error!(upload_id = %id, error = %e, "Failed to read file for integrity check");
+            (false, None)
+        }
+    };
+
+    if ok {
+        info!(upload_id = %id, username = %session.username, "Upload passed integrity check");
+    } else {
+        error!(
+            upload_id = %id,
+            username = %session.username,
+            expected = ?upload.content_hash,
+            actual = ?computed,
+            "Upload failed integrity check"
+        );
+    }
+    if let Err(e) = record_integrity_check(&state.db, &id, ok, computed.as_deref()) {
+        error!(upload_id = %id, error = %e, "Failed to record integrity check");
+    }
+
+    let back = headers
+        .get(header::REFERER)
+        .and_then(|value| value.to_str().ok())
+        .map(referer_path)
+        .filter(|path| path.starts_with("/admin"))
+        .unwrap_or("/admin/uploads");
+    Redirect::to(back)
+}
+
+/// Place a legal hold on an upload; any admin may do this
+pub async fn hold_upload(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    change_legal_hold(&state, &headers, "upload", &id, true).await
+}
+
+/// Release the legal hold on an upload (owners only)
+pub async fn release_upload(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    change_legal_hold(&state, &headers, "upload", &id, false).await
+}
+
+/// Place a legal hold on a link and with it all of its uploads
+pub async fn hold_link(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    change_legal_hold(&state, &headers, "link", &id, true).await
+}
+
+/// Release the legal hold on a link (owners only)
+pub async fn release_link(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    change_legal_hold(&state, &headers, "link", &id, false).await
+}
+
+/// Set the legal hold on an upload or link, record the change in the audit
+/// log and return to the page the admin came from
+async fn change_legal_hold(
+    state: &AppState,
+    headers: &HeaderMap,
+    target_type: &str,
+    id: &str,
+    held: bool,
+) -> Redirect {
+    let session = match get_session_from_headers(headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login"),
+    };
+
+    let back = headers
+        .get(header::REFERER)
+        .and_then(|value| value.to_str().ok())
+        .map(referer_path)
+        .filter(|path| path.starts_with("/admin"))
+        .unwrap_or(if target_type == "link" {
+            "/admin/links"
+        } else {
+            "/admin/uploads"
+        });
+
+    if !held && !is_owner(state, &session.username) {
+        warn!(username = %session.username, target_type, target_id = %id, "Legal hold release refused: not an owner");
+        return Redirect::to(back);
+    }
+
+    let (change_result, detail) = if target_type == "link" {
+        let detail = get_upload_link_by_id(&state.db, id)
+            .ok()
+            .flatten()
+            .map(|link| link.name);
+        (set_link_legal_hold(&state.db, id, held), detail)
+    } else {
+        let detail = get_file_upload_by_id(&state.db, id)
+            .ok()
+            .flatten()
+            .map(|upload| upload.original_filename);
+        (set_upload_legal_hold(&state.db, id, held), detail)
+    };
+
+    match change_result {
+        Ok(true) => {
+            let action = if held {
+                "legal_hold.placed"
+            } else {
+                "legal_hold.released"
+            };
+            info!(username = %session.username, target_type, target_id = %id, action, "Legal hold changed");
+            if let Err(e) = record_audit_event(
+                &state.db,
+                &session.username,
+                action,
+                target_type,
+                id,
+                detail.as_deref(),
+            ) {
+                error!(target_id = %id, error = %e, "Failed to record legal hold change in the audit log");
+            }
+        }
+        Ok(false) => {}
+        Err(e) => error!(target_type, target_id = %id, error = %e, "Failed to change legal hold"),
+    }
+
+    Redirect::to(back)
+}
+
+/// Number of entries shown on the audit log page
+const AUDIT_LOG_PAGE_SIZE: i64 = 200;
+
+/// Recent entries of the audit log
+pub async fn admin_audit_log(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    let events = match get_recent_audit_events(&state.db, AUDIT_LOG_PAGE_SIZE) {
+        Ok(events) => events,
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    };
+
+    AuditLogTemplate {
+        lang,
+        theme,
+        time: admin_display_time(&state, &session.username),
+        username: session.username,
+        csrf_token: session.csrf_token,
+        events,
+    }
+    .into_response()
+}
+
+/// Days of daily uploads charted on a link's statistics page
+const LINK_STATS_DAYS: i64 = 30;
+
+/// Statistics page of a link: totals, daily uploads and last activity
+pub async fn link_stats(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Response, AppError> {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Ok(Redirect::to("/login").into_response()),
+    };
+
+    let link =
+        get_upload_link_by_id(&state.db, &id)?.ok_or(AppError::NotFound("Link not found"))?;
+    let stats = get_link_stats(&state.db, &link.id, LINK_STATS_DAYS)?;
+
+    Ok(LinkStatsTemplate {
+        lang,
+        theme,
+        time: admin_display_time(&state, &session.username),
+        username: session.username,
+        csrf_token: session.csrf_token,
+        link,
+        stats,
+    }
+    .into_response())
+}
+
+/// Number of refused uploads shown on a link's attempts page
+const ATTEMPTS_PAGE_SIZE: i64 = 200;
+
+/// Uploads a link turned away, with the reason each was refused
+pub async fn link_attempts(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    let link = match get_upload_link_by_id(&state.db, &id) {
+        Ok(Some(link)) => link,
+        Ok(None) => return (StatusCode::NOT_FOUND, "Link not found").into_response(),
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    };
+    let attempts = match get_upload_attempts(&state.db, &link.id, ATTEMPTS_PAGE_SIZE) {
+        Ok(attempts) => attempts,
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    };
+
+    LinkAttemptsTemplate {
+        lang,
+        theme,
+        time: admin_display_time(&state, &session.username),
+        username: session.username,
+        csrf_token: session.csrf_token,
+        link,
+        attempts,
+    }
+    .into_response()
+}
+
+/// Post-upload command runs listed on the tasks page
+const COMMAND_LOG_SIZE: i64 = 50;
+
+pub async fn admin_tasks(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    let dead_tasks = match get_tasks_by_status(&state.db, "dead") {
+        Ok(tasks) => tasks,
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    };
+
+    AdminTasksTemplate {
+        lang,
+        theme,
+        time: admin_display_time(&state, &session.username),
+        username: session.username,
+        csrf_token: session.csrf_token,
+        dead_tasks,
+        command_runs: if state.config.post_upload_commands {
+            get_recent_tasks_by_kind(&state.db, POST_UPLOAD_COMMAND_KIND, COMMAND_LOG_SIZE)
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        },
+        pending_count: count_tasks_by_status(&state.db, "pending").unwrap_or(0),
+        running_count: count_tasks_by_status(&state.db, "running").unwrap_or(0),
+    }
+    .into_response()
+}
+
+pub async fn retry_task(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match retry_dead_task(&state.db, &id) {
+        Ok(_) => info!(task_id = %id, "Dead task requeued by admin"),
+        Err(e) => error!(task_id = %id, error = %e, "Failed to requeue task"),
+    }
+
+    Redirect::to("/admin/tasks")
+}
+
+pub async fn discard_task(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match delete_task(&state.db, &id) {
+        Ok(_) => info!(task_id = %id, "Dead task discarded by admin"),
+        Err(e) => error!(task_id = %id, error = %e, "Failed to discard task"),
+    }
+
+    Redirect::to("/admin/tasks")
+}
+
+pub async fn change_password_form(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    ChangePasswordTemplate {
+        lang,
+        theme,
+        error: None,
+        success: None,
+        username: session.username,
+        csrf_token: session.csrf_token,
+    }
+    .into_response()
+}
+
+pub async fn handle_change_password(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    Form(form): Form<ChangePasswordForm>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    // Validate that new passwords match
+    if form.new_password != form.confirm_password {
+        return ChangePasswordTemplate {
+            lang,
+            theme,
+            error: Some(lang.t("password-error-mismatch")),
+            success: None,
+            username: session.username,
+            csrf_token: session.csrf_token,
+        }
+        .into_response();
+    }
+
+    // Validate password length
+    if form.new_password.len() < 6 {
+        return ChangePasswordTemplate {
+            lang,
+            theme,
+            error: Some(lang.t("password-error-too-short")),
+            success: None,
+            username: session.username.clone(),
+            csrf_token: session.csrf_token.clone(),
+        }
+        .into_response();
+    }
+
+    // Get current admin user (using session username)
+    let admin = match get_admin_by_username(&state.db, &session.username) {
+        Ok(Some(admin)) => admin,
+        Ok(None) => {
+            return ChangePasswordTemplate {
+                lang,
+                theme,
+                error: Some(lang.t("password-error-not-found")),
+                success: None,
+                username: session.username,
+                csrf_token: session.csrf_token,
+            }
+            .into_response()
+        }
+        Err(_) => {
+            return ChangePasswordTemplate {
+                lang,
+                theme,
+                error: Some(lang.t("password-error-database")),
+                success: None,
+                username: session.username,
+                csrf_token: session.csrf_token,
+            }
+            .into_response()
+        }
+    };
+
+    // Verify current password (off the async executor)
+    if !verify_password_async(form.current_password, admin.password_hash).await {
+        return ChangePasswordTemplate {
+            lang,
+            theme,
+            error: Some(lang.t("password-error-incorrect")),
+            success: None,
+            username: session.username,
+            csrf_token: session.csrf_token,
+        }
+        .into_response();
+    }
+
+    // Hash new password (off the async executor)
+    let new_hash = match hash_password(form.new_password).await {
+        Ok(hash) => hash,
+        Err(_) => {
+            return ChangePasswordTemplate {
+                lang,
+                theme,
+                error: Some(lang.t("password-error-hash")),
+                success: None,
+                username: session.username,
+                csrf_token: session.csrf_token,
+            }
+            .into_response();
+        }
+    };
+
+    // Update password in database
+    match update_admin_password(&state.db, &session.username, &new_hash) {
+        Ok(_) => ChangePasswordTemplate {
+            lang,
+            theme,
+            error: None,
+            success: Some(lang.t("password-success")),
+            username: session.username,
+            csrf_token: session.csrf_token,
+        }
+        .into_response(),
+        Err(_) => ChangePasswordTemplate {
+            lang,
+            theme,
+            error: Some(lang.t("password-error-update")),
+            success: None,
+            username: session.username,
+            csrf_token: session.csrf_token,
+        }
+        .into_response(),
+    }
+}
+
+/// Render the two-factor page, offering `secret` for setup when it's off
+fn two_factor_page(
+    state: &AppState,
+    lang: Lang,
+    theme: Theme,
+    session: Session,
+    secret: Option<String>,
+    error: Option<String>,
+    success: Option<String>,
+) -> Response {
+    let enabled = match get_admin_by_username(&state.db, &session.username) {
+        Ok(Some(admin)) => admin.totp_secret.is_some(),
+        Ok(None) => return Redirect::to("/login").into_response(),
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    };
+
+    let (secret, qr_code) = if enabled {
+        (String::new(), None)
+    } else {
+        let secret = secret.unwrap_or_else(totp::generate_secret);
+        let qr_code = totp::qr_code_svg(&totp::provisioning_uri(&secret, &session.username));
+        (secret, qr_code)
+    };
+
+    TwoFactorTemplate {
+        lang,
+        theme,
+        error,
+        success,
+        username: session.username,
+        csrf_token: session.csrf_token,
+        enabled,
+        secret,
+        qr_code,
+    }
+    .into_response()
+}
+
+pub async fn two_factor_form(
     headers: HeaderMap,
-    Path(id): Path<String>,
+    lang: Lang,
+    theme: Theme,
     State(state): State<AppState>,
 ) -> impl IntoResponse {
     let session = match get_session_from_headers(&headers).await {
@@ -482,307 +3865,521 @@ pub async fn delete_link(
         None => return Redirect::to("/login").into_response(),
     };
 
-    // Check if there are any uploads associated with this link
-    match get_file_uploads_by_link_id(&state.db, &id) {
-        Ok(uploads) => {
-            if !uploads.is_empty() {
-                // There are uploads associated with this link, show error
-                let links = get_all_upload_links(&state.db).unwrap_or_default();
-                return AdminLinksTemplate {
-                    links,
-                    username: session.username,
-                    error: Some("Cannot delete link: it still has uploaded files. Please delete the files first.".to_string()),
-                }
-                .into_response();
-            }
-        }
-        Err(_) => {
-            // Database error checking uploads
-            return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
-        }
-    }
-
-    // No uploads associated, safe to delete
-    match delete_upload_link(&state.db, &id) {
-        Ok(_) => Redirect::to("/admin/links").into_response(),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Failed to delete link").into_response(),
-    }
+    two_factor_page(&state, lang, theme, session, None, None, None)
 }
 
-pub async fn admin_uploads(headers: HeaderMap, State(state): State<AppState>) -> impl IntoResponse {
-    let session = match get_session_from_headers(&headers).await {
-        Some(session) => session,
-        None => return Redirect::to("/login").into_response(),
-    };
-
-    match get_all_file_uploads(&state.db) {
-        Ok(uploads) => {
-            // Group uploads by link_id
-            let mut grouped_uploads: std::collections::HashMap<
-                String,
-                (UploadLink, Vec<FileUpload>),
-            > = std::collections::HashMap::new();
-
-            for upload in uploads {
-                if let Ok(Some(link)) = get_upload_link_by_id(&state.db, &upload.link_id) {
-                    grouped_uploads
-                        .entry(upload.link_id.clone())
-                        .or_insert_with(|| (link, Vec::new()))
-                        .1
-                        .push(upload);
-                } else {
-                    // If link is not found, create placeholder
-                    let placeholder_link = UploadLink {
-                        id: upload.link_id.clone(),
-                        token: "unknown".to_string(),
-                        name: "Deleted Link".to_string(),
-                        max_file_size: 0,
-                        remaining_quota: 0,
-                        expires_at: None,
-                        created_at: Utc::now(),
-                        is_active: false,
-                    };
-                    grouped_uploads
-                        .entry(upload.link_id.clone())
-                        .or_insert_with(|| (placeholder_link, Vec::new()))
-                        .1
-                        .push(upload);
-                }
-            }
+pub async fn handle_enable_two_factor(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    Form(form): Form<EnableTwoFactorForm>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
 
-            // Convert to sorted vector for template
-            let mut grouped_vec: Vec<(UploadLink, Vec<FileUpload>)> =
-                grouped_uploads.into_values().collect();
-            // Sort by link creation date (newest first)
-            grouped_vec.sort_by(|a, b| b.0.created_at.cmp(&a.0.created_at));
+    let secret = form.secret.trim().to_string();
+    if !totp::is_valid_secret(&secret) {
+        let error = Some(lang.t("two-factor-error-code"));
+        return two_factor_page(&state, lang, theme, session, None, error, None);
+    }
 
-            // Sort files within each group by upload date (newest first)
-            for (_, uploads) in &mut grouped_vec {
-                uploads.sort_by(|a, b| b.uploaded_at.cmp(&a.uploaded_at));
-            }
+    // Only store the secret once the app has shown it generates the right codes
+    let Some(step) = totp::verify_code(&secret, &form.code) else {
+        let error = Some(lang.t("two-factor-error-code"));
+        return two_factor_page(&state, lang, theme, session, Some(secret), error, None);
+    };
 
-            AdminUploadsTemplate {
-                grouped_uploads: grouped_vec,
-                username: session.username,
-            }
-            .into_response()
+    let saved = update_admin_totp(&state.db, &session.admin_id, Some(&secret))
+        .and_then(|_| record_totp_step(&state.db, &session.admin_id, step));
+    match saved {
+        Ok(_) => {
+            info!(username = %session.username, "Two-factor authentication enabled");
+            let success = Some(lang.t("two-factor-enabled"));
+            two_factor_page(&state, lang, theme, session, None, None, success)
+        }
+        Err(e) => {
+            error!(username = %session.username, error = %e, "Failed to enable two-factor authentication");
+            let error = Some(lang.t("two-factor-error-save"));
+            two_factor_page(&state, lang, theme, session, Some(secret), error, None)
         }
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
     }
 }
 
-pub async fn download_file(
+pub async fn handle_disable_two_factor(
     headers: HeaderMap,
-    Path(id): Path<String>,
+    lang: Lang,
+    theme: Theme,
     State(state): State<AppState>,
+    Form(form): Form<DisableTwoFactorForm>,
 ) -> impl IntoResponse {
-    // Check authentication
-    let _session = match get_session_from_headers(&headers).await {
+    let session = match get_session_from_headers(&headers).await {
         Some(session) => session,
         None => return Redirect::to("/login").into_response(),
     };
 
-    // Get the file upload record
-    let upload = match get_file_upload_by_id(&state.db, &id) {
-        Ok(Some(upload)) => {
-            debug!(
-                upload_id = %id,
-                original_filename = %upload.original_filename,
-                stored_filename = %upload.stored_filename,
-                "Found file upload record"
-            );
-            upload
-        }
-        Ok(None) => {
-            warn!(upload_id = %id, "File upload not found");
-            return (StatusCode::NOT_FOUND, "File not found").into_response();
+    let password_hash = match get_admin_by_username(&state.db, &session.username) {
+        Ok(Some(admin)) => admin.password_hash,
+        Ok(None) => return Redirect::to("/login").into_response(),
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    };
+
+    // Verify the password off the async executor
+    if !verify_password_async(form.password, password_hash).await {
+        let error = Some(lang.t("password-error-incorrect"));
+        return two_factor_page(&state, lang, theme, session, None, error, None);
+    }
+
+    match update_admin_totp(&state.db, &session.admin_id, None) {
+        Ok(_) => {
+            info!(username = %session.username, "Two-factor authentication disabled");
+            let success = Some(lang.t("two-factor-disabled"));
+            two_factor_page(&state, lang, theme, session, None, None, success)
         }
         Err(e) => {
-            error!(upload_id = %id, error = %e, "Database error while fetching file upload");
-            return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
+            error!(username = %session.username, error = %e, "Failed to disable two-factor authentication");
+            let error = Some(lang.t("two-factor-error-save"));
+            two_factor_page(&state, lang, theme, session, None, error, None)
         }
+    }
+}
+
+/// Render the display preferences page for an admin
+fn data_requests_page(
+    state: &AppState,
+    lang: Lang,
+    theme: Theme,
+    session: Session,
+    error: Option<String>,
+    success: Option<String>,
+) -> Response {
+    DataRequestsTemplate {
+        lang,
+        theme,
+        is_owner: is_owner(state, &session.username),
+        username: session.username,
+        csrf_token: session.csrf_token,
+        error,
+        success,
+        signing_enabled: state.config.gdpr_report_key.is_some(),
+    }
+    .into_response()
+}
+
+/// Data subject requests: export or erase a person's data, check reports
+pub async fn data_requests(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
     };
 
-    // Construct file path
-    let file_path = upload.file_path(&state.upload_dir);
+    data_requests_page(&state, lang, theme, session, None, None)
+}
 
-    debug!(
-        upload_id = %id,
-        file_path = %file_path.display(),
-        "Attempting to serve file"
-    );
+/// Download a report of everything stored about a person
+pub async fn export_subject_data(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    Form(form): Form<DataSubjectForm>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
 
-    // Check if file exists
-    if !file_path.exists() {
-        warn!(
-            upload_id = %id,
-            file_path = %file_path.display(),
-            "File not found on disk"
-        );
-        return (StatusCode::NOT_FOUND, "File not found on disk").into_response();
+    let subject = form.subject.trim();
+    if subject.is_empty() {
+        let error = Some(lang.t("gdpr-error-subject"));
+        return data_requests_page(&state, lang, theme, session, error, None);
     }
 
-    // Read file content
-    let file_content = match fs::read(&file_path).await {
-        Ok(content) => {
-            info!(
-                upload_id = %id,
-                original_filename = %upload.original_filename,
-                file_size = content.len(),
-                "File read successfully"
-            );
-            content
+    match gdpr::export(&state, subject, &session.username) {
+        Ok(report) => report_download(&state, &report),
+        Err(e) => {
+            error!(error = %e, "Failed to export personal data");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
         }
+    }
+}
+
+/// Erase everything stored about a person and download the report (owners only)
+pub async fn erase_subject_data(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    Form(form): Form<DataSubjectForm>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    if !is_owner(&state, &session.username) {
+        warn!(username = %session.username, "Data erasure refused: not an owner");
+        let error = Some(lang.t("gdpr-error-not-owner"));
+        return data_requests_page(&state, lang, theme, session, error, None);
+    }
+
+    let subject = form.subject.trim();
+    if subject.is_empty() {
+        let error = Some(lang.t("gdpr-error-subject"));
+        return data_requests_page(&state, lang, theme, session, error, None);
+    }
+
+    match gdpr::erase(&state, subject, &session.username).await {
+        Ok(report) => report_download(&state, &report),
         Err(e) => {
-            error!(
-                upload_id = %id,
-                file_path = %file_path.display(),
-                error = %e,
-                "Failed to read file"
-            );
-            return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to read file").into_response();
+            error!(error = %e, "Failed to erase personal data");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
         }
+    }
+}
+
+/// Check that a report handed out earlier is unaltered
+pub async fn verify_report(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    Form(form): Form<VerifyReportForm>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
     };
 
-    // Create response with proper headers
-    let response = Response::builder()
-        .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, &upload.mime_type)
-        .header(
-            header::CONTENT_DISPOSITION,
-            format!("attachment; filename=\"{}\"", upload.original_filename),
+    let verification = serde_json::from_str::<gdpr::SignedReport>(&form.report)
+        .map(|signed| gdpr::verify(&state, &signed));
+    let (error, success) = match verification {
+        Ok(gdpr::Verification::Valid) => (None, Some(lang.t("gdpr-verify-valid"))),
+        Ok(gdpr::Verification::Invalid) => (Some(lang.t("gdpr-verify-invalid")), None),
+        Ok(gdpr::Verification::Unsigned) => (Some(lang.t("gdpr-verify-unsigned")), None),
+        Ok(gdpr::Verification::NoKey) => (Some(lang.t("gdpr-verify-no-key")), None),
+        Err(_) => (Some(lang.t("gdpr-verify-malformed")), None),
+    };
+
+    data_requests_page(&state, lang, theme, session, error, success)
+}
+
+/// A signed report as a JSON file download
+fn report_download(state: &AppState, report: &gdpr::Report) -> Response {
+    let signed = gdpr::sign(state, report);
+    let kind = match report.kind {
+        gdpr::ReportKind::Export => "export",
+        gdpr::ReportKind::Erasure => "erasure",
+    };
+
+    match serde_json::to_string_pretty(&signed) {
+        Ok(body) => (
+            [
+                (header::CONTENT_TYPE, "application/json".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"gdpr-{}-{}.json\"", kind, report.id),
+                ),
+            ],
+            body,
         )
-        .header(header::CONTENT_LENGTH, file_content.len())
-        .body(Body::from(file_content))
-        .unwrap();
+            .into_response(),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Failed to encode report").into_response(),
+    }
+}
+
+fn preferences_page(
+    state: &AppState,
+    lang: Lang,
+    theme: Theme,
+    session: Session,
+    error: Option<String>,
+    success: Option<String>,
+) -> Response {
+    let timezone = get_admin_by_username(&state.db, &session.username)
+        .ok()
+        .flatten()
+        .and_then(|admin| admin.timezone);
+    let preview = DisplayTime::for_timezone(&state.config, timezone.as_deref()).format(Utc::now());
 
-    response.into_response()
+    PreferencesTemplate {
+        lang,
+        theme,
+        username: session.username,
+        csrf_token: session.csrf_token,
+        error,
+        success,
+        timezone,
+        default_timezone: state.config.display_timezone.name(),
+        preview,
+    }
+    .into_response()
 }
 
-pub async fn delete_upload(
+pub async fn preferences_form(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
     State(state): State<AppState>,
-    Path(id): Path<String>,
 ) -> impl IntoResponse {
-    let upload = match get_file_upload_by_id(&state.db, &id) {
-        Ok(Some(upload)) => upload,
-        Ok(None) => return Redirect::to("/admin/uploads"),
-        Err(_) => return Redirect::to("/admin/uploads"),
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
     };
 
-    // Delete file from disk
-    let file_path = upload.file_path(&state.upload_dir);
-    if (fs::remove_file(&file_path).await).is_err() {
-        // File might already be deleted, continue with database deletion
-    }
+    preferences_page(&state, lang, theme, session, None, None)
+}
 
-    // Delete from database
-    match delete_file_upload(&state.db, &id) {
-        Ok(_) => Redirect::to("/admin/uploads"),
-        Err(_) => Redirect::to("/admin/uploads"), // Still redirect on error for now
+pub async fn handle_preferences(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+    Form(form): Form<PreferencesForm>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    let timezone = form.timezone.trim();
+    let timezone = if timezone.is_empty() {
+        None
+    } else {
+        match timezone.parse::<chrono_tz::Tz>() {
+            Ok(tz) => Some(tz.name()),
+            Err(_) => {
+                let error = lang.t_arg("preferences-error-timezone", "timezone", timezone);
+                return preferences_page(&state, lang, theme, session, Some(error), None);
+            }
+        }
+    };
+
+    match update_admin_timezone(&state.db, &session.username, timezone) {
+        Ok(_) => {
+            let success = lang.t("preferences-saved");
+            preferences_page(&state, lang, theme, session, None, Some(success))
+        }
+        Err(e) => {
+            error!(username = %session.username, error = %e, "Failed to save preferences");
+            let error = lang.t("preferences-error-save");
+            preferences_page(&state, lang, theme, session, Some(error), None)
+        }
     }
 }
 
-pub async fn change_password_form(headers: HeaderMap) -> impl IntoResponse {
+pub async fn admin_email_templates(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
     let session = match get_session_from_headers(&headers).await {
         Some(session) => session,
         None => return Redirect::to("/login").into_response(),
     };
 
-    ChangePasswordTemplate {
-        error: None,
-        success: None,
+    let mut entries = Vec::with_capacity(email_templates::KINDS.len());
+    for kind in email_templates::KINDS {
+        match get_email_template(&state.db, kind.key) {
+            Ok(custom) => entries.push((kind, custom)),
+            Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+        }
+    }
+
+    EmailTemplatesTemplate {
+        lang,
+        theme,
+        time: admin_display_time(&state, &session.username),
         username: session.username,
+        csrf_token: session.csrf_token,
+        entries,
     }
     .into_response()
 }
 
-pub async fn handle_change_password(
+/// Render the editor for one email, previewing `subject` and `body` with sample values
+#[allow(clippy::too_many_arguments)]
+fn email_template_page(
+    lang: Lang,
+    theme: Theme,
+    session: Session,
+    kind: &'static TemplateKind,
+    subject: String,
+    body: String,
+    customized: bool,
+    error: Option<String>,
+    success: Option<String>,
+) -> Response {
+    let samples = kind.sample_values();
+
+    EditEmailTemplateTemplate {
+        lang,
+        theme,
+        username: session.username,
+        csrf_token: session.csrf_token,
+        kind,
+        preview_subject: email_templates::substitute(&subject, &samples),
+        preview_body: email_templates::substitute(&body, &samples),
+        subject,
+        body,
+        customized,
+        error,
+        success,
+    }
+    .into_response()
+}
+
+pub async fn edit_email_template(
     headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    Path(key): Path<String>,
     State(state): State<AppState>,
-    Form(form): Form<ChangePasswordForm>,
 ) -> impl IntoResponse {
     let session = match get_session_from_headers(&headers).await {
         Some(session) => session,
         None => return Redirect::to("/login").into_response(),
     };
 
-    // Validate that new passwords match
-    if form.new_password != form.confirm_password {
-        return ChangePasswordTemplate {
-            error: Some("New passwords do not match".to_string()),
-            success: None,
-            username: session.username,
-        }
-        .into_response();
-    }
+    let Some(kind) = email_templates::kind(&key) else {
+        return (StatusCode::NOT_FOUND, "Email template not found").into_response();
+    };
 
-    // Validate password length
-    if form.new_password.len() < 6 {
-        return ChangePasswordTemplate {
-            error: Some("Password must be at least 6 characters long".to_string()),
-            success: None,
-            username: session.username.clone(),
-        }
-        .into_response();
+    match get_email_template(&state.db, kind.key) {
+        Ok(Some(custom)) => email_template_page(
+            lang,
+            theme,
+            session,
+            kind,
+            custom.subject,
+            custom.body,
+            true,
+            None,
+            None,
+        ),
+        Ok(None) => email_template_page(
+            lang,
+            theme,
+            session,
+            kind,
+            kind.default_subject.to_string(),
+            kind.default_body.to_string(),
+            false,
+            None,
+            None,
+        ),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
     }
+}
 
-    // Get current admin user (using session username)
-    match get_admin_by_username(&state.db, &session.username) {
-        Ok(Some(admin)) => {
-            // Verify current password
-            if !verify_password(&form.current_password, &admin.password_hash) {
-                return ChangePasswordTemplate {
-                    error: Some("Current password is incorrect".to_string()),
-                    success: None,
-                    username: session.username,
-                }
-                .into_response();
-            }
+pub async fn handle_email_template(
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+    Path(key): Path<String>,
+    State(state): State<AppState>,
+    Form(form): Form<EmailTemplateForm>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
 
-            // Hash new password
-            let new_hash = match bcrypt::hash(&form.new_password, bcrypt::DEFAULT_COST) {
-                Ok(hash) => hash,
-                Err(_) => {
-                    return ChangePasswordTemplate {
-                        error: Some("Failed to hash new password".to_string()),
-                        success: None,
-                        username: session.username,
-                    }
-                    .into_response();
-                }
-            };
+    let Some(kind) = email_templates::kind(&key) else {
+        return (StatusCode::NOT_FOUND, "Email template not found").into_response();
+    };
 
-            // Update password in database
-            match update_admin_password(&state.db, &session.username, &new_hash) {
-                Ok(_) => ChangePasswordTemplate {
-                    error: None,
-                    success: Some("Password changed successfully!".to_string()),
-                    username: session.username,
-                }
-                .into_response(),
-                Err(_) => ChangePasswordTemplate {
-                    error: Some("Failed to update password in database".to_string()),
-                    success: None,
-                    username: session.username,
-                }
-                .into_response(),
-            }
-        }
-        Ok(None) => ChangePasswordTemplate {
-            error: Some("Admin user not found".to_string()),
-            success: None,
-            username: session.username,
+    let customized = matches!(get_email_template(&state.db, kind.key), Ok(Some(_)));
+    // Browsers submit CRLF line endings; emails are composed with plain newlines
+    let subject = form.subject.trim().to_string();
+    let body = form.body.replace("\r\n", "\n");
+
+    if form.action == "preview" {
+        return email_template_page(
+            lang, theme, session, kind, subject, body, customized, None, None,
+        );
+    }
+
+    if subject.is_empty() || body.trim().is_empty() {
+        let error = lang.t("email-template-error-empty");
+        return email_template_page(
+            lang,
+            theme,
+            session,
+            kind,
+            subject,
+            body,
+            customized,
+            Some(error),
+            None,
+        );
+    }
+
+    match save_email_template(&state.db, kind.key, &subject, &body) {
+        Ok(_) => {
+            info!(template = kind.key, username = %session.username, "Email template updated");
+            let success = lang.t("email-template-saved");
+            email_template_page(
+                lang,
+                theme,
+                session,
+                kind,
+                subject,
+                body,
+                true,
+                None,
+                Some(success),
+            )
         }
-        .into_response(),
-        Err(_) => ChangePasswordTemplate {
-            error: Some("Database error".to_string()),
-            success: None,
-            username: session.username,
+        Err(e) => {
+            error!(template = kind.key, error = %e, "Failed to save email template");
+            let error = lang.t("email-template-error-save");
+            email_template_page(
+                lang,
+                theme,
+                session,
+                kind,
+                subject,
+                body,
+                customized,
+                Some(error),
+                None,
+            )
         }
-        .into_response(),
     }
 }
 
+pub async fn reset_email_template(
+    headers: HeaderMap,
+    Path(key): Path<String>,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    let session = match get_session_from_headers(&headers).await {
+        Some(session) => session,
+        None => return Redirect::to("/login").into_response(),
+    };
+
+    let Some(kind) = email_templates::kind(&key) else {
+        return (StatusCode::NOT_FOUND, "Email template not found").into_response();
+    };
+
+    if let Err(e) = delete_email_template(&state.db, kind.key) {
+        error!(template = kind.key, error = %e, "Failed to reset email template");
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
+    }
+    info!(template = kind.key, username = %session.username, "Email template reset to default");
+
+    Redirect::to(&format!("/admin/email-templates/{}", kind.key)).into_response()
+}
+
 pub async fn logout(headers: HeaderMap) -> impl IntoResponse {
     // Extract session ID from cookie header and remove it from server-side store
     if let Some(session_id) = headers
@@ -804,3 +4401,37 @@ pub async fn logout(headers: HeaderMap) -> impl IntoResponse {
 
     response
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{in_bounded_memory, sparse_file, GIB};
+
+    #[tokio::test]
+    #[ignore = "downloads 5 GiB; run with `cargo test -- --ignored`"]
+    async fn downloading_a_file_larger_than_memory_streams_it_from_disk() {
+        const SIZE: u64 = 5 * GIB;
+
+        let dir = tempfile::tempdir().unwrap();
+        let source = sparse_file(dir.path(), SIZE);
+        let (file, content_length) = encryption::open(None, &source, None).await.unwrap();
+
+        let response =
+            attachment_response(file, content_length, "big.bin", "application/zip", None);
+        assert_eq!(
+            response.headers()[header::CONTENT_LENGTH],
+            SIZE.to_string().as_str()
+        );
+
+        let mut body = response.into_body().into_data_stream();
+        let received = in_bounded_memory(async {
+            let mut received = 0u64;
+            while let Some(chunk) = body.next().await {
+                received += chunk.unwrap().len() as u64;
+            }
+            received
+        })
+        .await;
+        assert_eq!(received, SIZE);
+    }
+}
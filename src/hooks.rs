@@ -0,0 +1,110 @@
+//! # Upload Hooks
+//!
+//! Extension point for code embedding the server: an [`UploadHook`] is told
+//! about every file before it is stored, after it has been stored and before
+//! it is deleted, whichever channel it came through (upload form, resumable
+//! sessions, S3 API, email-in). Hooks can add their own validation by
+//! rejecting a file, or side effects such as indexing or notifying another
+//! system, without changes to the handlers.
+//!
+//! Hooks are registered on the [`UploadHooks`] held in
+//! [`AppState`](crate::AppState) when the state is built, and run in the
+//! order they were registered:
+//!
+//! ```ignore
+//! let hooks = UploadHooks::default().with(MyHook::new());
+//! ```
+//!
+//! Operators can add hooks without recompiling through WASM plugins (see
+//! `plugins.rs`, built with `--features wasm-plugins`).
+//!
+//! Hooks run on the request path with the database unlocked. Slow work
+//! (network calls, reading the whole file) belongs in a spawned task.
+
+use std::sync::Arc;
+
+use crate::models::{FileUpload, UploadLink, Uploader};
+
+/// A file about to be stored, as known before any data is written
+#[derive(Debug)]
+#[cfg_attr(not(feature = "wasm-plugins"), allow(dead_code))]
+pub struct PendingUpload<'a> {
+    pub link: &'a UploadLink,
+
+    /// Filename supplied by the sender
+    pub original_filename: &'a str,
+
+    /// MIME type supplied by the sender
+    pub content_type: &'a str,
+
+    /// Who is sending the file
+    pub uploader: &'a Uploader,
+}
+
+/// Reason a hook refused an operation; the message is shown to the sender or admin
+#[derive(Debug, Clone)]
+pub struct HookRejection(pub String);
+
+impl std::fmt::Display for HookRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Callbacks around the lifecycle of an upload
+///
+/// Every method has a default that does nothing, so a hook only implements
+/// what it needs.
+pub trait UploadHook: Send + Sync {
+    /// Called before a file is written; an error refuses the upload
+    fn before_store(&self, _upload: &PendingUpload) -> Result<(), HookRejection> {
+        Ok(())
+    }
+
+    /// Called once a file is on disk and recorded
+    fn after_store(&self, _link: &UploadLink, _upload: &FileUpload) {}
+
+    /// Called before an upload is deleted; an error keeps the upload
+    ///
+    /// Not called for files removed as part of an erasure request, which
+    /// must always succeed (see [`crate::gdpr`]).
+    fn before_delete(&self, _upload: &FileUpload) -> Result<(), HookRejection> {
+        Ok(())
+    }
+}
+
+/// The hooks registered with the server, cheap to clone into the state
+#[derive(Clone, Default)]
+pub struct UploadHooks(Arc<Vec<Arc<dyn UploadHook>>>);
+
+impl UploadHooks {
+    /// Add a hook, run after the ones registered before it
+    #[cfg_attr(not(feature = "wasm-plugins"), allow(dead_code))]
+    pub fn with(self, hook: impl UploadHook + 'static) -> Self {
+        let mut hooks = Arc::unwrap_or_clone(self.0);
+        hooks.push(Arc::new(hook));
+        UploadHooks(Arc::new(hooks))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Ask every hook about a new file; the first rejection wins
+    pub fn before_store(&self, upload: &PendingUpload) -> Result<(), HookRejection> {
+        self.0.iter().try_for_each(|hook| hook.before_store(upload))
+    }
+
+    pub fn after_store(&self, link: &UploadLink, upload: &FileUpload) {
+        for hook in self.0.iter() {
+            hook.after_store(link, upload);
+        }
+    }
+
+    /// Ask every hook about a deletion; the first rejection wins
+    pub fn before_delete(&self, upload: &FileUpload) -> Result<(), HookRejection> {
+        self.0
+            .iter()
+            .try_for_each(|hook| hook.before_delete(upload))
+    }
+}
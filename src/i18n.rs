@@ -0,0 +1,207 @@
+//! # Internationalization
+//!
+//! Translates the guest upload pages and the admin UI. Message catalogs are
+//! Fluent files in `locales/<lang>/main.ftl`, compiled into the binary at
+//! build time; any key missing from a catalog falls back to English.
+//!
+//! ## Language Selection
+//! 1. A language forced on the upload link (guest upload page only)
+//! 2. The browser's `Accept-Language` header, honouring q-values
+//! 3. English
+//!
+//! ## Adding a Language
+//! Copy `locales/en/main.ftl` to `locales/<code>/main.ftl`, translate it and
+//! add a variant to [`Lang`].
+
+use axum::{extract::FromRequestParts, http::header::ACCEPT_LANGUAGE, http::request::Parts};
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource, FluentValue};
+use std::{borrow::Borrow, convert::Infallible, fmt::Display};
+use unic_langid::LanguageIdentifier;
+
+use crate::models::UploadLink;
+
+/// Message catalogs, compiled into the binary
+const CATALOGS: [(Lang, &str); 3] = [
+    (Lang::En, include_str!("../locales/en/main.ftl")),
+    (Lang::De, include_str!("../locales/de/main.ftl")),
+    (Lang::Fr, include_str!("../locales/fr/main.ftl")),
+];
+
+lazy_static::lazy_static! {
+    /// One bundle per language, in the order of [`Lang::ALL`]
+    static ref BUNDLES: Vec<FluentBundle<FluentResource>> = CATALOGS
+        .iter()
+        .map(|(lang, source)| bundle(*lang, source))
+        .collect();
+}
+
+/// Parse a catalog into a bundle; a broken catalog is a build mistake, so it panics
+fn bundle(lang: Lang, source: &str) -> FluentBundle<FluentResource> {
+    let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(|(_, errors)| {
+        panic!(
+            "invalid catalog locales/{}/main.ftl: {:?}",
+            lang.code(),
+            errors
+        )
+    });
+    let identifier: LanguageIdentifier = lang.code().parse().expect("valid language identifier");
+
+    let mut bundle = FluentBundle::new_concurrent(vec![identifier]);
+    // Unicode isolation marks around arguments end up verbatim in
+    // HTML attributes and plain-text messages
+    bundle.set_use_isolating(false);
+    bundle.add_resource(resource).unwrap_or_else(|errors| {
+        panic!(
+            "duplicate messages in locales/{}/main.ftl: {:?}",
+            lang.code(),
+            errors
+        )
+    });
+    bundle
+}
+
+/// Format a message from one language's bundle (None = the catalog lacks it)
+fn format(lang: Lang, key: &str, args: Option<&FluentArgs>) -> Option<String> {
+    let bundle = &BUNDLES[lang as usize];
+    let pattern = bundle.get_message(key)?.value()?;
+    let mut errors = Vec::new();
+    Some(
+        bundle
+            .format_pattern(pattern, args, &mut errors)
+            .into_owned(),
+    )
+}
+
+/// A supported UI language
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    #[default]
+    En,
+    De,
+    Fr,
+}
+
+impl Lang {
+    /// All supported languages, in the order they are offered in forms
+    pub const ALL: [Lang; 3] = [Lang::En, Lang::De, Lang::Fr];
+
+    /// ISO 639-1 code, used for `<html lang>` and stored on links
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::De => "de",
+            Lang::Fr => "fr",
+        }
+    }
+
+    /// Name of the language in the language itself
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Lang::En => "English",
+            Lang::De => "Deutsch",
+            Lang::Fr => "Français",
+        }
+    }
+
+    /// Parse a language tag such as `de` or `de-AT`; unknown languages yield `None`
+    pub fn from_code(code: &str) -> Option<Lang> {
+        let primary = code.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+        Lang::ALL.into_iter().find(|lang| lang.code() == primary)
+    }
+
+    /// Pick the best supported language from an `Accept-Language` header
+    pub fn negotiate(accept_language: &str) -> Lang {
+        let mut best: Option<(Lang, f32)> = None;
+
+        for entry in accept_language.split(',') {
+            let mut parts = entry.split(';');
+            let tag = parts.next().unwrap_or_default();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            if quality <= 0.0 {
+                continue;
+            }
+            if let Some(lang) = Lang::from_code(tag) {
+                // Header order breaks ties, so only a strictly better q wins
+                if best.is_none_or(|(_, best_quality)| quality > best_quality) {
+                    best = Some((lang, quality));
+                }
+            }
+        }
+
+        best.map(|(lang, _)| lang).unwrap_or_default()
+    }
+
+    /// The language to render a link's upload page in
+    pub fn for_link(self, link: &UploadLink) -> Lang {
+        link.language
+            .as_deref()
+            .and_then(Lang::from_code)
+            .unwrap_or(self)
+    }
+
+    /// Translate a message, falling back to English and then to the key itself
+    fn lookup(self, key: &str, args: Option<&FluentArgs>) -> String {
+        format(self, key, args)
+            .or_else(|| format(Lang::En, key, args))
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    /// Translate a message without arguments
+    pub fn t(self, key: &str) -> String {
+        self.lookup(key, None)
+    }
+
+    /// Translate a message with a single argument
+    pub fn t_arg(self, key: &str, name: &'static str, value: impl Display) -> String {
+        self.t_args(key, &[(name, value.to_string())])
+    }
+
+    /// Translate a message whose wording depends on a count (`$count`)
+    pub fn t_count(self, key: &str, count: impl Borrow<i64>) -> String {
+        self.t_count_args(key, count, &[])
+    }
+
+    /// Translate a message with a count (`$count`) and further string arguments
+    pub fn t_count_args(
+        self,
+        key: &str,
+        count: impl Borrow<i64>,
+        values: &[(&'static str, String)],
+    ) -> String {
+        let mut args = fluent_args(values);
+        // Passed as a number so plural variants (`[one]`, `*[other]`) are selected
+        args.set("count", FluentValue::from(*count.borrow()));
+        self.lookup(key, Some(&args))
+    }
+
+    /// Translate a message with several string arguments
+    pub fn t_args(self, key: &str, values: &[(&'static str, String)]) -> String {
+        self.lookup(key, Some(&fluent_args(values)))
+    }
+}
+
+fn fluent_args<'a>(values: &'a [(&'static str, String)]) -> FluentArgs<'a> {
+    let mut args = FluentArgs::with_capacity(values.len());
+    for (name, value) in values {
+        args.set(*name, FluentValue::from(value.as_str()));
+    }
+    args
+}
+
+/// Negotiates the request language from `Accept-Language`
+impl<S: Send + Sync> FromRequestParts<S> for Lang {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .headers
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .map(Lang::negotiate)
+            .unwrap_or_default())
+    }
+}
@@ -0,0 +1,315 @@
+//! # IMAP Client
+//!
+//! Just enough IMAP4rev1 (RFC 3501) for the email-in gateway (see
+//! [`crate::emailin`]): log in, select a folder, find unseen messages, fetch
+//! one whole and flag it as seen. Commands are sent one at a time and their
+//! responses read up to the tagged completion; literals (`{n}`) in untagged
+//! responses are collected, which is where fetched messages arrive.
+
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+/// Longest response line accepted, not counting literals
+const MAX_LINE_BYTES: usize = 64 * 1024;
+
+/// An untagged response (`* ...`) with the literals it carried
+#[derive(Debug, Default)]
+struct Untagged {
+    /// The response text, with each literal left out
+    text: String,
+    literals: Vec<Vec<u8>>,
+}
+
+/// A logged-in (or about to be) connection to an IMAP server
+pub struct Session<S> {
+    stream: BufReader<S>,
+    next_tag: u32,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Session<S> {
+    /// Start a session on a connected stream, reading the server greeting
+    pub async fn start(stream: S) -> Result<Self, String> {
+        let mut session = Session {
+            stream: BufReader::new(stream),
+            next_tag: 1,
+        };
+        let (greeting, _) = session.read_line().await?;
+        if !greeting.starts_with("* OK") && !greeting.starts_with("* PREAUTH") {
+            return Err(format!("unexpected greeting: {}", greeting.trim_end()));
+        }
+        Ok(session)
+    }
+
+    pub async fn login(&mut self, username: &str, password: &str) -> Result<(), String> {
+        let command = format!("LOGIN {} {}", quote(username)?, quote(password)?);
+        self.command(&command).await.map(|_| ())
+    }
+
+    pub async fn select(&mut self, folder: &str) -> Result<(), String> {
+        self.command(&format!("SELECT {}", quote(folder)?))
+            .await
+            .map(|_| ())
+    }
+
+    /// UIDs of the messages in the selected folder not flagged as seen
+    pub async fn uid_search_unseen(&mut self) -> Result<Vec<u32>, String> {
+        let responses = self.command("UID SEARCH UNSEEN").await?;
+        Ok(responses
+            .iter()
+            .filter_map(|response| response.text.strip_prefix("SEARCH"))
+            .flat_map(|uids| uids.split_whitespace().filter_map(|uid| uid.parse().ok()))
+            .collect())
+    }
+
+    /// The whole raw message with this UID, without flagging it as seen
+    ///
+    /// None when the message is gone.
+    pub async fn uid_fetch_message(&mut self, uid: u32) -> Result<Option<Vec<u8>>, String> {
+        let responses = self
+            .command(&format!("UID FETCH {} BODY.PEEK[]", uid))
+            .await?;
+        Ok(responses
+            .into_iter()
+            .filter(|response| response.text.contains("FETCH"))
+            .find_map(|response| response.literals.into_iter().next()))
+    }
+
+    pub async fn uid_mark_seen(&mut self, uid: u32) -> Result<(), String> {
+        self.command(&format!("UID STORE {} +FLAGS.SILENT (\\Seen)", uid))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn logout(&mut self) -> Result<(), String> {
+        self.command("LOGOUT").await.map(|_| ())
+    }
+
+    /// Send a command and collect its untagged responses until it completes
+    ///
+    /// A `NO` or `BAD` completion is returned as an error with the server's text.
+    async fn command(&mut self, command: &str) -> Result<Vec<Untagged>, String> {
+        let tag = format!("A{}", self.next_tag);
+        self.next_tag += 1;
+
+        let stream = self.stream.get_mut();
+        stream
+            .write_all(format!("{} {}\r\n", tag, command).as_bytes())
+            .await
+            .map_err(|e| format!("failed to send command: {}", e))?;
+        stream
+            .flush()
+            .await
+            .map_err(|e| format!("failed to send command: {}", e))?;
+
+        let mut responses = Vec::new();
+        loop {
+            let (line, literals) = self.read_line().await?;
+            if let Some(untagged) = line.strip_prefix("* ") {
+                responses.push(Untagged {
+                    text: untagged.trim_end().to_string(),
+                    literals,
+                });
+                continue;
+            }
+            let Some(status) = line
+                .strip_prefix(tag.as_str())
+                .and_then(|rest| rest.strip_prefix(' '))
+            else {
+                // Continuation requests aren't expected for these commands
+                continue;
+            };
+            if status.starts_with("OK") {
+                return Ok(responses);
+            }
+            let verb = command.split(' ').next().unwrap_or(command);
+            return Err(format!("{} failed: {}", verb, status.trim_end()));
+        }
+    }
+
+    /// Read one response line, following literals to where the line really ends
+    async fn read_line(&mut self) -> Result<(String, Vec<Vec<u8>>), String> {
+        let mut text = String::new();
+        let mut literals = Vec::new();
+
+        loop {
+            let mut line = Vec::new();
+            let read = (&mut self.stream)
+                .take(MAX_LINE_BYTES as u64)
+                .read_until(b'\n', &mut line)
+                .await
+                .map_err(|e| format!("failed to read response: {}", e))?;
+            if read == 0 {
+                return Err("connection closed by server".to_string());
+            }
+            if !line.ends_with(b"\n") {
+                return Err("response line too long".to_string());
+            }
+
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\r', '\n']);
+            match literal_len(line) {
+                Some((before, len)) => {
+                    text.push_str(before);
+                    let mut literal = vec![0u8; len];
+                    self.stream
+                        .read_exact(&mut literal)
+                        .await
+                        .map_err(|e| format!("failed to read response: {}", e))?;
+                    literals.push(literal);
+                }
+                None => {
+                    text.push_str(line);
+                    return Ok((text, literals));
+                }
+            }
+        }
+    }
+}
+
+/// Split a line ending in a literal announcement (`... {123}`) into the text
+/// before it and the literal's length
+fn literal_len(line: &str) -> Option<(&str, usize)> {
+    let open = line.strip_suffix('}')?.rfind('{')?;
+    let len = line[open + 1..line.len() - 1].parse().ok()?;
+    Some((&line[..open], len))
+}
+
+/// Quote a command argument as an IMAP quoted string
+///
+/// Line breaks can't be quoted; such arguments are refused rather than sent
+/// as a literal.
+fn quote(value: &str) -> Result<String, String> {
+    if value.contains(['\r', '\n', '\0']) {
+        return Err("line breaks are not allowed in IMAP arguments".to_string());
+    }
+    Ok(format!(
+        "\"{}\"",
+        value.replace('\\', "\\\\").replace('"', "\\\"")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{duplex, DuplexStream};
+
+    /// A server that answers each command line with the next scripted reply
+    ///
+    /// Replies may use `{tag}` for the tag of the command they answer.
+    /// Returns the command lines it received.
+    fn fake_server(
+        stream: DuplexStream,
+        greeting: &'static str,
+        replies: Vec<String>,
+    ) -> tokio::task::JoinHandle<Vec<String>> {
+        tokio::spawn(async move {
+            let mut stream = BufReader::new(stream);
+            stream
+                .get_mut()
+                .write_all(greeting.as_bytes())
+                .await
+                .unwrap();
+            let mut received = Vec::new();
+            for reply in replies {
+                let mut line = String::new();
+                if stream.read_line(&mut line).await.unwrap() == 0 {
+                    break;
+                }
+                let (tag, command) = line.trim_end().split_once(' ').unwrap();
+                received.push(command.to_string());
+                let reply = reply.replace("{tag}", tag);
+                stream.get_mut().write_all(reply.as_bytes()).await.unwrap();
+            }
+            received
+        })
+    }
+
+    #[tokio::test]
+    async fn fetches_unseen_messages() {
+        let (client, server) = duplex(64 * 1024);
+        let message = "Subject: Hi\r\n\r\nA line with {3}\r\nand more\r\n";
+        let server = fake_server(
+            server,
+            "* OK IMAP4rev1 ready\r\n",
+            vec![
+                "{tag} OK LOGIN completed\r\n".to_string(),
+                "* 3 EXISTS\r\n* FLAGS (\\Seen)\r\n{tag} OK [READ-WRITE] SELECT completed\r\n"
+                    .to_string(),
+                "* SEARCH 4 17\r\n{tag} OK SEARCH completed\r\n".to_string(),
+                format!(
+                    "* 2 FETCH (UID 17 BODY[] {{{}}}\r\n{})\r\n{{tag}} OK FETCH completed\r\n",
+                    message.len(),
+                    message
+                ),
+                "{tag} OK STORE completed\r\n".to_string(),
+                "* BYE logging out\r\n{tag} OK LOGOUT completed\r\n".to_string(),
+            ],
+        );
+
+        let mut session = Session::start(client).await.unwrap();
+        session
+            .login("drop@example.com", "pa\"ss\\word")
+            .await
+            .unwrap();
+        session.select("INBOX").await.unwrap();
+        assert_eq!(session.uid_search_unseen().await.unwrap(), vec![4, 17]);
+        assert_eq!(
+            session.uid_fetch_message(17).await.unwrap().as_deref(),
+            Some(message.as_bytes())
+        );
+        session.uid_mark_seen(17).await.unwrap();
+        session.logout().await.unwrap();
+
+        assert_eq!(
+            server.await.unwrap(),
+            vec![
+                r#"LOGIN "drop@example.com" "pa\"ss\\word""#,
+                r#"SELECT "INBOX""#,
+                "UID SEARCH UNSEEN",
+                "UID FETCH 17 BODY.PEEK[]",
+                "UID STORE 17 +FLAGS.SILENT (\\Seen)",
+                "LOGOUT",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_refused_commands() {
+        let (client, server) = duplex(4096);
+        let _server = fake_server(
+            server,
+            "* OK ready\r\n",
+            vec!["{tag} NO [AUTHENTICATIONFAILED] Invalid credentials\r\n".to_string()],
+        );
+
+        let mut session = Session::start(client).await.unwrap();
+        let error = session.login("drop", "wrong").await.unwrap_err();
+        assert_eq!(
+            error,
+            "LOGIN failed: NO [AUTHENTICATIONFAILED] Invalid credentials"
+        );
+    }
+
+    #[tokio::test]
+    async fn refuses_unexpected_greeting() {
+        let (client, server) = duplex(4096);
+        let _server = fake_server(server, "* BYE too busy\r\n", Vec::new());
+        assert!(Session::start(client).await.is_err());
+    }
+
+    #[test]
+    fn finds_literal_announcements() {
+        assert_eq!(
+            literal_len("* 1 FETCH (BODY[] {42}"),
+            Some(("* 1 FETCH (BODY[] ", 42))
+        );
+        assert_eq!(literal_len("* OK {not a number}"), None);
+        assert_eq!(literal_len("A1 OK done"), None);
+    }
+
+    #[test]
+    fn quotes_arguments() {
+        assert_eq!(quote("INBOX").unwrap(), "\"INBOX\"");
+        assert_eq!(quote(r#"a"b\c"#).unwrap(), r#""a\"b\\c""#);
+        assert!(quote("evil\r\nA2 DELETE INBOX").is_err());
+    }
+}
@@ -0,0 +1,1207 @@
+//! # Upload Ingestion
+//!
+//! Stores files from every channel (the upload form, resumable and tus
+//! uploads, email, the S3 facade and the API) through one pipeline,
+//! [`store_upload`]: the file lands in its storage directory (see
+//! [`crate::storage`]) under a UUID name, the link's quotas and bandwidth
+//! limit are enforced while the data is written, and the usual post-upload
+//! tasks run.
+//!
+//! ## Filename Collisions
+//! A file uploaded under a name that already exists on the link is handled
+//! according to the link's [`CollisionPolicy`]: kept as the next version,
+//! stored in place of the latest version, or refused. Resumable uploads
+//! check with [`check_collision`] before any data arrives.
+//!
+//! ## Guest Folders
+//! Files from one guest on one link share a folder, so the uploads tree can
+//! be browsed by person. The upload page hands each visitor a session cookie
+//! (`drop_guest`) scoped to the link's upload URLs whose value names their
+//! folder; [`guest_folder_for`] turns it back into a folder for each upload.
+//! Clients without the cookie get a new folder per file, as before.
+//!
+//! ## Duplicate Uploads
+//! Every file is hashed (SHA-256) as it is written. A file whose contents
+//! the link already has is handled as set by `DUPLICATE_UPLOADS`: `warn`
+//! stores it and tells the guest it looks like a duplicate, `skip` discards
+//! it without using any quota, and `accept` stores it silently.
+//!
+//! ## Shared Storage
+//! With `DEDUPLICATE_STORAGE` on (the default), a stored file whose contents
+//! any earlier upload already has, on whatever link, is dropped again and the
+//! new upload recorded against the existing copy ([`reuse_stored_copy`]). The
+//! copy is only removed from disk once no upload refers to it any more (see
+//! [`crate::tasks::remove_upload_file`]). Quotas still count every upload.
+//!
+//! ## Refused Uploads
+//! Files turned away for a reason the sender can act on (link expired, too
+//! large, duplicate, infected, refused by a hook or rule) are recorded with the
+//! reason and client address via [`record_failed_attempt`], and listed on
+//! the link's attempts page so an admin can tell a guest what went wrong.
+//! Storage and database failures are not recorded; they are logged.
+//!
+//! ## Uploader Identity
+//! Each upload records the client address it came from ([`ClientIp`]) and,
+//! for emailed files, the sender, so a person's data can be found again on
+//! request (see [`crate::gdpr`]). The `User-Agent` of the request and a
+//! name the guest may give on the upload form are kept for provenance. Behind a reverse proxy, set
+//! `TRUST_PROXY_HEADERS=true` so the address is taken from `X-Forwarded-For`,
+//! and `TRUSTED_PROXY_HOPS` to the number of proxies in front of the server.
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header, request::Parts, HeaderMap},
+};
+use chrono::{Duration, Utc};
+use ring::digest;
+use std::{convert::Infallible, net::SocketAddr};
+use tokio::{
+    fs,
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    sync::{OwnedMutexGuard, Semaphore},
+};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    antivirus::{self, ScanOutcome},
+    database::*,
+    encryption::{self, Keys},
+    events, filetypes,
+    hooks::{HookRejection, PendingUpload},
+    models::{CollisionPolicy, FileUpload, NewFileUpload, Reservation, UploadLink, Uploader},
+    request_id::RequestId,
+    rules::{RuleInput, RuleRejection},
+    storage,
+    tasks::{enqueue, enqueue_post_upload_tasks, remove_upload_file, Task},
+    throttle::TokenBucket,
+    AppState,
+};
+
+/// Size of the buffer used when copying incoming data to disk
+const COPY_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Cookie naming the folder a guest's uploads on a link are stored in
+const GUEST_COOKIE: &str = "drop_guest";
+
+/// Reasons storing an incoming file can fail
+#[derive(Debug)]
+pub enum IngestError {
+    /// The link is expired or inactive
+    LinkUnavailable,
+
+    /// The link accepts no more files
+    FileLimitReached,
+
+    /// The file does not fit in the link's remaining quota or per-file limit
+    QuotaExceeded,
+
+    /// The file does not fit in what is left of the guest's own quota; holds
+    /// the bytes that were left
+    GuestQuotaExceeded(u64),
+
+    /// The file does not fit in what the link may still receive in 24 hours;
+    /// holds the bytes that were left
+    DailyQuotaExceeded(u64),
+
+    /// A file with the same name exists and the link rejects duplicates
+    Duplicate,
+
+    /// The link doesn't accept files of this type (see [`crate::filetypes`])
+    FileType,
+
+    /// The file's contents don't match the type its name or declared MIME type promises
+    ContentMismatch,
+
+    /// The virus scanner found malware; holds the signature name
+    Infected(String),
+
+    /// The virus scanner is enabled but could not scan the file
+    ScanFailed(String),
+
+    /// The incoming data broke off, e.g. the sender's connection dropped
+    Interrupted(std::io::Error),
+
+    /// Writing the file to disk failed
+    Io(std::io::Error),
+
+    /// Recording the upload failed
+    Database(String),
+
+    /// An upload hook refused the file
+    Rejected(HookRejection),
+
+    /// The upload rules refused the file, with the script's reason if it gave one
+    RulesRefused(Option<String>),
+
+    /// The link already has a file with the same contents and skips duplicates;
+    /// holds the existing upload
+    SameContent(Box<FileUpload>),
+}
+
+impl std::fmt::Display for IngestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IngestError::LinkUnavailable => write!(f, "upload link has expired or is inactive"),
+            IngestError::FileLimitReached => write!(f, "upload link accepts no more files"),
+            IngestError::QuotaExceeded => write!(f, "file exceeds the link's remaining quota"),
+            IngestError::GuestQuotaExceeded(_) => {
+                write!(f, "file exceeds what is left of the guest's quota")
+            }
+            IngestError::DailyQuotaExceeded(_) => {
+                write!(f, "file exceeds what the link may still receive today")
+            }
+            IngestError::Duplicate => write!(f, "a file with this name was already uploaded"),
+            IngestError::FileType => write!(f, "the link does not accept files of this type"),
+            IngestError::ContentMismatch => {
+                write!(f, "the file's contents don't match its file type")
+            }
+            IngestError::Infected(signature) => {
+                write!(f, "the virus scanner found {} in the file", signature)
+            }
+            IngestError::ScanFailed(e) => write!(f, "virus scan failed: {}", e),
+            IngestError::Interrupted(e) => write!(f, "upload interrupted: {}", e),
+            IngestError::Io(e) => write!(f, "I/O error: {}", e),
+            IngestError::Database(e) => write!(f, "database error: {}", e),
+            IngestError::Rejected(reason) => write!(f, "upload refused: {}", reason),
+            IngestError::RulesRefused(Some(reason)) => write!(f, "upload refused: {}", reason),
+            IngestError::RulesRefused(None) => write!(f, "refused by the upload rules"),
+            IngestError::SameContent(existing) => write!(
+                f,
+                "the same file was already uploaded as {}",
+                existing.original_filename
+            ),
+        }
+    }
+}
+
+/// A file stored by [`store_upload`]
+#[derive(Debug)]
+pub struct StoredUpload {
+    /// ID of the new upload record
+    pub id: String,
+
+    pub file_size: i64,
+
+    /// Whether the file took the place of the latest version with its name
+    pub replaced: bool,
+
+    /// Name of an earlier upload on the link with the same contents, when
+    /// `DUPLICATE_UPLOADS` keeps such files anyway
+    pub same_content_as: Option<String>,
+}
+
+/// Store a file on a link and record it as an upload
+///
+/// Every channel stores files through here, the upload form included, so
+/// they all apply the same checks: file type, filename collisions, hooks,
+/// the link's, guest's and daily quotas, the link's bandwidth limit, file
+/// contents, the virus scanner, the upload rules and duplicate contents.
+///
+/// # Arguments
+/// * `state` - Application state
+/// * `link` - Link the file is uploaded to
+/// * `original_filename` - Filename supplied by the sender
+/// * `content_type` - MIME type supplied by the sender
+/// * `guest_folder` - Folder shared with the sender's other files (None = a new folder)
+/// * `uploader` - Who sent the file
+/// * `reader` - File contents; read in chunks so large files are never buffered
+///
+/// # Returns
+/// The new upload. A refused file is recorded as a failed attempt.
+pub async fn store_upload<R>(
+    state: &AppState,
+    link: &UploadLink,
+    original_filename: &str,
+    content_type: &str,
+    guest_folder: Option<&str>,
+    uploader: &Uploader,
+    reader: R,
+) -> Result<StoredUpload, IngestError>
+where
+    R: AsyncRead + Unpin,
+{
+    let result = store_file(
+        state,
+        link,
+        original_filename,
+        content_type,
+        guest_folder,
+        uploader,
+        reader,
+    )
+    .await;
+
+    // Storage and database failures aren't the sender's doing
+    let refused = match &result {
+        Err(IngestError::LinkUnavailable | IngestError::FileLimitReached) => {
+            Some(("link_unavailable", None))
+        }
+        Err(IngestError::QuotaExceeded) => Some(("too_large", None)),
+        Err(IngestError::GuestQuotaExceeded(_)) => Some(("guest_quota", None)),
+        Err(IngestError::DailyQuotaExceeded(_)) => Some(("daily_quota", None)),
+        Err(IngestError::Interrupted(e)) => Some(("interrupted", Some(e.to_string()))),
+        Err(IngestError::Duplicate) => Some(("duplicate", None)),
+        Err(IngestError::FileType) => Some(("file_type", None)),
+        Err(IngestError::ContentMismatch) => Some(("file_content", None)),
+        Err(IngestError::Infected(signature)) => Some(("infected", Some(signature.clone()))),
+        Err(IngestError::Rejected(reason)) => Some(("refused", Some(reason.to_string()))),
+        Err(IngestError::RulesRefused(reason)) => Some(("refused", reason.clone())),
+        Err(IngestError::SameContent(existing)) => {
+            Some(("same_content", Some(existing.original_filename.clone())))
+        }
+        _ => None,
+    };
+    if let Some((reason, detail)) = refused {
+        record_failed_attempt(
+            state,
+            link,
+            Some(original_filename),
+            reason,
+            detail.as_deref(),
+            uploader,
+        );
+    }
+    result
+}
+
+async fn store_file<R>(
+    state: &AppState,
+    link: &UploadLink,
+    original_filename: &str,
+    content_type: &str,
+    guest_folder: Option<&str>,
+    uploader: &Uploader,
+    mut reader: R,
+) -> Result<StoredUpload, IngestError>
+where
+    R: AsyncRead + Unpin,
+{
+    if !link.is_valid() {
+        return Err(
+            if !link.is_active || link.is_archived() || link.is_expired() {
+                IngestError::LinkUnavailable
+            } else if link.remaining_quota <= 0 {
+                IngestError::QuotaExceeded
+            } else {
+                IngestError::FileLimitReached
+            },
+        );
+    }
+    if !filetypes::name_allowed(link, original_filename) {
+        return Err(IngestError::FileType);
+    }
+    let replaced = check_collision(state, link, original_filename)?;
+    state
+        .hooks
+        .before_store(&PendingUpload {
+            link,
+            original_filename,
+            content_type,
+            uploader,
+        })
+        .map_err(IngestError::Rejected)?;
+
+    // A guest without a folder yet has uploaded nothing under it
+    let link_max_bytes = link.max_file_bytes() as u64;
+    let guest_left = guest_allowance(state, link, guest_folder, uploader)?
+        .filter(|allowance| *allowance < link_max_bytes);
+    let daily_left = daily_allowance(state, link)?
+        .filter(|allowance| *allowance < guest_left.unwrap_or(link_max_bytes));
+    let max_bytes = daily_left.or(guest_left).unwrap_or(link_max_bytes);
+
+    // A file too large ran into the tightest of the limits
+    let too_large = || match (daily_left, guest_left) {
+        (Some(left), _) => IngestError::DailyQuotaExceeded(left),
+        (None, Some(left)) => IngestError::GuestQuotaExceeded(left),
+        (None, None) => IngestError::QuotaExceeded,
+    };
+    if max_bytes == 0 && (daily_left.is_some() || guest_left.is_some()) {
+        return Err(too_large());
+    }
+
+    let guest_folder = guest_folder
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let storage_dir = storage::storage_dir(&state.config, &link.id, &guest_folder);
+    let volume = storage::choose_volume(state);
+    let guest_dir = volume.join(&storage_dir);
+    fs::create_dir_all(&guest_dir)
+        .await
+        .map_err(IngestError::Io)?;
+
+    let stored_filename = storage::stored_filename(original_filename);
+    let file_path = guest_dir.join(&stored_filename);
+
+    let mut hasher = ContentHasher::new();
+    let copy_result = copy_with_limit(
+        &state.disk_write_limiter,
+        &mut reader,
+        &file_path,
+        max_bytes,
+        TokenBucket::for_link(link).as_ref(),
+        &mut hasher,
+    )
+    .await;
+
+    let file_size = match copy_result {
+        Ok(file_size) => file_size as i64,
+        Err(e) => {
+            // Never leave partial files behind
+            let _ = fs::remove_file(&file_path).await;
+            storage::remove_empty_dirs(&volume, &storage_dir).await;
+            return Err(match e {
+                IngestError::QuotaExceeded => too_large(),
+                e => e,
+            });
+        }
+    };
+    if !link.can_accept_file(file_size) {
+        let _ = fs::remove_file(&file_path).await;
+        storage::remove_empty_dirs(&volume, &storage_dir).await;
+        return Err(IngestError::QuotaExceeded);
+    }
+
+    let sniffed = match filetypes::sniff_file(&file_path).await {
+        Ok(sniffed) => sniffed,
+        Err(e) => {
+            let _ = fs::remove_file(&file_path).await;
+            storage::remove_empty_dirs(&volume, &storage_dir).await;
+            return Err(IngestError::Io(e));
+        }
+    };
+    if !content_matches(state, link, original_filename, content_type, &sniffed) {
+        let _ = fs::remove_file(&file_path).await;
+        storage::remove_empty_dirs(&volume, &storage_dir).await;
+        return Err(IngestError::ContentMismatch);
+    }
+
+    let scan_result = match scan_for_viruses(state, link, original_filename, &file_path).await {
+        Ok(scan_result) => scan_result,
+        Err(e) => {
+            let _ = fs::remove_file(&file_path).await;
+            storage::remove_empty_dirs(&volume, &storage_dir).await;
+            return Err(e);
+        }
+    };
+
+    let rules_result = check_upload_rules(
+        state,
+        link,
+        original_filename,
+        content_type,
+        file_size,
+        uploader,
+    );
+    if let Err(rejection) = rules_result {
+        let _ = fs::remove_file(&file_path).await;
+        storage::remove_empty_dirs(&volume, &storage_dir).await;
+        let reason = match rejection {
+            RuleRejection::Refused(reason) => reason,
+            RuleRejection::Failed => None,
+        };
+        return Err(IngestError::RulesRefused(reason));
+    }
+
+    let content_hash = hasher.finish();
+    let duplicate = find_duplicate(state, link, &content_hash, replaced.as_ref());
+    if let Some(existing) = duplicate.as_ref() {
+        if state.config.duplicate_uploads == "skip" {
+            let _ = fs::remove_file(&file_path).await;
+            storage::remove_empty_dirs(&volume, &storage_dir).await;
+            return Err(IngestError::SameContent(Box::new(existing.clone())));
+        }
+    }
+
+    let shared_copy = reuse_stored_copy(state, &content_hash, file_size, &file_path).await;
+    let shared = shared_copy.is_some();
+    if shared {
+        storage::remove_empty_dirs(&volume, &storage_dir).await;
+    }
+    let (volume, storage_dir, stored_filename) = match &shared_copy {
+        Some(copy) => (
+            copy.upload.volume_root(&state.upload_dir),
+            copy.upload.storage_dir.clone(),
+            copy.upload.stored_filename.clone(),
+        ),
+        None => (volume, storage_dir, stored_filename),
+    };
+    let encryption_key_id = match &shared_copy {
+        Some(copy) => copy.upload.encryption_key_id.clone(),
+        None => match encrypt_stored_file(state, &file_path).await {
+            Ok(key_id) => key_id,
+            Err(e) => {
+                let _ = fs::remove_file(&file_path).await;
+                storage::remove_empty_dirs(&volume, &storage_dir).await;
+                return Err(IngestError::Io(e));
+            }
+        },
+    };
+
+    // Take the file's place on the link; concurrent uploads may have used the last one
+    match reserve_upload(&state.db, &link.id, file_size).map_err(|e| e.to_string()) {
+        Ok(Reservation::Reserved) => {}
+        Ok(Reservation::QuotaExhausted) => {
+            warn!(link_id = %link.id, original_filename = %original_filename, "Link's quota used up while storing");
+            let _ = fs::remove_file(&file_path).await;
+            storage::remove_empty_dirs(&volume, &storage_dir).await;
+            return Err(IngestError::QuotaExceeded);
+        }
+        Ok(Reservation::FilesExhausted) => {
+            warn!(link_id = %link.id, original_filename = %original_filename, "Link's file limit reached while storing");
+            let _ = fs::remove_file(&file_path).await;
+            storage::remove_empty_dirs(&volume, &storage_dir).await;
+            return Err(IngestError::FileLimitReached);
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&file_path).await;
+            storage::remove_empty_dirs(&volume, &storage_dir).await;
+            return Err(IngestError::Database(e));
+        }
+    }
+
+    let db_save_result = create_file_upload(
+        &state.db,
+        &NewFileUpload {
+            link_id: &link.id,
+            original_filename,
+            stored_filename: &stored_filename,
+            file_size,
+            mime_type: content_type,
+            detected_mime: sniffed.mime(original_filename),
+            guest_folder: &guest_folder,
+            storage_dir: &storage_dir,
+            volume: &volume.to_string_lossy(),
+            uploader,
+            content_hash: &content_hash,
+            scan_result,
+            encryption_key_id: encryption_key_id.as_deref(),
+        },
+    )
+    .map_err(|e| format!("{}", e));
+
+    let upload_id = match db_save_result {
+        Ok(id) => id,
+        Err(error_msg) => {
+            error!(
+                original_filename = %original_filename,
+                link_id = %link.id,
+                error = %error_msg,
+                "Failed to save upload information to database"
+            );
+            let _ = fs::remove_file(&file_path).await;
+            storage::remove_empty_dirs(&volume, &storage_dir).await;
+            release_reservation(state, link, file_size);
+            return Err(IngestError::Database(error_msg));
+        }
+    };
+    // The new row keeps a shared copy in use from here on
+    drop(shared_copy);
+
+    if let Some(replaced) = &replaced {
+        remove_replaced_upload(state, link, replaced).await;
+    }
+
+    info!(
+        upload_id = %upload_id,
+        original_filename = %original_filename,
+        file_size = file_size,
+        link_id = %link.id,
+        "File ingested successfully"
+    );
+
+    enqueue_post_upload_tasks(state, link, &upload_id);
+    events::upload_created(state, link, &upload_id);
+    run_after_store_hooks(state, link, &upload_id);
+
+    Ok(StoredUpload {
+        id: upload_id,
+        file_size,
+        replaced: replaced.is_some(),
+        same_content_as: duplicate.map(|existing| existing.original_filename),
+    })
+}
+
+/// Give back the quota and file taken by [`reserve_upload`] for a file that wasn't recorded
+fn release_reservation(state: &AppState, link: &UploadLink, file_size: i64) {
+    if let Err(e) = update_remaining_quota(&state.db, &link.id, -file_size, -1) {
+        error!(link_id = %link.id, error = %e, "Failed to give back reserved quota");
+    }
+}
+
+/// Bytes a guest may still upload to a link (None = the link has no per-guest quota)
+///
+/// Guests are told apart by their guest folder, their client address or
+/// either, as set by `GUEST_QUOTA_BY`. A guest who can't be identified
+/// (no cookie) starts from zero.
+fn guest_allowance(
+    state: &AppState,
+    link: &UploadLink,
+    guest_folder: Option<&str>,
+    uploader: &Uploader,
+) -> Result<Option<u64>, IngestError> {
+    let Some(quota) = link.guest_quota else {
+        return Ok(None);
+    };
+    let (guest_folder, uploader_ip) = match state.config.guest_quota_by.as_str() {
+        "ip" => (None, uploader.ip.as_deref()),
+        "both" => (guest_folder, uploader.ip.as_deref()),
+        _ => (guest_folder, None),
+    };
+
+    let used = get_guest_upload_bytes(&state.db, &link.id, guest_folder, uploader_ip)
+        .map_err(|e| IngestError::Database(e.to_string()))?;
+    Ok(Some((quota - used).max(0) as u64))
+}
+
+/// Bytes a link may still receive within its rolling 24 hours (None = no daily quota)
+///
+/// Counted from the upload history, so a file stops counting a day after it
+/// arrived; files that were since deleted or replaced no longer count.
+fn daily_allowance(state: &AppState, link: &UploadLink) -> Result<Option<u64>, IngestError> {
+    let Some(quota) = link.daily_quota else {
+        return Ok(None);
+    };
+    let since = Utc::now() - Duration::hours(24);
+    let used = get_link_upload_bytes_since(&state.db, &link.id, since)
+        .map_err(|e| IngestError::Database(e.to_string()))?;
+    Ok(Some((quota - used).max(0) as u64))
+}
+
+/// Record an upload that was turned away, for the link's attempts page
+///
+/// `reason` is a short code such as `too_large` (see the `attempt-reason-*`
+/// messages). A failure to record is only logged.
+pub fn record_failed_attempt(
+    state: &AppState,
+    link: &UploadLink,
+    filename: Option<&str>,
+    reason: &str,
+    detail: Option<&str>,
+    uploader: &Uploader,
+) {
+    if let Err(e) = record_upload_attempt(
+        &state.db,
+        &link.id,
+        filename,
+        reason,
+        detail,
+        uploader.ip.as_deref(),
+        uploader.request_id.as_deref(),
+    ) {
+        error!(link_id = %link.id, reason = %reason, error = %e, "Failed to record refused upload");
+    }
+}
+
+/// SHA-256 of a file's contents, fed as the data is written
+pub struct ContentHasher(digest::Context);
+
+impl ContentHasher {
+    pub fn new() -> Self {
+        ContentHasher(digest::Context::new(&digest::SHA256))
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    /// The hash as lowercase hex
+    pub fn finish(self) -> String {
+        self.0
+            .finish()
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+/// Whether a file's contents match its name on links limiting file types, and
+/// its declared MIME type when `REJECT_TYPE_MISMATCH` is on (see [`crate::filetypes`])
+fn content_matches(
+    state: &AppState,
+    link: &UploadLink,
+    original_filename: &str,
+    content_type: &str,
+    sniffed: &filetypes::Sniffed,
+) -> bool {
+    sniffed.allowed_on(link, original_filename)
+        && (!state.config.reject_type_mismatch || sniffed.matches_declared(content_type))
+}
+
+/// Encrypt a file that passed all checks, if encryption at rest is on (see [`crate::encryption`])
+///
+/// Returns the ID of the key to record with the file (None = left unencrypted).
+pub async fn encrypt_stored_file(
+    state: &AppState,
+    file_path: &std::path::Path,
+) -> std::io::Result<Option<String>> {
+    match state.encryption.as_deref() {
+        Some(keys) => encryption::encrypt_file(keys, file_path, None)
+            .await
+            .map(Some),
+        None => Ok(None),
+    }
+}
+
+/// SHA-256 of a stored file's contents, read back from disk
+pub async fn hash_file(
+    keys: Option<&Keys>,
+    path: &std::path::Path,
+    key_id: Option<&str>,
+) -> std::io::Result<String> {
+    let (mut file, _) = encryption::open(keys, path, key_id).await?;
+    let mut hasher = ContentHasher::new();
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher.finish())
+}
+
+/// A stored copy a new upload is going to share
+///
+/// Holds the stored-copy lock, so the copy can't be removed before the new
+/// upload's row points at it. Drop it once that row is recorded (or wasn't).
+struct SharedCopy {
+    upload: FileUpload,
+    _lock: OwnedMutexGuard<()>,
+}
+
+/// An earlier upload whose stored copy the file just written at `file_path` can share
+///
+/// When one is found, the new file is removed and the caller records the
+/// upload against the existing copy's volume, directory and filename. Only
+/// copies still on disk with the expected size are shared; a failed lookup is
+/// logged and the new file kept.
+async fn reuse_stored_copy(
+    state: &AppState,
+    content_hash: &str,
+    file_size: i64,
+    file_path: &std::path::Path,
+) -> Option<SharedCopy> {
+    if !state.config.deduplicate_storage {
+        return None;
+    }
+
+    // Looked up under the lock, so the copy found is still there when shared
+    let lock = state.stored_copies.clone().lock_owned().await;
+
+    let existing = match get_stored_copy_by_hash(&state.db, content_hash, file_size) {
+        Ok(existing) => existing?,
+        Err(e) => {
+            error!(error = %e, "Failed to look up stored copies by content");
+            return None;
+        }
+    };
+    let existing_path = existing.file_path(&state.upload_dir);
+    if existing_path == file_path {
+        return None;
+    }
+    let len = encryption::content_len(
+        state.encryption.as_deref(),
+        &existing_path,
+        existing.encryption_key_id.as_deref(),
+    );
+    match len.await {
+        Ok(len) if len == file_size as u64 => {}
+        _ => return None,
+    }
+
+    if let Err(e) = fs::remove_file(file_path).await {
+        warn!(file_path = %file_path.display(), error = %e, "Failed to remove file after finding a stored copy");
+        return None;
+    }
+    info!(upload_id = %existing.id, file_size, "Sharing stored copy of identical file");
+    Some(SharedCopy {
+        upload: existing,
+        _lock: lock,
+    })
+}
+
+/// An earlier upload on the link with the same contents, unless `DUPLICATE_UPLOADS=accept`
+///
+/// The version a new file replaces doesn't count, as it is about to go. A
+/// failed lookup is logged and treated as no duplicate.
+fn find_duplicate(
+    state: &AppState,
+    link: &UploadLink,
+    content_hash: &str,
+    replaced: Option<&FileUpload>,
+) -> Option<FileUpload> {
+    if state.config.duplicate_uploads == "accept" {
+        return None;
+    }
+    let except_id = replaced.map(|upload| upload.id.as_str());
+    match get_file_upload_by_hash(&state.db, &link.id, content_hash, except_id) {
+        Ok(Some(existing)) => {
+            info!(
+                link_id = %link.id,
+                existing_upload_id = %existing.id,
+                "Received file has the same contents as an earlier upload"
+            );
+            Some(existing)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            error!(link_id = %link.id, error = %e, "Failed to check for duplicate uploads");
+            None
+        }
+    }
+}
+
+/// Scan a received file for malware, if a virus scanner is configured
+///
+/// # Returns
+/// The verdict to record with the upload (None = scanning is off)
+async fn scan_for_viruses(
+    state: &AppState,
+    link: &UploadLink,
+    filename: &str,
+    file_path: &std::path::Path,
+) -> Result<Option<&'static str>, IngestError> {
+    if !antivirus::is_enabled(&state.config) {
+        return Ok(None);
+    }
+
+    match antivirus::scan_file(&state.config, file_path).await {
+        Ok(ScanOutcome::Clean) => Ok(Some(antivirus::RESULT_CLEAN)),
+        Ok(ScanOutcome::Infected(signature)) => {
+            warn!(filename = %filename, link_id = %link.id, signature = %signature, "Upload rejected: virus scanner found malware");
+            Err(IngestError::Infected(signature))
+        }
+        Err(e) => {
+            error!(filename = %filename, link_id = %link.id, error = %e, "Virus scan failed, refusing upload");
+            Err(IngestError::ScanFailed(e))
+        }
+    }
+}
+
+/// Run the operator's upload rules (see [`crate::rules`]) for a received file
+fn check_upload_rules(
+    state: &AppState,
+    link: &UploadLink,
+    filename: &str,
+    mime_type: &str,
+    size: i64,
+    uploader: &Uploader,
+) -> Result<(), RuleRejection> {
+    let Some(rules) = &state.upload_rules else {
+        return Ok(());
+    };
+    let project = link
+        .project_id
+        .as_deref()
+        .and_then(|id| get_project_by_id(&state.db, id).ok().flatten());
+
+    let result = rules.check(&RuleInput {
+        filename,
+        mime_type,
+        size,
+        link_name: &link.name,
+        project_name: project.as_ref().map(|project| project.name.as_str()),
+        uploader_ip: uploader.ip.as_deref(),
+        uploader_email: uploader.email.as_deref(),
+    });
+    if let Err(rejection) = &result {
+        warn!(filename = %filename, link_id = %link.id, rejection = ?rejection, "Upload refused by upload rules");
+    }
+    result
+}
+
+/// Tell the upload hooks about a newly stored file
+fn run_after_store_hooks(state: &AppState, link: &UploadLink, upload_id: &str) {
+    if state.hooks.is_empty() {
+        return;
+    }
+    match get_file_upload_by_id(&state.db, upload_id) {
+        Ok(Some(upload)) => state.hooks.after_store(link, &upload),
+        Ok(None) => {}
+        Err(e) => error!(upload_id = %upload_id, error = %e, "Failed to load upload for hooks"),
+    }
+}
+
+/// Address of the client that sent a request
+///
+/// The `X-Forwarded-For` entry added by the outermost trusted proxy when proxy
+/// headers are trusted (see [`forwarded_client`]), else the peer address of
+/// the connection.
+pub struct ClientIp(pub Option<String>);
+
+impl FromRequestParts<AppState> for ClientIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let forwarded = parts
+            .headers
+            .get("x-forwarded-for")
+            .filter(|_| state.config.trust_proxy_headers)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| forwarded_client(value, state.config.trusted_proxy_hops))
+            .map(str::to_string);
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_string());
+
+        Ok(ClientIp(forwarded.or(peer)))
+    }
+}
+
+/// The client address in an `X-Forwarded-For` value behind `hops` proxies
+///
+/// Each proxy appends the address it received the request from, so only the
+/// last `hops` entries are written by proxies we trust; anything left of
+/// them was sent by the client and may be made up. The entry `hops` from the
+/// right is therefore the client as seen by the outermost proxy.
+fn forwarded_client(value: &str, hops: usize) -> Option<&str> {
+    let entries: Vec<&str> = value.split(',').map(str::trim).collect();
+    let index = entries.len().saturating_sub(hops.max(1));
+    entries.get(index).copied().filter(|ip| !ip.is_empty())
+}
+
+impl ClientIp {
+    /// The sender of an upload coming from this client, in request `request_id`
+    pub fn uploader(self, request_id: RequestId, headers: &HeaderMap) -> Uploader {
+        let user_agent = headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|agent| !agent.is_empty())
+            .map(|agent| agent.chars().take(MAX_USER_AGENT_CHARS).collect());
+
+        Uploader {
+            ip: self.0,
+            email: None,
+            request_id: Some(request_id.0),
+            user_agent,
+            name: None,
+        }
+    }
+}
+
+/// Longest `User-Agent` kept with an upload
+const MAX_USER_AGENT_CHARS: usize = 512;
+
+/// Longest name a guest may give with an upload
+pub const MAX_UPLOADER_NAME_CHARS: usize = 100;
+
+/// A name given on the upload form, trimmed and cut to length (None if blank)
+pub fn uploader_name(name: &str) -> Option<String> {
+    let name: String = name
+        .trim()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_UPLOADER_NAME_CHARS)
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Longest message a guest may send along with their files
+pub const MAX_UPLOAD_MESSAGE_CHARS: usize = 2000;
+
+/// A message from the upload form, trimmed and without control characters
+/// other than line breaks and tabs
+pub fn upload_message(message: &str) -> String {
+    message
+        .trim()
+        .chars()
+        .filter(|c| !c.is_control() || *c == '\n' || *c == '\t')
+        .collect()
+}
+
+/// Folder for a guest's upload: the one named by their cookie, or a new one
+///
+/// The cookie is only trusted when it is a UUID whose folder holds no files
+/// of another link, so guests can't write into someone else's folder.
+pub fn guest_folder_for(state: &AppState, link: &UploadLink, headers: &HeaderMap) -> String {
+    guest_cookie_value(headers)
+        .filter(|folder| {
+            !is_guest_folder_used_by_other_link(&state.db, folder, &link.id).unwrap_or(true)
+        })
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
+
+/// `Set-Cookie` value starting a guest session on a link, unless the request already has one
+pub fn new_guest_cookie(link: &UploadLink, headers: &HeaderMap) -> Option<String> {
+    if guest_cookie_value(headers).is_some() {
+        return None;
+    }
+
+    // A session cookie: the folder lasts as long as the guest's browser session
+    Some(format!(
+        "{}={}; Path=/upload/{}; HttpOnly; SameSite=Lax",
+        GUEST_COOKIE,
+        Uuid::new_v4(),
+        link.token
+    ))
+}
+
+/// The guest folder named by the request's cookie, if it is a well-formed UUID
+fn guest_cookie_value(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::COOKIE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|cookies| {
+            cookies
+                .split(';')
+                .find_map(|cookie| cookie.trim().strip_prefix(GUEST_COOKIE)?.strip_prefix('='))
+        })
+        .and_then(|value| Uuid::parse_str(value).ok())
+        .map(|uuid| uuid.to_string())
+}
+
+/// Apply the link's collision policy to a new file name
+///
+/// Fails with [`IngestError::Duplicate`] when the link rejects duplicates.
+/// Under `overwrite-latest`, returns the version the new file replaces; it
+/// is removed with [`remove_replaced_upload`] once the new file is stored.
+pub fn check_collision(
+    state: &AppState,
+    link: &UploadLink,
+    original_filename: &str,
+) -> Result<Option<FileUpload>, IngestError> {
+    if link.collision_policy == CollisionPolicy::KeepBoth {
+        return Ok(None);
+    }
+
+    let existing = get_latest_file_upload_by_name(&state.db, &link.id, original_filename)
+        .map_err(|e| IngestError::Database(e.to_string()))?;
+
+    match (link.collision_policy, existing) {
+        (CollisionPolicy::RejectDuplicate, Some(_)) => Err(IngestError::Duplicate),
+        // A version under legal hold is never overwritten; the new file is kept beside it
+        (CollisionPolicy::OverwriteLatest, Some(existing))
+            if link.legal_hold || existing.legal_hold =>
+        {
+            Ok(None)
+        }
+        (CollisionPolicy::OverwriteLatest, existing) => Ok(existing),
+        _ => Ok(None),
+    }
+}
+
+/// Delete a version that was overwritten by a new upload and give its quota back
+async fn remove_replaced_upload(state: &AppState, link: &UploadLink, replaced: &FileUpload) {
+    // A hook may keep the old version; the new one is then stored beside it
+    if let Err(reason) = state.hooks.before_delete(replaced) {
+        warn!(upload_id = %replaced.id, reason = %reason, "Upload hook kept overwritten version");
+        return;
+    }
+
+    let delete_result = delete_file_upload(&state.db, &replaced.id).map_err(|e| e.to_string());
+    if let Err(error_msg) = delete_result {
+        error!(upload_id = %replaced.id, error = %error_msg, "Failed to delete overwritten upload");
+        return;
+    }
+
+    // A negative upload size and count refund the quota the old version used
+    if let Err(e) = update_remaining_quota(&state.db, &link.id, -replaced.file_size, -1) {
+        error!(link_id = %link.id, error = %e, "Failed to refund quota of overwritten upload");
+    }
+
+    let task = Task::RemoveUploadFile {
+        storage_dir: replaced.storage_dir.clone(),
+        stored_filename: replaced.stored_filename.clone(),
+        volume: replaced.volume.clone(),
+    };
+    let enqueue_result = enqueue(state, &task).map_err(|e| e.to_string());
+    if let Err(error_msg) = enqueue_result {
+        error!(upload_id = %replaced.id, error = %error_msg, "Failed to enqueue file removal, removing directly");
+        let _ = remove_upload_file(
+            state,
+            &replaced.storage_dir,
+            &replaced.stored_filename,
+            replaced.volume.clone(),
+        )
+        .await;
+    }
+
+    info!(
+        upload_id = %replaced.id,
+        original_filename = %replaced.original_filename,
+        link_id = %link.id,
+        "Overwrote latest version of file"
+    );
+}
+
+/// Copy `reader` to a new file, failing once more than `max_bytes` arrive
+///
+/// The data is written under a temporary name and only moved to `file_path`
+/// once complete; a failed copy leaves nothing behind. Reading is paced by
+/// the link's `throttle`, if it has one.
+pub(crate) async fn copy_with_limit<R>(
+    disk_writes: &Semaphore,
+    reader: &mut R,
+    file_path: &std::path::Path,
+    max_bytes: u64,
+    throttle: Option<&TokenBucket>,
+    hasher: &mut ContentHasher,
+) -> Result<u64, IngestError>
+where
+    R: AsyncRead + Unpin,
+{
+    let partial_path = storage::PartialFile::new(file_path);
+    let copied = copy_to_file(
+        disk_writes,
+        reader,
+        partial_path.path(),
+        max_bytes,
+        throttle,
+        hasher,
+    )
+    .await;
+    let result = match copied {
+        Ok(written) => fs::rename(&partial_path, file_path)
+            .await
+            .map(|_| written)
+            .map_err(IngestError::Io),
+        Err(e) => Err(e),
+    };
+    if result.is_err() {
+        let _ = fs::remove_file(&partial_path).await;
+    }
+    result
+}
+
+async fn copy_to_file<R>(
+    disk_writes: &Semaphore,
+    reader: &mut R,
+    file_path: &std::path::Path,
+    max_bytes: u64,
+    throttle: Option<&TokenBucket>,
+    hasher: &mut ContentHasher,
+) -> Result<u64, IngestError>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut file = fs::File::create(file_path).await.map_err(IngestError::Io)?;
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    let mut written: u64 = 0;
+
+    loop {
+        let read = reader
+            .read(&mut buffer)
+            .await
+            .map_err(IngestError::Interrupted)?;
+        if read == 0 {
+            break;
+        }
+        if written + read as u64 > max_bytes {
+            return Err(IngestError::QuotaExceeded);
+        }
+        if let Some(bucket) = throttle {
+            bucket.consume(read).await;
+        }
+
+        // Limited number of concurrent disk writers
+        let _write_permit = disk_writes.acquire().await;
+        file.write_all(&buffer[..read])
+            .await
+            .map_err(IngestError::Io)?;
+        hasher.update(&buffer[..read]);
+        written += read as u64;
+    }
+
+    file.flush().await.map_err(IngestError::Io)?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{in_bounded_memory, sparse_file, GIB};
+
+    #[tokio::test]
+    async fn oversized_upload_is_refused_without_leftovers_or_buffering() {
+        const LIMIT: u64 = 128 * 1024 * 1024;
+
+        let dir = tempfile::tempdir().unwrap();
+        let source = sparse_file(dir.path(), 4 * GIB);
+        let target = dir.path().join("stored.bin");
+        let mut reader = fs::File::open(&source).await.unwrap();
+        let disk_writes = Semaphore::new(1);
+        let mut hasher = ContentHasher::new();
+
+        // Reading up to the limit must not hold the data in memory
+        let result = in_bounded_memory(copy_with_limit(
+            &disk_writes,
+            &mut reader,
+            &target,
+            LIMIT,
+            None,
+            &mut hasher,
+        ))
+        .await;
+
+        assert!(matches!(result, Err(IngestError::QuotaExceeded)));
+        assert!(!target.exists());
+        let leftovers: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| *path != source)
+            .collect();
+        assert!(leftovers.is_empty(), "left behind: {:?}", leftovers);
+    }
+
+    #[tokio::test]
+    async fn upload_within_limit_is_stored_in_full() {
+        const SIZE: u64 = 8 * 1024 * 1024;
+
+        let dir = tempfile::tempdir().unwrap();
+        let source = sparse_file(dir.path(), SIZE);
+        let target = dir.path().join("stored.bin");
+        let mut reader = fs::File::open(&source).await.unwrap();
+        let disk_writes = Semaphore::new(1);
+        let mut hasher = ContentHasher::new();
+
+        let written = copy_with_limit(&disk_writes, &mut reader, &target, SIZE, None, &mut hasher)
+            .await
+            .unwrap();
+
+        assert_eq!(written, SIZE);
+        assert_eq!(std::fs::metadata(&target).unwrap().len(), SIZE);
+    }
+
+    #[tokio::test]
+    #[ignore = "hashes 5 GiB; run with `cargo test -- --ignored`"]
+    async fn hashing_a_file_larger_than_memory_reads_it_in_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = sparse_file(dir.path(), 5 * GIB);
+
+        let hash = in_bounded_memory(hash_file(None, &source, None))
+            .await
+            .unwrap();
+
+        // SHA-256 of 5 GiB of zeros
+        assert_eq!(
+            hash,
+            "7f06c62352aebd8125b2a1841e2b9e1ffcbed602f381c3dcb3200200e383d1d5"
+        );
+    }
+
+    #[test]
+    fn forwarded_client_ignores_entries_sent_by_the_client() {
+        let spoofed = "10.0.0.1, 203.0.113.7";
+        assert_eq!(forwarded_client(spoofed, 1), Some("203.0.113.7"));
+        assert_eq!(
+            forwarded_client("203.0.113.7, 192.168.1.2", 2),
+            Some("203.0.113.7")
+        );
+        assert_eq!(
+            forwarded_client("10.0.0.1, 203.0.113.7, 192.168.1.2", 2),
+            Some("203.0.113.7")
+        );
+        assert_eq!(forwarded_client("203.0.113.7", 3), Some("203.0.113.7"));
+        assert_eq!(forwarded_client(" ", 1), None);
+    }
+}
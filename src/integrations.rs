@@ -0,0 +1,185 @@
+//! # No-Code Integrations (Zapier, Make, n8n)
+//!
+//! A polling endpoint that lets automation platforms trigger workflows on
+//! incoming files without custom code. Every upload is described by the flat
+//! [`UploadEventPayload`], the same shape pushed to other integrations.
+//!
+//! ## Endpoint
+//! `GET /api/integrations/uploads?cursor=<cursor>&limit=<n>` returns
+//!
+//! ```json
+//! { "items": [ { "event": "upload.created", "id": "...", ... } ],
+//!   "cursor": "...", "has_more": false }
+//! ```
+//!
+//! Items are in upload order (oldest first). Pass the returned `cursor` on
+//! the next poll to receive only newer uploads; without a cursor the most
+//! recent uploads are returned, so a new integration starts from "now"
+//! instead of replaying the whole history. Zapier's "Retrieve Poll" trigger
+//! can read the `items` key directly and deduplicates on `id`.
+//!
+//! ## Authentication
+//! Set `INTEGRATION_API_KEY` and send it as `Authorization: Bearer <key>`,
+//! `X-Api-Key: <key>` or `?api_key=<key>`. Bearer tokens accepted by the
+//! [`crate::jwt`] settings work as well. The endpoint is disabled while
+//! neither is configured.
+
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::{database::get_file_uploads_after, jwt, models::UploadEventPayload, AppState};
+
+/// Uploads returned per poll unless the client asks for fewer
+const DEFAULT_POLL_LIMIT: i64 = 50;
+
+/// Upper bound for the page size a client may request
+const MAX_POLL_LIMIT: i64 = 500;
+
+/// Query parameters of the polling endpoint
+#[derive(Debug, Deserialize)]
+pub struct PollQuery {
+    /// Cursor returned by the previous poll
+    pub cursor: Option<String>,
+
+    /// Maximum number of uploads to return
+    pub limit: Option<i64>,
+
+    /// API key, for clients that can't set headers
+    pub api_key: Option<String>,
+}
+
+/// Response of the polling endpoint
+#[derive(Debug, Serialize)]
+pub struct PollResponse {
+    /// New uploads, oldest first
+    pub items: Vec<UploadEventPayload>,
+
+    /// Cursor to pass on the next poll (unchanged when nothing new arrived)
+    pub cursor: Option<String>,
+
+    /// Whether more uploads are waiting beyond this page
+    pub has_more: bool,
+}
+
+/// List uploads received after the client's cursor
+pub async fn poll_uploads(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Query(query): Query<PollQuery>,
+) -> Response {
+    let expected_key = state.config.integration_api_key.as_deref();
+    if expected_key.is_none() && state.jwt.is_none() {
+        return (StatusCode::NOT_FOUND, "Integrations API is disabled").into_response();
+    }
+
+    let supplied_key = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| {
+            headers
+                .get("x-api-key")
+                .and_then(|value| value.to_str().ok())
+        })
+        .or(query.api_key.as_deref());
+
+    let key_valid = supplied_key
+        .zip(expected_key)
+        .is_some_and(|(key, expected)| keys_match(key.trim(), expected));
+    if !key_valid && jwt::authenticate(&state, &headers).is_none() {
+        return (StatusCode::UNAUTHORIZED, "Invalid or missing API key").into_response();
+    }
+
+    let cursor = match query.cursor.as_deref().filter(|cursor| !cursor.is_empty()) {
+        Some(raw) => match decode_cursor(raw) {
+            Some(cursor) => Some(cursor),
+            None => return (StatusCode::BAD_REQUEST, "Invalid cursor").into_response(),
+        },
+        None => None,
+    };
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_POLL_LIMIT)
+        .clamp(1, MAX_POLL_LIMIT);
+
+    // Fetch one extra row to find out whether another page follows
+    let rows = match get_file_uploads_after(
+        &state.db,
+        cursor
+            .as_ref()
+            .map(|(uploaded_at, id)| (uploaded_at.as_str(), id.as_str())),
+        limit + 1,
+    ) {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!(error = %e, "Database error while polling uploads");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
+        }
+    };
+
+    // Without a cursor the newest uploads are returned, so the extra row is the oldest one
+    let has_more = rows.len() as i64 > limit && cursor.is_some();
+    let rows: Vec<_> = if cursor.is_some() {
+        rows.into_iter().take(limit as usize).collect()
+    } else {
+        let skip = rows.len().saturating_sub(limit as usize);
+        rows.into_iter().skip(skip).collect()
+    };
+
+    let next_cursor = match rows.last() {
+        Some((upload, _)) => Some(encode_cursor(&upload.uploaded_at.to_rfc3339(), &upload.id)),
+        None => query.cursor.filter(|cursor| !cursor.is_empty()),
+    };
+
+    let items = rows
+        .iter()
+        .map(|(upload, link)| UploadEventPayload::from_upload(upload, link.as_ref()))
+        .collect();
+
+    Json(PollResponse {
+        items,
+        cursor: next_cursor,
+        has_more,
+    })
+    .into_response()
+}
+
+/// Compare API keys without leaking the position of the first mismatch
+fn keys_match(supplied: &str, expected: &str) -> bool {
+    supplied.len() == expected.len()
+        && supplied
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Encode an `(uploaded_at, id)` position as a URL-safe opaque cursor
+fn encode_cursor(uploaded_at: &str, id: &str) -> String {
+    format!("{}|{}", uploaded_at, id)
+        .bytes()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Decode a cursor produced by [`encode_cursor`]
+fn decode_cursor(cursor: &str) -> Option<(String, String)> {
+    if !cursor.len().is_multiple_of(2) || !cursor.is_ascii() {
+        return None;
+    }
+
+    let bytes = (0..cursor.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&cursor[i..i + 2], 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    let decoded = String::from_utf8(bytes).ok()?;
+    let (uploaded_at, id) = decoded.split_once('|')?;
+
+    Some((uploaded_at.to_string(), id.to_string()))
+}
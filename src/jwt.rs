@@ -0,0 +1,432 @@
+//! # JWT Bearer Authentication
+//!
+//! API routes accept a signed JSON Web Token in an `Authorization: Bearer`
+//! header in place of their usual credentials: an admin session cookie on
+//! `/api/graphql`, the integration key on `/api/integrations/uploads` and an
+//! API key on `/api/v1`. This lets callers that sign in with an external
+//! identity provider, and other services with a token of their own, use the
+//! API without an interactive login.
+//!
+//! Tokens must be signed with HS256 (shared secret), RS256 or ES256 (public
+//! keys), carry the configured issuer and audience, and not be expired. The
+//! `sub` claim is logged as the caller. Decoding and checking are left to the
+//! `jsonwebtoken` crate.
+//!
+//! ## Configuration
+//! - `JWT_ISSUER`: Expected `iss` claim; enables bearer tokens (unset = disabled)
+//! - `JWT_AUDIENCE`: Expected `aud` claim (unset = not checked)
+//! - `JWT_HS256_SECRET`: Shared secret for HS256-signed tokens
+//! - `JWT_PUBLIC_KEYS`: Comma-separated PEM public key files (RSA or P-256) for RS256/ES256 tokens
+//! - `JWT_LEEWAY_SECS`: Clock difference tolerated on `exp` and `nbf` (default: `60`)
+
+use axum::http::{header, HeaderMap};
+use jsonwebtoken::{errors::ErrorKind, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use std::path::Path;
+use tracing::{debug, info};
+
+use crate::{config::Config, AppState};
+
+/// Checks bearer tokens against the configured issuer and keys
+pub struct JwtVerifier {
+    /// Claims every token must carry; the algorithm is set per token
+    validation: Validation,
+    secret: Option<DecodingKey>,
+    /// Public keys, with the algorithm each verifies (RS256 or ES256)
+    keys: Vec<(Algorithm, DecodingKey)>,
+}
+
+/// The caller a valid token was issued to
+#[derive(Debug, Clone)]
+pub struct Claims {
+    pub subject: String,
+}
+
+/// The claims read from a token; the others are checked by `Validation`
+#[derive(Deserialize)]
+struct TokenClaims {
+    /// Required by `Validation`; optional here so its absence is reported as such
+    sub: Option<String>,
+}
+
+impl JwtVerifier {
+    /// Build the verifier from the configuration (None = bearer tokens disabled)
+    ///
+    /// Fails when a key file can't be read, so a misconfigured server
+    /// doesn't start refusing every token.
+    pub fn from_config(config: &Config) -> Result<Option<JwtVerifier>, String> {
+        let Some(issuer) = config.jwt_issuer.as_deref() else {
+            return Ok(None);
+        };
+
+        let keys = config
+            .jwt_public_keys
+            .iter()
+            .map(|path| read_public_key(path))
+            .collect::<Result<Vec<_>, _>>()?;
+        let secret = config
+            .jwt_hs256_secret
+            .as_deref()
+            .map(|secret| DecodingKey::from_secret(secret.as_bytes()));
+
+        if secret.is_none() && keys.is_empty() {
+            return Err("JWT_ISSUER requires JWT_HS256_SECRET or JWT_PUBLIC_KEYS".to_string());
+        }
+
+        info!(issuer = %issuer, public_keys = keys.len(), "JWT bearer authentication enabled");
+        Ok(Some(JwtVerifier {
+            validation: validation(
+                issuer,
+                config.jwt_audience.as_deref(),
+                config.jwt_leeway_secs,
+            ),
+            secret,
+            keys,
+        }))
+    }
+
+    /// Check a token's signature and claims
+    pub fn verify(&self, token: &str) -> Result<Claims, &'static str> {
+        let header = jsonwebtoken::decode_header(token).map_err(|e| reason(e.kind()))?;
+        let keys: Vec<&DecodingKey> = match header.alg {
+            Algorithm::HS256 => self.secret.iter().collect(),
+            Algorithm::RS256 | Algorithm::ES256 => self
+                .keys
+                .iter()
+                .filter(|(alg, _)| *alg == header.alg)
+                .map(|(_, key)| key)
+                .collect(),
+            _ => return Err("unsupported algorithm"),
+        };
+
+        let mut validation = self.validation.clone();
+        validation.algorithms = vec![header.alg];
+        for key in keys {
+            match jsonwebtoken::decode::<TokenClaims>(token, key, &validation) {
+                Ok(data) => {
+                    let subject = data.claims.sub.ok_or("no subject")?;
+                    return Ok(Claims { subject });
+                }
+                // Signed with another of the configured keys, maybe
+                Err(e) if *e.kind() == ErrorKind::InvalidSignature => continue,
+                Err(e) => return Err(reason(e.kind())),
+            }
+        }
+        Err("bad signature")
+    }
+}
+
+/// What a token must carry: the issuer, the audience if one is configured, an
+/// expiry and a subject
+fn validation(issuer: &str, audience: Option<&str>, leeway_secs: i64) -> Validation {
+    let mut validation = Validation::default();
+    validation.set_issuer(&[issuer]);
+    match audience {
+        Some(audience) => {
+            validation.set_audience(&[audience]);
+            validation.set_required_spec_claims(&["exp", "iss", "sub", "aud"]);
+        }
+        None => {
+            validation.validate_aud = false;
+            validation.set_required_spec_claims(&["exp", "iss", "sub"]);
+        }
+    }
+    validation.validate_nbf = true;
+    validation.leeway = leeway_secs.max(0) as u64;
+    validation
+}
+
+/// Why a token was refused, for the debug log
+fn reason(kind: &ErrorKind) -> &'static str {
+    match kind {
+        ErrorKind::InvalidSignature => "bad signature",
+        ErrorKind::InvalidAlgorithm => "unsupported algorithm",
+        ErrorKind::ExpiredSignature => "expired",
+        ErrorKind::ImmatureSignature => "not yet valid",
+        ErrorKind::InvalidIssuer => "wrong issuer",
+        ErrorKind::InvalidAudience => "wrong audience",
+        ErrorKind::MissingRequiredClaim(claim) => match claim.as_str() {
+            "exp" => "no expiry",
+            "sub" => "no subject",
+            "iss" => "wrong issuer",
+            "aud" => "wrong audience",
+            _ => "missing claim",
+        },
+        _ => "malformed token",
+    }
+}
+
+/// The token of an `Authorization: Bearer` header
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+/// The caller of a request carrying a valid bearer token
+///
+/// Returns None when bearer tokens are disabled, there is no token, or it
+/// doesn't verify; the reason is logged at debug level.
+pub fn authenticate(state: &AppState, headers: &HeaderMap) -> Option<Claims> {
+    let verifier = state.jwt.as_ref()?;
+    let token = bearer_token(headers)?;
+    match verifier.verify(token) {
+        Ok(claims) => {
+            debug!(subject = %claims.subject, "Request authenticated by bearer token");
+            Some(claims)
+        }
+        Err(reason) => {
+            debug!(reason = %reason, "Rejected bearer token");
+            None
+        }
+    }
+}
+
+/// Read a PEM public key file, as an RS256 key if it is RSA and ES256 if it is EC
+fn read_public_key(path: &Path) -> Result<(Algorithm, DecodingKey), String> {
+    let pem =
+        std::fs::read(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+
+    if let Ok(key) = DecodingKey::from_rsa_pem(&pem) {
+        Ok((Algorithm::RS256, key))
+    } else if let Ok(key) = DecodingKey::from_ec_pem(&pem) {
+        Ok((Algorithm::ES256, key))
+    } else {
+        Err(format!(
+            "{}: only RSA and P-256 public keys are supported",
+            path.display()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use jsonwebtoken::{EncodingKey, Header};
+    use serde_json::{json, Value};
+
+    const SECRET: &[u8] = b"a shared secret for tests only";
+    const ISSUER: &str = "https://idp.example.com";
+    const AUDIENCE: &str = "needadrop";
+
+    /// Payload of the RFC 7515 examples (appendix A.1 and A.3)
+    const RFC_PAYLOAD: &str = "eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFtcGxlLmNvbS9pc19yb290Ijp0cnVlfQ";
+
+    fn hs256_verifier() -> JwtVerifier {
+        JwtVerifier {
+            validation: validation(ISSUER, Some(AUDIENCE), 60),
+            secret: Some(DecodingKey::from_secret(SECRET)),
+            keys: Vec::new(),
+        }
+    }
+
+    /// A verifier for the RFC examples, whose expiry in 2011 is ignored
+    fn rfc_verifier(
+        secret: Option<DecodingKey>,
+        keys: Vec<(Algorithm, DecodingKey)>,
+    ) -> JwtVerifier {
+        let mut validation = validation("joe", None, 60);
+        validation.validate_exp = false;
+        JwtVerifier {
+            validation,
+            secret,
+            keys,
+        }
+    }
+
+    /// Claims a test token starts from, valid for the next five minutes
+    fn valid_claims() -> Value {
+        json!({
+            "iss": ISSUER,
+            "aud": AUDIENCE,
+            "sub": "ci-pipeline",
+            "exp": Utc::now().timestamp() + 300,
+        })
+    }
+
+    fn token_signed_with(secret: &[u8], claims: &Value) -> String {
+        jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            claims,
+            &EncodingKey::from_secret(secret),
+        )
+        .unwrap()
+    }
+
+    fn hs256_token(claims: &Value) -> String {
+        token_signed_with(SECRET, claims)
+    }
+
+    fn subject(result: Result<Claims, &'static str>) -> Result<String, &'static str> {
+        result.map(|claims| claims.subject)
+    }
+
+    #[test]
+    fn verifies_rfc_7515_hs256_example() {
+        let jwk = json!({
+            "kty": "oct",
+            "k": "AyM1SysPpbyDfgZld3umj1qzKObwVMkoqQ-EstJQLr_T-1qS0gZH75aKtMN3Yj0iPS4hcgUuTwjAzZr1Z9CAow",
+        });
+        let key = DecodingKey::from_jwk(&serde_json::from_value(jwk).unwrap()).unwrap();
+        let verifier = rfc_verifier(Some(key), Vec::new());
+        let token = format!(
+            "eyJ0eXAiOiJKV1QiLA0KICJhbGciOiJIUzI1NiJ9.{}.dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk",
+            RFC_PAYLOAD
+        );
+
+        // The signature holds; the example just has no subject
+        assert_eq!(subject(verifier.verify(&token)), Err("no subject"));
+    }
+
+    #[test]
+    fn verifies_rfc_7515_es256_example() {
+        let key = DecodingKey::from_ec_components(
+            "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU",
+            "x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0",
+        )
+        .unwrap();
+        let verifier = rfc_verifier(None, vec![(Algorithm::ES256, key)]);
+        let token = format!(
+            "eyJhbGciOiJFUzI1NiJ9.{}.DtEhU3ljbEg8L38VWAfUAqOyKAM6-Xx-F4GawxaepmXFCgfTjDxw5djxLa8ISlSApmWQxfKTUJqPP3-Kg6NU1Q",
+            RFC_PAYLOAD
+        );
+        assert_eq!(subject(verifier.verify(&token)), Err("no subject"));
+
+        // The same signature doesn't hold for another payload
+        let tampered = token.replacen("eyJpc3MiOiJqb2Ui", "eyJpc3MiOiJqb2Ai", 1);
+        assert_eq!(subject(verifier.verify(&tampered)), Err("bad signature"));
+    }
+
+    #[test]
+    fn accepts_valid_token() {
+        let verifier = hs256_verifier();
+        assert_eq!(
+            subject(verifier.verify(&hs256_token(&valid_claims()))),
+            Ok("ci-pipeline".to_string())
+        );
+
+        let mut claims = valid_claims();
+        claims["aud"] = json!(["other", AUDIENCE]);
+        assert_eq!(
+            subject(verifier.verify(&hs256_token(&claims))),
+            Ok("ci-pipeline".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_unsigned_tokens() {
+        let verifier = hs256_verifier();
+        let token = hs256_token(&valid_claims());
+        let (_, payload_and_signature) = token.split_once('.').unwrap();
+        let (payload, signature) = payload_and_signature.split_once('.').unwrap();
+
+        // {"alg":"none","typ":"JWT"}
+        let none = "eyJhbGciOiJub25lIiwidHlwIjoiSldUIn0";
+        assert!(verifier.verify(&format!("{}.{}.", none, payload)).is_err());
+        assert!(verifier
+            .verify(&format!("{}.{}.{}", none, payload, signature))
+            .is_err());
+
+        // {"alg":"PS256","typ":"JWT"}
+        let ps256 = "eyJhbGciOiJQUzI1NiIsInR5cCI6IkpXVCJ9";
+        assert_eq!(
+            subject(verifier.verify(&format!("{}.{}.{}", ps256, payload, signature))),
+            Err("unsupported algorithm")
+        );
+
+        // An HS256 token can't be passed off as signed with a public key
+        let verifier = JwtVerifier {
+            secret: None,
+            ..hs256_verifier()
+        };
+        assert_eq!(subject(verifier.verify(&token)), Err("bad signature"));
+    }
+
+    #[test]
+    fn rejects_tampered_tokens() {
+        let verifier = hs256_verifier();
+        let token = hs256_token(&valid_claims());
+        let (signed, signature) = token.rsplit_once('.').unwrap();
+
+        let mut claims = valid_claims();
+        claims["sub"] = json!("admin");
+        let other = token_signed_with(b"another secret", &claims);
+        let forged_payload = other.split('.').nth(1).unwrap();
+        let header = signed.split('.').next().unwrap();
+        let forged = format!("{}.{}.{}", header, forged_payload, signature);
+        assert_eq!(subject(verifier.verify(&forged)), Err("bad signature"));
+        assert_eq!(subject(verifier.verify(&other)), Err("bad signature"));
+
+        let flipped = if signature.starts_with('A') { "B" } else { "A" };
+        let forged = format!("{}.{}{}", signed, flipped, &signature[1..]);
+        assert_eq!(subject(verifier.verify(&forged)), Err("bad signature"));
+
+        assert_eq!(subject(verifier.verify(signed)), Err("malformed token"));
+        assert_eq!(
+            subject(verifier.verify(&format!("{}.!!!", signed))),
+            Err("bad signature")
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_issuer_or_audience() {
+        let verifier = hs256_verifier();
+
+        let mut claims = valid_claims();
+        claims["iss"] = json!("https://evil.example.com");
+        assert_eq!(
+            subject(verifier.verify(&hs256_token(&claims))),
+            Err("wrong issuer")
+        );
+
+        let mut claims = valid_claims();
+        claims["aud"] = json!("another-service");
+        assert_eq!(
+            subject(verifier.verify(&hs256_token(&claims))),
+            Err("wrong audience")
+        );
+
+        let mut claims = valid_claims();
+        claims.as_object_mut().unwrap().remove("aud");
+        assert_eq!(
+            subject(verifier.verify(&hs256_token(&claims))),
+            Err("wrong audience")
+        );
+    }
+
+    #[test]
+    fn checks_expiry_and_not_before_with_leeway() {
+        let verifier = hs256_verifier();
+        let now = Utc::now().timestamp();
+
+        let mut claims = valid_claims();
+        claims["exp"] = json!(now - 120);
+        assert_eq!(
+            subject(verifier.verify(&hs256_token(&claims))),
+            Err("expired")
+        );
+
+        claims["exp"] = json!(now - 30);
+        assert!(verifier.verify(&hs256_token(&claims)).is_ok());
+
+        let mut claims = valid_claims();
+        claims.as_object_mut().unwrap().remove("exp");
+        assert_eq!(
+            subject(verifier.verify(&hs256_token(&claims))),
+            Err("no expiry")
+        );
+
+        let mut claims = valid_claims();
+        claims["nbf"] = json!(now + 300);
+        assert_eq!(
+            subject(verifier.verify(&hs256_token(&claims))),
+            Err("not yet valid")
+        );
+
+        claims["nbf"] = json!(now + 30);
+        assert!(verifier.verify(&hs256_token(&claims)).is_ok());
+    }
+}
@@ -0,0 +1,121 @@
+//! # Listener Handoff and Draining
+//!
+//! Upgrades shouldn't abort guests' in-progress uploads, which can run for
+//! hours on multi-gigabyte files. Two pieces make restarts seamless:
+//!
+//! - **Handoff**: the listening socket is either inherited from the service
+//!   manager (systemd socket activation, `LISTEN_FDS`), so connections queue
+//!   in the kernel while the process is replaced, or bound with
+//!   `SO_REUSEPORT`, so a new process can bind the same port while the old
+//!   one still runs.
+//! - **Draining**: on `SIGTERM` or `SIGINT` the server stops accepting
+//!   connections and waits for requests in flight to finish, up to
+//!   `SHUTDOWN_DRAIN_SECS`, before exiting. If the timeout forces the exit,
+//!   the partial files of uploads cut short are deleted first; either way
+//!   the database is checkpointed so it is left as a single clean file.
+//!
+//! With `LISTEN_REUSE_PORT=true`, an upgrade is: start the new process, wait
+//! until it serves requests, then send `SIGTERM` to the old one.
+//!
+//! ## Configuration
+//! - `LISTEN_REUSE_PORT`: Bind with `SO_REUSEPORT` so old and new processes can share the port (default: `false`)
+//! - `SHUTDOWN_DRAIN_SECS`: How long requests in flight may take to finish after a shutdown signal (default: `3600`)
+
+use std::{
+    net::{SocketAddr, TcpListener as StdTcpListener},
+    os::fd::{FromRawFd, RawFd},
+    time::Duration,
+};
+use tokio::{
+    net::{TcpListener, TcpSocket},
+    signal::unix::{signal, SignalKind},
+};
+use tracing::{info, warn};
+
+use crate::{
+    config::Config,
+    database::{self, DbPool},
+    storage,
+};
+
+/// First file descriptor passed by systemd socket activation
+const SD_LISTEN_FDS_START: RawFd = 3;
+
+/// Connections waiting to be accepted before the kernel refuses new ones
+const LISTEN_BACKLOG: u32 = 1024;
+
+/// The listener of the main server: inherited from the service manager, or bound to `addr`
+pub fn bind(config: &Config, addr: SocketAddr) -> std::io::Result<TcpListener> {
+    if let Some(listener) = inherited_listener()? {
+        info!(addr = ?listener.local_addr().ok(), "Using listener passed by the service manager");
+        return Ok(listener);
+    }
+
+    let socket = if addr.is_ipv4() {
+        TcpSocket::new_v4()?
+    } else {
+        TcpSocket::new_v6()?
+    };
+    socket.set_reuseaddr(true)?;
+    if config.listen_reuse_port {
+        socket.set_reuseport(true)?;
+    }
+    socket.bind(addr)?;
+    socket.listen(LISTEN_BACKLOG)
+}
+
+/// The first socket passed by systemd socket activation, if any
+fn inherited_listener() -> std::io::Result<Option<TcpListener>> {
+    let Ok(fds) = std::env::var("LISTEN_FDS") else {
+        return Ok(None);
+    };
+    // The variables are meant for the process systemd started, not its children
+    let for_us = std::env::var("LISTEN_PID")
+        .map(|pid| pid.trim() == std::process::id().to_string())
+        .unwrap_or(true);
+    if !for_us || fds.trim().parse::<u32>().unwrap_or(0) == 0 {
+        return Ok(None);
+    }
+
+    // SAFETY: systemd hands over ownership of this descriptor and nothing
+    // else in the process uses it
+    let listener = unsafe { StdTcpListener::from_raw_fd(SD_LISTEN_FDS_START) };
+    listener.set_nonblocking(true)?;
+    TcpListener::from_std(listener).map(Some)
+}
+
+/// Resolve once a shutdown signal arrives, and bound the time left for draining
+///
+/// The server stops accepting connections when this resolves; if requests
+/// in flight haven't finished after `SHUTDOWN_DRAIN_SECS`, the process exits
+/// anyway, after removing the partial files of those uploads and closing `db`.
+pub async fn shutdown_signal(config: &Config, db: DbPool) {
+    let (Ok(mut terminate), Ok(mut interrupt)) = (
+        signal(SignalKind::terminate()),
+        signal(SignalKind::interrupt()),
+    ) else {
+        warn!("Failed to install shutdown signal handlers, draining is disabled");
+        return std::future::pending().await;
+    };
+
+    tokio::select! {
+        _ = terminate.recv() => {}
+        _ = interrupt.recv() => {}
+    }
+
+    let drain = Duration::from_secs(config.shutdown_drain_secs);
+    info!(
+        drain_secs = config.shutdown_drain_secs,
+        "Shutdown requested, finishing requests in flight"
+    );
+    tokio::spawn(async move {
+        tokio::time::sleep(drain).await;
+        let removed = storage::remove_in_flight_partials();
+        warn!(
+            removed_partial_files = removed,
+            "Requests still in flight after the drain timeout, exiting"
+        );
+        database::close(&db);
+        std::process::exit(0);
+    });
+}
@@ -0,0 +1,82 @@
+//! # Outgoing Email
+//!
+//! Sends plain-text notification emails over SMTP. Emails are never sent
+//! from a request handler directly: callers enqueue a
+//! [`crate::tasks::Task::SendEmail`] so delivery is retried when the mail
+//! server is unreachable.
+//!
+//! ## Configuration
+//! - `SMTP_HOST`: Mail server (unset = email disabled)
+//! - `SMTP_PORT`: Port (default: `587`)
+//! - `SMTP_SECURITY`: `starttls` (default), `tls` (implicit TLS, usually port 465) or `none`
+//! - `SMTP_USERNAME` / `SMTP_PASSWORD`: Optional credentials
+//! - `SMTP_FROM`: Sender, e.g. `NeedADrop <drop@example.com>`
+//! - `ADMIN_EMAIL`: Recipient of admin notifications
+
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+use crate::config::Config;
+
+/// Whether outgoing email is configured
+pub fn is_configured(config: &Config) -> bool {
+    config.smtp_host.is_some()
+}
+
+/// Send a plain-text email
+pub async fn send_email(
+    config: &Config,
+    to: &str,
+    subject: &str,
+    body: &str,
+) -> Result<(), String> {
+    let host = config
+        .smtp_host
+        .as_deref()
+        .ok_or_else(|| "SMTP_HOST is not configured".to_string())?;
+
+    let from: Mailbox = config
+        .smtp_from
+        .parse()
+        .map_err(|e| format!("Invalid SMTP_FROM: {}", e))?;
+    let to: Mailbox = to
+        .parse()
+        .map_err(|e| format!("Invalid recipient {}: {}", to, e))?;
+
+    let message = Message::builder()
+        .from(from)
+        .to(to)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(body.to_string())
+        .map_err(|e| format!("Failed to build email: {}", e))?;
+
+    let builder = match config.smtp_security.as_str() {
+        "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(host),
+        "none" => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+            host,
+        )),
+        _ => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host),
+    }
+    .map_err(|e| format!("Invalid SMTP_HOST: {}", e))?
+    .port(config.smtp_port);
+
+    let builder = match config.smtp_username.as_deref() {
+        Some(username) => builder.credentials(Credentials::new(
+            username.to_string(),
+            config.smtp_password.clone().unwrap_or_default(),
+        )),
+        None => builder,
+    };
+
+    builder
+        .build()
+        .send(message)
+        .await
+        .map_err(|e| format!("Failed to send email: {}", e))?;
+
+    Ok(())
+}
@@ -6,20 +6,23 @@
 //! ## Architecture Overview
 //! - **Web Framework**: Axum 0.8 with Tower 0.5 service layer
 //! - **Database**: SQLite with rusqlite 0.37
-//! - **Authentication**: Session-based with bcrypt password hashing
+//! - **Authentication**: Session-based with bcrypt password hashing and optional TOTP codes
 //! - **File Storage**: Local filesystem with UUID-based isolation
-//! - **Logging**: Structured logging with tracing crate
+//! - **Logging**: Structured logging with tracing crate, as text or JSON lines
 
 // Import core web framework dependencies
 use axum::{
-    extract::DefaultBodyLimit, // For setting request body size limits
-    middleware,                // For custom middleware integration
-    response::IntoResponse,    // Trait for converting types to HTTP responses
-    routing::{get, post},      // HTTP method routing helpers
-    Router,                    // Main router type for building the application
+    extract::DefaultBodyLimit,  // For setting request body size limits
+    middleware,                 // For custom middleware integration
+    response::IntoResponse,     // Trait for converting types to HTTP responses
+    routing::{get, head, post}, // HTTP method routing helpers
+    Router,                     // Main router type for building the application
 };
 use std::{path::PathBuf, sync::Arc}; // Standard library types for file paths and thread-safe references
-use tokio::fs; // Async filesystem operations
+use tokio::{
+    fs,
+    sync::{Mutex, Semaphore},
+}; // Async filesystem operations and concurrency limits
 use tower::ServiceBuilder; // Service layer builder for middleware composition
 use tower_http::{
     // HTTP-specific middleware from tower-http 0.6
@@ -30,31 +33,112 @@ use tower_http::{
 use tracing::info; // Structured logging macros
 
 // Application modules
+mod antivirus; // Scanning received files with ClamAV
+mod api; // REST API for scripts and CI jobs
+mod archive; // ZIP downloads of a link's files
 mod auth; // Authentication and session management
+mod calendar; // iCalendar feed of link expiries
+mod cleanup; // Deactivating expired links and deleting their files
+mod commands; // Per-link commands run after each upload
+mod compression; // Compressing pages and assets, but not stored files
+mod config; // Runtime configuration from environment variables
 mod database; // Database operations and initialization
+mod datetime; // Time zone aware formatting of timestamps
+mod email_templates; // Customizable notification email texts
+mod emailin; // Turning email attachments into uploads
+mod encryption; // Encrypting stored files at rest
+mod errors; // Themed error pages and JSON errors
+mod events; // Publishing server events to external automation
+mod filetypes; // Per-link lists of accepted file types
+mod gdpr; // Exporting and erasing a person's data on request
+#[cfg(feature = "graphql")]
+mod graphql; // Optional GraphQL API
 mod handlers; // HTTP request handlers
+mod hooks; // Extension hooks around storing and deleting uploads
+mod i18n; // Translations and language negotiation
+mod imap; // Minimal IMAP client for the email-in gateway
+mod ingest; // Storing uploads that don't come from the upload form
+mod integrations; // Polling endpoint for Zapier/Make
+mod jwt; // Bearer token authentication for API routes
+mod listener; // Listener handoff and draining for seamless restarts
+mod mailer; // Outgoing email over SMTP
 mod models; // Data models and structures
+mod mqtt; // MQTT event sink
+mod mtls; // Admin listener requiring client certificates
+mod notifications; // Admin emails about new uploads
+mod pii; // Flagging uploads that likely contain personal data
+#[cfg(feature = "wasm-plugins")]
+mod plugins; // Sandboxed WASM upload plugins
+mod progress; // In-flight upload progress tracking
+mod public_drop; // Shared upload page without per-person links
+mod pwa; // Web app manifest and service worker
+mod ratelimit; // Per-client limits on logins and uploads started
+mod replication; // Mirroring uploads to a remote host
+mod request_id; // Request IDs for matching failures with log entries
+mod resumable; // Chunked uploads that survive connection loss
+mod rules; // Operator-written upload rules (Rhai)
+mod s3; // S3-compatible API facade
+mod scheduler; // Periodic housekeeping jobs
+mod storage; // On-disk layout of uploaded files
+mod tasks; // Persistent background task queue
 mod templates; // HTML template rendering
+#[cfg(test)]
+mod testing; // Large sparse files and memory checks for tests
+mod theme; // Light and dark theme preference
+mod throttle; // Per-link bandwidth throttling
+mod totp; // Two-factor codes for admin logins
+mod tus; // tus.io protocol for resumable upload clients
+mod webdav; // Pushing uploads to Nextcloud/ownCloud
+mod webhooks; // Signed event deliveries to HTTP endpoints
 
 // Import specific items from modules
-use auth::auth_middleware; // Authentication middleware for protected routes
+use auth::{auth_middleware, csrf_middleware}; // Authentication and CSRF middleware for protected routes
+use config::Config; // Runtime configuration
 use database::*; // Database initialization and operations
 use handlers::*; // All HTTP request handlers
 
 /// Application state shared across all handlers
 ///
 /// This struct contains the shared resources that all request handlers need access to:
-/// - Database connection pool (wrapped in Arc<Mutex> for thread safety)
+/// - Database connection pool
 /// - Upload directory path for file storage
 #[derive(Clone)]
 pub struct AppState {
-    /// Thread-safe database connection shared across all handlers
-    /// Using Arc<Mutex<rusqlite::Connection>> for SQLite connection sharing
-    pub db: Arc<std::sync::Mutex<rusqlite::Connection>>,
+    /// Pool of SQLite connections shared across all handlers
+    /// (cheap to clone: clones share the same connections)
+    pub db: DbPool,
 
     /// Base directory where uploaded files are stored
     /// Each upload link gets its own subdirectory using UUID
     pub upload_dir: PathBuf,
+
+    /// Runtime configuration loaded at startup
+    pub config: Arc<Config>,
+
+    /// Limits the number of uploads processed simultaneously
+    pub upload_limiter: Arc<Semaphore>,
+
+    /// Limits the number of uploads writing to disk simultaneously
+    pub disk_write_limiter: Arc<Semaphore>,
+
+    /// Per-client request limits on logins and uploads
+    pub rate_limits: Arc<ratelimit::RateLimits>,
+
+    /// Extension hooks run around storing and deleting uploads
+    pub hooks: hooks::UploadHooks,
+
+    /// Operator-written rules checked for every received file (None = no rules)
+    pub upload_rules: Option<Arc<rules::UploadRules>>,
+
+    /// Verifier for API bearer tokens (None = only sessions and API keys)
+    pub jwt: Option<Arc<jwt::JwtVerifier>>,
+
+    /// Keys stored files are encrypted with (None = encryption at rest is off)
+    pub encryption: Option<Arc<encryption::Keys>>,
+
+    /// Held while a new upload takes a share of a stored copy and while a
+    /// stored file is removed, so a copy can't go between the two
+    pub stored_copies: Arc<Mutex<()>>,
 }
 
 /// Main application entry point
@@ -68,25 +152,121 @@ pub struct AppState {
 /// 6. Middleware stack for CORS, tracing, and authentication
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Load environment variables from .env file (if present)
+    // This allows configuration without hardcoding values
+    dotenvy::dotenv().ok();
+
     // Initialize structured logging system with environment-based configuration
     // Default level is INFO, can be overridden with RUST_LOG env variable
     init_logging();
 
-    // Load environment variables from .env file (if present)
-    // This allows configuration without hardcoding values
-    dotenvy::dotenv().ok();
+    // Read the optional config file; environment variables override its settings
+    let config_file = config::load_file()?;
+
+    // Load runtime configuration (limits, timeouts) from the environment
+    let config = Config::from_env();
+    info!(
+        config_file = ?config_file,
+        max_concurrent_uploads = config.max_concurrent_uploads,
+        max_concurrent_disk_writes = config.max_concurrent_disk_writes,
+        replication_target = ?config.replication_target,
+        "Configuration loaded"
+    );
 
     // Initialize SQLite database connection and create tables if they don't exist
     // This also creates the default admin user if none exists
-    let db = init_database()?;
+    let db = init_database(&config)?;
 
     // Create the upload directory structure
     // Each upload link will get its own UUID-based subdirectory
-    let upload_dir = PathBuf::from("uploads");
+    let upload_dir = config.upload_dir.clone();
     fs::create_dir_all(&upload_dir).await?;
 
+    // Create the extra storage volumes new files are spread across (if configured)
+    for volume in &config.storage_volumes {
+        fs::create_dir_all(volume).await?;
+    }
+
+    // Emailed upload links need a host to be clickable
+    if mailer::is_configured(&config) && config.public_base_url.is_none() {
+        tracing::warn!("PUBLIC_BASE_URL is not set; upload links in emails will have no host");
+    }
+
+    // Load WASM upload plugins (built with `--features wasm-plugins`)
+    #[cfg(feature = "wasm-plugins")]
+    let upload_hooks = plugins::load(&config, hooks::UploadHooks::default())?;
+    #[cfg(not(feature = "wasm-plugins"))]
+    let upload_hooks = {
+        if !config.wasm_plugins.is_empty() {
+            tracing::warn!("WASM_PLUGINS is set but this build has no plugin support; rebuild with --features wasm-plugins");
+        }
+        hooks::UploadHooks::default()
+    };
+
+    // Compile the upload rules script (if configured); a broken script stops startup
+    let upload_rules = match &config.upload_rules_file {
+        Some(path) => Some(Arc::new(rules::UploadRules::load(path).map_err(|e| {
+            format!("failed to load upload rules {}: {}", path.display(), e)
+        })?)),
+        None => None,
+    };
+
+    // Load the keys API bearer tokens are checked against (if configured)
+    let jwt = jwt::JwtVerifier::from_config(&config)?.map(Arc::new);
+
+    // Load the keys stored files are encrypted with (if configured)
+    let encryption = encryption::Keys::from_config(&config)?.map(Arc::new);
+
     // Create shared application state that will be available to all handlers
-    let state = AppState { db, upload_dir };
+    let state = AppState {
+        db,
+        upload_dir,
+        upload_limiter: Arc::new(Semaphore::new(config.max_concurrent_uploads.max(1))),
+        disk_write_limiter: Arc::new(Semaphore::new(config.max_concurrent_disk_writes.max(1))),
+        rate_limits: Arc::new(ratelimit::RateLimits::from_config(&config)),
+        config: Arc::new(config),
+        hooks: upload_hooks,
+        upload_rules,
+        jwt,
+        encryption,
+        stored_copies: Arc::new(Mutex::new(())),
+    };
+
+    // Files stored before their encryption was recorded are looked at once
+    encryption::record_existing(&state.db, &state.upload_dir).await?;
+
+    // `needadrop rotate-encryption-key` re-encrypts the stored files and exits instead of serving
+    if std::env::args().nth(1).as_deref() == Some("rotate-encryption-key") {
+        let keys = state
+            .encryption
+            .as_deref()
+            .ok_or("rotate-encryption-key requires ENCRYPTION_KEY")?;
+        return encryption::rotate(keys, &state.db, &state.upload_dir).await;
+    }
+
+    // Apply the admin session timeouts before anyone can log in
+    auth::configure(&state.config);
+
+    // Set up (or switch off) the shared link behind /drop
+    public_drop::init(&state);
+
+    // Connect to the MQTT broker for event publishing (no-op unless configured)
+    mqtt::connect(&state.config);
+
+    // Start background workers for the persistent task queue
+    tasks::spawn_workers(state.clone());
+
+    // Run periodic housekeeping jobs (expiry events, reminders, ...)
+    scheduler::spawn(state.clone());
+
+    // Deactivate expired links and delete their files after the retention period
+    cleanup::spawn(state.clone());
+
+    // Poll the email-in mailbox (no-op unless configured)
+    emailin::spawn_poller(state.clone());
+
+    // Rate limit for requests that start an upload (per client address and link)
+    let upload_rate_limit = middleware::from_fn_with_state(state.clone(), ratelimit::limit_uploads);
 
     // Build the main application router with all routes and middleware
     let app = Router::new()
@@ -96,11 +276,81 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         // File upload routes for guests with valid tokens
         // GET: Display upload form  POST: Handle file upload
         .route("/upload/{token}", get(upload_form))
-        .route("/upload/{token}", post(handle_upload))
+        // The global body limit is disabled here; handle_upload enforces each link's own limit
+        .route(
+            "/upload/{token}",
+            post(handle_upload)
+                .layer(DefaultBodyLimit::disable())
+                .layer(upload_rate_limit.clone()),
+        )
+        // The same upload answered with per-file JSON results (used by the upload page)
+        .route(
+            "/upload/{token}/api",
+            post(handle_upload_json)
+                .layer(DefaultBodyLimit::disable())
+                .layer(upload_rate_limit.clone()),
+        )
+        // Server-side progress of an in-flight upload (polled when events aren't available)
+        .route("/upload/{token}/progress/{upload_id}", get(upload_progress))
+        // The same progress as server-sent events, pushed as bytes arrive
+        .route(
+            "/upload/{token}/progress/{upload_id}/events",
+            get(upload_progress_events),
+        )
+        // Resumable upload sessions (used by the service worker's offline queue)
+        .route(
+            "/upload/{token}/sessions",
+            post(resumable::create_session).layer(upload_rate_limit.clone()),
+        )
+        .route(
+            "/upload/{token}/sessions/{id}",
+            get(resumable::session_status)
+                .patch(resumable::append_chunk)
+                .delete(resumable::cancel_session),
+        )
+        // tus 1.0 endpoint for standard resumable upload clients
+        .route(
+            "/upload/{token}/tus",
+            post(tus::create_upload)
+                .layer(upload_rate_limit)
+                .options(tus::options),
+        )
+        .route(
+            "/upload/{token}/tus/{id}",
+            head(tus::upload_offset)
+                .patch(tus::append_data)
+                .delete(tus::terminate),
+        )
+        // Share links: files handed to guests for download
+        .route("/share/{token}", get(share_download_page))
+        .route("/share/{token}/files/{file_id}", get(download_shared_file))
+        // Public drop for internal networks (404 unless PUBLIC_DROP is enabled)
+        .route("/drop", get(public_drop::drop_page))
+        .route("/drop/verify", post(public_drop::verify_captcha))
+        // Installable web app: manifest, service worker and icon
+        .route("/manifest.webmanifest", get(pwa::manifest))
+        .route("/sw.js", get(pwa::service_worker))
+        .route("/icon.svg", get(pwa::icon))
         // Admin authentication routes
         // GET: Display login form  POST: Process login credentials
         .route("/login", get(login_form))
-        .route("/login", post(handle_login))
+        .route(
+            "/login",
+            post(handle_login).layer(middleware::from_fn_with_state(
+                state.clone(),
+                ratelimit::limit_logins,
+            )),
+        )
+        // Second login step for admins with two-factor authentication
+        .route(
+            "/login/verify",
+            post(handle_login_code).layer(middleware::from_fn_with_state(
+                state.clone(),
+                ratelimit::limit_logins,
+            )),
+        )
+        // Light/dark theme toggle (cookie, plus the admin's account when logged in)
+        .route("/theme", post(theme::set_theme))
         // === ADMIN ROUTES (authentication required) ===
         // All routes under /admin are protected by auth_middleware
         .nest(
@@ -112,20 +362,162 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .route("/links", get(admin_links)) // Display all upload links
                 .route("/links/create", get(create_link_form)) // Create new upload link form
                 .route("/links/create", post(handle_create_link)) // Process new upload link
+                .route("/links/{id}/edit", get(edit_link_form)) // Edit upload link form
+                .route("/links/{id}/edit", post(handle_edit_link)) // Save changes to an upload link
                 .route("/links/{id}/delete", post(delete_link)) // Delete upload link
+                .route("/links/{id}/toggle", post(toggle_link)) // Pause or resume an upload link
+                .route("/links/{id}/archive", post(archive_link)) // Hide a finished link
+                .route("/links/{id}/unarchive", post(unarchive_link)) // Restore an archived link
+                .route("/links/bulk-archive", post(bulk_archive_links)) // Archive or restore selected links
+                .route("/links/{id}/hold", post(hold_link)) // Place a legal hold on a link's uploads
+                .route("/links/{id}/release", post(release_link)) // Release a link's legal hold (owners only)
+                .route("/links/{id}/attempts", get(link_attempts)) // Uploads the link refused, and why
+                .route("/links/{id}/stats", get(link_stats)) // Uploads over time, totals and last activity
+                .route("/links/{id}/download-all", get(download_link_files)) // All of a link's files as one ZIP
+                // Projects grouping links per client or engagement
+                .route("/projects", get(admin_projects)) // Project list with totals
+                .route("/projects", post(handle_create_project)) // Create a project
+                .route("/projects/{id}/delete", post(delete_project)) // Delete a project, keeping its links
+                // Share links handing files to guests
+                .route("/shares", get(admin_shares)) // Share link list, or pick a share for an upload
+                .route("/shares", post(handle_create_share)) // Create a share link
+                .route("/shares/{id}", get(admin_share)) // Files offered on a share link
+                .route("/shares/{id}/uploads", post(add_share_upload)) // Offer an existing upload
+                .route(
+                    "/shares/{id}/files",
+                    post(upload_share_files).layer(DefaultBodyLimit::disable()),
+                ) // Upload the admin's own files to a share
+                .route(
+                    "/shares/{id}/files/{file_id}/delete",
+                    post(remove_share_file),
+                ) // Take a file off a share
+                .route("/shares/{id}/delete", post(delete_share)) // Delete a share link
+                // API keys for the REST API
+                .route("/api-keys", get(admin_api_keys)) // Key list
+                .route("/api-keys", post(handle_create_api_key)) // Create a key, shown once
+                .route("/api-keys/{id}/delete", post(revoke_api_key)) // Revoke a key
+                // Admin accounts and roles (owners only)
+                .route("/admins", get(admin_accounts)) // Account list
+                .route("/admins", post(handle_create_admin)) // Add an account
+                .route("/admins/{id}/role", post(change_admin_role)) // Change an account's role
+                .route("/admins/{id}/delete", post(delete_admin_account)) // Delete an account
+                .route(
+                    "/admins/{id}/two-factor/reset",
+                    post(reset_admin_two_factor),
+                ) // Turn off an account's two-factor authentication
+                // Webhook endpoints for server events
+                .route("/webhooks", get(admin_webhooks)) // Endpoint list
+                .route("/webhooks", post(handle_create_webhook)) // Add an endpoint
+                .route("/webhooks/{id}/delete", post(remove_webhook)) // Remove an endpoint
                 // File management
                 .route("/uploads", get(admin_uploads)) // View all uploaded files
+                .route("/search", get(admin_search)) // Full-text search over uploads and links
                 .route("/uploads/{id}/download", get(download_file)) // Download specific file
+                .route("/uploads/{id}/preview", get(preview_upload)) // Show an image, PDF or text file in the browser
+                .route("/uploads/{id}/preview/content", get(preview_upload_content)) // The previewed image or PDF, inline
                 .route("/uploads/{id}/delete", post(delete_upload)) // Delete uploaded file
+                .route("/uploads/bulk", post(bulk_uploads)) // Delete or download selected files
+                .route("/uploads/{id}/star", post(star_upload)) // Star or unstar for the current admin
+                .route("/uploads/{id}/hold", post(hold_upload)) // Place a legal hold on a file
+                .route("/uploads/{id}/release", post(release_upload)) // Release a file's legal hold (owners only)
+                .route("/uploads/{id}/pii-reviewed", post(review_pii_findings)) // Dismiss a personal data warning after review
+                .route("/uploads/{id}/verify", post(verify_upload)) // Re-hash a file and compare it to the stored checksum
+                // Audit log of legal holds and data subject requests
+                .route("/audit", get(admin_audit_log)) // Recent audit log entries
+                // Data subject requests (GDPR)
+                .route("/gdpr", get(data_requests)) // Export, erasure and report check forms
+                .route("/gdpr/export", post(export_subject_data)) // Download a person's data report
+                .route("/gdpr/erase", post(erase_subject_data)) // Erase a person's data (owners only)
+                .route("/gdpr/verify", post(verify_report)) // Check a report's signature
+                // Background task queue
+                .route("/tasks", get(admin_tasks)) // Dead-letter view
+                .route("/tasks/{id}/retry", post(retry_task)) // Requeue a dead task
+                .route("/tasks/{id}/delete", post(discard_task)) // Discard a dead task
+                // Notification email texts
+                .route("/email-templates", get(admin_email_templates)) // List customizable emails
+                .route("/email-templates/{key}", get(edit_email_template)) // Template editor
+                .route("/email-templates/{key}", post(handle_email_template)) // Save or preview a template
+                .route("/email-templates/{key}/reset", post(reset_email_template)) // Restore the default text
                 // Admin account management
                 .route("/change-password", get(change_password_form)) // Password change form
                 .route("/change-password", post(handle_change_password)) // Process password change
+                .route("/preferences", get(preferences_form)) // Display preferences form
+                .route("/preferences", post(handle_preferences)) // Save display preferences
+                .route("/two-factor", get(two_factor_form)) // Two-factor status and setup
+                .route("/two-factor/enable", post(handle_enable_two_factor)) // Turn two-factor on with a verified code
+                .route("/two-factor/disable", post(handle_disable_two_factor)) // Turn two-factor off (password required)
+                // Posts must carry the session's CSRF token (checked after authentication)
+                .route_layer(middleware::from_fn(csrf_middleware))
                 // Apply authentication middleware to all nested routes
                 // This ensures only logged-in admins can access these endpoints
                 .route_layer(middleware::from_fn(auth_middleware)),
         )
         // Logout route (available to authenticated users)
-        .route("/logout", post(logout))
+        .route(
+            "/logout",
+            post(logout).layer(middleware::from_fn(csrf_middleware)),
+        )
+        // === INTEGRATION API (API key or bearer token required, disabled without either) ===
+        .route("/api/integrations/uploads", get(integrations::poll_uploads)) // New uploads since cursor
+        // === REST API (API key required, keys are created under /admin/api-keys) ===
+        .nest(
+            "/api/v1",
+            Router::new()
+                .route("/links", get(api::list_links).post(api::create_link)) // List or create links
+                .route(
+                    "/links/{id}",
+                    get(api::get_link)
+                        .patch(api::update_link)
+                        .delete(api::delete_link),
+                ) // One link
+                .route(
+                    "/links/{id}/uploads",
+                    get(api::list_link_uploads)
+                        .post(api::upload_file)
+                        .layer(DefaultBodyLimit::disable()),
+                ) // A link's files; POST uploads one
+                .route("/uploads", get(api::list_uploads)) // Recent uploads
+                .route("/uploads/{id}", get(api::get_upload)) // One upload
+                .route("/uploads/{id}/download", get(api::download_upload)) // File contents
+                .route_layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    api::require_api_key,
+                )),
+        )
+        // === CALENDAR FEED (token in URL, disabled without CALENDAR_FEED_TOKEN) ===
+        .route(
+            "/calendar/{token}/links.ics",
+            get(calendar::link_expiry_feed),
+        );
+
+    // Optional GraphQL API (built with `--features graphql`)
+    // GET: GraphiQL explorer  POST: Execute queries (admin session or bearer token required)
+    #[cfg(feature = "graphql")]
+    let app = app.route(
+        "/api/graphql",
+        get(graphql::graphiql).post(graphql::graphql_handler),
+    );
+
+    // Optional S3-compatible API for rclone and backup agents (S3_API=write|read-write)
+    let app = if s3::is_enabled(&state) {
+        app.route(
+            "/s3/{bucket}",
+            get(s3::list_objects)
+                .head(s3::head_bucket)
+                .put(s3::create_bucket),
+        )
+        .route(
+            "/s3/{bucket}/{*key}",
+            get(s3::get_object)
+                .head(s3::head_object)
+                .put(s3::put_object)
+                .layer(DefaultBodyLimit::disable()),
+        )
+    } else {
+        app
+    };
+
+    let app = app
         // === STATIC FILE SERVING ===
         // Serve CSS, JS, images, and other static assets from the /static directory
         .nest_service("/static", ServeDir::new("static"))
@@ -133,25 +525,59 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         // Applied in reverse order (last added = first executed)
         .layer(
             ServiceBuilder::new()
+                // Give every request an ID, returned in the X-Request-Id header
+                .layer(middleware::from_fn(request_id::assign_request_id))
                 // HTTP request/response tracing for debugging and monitoring
-                .layer(TraceLayer::new_for_http())
+                .layer(TraceLayer::new_for_http().make_span_with(request_span))
                 // CORS policy - permissive for development (should be restrictive in production)
                 .layer(CorsLayer::permissive())
-                // Set maximum request body size to 100MB for file uploads
-                // This prevents memory exhaustion from extremely large uploads
-                .layer(DefaultBodyLimit::max(100 * 1024 * 1024)),
+                // Compress pages, JSON and static assets (stored files are sent as they are)
+                .layer(compression::layer(&state.config))
+                // Render bare error responses as themed pages (or JSON when requested)
+                .layer(middleware::from_fn(errors::error_pages))
+                // Set maximum request body size (MAX_BODY_SIZE_MB) for all other requests
+                // Guest uploads override this with a per-link limit (see the upload route)
+                .layer(DefaultBodyLimit::max(
+                    state.config.max_body_size_mb as usize * 1024 * 1024,
+                )),
         )
         // Attach the application state to the router
         // This makes the state available to all handlers via the State extractor
-        .with_state(state);
+        .with_state(state.clone());
+
+    // Kept for the listener and shutdown handling once the state moves
+    let config = state.config.clone();
+    let db = state.db.clone();
+
+    // With a mutual TLS admin listener, admin routes are only served there
+    let app = if mtls::is_enabled(&state.config) {
+        mtls::spawn(state, app.clone()).await?;
+        app.layer(middleware::from_fn(mtls::refuse_admin_routes))
+    } else {
+        app
+    };
 
     // Log server startup
-    info!("Starting server on http://localhost:3000");
+    let addr = std::net::SocketAddr::new(config.bind_address, config.port);
+    info!("Starting server on http://{}", addr);
 
     // Create TCP listener and start the server
-    // Binds to all interfaces (0.0.0.0) on port 3000
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
-    axum::serve(listener, app).await?;
+    // Binds to BIND_ADDRESS and PORT (all interfaces, port 3000 by default), unless systemd passes a socket
+    // Peer addresses are kept so uploads can record where they came from
+    let listener = listener::bind(&config, addr)?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    // On SIGTERM stop accepting connections, but let uploads in flight finish
+    .with_graceful_shutdown({
+        let db = db.clone();
+        async move { listener::shutdown_signal(&config, db).await }
+    })
+    .await?;
+
+    info!("All requests finished, shutting down");
+    database::close(&db);
 
     Ok(())
 }
@@ -160,8 +586,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 ///
 /// Returns the main index page with application information and links to admin login.
 /// This is the only page accessible without any authentication.
-async fn index() -> impl IntoResponse {
-    templates::IndexTemplate.into_response()
+async fn index(lang: i18n::Lang, theme: theme::Theme) -> impl IntoResponse {
+    templates::IndexTemplate { lang, theme }.into_response()
 }
 
 /// Initialize the structured logging system
@@ -177,6 +603,10 @@ async fn index() -> impl IntoResponse {
 /// - `RUST_LOG=debug` for detailed debugging
 /// - `RUST_LOG=warn` for warnings and errors only
 /// - `RUST_LOG=needadrop=debug,info` for module-specific levels
+///
+/// With `LOG_FORMAT=json` every event is written as one JSON object per line,
+/// with the fields of its request span (method, path, request ID) alongside,
+/// for log collectors such as Loki or Elasticsearch.
 fn init_logging() {
     use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
@@ -185,17 +615,55 @@ fn init_logging() {
     let env_filter =
         EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("needadrop=info,info"));
 
+    let json = std::env::var("LOG_FORMAT").is_ok_and(|format| format.trim() == "json");
+
     // Build and initialize the subscriber with formatting and filtering
-    tracing_subscriber::registry()
-        .with(
-            fmt::layer()
-                .with_target(true) // Include module names in output
-                .with_thread_ids(true) // Include thread IDs for async debugging
-                .with_file(true) // Include source file names
-                .with_line_number(true), // Include line numbers
-        )
-        .with(env_filter)
-        .init();
+    let registry = tracing_subscriber::registry().with(env_filter);
+    if json {
+        registry
+            .with(
+                fmt::layer()
+                    .json()
+                    .with_current_span(true) // Include the request span's fields
+                    .with_span_list(false) // Outer spans only repeat them
+                    .with_target(true)
+                    .with_thread_ids(true)
+                    .with_file(true)
+                    .with_line_number(true),
+            )
+            .init();
+    } else {
+        registry
+            .with(
+                fmt::layer()
+                    .with_target(true) // Include module names in output
+                    .with_thread_ids(true) // Include thread IDs for async debugging
+                    .with_file(true) // Include source file names
+                    .with_line_number(true), // Include line numbers
+            )
+            .init();
+    }
 
-    info!("Logging system initialized with structured output");
+    info!(json, "Logging system initialized with structured output");
+}
+
+/// Tracing span around each request, so its log events carry the request ID
+///
+/// The ID is in the `X-Request-Id` header, put there by the reverse proxy or
+/// by [`request_id::assign_request_id`].
+fn request_span(request: &axum::extract::Request) -> tracing::Span {
+    let span = tracing::info_span!(
+        "request",
+        method = %request.method(),
+        path = %request.uri().path(),
+        request_id = tracing::field::Empty,
+    );
+    if let Some(request_id) = request
+        .headers()
+        .get(&request_id::X_REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+    {
+        span.record("request_id", request_id);
+    }
+    span
 }
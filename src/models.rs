@@ -4,7 +4,7 @@
 //! All models implement Serialize/Deserialize for JSON API compatibility
 //! and database operations.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 
 /// Upload Link Model
@@ -17,87 +17,1189 @@ use serde::{Deserialize, Serialize};
 /// - Quota system prevents storage abuse
 /// - Time-based expiration for access control
 /// - Active/inactive states for link management
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UploadLink {
     /// Unique identifier for the upload link (UUID)
     pub id: String,
 
-    /// Public token used in URLs (UUID) - safe to expose to guests
-    pub token: String,
+    /// Public token used in URLs (UUID) - safe to expose to guests
+    pub token: String,
+
+    /// Human-readable name for the link (set by admin)
+    pub name: String,
+
+    /// Total quota in bytes - maximum size of all files together
+    pub total_quota: i64,
+
+    /// Remaining quota in bytes - decreases with each upload
+    pub remaining_quota: i64,
+
+    /// Optional expiration time - link becomes invalid after this time
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// When the link was created
+    pub created_at: DateTime<Utc>,
+
+    /// Whether the link is active (admin can deactivate without deleting)
+    pub is_active: bool,
+
+    /// Optional bandwidth limit in KB/s, shared by all uploads and downloads on this link
+    pub rate_limit_kbps: Option<i64>,
+
+    /// Folder on the WebDAV server (Nextcloud/ownCloud) that received files are pushed to
+    pub webdav_folder: Option<String>,
+
+    /// Email address of the invited guest, used for reminders
+    pub guest_email: Option<String>,
+
+    /// Language code the guest upload page is forced to (None = negotiate)
+    pub language: Option<String>,
+
+    /// Logo shown on the guest upload page instead of the default icon
+    pub logo_url: Option<String>,
+
+    /// Accent color (`#rrggbb`) of the guest upload page
+    pub accent_color: Option<String>,
+
+    /// Whether this is the shared link behind the public drop URL (`/drop`)
+    pub is_public: bool,
+
+    /// Project the link is filed under, if any
+    pub project_id: Option<String>,
+
+    /// When the link was archived; archived links are hidden from the default
+    /// admin lists and accept no more uploads, but their files stay available
+    pub archived_at: Option<DateTime<Utc>>,
+
+    /// What happens when a file is uploaded under a name that already exists on the link
+    pub collision_policy: CollisionPolicy,
+
+    /// Whether the link's uploads are under legal hold and must not be deleted
+    pub legal_hold: bool,
+
+    /// Command run after each upload on this link (see [`crate::commands`])
+    pub post_upload_command: Option<String>,
+
+    /// Bytes a single guest may upload to this link (None = only the link's quota applies)
+    pub guest_quota: Option<i64>,
+
+    /// Number of files the link accepts in total (None = no limit)
+    pub max_uploads: Option<i64>,
+
+    /// Number of files received so far; overwritten versions give theirs back
+    pub uploads_used: i64,
+
+    /// Bytes the link accepts in any 24 hours (None = no rolling limit)
+    pub daily_quota: Option<i64>,
+
+    /// File types the link accepts, e.g. `image/*, .pdf` (None = any type; see [`crate::filetypes`])
+    pub allowed_file_types: Option<String>,
+
+    /// Whether the admin is emailed about each file received (see [`crate::notifications`])
+    pub notify_on_upload: bool,
+
+    /// Largest single file the link accepts in bytes (None = only the quota applies)
+    pub per_file_limit: Option<i64>,
+
+    /// Days files are kept after upload before the cleanup job deletes them (None = kept until deleted)
+    pub retention_days: Option<i64>,
+
+    /// Note from the admin shown on the guest upload page, e.g. what to send
+    pub guest_note: Option<String>,
+}
+
+/// Outcome of charging a new file to its link (see [`crate::database::reserve_upload`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reservation {
+    /// The file's size and place were taken off the link
+    Reserved,
+
+    /// The link has fewer bytes left than the file needs
+    QuotaExhausted,
+
+    /// The link accepts no more files, or no longer exists
+    FilesExhausted,
+}
+
+/// Handling of uploads whose filename already exists on the same link
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CollisionPolicy {
+    /// Keep the new file as the next version of the existing one
+    #[default]
+    KeepBoth,
+
+    /// Replace the latest version with the new file
+    OverwriteLatest,
+
+    /// Refuse the new file
+    RejectDuplicate,
+}
+
+impl CollisionPolicy {
+    /// All policies, in the order they are offered in forms
+    pub const ALL: [CollisionPolicy; 3] = [
+        CollisionPolicy::KeepBoth,
+        CollisionPolicy::OverwriteLatest,
+        CollisionPolicy::RejectDuplicate,
+    ];
+
+    /// Value stored in the database and submitted by forms
+    pub fn code(self) -> &'static str {
+        match self {
+            CollisionPolicy::KeepBoth => "keep-both",
+            CollisionPolicy::OverwriteLatest => "overwrite-latest",
+            CollisionPolicy::RejectDuplicate => "reject-duplicate",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<CollisionPolicy> {
+        CollisionPolicy::ALL
+            .into_iter()
+            .find(|policy| policy.code() == code.trim())
+    }
+}
+
+/// File Upload Model
+///
+/// Represents an uploaded file associated with an upload link.
+/// Files are stored with UUID-based names for security.
+///
+/// ## Storage Strategy
+/// - Original filename preserved for downloads
+/// - UUID-based stored filename prevents conflicts
+/// - Guest folder isolation (one folder per upload session)
+/// - MIME type detection and storage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileUpload {
+    /// Unique identifier for this file upload (UUID)
+    pub id: String,
+
+    /// Foreign key to the upload link that this file belongs to
+    pub link_id: String,
+
+    /// Original filename as uploaded by the user
+    pub original_filename: String,
+
+    /// UUID-based filename used for storage (prevents conflicts)
+    pub stored_filename: String,
+
+    /// File size in bytes
+    pub file_size: i64,
+
+    /// MIME type declared by the client during upload
+    pub mime_type: String,
+
+    /// MIME type recognized from the file's contents (see [`crate::filetypes`];
+    /// None = no known signature or stored before detection)
+    pub detected_mime: Option<String>,
+
+    /// When the file was uploaded
+    pub uploaded_at: DateTime<Utc>,
+
+    /// UUID-based folder where this file is stored (guest isolation)
+    pub guest_folder: String,
+
+    /// Mirror status ("pending", "replicated", "failed"); None when replication is off
+    pub replication_status: Option<String>,
+
+    /// Version among the uploads with the same filename on the link (1 = first upload)
+    pub version: i64,
+
+    /// Directory the file is stored in, relative to its volume (see [`crate::storage`])
+    pub storage_dir: String,
+
+    /// Storage root the file was written to (None = the upload directory)
+    pub volume: Option<String>,
+
+    /// Whether the file is under legal hold and must not be deleted
+    /// (a hold on its link applies as well)
+    pub legal_hold: bool,
+
+    /// Client address the file was uploaded from, if recorded
+    pub uploader_ip: Option<String>,
+
+    /// Sender address of an emailed file
+    pub uploader_email: Option<String>,
+
+    /// `User-Agent` of the browser or tool that sent the file, if recorded
+    pub uploader_user_agent: Option<String>,
+
+    /// Name the guest gave on the upload form, if any
+    pub uploader_name: Option<String>,
+
+    /// Message the guest sent along with the file (see [`UploadMessage`])
+    pub message_id: Option<String>,
+
+    /// Kinds of personal data found by the scan, comma-separated
+    /// (see [`crate::pii`]; empty = none found, None = not scanned)
+    pub pii_findings: Option<String>,
+
+    /// Whether an admin reviewed the personal data findings
+    pub pii_reviewed: bool,
+
+    /// Hex SHA-256 of the file's contents (None for files stored before hashing)
+    pub content_hash: Option<String>,
+
+    /// Virus scan verdict, e.g. `clean` (None = not scanned; see [`crate::antivirus`])
+    pub scan_result: Option<String>,
+
+    /// Whether the file on disk matched its hash at the last integrity check (None = never checked)
+    pub integrity_ok: Option<bool>,
+
+    /// When the file was last checked against its hash
+    pub integrity_checked_at: Option<DateTime<Utc>>,
+
+    /// Hex ID of the key the stored file is encrypted with (None = stored
+    /// unencrypted; see [`crate::encryption`])
+    pub encryption_key_id: Option<String>,
+}
+
+/// Administrator User Model
+///
+/// Represents an administrator account with password authentication.
+/// Passwords are stored as bcrypt hashes for security.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Admin {
+    /// Unique identifier for the admin user (UUID)
+    pub id: String,
+
+    /// Username for login (must be unique)
+    pub username: String,
+
+    /// Bcrypt hash of the password (never store plaintext passwords)
+    pub password_hash: String,
+
+    /// When the admin account was created
+    pub created_at: DateTime<Utc>,
+
+    /// Preferred display time zone (None = server default)
+    pub timezone: Option<String>,
+
+    /// Preferred light/dark theme (None = follow the theme cookie)
+    pub theme: Option<String>,
+
+    /// What the admin may do
+    pub role: AdminRole,
+
+    /// Base32 secret for two-factor codes (None = password only)
+    pub totp_secret: Option<String>,
+}
+
+/// What an admin account may do
+///
+/// Enforced for every admin route in [`crate::auth::auth_middleware`], and
+/// in the handlers of the owner-only actions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AdminRole {
+    /// Everything, including releasing legal holds, erasing personal data
+    /// and managing admin accounts
+    Owner,
+
+    /// Everything else: links, uploads, shares, keys and settings
+    #[default]
+    Manager,
+
+    /// Read-only: can browse and download, but not create, change or delete
+    Auditor,
+}
+
+impl AdminRole {
+    /// All roles, in the order they are offered in forms
+    pub const ALL: [AdminRole; 3] = [AdminRole::Owner, AdminRole::Manager, AdminRole::Auditor];
+
+    /// Value stored in the database and submitted by forms
+    pub fn code(self) -> &'static str {
+        match self {
+            AdminRole::Owner => "owner",
+            AdminRole::Manager => "manager",
+            AdminRole::Auditor => "auditor",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<AdminRole> {
+        AdminRole::ALL
+            .into_iter()
+            .find(|role| role.code() == code.trim())
+    }
+
+    /// Owners may release legal holds, erase data and manage admins
+    pub fn is_owner(self) -> bool {
+        self == AdminRole::Owner
+    }
+
+    /// Auditors may look but not touch
+    pub fn is_read_only(self) -> bool {
+        self == AdminRole::Auditor
+    }
+}
+
+/// Background Task Model
+///
+/// A unit of deferred work stored in the database so it survives restarts.
+/// Tasks are retried with exponential backoff and end up in the dead-letter
+/// state ("dead") once they run out of attempts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackgroundTask {
+    /// Unique identifier for the task (UUID)
+    pub id: String,
+
+    /// Short task type name (e.g. "remove_upload_file")
+    pub kind: String,
+
+    /// JSON-encoded task definition
+    pub payload: String,
+
+    /// One of "pending", "running", "done" or "dead"
+    pub status: String,
+
+    /// Number of failed attempts so far
+    pub attempts: i64,
+
+    /// Attempts allowed before the task is dead-lettered
+    pub max_attempts: i64,
+
+    /// Error message from the most recent failed attempt
+    pub last_error: Option<String>,
+
+    /// Output worth keeping from a successful run (e.g. of a post-upload command)
+    pub output: Option<String>,
+
+    /// Earliest time the task may run (used for retry backoff)
+    pub run_at: DateTime<Utc>,
+
+    /// When the task was enqueued
+    pub created_at: DateTime<Utc>,
+
+    /// When the task was last updated
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Email Template Model
+///
+/// A resumable upload in progress
+///
+/// Guests on unreliable connections send a file in chunks; the bytes received
+/// so far are kept in a partial file until `received` reaches `total_size`.
+#[derive(Debug, Clone)]
+pub struct UploadSession {
+    pub id: String,
+    pub link_id: String,
+    pub original_filename: String,
+    pub content_type: String,
+
+    /// Announced size of the whole file in bytes
+    pub total_size: i64,
+
+    /// Bytes stored so far; the next chunk must start at this offset
+    pub received: i64,
+
+    /// Last time a chunk arrived (stale sessions are purged)
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Audit Log Entry
+///
+/// A sensitive admin action, such as placing or releasing a legal hold.
+/// Entries are only ever added, never changed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub id: String,
+
+    /// Username of the admin who acted
+    pub actor: String,
+
+    /// What was done, e.g. "legal_hold.placed"
+    pub action: String,
+
+    /// Kind of record acted on ("upload" or "link")
+    pub target_type: String,
+
+    /// ID of the record acted on
+    pub target_id: String,
+
+    /// Human-readable context, such as the file or link name
+    pub detail: Option<String>,
+
+    pub created_at: DateTime<Utc>,
+}
+
+/// A note a guest wrote on the upload form, shared by the files sent with it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadMessage {
+    pub id: String,
+    pub link_id: String,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An upload that was turned away, kept so admins can tell a guest why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadAttempt {
+    pub id: String,
+    pub link_id: String,
+
+    /// Name of the refused file, when it got that far
+    pub filename: Option<String>,
+
+    /// Why it was refused, e.g. "too_large" or "refused"
+    pub reason: String,
+
+    /// Context such as the file size or the reason given by a rule
+    pub detail: Option<String>,
+
+    /// Client address the attempt came from, if known
+    pub uploader_ip: Option<String>,
+
+    /// ID of the request that was refused, as found in the server logs
+    pub request_id: Option<String>,
+
+    pub attempted_at: DateTime<Utc>,
+}
+
+/// Project Model
+///
+/// Groups upload links, and through them their uploads, per client or
+/// engagement. A project quota caps the total quota of its links.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Project {
+    pub id: String,
+
+    /// Unique display name, e.g. the client's name
+    pub name: String,
+
+    /// Upper bound on the summed quota of the project's links (None = unlimited)
+    pub quota_bytes: Option<i64>,
+
+    pub created_at: DateTime<Utc>,
+}
+
+/// A project with its totals, as shown on the projects page
+#[derive(Debug, Clone)]
+pub struct ProjectStats {
+    pub project: Project,
+
+    /// Number of links filed under the project
+    pub link_count: i64,
+
+    /// Summed quota of the project's links in bytes
+    pub allocated_bytes: i64,
+
+    /// Number of files received on the project's links
+    pub upload_count: i64,
+
+    /// Total size of those files in bytes
+    pub used_bytes: i64,
+}
+
+/// Create project form data
+#[derive(Debug, Deserialize)]
+pub struct CreateProjectForm {
+    pub name: String,
+
+    /// Optional project quota in megabytes (empty = unlimited)
+    #[serde(default, deserialize_with = "deserialize_optional_int")]
+    pub quota_mb: Option<i32>,
+}
+
+/// Share link for sending files to a guest
+///
+/// The reverse of an upload link: the admin picks uploaded files, or
+/// uploads files of their own, and the guest downloads them through a
+/// secret token. A share can expire and limit the number of downloads.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareLink {
+    pub id: String,
+
+    /// Secret token in the share URL (`/share/<token>`)
+    pub token: String,
+
+    /// Display name, shown to the guest
+    pub name: String,
+
+    /// When the share stops working (None = never)
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// Total downloads allowed across all files (None = unlimited)
+    pub max_downloads: Option<i64>,
+
+    /// Downloads so far
+    pub download_count: i64,
+
+    pub created_at: DateTime<Utc>,
+}
+
+/// A file offered on a share link
+///
+/// Either an existing upload (`upload_id`) or a file the admin uploaded to
+/// the share (`stored_filename`, kept in the share's own directory). Name,
+/// size and type are copied when the file is added.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedFile {
+    pub id: String,
+    pub share_link_id: String,
+    pub upload_id: Option<String>,
+    pub stored_filename: Option<String>,
+    pub original_filename: String,
+    pub file_size: i64,
+    pub mime_type: String,
+    pub added_at: DateTime<Utc>,
+
+    /// Hex ID of the key a file the admin uploaded is encrypted with
+    /// (None = unencrypted; shared uploads follow their upload's)
+    pub encryption_key_id: Option<String>,
+}
+
+/// A share link with the number and size of its files, as listed for admins
+#[derive(Debug, Clone)]
+pub struct ShareLinkSummary {
+    pub link: ShareLink,
+    pub file_count: i64,
+    pub total_bytes: i64,
+}
+
+/// Create share link form data
+#[derive(Debug, Deserialize)]
+pub struct CreateShareLinkForm {
+    pub name: String,
+
+    /// Optional expiration time in hours (empty = never expires)
+    #[serde(default, deserialize_with = "deserialize_optional_int")]
+    pub expires_in_hours: Option<i32>,
+
+    /// Optional limit on the number of downloads (empty = unlimited)
+    #[serde(default, deserialize_with = "deserialize_optional_int")]
+    pub max_downloads: Option<i32>,
+
+    /// Upload to share right away (set when coming from the uploads list)
+    #[serde(default)]
+    pub upload_id: Option<String>,
+}
+
+/// Adds an existing upload to a share link
+#[derive(Debug, Deserialize)]
+pub struct ShareUploadForm {
+    pub upload_id: String,
+}
+
+/// `?upload=<id>` of the share list: the upload the admin wants to share
+#[derive(Debug, Deserialize)]
+pub struct ShareQuery {
+    #[serde(default)]
+    pub upload: Option<String>,
+}
+
+/// Key a script or CI job uses to call the REST API (see [`crate::api`])
+///
+/// Only a hash of the key is stored; the key itself is shown once, when it
+/// is created.
+#[derive(Debug, Clone)]
+pub struct ApiKey {
+    pub id: String,
+
+    /// Label telling keys apart, e.g. the job that uses it
+    pub name: String,
+
+    /// First characters of the key, to recognize it in the list
+    pub prefix: String,
+
+    pub created_at: DateTime<Utc>,
+
+    /// When the key last authenticated a request (None = never used)
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// Create admin account form data
+#[derive(Debug, Deserialize)]
+pub struct CreateAdminForm {
+    pub username: String,
+    pub password: String,
+
+    /// Role code (see [`AdminRole::code`])
+    pub role: String,
+}
+
+/// Change admin role form data
+#[derive(Debug, Deserialize)]
+pub struct AdminRoleForm {
+    /// Role code (see [`AdminRole::code`])
+    pub role: String,
+}
+
+/// Create API key form data
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyForm {
+    pub name: String,
+}
+
+/// Endpoint that events are POSTed to (see [`crate::webhooks`])
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+
+    /// Key the payload signature is computed with
+    pub secret: String,
+
+    /// Comma-separated events delivered to this endpoint (None = every event)
+    pub events: Option<String>,
+
+    pub created_at: DateTime<Utc>,
+}
+
+impl Webhook {
+    /// Whether this endpoint subscribed to an event
+    pub fn wants(&self, event: &str) -> bool {
+        match &self.events {
+            Some(events) => events.split(',').any(|wanted| wanted.trim() == event),
+            None => true,
+        }
+    }
+}
+
+/// Create webhook form data
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookForm {
+    pub url: String,
+
+    /// Signing secret (empty = generate one)
+    #[serde(default)]
+    pub secret: Option<String>,
+
+    /// Comma-separated event names (empty = every event)
+    #[serde(default)]
+    pub events: Option<String>,
+}
+
+/// Matches of an admin search
+#[derive(Debug, Default)]
+pub struct SearchResults {
+    /// Matching uploads with the name of their link
+    pub uploads: Vec<(FileUpload, String)>,
+
+    pub links: Vec<UploadLink>,
+}
+
+/// `?q=` of the admin search
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    #[serde(default)]
+    pub q: String,
+}
+
+/// Filters accepted by the admin link and upload lists
+/// (`?project=<id>&archived=true&starred=true&sort=name&dir=asc&page=2`)
+#[derive(Debug, Default, Deserialize)]
+pub struct ListFilter {
+    #[serde(default)]
+    pub project: Option<String>,
+
+    /// Show archived links instead of the current ones
+    #[serde(default)]
+    pub archived: bool,
+
+    /// Only show uploads the admin starred (uploads list only)
+    #[serde(default)]
+    pub starred: bool,
+
+    /// Only show uploads flagged for personal data and not yet reviewed (uploads list only)
+    #[serde(default)]
+    pub flagged: bool,
+
+    /// Column the list is sorted by
+    #[serde(default)]
+    pub sort: SortColumn,
+
+    /// Sort direction; the column's usual direction when missing
+    #[serde(default)]
+    pub dir: Option<SortDirection>,
+
+    /// Page of the list, starting at 1
+    #[serde(default)]
+    pub page: Option<i64>,
+}
+
+impl ListFilter {
+    pub fn list_sort(&self) -> ListSort {
+        ListSort {
+            column: self.sort,
+            direction: self.dir.unwrap_or(self.sort.default_direction()),
+        }
+    }
+}
+
+/// Column an admin list can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortColumn {
+    /// Link name or filename
+    Name,
+
+    /// Creation or upload time
+    #[default]
+    CreatedAt,
+
+    /// Link quota or file size
+    Size,
+}
+
+impl SortColumn {
+    /// Value used in list URLs
+    pub fn code(self) -> &'static str {
+        match self {
+            SortColumn::Name => "name",
+            SortColumn::CreatedAt => "created_at",
+            SortColumn::Size => "size",
+        }
+    }
+
+    /// Direction a list is sorted in when the column is first picked: names A-Z, newest and largest first
+    pub fn default_direction(self) -> SortDirection {
+        match self {
+            SortColumn::Name => SortDirection::Asc,
+            SortColumn::CreatedAt | SortColumn::Size => SortDirection::Desc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+impl SortDirection {
+    /// Value used in list URLs
+    pub fn code(self) -> &'static str {
+        match self {
+            SortDirection::Asc => "asc",
+            SortDirection::Desc => "desc",
+        }
+    }
+
+    /// SQL keyword for `ORDER BY`
+    pub fn sql(self) -> &'static str {
+        match self {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        }
+    }
+
+    pub fn reversed(self) -> SortDirection {
+        match self {
+            SortDirection::Asc => SortDirection::Desc,
+            SortDirection::Desc => SortDirection::Asc,
+        }
+    }
+}
+
+/// Order of an admin list (newest first by default)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListSort {
+    pub column: SortColumn,
+    pub direction: SortDirection,
+}
+
+impl Default for ListSort {
+    fn default() -> Self {
+        ListSort {
+            column: SortColumn::CreatedAt,
+            direction: SortDirection::Desc,
+        }
+    }
+}
+
+/// Which uploads the admin uploads page lists
+#[derive(Debug, Clone, Default)]
+pub struct UploadListQuery<'a> {
+    /// Only uploads of links filed under this project
+    pub project: Option<&'a str>,
+
+    /// Uploads of archived links instead of the current ones
+    pub archived: bool,
+
+    /// Only uploads this admin starred
+    pub starred_by: Option<&'a str>,
+
+    /// Only uploads awaiting personal data review
+    pub flagged: bool,
+
+    /// Order of the uploads within each link's group
+    pub sort: ListSort,
+}
+
+/// One page of the uploads list, grouped by link, with totals over all pages
+#[derive(Debug, Default)]
+pub struct UploadsPage {
+    pub groups: Vec<(UploadLink, Vec<FileUpload>)>,
+
+    /// Uploads matching the query across all pages
+    pub total: i64,
+
+    /// Combined size of those uploads in bytes
+    pub total_size: i64,
+}
+
+/// Position in an admin list shown a page at a time
+#[derive(Debug, Clone, Copy)]
+pub struct Pagination {
+    /// Current page, starting at 1
+    pub page: i64,
+
+    pub per_page: i64,
+
+    /// Rows across all pages
+    pub total: i64,
+}
+
+impl Pagination {
+    /// Start at `page` (first page when missing or out of range); the total is filled in once known
+    pub fn new(page: Option<i64>, per_page: i64) -> Pagination {
+        Pagination {
+            page: page.unwrap_or(1).max(1),
+            per_page,
+            total: 0,
+        }
+    }
+
+    /// Rows to skip to reach the current page
+    pub fn offset(&self) -> i64 {
+        (self.page - 1) * self.per_page
+    }
+
+    pub fn page_count(&self) -> i64 {
+        ((self.total + self.per_page - 1) / self.per_page).max(1)
+    }
+
+    pub fn has_previous(&self) -> bool {
+        self.page > 1
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.page < self.page_count()
+    }
+}
+
+/// Star toggle form data
+#[derive(Debug, Deserialize)]
+pub struct StarForm {
+    /// `true` to star the upload, `false` to remove the star
+    pub starred: bool,
+}
+
+/// Data subject request form data
+#[derive(Debug, Deserialize)]
+pub struct DataSubjectForm {
+    /// Email address, client address or guest folder identifying the person
+    pub subject: String,
+}
+
+/// Report signature check form data
+#[derive(Debug, Deserialize)]
+pub struct VerifyReportForm {
+    /// The report file's contents
+    pub report: String,
+}
+
+/// An admin-customized subject and body for one kind of notification email.
+/// Kinds without a stored template use the built-in defaults from
+/// [`crate::email_templates`].
+#[derive(Debug, Clone)]
+pub struct EmailTemplate {
+    /// Subject line, may contain `{{variable}}` placeholders
+    pub subject: String,
+
+    /// Plain-text body, may contain `{{variable}}` placeholders
+    pub body: String,
+
+    /// When the template was last edited
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Aggregate statistics shown on the admin dashboard
+///
+/// Computed with `COUNT`/`SUM` queries so the dashboard doesn't have to load
+/// every link and upload row.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DashboardStats {
+    /// Links that are active, not expired and still have quota left
+    pub active_links: i64,
+
+    /// Total number of uploaded files
+    pub total_uploads: i64,
+
+    /// Combined size of all uploaded files in bytes
+    pub total_upload_size: i64,
+}
+
+/// Usage of one link over its lifetime, for its statistics page
+#[derive(Debug, Clone, Default)]
+pub struct LinkStats {
+    /// Number of files received on the link
+    pub upload_count: i64,
+
+    /// Combined size of those files in bytes
+    pub total_bytes: i64,
+
+    /// Distinct client addresses the files came from
+    pub unique_uploader_ips: i64,
+
+    /// Uploads the link turned away
+    pub refused_count: i64,
+
+    /// When the most recent file arrived
+    pub last_upload_at: Option<DateTime<Utc>>,
+
+    /// When an upload was last refused
+    pub last_refused_at: Option<DateTime<Utc>>,
+
+    /// Files received per day over the recent past, oldest first
+    pub daily: Vec<DailyUploads>,
+}
+
+impl LinkStats {
+    pub fn formatted_total_bytes(&self) -> String {
+        format_file_size(self.total_bytes)
+    }
+
+    /// Most recent upload or refused upload, whichever came last
+    pub fn last_activity(&self) -> Option<DateTime<Utc>> {
+        self.last_upload_at.max(self.last_refused_at)
+    }
+
+    /// Most files received on one day of `daily`, for scaling the chart
+    pub fn busiest_day_count(&self) -> i64 {
+        self.daily
+            .iter()
+            .map(|day| day.upload_count)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Files received on one day (UTC)
+#[derive(Debug, Clone)]
+pub struct DailyUploads {
+    /// The day as `YYYY-MM-DD`
+    pub day: String,
+
+    pub upload_count: i64,
+
+    /// Combined size of the day's files in bytes
+    pub total_bytes: i64,
+}
+
+impl DailyUploads {
+    pub fn formatted_size(&self) -> String {
+        format_file_size(self.total_bytes)
+    }
+}
+
+/// Space used on one storage volume, for the admin dashboard
+#[derive(Debug, Clone)]
+pub struct VolumeUsage {
+    /// Root directory of the volume
+    pub path: String,
+
+    /// Number of uploaded files stored on the volume
+    pub file_count: i64,
+
+    /// Combined size of those files in bytes
+    pub stored_bytes: i64,
+
+    /// Free space of the file system, if it could be determined
+    pub free_bytes: Option<u64>,
+
+    /// Size of the file system, if it could be determined
+    pub total_bytes: Option<u64>,
+}
+
+impl VolumeUsage {
+    pub fn formatted_stored(&self) -> String {
+        format_file_size(self.stored_bytes)
+    }
+
+    pub fn formatted_free(&self) -> Option<String> {
+        self.free_bytes.map(|bytes| format_file_size(bytes as i64))
+    }
 
-    /// Human-readable name for the link (set by admin)
-    pub name: String,
+    pub fn formatted_total(&self) -> Option<String> {
+        self.total_bytes.map(|bytes| format_file_size(bytes as i64))
+    }
 
-    /// Total quota in bytes - maximum total file size allowed
-    pub max_file_size: i64,
+    /// Percentage of the file system in use, if known
+    pub fn used_percent(&self) -> Option<u64> {
+        match (self.free_bytes, self.total_bytes) {
+            (Some(free), Some(total)) if total > 0 => Some((total - free.min(total)) * 100 / total),
+            _ => None,
+        }
+    }
 
-    /// Remaining quota in bytes - decreases with each upload
-    pub remaining_quota: i64,
+    /// Whether the file system is at least 90% full
+    pub fn is_nearly_full(&self) -> bool {
+        self.used_percent().is_some_and(|percent| percent >= 90)
+    }
+}
 
-    /// Optional expiration time - link becomes invalid after this time
-    pub expires_at: Option<DateTime<Utc>>,
+/// Criteria for searching uploaded files
+///
+/// Every field except the paging parameters is optional; unset fields match
+/// everything.
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "graphql"), allow(dead_code))]
+pub struct UploadFilter {
+    /// Only uploads made through this link
+    pub link_id: Option<String>,
 
-    /// When the link was created
-    pub created_at: DateTime<Utc>,
+    /// MIME type prefix, e.g. "image/" or "application/pdf"
+    pub mime_type_prefix: Option<String>,
 
-    /// Whether the link is active (admin can deactivate without deleting)
-    pub is_active: bool,
+    /// Case-insensitive substring of the original filename
+    pub filename_contains: Option<String>,
+
+    /// Minimum file size in bytes
+    pub min_size: Option<i64>,
+
+    /// Maximum file size in bytes
+    pub max_size: Option<i64>,
+
+    /// Only uploads made at or after this time
+    pub uploaded_after: Option<DateTime<Utc>>,
+
+    /// Only uploads made before this time
+    pub uploaded_before: Option<DateTime<Utc>>,
+
+    /// Maximum number of results
+    pub limit: i64,
+
+    /// Number of results to skip
+    pub offset: i64,
 }
 
-/// File Upload Model
-///
-/// Represents an uploaded file associated with an upload link.
-/// Files are stored with UUID-based names for security.
+impl Default for UploadFilter {
+    fn default() -> Self {
+        Self {
+            link_id: None,
+            mime_type_prefix: None,
+            filename_contains: None,
+            min_size: None,
+            max_size: None,
+            uploaded_after: None,
+            uploaded_before: None,
+            limit: 100,
+            offset: 0,
+        }
+    }
+}
+
+/// Flat JSON description of a received upload for automation platforms
 ///
-/// ## Storage Strategy
-/// - Original filename preserved for downloads
-/// - UUID-based stored filename prevents conflicts
-/// - Guest folder isolation (one folder per upload session)
-/// - MIME type detection and storage
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FileUpload {
-    /// Unique identifier for this file upload (UUID)
+/// Zapier, Make and similar tools map flat objects straight onto their
+/// fields, so this shape is deliberately un-nested and kept stable: fields
+/// may be added, but never renamed or removed.
+#[derive(Debug, Clone, Serialize)]
+pub struct UploadEventPayload {
+    /// Event type, always "upload.created" for uploads
+    pub event: String,
+
+    /// Upload ID (stable, use for deduplication)
     pub id: String,
 
-    /// Foreign key to the upload link that this file belongs to
+    /// ID of the link the file was uploaded through
     pub link_id: String,
 
-    /// Original filename as uploaded by the user
-    pub original_filename: String,
+    /// Name of the link (empty if the link has been deleted)
+    pub link_name: String,
 
-    /// UUID-based filename used for storage (prevents conflicts)
-    pub stored_filename: String,
+    /// Token of the link (empty if the link has been deleted)
+    pub link_token: String,
+
+    /// Filename as supplied by the uploader
+    pub original_filename: String,
 
     /// File size in bytes
     pub file_size: i64,
 
-    /// MIME type detected during upload
+    /// File size formatted for humans (e.g. "1.5 MB")
+    pub file_size_human: String,
+
+    /// MIME type supplied by the uploader
     pub mime_type: String,
 
-    /// When the file was uploaded
-    pub uploaded_at: DateTime<Utc>,
+    /// Upload time (RFC 3339, UTC)
+    pub uploaded_at: String,
 
-    /// UUID-based folder where this file is stored (guest isolation)
-    pub guest_folder: String,
+    /// Admin download path, relative to the server's base URL
+    pub download_path: String,
 }
 
-/// Administrator User Model
+impl UploadEventPayload {
+    pub fn from_upload(upload: &FileUpload, link: Option<&UploadLink>) -> Self {
+        Self {
+            event: "upload.created".to_string(),
+            id: upload.id.clone(),
+            link_id: upload.link_id.clone(),
+            link_name: link.map(|link| link.name.clone()).unwrap_or_default(),
+            link_token: link.map(|link| link.token.clone()).unwrap_or_default(),
+            original_filename: upload.original_filename.clone(),
+            file_size: upload.file_size,
+            file_size_human: upload.formatted_size(),
+            mime_type: upload.mime_type.clone(),
+            uploaded_at: upload.uploaded_at.to_rfc3339(),
+            download_path: format!("/admin/uploads/{}/download", upload.id),
+        }
+    }
+}
+
+/// Flat JSON description of an upload link for automation platforms
 ///
-/// Represents an administrator account with password authentication.
-/// Passwords are stored as bcrypt hashes for security.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Admin {
-    /// Unique identifier for the admin user (UUID)
+/// Follows the same stability rules as [`UploadEventPayload`].
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkEventPayload {
+    /// Event type, e.g. "link.created" or "link.deleted"
+    pub event: String,
+
+    /// Link ID
     pub id: String,
 
-    /// Username for login (must be unique)
-    pub username: String,
+    /// Name of the link
+    pub name: String,
 
-    /// Bcrypt hash of the password (never store plaintext passwords)
-    pub password_hash: String,
+    /// Public token of the link
+    pub token: String,
 
-    /// When the admin account was created
-    pub created_at: DateTime<Utc>,
+    /// Total quota in bytes
+    pub total_quota: i64,
+
+    /// Size limit for a single file in bytes, if any
+    pub per_file_limit: Option<i64>,
+
+    /// Remaining quota in bytes
+    pub remaining_quota: i64,
+
+    /// Expiration time (RFC 3339, UTC), if any
+    pub expires_at: Option<String>,
+
+    /// Creation time (RFC 3339, UTC)
+    pub created_at: String,
+}
+
+impl LinkEventPayload {
+    pub fn from_link(event: &str, link: &UploadLink) -> Self {
+        Self {
+            event: event.to_string(),
+            id: link.id.clone(),
+            name: link.name.clone(),
+            token: link.token.clone(),
+            total_quota: link.total_quota,
+            per_file_limit: link.per_file_limit,
+            remaining_quota: link.remaining_quota,
+            expires_at: link.expires_at.map(|expires_at| expires_at.to_rfc3339()),
+            created_at: link.created_at.to_rfc3339(),
+        }
+    }
 }
 
 // === Form Models for HTML Forms ===
@@ -112,13 +1214,208 @@ pub struct CreateLinkForm {
     /// Human-readable name for the upload link
     pub name: String,
 
-    /// Maximum file size quota in megabytes (converted to bytes in handler)
-    pub max_file_size_mb: f64,
+    /// Total quota in megabytes (converted to bytes in handler)
+    pub total_quota_mb: f64,
+
+    /// Optional size limit for a single file in megabytes (empty = only the quota applies)
+    #[serde(default, deserialize_with = "deserialize_optional_int")]
+    pub per_file_limit_mb: Option<i32>,
 
     /// Optional expiration time in hours from now
     /// Uses custom deserializer to handle empty form fields
     #[serde(deserialize_with = "deserialize_optional_int")]
     pub expires_in_hours: Option<i32>,
+
+    /// Optional bandwidth limit in KB/s (empty = unlimited)
+    #[serde(default, deserialize_with = "deserialize_optional_int")]
+    pub rate_limit_kbps: Option<i32>,
+
+    /// Optional WebDAV folder to push received files to (empty = don't push)
+    #[serde(default)]
+    pub webdav_folder: Option<String>,
+
+    /// Optional email address of the invited guest (empty = none)
+    #[serde(default)]
+    pub guest_email: Option<String>,
+
+    /// Optional language code to force on the upload page (empty = automatic)
+    #[serde(default)]
+    pub language: Option<String>,
+
+    /// Optional logo URL for the upload page (empty = default icon)
+    #[serde(default)]
+    pub logo_url: Option<String>,
+
+    /// Optional accent color for the upload page, e.g. `#0a7cff` (empty = default theme)
+    #[serde(default)]
+    pub accent_color: Option<String>,
+
+    /// Optional project ID to file the link under (empty = none)
+    #[serde(default)]
+    pub project_id: Option<String>,
+
+    /// Filename collision policy code (empty = keep both)
+    #[serde(default)]
+    pub collision_policy: Option<String>,
+
+    /// Optional command to run after each upload (empty = none)
+    #[serde(default)]
+    pub post_upload_command: Option<String>,
+
+    /// Optional upload limit per guest in megabytes (empty = none)
+    #[serde(default, deserialize_with = "deserialize_optional_int")]
+    pub guest_quota_mb: Option<i32>,
+
+    /// Optional number of files the link accepts (empty = unlimited)
+    #[serde(default, deserialize_with = "deserialize_optional_int")]
+    pub max_uploads: Option<i32>,
+
+    /// Optional limit per 24 hours in megabytes (empty = none)
+    #[serde(default, deserialize_with = "deserialize_optional_int")]
+    pub daily_quota_mb: Option<i32>,
+
+    /// Optional comma-separated extensions and MIME types to accept (empty = any)
+    #[serde(default)]
+    pub allowed_file_types: Option<String>,
+
+    /// Email the admin about each file received (unchecked checkboxes are not submitted)
+    #[serde(default)]
+    pub notify_on_upload: bool,
+
+    /// Optional number of days files are kept after upload (empty = until deleted)
+    #[serde(default, deserialize_with = "deserialize_optional_int")]
+    pub retention_days: Option<i32>,
+
+    /// Optional note shown to guests on the upload page (empty = none)
+    #[serde(default)]
+    pub guest_note: Option<String>,
+}
+
+/// Form data for changing an existing upload link
+#[derive(Debug, Deserialize)]
+pub struct EditLinkForm {
+    /// New name of the link
+    pub name: String,
+
+    /// New quota in megabytes; must cover what was already uploaded
+    pub total_quota_mb: f64,
+
+    /// New size limit for a single file in megabytes (empty = only the quota applies)
+    #[serde(default, deserialize_with = "deserialize_optional_int")]
+    pub per_file_limit_mb: Option<i32>,
+
+    /// New expiration in hours from now (empty = keep the current one, 0 = never expires)
+    #[serde(default, deserialize_with = "deserialize_optional_int")]
+    pub expires_in_hours: Option<i32>,
+
+    /// New number of days files are kept after upload (empty = until deleted)
+    #[serde(default, deserialize_with = "deserialize_optional_int")]
+    pub retention_days: Option<i32>,
+
+    /// New note shown to guests on the upload page (empty = none)
+    #[serde(default)]
+    pub guest_note: Option<String>,
+
+    /// Whether the link accepts uploads (unchecked checkboxes are not submitted)
+    #[serde(default)]
+    pub is_active: bool,
+}
+
+/// Settings for a new upload link, as validated by the create link handler
+#[derive(Default)]
+pub struct NewUploadLink<'a> {
+    pub name: &'a str,
+
+    /// Total quota in bytes
+    pub total_quota: i64,
+
+    /// Size limit for a single file in bytes
+    pub per_file_limit: Option<i64>,
+
+    pub expires_at: Option<DateTime<Utc>>,
+    pub rate_limit_kbps: Option<i64>,
+    pub webdav_folder: Option<&'a str>,
+    pub guest_email: Option<&'a str>,
+    pub language: Option<&'a str>,
+    pub logo_url: Option<&'a str>,
+    pub accent_color: Option<&'a str>,
+    pub project_id: Option<&'a str>,
+    pub collision_policy: CollisionPolicy,
+    pub post_upload_command: Option<&'a str>,
+    pub guest_quota: Option<i64>,
+    pub max_uploads: Option<i64>,
+    pub daily_quota: Option<i64>,
+    pub allowed_file_types: Option<&'a str>,
+    pub notify_on_upload: bool,
+
+    /// Days files are kept after upload
+    pub retention_days: Option<i64>,
+
+    /// Note shown to guests on the upload page
+    pub guest_note: Option<&'a str>,
+}
+
+/// A received file to record as an upload
+pub struct NewFileUpload<'a> {
+    pub link_id: &'a str,
+    pub original_filename: &'a str,
+    pub stored_filename: &'a str,
+
+    /// File size in bytes
+    pub file_size: i64,
+
+    pub mime_type: &'a str,
+
+    /// MIME type recognized from the file's contents (None = no known signature)
+    pub detected_mime: Option<&'a str>,
+
+    pub guest_folder: &'a str,
+
+    /// Directory the file was written to, relative to its volume
+    pub storage_dir: &'a str,
+
+    /// Storage root the file was written to
+    pub volume: &'a str,
+
+    pub uploader: &'a Uploader,
+
+    /// Hex SHA-256 of the file's contents
+    pub content_hash: &'a str,
+
+    /// Virus scan verdict (None = not scanned)
+    pub scan_result: Option<&'a str>,
+
+    /// Hex ID of the key the stored file is encrypted with (None = unencrypted)
+    pub encryption_key_id: Option<&'a str>,
+}
+
+/// Who sent a file, as far as it is known; used to find a person's data on request
+#[derive(Debug, Clone, Default)]
+pub struct Uploader {
+    /// Client address of the upload request
+    pub ip: Option<String>,
+
+    /// Sender address of an emailed file
+    pub email: Option<String>,
+
+    /// ID of the upload request, for matching refused uploads with the logs
+    pub request_id: Option<String>,
+
+    /// `User-Agent` of the upload request
+    pub user_agent: Option<String>,
+
+    /// Name the guest gave on the upload form
+    pub name: Option<String>,
+}
+
+/// Query parameters accepted by the guest upload endpoint
+///
+/// The upload page generates a random upload ID so it can poll the
+/// progress endpoint while the file is being transferred.
+#[derive(Debug, Deserialize)]
+pub struct UploadQuery {
+    /// Client-generated upload ID (UUID) used for progress tracking
+    pub upload_id: Option<String>,
 }
 
 /// Custom deserializer for optional integer fields from HTML forms
@@ -167,10 +1464,65 @@ pub struct ChangePasswordForm {
     pub confirm_password: String,
 }
 
+/// Second login step for admins with two-factor authentication
+#[derive(Debug, Deserialize)]
+pub struct LoginCodeForm {
+    /// Pending login the password step handed out
+    pub token: String,
+
+    /// Code from the authenticator app
+    pub code: String,
+}
+
+/// Form data for turning two-factor authentication on
+#[derive(Debug, Deserialize)]
+pub struct EnableTwoFactorForm {
+    /// Secret shown on the setup page
+    pub secret: String,
+
+    /// Code generated from it, proving the app was set up
+    pub code: String,
+}
+
+/// Form data for turning two-factor authentication off
+#[derive(Debug, Deserialize)]
+pub struct DisableTwoFactorForm {
+    /// Current password, so an unattended session can't switch it off
+    pub password: String,
+}
+
+/// Display preferences form data
+#[derive(Debug, Deserialize)]
+pub struct PreferencesForm {
+    /// IANA time zone name (empty = server default)
+    #[serde(default)]
+    pub timezone: String,
+}
+
+/// Email template editor form data
+#[derive(Debug, Deserialize)]
+pub struct EmailTemplateForm {
+    pub subject: String,
+    pub body: String,
+    /// `preview` renders the submitted text without saving it
+    #[serde(default)]
+    pub action: String,
+}
+
 // === Business Logic Implementation ===
 // Methods that implement business rules and validation
 
 impl UploadLink {
+    /// Longest note an admin can leave for guests on the upload page
+    pub const MAX_GUEST_NOTE_CHARS: usize = 2000;
+
+    /// Address of the guest upload page under `PUBLIC_BASE_URL`
+    ///
+    /// Without a base URL only the path is known (`/upload/<token>`).
+    pub fn upload_url(&self, base_url: Option<&str>) -> String {
+        format!("{}/upload/{}", base_url.unwrap_or_default(), self.token)
+    }
+
     /// Check if the upload link has expired based on its expiration time
     ///
     /// Returns true if the link has an expiration time and it has passed.
@@ -187,42 +1539,304 @@ impl UploadLink {
     ///
     /// A link is valid if:
     /// - It is marked as active by admin
+    /// - It has not been archived
     /// - It has not expired
     /// - It has remaining quota (> 0 bytes)
+    /// - It has files left, if their number is limited
     pub fn is_valid(&self) -> bool {
-        self.is_active && !self.is_expired() && self.remaining_quota > 0
+        self.is_active
+            && !self.is_archived()
+            && !self.is_expired()
+            && self.remaining_quota > 0
+            && self.uploads_left().is_none_or(|left| left > 0)
+    }
+
+    /// Number of files the link still accepts (None = no limit)
+    pub fn uploads_left(&self) -> Option<i64> {
+        self.max_uploads.map(|max| (max - self.uploads_used).max(0))
+    }
+
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
     }
 
     /// Check if the upload link can accept a specific file size
     ///
-    /// Returns true if the link is valid and has enough remaining quota
-    /// to accommodate the specified file size.
+    /// Returns true if the link is valid, the file is within the per-file
+    /// limit and there is enough remaining quota to accommodate it.
     pub fn can_accept_file(&self, file_size: i64) -> bool {
-        self.is_valid() && self.remaining_quota >= file_size
+        self.is_valid() && file_size <= self.max_file_bytes()
+    }
+
+    /// Largest file the link accepts right now: the remaining quota, capped by the per-file limit
+    pub fn max_file_bytes(&self) -> i64 {
+        let remaining = self.remaining_quota.max(0);
+        self.per_file_limit
+            .map_or(remaining, |limit| limit.clamp(0, remaining))
     }
 
-    /// Format the maximum file size in a human-readable format
+    /// Whether the per-file limit, not the remaining quota, caps the next file
+    pub fn is_file_size_limited(&self) -> bool {
+        self.per_file_limit
+            .is_some_and(|limit| limit < self.remaining_quota)
+    }
+
+    /// Format the total quota in a human-readable format
     ///
     /// Converts bytes to appropriate units (B, KB, MB, GB) for display.
-    pub fn formatted_max_size(&self) -> String {
-        format_file_size(self.max_file_size)
+    pub fn formatted_total_quota(&self) -> String {
+        format_file_size(self.total_quota)
+    }
+
+    /// Format the per-file limit in a human-readable format, if the link has one
+    pub fn formatted_per_file_limit(&self) -> Option<String> {
+        self.per_file_limit.map(format_file_size)
+    }
+
+    /// Format the per-guest quota in a human-readable format, if the link has one
+    pub fn formatted_guest_quota(&self) -> Option<String> {
+        self.guest_quota.map(format_file_size)
+    }
+
+    /// Format the rolling 24-hour quota in a human-readable format, if the link has one
+    pub fn formatted_daily_quota(&self) -> Option<String> {
+        self.daily_quota.map(format_file_size)
+    }
+
+    /// Format the remaining quota in a human-readable format
+    pub fn formatted_remaining_quota(&self) -> String {
+        format_file_size(self.remaining_quota.max(0))
+    }
+
+    /// Bytes of the quota taken up by files already received
+    pub fn used_quota(&self) -> i64 {
+        (self.total_quota - self.remaining_quota).max(0)
+    }
+
+    /// Format the used part of the quota in a human-readable format
+    pub fn formatted_used_quota(&self) -> String {
+        format_file_size(self.used_quota())
+    }
+
+    /// The quota in megabytes, as entered in the link forms
+    pub fn total_quota_mb(&self) -> f64 {
+        self.total_quota as f64 / (1024.0 * 1024.0)
+    }
+
+    /// When the cleanup job deletes `upload`, if the link has a retention period
+    pub fn deletes_upload_at(&self, upload: &FileUpload) -> Option<DateTime<Utc>> {
+        self.retention_days
+            .map(|days| upload.uploaded_at + Duration::days(days))
+    }
+
+    /// The per-file limit in whole megabytes, as entered in the link forms
+    pub fn per_file_limit_mb(&self) -> Option<i64> {
+        self.per_file_limit.map(|limit| limit / (1024 * 1024))
     }
+
+    /// Check if less than `percent` percent of the quota is left
+    ///
+    /// A threshold of 0 disables the check.
+    pub fn is_quota_low(&self, percent: u8) -> bool {
+        percent > 0
+            && self.total_quota > 0
+            && self.remaining_quota * 100 < self.total_quota * percent as i64
+    }
+
+    /// Bandwidth limit in bytes per second, if the link is throttled
+    pub fn rate_limit_bytes_per_sec(&self) -> Option<u64> {
+        self.rate_limit_kbps
+            .filter(|kbps| *kbps > 0)
+            .map(|kbps| kbps as u64 * 1024)
+    }
+}
+
+impl Project {
+    /// Quota still free for new links, given the quota already handed out
+    ///
+    /// Returns None for projects without a quota.
+    pub fn unallocated_quota(&self, allocated_bytes: i64) -> Option<i64> {
+        self.quota_bytes
+            .map(|quota| (quota - allocated_bytes).max(0))
+    }
+}
+
+impl ShareLink {
+    /// Check if the share link has expired based on its expiration time
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| Utc::now() > expires_at)
+    }
+
+    /// Check if the share link can still be downloaded from
+    pub fn is_valid(&self) -> bool {
+        !self.is_expired() && self.downloads_left().is_none_or(|left| left > 0)
+    }
+
+    /// Number of downloads left (None = no limit)
+    pub fn downloads_left(&self) -> Option<i64> {
+        self.max_downloads
+            .map(|max| (max - self.download_count).max(0))
+    }
+}
+
+impl SharedFile {
+    /// Format the file size in a human-readable format
+    pub fn formatted_size(&self) -> String {
+        format_file_size(self.file_size)
+    }
+}
+
+impl ShareLinkSummary {
+    pub fn formatted_total(&self) -> String {
+        format_file_size(self.total_bytes)
+    }
+}
+
+impl ProjectStats {
+    pub fn formatted_allocated(&self) -> String {
+        format_file_size(self.allocated_bytes)
+    }
+
+    pub fn formatted_quota(&self) -> Option<String> {
+        self.project.quota_bytes.map(format_file_size)
+    }
+
+    pub fn formatted_used(&self) -> String {
+        format_file_size(self.used_bytes)
+    }
+}
+
+/// How a file can be shown in the browser on the admin preview page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewKind {
+    /// Raster image, shown in an `<img>`
+    Image,
+
+    /// PDF document, shown by the browser's viewer
+    Pdf,
+
+    /// Plain text, shown escaped on the page itself
+    Text,
+
+    /// Nothing safe to show; download only
+    None,
 }
 
+/// Image types browsers show without running anything (SVG is left out, it can carry scripts)
+const PREVIEW_IMAGE_TYPES: [&str; 5] = [
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "image/bmp",
+];
+
 impl FileUpload {
+    /// Detected type, or the declared one for files without a known signature,
+    /// lowercase and without parameters
+    fn effective_mime(&self) -> String {
+        self.detected_mime
+            .as_deref()
+            .unwrap_or(&self.mime_type)
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()
+    }
+
+    /// How the file can be previewed
+    pub fn preview_kind(&self) -> PreviewKind {
+        let mime = self.effective_mime();
+
+        if PREVIEW_IMAGE_TYPES.contains(&mime.as_str()) {
+            PreviewKind::Image
+        } else if mime == "application/pdf" {
+            PreviewKind::Pdf
+        } else if self.detected_mime.is_none()
+            && (mime.starts_with("text/")
+                || mime == "application/json"
+                || mime == "application/xml")
+        {
+            // Files with a known signature aren't text, whatever they were sent as
+            PreviewKind::Text
+        } else {
+            PreviewKind::None
+        }
+    }
+
+    /// Content type an image or PDF preview is served with, always one of a fixed list
+    pub fn preview_content_type(&self) -> Option<&'static str> {
+        let mime = self.effective_mime();
+        match self.preview_kind() {
+            PreviewKind::Image => PREVIEW_IMAGE_TYPES.into_iter().find(|image| *image == mime),
+            PreviewKind::Pdf => Some("application/pdf"),
+            PreviewKind::Text | PreviewKind::None => None,
+        }
+    }
+
+    /// Content type for handing out the raw file: a preview type when the
+    /// file has one, otherwise `application/octet-stream`
+    pub fn download_content_type(&self) -> &'static str {
+        self.preview_content_type()
+            .unwrap_or("application/octet-stream")
+    }
+
+    /// Whether the virus scanner checked the file and found nothing
+    pub fn passed_virus_scan(&self) -> bool {
+        self.scan_result.as_deref() == Some(crate::antivirus::RESULT_CLEAN)
+    }
+
+    /// Whether the personal data scan found something no admin has reviewed yet
+    pub fn needs_pii_review(&self) -> bool {
+        !self.pii_reviewed
+            && self
+                .pii_findings
+                .as_deref()
+                .is_some_and(|found| !found.is_empty())
+    }
+
+    /// Shortened content hash for display, with the full hash left for a tooltip
+    pub fn short_hash(&self) -> Option<&str> {
+        self.content_hash
+            .as_deref()
+            .map(|hash| &hash[..hash.len().min(12)])
+    }
+
+    /// Type recognized from the contents, when it differs from the declared one
+    pub fn detected_type_differs(&self) -> Option<&str> {
+        self.detected_mime
+            .as_deref()
+            .filter(|detected| !self.mime_type.eq_ignore_ascii_case(detected))
+    }
+
+    /// Whether the last integrity check found the file missing or changed
+    pub fn is_corrupted(&self) -> bool {
+        self.integrity_ok == Some(false)
+    }
+
+    /// Storage root holding the file: its volume, or `upload_dir` for older files
+    pub fn volume_root(&self, upload_dir: &std::path::Path) -> std::path::PathBuf {
+        self.volume
+            .as_deref()
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(|| upload_dir.to_path_buf())
+    }
+
     /// Construct the full filesystem path for this uploaded file
     ///
-    /// Combines the base upload directory with the guest folder and stored filename
+    /// Combines the file's volume with its storage directory and stored filename
     /// to create the complete path where the file is stored on disk.
     ///
     /// # Arguments
-    /// * `upload_dir` - Base directory where all uploads are stored
+    /// * `upload_dir` - Base directory, used for files stored before volumes existed
     ///
     /// # Returns
-    /// Complete path to the file: `upload_dir/guest_folder/stored_filename`
+    /// Complete path to the file: `volume/storage_dir/stored_filename`
     pub fn file_path(&self, upload_dir: &std::path::Path) -> std::path::PathBuf {
-        upload_dir
-            .join(&self.guest_folder)
+        self.volume_root(upload_dir)
+            .join(&self.storage_dir)
             .join(&self.stored_filename)
     }
 
@@ -0,0 +1,79 @@
+//! # MQTT Event Sink
+//!
+//! Publishes server events to an MQTT broker so home-lab automation
+//! (Node-RED, Home Assistant, ...) can react to incoming drops. Each event is
+//! sent as JSON to `<prefix>/<event>` with the dots of the event name turned
+//! into topic levels, e.g. `needadrop/upload/created`.
+//!
+//! The connection is kept open by a background task and re-established
+//! automatically. Messages are published with QoS 1 (at least once), so
+//! consumers should deduplicate on the payload's `id`.
+//!
+//! ## Configuration
+//! - `MQTT_HOST`: Broker host (unset = MQTT disabled)
+//! - `MQTT_PORT`: Broker port (default: `1883`)
+//! - `MQTT_USERNAME` / `MQTT_PASSWORD`: Optional credentials
+//! - `MQTT_TOPIC_PREFIX`: Topic prefix (default: `needadrop`)
+//! - `MQTT_CLIENT_ID`: Client ID (default: `needadrop`)
+//!
+//! Connections are plain TCP; run the broker on a trusted network or put a
+//! TLS-terminating proxy in front of it.
+
+use rumqttc::{AsyncClient, MqttOptions, QoS};
+use std::{sync::OnceLock, time::Duration};
+use tracing::{debug, info, warn};
+
+use crate::config::Config;
+
+/// Messages buffered while the broker is unreachable
+const QUEUE_CAPACITY: usize = 100;
+
+/// Delay before reconnecting after the connection dropped
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Connected client and topic prefix, set once MQTT is configured
+static PUBLISHER: OnceLock<(AsyncClient, String)> = OnceLock::new();
+
+/// Connect to the broker if MQTT is configured
+pub fn connect(config: &Config) {
+    let Some(host) = config.mqtt_host.as_deref() else {
+        return;
+    };
+
+    let mut options = MqttOptions::new(&config.mqtt_client_id, host, config.mqtt_port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if let Some(username) = config.mqtt_username.as_deref() {
+        options.set_credentials(username, config.mqtt_password.as_deref().unwrap_or(""));
+    }
+
+    let (client, mut eventloop) = AsyncClient::new(options, QUEUE_CAPACITY);
+    let prefix = config.mqtt_topic_prefix.trim_end_matches('/').to_string();
+    if PUBLISHER.set((client, prefix)).is_err() {
+        return;
+    }
+
+    info!(host = %host, port = config.mqtt_port, "Publishing events to MQTT broker");
+
+    // The event loop drives the connection; it must be polled for anything to be sent
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = eventloop.poll().await {
+                warn!(error = %e, "MQTT connection error, reconnecting");
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    });
+}
+
+/// Queue an event for publishing (no-op unless MQTT is configured)
+pub fn publish(event: &str, payload: Vec<u8>) {
+    let Some((client, prefix)) = PUBLISHER.get() else {
+        return;
+    };
+
+    let topic = format!("{}/{}", prefix, event.replace('.', "/"));
+    match client.try_publish(&topic, QoS::AtLeastOnce, false, payload) {
+        Ok(()) => debug!(topic = %topic, "Queued MQTT event"),
+        Err(e) => warn!(topic = %topic, error = %e, "Dropped MQTT event"),
+    }
+}
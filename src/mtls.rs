@@ -0,0 +1,320 @@
+//! # Admin Client Certificates
+//!
+//! For locked-down deployments the admin interface can be moved to its own
+//! HTTPS listener that only accepts clients presenting a certificate issued
+//! by a configured CA (mutual TLS). Guests keep using the main listener,
+//! which then answers admin routes with 404.
+//!
+//! The admin listener serves the same routes as the main one, so guest
+//! pages work there too. Admins still log in with their password, unless
+//! `ADMIN_TLS_CERT_LOGIN` is set: then a certificate whose common name (CN)
+//! is an admin's username logs that admin in.
+//!
+//! ## Configuration
+//! - `ADMIN_TLS_ADDR`: Address of the admin listener, e.g. `0.0.0.0:3443` (unset = admin routes on the main listener)
+//! - `ADMIN_TLS_CERT` / `ADMIN_TLS_KEY`: PEM certificate chain and private key of the admin listener
+//! - `ADMIN_TLS_CLIENT_CA`: PEM certificates of the CAs client certificates must be issued by
+//! - `ADMIN_TLS_CERT_LOGIN`: `true` to log admins in by their certificate's CN (default: `false`)
+
+use axum::{
+    extract::{connect_info::Connected, ConnectInfo, Request, State},
+    http::{header, HeaderMap, StatusCode, Uri},
+    middleware::{self, Next},
+    response::{IntoResponse, Redirect, Response},
+    serve::IncomingStream,
+    Router,
+};
+use std::{net::SocketAddr, path::Path, sync::Arc, time::Duration};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_rustls::{
+    rustls::{
+        crypto::ring,
+        pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+        server::WebPkiClientVerifier,
+        RootCertStore, ServerConfig,
+    },
+    server::TlsStream,
+    TlsAcceptor,
+};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    auth::{create_session, extract_session_id_from_cookies, get_session},
+    config::Config,
+    database::get_admin_by_username,
+    AppState,
+};
+
+/// How long a client may take to complete the TLS handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connections that completed the handshake and wait to be served
+const ACCEPT_QUEUE: usize = 64;
+
+/// Paths only served on the admin listener when it is enabled
+const ADMIN_PATHS: [&str; 4] = ["/admin", "/login", "/logout", "/api/graphql"];
+
+/// Whether an admin listener is configured
+pub fn is_enabled(config: &Config) -> bool {
+    config.admin_tls_addr.is_some()
+}
+
+/// The peer of an admin connection, with the CN of its client certificate
+#[derive(Debug, Clone)]
+pub struct TlsPeer {
+    pub addr: SocketAddr,
+    pub common_name: Option<String>,
+}
+
+/// Connections that passed the mutual TLS handshake
+///
+/// Handshakes run in their own tasks, so a slow client doesn't hold up others.
+pub struct TlsListener {
+    connections: mpsc::Receiver<(TlsStream<TcpStream>, TlsPeer)>,
+    local_addr: SocketAddr,
+}
+
+impl axum::serve::Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = TlsPeer;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.connections.recv().await {
+            Some(connection) => connection,
+            // The accept loop is gone; nothing will ever arrive
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> std::io::Result<Self::Addr> {
+        Ok(TlsPeer {
+            addr: self.local_addr,
+            common_name: None,
+        })
+    }
+}
+
+impl Connected<IncomingStream<'_, TlsListener>> for TlsPeer {
+    fn connect_info(stream: IncomingStream<'_, TlsListener>) -> Self {
+        stream.remote_addr().clone()
+    }
+}
+
+/// Start the admin listener serving `app`
+///
+/// Fails when the certificates can't be loaded or the address can't be
+/// bound, so a misconfigured server doesn't start without its admin
+/// interface.
+pub async fn spawn(state: AppState, app: Router) -> Result<(), String> {
+    let config = &state.config;
+    let addr = config.admin_tls_addr.clone().unwrap_or_default();
+    let tls_config = server_config(config)?;
+
+    let tcp = TcpListener::bind(&addr)
+        .await
+        .map_err(|e| format!("failed to bind admin listener {}: {}", addr, e))?;
+    let local_addr = tcp
+        .local_addr()
+        .map_err(|e| format!("failed to bind admin listener {}: {}", addr, e))?;
+
+    let (sender, connections) = mpsc::channel(ACCEPT_QUEUE);
+    tokio::spawn(accept_loop(tcp, TlsAcceptor::from(tls_config), sender));
+
+    let app = app
+        .layer(middleware::from_fn_with_state(state.clone(), admin_peer))
+        .into_make_service_with_connect_info::<TlsPeer>();
+    let listener = TlsListener {
+        connections,
+        local_addr,
+    };
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            error!(error = %e, "Admin listener stopped");
+        }
+    });
+
+    info!(addr = %local_addr, "Admin listener requires client certificates");
+    Ok(())
+}
+
+/// TLS settings of the admin listener: its certificate, and client certificates required
+fn server_config(config: &Config) -> Result<Arc<ServerConfig>, String> {
+    let (Some(cert_path), Some(key_path), Some(ca_path)) = (
+        config.admin_tls_cert.as_deref(),
+        config.admin_tls_key.as_deref(),
+        config.admin_tls_client_ca.as_deref(),
+    ) else {
+        return Err(
+            "ADMIN_TLS_CERT, ADMIN_TLS_KEY and ADMIN_TLS_CLIENT_CA are required with ADMIN_TLS_ADDR"
+                .to_string(),
+        );
+    };
+
+    let certs = read_certificates(cert_path)?;
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .map_err(|e| format!("failed to read {}: {}", key_path.display(), e))?;
+
+    let mut roots = RootCertStore::empty();
+    for ca in read_certificates(ca_path)? {
+        roots
+            .add(ca)
+            .map_err(|e| format!("invalid CA certificate in {}: {}", ca_path.display(), e))?;
+    }
+
+    let provider = Arc::new(ring::default_provider());
+    let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut server_config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(certs, key)
+        .map_err(|e| e.to_string())?;
+    server_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+    Ok(Arc::new(server_config))
+}
+
+fn read_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>, String> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    if certs.is_empty() {
+        return Err(format!("no certificates in {}", path.display()));
+    }
+    Ok(certs)
+}
+
+/// Accept connections and hand those that complete the handshake to the server
+async fn accept_loop(
+    tcp: TcpListener,
+    acceptor: TlsAcceptor,
+    sender: mpsc::Sender<(TlsStream<TcpStream>, TlsPeer)>,
+) {
+    loop {
+        let (stream, addr) = match tcp.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                warn!(error = %e, "Failed to accept admin connection");
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+
+        let acceptor = acceptor.clone();
+        let sender = sender.clone();
+        tokio::spawn(async move {
+            // Clients without a valid certificate fail here
+            let tls = match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(tls)) => tls,
+                Ok(Err(e)) => {
+                    debug!(peer = %addr, error = %e, "Admin TLS handshake failed");
+                    return;
+                }
+                Err(_) => {
+                    debug!(peer = %addr, "Admin TLS handshake timed out");
+                    return;
+                }
+            };
+            let common_name = tls
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|certs| certs.first())
+                .and_then(|cert| common_name(cert));
+            let _ = sender.send((tls, TlsPeer { addr, common_name })).await;
+        });
+    }
+}
+
+/// Common name in the subject of a certificate
+fn common_name(cert: &CertificateDer) -> Option<String> {
+    let (_, cert) = x509_parser::parse_x509_certificate(cert.as_ref()).ok()?;
+    let name = cert.subject().iter_common_name().next()?.as_str().ok()?;
+    Some(name.to_string())
+}
+
+/// Give admin-listener requests the peer address other handlers expect, and
+/// log admins in by certificate when enabled
+async fn admin_peer(State(state): State<AppState>, mut request: Request, next: Next) -> Response {
+    let Some(ConnectInfo(peer)) = request.extensions().get::<ConnectInfo<TlsPeer>>().cloned()
+    else {
+        return next.run(request).await;
+    };
+    request.extensions_mut().insert(ConnectInfo(peer.addr));
+
+    if state.config.admin_tls_cert_login {
+        // Only the URI and headers are held across awaits: the body isn't `Sync`
+        let login = certificate_login(&state, &peer, request.uri(), request.headers()).await;
+        if let Some(response) = login {
+            return response;
+        }
+    }
+    next.run(request).await
+}
+
+/// Start a session for the admin named by the client certificate, if there
+/// is no session yet and the request needs one
+async fn certificate_login(
+    state: &AppState,
+    peer: &TlsPeer,
+    uri: &Uri,
+    headers: &HeaderMap,
+) -> Option<Response> {
+    let path = uri.path();
+    if path != "/login" && !path.starts_with("/admin") {
+        return None;
+    }
+    let session_id = headers
+        .get(header::COOKIE)
+        .and_then(|header| header.to_str().ok())
+        .and_then(extract_session_id_from_cookies);
+    if let Some(session_id) = session_id {
+        if get_session(session_id).await.is_some() {
+            return None;
+        }
+    }
+
+    let common_name = peer.common_name.as_deref()?;
+    let admin = match get_admin_by_username(&state.db, common_name) {
+        Ok(admin) => admin?,
+        Err(e) => {
+            error!(common_name = %common_name, error = %e, "Failed to look up certificate admin");
+            return None;
+        }
+    };
+
+    info!(admin_id = %admin.id, username = %admin.username, peer = %peer.addr, "Admin logged in by client certificate");
+    let session_id = create_session(admin.id, admin.username, admin.role).await;
+
+    let target = if path == "/login" {
+        "/admin".to_string()
+    } else {
+        uri.to_string()
+    };
+    let mut response = Redirect::to(&target).into_response();
+    let cookie = format!(
+        "session_id={}; Path=/; HttpOnly; SameSite=Strict; Secure",
+        session_id
+    );
+    response
+        .headers_mut()
+        .insert(header::SET_COOKIE, cookie.parse().unwrap());
+    Some(response)
+}
+
+/// Hide admin routes on the main listener while the admin listener serves them
+pub async fn refuse_admin_routes(request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    let is_admin_path = ADMIN_PATHS
+        .iter()
+        .any(|prefix| path == *prefix || path.starts_with(&format!("{}/", prefix)));
+    if is_admin_path {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    next.run(request).await
+}
@@ -0,0 +1,92 @@
+//! # Admin Notifications
+//!
+//! Emails the admin when something happens on a link they asked to hear
+//! about. Emails are rendered from the customizable templates (see
+//! [`crate::email_templates`]) and queued as [`Task::SendEmail`], so they are
+//! sent in the background and retried with backoff when the mail server is
+//! unavailable; the upload itself never waits for SMTP.
+//!
+//! ## Configuration
+//! - `ADMIN_EMAIL`: Recipient (unset = no admin notifications)
+//! - `SMTP_*`: Mail server, see [`crate::mailer`]
+//!
+//! Links opt in with `notify_on_upload`, set when the link is created.
+
+use tracing::{debug, error, warn};
+
+use crate::{
+    database::get_file_upload_by_id,
+    datetime::DisplayTime,
+    email_templates, mailer,
+    models::UploadLink,
+    tasks::{enqueue, Task},
+    AppState,
+};
+
+/// Whether admin notifications can be delivered at all
+pub fn is_enabled(state: &AppState) -> bool {
+    mailer::is_configured(&state.config) && state.config.admin_email.is_some()
+}
+
+/// Queue the "file received" email for an upload on a link with notifications on
+///
+/// Failures are logged: the upload has already been stored.
+pub fn upload_received(state: &AppState, link: &UploadLink, upload_id: &str) {
+    if !link.notify_on_upload || !is_enabled(state) {
+        return;
+    }
+    let Some(admin_email) = &state.config.admin_email else {
+        return;
+    };
+
+    let upload = match get_file_upload_by_id(&state.db, upload_id) {
+        Ok(Some(upload)) => upload,
+        Ok(None) => {
+            debug!(upload_id = %upload_id, "Upload vanished before its notification was sent");
+            return;
+        }
+        Err(e) => {
+            warn!(upload_id = %upload_id, error = %e, "Failed to load upload for notification");
+            return;
+        }
+    };
+
+    let time = DisplayTime::from_config(&state.config);
+    let values = [
+        ("link_name", link.name.clone()),
+        ("filename", upload.original_filename.clone()),
+        ("file_size", upload.formatted_size()),
+        ("uploaded_at", time.format(upload.uploaded_at)),
+        ("guest_folder", upload.guest_folder.clone()),
+        (
+            "uploader_email",
+            upload.uploader_email.clone().unwrap_or_default(),
+        ),
+    ];
+
+    if let Some(task) = email_task(state, "upload_received", admin_email, &values) {
+        if let Err(e) = enqueue(state, &task) {
+            error!(upload_id = %upload_id, error = %e, "Failed to enqueue upload notification");
+        }
+    }
+}
+
+/// Render an email template into a send task, logging templates that fail to load
+pub fn email_task(
+    state: &AppState,
+    template: &str,
+    to: &str,
+    values: &[(&str, String)],
+) -> Option<Task> {
+    match email_templates::render(&state.db, template, values) {
+        Ok((subject, body)) => Some(Task::SendEmail {
+            to: to.to_string(),
+            subject,
+            body,
+        }),
+        Err(e) => {
+            error!(template = %template, error = %e, "Failed to render email template");
+            None
+        }
+    }
+}
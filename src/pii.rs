@@ -0,0 +1,185 @@
+//! # Personal Data Scanning
+//!
+//! Optional check of text-based uploads (plain text, CSV, JSON, XML, ...)
+//! for data that is likely personal: US social security numbers, IBANs and
+//! payment card numbers. Each upload is scanned by a background task after
+//! it arrives. Candidates found by pattern are validated (SSN number ranges,
+//! the IBAN checksum, the Luhn check digit) to keep false alarms down.
+//!
+//! Findings only flag the upload for compliance review on the uploads page;
+//! nothing is blocked or changed. An admin marks a flagged upload as
+//! reviewed once it has been looked at, which is recorded in the audit log.
+//!
+//! ## Configuration
+//! - `PII_SCAN`: `true` to scan new uploads (default: `false`)
+//! - `PII_SCAN_MAX_KB`: How much of each file is scanned, in KB (default: 10240)
+
+use regex::Regex;
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+use crate::encryption::{self, Keys};
+
+/// MIME types outside `text/*` whose content is text
+const TEXT_MIME_TYPES: [&str; 6] = [
+    "application/json",
+    "application/xml",
+    "application/csv",
+    "application/x-yaml",
+    "application/sql",
+    "application/x-ndjson",
+];
+
+/// Extensions of text files commonly uploaded with a generic MIME type
+const TEXT_EXTENSIONS: [&str; 13] = [
+    "txt", "csv", "tsv", "json", "xml", "md", "log", "yaml", "yml", "sql", "html", "eml", "vcf",
+];
+
+lazy_static::lazy_static! {
+    static ref SSN: Regex = Regex::new(r"\b(\d{3})-(\d{2})-(\d{4})\b").expect("valid SSN pattern");
+    static ref IBAN: Regex =
+        Regex::new(r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]){11,30}\b").expect("valid IBAN pattern");
+    static ref CARD: Regex = Regex::new(r"\b\d(?:[ -]?\d){12,18}\b").expect("valid card pattern");
+}
+
+/// A kind of personal data the scanner recognizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PiiKind {
+    Ssn,
+    Iban,
+    CreditCard,
+}
+
+impl PiiKind {
+    /// All kinds, in the order findings are listed
+    pub const ALL: [PiiKind; 3] = [PiiKind::Ssn, PiiKind::Iban, PiiKind::CreditCard];
+
+    /// Value stored in `file_uploads.pii_findings`
+    pub fn code(self) -> &'static str {
+        match self {
+            PiiKind::Ssn => "ssn",
+            PiiKind::Iban => "iban",
+            PiiKind::CreditCard => "credit-card",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<PiiKind> {
+        PiiKind::ALL
+            .into_iter()
+            .find(|kind| kind.code() == code.trim())
+    }
+
+    /// Whether the text holds at least one valid value of this kind
+    fn found_in(self, text: &str) -> bool {
+        match self {
+            PiiKind::Ssn => SSN
+                .captures_iter(text)
+                .any(|caps| is_valid_ssn(&caps[1], &caps[2], &caps[3])),
+            PiiKind::Iban => IBAN
+                .find_iter(text)
+                .any(|candidate| is_valid_iban(candidate.as_str())),
+            PiiKind::CreditCard => CARD
+                .find_iter(text)
+                .any(|candidate| is_valid_card_number(candidate.as_str())),
+        }
+    }
+}
+
+/// Whether an upload is text that can be scanned
+pub fn is_scannable(mime_type: &str, original_filename: &str) -> bool {
+    let mime_type = mime_type.to_ascii_lowercase();
+    let extension = Path::new(original_filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+
+    mime_type.starts_with("text/")
+        || TEXT_MIME_TYPES.contains(&mime_type.as_str())
+        || TEXT_EXTENSIONS.contains(&extension.as_str())
+}
+
+/// Scan the start of a file, returning the kinds of personal data found
+///
+/// Files that turn out to be binary (they contain NUL bytes) yield no findings.
+pub async fn scan_file(
+    keys: Option<&Keys>,
+    path: &Path,
+    key_id: Option<&str>,
+    max_bytes: u64,
+) -> std::io::Result<Vec<PiiKind>> {
+    let (file, _) = encryption::open(keys, path, key_id).await?;
+    let mut content = Vec::new();
+    file.take(max_bytes).read_to_end(&mut content).await?;
+
+    if content.contains(&0) {
+        return Ok(Vec::new());
+    }
+
+    let text = String::from_utf8_lossy(&content);
+    Ok(PiiKind::ALL
+        .into_iter()
+        .filter(|kind| kind.found_in(&text))
+        .collect())
+}
+
+/// Findings in the form stored with the upload (comma-separated codes, empty = clean)
+pub fn findings_code(kinds: &[PiiKind]) -> String {
+    kinds
+        .iter()
+        .map(|kind| kind.code())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// SSNs never start with 000, 666 or 9xx and have no all-zero group or serial
+fn is_valid_ssn(area: &str, group: &str, serial: &str) -> bool {
+    area != "000" && area != "666" && !area.starts_with('9') && group != "00" && serial != "0000"
+}
+
+/// ISO 13616 check: move the first four characters to the end, turn letters
+/// into numbers (A = 10) and the result modulo 97 must be 1
+fn is_valid_iban(candidate: &str) -> bool {
+    let iban: String = candidate.chars().filter(|c| !c.is_whitespace()).collect();
+    if !(15..=34).contains(&iban.len()) {
+        return false;
+    }
+
+    let rearranged = iban[4..].chars().chain(iban[..4].chars());
+    let mut remainder: u32 = 0;
+    for c in rearranged {
+        let Some(value) = c.to_digit(36) else {
+            return false;
+        };
+        let factor = if value < 10 { 10 } else { 100 };
+        remainder = (remainder * factor + value) % 97;
+    }
+    remainder == 1
+}
+
+/// Card numbers have 13 to 19 digits, a known leading digit and a valid Luhn check digit
+fn is_valid_card_number(candidate: &str) -> bool {
+    let digits: Vec<u32> = candidate.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) || !matches!(digits[0], 2..=6) {
+        return false;
+    }
+
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &digit)| {
+            if i % 2 == 1 {
+                let doubled = digit * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                digit
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
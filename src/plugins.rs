@@ -0,0 +1,212 @@
+//! # WASM Plugins
+//!
+//! Operator-supplied WebAssembly modules that act as [`UploadHook`]s, so
+//! uploads can be validated by custom rules without recompiling the server.
+//! Built with `--features wasm-plugins`.
+//!
+//! Plugins are sandboxed: they get no imports at all (no files, network or
+//! clock), each call runs in a fresh instance with a fuel budget and a
+//! memory cap, and a plugin that traps or runs out of fuel refuses the
+//! operation it was asked about.
+//!
+//! ## Plugin Interface
+//! A plugin exports its `memory`, an `alloc(len: i32) -> i32` function the
+//! server uses to pass it a JSON event, and any of these hooks:
+//! - `before_store(ptr: i32, len: i32) -> i64`: a file is about to be stored
+//! - `before_delete(ptr: i32, len: i32) -> i64`: an upload is about to be deleted
+//! - `after_store(ptr: i32, len: i32)`: a file was stored
+//!
+//! The `before_*` hooks return `0` to allow the operation. Anything else
+//! refuses it and points at a UTF-8 reason in the plugin's memory, packed as
+//! `(ptr << 32) | len`, which is shown to the sender or admin.
+//!
+//! ## Configuration
+//! - `WASM_PLUGINS`: Comma-separated paths of `.wasm` plugins, run in order
+//! - `WASM_PLUGIN_FUEL`: Instruction budget of one hook call (default: 10000000)
+//! - `WASM_PLUGIN_MEMORY_MB`: Memory a plugin may use, in MB (default: 16)
+
+use serde_json::json;
+use std::path::Path;
+use tracing::{error, info};
+use wasmtime::{Engine, InstancePre, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::{
+    config::Config,
+    hooks::{HookRejection, PendingUpload, UploadHook, UploadHooks},
+    models::{FileUpload, UploadLink},
+};
+
+/// Longest refusal reason read back from a plugin
+const MAX_REASON_BYTES: usize = 1024;
+
+/// One loaded plugin module
+pub struct WasmPlugin {
+    name: String,
+    engine: Engine,
+    instance_pre: InstancePre<StoreLimits>,
+    fuel: u64,
+    memory_bytes: usize,
+}
+
+/// Register every configured plugin as an upload hook
+///
+/// A plugin that fails to load stops the server, rather than quietly
+/// accepting uploads it was meant to check.
+pub fn load(config: &Config, hooks: UploadHooks) -> Result<UploadHooks, String> {
+    let mut engine_config = wasmtime::Config::new();
+    engine_config.consume_fuel(true);
+    let engine = Engine::new(&engine_config).map_err(|e| e.to_string())?;
+
+    let mut hooks = hooks;
+    for path in &config.wasm_plugins {
+        let plugin = WasmPlugin::load(&engine, path, config)
+            .map_err(|e| format!("failed to load plugin {}: {}", path.display(), e))?;
+        info!(plugin = %plugin.name, "WASM plugin loaded");
+        hooks = hooks.with(plugin);
+    }
+    Ok(hooks)
+}
+
+impl WasmPlugin {
+    fn load(engine: &Engine, path: &Path, config: &Config) -> Result<WasmPlugin, String> {
+        let module = Module::from_file(engine, path).map_err(|e| e.to_string())?;
+        for export in ["memory", "alloc"] {
+            if module.get_export(export).is_none() {
+                return Err(format!("missing export `{}`", export));
+            }
+        }
+
+        // No imports are defined, so a plugin asking for any can't be instantiated
+        let linker = Linker::new(engine);
+        let instance_pre = linker.instantiate_pre(&module).map_err(|e| e.to_string())?;
+
+        Ok(WasmPlugin {
+            name: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            engine: engine.clone(),
+            instance_pre,
+            fuel: config.wasm_plugin_fuel,
+            memory_bytes: (config.wasm_plugin_memory_mb as usize).saturating_mul(1024 * 1024),
+        })
+    }
+
+    /// Whether the plugin implements a hook
+    fn exports(&self, hook: &str) -> bool {
+        self.instance_pre.module().get_export(hook).is_some()
+    }
+
+    /// Run a hook in a fresh instance, returning its result (`0` for hooks without one)
+    ///
+    /// Reads the refusal reason while the instance is still alive.
+    fn call(&self, hook: &str, event: &serde_json::Value) -> Result<Option<String>, String> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.memory_bytes)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(self.fuel).map_err(|e| e.to_string())?;
+
+        let instance = self
+            .instance_pre
+            .instantiate(&mut store)
+            .map_err(|e| e.to_string())?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or("`memory` is not a memory")?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, "alloc")
+            .map_err(|e| e.to_string())?;
+
+        let input = event.to_string();
+        let len = i32::try_from(input.len()).map_err(|_| "event too large")?;
+        let ptr = alloc.call(&mut store, len).map_err(|e| e.to_string())?;
+        memory
+            .write(&mut store, ptr as u32 as usize, input.as_bytes())
+            .map_err(|e| e.to_string())?;
+
+        if hook == "after_store" {
+            let func = instance
+                .get_typed_func::<(i32, i32), ()>(&mut store, hook)
+                .map_err(|e| e.to_string())?;
+            func.call(&mut store, (ptr, len))
+                .map_err(|e| e.to_string())?;
+            return Ok(None);
+        }
+
+        let func = instance
+            .get_typed_func::<(i32, i32), i64>(&mut store, hook)
+            .map_err(|e| e.to_string())?;
+        let result = func
+            .call(&mut store, (ptr, len))
+            .map_err(|e| e.to_string())?;
+        if result == 0 {
+            return Ok(None);
+        }
+
+        let reason_ptr = (result as u64 >> 32) as usize;
+        let reason_len = (result as u64 & 0xffff_ffff) as usize;
+        let mut reason = vec![0; reason_len.min(MAX_REASON_BYTES)];
+        memory
+            .read(&store, reason_ptr, &mut reason)
+            .map_err(|e| e.to_string())?;
+        Ok(Some(String::from_utf8_lossy(&reason).into_owned()))
+    }
+
+    /// Ask a `before_*` hook; a failing plugin refuses
+    fn decide(&self, hook: &str, event: serde_json::Value) -> Result<(), HookRejection> {
+        if !self.exports(hook) {
+            return Ok(());
+        }
+        match self.call(hook, &event) {
+            Ok(None) => Ok(()),
+            Ok(Some(reason)) => Err(HookRejection(reason)),
+            Err(e) => {
+                error!(plugin = %self.name, hook = %hook, error = %e, "WASM plugin failed");
+                Err(HookRejection(format!("plugin {} failed", self.name)))
+            }
+        }
+    }
+}
+
+impl UploadHook for WasmPlugin {
+    fn before_store(&self, upload: &PendingUpload) -> Result<(), HookRejection> {
+        self.decide(
+            "before_store",
+            json!({
+                "link": link_event(upload.link),
+                "original_filename": upload.original_filename,
+                "content_type": upload.content_type,
+                "uploader_ip": upload.uploader.ip,
+                "uploader_email": upload.uploader.email,
+            }),
+        )
+    }
+
+    fn after_store(&self, link: &UploadLink, upload: &FileUpload) {
+        if !self.exports("after_store") {
+            return;
+        }
+        let event = json!({ "link": link_event(link), "upload": upload });
+        if let Err(e) = self.call("after_store", &event) {
+            error!(plugin = %self.name, upload_id = %upload.id, error = %e, "WASM plugin failed");
+        }
+    }
+
+    fn before_delete(&self, upload: &FileUpload) -> Result<(), HookRejection> {
+        self.decide("before_delete", json!({ "upload": upload }))
+    }
+}
+
+/// The parts of a link a plugin is told about (never its token)
+fn link_event(link: &UploadLink) -> serde_json::Value {
+    json!({
+        "id": link.id,
+        "name": link.name,
+        "project_id": link.project_id,
+        "total_quota": link.total_quota,
+        "per_file_limit": link.per_file_limit,
+        "remaining_quota": link.remaining_quota,
+    })
+}
@@ -0,0 +1,121 @@
+//! # Upload Progress Tracking
+//!
+//! This module keeps track of in-flight uploads so guests can see how many
+//! bytes the server has actually received, rather than only what the browser
+//! reports as sent.
+//!
+//! ## How It Works
+//! - The upload page generates a random upload ID (UUID) and passes it as the
+//!   `upload_id` query parameter when posting the file
+//! - `handle_upload` registers the ID and advances the counter for every chunk
+//!   it reads from the multipart stream
+//! - The page follows `/upload/{token}/progress/{upload_id}/events`, a stream
+//!   of server-sent events, or polls `/upload/{token}/progress/{upload_id}`
+//!   for the current state in browsers without `EventSource`
+//!
+//! ## Storage
+//! Progress entries live in memory only. They are short-lived by nature and
+//! are pruned automatically a few minutes after the upload finishes.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// How long finished (or abandoned) entries are kept before being pruned
+const PROGRESS_RETENTION_MINUTES: i64 = 10;
+
+/// Server-side progress of a single upload
+#[derive(Debug, Clone, Serialize)]
+pub struct UploadProgress {
+    /// Token of the upload link this upload belongs to
+    #[serde(skip)]
+    pub token: String,
+
+    /// Number of file bytes received so far
+    pub bytes_received: u64,
+
+    /// Expected request size in bytes (from Content-Length), if known
+    pub bytes_expected: Option<u64>,
+
+    /// Whether the server has finished processing the upload
+    pub completed: bool,
+
+    /// When this entry was last updated
+    #[serde(skip)]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Type alias for the thread-safe progress storage, keyed by upload ID
+type ProgressStore = std::sync::Arc<tokio::sync::RwLock<HashMap<String, UploadProgress>>>;
+
+// Global in-memory progress store
+lazy_static::lazy_static! {
+    static ref PROGRESS: ProgressStore = std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new()));
+}
+
+/// Register a new in-flight upload
+///
+/// Also prunes stale entries so the store cannot grow without bound.
+///
+/// # Arguments
+/// * `upload_id` - Client-generated upload ID
+/// * `token` - Token of the upload link being used
+/// * `bytes_expected` - Expected request size in bytes, if known
+pub async fn start_progress(upload_id: &str, token: &str, bytes_expected: Option<u64>) {
+    let mut progress = PROGRESS.write().await;
+
+    let cutoff = Utc::now() - Duration::minutes(PROGRESS_RETENTION_MINUTES);
+    progress.retain(|_, entry| entry.updated_at > cutoff);
+
+    progress.insert(
+        upload_id.to_string(),
+        UploadProgress {
+            token: token.to_string(),
+            bytes_received: 0,
+            bytes_expected,
+            completed: false,
+            updated_at: Utc::now(),
+        },
+    );
+}
+
+/// Record that more bytes have been received for an upload
+///
+/// # Arguments
+/// * `upload_id` - Upload ID to update
+/// * `bytes` - Number of newly received bytes
+pub async fn advance_progress(upload_id: &str, bytes: u64) {
+    let mut progress = PROGRESS.write().await;
+    if let Some(entry) = progress.get_mut(upload_id) {
+        entry.bytes_received += bytes;
+        entry.updated_at = Utc::now();
+    }
+}
+
+/// Mark an upload as finished (successfully or not)
+///
+/// # Arguments
+/// * `upload_id` - Upload ID to complete
+pub async fn finish_progress(upload_id: &str) {
+    let mut progress = PROGRESS.write().await;
+    if let Some(entry) = progress.get_mut(upload_id) {
+        entry.completed = true;
+        entry.updated_at = Utc::now();
+    }
+}
+
+/// Retrieve the progress of an upload
+///
+/// Returns None if the upload ID is unknown or belongs to a different link,
+/// so one guest cannot observe uploads made through another link.
+///
+/// # Arguments
+/// * `upload_id` - Upload ID to look up
+/// * `token` - Token of the upload link the caller is using
+pub async fn get_progress(upload_id: &str, token: &str) -> Option<UploadProgress> {
+    let progress = PROGRESS.read().await;
+    progress
+        .get(upload_id)
+        .filter(|entry| entry.token == token)
+        .cloned()
+}
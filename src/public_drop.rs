@@ -0,0 +1,191 @@
+//! # Public Drop
+//!
+//! Opt-in upload page at `/drop` for internal-network deployments where
+//! creating a link per person is overkill. It is backed by one shared upload
+//! link (marked `is_public`) that is created at startup and carries its own
+//! quota and bandwidth limit; admins see it in the link list like any other.
+//!
+//! Unless disabled, visitors solve a small arithmetic CAPTCHA before they
+//! are sent on to the upload page. Solving it sets the `drop_pass` cookie,
+//! which every upload to the public link must carry.
+//!
+//! ## Configuration
+//! - `PUBLIC_DROP`: `true` to enable the public drop (default: `false`)
+//! - `PUBLIC_DROP_NAME`: Name shown on the upload page (default: `Public Drop`)
+//! - `PUBLIC_DROP_QUOTA_MB`: Total quota in MB (default: 1024)
+//! - `PUBLIC_DROP_RATE_LIMIT_KBPS`: Bandwidth limit in KB/s (default: 0 = unlimited)
+//! - `PUBLIC_DROP_CAPTCHA`: Require the CAPTCHA (default: `true`)
+
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+    Form,
+};
+use moka::sync::Cache;
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    database::{deactivate_public_upload_link, get_public_upload_link, upsert_public_upload_link},
+    i18n::Lang,
+    templates::CaptchaTemplate,
+    theme::Theme,
+    AppState,
+};
+
+/// How long an unanswered CAPTCHA stays valid
+const CHALLENGE_TTL_SECS: u64 = 10 * 60;
+
+/// How long a solved CAPTCHA lets a visitor upload
+const PASS_TTL_SECS: u64 = 12 * 60 * 60;
+
+/// Upper bound on outstanding challenges and passes, so bots can't exhaust memory
+const CACHE_CAPACITY: u64 = 100_000;
+
+lazy_static::lazy_static! {
+    /// Open challenges by ID, with their expected answer
+    static ref CHALLENGES: Cache<String, i64> = Cache::builder()
+        .max_capacity(CACHE_CAPACITY)
+        .time_to_live(Duration::from_secs(CHALLENGE_TTL_SECS))
+        .build();
+
+    /// Passes handed out for solved challenges
+    static ref PASSES: Cache<String, ()> = Cache::builder()
+        .max_capacity(CACHE_CAPACITY)
+        .time_to_live(Duration::from_secs(PASS_TTL_SECS))
+        .build();
+}
+
+/// Create, update or deactivate the public drop link to match the configuration
+pub fn init(state: &AppState) {
+    let config = &state.config;
+
+    if !config.public_drop {
+        if let Err(e) = deactivate_public_upload_link(&state.db) {
+            error!(error = %e, "Failed to deactivate the public drop link");
+        }
+        return;
+    }
+
+    let quota = config.public_drop_quota_mb.max(1) * 1024 * 1024;
+    let rate_limit =
+        (config.public_drop_rate_limit_kbps > 0).then_some(config.public_drop_rate_limit_kbps);
+
+    match upsert_public_upload_link(&state.db, &config.public_drop_name, quota, rate_limit) {
+        Ok(link) => info!(
+            link_id = %link.id,
+            remaining_quota = link.remaining_quota,
+            captcha = config.public_drop_captcha,
+            "Public drop enabled at /drop"
+        ),
+        Err(e) => error!(error = %e, "Failed to set up the public drop link"),
+    }
+}
+
+/// Whether the request may upload to the public drop link
+pub fn has_pass(config: &Config, headers: &HeaderMap) -> bool {
+    if !config.public_drop_captcha {
+        return true;
+    }
+
+    headers
+        .get(header::COOKIE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|cookies| {
+            cookies
+                .split(';')
+                .find_map(|cookie| cookie.trim().strip_prefix("drop_pass="))
+        })
+        .is_some_and(|pass| PASSES.contains_key(pass))
+}
+
+/// Entry point of the public drop: CAPTCHA first, then the upload page
+pub async fn drop_page(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    lang: Lang,
+    theme: Theme,
+) -> Response {
+    if !state.config.public_drop {
+        return (StatusCode::NOT_FOUND, "Not found").into_response();
+    }
+
+    let link = match get_public_upload_link(&state.db) {
+        Ok(Some(link)) => link,
+        Ok(None) => return (StatusCode::NOT_FOUND, "Not found").into_response(),
+        Err(e) => {
+            error!(error = %e, "Database error while fetching the public drop link");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
+        }
+    };
+
+    if has_pass(&state.config, &headers) {
+        return Redirect::to(&format!("/upload/{}", link.token)).into_response();
+    }
+
+    captcha_page(lang, theme, link.name, None)
+}
+
+/// CAPTCHA answer form data
+#[derive(Debug, Deserialize)]
+pub struct CaptchaForm {
+    pub challenge: String,
+    pub answer: String,
+}
+
+/// Check a CAPTCHA answer and hand out a pass
+pub async fn verify_captcha(
+    State(state): State<AppState>,
+    lang: Lang,
+    theme: Theme,
+    Form(form): Form<CaptchaForm>,
+) -> Response {
+    if !state.config.public_drop {
+        return (StatusCode::NOT_FOUND, "Not found").into_response();
+    }
+
+    // Each challenge can only be answered once
+    let expected = CHALLENGES.remove(&form.challenge);
+    let answer = form.answer.trim().parse::<i64>().ok();
+
+    if expected.is_none() || expected != answer {
+        warn!("Public drop CAPTCHA failed");
+        let name = state.config.public_drop_name.clone();
+        return captcha_page(lang, theme, name, Some(lang.t("captcha-error")));
+    }
+
+    let pass = Uuid::new_v4().to_string();
+    PASSES.insert(pass.clone(), ());
+
+    let mut response = Redirect::to("/drop").into_response();
+    let cookie = format!(
+        "drop_pass={}; Path=/; Max-Age={}; HttpOnly; SameSite=Lax",
+        pass, PASS_TTL_SECS
+    );
+    response
+        .headers_mut()
+        .insert(header::SET_COOKIE, cookie.parse().unwrap());
+    response
+}
+
+/// Render a fresh challenge
+fn captcha_page(lang: Lang, theme: Theme, name: String, error: Option<String>) -> Response {
+    let challenge = Uuid::new_v4();
+    let bytes = challenge.as_bytes();
+    let (a, b) = (i64::from(bytes[0] % 20) + 1, i64::from(bytes[1] % 20) + 1);
+    CHALLENGES.insert(challenge.to_string(), a + b);
+
+    CaptchaTemplate {
+        lang,
+        theme,
+        name,
+        challenge: challenge.to_string(),
+        question: format!("{} + {}", a, b),
+        error,
+    }
+    .into_response()
+}
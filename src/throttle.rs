@@ -0,0 +1,132 @@
+//! # Bandwidth Throttling
+//!
+//! Token-bucket rate limiting for upload and download streams, so a single
+//! guest transferring a huge archive cannot saturate the server's uplink.
+//!
+//! The bucket is refilled continuously at the configured rate. Consuming more
+//! bytes than are available sleeps just long enough for the bucket to catch up,
+//! which smooths the transfer to the target rate without buffering extra data.
+//!
+//! The rate is per link: every transfer on a link, in either direction and
+//! through any channel, draws from the one bucket [`TokenBucket::for_link`]
+//! hands out, so parallel uploads share the link's rate instead of each
+//! getting all of it.
+
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use moka::sync::Cache;
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::models::UploadLink;
+
+/// Most links with a bucket kept at once; the least recently used are dropped first
+const MAX_TRACKED_LINKS: u64 = 10_000;
+
+/// How long a link's bucket outlives its last transfer
+const IDLE_BUCKET_SECS: u64 = 10 * 60;
+
+lazy_static::lazy_static! {
+    /// The bucket of each throttled link, by link ID
+    static ref LINK_BUCKETS: Cache<String, TokenBucket> = Cache::builder()
+        .max_capacity(MAX_TRACKED_LINKS)
+        .time_to_idle(Duration::from_secs(IDLE_BUCKET_SECS))
+        .build();
+}
+
+/// Fill level of a token bucket
+#[derive(Debug)]
+struct Tokens {
+    /// Currently available tokens (may go negative after a large chunk)
+    available: f64,
+
+    /// When the bucket was last refilled
+    last_refill: Instant,
+}
+
+/// Token bucket limiting throughput to a fixed number of bytes per second
+///
+/// Clones share the same tokens.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    /// Refill rate in bytes per second
+    rate: f64,
+
+    /// Maximum number of tokens the bucket can hold (one second of burst)
+    capacity: f64,
+
+    tokens: Arc<Mutex<Tokens>>,
+}
+
+impl TokenBucket {
+    /// Create a new bucket allowing `bytes_per_sec` bytes per second
+    fn new(bytes_per_sec: u64) -> Self {
+        let rate = bytes_per_sec.max(1) as f64;
+        Self {
+            rate,
+            capacity: rate,
+            tokens: Arc::new(Mutex::new(Tokens {
+                available: rate,
+                last_refill: Instant::now(),
+            })),
+        }
+    }
+
+    /// The bucket shared by all transfers of `link`, if the link is throttled
+    pub fn for_link(link: &UploadLink) -> Option<Self> {
+        let bytes_per_sec = link.rate_limit_bytes_per_sec()?;
+        let bucket = LINK_BUCKETS.get_with(link.id.clone(), || Self::new(bytes_per_sec));
+
+        // The link's rate was changed since its bucket was made
+        if bucket.rate != bytes_per_sec.max(1) as f64 {
+            let bucket = Self::new(bytes_per_sec);
+            LINK_BUCKETS.insert(link.id.clone(), bucket.clone());
+            return Some(bucket);
+        }
+        Some(bucket)
+    }
+
+    /// Wait until `bytes` bytes may be transferred
+    ///
+    /// Chunks larger than the bucket capacity are allowed; the bucket simply
+    /// goes into debt and the caller sleeps until it has been repaid. With
+    /// several transfers on one bucket, each waits out the debt of all.
+    pub async fn consume(&self, bytes: usize) {
+        let wait = {
+            let mut tokens = self.tokens.lock().unwrap();
+            let now = Instant::now();
+            let elapsed = now.duration_since(tokens.last_refill).as_secs_f64();
+            tokens.available = (tokens.available + elapsed * self.rate).min(self.capacity);
+            tokens.last_refill = now;
+
+            tokens.available -= bytes as f64;
+            (tokens.available < 0.0).then(|| Duration::from_secs_f64(-tokens.available / self.rate))
+        };
+        if let Some(wait) = wait {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Pace an existing byte stream with a token bucket
+///
+/// # Arguments
+/// * `stream` - Source stream (e.g. a file being downloaded)
+/// * `bucket` - Bucket of the link the stream belongs to
+pub fn throttle_stream<S>(
+    stream: S,
+    bucket: TokenBucket,
+) -> impl Stream<Item = Result<Bytes, std::io::Error>>
+where
+    S: Stream<Item = Result<Bytes, std::io::Error>> + Unpin,
+{
+    futures::stream::unfold((stream, bucket), |(mut stream, bucket)| async move {
+        let item = stream.next().await?;
+        if let Ok(chunk) = &item {
+            bucket.consume(chunk.len()).await;
+        }
+        Some((item, (stream, bucket)))
+    })
+}
@@ -0,0 +1,252 @@
+//! # Two-Factor Authentication (TOTP)
+//!
+//! Admins may protect their account with time-based one-time passwords
+//! (RFC 6238) from an authenticator app. Setting it up shows a new secret as
+//! a QR code; it is only stored on the admin once a code generated from it
+//! has been entered, so a failed scan can't lock anyone out.
+//!
+//! With a secret stored, a correct password no longer creates a session.
+//! [`crate::handlers::handle_login`] instead starts a pending login and asks
+//! for a code, which `POST /login/verify` checks before the session is made.
+//! A pending login lasts a few minutes and allows a few wrong codes.
+//!
+//! Codes are 6 digits over 30 second steps with HMAC-SHA1, which every
+//! common authenticator app uses by default. One step of clock drift either
+//! way is accepted, and each step is used at most once per admin, so a code
+//! seen over someone's shoulder can't be replayed.
+//!
+//! Logins by client certificate on the admin listener (see [`crate::mtls`])
+//! already have a second factor and skip the code.
+
+use chrono::Utc;
+use qrcode::{render::svg, QrCode};
+use ring::{
+    hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::models::AdminRole;
+
+/// Issuer shown next to the account in authenticator apps
+const ISSUER: &str = "NeedADrop";
+
+/// Length of a time step in seconds
+const STEP_SECS: i64 = 30;
+
+/// Number of digits in a code
+const DIGITS: u32 = 6;
+
+/// Steps of clock drift accepted either way
+const ALLOWED_DRIFT: i64 = 1;
+
+/// Bytes of a generated secret (160 bits, as RFC 4226 recommends)
+const SECRET_BYTES: usize = 20;
+
+/// How long a pending login waits for its code
+const PENDING_LOGIN_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Wrong codes after which a pending login is dropped
+const MAX_CODE_ATTEMPTS: u32 = 5;
+
+/// A new random secret, base32 encoded as authenticator apps expect
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; SECRET_BYTES];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("system random number generator failed");
+    base32::encode(&bytes)
+}
+
+/// Whether `secret` is a base32 secret this module could have generated
+pub fn is_valid_secret(secret: &str) -> bool {
+    base32::decode(secret).is_some_and(|bytes| bytes.len() == SECRET_BYTES)
+}
+
+/// The `otpauth://` URI an authenticator app is set up with
+pub fn provisioning_uri(secret: &str, username: &str) -> String {
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={DIGITS}&period={STEP_SECS}",
+        issuer = ISSUER,
+        account = percent_encode(username),
+    )
+}
+
+/// The provisioning URI as an inline SVG QR code
+pub fn qr_code_svg(uri: &str) -> Option<String> {
+    let code = QrCode::new(uri.as_bytes()).ok()?;
+    Some(
+        code.render::<svg::Color>()
+            .min_dimensions(200, 200)
+            .quiet_zone(true)
+            .build(),
+    )
+}
+
+/// Check a code against a secret
+///
+/// Returns the time step the code belongs to, so the caller can refuse it
+/// if that step was already used, or None if the code is wrong.
+pub fn verify_code(secret: &str, code: &str) -> Option<i64> {
+    let key = base32::decode(secret)?;
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    if code.len() != DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let current = Utc::now().timestamp() / STEP_SECS;
+    (current - ALLOWED_DRIFT..=current + ALLOWED_DRIFT)
+        .find(|&step| codes_match(&code_at(&key, step), &code))
+}
+
+/// The code of a time step (RFC 4226 dynamic truncation)
+fn code_at(key: &[u8], step: i64) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, key);
+    let tag = hmac::sign(&key, &step.to_be_bytes());
+    let digest = tag.as_ref();
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    format!(
+        "{:0width$}",
+        binary % 10u32.pow(DIGITS),
+        width = DIGITS as usize
+    )
+}
+
+/// Compare codes in constant time
+fn codes_match(expected: &str, supplied: &str) -> bool {
+    expected.len() == supplied.len()
+        && expected
+            .bytes()
+            .zip(supplied.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Percent-encode an account name for the otpauth URI
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// An admin who entered the right password and still owes a code
+#[derive(Debug, Clone)]
+pub struct PendingLogin {
+    pub admin_id: String,
+    pub username: String,
+    pub role: AdminRole,
+    created_at: Instant,
+    failed_attempts: u32,
+}
+
+// Pending logins by the random token handed to the code form
+lazy_static::lazy_static! {
+    static ref PENDING_LOGINS: Mutex<HashMap<String, PendingLogin>> = Mutex::new(HashMap::new());
+}
+
+/// Start a pending login and return the token the code form carries
+pub fn start_pending_login(admin_id: String, username: String, role: AdminRole) -> String {
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let now = Instant::now();
+    let mut pending = PENDING_LOGINS.lock().unwrap();
+    pending.retain(|_, login| now.duration_since(login.created_at) < PENDING_LOGIN_TTL);
+    pending.insert(
+        token.clone(),
+        PendingLogin {
+            admin_id,
+            username,
+            role,
+            created_at: now,
+            failed_attempts: 0,
+        },
+    );
+    token
+}
+
+/// The pending login of a token, if it hasn't expired
+pub fn pending_login(token: &str) -> Option<PendingLogin> {
+    let mut pending = PENDING_LOGINS.lock().unwrap();
+    let login = pending.get(token)?;
+    if login.created_at.elapsed() >= PENDING_LOGIN_TTL {
+        pending.remove(token);
+        return None;
+    }
+    Some(login.clone())
+}
+
+/// End a pending login once its code was accepted
+pub fn finish_pending_login(token: &str) {
+    PENDING_LOGINS.lock().unwrap().remove(token);
+}
+
+/// Count a wrong code; returns false once the login has run out of attempts
+pub fn record_failed_code(token: &str) -> bool {
+    let mut pending = PENDING_LOGINS.lock().unwrap();
+    let Some(login) = pending.get_mut(token) else {
+        return false;
+    };
+    login.failed_attempts += 1;
+    if login.failed_attempts >= MAX_CODE_ATTEMPTS {
+        pending.remove(token);
+        return false;
+    }
+    true
+}
+
+/// RFC 4648 base32 without padding, as used in otpauth URIs
+mod base32 {
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+    pub fn encode(bytes: &[u8]) -> String {
+        let mut output = String::with_capacity(bytes.len().div_ceil(5) * 8);
+        let mut buffer = 0u32;
+        let mut bits = 0;
+        for &byte in bytes {
+            buffer = (buffer << 8) | byte as u32;
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                output.push(ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+            }
+        }
+        if bits > 0 {
+            output.push(ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+        }
+        output
+    }
+
+    /// Decode, ignoring case, spaces and padding
+    pub fn decode(text: &str) -> Option<Vec<u8>> {
+        let mut output = Vec::with_capacity(text.len() * 5 / 8);
+        let mut buffer = 0u32;
+        let mut bits = 0;
+        for c in text.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+            let value = ALPHABET
+                .iter()
+                .position(|&a| a as char == c.to_ascii_uppercase())?;
+            buffer = (buffer << 5) | value as u32;
+            bits += 5;
+            if bits >= 8 {
+                bits -= 8;
+                output.push((buffer >> bits) as u8);
+            }
+        }
+        Some(output)
+    }
+}
@@ -0,0 +1,341 @@
+//! # tus Uploads
+//!
+//! A [tus](https://tus.io) 1.0.0 endpoint per link, so guests and scripts
+//! with standard tus clients (tus-js-client, Uppy, `tusd` tooling) can
+//! resume interrupted large uploads. It is a second front end for the
+//! sessions of [`crate::resumable`]: partial data, expiry and completion
+//! work the same way.
+//!
+//! ## Endpoints
+//! - `OPTIONS /upload/{token}/tus`: Supported version, extensions and maximum size
+//! - `POST /upload/{token}/tus`: Create an upload (`Upload-Length`, and
+//!   `Upload-Metadata` with `filename` and `filetype`); `Location` names it
+//! - `HEAD /upload/{token}/tus/{id}`: Current `Upload-Offset`
+//! - `PATCH /upload/{token}/tus/{id}`: Append `application/offset+octet-stream`
+//!   data at `Upload-Offset`; the chunk that completes the file stores it
+//! - `DELETE /upload/{token}/tus/{id}`: Abandon an upload
+//!
+//! Supported extensions are `creation`, `termination` and `expiration`.
+//! Uploads of unknown length (`Upload-Defer-Length`) are not supported, as
+//! a link's quota has to be checked up front.
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use tracing::info;
+
+use crate::{
+    database::get_upload_link_by_token,
+    ingest::ClientIp,
+    models::UploadSession,
+    public_drop,
+    request_id::RequestId,
+    resumable::{self, SessionGuard},
+    AppState,
+};
+
+/// The only protocol version spoken
+const TUS_VERSION: &str = "1.0.0";
+
+/// Protocol extensions beyond the core protocol
+const TUS_EXTENSIONS: &str = "creation,termination,expiration";
+
+/// Content type of the data in a `PATCH` request
+const OFFSET_OCTET_STREAM: &str = "application/offset+octet-stream";
+
+/// Describe the server's tus support
+pub async fn options(State(state): State<AppState>, Path(token): Path<String>) -> Response {
+    let mut response = StatusCode::NO_CONTENT.into_response();
+    let headers = response.headers_mut();
+    headers.insert("tus-version", HeaderValue::from_static(TUS_VERSION));
+    headers.insert("tus-extension", HeaderValue::from_static(TUS_EXTENSIONS));
+    if let Ok(Some(link)) = get_upload_link_by_token(&state.db, &token) {
+        headers.insert("tus-max-size", HeaderValue::from(link.max_file_bytes()));
+    }
+    with_tus_headers(response)
+}
+
+/// Create an upload (creation extension)
+pub async fn create_upload(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(response) = check_version(&headers) {
+        return response;
+    }
+
+    let link = match resumable::usable_link(&state, &token) {
+        Ok(link) => link,
+        Err(response) => return with_tus_headers(response),
+    };
+    if link.is_public && !public_drop::has_pass(&state.config, &headers) {
+        return tus_error(StatusCode::FORBIDDEN, "solve the CAPTCHA at /drop first");
+    }
+
+    if headers.contains_key("upload-defer-length") {
+        return tus_error(
+            StatusCode::BAD_REQUEST,
+            "uploads of unknown length are not supported",
+        );
+    }
+    let Some(size) = header_number(&headers, "upload-length") else {
+        return tus_error(StatusCode::BAD_REQUEST, "Upload-Length header is required");
+    };
+
+    let metadata = headers
+        .get("upload-metadata")
+        .and_then(|value| value.to_str().ok())
+        .map(parse_metadata)
+        .unwrap_or_default();
+    let value_of = |key: &str| {
+        metadata
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    };
+    let filename = value_of("filename")
+        .or_else(|| value_of("name"))
+        .unwrap_or("unnamed_file");
+    let content_type = value_of("filetype").or_else(|| value_of("type"));
+
+    let session_id =
+        match resumable::start_session(&state, &link, filename, content_type, size).await {
+            Ok(session_id) => session_id,
+            Err(response) => return with_tus_headers(response),
+        };
+
+    let mut response = StatusCode::CREATED.into_response();
+    let location = format!("/upload/{}/tus/{}", token, session_id);
+    if let Ok(location) = HeaderValue::from_str(&location) {
+        response.headers_mut().insert(header::LOCATION, location);
+    }
+    response
+        .headers_mut()
+        .insert("upload-offset", HeaderValue::from(0));
+    insert_expiry(&state, &mut response, chrono::Utc::now());
+    with_tus_headers(response)
+}
+
+/// Report how much of an upload has arrived
+///
+/// A client seeing the full length considers the upload done, so a file
+/// that arrived completely but failed to be stored is retried here.
+pub async fn upload_offset(
+    State(state): State<AppState>,
+    Path((token, id)): Path<(String, String)>,
+    client_ip: ClientIp,
+    request_id: RequestId,
+    headers: HeaderMap,
+) -> Response {
+    let Some(_guard) = SessionGuard::acquire(&id) else {
+        return tus_error(StatusCode::CONFLICT, "another chunk is being received");
+    };
+
+    let (link, session) = match resumable::session_for_link(&state, &token, &id) {
+        Ok(found) => found,
+        Err(response) => return with_tus_headers(response),
+    };
+
+    if session.received >= session.total_size {
+        let uploader = client_ip.uploader(request_id, &headers);
+        if let Err(response) =
+            resumable::complete_session(&state, &link, &session, &headers, &uploader).await
+        {
+            return with_tus_headers(response);
+        }
+    }
+
+    let mut response = StatusCode::OK.into_response();
+    insert_progress(&mut response, &session);
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    insert_expiry(&state, &mut response, session.updated_at);
+    with_tus_headers(response)
+}
+
+/// Append data to an upload, storing the file once it is complete
+pub async fn append_data(
+    State(state): State<AppState>,
+    Path((token, id)): Path<(String, String)>,
+    client_ip: ClientIp,
+    request_id: RequestId,
+    headers: HeaderMap,
+    body: Body,
+) -> Response {
+    if let Err(response) = check_version(&headers) {
+        return response;
+    }
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    if content_type != Some(OFFSET_OCTET_STREAM) {
+        return tus_error(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Content-Type must be application/offset+octet-stream",
+        );
+    }
+    let Some(offset) = header_number(&headers, "upload-offset") else {
+        return tus_error(StatusCode::BAD_REQUEST, "Upload-Offset header is required");
+    };
+
+    // Claimed before reading the session so the offset can't change underneath us
+    let Some(_guard) = SessionGuard::acquire(&id) else {
+        return tus_error(StatusCode::CONFLICT, "another chunk is being received");
+    };
+
+    let (link, session) = match resumable::session_for_link(&state, &token, &id) {
+        Ok(found) => found,
+        Err(response) => return with_tus_headers(response),
+    };
+    if offset != session.received {
+        return tus_error(StatusCode::CONFLICT, "Upload-Offset does not match");
+    }
+
+    let session = match resumable::receive_chunk(&state, &link, session, body).await {
+        Ok(session) => session,
+        Err(response) => return with_tus_headers(response),
+    };
+
+    if session.received >= session.total_size {
+        let uploader = client_ip.uploader(request_id, &headers);
+        match resumable::complete_session(&state, &link, &session, &headers, &uploader).await {
+            Ok(upload_id) => {
+                info!(session_id = %session.id, upload_id = %upload_id, "tus upload completed");
+            }
+            Err(response) => return with_tus_headers(response),
+        }
+    }
+
+    let mut response = StatusCode::NO_CONTENT.into_response();
+    insert_progress(&mut response, &session);
+    insert_expiry(&state, &mut response, session.updated_at);
+    with_tus_headers(response)
+}
+
+/// Abandon an upload (termination extension)
+pub async fn terminate(
+    State(state): State<AppState>,
+    Path((token, id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(response) = check_version(&headers) {
+        return response;
+    }
+    let Some(_guard) = SessionGuard::acquire(&id) else {
+        return tus_error(StatusCode::CONFLICT, "another chunk is being received");
+    };
+
+    let (_, session) = match resumable::session_for_link(&state, &token, &id) {
+        Ok(found) => found,
+        Err(response) => return with_tus_headers(response),
+    };
+
+    match resumable::abandon_session(&state, &session).await {
+        Ok(()) => with_tus_headers(StatusCode::NO_CONTENT.into_response()),
+        Err(response) => with_tus_headers(response),
+    }
+}
+
+/// Refuse requests for a protocol version other than ours
+#[allow(clippy::result_large_err)]
+fn check_version(headers: &HeaderMap) -> Result<(), Response> {
+    let version = headers
+        .get("tus-resumable")
+        .and_then(|value| value.to_str().ok());
+    if version == Some(TUS_VERSION) {
+        return Ok(());
+    }
+
+    let mut response = tus_error(
+        StatusCode::PRECONDITION_FAILED,
+        "unsupported tus version, 1.0.0 is required",
+    );
+    response
+        .headers_mut()
+        .insert("tus-version", HeaderValue::from_static(TUS_VERSION));
+    Err(response)
+}
+
+fn tus_error(status: StatusCode, message: &str) -> Response {
+    with_tus_headers(resumable::error_response(status, message))
+}
+
+/// Every tus response names the protocol version
+fn with_tus_headers(mut response: Response) -> Response {
+    response
+        .headers_mut()
+        .insert("tus-resumable", HeaderValue::from_static(TUS_VERSION));
+    response
+}
+
+fn insert_progress(response: &mut Response, session: &UploadSession) {
+    let headers = response.headers_mut();
+    headers.insert("upload-offset", HeaderValue::from(session.received));
+    headers.insert("upload-length", HeaderValue::from(session.total_size));
+}
+
+/// When the upload is purged unless more data arrives (expiration extension)
+fn insert_expiry(
+    state: &AppState,
+    response: &mut Response,
+    last_activity: chrono::DateTime<chrono::Utc>,
+) {
+    let expires = last_activity + resumable::session_ttl(state);
+    let expires = expires.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+    if let Ok(expires) = HeaderValue::from_str(&expires) {
+        response.headers_mut().insert("upload-expires", expires);
+    }
+}
+
+fn header_number(headers: &HeaderMap, name: &str) -> Option<i64> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .filter(|number| *number >= 0)
+}
+
+/// Parse `Upload-Metadata`: comma-separated keys, each with a base64 value
+fn parse_metadata(header: &str) -> Vec<(String, String)> {
+    header
+        .split(',')
+        .filter_map(|pair| {
+            let mut parts = pair.trim().splitn(2, ' ');
+            let key = parts.next().filter(|key| !key.is_empty())?;
+            let value = match parts.next() {
+                Some(encoded) => String::from_utf8(base64_decode(encoded.trim())?).ok()?,
+                None => String::new(),
+            };
+            Some((key.to_string(), value))
+        })
+        .collect()
+}
+
+/// Decode standard (padded) base64, as used in `Upload-Metadata`
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in text.trim_end_matches('=').bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
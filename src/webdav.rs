@@ -0,0 +1,196 @@
+//! # WebDAV Push (Nextcloud / ownCloud)
+//!
+//! Copies received files into a folder on a WebDAV server, so teams find
+//! them directly in their Nextcloud or ownCloud. Each upload link can map to
+//! its own folder; links without a folder are not pushed.
+//!
+//! Pushes run as background tasks (see [`crate::tasks`]), so failures are
+//! retried with backoff and end up in the admin dead-letter view when the
+//! server stays unreachable.
+//!
+//! ## Configuration
+//! - `WEBDAV_URL`: Base URL, e.g. `https://cloud.example.com/remote.php/dav/files/alice`
+//! - `WEBDAV_USERNAME` / `WEBDAV_PASSWORD`: Credentials (use an app password)
+
+use reqwest::{header, Method, StatusCode, Url};
+use std::path::Path;
+use tokio_util::io::ReaderStream;
+
+use crate::{
+    encryption::{self, Keys},
+    models::FileUpload,
+};
+
+lazy_static::lazy_static! {
+    static ref HTTP_CLIENT: reqwest::Client = reqwest::Client::new();
+}
+
+/// Connection details for the WebDAV server
+pub struct WebdavTarget<'a> {
+    /// Base URL that link folders are relative to
+    pub base_url: &'a str,
+
+    /// Username for basic authentication
+    pub username: Option<&'a str>,
+
+    /// Password for basic authentication
+    pub password: Option<&'a str>,
+}
+
+impl WebdavTarget<'_> {
+    /// Build a request with authentication applied
+    fn request(&self, method: Method, url: Url) -> reqwest::RequestBuilder {
+        let request = HTTP_CLIENT.request(method, url);
+        match self.username {
+            Some(username) => request.basic_auth(username, self.password),
+            None => request,
+        }
+    }
+
+    /// URL of a path below the base URL, with every segment percent-encoded
+    fn url_for(&self, segments: &[&str]) -> Result<Url, String> {
+        let mut url =
+            Url::parse(self.base_url).map_err(|e| format!("Invalid WEBDAV_URL: {}", e))?;
+        url.path_segments_mut()
+            .map_err(|_| "Invalid WEBDAV_URL: cannot be a base".to_string())?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url)
+    }
+}
+
+/// Upload a received file into `folder` on the WebDAV server
+///
+/// Missing folders are created. An existing file with the same name is
+/// never overwritten; the upload ID is appended to the name instead.
+pub async fn push_file(
+    target: &WebdavTarget<'_>,
+    folder: &str,
+    upload_dir: &Path,
+    keys: Option<&Keys>,
+    upload: &FileUpload,
+) -> Result<(), String> {
+    let folder_segments: Vec<&str> = folder
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+
+    ensure_folder(target, &folder_segments).await?;
+
+    let filename = remote_filename(&upload.original_filename);
+    let status = put_file(
+        target,
+        &folder_segments,
+        &filename,
+        upload_dir,
+        keys,
+        upload,
+    )
+    .await?;
+
+    // Name already taken: keep both files
+    if status == StatusCode::PRECONDITION_FAILED {
+        let unique_name = with_suffix(&filename, &upload.id[..8]);
+        let status = put_file(
+            target,
+            &folder_segments,
+            &unique_name,
+            upload_dir,
+            keys,
+            upload,
+        )
+        .await?;
+        if !status.is_success() {
+            return Err(format!(
+                "WebDAV upload of {} failed: {}",
+                unique_name, status
+            ));
+        }
+    } else if !status.is_success() {
+        return Err(format!("WebDAV upload of {} failed: {}", filename, status));
+    }
+
+    Ok(())
+}
+
+/// Create every folder along the path (MKCOL is not recursive)
+async fn ensure_folder(target: &WebdavTarget<'_>, segments: &[&str]) -> Result<(), String> {
+    let mkcol = Method::from_bytes(b"MKCOL").expect("MKCOL is a valid method");
+
+    for depth in 1..=segments.len() {
+        let url = target.url_for(&segments[..depth])?;
+        let response = target
+            .request(mkcol.clone(), url)
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))?;
+
+        // 405 Method Not Allowed means the folder already exists
+        let status = response.status();
+        if !status.is_success() && status != StatusCode::METHOD_NOT_ALLOWED {
+            return Err(format!(
+                "Failed to create WebDAV folder {}: {}",
+                segments[..depth].join("/"),
+                status
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Stream the file to the server without overwriting an existing file
+async fn put_file(
+    target: &WebdavTarget<'_>,
+    folder_segments: &[&str],
+    filename: &str,
+    upload_dir: &Path,
+    keys: Option<&Keys>,
+    upload: &FileUpload,
+) -> Result<StatusCode, String> {
+    let path = upload.file_path(upload_dir);
+    let (file, _) = encryption::open(keys, &path, upload.encryption_key_id.as_deref())
+        .await
+        .map_err(|e| format!("Failed to open upload: {}", e))?;
+
+    let mut segments = folder_segments.to_vec();
+    segments.push(filename);
+    let url = target.url_for(&segments)?;
+
+    let response = target
+        .request(Method::PUT, url)
+        .header(header::IF_NONE_MATCH, "*")
+        .header(header::CONTENT_TYPE, &upload.mime_type)
+        .header(header::CONTENT_LENGTH, upload.file_size)
+        .body(reqwest::Body::wrap_stream(ReaderStream::new(file)))
+        .send()
+        .await
+        .map_err(|e| format!("WebDAV request failed: {}", e))?;
+
+    Ok(response.status())
+}
+
+/// Reduce a guest-supplied filename to a safe single path segment
+fn remote_filename(original: &str) -> String {
+    let name = original
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .trim();
+
+    if name.is_empty() || name == "." || name == ".." {
+        "unnamed_file".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Insert a suffix before the extension: `report.pdf` -> `report (suffix).pdf`
+fn with_suffix(filename: &str, suffix: &str) -> String {
+    match filename.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => {
+            format!("{} ({}).{}", stem, suffix, extension)
+        }
+        _ => format!("{} ({})", filename, suffix),
+    }
+}
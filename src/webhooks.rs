@@ -0,0 +1,157 @@
+//! # Webhooks
+//!
+//! Delivers server events (see [`crate::events`]) to HTTP endpoints that
+//! admins register on the webhooks page (`/admin/webhooks`). Each endpoint
+//! has a URL, a signing secret and optionally a list of events it wants;
+//! without a list it receives every event.
+//!
+//! ## Requests
+//! Events are POSTed as the same JSON payloads that go to MQTT, with:
+//! - `X-NeedADrop-Event`: Event name, e.g. `upload.created`
+//! - `X-NeedADrop-Delivery`: ID of the delivery, unchanged across retries
+//! - `X-NeedADrop-Signature`: `sha256=` and the hex HMAC-SHA256 of the body,
+//!   keyed with the endpoint's secret
+//!
+//! Receivers should recompute the signature over the raw body and compare it
+//! in constant time before trusting the payload.
+//!
+//! ## Delivery
+//! Every delivery is a background task (see [`crate::tasks`]): a response
+//! other than 2xx, or no response within [`DELIVERY_TIMEOUT`], is retried
+//! with backoff, and deliveries that keep failing end up in the dead-letter
+//! view. Deliveries for an endpoint that was deleted in the meantime are
+//! dropped.
+
+use reqwest::{header, Url};
+use ring::{
+    hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+use std::time::Duration;
+use tracing::{debug, error, warn};
+use uuid::Uuid;
+
+use crate::{
+    database::get_webhooks,
+    models::Webhook,
+    tasks::{enqueue, Task},
+    AppState,
+};
+
+/// Events an endpoint can subscribe to
+pub const EVENTS: &[&str] = &[
+    "upload.created",
+    "link.created",
+    "link.deleted",
+    "link.expired",
+    "link.quota_low",
+    "link.quota_exhausted",
+];
+
+/// How long an endpoint may take to answer before the delivery is retried
+pub const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+lazy_static::lazy_static! {
+    static ref HTTP_CLIENT: reqwest::Client = reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+}
+
+/// A new random signing secret: 24 random bytes in hex
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 24];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("system random number generator failed");
+    to_hex(&bytes)
+}
+
+/// Check a webhook URL entered by an admin
+pub fn is_valid_url(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.has_host())
+}
+
+/// Normalize a comma-separated event list (None = every event)
+///
+/// # Returns
+/// The list in one spelling, or the first entry that is not an event
+pub fn parse_events(input: &str) -> Result<Option<String>, String> {
+    let mut events = Vec::new();
+    for entry in input.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        if !EVENTS.contains(&entry) {
+            return Err(entry.to_string());
+        }
+        if !events.contains(&entry) {
+            events.push(entry);
+        }
+    }
+
+    Ok((!events.is_empty()).then(|| events.join(", ")))
+}
+
+/// `X-NeedADrop-Signature` value of a payload
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    format!("sha256={}", to_hex(hmac::sign(&key, body).as_ref()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Queue a delivery of an event to every endpoint that wants it
+///
+/// Failures are logged rather than returned, like every event sink.
+pub fn publish(state: &AppState, event: &str, body: &[u8]) {
+    let webhooks = match get_webhooks(&state.db) {
+        Ok(webhooks) => webhooks,
+        Err(e) => {
+            error!(event = %event, error = %e, "Failed to load webhooks");
+            return;
+        }
+    };
+
+    for webhook in webhooks.iter().filter(|webhook| webhook.wants(event)) {
+        let task = Task::DeliverWebhook {
+            webhook_id: webhook.id.clone(),
+            delivery_id: Uuid::new_v4().to_string(),
+            event: event.to_string(),
+            payload: String::from_utf8_lossy(body).into_owned(),
+        };
+        if let Err(e) = enqueue(state, &task) {
+            error!(webhook_id = %webhook.id, event = %event, error = %e, "Failed to enqueue webhook delivery");
+        }
+    }
+}
+
+/// POST one event to an endpoint; anything but a 2xx answer is an error
+pub async fn deliver(
+    webhook: &Webhook,
+    delivery_id: &str,
+    event: &str,
+    payload: &str,
+) -> Result<(), String> {
+    let response = HTTP_CLIENT
+        .post(&webhook.url)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header("X-NeedADrop-Event", event)
+        .header("X-NeedADrop-Delivery", delivery_id)
+        .header(
+            "X-NeedADrop-Signature",
+            signature(&webhook.secret, payload.as_bytes()),
+        )
+        .body(payload.to_string())
+        .send()
+        .await
+        .map_err(|e| format!("Webhook request failed: {}", e))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        warn!(webhook_id = %webhook.id, event = %event, status = %status, "Webhook endpoint refused delivery");
+        return Err(format!("Webhook endpoint answered {}", status));
+    }
+
+    debug!(webhook_id = %webhook.id, event = %event, delivery_id = %delivery_id, "Webhook delivered");
+    Ok(())
+}
@@ -0,0 +1,178 @@
+// NeedADrop service worker
+//
+// Keeps the upload page available offline and finishes uploads that were
+// queued while the device had no connection. Queued files live in IndexedDB
+// and are sent through the resumable upload session endpoints in chunks, so
+// a connection that drops halfway only costs the current chunk.
+
+const PAGE_CACHE = 'needadrop-pages-v1';
+const DB_NAME = 'needadrop';
+const QUEUE_STORE = 'uploads';
+const SYNC_TAG = 'needadrop-uploads';
+const CHUNK_SIZE = 1024 * 1024;
+
+self.addEventListener('install', () => self.skipWaiting());
+self.addEventListener('activate', event => event.waitUntil(self.clients.claim()));
+
+// Upload pages: network first, the last copy we saw when offline
+self.addEventListener('fetch', event => {
+    const request = event.request;
+    const url = new URL(request.url);
+    if (request.method !== 'GET' || request.mode !== 'navigate' || !url.pathname.startsWith('/upload/')) {
+        return;
+    }
+
+    event.respondWith(
+        fetch(request)
+            .then(response => {
+                if (response.ok) {
+                    const copy = response.clone();
+                    caches.open(PAGE_CACHE).then(cache => cache.put(request, copy));
+                }
+                return response;
+            })
+            .catch(() => caches.match(request).then(cached => cached || Response.error()))
+    );
+});
+
+self.addEventListener('message', event => {
+    const message = event.data || {};
+    if (message.type === 'queue') {
+        event.waitUntil(enqueue(message).then(scheduleSync));
+    } else if (message.type === 'flush') {
+        event.waitUntil(processQueue().catch(() => {}));
+    }
+});
+
+self.addEventListener('sync', event => {
+    if (event.tag === SYNC_TAG) {
+        event.waitUntil(processQueue());
+    }
+});
+
+function openDb() {
+    return new Promise((resolve, reject) => {
+        const request = indexedDB.open(DB_NAME, 1);
+        request.onupgradeneeded = () => request.result.createObjectStore(QUEUE_STORE, { keyPath: 'id', autoIncrement: true });
+        request.onsuccess = () => resolve(request.result);
+        request.onerror = () => reject(request.error);
+    });
+}
+
+function withStore(mode, action) {
+    return openDb().then(db => new Promise((resolve, reject) => {
+        const transaction = db.transaction(QUEUE_STORE, mode);
+        const request = action(transaction.objectStore(QUEUE_STORE));
+        transaction.oncomplete = () => resolve(request && request.result);
+        transaction.onerror = () => reject(transaction.error);
+    }));
+}
+
+function enqueue(message) {
+    return withStore('readwrite', store => store.add({
+        token: message.token,
+        file: message.file,
+        name: message.file.name,
+        type: message.file.type,
+        size: message.file.size,
+        sessionId: null
+    }));
+}
+
+function scheduleSync() {
+    // Without Background Sync the page asks us to flush when it comes back online
+    if (self.registration.sync) {
+        return self.registration.sync.register(SYNC_TAG).catch(() => processQueue());
+    }
+    return processQueue().catch(() => {});
+}
+
+function notify(message) {
+    return self.clients.matchAll({ type: 'window' })
+        .then(clients => clients.forEach(client => client.postMessage(message)));
+}
+
+let running = null;
+
+// Send every queued file; rejects while offline so Background Sync retries later
+function processQueue() {
+    if (!running) {
+        running = withStore('readonly', store => store.getAll())
+            .then(entries => entries.reduce((previous, entry) => previous.then(() => sendEntry(entry)), Promise.resolve()))
+            .finally(() => { running = null; });
+    }
+    return running;
+}
+
+function sessionsUrl(entry) {
+    return '/upload/' + encodeURIComponent(entry.token) + '/sessions';
+}
+
+function sendEntry(entry) {
+    return ensureSession(entry)
+        .then(status => sendChunks(entry, status))
+        .then(() => withStore('readwrite', store => store.delete(entry.id)))
+        .then(() => notify({ type: 'uploaded', token: entry.token, name: entry.name, size: entry.size }))
+        .catch(error => {
+            if (error.permanent) {
+                return withStore('readwrite', store => store.delete(entry.id))
+                    .then(() => notify({ type: 'failed', token: entry.token, name: entry.name, error: error.message }));
+            }
+            throw error;
+        });
+}
+
+function readStatus(response) {
+    return response.json().catch(() => ({})).then(body => {
+        if (response.ok || response.status === 409) {
+            return body;
+        }
+        const error = new Error(body.error || response.statusText);
+        // Client errors won't go away by retrying
+        error.permanent = response.status >= 400 && response.status < 500;
+        throw error;
+    });
+}
+
+// Resume the entry's session, or start one if it has none or it expired
+function ensureSession(entry) {
+    const resume = entry.sessionId
+        ? fetch(sessionsUrl(entry) + '/' + entry.sessionId).then(response => response.status === 404 ? null : readStatus(response))
+        : Promise.resolve(null);
+
+    return resume.then(status => {
+        if (status) {
+            return status;
+        }
+        return fetch(sessionsUrl(entry), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ filename: entry.name, size: entry.size, content_type: entry.type })
+        })
+            .then(readStatus)
+            .then(created => {
+                entry.sessionId = created.id;
+                return withStore('readwrite', store => store.put(entry)).then(() => created);
+            });
+    });
+}
+
+function sendChunks(entry, status) {
+    if (status.upload_id) {
+        return Promise.resolve(status);
+    }
+    if (typeof status.offset !== 'number') {
+        // Another request is still writing to the session; try again later
+        return Promise.reject(new Error(status.error || 'session busy'));
+    }
+    const offset = status.offset;
+    const end = Math.min(offset + CHUNK_SIZE, entry.size);
+
+    return fetch(sessionsUrl(entry) + '/' + entry.sessionId, {
+        method: 'PATCH',
+        headers: { 'Upload-Offset': String(offset), 'Content-Type': 'application/octet-stream' },
+        body: entry.file.slice(offset, end)
+    })
+        .then(readStatus)
+        .then(next => sendChunks(entry, next));
+}